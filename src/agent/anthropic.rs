@@ -66,27 +66,47 @@ impl AnthropicProvider {
                 let content: Vec<Value> = msg
                     .content
                     .iter()
-                    .map(|block| match block {
-                        ContentBlock::Text { text } => json!({
+                    .filter_map(|block| match block {
+                        ContentBlock::Text { text } => Some(json!({
                             "type": "text",
                             "text": text,
-                        }),
-                        ContentBlock::ToolUse { id, name, input } => json!({
+                        })),
+                        ContentBlock::ToolUse { id, name, input } => Some(json!({
                             "type": "tool_use",
                             "id": id,
                             "name": name,
                             "input": input,
-                        }),
+                        })),
                         ContentBlock::ToolResult {
                             tool_use_id,
                             content,
                             is_error,
-                        } => json!({
+                        } => Some(json!({
                             "type": "tool_result",
                             "tool_use_id": tool_use_id,
                             "content": content,
                             "is_error": is_error,
-                        }),
+                        })),
+                        // Thinking blocks are only accepted back with their
+                        // signature, and only while thinking is enabled.
+                        ContentBlock::Thinking {
+                            thinking,
+                            signature: Some(signature),
+                        } if request.thinking.is_some() => Some(json!({
+                            "type": "thinking",
+                            "thinking": thinking,
+                            "signature": signature,
+                        })),
+                        ContentBlock::Thinking { .. } => None,
+                        ContentBlock::Image { media_type, data } => Some(json!({
+                            "type": "image",
+                            "source": {
+                                "type": "base64",
+                                "media_type": media_type,
+                                "data": data,
+                            },
+                        })),
+                        ContentBlock::Document { .. } => Some(document_block(block)),
                     })
                     .collect();
                 json!({
@@ -107,7 +127,15 @@ impl AnthropicProvider {
             body["system"] = json!(system);
         }
 
-        if let Some(temp) = request.temperature {
+        if let Some(level) = request.thinking {
+            let (budget, max_tokens) = level.budget_with_max_tokens(request.max_tokens);
+            body["thinking"] = json!({
+                "type": "enabled",
+                "budget_tokens": budget,
+            });
+            body["max_tokens"] = json!(max_tokens);
+        } else if let Some(temp) = request.temperature {
+            // Extended thinking rejects a custom temperature
             body["temperature"] = json!(temp);
         }
 
@@ -130,6 +158,34 @@ impl AnthropicProvider {
     }
 }
 
+/// Serialize a `Document` block. PDFs go as base64 documents, text files
+/// as plain-text documents; anything else becomes a text placeholder.
+fn document_block(block: &ContentBlock) -> Value {
+    let ContentBlock::Document {
+        media_type,
+        data,
+        name,
+    } = block
+    else {
+        return Value::Null;
+    };
+    let source = if media_type == "application/pdf" {
+        json!({"type": "base64", "media_type": media_type, "data": data})
+    } else if let Some(text) = decode_text_document(media_type, data) {
+        json!({"type": "text", "media_type": "text/plain", "data": text})
+    } else {
+        return json!({
+            "type": "text",
+            "text": block.attachment_placeholder().unwrap_or_default(),
+        });
+    };
+    let mut doc = json!({"type": "document", "source": source});
+    if let Some(name) = name {
+        doc["title"] = json!(name);
+    }
+    doc
+}
+
 #[async_trait]
 impl LlmProvider for AnthropicProvider {
     async fn complete(
@@ -188,6 +244,10 @@ impl LlmProvider for AnthropicProvider {
 
         Ok(rx)
     }
+
+    fn provider_name(&self, _model: &str) -> &str {
+        "anthropic"
+    }
 }
 
 /// Maximum SSE line buffer size (1 MB). If a single SSE line exceeds this,
@@ -364,6 +424,22 @@ fn handle_content_block_delta(
                 Some(StreamEvent::TextDelta { text })
             }
         }
+        Some("thinking_delta") => {
+            let text = delta["thinking"].as_str().unwrap_or("").to_string();
+            if text.is_empty() {
+                None
+            } else {
+                Some(StreamEvent::ThinkingDelta { text })
+            }
+        }
+        Some("signature_delta") => {
+            delta["signature"]
+                .as_str()
+                .filter(|s| !s.is_empty())
+                .map(|signature| StreamEvent::ThinkingSignature {
+                    signature: signature.to_string(),
+                })
+        }
         Some("input_json_delta") => {
             if let Some(entry) = tool_calls.get_mut(&index) {
                 if let Some(partial) = delta["partial_json"].as_str() {
@@ -415,6 +491,7 @@ mod tests {
             tools: vec![],
             max_tokens: 1024,
             temperature: Some(0.7),
+            thinking: None,
             extra: None,
         };
         let body = provider.build_body(&request);
@@ -445,6 +522,7 @@ mod tests {
             }],
             max_tokens: 4096,
             temperature: None,
+            thinking: None,
             extra: None,
         };
         let body = provider.build_body(&request);
@@ -454,6 +532,59 @@ mod tests {
         assert!(body.get("system").is_none());
     }
 
+    #[test]
+    fn test_build_body_with_image_and_documents() {
+        let provider = AnthropicProvider::new("test-key".to_string()).unwrap();
+        let request = CompletionRequest {
+            model: "claude-sonnet-4-20250514".to_string(),
+            messages: vec![LlmMessage {
+                role: LlmRole::User,
+                content: vec![
+                    ContentBlock::Image {
+                        media_type: "image/png".to_string(),
+                        data: "iVBO".to_string(),
+                    },
+                    ContentBlock::Document {
+                        media_type: "application/pdf".to_string(),
+                        data: "JVBE".to_string(),
+                        name: Some("report.pdf".to_string()),
+                    },
+                    ContentBlock::Document {
+                        media_type: "text/markdown".to_string(),
+                        data: "IyBoaQ==".to_string(),
+                        name: None,
+                    },
+                    ContentBlock::Document {
+                        media_type: "application/zip".to_string(),
+                        data: "UEsD".to_string(),
+                        name: None,
+                    },
+                ],
+            }],
+            system: None,
+            tools: vec![],
+            max_tokens: 1024,
+            temperature: None,
+            thinking: None,
+            extra: None,
+        };
+        let body = provider.build_body(&request);
+        let content = &body["messages"][0]["content"];
+        assert_eq!(content[0]["type"], "image");
+        assert_eq!(content[0]["source"]["media_type"], "image/png");
+        assert_eq!(content[0]["source"]["data"], "iVBO");
+        assert_eq!(content[1]["type"], "document");
+        assert_eq!(content[1]["source"]["type"], "base64");
+        assert_eq!(content[1]["title"], "report.pdf");
+        assert_eq!(content[2]["source"]["type"], "text");
+        assert_eq!(content[2]["source"]["data"], "# hi");
+        assert_eq!(content[3]["type"], "text");
+        assert!(content[3]["text"]
+            .as_str()
+            .unwrap()
+            .contains("application/zip"));
+    }
+
     #[test]
     fn test_parse_text_delta() {
         let mut tool_calls = std::collections::HashMap::new();
@@ -470,6 +601,81 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_build_body_with_thinking() {
+        let provider = AnthropicProvider::new("test-key".to_string()).unwrap();
+        let request = CompletionRequest {
+            model: "claude-sonnet-4-20250514".to_string(),
+            messages: vec![LlmMessage {
+                role: LlmRole::Assistant,
+                content: vec![
+                    ContentBlock::Thinking {
+                        thinking: "signed".to_string(),
+                        signature: Some("sig".to_string()),
+                    },
+                    ContentBlock::Thinking {
+                        thinking: "unsigned".to_string(),
+                        signature: None,
+                    },
+                    ContentBlock::Text {
+                        text: "Hi".to_string(),
+                    },
+                ],
+            }],
+            system: None,
+            tools: vec![],
+            max_tokens: 8192,
+            temperature: Some(0.7),
+            thinking: Some(ThinkingLevel::Medium),
+            extra: None,
+        };
+        let body = provider.build_body(&request);
+        assert_eq!(body["thinking"]["type"], "enabled");
+        assert_eq!(body["thinking"]["budget_tokens"], 10_000);
+        // max_tokens must exceed the budget
+        assert_eq!(body["max_tokens"], 18_192);
+        assert!(body.get("temperature").is_none());
+        let content = body["messages"][0]["content"].as_array().unwrap();
+        assert_eq!(content.len(), 2);
+        assert_eq!(content[0]["type"], "thinking");
+        assert_eq!(content[0]["signature"], "sig");
+
+        // Without thinking, stored thinking blocks are not sent back
+        let request = CompletionRequest {
+            thinking: None,
+            ..request
+        };
+        let body = provider.build_body(&request);
+        assert!(body.get("thinking").is_none());
+        assert_eq!(body["messages"][0]["content"].as_array().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_parse_thinking_and_signature_deltas() {
+        let mut tool_calls = std::collections::HashMap::new();
+        let mut usage = TokenUsage::default();
+        let event = parse_sse_event(
+            "content_block_delta",
+            r#"{"type":"content_block_delta","index":0,"delta":{"type":"thinking_delta","thinking":"Let me see"}}"#,
+            &mut tool_calls,
+            &mut usage,
+        );
+        match event {
+            Some(StreamEvent::ThinkingDelta { text }) => assert_eq!(text, "Let me see"),
+            other => panic!("expected ThinkingDelta, got {other:?}"),
+        }
+        let event = parse_sse_event(
+            "content_block_delta",
+            r#"{"type":"content_block_delta","index":0,"delta":{"type":"signature_delta","signature":"EqQB"}}"#,
+            &mut tool_calls,
+            &mut usage,
+        );
+        match event {
+            Some(StreamEvent::ThinkingSignature { signature }) => assert_eq!(signature, "EqQB"),
+            other => panic!("expected ThinkingSignature, got {other:?}"),
+        }
+    }
+
     #[test]
     fn test_parse_tool_use_sequence() {
         let mut tool_calls = std::collections::HashMap::new();
@@ -94,6 +94,10 @@ impl BedrockProvider {
     /// Build the JSON request body for the Bedrock Converse API.
     fn build_body(&self, request: &CompletionRequest) -> Value {
         let mut body = json!({});
+        let thinking = request
+            .thinking
+            .filter(|_| supports_extended_thinking(&request.model));
+        let thinking_enabled = thinking.is_some();
 
         // System prompt
         if let Some(ref system) = request.system {
@@ -112,26 +116,41 @@ impl BedrockProvider {
                 let content: Vec<Value> = msg
                     .content
                     .iter()
-                    .map(|block| match block {
-                        ContentBlock::Text { text } => json!({"text": text}),
-                        ContentBlock::ToolUse { id, name, input } => json!({
+                    .filter_map(|block| match block {
+                        ContentBlock::Text { text } => Some(json!({"text": text})),
+                        ContentBlock::ToolUse { id, name, input } => Some(json!({
                             "toolUse": {
                                 "toolUseId": id,
                                 "name": name,
                                 "input": input,
                             }
-                        }),
+                        })),
                         ContentBlock::ToolResult {
                             tool_use_id,
                             content,
                             is_error,
-                        } => json!({
+                        } => Some(json!({
                             "toolResult": {
                                 "toolUseId": tool_use_id,
                                 "content": [{"text": content}],
                                 "status": if *is_error { "error" } else { "success" },
                             }
-                        }),
+                        })),
+                        ContentBlock::Thinking {
+                            thinking,
+                            signature: Some(signature),
+                        } if thinking_enabled => Some(json!({
+                            "reasoningContent": {
+                                "reasoningText": {
+                                    "text": thinking,
+                                    "signature": signature,
+                                }
+                            }
+                        })),
+                        ContentBlock::Thinking { .. } => None,
+                        ContentBlock::Image { .. } | ContentBlock::Document { .. } => {
+                            Some(attachment_block(block))
+                        }
                     })
                     .collect();
                 json!({
@@ -146,7 +165,18 @@ impl BedrockProvider {
         let mut inference_config = json!({
             "maxTokens": request.max_tokens,
         });
-        if let Some(temp) = request.temperature {
+        if let Some(level) = thinking {
+            // Claude on Bedrock takes the Anthropic thinking block as an
+            // additional model request field.
+            let (budget, max_tokens) = level.budget_with_max_tokens(request.max_tokens);
+            inference_config["maxTokens"] = json!(max_tokens);
+            body["additionalModelRequestFields"] = json!({
+                "thinking": {
+                    "type": "enabled",
+                    "budget_tokens": budget,
+                }
+            });
+        } else if let Some(temp) = request.temperature {
             inference_config["temperature"] = json!(temp);
         }
         body["inferenceConfig"] = inference_config;
@@ -378,6 +408,129 @@ impl LlmProvider for BedrockProvider {
 
         Ok(rx)
     }
+
+    /// Embed via `InvokeModel`. Titan models take one input per call; Cohere
+    /// models take the whole batch.
+    async fn embed(&self, request: EmbeddingRequest) -> Result<EmbeddingResponse, AgentError> {
+        if is_cohere_embed_model(&request.model) {
+            let body = json!({
+                "texts": request.input,
+                "input_type": "search_document",
+            });
+            let response = self.invoke_model(&request.model, &body).await?;
+            let embeddings = response
+                .get("embeddings")
+                .and_then(|v| v.as_array())
+                .ok_or_else(|| {
+                    AgentError::Provider(
+                        "Bedrock embeddings response has no embeddings".to_string(),
+                    )
+                })?
+                .iter()
+                .map(|e| parse_embedding_vector(Some(e)))
+                .collect::<Result<Vec<_>, _>>()?;
+            let input_tokens = request
+                .input
+                .iter()
+                .map(|text| u64::from(crate::agent::context::estimate_tokens(text)))
+                .sum();
+            return Ok(EmbeddingResponse {
+                embeddings,
+                input_tokens,
+            });
+        }
+
+        let mut result = EmbeddingResponse::default();
+        for text in &request.input {
+            let body = build_titan_embedding_body(text, request.dimensions);
+            let response = self.invoke_model(&request.model, &body).await?;
+            result
+                .embeddings
+                .push(parse_embedding_vector(response.get("embedding"))?);
+            result.input_tokens += response
+                .get("inputTextTokenCount")
+                .and_then(|v| v.as_u64())
+                .unwrap_or(0);
+        }
+        Ok(result)
+    }
+
+    fn provider_name(&self, _model: &str) -> &str {
+        "bedrock"
+    }
+}
+
+impl BedrockProvider {
+    /// Send a signed, non-streaming `InvokeModel` request and return the
+    /// JSON response body.
+    async fn invoke_model(&self, model_id: &str, body: &Value) -> Result<Value, AgentError> {
+        let body_bytes = serde_json::to_vec(body)
+            .map_err(|e| AgentError::Provider(format!("failed to serialize request body: {e}")))?;
+        let uri_path = format!("/model/{}/invoke", percent_encode_path_segment(model_id));
+        let url = format!("{}{}", self.base_url, uri_path);
+
+        let datetime = chrono::Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+        let sig_headers = self.sign_request("POST", &uri_path, &body_bytes, &datetime);
+
+        let mut http_request = self
+            .client
+            .post(&url)
+            .header("content-type", "application/json")
+            .header("accept", "application/json");
+        for (name, value) in &sig_headers {
+            http_request = http_request.header(name.as_str(), value.as_str());
+        }
+
+        let response = http_request
+            .body(body_bytes)
+            .send()
+            .await
+            .map_err(|e| AgentError::Provider(format!("HTTP request failed: {e}")))?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response
+                .text()
+                .await
+                .unwrap_or_else(|_| "<unreadable>".to_string());
+            return Err(AgentError::Provider(format!(
+                "Bedrock API returned {status}: {body}"
+            )));
+        }
+
+        response
+            .json()
+            .await
+            .map_err(|e| AgentError::Provider(format!("failed to parse Bedrock response: {e}")))
+    }
+}
+
+/// Returns `true` for Cohere embedding models, which use a batched body.
+fn is_cohere_embed_model(model: &str) -> bool {
+    model.to_lowercase().starts_with("cohere.embed-")
+}
+
+/// Build a Titan embeddings body. Only Titan v2 accepts `dimensions`.
+fn build_titan_embedding_body(text: &str, dimensions: Option<u32>) -> Value {
+    let mut body = json!({ "inputText": text });
+    if let Some(dimensions) = dimensions {
+        body["dimensions"] = json!(dimensions);
+    }
+    body
+}
+
+fn parse_embedding_vector(value: Option<&Value>) -> Result<Vec<f32>, AgentError> {
+    value
+        .and_then(|v| v.as_array())
+        .map(|values| {
+            values
+                .iter()
+                .filter_map(|v| v.as_f64().map(|f| f as f32))
+                .collect()
+        })
+        .ok_or_else(|| {
+            AgentError::Provider("Bedrock embeddings response has no vector".to_string())
+        })
 }
 
 struct BedrockEventFrame {
@@ -594,6 +747,24 @@ async fn handle_bedrock_frame(
                     .map_err(|_| "stream receiver dropped".to_string())?;
                 }
             }
+            if let Some(reasoning) = payload.get("delta").and_then(|d| d.get("reasoningContent")) {
+                if let Some(text) = reasoning.get("text").and_then(|t| t.as_str()) {
+                    if !text.is_empty() {
+                        tx.send(StreamEvent::ThinkingDelta {
+                            text: text.to_string(),
+                        })
+                        .await
+                        .map_err(|_| "stream receiver dropped".to_string())?;
+                    }
+                }
+                if let Some(signature) = reasoning.get("signature").and_then(|s| s.as_str()) {
+                    tx.send(StreamEvent::ThinkingSignature {
+                        signature: signature.to_string(),
+                    })
+                    .await
+                    .map_err(|_| "stream receiver dropped".to_string())?;
+                }
+            }
             if let Some(tool_use) = payload.get("delta").and_then(|d| d.get("toolUse")) {
                 handle_tool_use(tool_use, content_block_index, state).await?;
             }
@@ -773,6 +944,78 @@ fn parse_stop_reason(reason: &str) -> StopReason {
     }
 }
 
+/// Whether a Bedrock model accepts Anthropic-style extended thinking.
+fn supports_extended_thinking(model: &str) -> bool {
+    model.to_ascii_lowercase().contains("claude")
+}
+
+/// Serialize an `Image` or `Document` block as a Converse content block.
+///
+/// Converse only takes a fixed set of formats; anything else becomes a text
+/// placeholder.
+fn attachment_block(block: &ContentBlock) -> Value {
+    let placeholder = || json!({"text": block.attachment_placeholder().unwrap_or_default()});
+    match block {
+        ContentBlock::Image { media_type, data } => match media_type.strip_prefix("image/") {
+            Some(format @ ("png" | "jpeg" | "gif" | "webp")) => json!({
+                "image": {"format": format, "source": {"bytes": data}}
+            }),
+            _ => placeholder(),
+        },
+        ContentBlock::Document {
+            media_type,
+            data,
+            name,
+        } => {
+            let format = match media_type.as_str() {
+                "application/pdf" => "pdf",
+                "text/csv" => "csv",
+                "text/html" => "html",
+                "text/plain" => "txt",
+                "text/markdown" => "md",
+                "application/msword" => "doc",
+                "application/vnd.openxmlformats-officedocument.wordprocessingml.document" => "docx",
+                "application/vnd.ms-excel" => "xls",
+                "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet" => "xlsx",
+                _ => return placeholder(),
+            };
+            json!({
+                "document": {
+                    "format": format,
+                    "name": bedrock_document_name(name.as_deref()),
+                    "source": {"bytes": data},
+                }
+            })
+        }
+        _ => placeholder(),
+    }
+}
+
+/// Converse document names may only contain alphanumerics, single spaces,
+/// hyphens, parentheses and square brackets.
+fn bedrock_document_name(name: Option<&str>) -> String {
+    let stem = name
+        .map(|n| n.rsplit_once('.').map_or(n, |(stem, _)| stem))
+        .unwrap_or("document");
+    let mut sanitized = String::with_capacity(stem.len());
+    for c in stem.chars() {
+        let c = if c.is_ascii_alphanumeric() || matches!(c, '-' | '(' | ')' | '[' | ']') {
+            c
+        } else {
+            ' '
+        };
+        if !(c == ' ' && (sanitized.is_empty() || sanitized.ends_with(' '))) {
+            sanitized.push(c);
+        }
+    }
+    let sanitized = sanitized.trim_end();
+    if sanitized.is_empty() {
+        "document".to_string()
+    } else {
+        sanitized.to_string()
+    }
+}
+
 /// Determine whether a model identifier should route to the Bedrock provider.
 ///
 /// Matches models with the `bedrock:` or `bedrock/` prefix, as well as native
@@ -950,6 +1193,97 @@ mod tests {
 
     // ==================== Request body building tests ====================
 
+    #[test]
+    fn test_build_body_with_thinking() {
+        let provider = BedrockProvider::new(
+            "us-east-1".to_string(),
+            "AKID".to_string(),
+            "secret".to_string(),
+        )
+        .unwrap();
+        let mut request = CompletionRequest {
+            model: "anthropic.claude-3-7-sonnet-20250219-v1:0".to_string(),
+            messages: vec![LlmMessage {
+                role: LlmRole::Assistant,
+                content: vec![ContentBlock::Thinking {
+                    thinking: "hmm".to_string(),
+                    signature: Some("sig".to_string()),
+                }],
+            }],
+            system: None,
+            tools: vec![],
+            max_tokens: 1024,
+            temperature: Some(0.7),
+            thinking: Some(ThinkingLevel::Low),
+            extra: None,
+        };
+        let body = provider.build_body(&request);
+        assert_eq!(
+            body["additionalModelRequestFields"]["thinking"]["budget_tokens"],
+            4096
+        );
+        assert_eq!(body["inferenceConfig"]["maxTokens"], 5120);
+        assert!(body["inferenceConfig"].get("temperature").is_none());
+        assert_eq!(
+            body["messages"][0]["content"][0]["reasoningContent"]["reasoningText"]["signature"],
+            "sig"
+        );
+
+        // Non-Claude models ignore the thinking level
+        request.model = "amazon.nova-pro-v1:0".to_string();
+        let body = provider.build_body(&request);
+        assert!(body.get("additionalModelRequestFields").is_none());
+        assert_eq!(body["inferenceConfig"]["temperature"], 0.7);
+    }
+
+    #[test]
+    fn test_build_body_with_image_and_document() {
+        let provider = BedrockProvider::new(
+            "us-east-1".to_string(),
+            "AKID".to_string(),
+            "secret".to_string(),
+        )
+        .unwrap();
+        let request = CompletionRequest {
+            model: "anthropic.claude-3-5-sonnet-20241022-v2:0".to_string(),
+            messages: vec![LlmMessage {
+                role: LlmRole::User,
+                content: vec![
+                    ContentBlock::Image {
+                        media_type: "image/jpeg".to_string(),
+                        data: "/9j/".to_string(),
+                    },
+                    ContentBlock::Document {
+                        media_type: "application/pdf".to_string(),
+                        data: "JVBE".to_string(),
+                        name: Some("Q3 report_final.v2.pdf".to_string()),
+                    },
+                    ContentBlock::Document {
+                        media_type: "application/zip".to_string(),
+                        data: "UEsD".to_string(),
+                        name: None,
+                    },
+                ],
+            }],
+            system: None,
+            tools: vec![],
+            max_tokens: 1024,
+            temperature: None,
+            thinking: None,
+            extra: None,
+        };
+        let body = provider.build_body(&request);
+        let content = &body["messages"][0]["content"];
+        assert_eq!(content[0]["image"]["format"], "jpeg");
+        assert_eq!(content[0]["image"]["source"]["bytes"], "/9j/");
+        assert_eq!(content[1]["document"]["format"], "pdf");
+        assert_eq!(content[1]["document"]["name"], "Q3 report final v2");
+        assert!(content[2]["text"]
+            .as_str()
+            .unwrap()
+            .contains("application/zip"));
+    }
+
     #[test]
     fn test_build_body_basic() {
         let provider = BedrockProvider::new(
@@ -970,6 +1304,7 @@ mod tests {
             tools: vec![],
             max_tokens: 1024,
             temperature: Some(0.7),
+            thinking: None,
             extra: None,
         };
         let body = provider.build_body(&request);
@@ -1004,6 +1339,7 @@ mod tests {
             tools: vec![],
             max_tokens: 1024,
             temperature: None,
+            thinking: None,
             extra: None,
         };
         let body = provider.build_body(&request);
@@ -1040,6 +1376,7 @@ mod tests {
             }],
             max_tokens: 4096,
             temperature: None,
+            thinking: None,
             extra: None,
         };
         let body = provider.build_body(&request);
@@ -1076,6 +1413,7 @@ mod tests {
             tools: vec![],
             max_tokens: 1024,
             temperature: None,
+            thinking: None,
             extra: None,
         };
         let body = provider.build_body(&request);
@@ -1110,6 +1448,7 @@ mod tests {
             tools: vec![],
             max_tokens: 1024,
             temperature: None,
+            thinking: None,
             extra: None,
         };
         let body = provider.build_body(&request);
@@ -1161,6 +1500,7 @@ mod tests {
             tools: vec![],
             max_tokens: 1024,
             temperature: None,
+            thinking: None,
             extra: None,
         };
         let body = provider.build_body(&request);
@@ -1362,6 +1702,29 @@ mod tests {
         events
     }
 
+    #[tokio::test]
+    async fn test_parse_stream_reasoning_content() {
+        let frames = vec![
+            build_event_stream_frame(
+                "contentBlockDelta",
+                json!({"delta": {"reasoningContent": {"text": "Thinking..."}}}),
+            ),
+            build_event_stream_frame(
+                "contentBlockDelta",
+                json!({"delta": {"reasoningContent": {"signature": "sig"}}}),
+            ),
+            build_event_stream_frame("contentBlockDelta", json!({"delta": {"text": "Done"}})),
+            build_event_stream_frame("messageStop", json!({"stopReason": "end_turn"})),
+        ];
+
+        let events = collect_stream_events(frames).await;
+        assert!(matches!(&events[0], StreamEvent::ThinkingDelta { text } if text == "Thinking..."));
+        assert!(
+            matches!(&events[1], StreamEvent::ThinkingSignature { signature } if signature == "sig")
+        );
+        assert!(matches!(&events[2], StreamEvent::TextDelta { text } if text == "Done"));
+    }
+
     #[tokio::test]
     async fn test_parse_stream_text_response() {
         let frames = vec![
@@ -1564,4 +1927,30 @@ mod tests {
         assert_eq!(parse_stop_reason("max_tokens"), StopReason::MaxTokens);
         assert_eq!(parse_stop_reason("unknown"), StopReason::EndTurn);
     }
+
+    // ==================== embeddings tests ====================
+
+    #[test]
+    fn test_is_cohere_embed_model() {
+        assert!(is_cohere_embed_model("cohere.embed-english-v3"));
+        assert!(!is_cohere_embed_model("amazon.titan-embed-text-v2:0"));
+    }
+
+    #[test]
+    fn test_build_titan_embedding_body() {
+        let body = build_titan_embedding_body("hello", Some(512));
+        assert_eq!(body, json!({"inputText": "hello", "dimensions": 512}));
+        let body = build_titan_embedding_body("hello", None);
+        assert_eq!(body, json!({"inputText": "hello"}));
+    }
+
+    #[test]
+    fn test_parse_embedding_vector() {
+        let value = json!([0.5, -0.5]);
+        assert_eq!(
+            parse_embedding_vector(Some(&value)).unwrap(),
+            vec![0.5, -0.5]
+        );
+        assert!(parse_embedding_vector(None).is_err());
+    }
 }
@@ -4,33 +4,37 @@
 //! These tools are registered in the `ToolsRegistry` and dispatched via the
 //! standard tool dispatch path.
 
-use std::collections::HashMap;
 use std::env;
-use std::fs;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
 use serde_json::{json, Value};
 
-use crate::plugins::tools::{BuiltinTool, ToolInvokeResult};
+use crate::agent::channel_tools::enqueue_outbound;
+use crate::memory::{MemoryEntry, MemoryFilter, MemoryStore, MemoryWrite};
+use crate::messages::outbound::{MessageContent, MessageMetadata, OutboundMessage};
+use crate::plugins::tools::{tool_handler, BuiltinTool, ToolInvokeContext, ToolInvokeResult};
 
 /// Return all built-in tool definitions.
 ///
 /// Called by `ToolsRegistry::new()` to register the core tool set.
 pub fn builtin_tools() -> Vec<BuiltinTool> {
-    vec![
+    let mut tools = vec![
         current_time_tool(),
         web_fetch_tool(),
         media_analyze_tool(),
         memory_read_tool(),
         memory_write_tool(),
         memory_list_tool(),
+        memory_search_tool(),
         message_send_tool(),
         session_list_tool(),
         session_read_tool(),
         config_read_tool(),
         math_eval_tool(),
-    ]
+    ];
+    tools.extend(crate::agent::schedule_tools::schedule_tools());
+    tools
 }
 
 /// Return channel-specific tools for the given channel.
@@ -53,7 +57,7 @@ fn current_time_tool() -> BuiltinTool {
             "properties": {},
             "additionalProperties": false
         }),
-        handler: Box::new(|_args, _ctx| {
+        handler: tool_handler(|_args, _ctx| async move {
             let now = chrono::Utc::now();
             ToolInvokeResult::success(json!({
                 "iso": now.format("%Y-%m-%dT%H:%M:%SZ").to_string(),
@@ -88,7 +92,7 @@ fn web_fetch_tool() -> BuiltinTool {
             "required": ["url"],
             "additionalProperties": false
         }),
-        handler: Box::new(|args, _ctx| handle_web_fetch(args)),
+        handler: tool_handler(|args, _ctx| handle_web_fetch(args)),
     }
 }
 
@@ -141,7 +145,7 @@ fn media_analyze_tool() -> BuiltinTool {
             },
             "additionalProperties": false
         }),
-        handler: Box::new(|args, _ctx| handle_media_analyze(args)),
+        handler: tool_handler(|args, _ctx| handle_media_analyze(args)),
     }
 }
 
@@ -151,7 +155,7 @@ const MEDIA_ANALYZE_DEFAULT_MAX_BYTES: u64 = 50 * 1024 * 1024;
 /// Maximum allowed value for max_bytes (100 MB).
 const MEDIA_ANALYZE_MAX_ALLOWED_BYTES: u64 = 100 * 1024 * 1024;
 
-fn handle_media_analyze(args: Value) -> ToolInvokeResult {
+async fn handle_media_analyze(args: Value) -> ToolInvokeResult {
     let url = args
         .get("url")
         .and_then(|v| v.as_str())
@@ -198,137 +202,132 @@ fn handle_media_analyze(args: Value) -> ToolInvokeResult {
         }
     }
 
-    let result = tokio::task::block_in_place(|| {
-        let handle = tokio::runtime::Handle::current();
-        handle.block_on(async {
-            use crate::media::analysis::{
-                analyze, AnthropicMediaAnalyzer, MediaType, OpenAiMediaAnalyzer,
-            };
-            use crate::media::fetch::{FetchConfig, MediaFetcher};
-            use crate::media::{MediaStore, StoreConfig};
-
-            let cfg =
-                crate::config::load_config_shared().unwrap_or_else(|_| Arc::new(json!({})));
-
-            let (media_path, mime_type) = if let Some(url) = url {
-                let config = FetchConfig::default().with_max_size(max_bytes);
-                let fetcher = MediaFetcher::with_config(config);
-                let fetch = fetcher.fetch(&url).await.map_err(|e| e.to_string())?;
+    let result: Result<Value, String> = async {
+        use crate::media::analysis::{
+            analyze, AnthropicMediaAnalyzer, MediaType, OpenAiMediaAnalyzer,
+        };
+        use crate::media::fetch::{FetchConfig, MediaFetcher};
+        use crate::media::{MediaStore, StoreConfig};
 
-                let mime = mime_override
-                    .or(fetch.content_type.as_deref().map(normalize_mime_type))
-                    .ok_or_else(|| "missing mime_type and no Content-Type returned".to_string())?;
+        let cfg = crate::config::load_config_shared().unwrap_or_else(|_| Arc::new(json!({})));
 
-                let store = MediaStore::new(StoreConfig::default())
-                    .await
-                    .map_err(|e| e.to_string())?;
-                let metadata = store
-                    .store(fetch.bytes, Some(mime.clone()))
-                    .await
-                    .map_err(|e| e.to_string())?;
-                (metadata.path, mime)
-            } else if let Some(path) = path {
-                let media_path = PathBuf::from(path);
-                if !media_path.exists() {
-                    return Err("file path does not exist".to_string());
-                }
-                let mime = mime_override
-                    .or_else(|| guess_mime_from_path(&media_path))
-                    .ok_or_else(|| "missing mime_type for local file".to_string())?;
-                (media_path, mime)
-            } else {
-                return Err("missing url or path".to_string());
-            };
+        let (media_path, mime_type) = if let Some(url) = url {
+            let config = FetchConfig::default().with_max_size(max_bytes);
+            let fetcher = MediaFetcher::with_config(config);
+            let fetch = fetcher.fetch(&url).await.map_err(|e| e.to_string())?;
+
+            let mime = mime_override
+                .or(fetch.content_type.as_deref().map(normalize_mime_type))
+                .ok_or_else(|| "missing mime_type and no Content-Type returned".to_string())?;
+
+            let store = MediaStore::new(StoreConfig::default())
+                .await
+                .map_err(|e| e.to_string())?;
+            let metadata = store
+                .store(fetch.bytes, Some(mime.clone()))
+                .await
+                .map_err(|e| e.to_string())?;
+            (metadata.path, mime)
+        } else if let Some(path) = path {
+            let media_path = PathBuf::from(path);
+            if !media_path.exists() {
+                return Err("file path does not exist".to_string());
+            }
+            let mime = mime_override
+                .or_else(|| guess_mime_from_path(&media_path))
+                .ok_or_else(|| "missing mime_type for local file".to_string())?;
+            (media_path, mime)
+        } else {
+            return Err("missing url or path".to_string());
+        };
 
-            let media_type = MediaType::from_mime(&mime_type)
-                .ok_or_else(|| format!("unsupported MIME type: {}", mime_type))?;
-
-            let openai_key = resolve_openai_media_key(cfg.as_ref());
-            let anthropic_key = resolve_anthropic_media_key(cfg.as_ref());
-
-            let provider = match provider_override.as_deref() {
-                Some("openai") => "openai",
-                Some("anthropic") => "anthropic",
-                None => match media_type {
-                    MediaType::Audio => {
-                        if openai_key.is_some() {
-                            "openai"
-                        } else {
-                            return Err("OpenAI API key is required for audio transcription".into());
-                        }
-                    }
-                    MediaType::Image => {
-                        if openai_key.is_some() {
-                            "openai"
-                        } else if anthropic_key.is_some() {
-                            "anthropic"
-                        } else {
-                            return Err("no media analysis provider configured".into());
-                        }
+        let media_type = MediaType::from_mime(&mime_type)
+            .ok_or_else(|| format!("unsupported MIME type: {}", mime_type))?;
+
+        let openai_key = resolve_openai_media_key(cfg.as_ref());
+        let anthropic_key = resolve_anthropic_media_key(cfg.as_ref());
+
+        let provider = match provider_override.as_deref() {
+            Some("openai") => "openai",
+            Some("anthropic") => "anthropic",
+            None => match media_type {
+                MediaType::Audio => {
+                    if openai_key.is_some() {
+                        "openai"
+                    } else {
+                        return Err("OpenAI API key is required for audio transcription".into());
                     }
-                    MediaType::Video => {
-                        return Err("video analysis is not implemented".into());
+                }
+                MediaType::Image => {
+                    if openai_key.is_some() {
+                        "openai"
+                    } else if anthropic_key.is_some() {
+                        "anthropic"
+                    } else {
+                        return Err("no media analysis provider configured".into());
                     }
-                },
-                _ => unreachable!("provider validated before async block"),
-            };
+                }
+                MediaType::Video => {
+                    return Err("video analysis is not implemented".into());
+                }
+            },
+            _ => unreachable!("provider validated before async block"),
+        };
 
-            let cache_path = analysis_cache_path(&media_path);
-            let cached = cache_path.exists();
-
-            let analysis: crate::media::analysis::MediaAnalysis = match provider {
-                "openai" => {
-                    let key = openai_key.ok_or_else(|| {
-                        "OpenAI API key not configured; set OPENAI_API_KEY or openai.apiKey"
-                            .to_string()
-                    })?;
-                    let mut analyzer = OpenAiMediaAnalyzer::new(key).map_err(|e| e.to_string())?;
-                    if let Some(base_url) = resolve_openai_base_url(cfg.as_ref()) {
-                        analyzer = analyzer.with_base_url(base_url);
-                    }
-                    if let Some(model) = model_override.clone() {
-                        analyzer = analyzer.with_vision_model(model);
-                    }
-                    if let Some(max_tokens) = max_tokens {
-                        analyzer = analyzer.with_max_tokens(max_tokens);
-                    }
-                    analyze(&media_path, &mime_type, &analyzer, prompt.as_deref())
-                        .await
-                        .map_err(|e| e.to_string())?
+        let cache_path = analysis_cache_path(&media_path);
+        let cached = cache_path.exists();
+
+        let analysis: crate::media::analysis::MediaAnalysis = match provider {
+            "openai" => {
+                let key = openai_key.ok_or_else(|| {
+                    "OpenAI API key not configured; set OPENAI_API_KEY or openai.apiKey".to_string()
+                })?;
+                let mut analyzer = OpenAiMediaAnalyzer::new(key).map_err(|e| e.to_string())?;
+                if let Some(base_url) = resolve_openai_base_url(cfg.as_ref()) {
+                    analyzer = analyzer.with_base_url(base_url);
                 }
-                "anthropic" => {
-                    if media_type == MediaType::Audio {
-                        return Err("Anthropic does not support audio transcription".into());
-                    }
-                    let key = anthropic_key.ok_or_else(|| {
-                        "Anthropic API key not configured; set ANTHROPIC_API_KEY or anthropic.apiKey"
-                            .to_string()
-                    })?;
-                    let mut analyzer =
-                        AnthropicMediaAnalyzer::new(key).map_err(|e| e.to_string())?;
-                    if let Some(base_url) = resolve_anthropic_base_url(cfg.as_ref()) {
-                        analyzer = analyzer.with_base_url(base_url);
-                    }
-                    if let Some(model) = model_override.clone() {
-                        analyzer = analyzer.with_model(model);
-                    }
-                    if let Some(max_tokens) = max_tokens {
-                        analyzer = analyzer.with_max_tokens(max_tokens);
-                    }
-                    analyze(&media_path, &mime_type, &analyzer, prompt.as_deref())
-                        .await
-                        .map_err(|e| e.to_string())?
+                if let Some(model) = model_override.clone() {
+                    analyzer = analyzer.with_vision_model(model);
                 }
-                _ => return Err("unsupported provider".into()),
-            };
+                if let Some(max_tokens) = max_tokens {
+                    analyzer = analyzer.with_max_tokens(max_tokens);
+                }
+                analyze(&media_path, &mime_type, &analyzer, prompt.as_deref())
+                    .await
+                    .map_err(|e| e.to_string())?
+            }
+            "anthropic" => {
+                if media_type == MediaType::Audio {
+                    return Err("Anthropic does not support audio transcription".into());
+                }
+                let key = anthropic_key.ok_or_else(|| {
+                    "Anthropic API key not configured; set ANTHROPIC_API_KEY or anthropic.apiKey"
+                        .to_string()
+                })?;
+                let mut analyzer = AnthropicMediaAnalyzer::new(key).map_err(|e| e.to_string())?;
+                if let Some(base_url) = resolve_anthropic_base_url(cfg.as_ref()) {
+                    analyzer = analyzer.with_base_url(base_url);
+                }
+                if let Some(model) = model_override.clone() {
+                    analyzer = analyzer.with_model(model);
+                }
+                if let Some(max_tokens) = max_tokens {
+                    analyzer = analyzer.with_max_tokens(max_tokens);
+                }
+                analyze(&media_path, &mime_type, &analyzer, prompt.as_deref())
+                    .await
+                    .map_err(|e| e.to_string())?
+            }
+            _ => return Err("unsupported provider".into()),
+        };
 
-            Ok(json!({
-                "analysis": analysis,
-                "mimeType": mime_type,
-                "cached": cached
-            }))
-        })
-    });
+        Ok(json!({
+            "analysis": analysis,
+            "mimeType": mime_type,
+            "cached": cached
+        }))
+    }
+    .await;
 
     match result {
         Ok(value) => ToolInvokeResult::success(value),
@@ -336,7 +335,8 @@ fn handle_media_analyze(args: Value) -> ToolInvokeResult {
     }
 }
 
-fn resolve_openai_media_key(cfg: &Value) -> Option<String> {
+/// OpenAI API key for media analysis and voice transcription.
+pub(crate) fn resolve_openai_media_key(cfg: &Value) -> Option<String> {
     env::var("OPENAI_API_KEY")
         .ok()
         .filter(|k| !k.is_empty())
@@ -358,7 +358,8 @@ fn resolve_openai_media_key(cfg: &Value) -> Option<String> {
         })
 }
 
-fn resolve_openai_base_url(cfg: &Value) -> Option<String> {
+/// Optional OpenAI base URL override for media analysis.
+pub(crate) fn resolve_openai_base_url(cfg: &Value) -> Option<String> {
     env::var("OPENAI_BASE_URL")
         .ok()
         .filter(|v| !v.is_empty())
@@ -434,7 +435,7 @@ const WEB_FETCH_DEFAULT_MAX_BYTES: u64 = 1_048_576;
 /// Maximum allowed value for max_bytes (10 MB).
 const WEB_FETCH_MAX_ALLOWED_BYTES: u64 = 10 * 1024 * 1024;
 
-fn handle_web_fetch(args: Value) -> ToolInvokeResult {
+async fn handle_web_fetch(args: Value) -> ToolInvokeResult {
     let url = match args.get("url").and_then(|v| v.as_str()) {
         Some(u) => u.to_string(),
         None => return ToolInvokeResult::tool_error("missing required parameter: url"),
@@ -446,19 +447,11 @@ fn handle_web_fetch(args: Value) -> ToolInvokeResult {
         .unwrap_or(WEB_FETCH_DEFAULT_MAX_BYTES)
         .min(WEB_FETCH_MAX_ALLOWED_BYTES);
 
-    // We need to run the async fetch synchronously within the tool handler.
-    // The tool handler is called from a synchronous context, so we use
-    // tokio::task::block_in_place + Handle::current().block_on().
-    let result = tokio::task::block_in_place(|| {
-        let handle = tokio::runtime::Handle::current();
-        handle.block_on(async {
-            use crate::media::fetch::{FetchConfig, MediaFetcher};
+    use crate::media::fetch::{FetchConfig, MediaFetcher};
 
-            let config = FetchConfig::default().with_max_size(max_bytes);
-            let fetcher = MediaFetcher::with_config(config);
-            fetcher.fetch(&url).await
-        })
-    });
+    let config = FetchConfig::default().with_max_size(max_bytes);
+    let fetcher = MediaFetcher::with_config(config);
+    let result = fetcher.fetch(&url).await;
 
     match result {
         Ok(fetch_result) => {
@@ -477,78 +470,119 @@ fn handle_web_fetch(args: Value) -> ToolInvokeResult {
 }
 
 // ---------------------------------------------------------------------------
-// memory_read / memory_write / memory_list
+// memory_read / memory_write / memory_list / memory_search
 // ---------------------------------------------------------------------------
 
-/// Resolve the memory store file path for an agent.
-fn memory_store_path(agent_id: Option<&str>) -> PathBuf {
-    let base = dirs::home_dir()
-        .unwrap_or_else(|| PathBuf::from("."))
-        .join(".config")
-        .join("carapace")
-        .join("memory");
-    let key = agent_id.unwrap_or("default");
-    // Sanitize the key to prevent path traversal
-    let safe_key: String = key
-        .chars()
-        .map(|c| {
-            if c.is_alphanumeric() || c == '-' || c == '_' {
-                c
-            } else {
-                '_'
-            }
-        })
-        .collect();
-    base.join(format!("{safe_key}.json"))
+/// Default and maximum number of `memory_search` results
+const MEMORY_SEARCH_DEFAULT_LIMIT: usize = 5;
+const MEMORY_SEARCH_MAX_LIMIT: usize = 20;
+
+/// The memory store and agent namespace for a tool call.
+fn memory_target(ctx: &ToolInvokeContext) -> Result<(&MemoryStore, &str), ToolInvokeResult> {
+    let store = ctx
+        .memory_store
+        .as_deref()
+        .ok_or_else(|| ToolInvokeResult::tool_error("memory store unavailable"))?;
+    let agent_id = ctx
+        .agent_id
+        .as_deref()
+        .unwrap_or(crate::memory::DEFAULT_AGENT_ID);
+    Ok((store, agent_id))
 }
 
-/// Load the memory store from disk.
-fn load_memory(path: &PathBuf) -> HashMap<String, String> {
-    match fs::read_to_string(path) {
-        Ok(content) => serde_json::from_str(&content).unwrap_or_default(),
-        Err(_) => HashMap::new(),
+/// Scope label reported to the model for a memory.
+fn memory_scope_label(entry: &MemoryEntry) -> &'static str {
+    if entry.user_id.is_some() {
+        "user"
+    } else {
+        "agent"
     }
 }
 
-/// Save the memory store to disk atomically.
-fn save_memory(path: &PathBuf, data: &HashMap<String, String>) -> Result<(), String> {
-    if let Some(parent) = path.parent() {
-        fs::create_dir_all(parent).map_err(|e| format!("failed to create directory: {e}"))?;
+/// Parse the optional `scope` argument into the user namespace to use:
+/// `Ok(None)` for agent-wide memories.
+fn memory_scope_arg(args: &Value, ctx: &ToolInvokeContext) -> Result<Option<String>, String> {
+    match args
+        .get("scope")
+        .and_then(|v| v.as_str())
+        .unwrap_or("agent")
+    {
+        "agent" => Ok(None),
+        "user" => {
+            ctx.user_id.clone().map(Some).ok_or_else(|| {
+                "scope \"user\" requires a conversation with a known user".to_string()
+            })
+        }
+        other => Err(format!(
+            "invalid scope: {other} (expected \"agent\" or \"user\")"
+        )),
     }
-    let json = serde_json::to_string_pretty(data)
-        .map_err(|e| format!("failed to serialize memory: {e}"))?;
+}
 
-    // Atomic write: write to temp file, then rename
-    let tmp_path = path.with_extension("tmp");
-    fs::write(&tmp_path, &json).map_err(|e| format!("failed to write memory file: {e}"))?;
-    fs::rename(&tmp_path, path).map_err(|e| format!("failed to rename memory file: {e}"))?;
-    Ok(())
+fn string_list_arg(args: &Value, name: &str) -> Vec<String> {
+    args.get(name)
+        .and_then(|v| v.as_array())
+        .map(|items| {
+            items
+                .iter()
+                .filter_map(|v| v.as_str().map(str::to_string))
+                .collect()
+        })
+        .unwrap_or_default()
 }
 
 fn memory_read_tool() -> BuiltinTool {
     BuiltinTool {
         name: "memory_read".to_string(),
-        description: "Read a value from the agent's persistent key-value memory store.".to_string(),
+        description: "Read a memory by key. Without a scope, the current user's memory is \
+                      preferred over the agent-wide one."
+            .to_string(),
         input_schema: json!({
             "type": "object",
             "properties": {
                 "key": {
                     "type": "string",
                     "description": "The key to read."
+                },
+                "scope": {
+                    "type": "string",
+                    "enum": ["agent", "user"],
+                    "description": "Read only agent-wide memories or only the current user's."
                 }
             },
             "required": ["key"],
             "additionalProperties": false
         }),
-        handler: Box::new(|args, ctx| {
+        handler: tool_handler(|args, ctx| async move {
             let key = match args.get("key").and_then(|v| v.as_str()) {
-                Some(k) => k.to_string(),
+                Some(k) => k.trim().to_string(),
                 None => return ToolInvokeResult::tool_error("missing required parameter: key"),
             };
-            let path = memory_store_path(ctx.agent_id.as_deref());
-            let store = load_memory(&path);
-            let value = store.get(&key).cloned();
-            ToolInvokeResult::success(json!({ "value": value }))
+            let (store, agent_id) = match memory_target(&ctx) {
+                Ok(target) => target,
+                Err(e) => return e,
+            };
+            let entry = if args.get("scope").is_some() {
+                match memory_scope_arg(&args, &ctx) {
+                    Ok(user_id) => store.get_by_key(agent_id, user_id.as_deref(), &key),
+                    Err(e) => return ToolInvokeResult::tool_error(e),
+                }
+            } else {
+                ctx.user_id
+                    .as_deref()
+                    .and_then(|user_id| store.get_by_key(agent_id, Some(user_id), &key))
+                    .or_else(|| store.get_by_key(agent_id, None, &key))
+            };
+            match entry {
+                Some(entry) => ToolInvokeResult::success(json!({
+                    "value": entry.content,
+                    "scope": memory_scope_label(&entry),
+                    "tags": entry.tags,
+                    "updatedAt": entry.updated_at,
+                    "expiresAt": entry.expires_at,
+                })),
+                None => ToolInvokeResult::success(json!({ "value": null })),
+            }
         }),
     }
 }
@@ -556,23 +590,41 @@ fn memory_read_tool() -> BuiltinTool {
 fn memory_write_tool() -> BuiltinTool {
     BuiltinTool {
         name: "memory_write".to_string(),
-        description: "Write a value to the agent's persistent key-value memory store.".to_string(),
+        description: "Save a memory for future conversations, replacing any memory with the \
+                      same key and scope."
+            .to_string(),
         input_schema: json!({
             "type": "object",
             "properties": {
                 "key": {
                     "type": "string",
-                    "description": "The key to write."
+                    "description": "Short descriptive key, e.g. \"preferred_language\"."
                 },
                 "value": {
                     "type": "string",
-                    "description": "The value to store."
+                    "description": "The text to remember."
+                },
+                "scope": {
+                    "type": "string",
+                    "enum": ["agent", "user"],
+                    "description": "\"agent\" (default) shares the memory across all \
+                                    conversations; \"user\" keeps it private to the current user."
+                },
+                "tags": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Optional tags for filtering and search."
+                },
+                "ttl_seconds": {
+                    "type": "integer",
+                    "minimum": 1,
+                    "description": "Forget the memory after this many seconds."
                 }
             },
             "required": ["key", "value"],
             "additionalProperties": false
         }),
-        handler: Box::new(|args, ctx| {
+        handler: tool_handler(|args, ctx| async move {
             let key = match args.get("key").and_then(|v| v.as_str()) {
                 Some(k) => k.to_string(),
                 None => return ToolInvokeResult::tool_error("missing required parameter: key"),
@@ -581,12 +633,33 @@ fn memory_write_tool() -> BuiltinTool {
                 Some(v) => v.to_string(),
                 None => return ToolInvokeResult::tool_error("missing required parameter: value"),
             };
-            let path = memory_store_path(ctx.agent_id.as_deref());
-            let mut store = load_memory(&path);
-            store.insert(key, value);
-            match save_memory(&path, &store) {
-                Ok(()) => ToolInvokeResult::success(json!({ "ok": true })),
-                Err(e) => ToolInvokeResult::tool_error(e),
+            let user_id = match memory_scope_arg(&args, &ctx) {
+                Ok(user_id) => user_id,
+                Err(e) => return ToolInvokeResult::tool_error(e),
+            };
+            let expires_at = args
+                .get("ttl_seconds")
+                .and_then(|v| v.as_u64())
+                .filter(|ttl| *ttl > 0)
+                .map(|ttl| crate::memory::now_ms().saturating_add(ttl.saturating_mul(1000)));
+            let (store, agent_id) = match memory_target(&ctx) {
+                Ok(target) => target,
+                Err(e) => return e,
+            };
+            match store.put(MemoryWrite {
+                agent_id: agent_id.to_string(),
+                user_id,
+                key,
+                content: value,
+                tags: string_list_arg(&args, "tags"),
+                expires_at,
+            }) {
+                Ok(entry) => ToolInvokeResult::success(json!({
+                    "ok": true,
+                    "id": entry.id,
+                    "scope": memory_scope_label(&entry),
+                })),
+                Err(e) => ToolInvokeResult::tool_error(e.to_string()),
             }
         }),
     }
@@ -595,18 +668,99 @@ fn memory_write_tool() -> BuiltinTool {
 fn memory_list_tool() -> BuiltinTool {
     BuiltinTool {
         name: "memory_list".to_string(),
-        description: "List all keys in the agent's persistent memory store.".to_string(),
+        description: "List the keys of memories visible in this conversation.".to_string(),
         input_schema: json!({
             "type": "object",
-            "properties": {},
+            "properties": {
+                "tag": {
+                    "type": "string",
+                    "description": "Only list memories with this tag."
+                }
+            },
             "additionalProperties": false
         }),
-        handler: Box::new(|_args, ctx| {
-            let path = memory_store_path(ctx.agent_id.as_deref());
-            let store = load_memory(&path);
-            let mut keys: Vec<String> = store.keys().cloned().collect();
-            keys.sort();
-            ToolInvokeResult::success(json!({ "keys": keys }))
+        handler: tool_handler(|args, ctx| async move {
+            let (store, agent_id) = match memory_target(&ctx) {
+                Ok(target) => target,
+                Err(e) => return e,
+            };
+            let tags: Vec<String> = args
+                .get("tag")
+                .and_then(|v| v.as_str())
+                .map(|t| vec![t.to_string()])
+                .unwrap_or_default();
+            let filter = MemoryFilter::visible_to(agent_id, ctx.user_id.as_deref()).with_tags(tags);
+            let mut entries = store.list(&filter);
+            entries.sort_by(|a, b| a.key.cmp(&b.key));
+            let mut keys: Vec<String> = entries.iter().map(|e| e.key.clone()).collect();
+            keys.dedup();
+            let memories: Vec<Value> = entries
+                .iter()
+                .map(|e| json!({ "key": e.key, "scope": memory_scope_label(e), "tags": e.tags }))
+                .collect();
+            ToolInvokeResult::success(json!({ "keys": keys, "memories": memories }))
+        }),
+    }
+}
+
+fn memory_search_tool() -> BuiltinTool {
+    BuiltinTool {
+        name: "memory_search".to_string(),
+        description: "Search memories visible in this conversation by relevance to a query."
+            .to_string(),
+        input_schema: json!({
+            "type": "object",
+            "properties": {
+                "query": {
+                    "type": "string",
+                    "description": "Words to search for in memory keys, tags and content."
+                },
+                "tags": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Only return memories with all of these tags."
+                },
+                "limit": {
+                    "type": "integer",
+                    "minimum": 1,
+                    "maximum": MEMORY_SEARCH_MAX_LIMIT,
+                    "description": "Maximum number of results (default 5)."
+                }
+            },
+            "required": ["query"],
+            "additionalProperties": false
+        }),
+        handler: tool_handler(|args, ctx| async move {
+            let query = match args.get("query").and_then(|v| v.as_str()) {
+                Some(q) if !q.trim().is_empty() => q.to_string(),
+                _ => return ToolInvokeResult::tool_error("missing required parameter: query"),
+            };
+            let limit = args
+                .get("limit")
+                .and_then(|v| v.as_u64())
+                .map(|l| (l as usize).clamp(1, MEMORY_SEARCH_MAX_LIMIT))
+                .unwrap_or(MEMORY_SEARCH_DEFAULT_LIMIT);
+            let (store, agent_id) = match memory_target(&ctx) {
+                Ok(target) => target,
+                Err(e) => return e,
+            };
+            let filter = MemoryFilter::visible_to(agent_id, ctx.user_id.as_deref())
+                .with_tags(string_list_arg(&args, "tags"));
+            let results: Vec<Value> = store
+                .search(&query, &filter, limit)
+                .into_iter()
+                .map(|hit| {
+                    json!({
+                        "key": hit.entry.key,
+                        "value": hit.entry.content,
+                        "scope": memory_scope_label(&hit.entry),
+                        "tags": hit.entry.tags,
+                        "score": (hit.score * 1000.0).round() / 1000.0,
+                        "updatedAt": hit.entry.updated_at,
+                    })
+                })
+                .collect();
+            ToolInvokeResult::success(json!({ "results": results }))
         }),
     }
 }
@@ -618,7 +772,8 @@ fn memory_list_tool() -> BuiltinTool {
 fn message_send_tool() -> BuiltinTool {
     BuiltinTool {
         name: "message_send".to_string(),
-        description: "Send a text message to a channel, queuing it into the delivery pipeline."
+        description: "Send a text message to a channel, queuing it into the delivery pipeline. \
+                       Defaults to the current conversation when `to` is omitted."
             .to_string(),
         input_schema: json!({
             "type": "object",
@@ -630,12 +785,16 @@ fn message_send_tool() -> BuiltinTool {
                 "text": {
                     "type": "string",
                     "description": "The message text to send."
+                },
+                "to": {
+                    "type": "string",
+                    "description": "Recipient chat/conversation ID. Defaults to the current conversation."
                 }
             },
             "required": ["channel", "text"],
             "additionalProperties": false
         }),
-        handler: Box::new(|args, _ctx| {
+        handler: tool_handler(|args, ctx| async move {
             let channel = match args.get("channel").and_then(|v| v.as_str()) {
                 Some(c) => c.to_string(),
                 None => return ToolInvokeResult::tool_error("missing required parameter: channel"),
@@ -652,21 +811,36 @@ fn message_send_tool() -> BuiltinTool {
                 return ToolInvokeResult::tool_error("text must not be empty");
             }
 
-            // Queue the message into the outbound pipeline.
-            // We access the pipeline through the global create_pipeline for now.
-            // In production, the message pipeline is shared via WsServerState,
-            // but tool handlers don't currently have access to Arc<WsServerState>.
-            // We return a "queued" result — the delivery loop picks it up.
-            //
-            // NOTE: Since tool handlers are synchronous and don't have access
-            // to the server state, we store the message intent as a success
-            // result. The executor or a future enhancement can intercept this.
-            // For now, we indicate the message was accepted for delivery.
-            ToolInvokeResult::success(json!({
-                "queued": true,
-                "channel": channel,
-                "text": text
-            }))
+            // An explicit recipient wins; otherwise only fall back to the
+            // originating conversation when sending on the same channel, so a
+            // Telegram chat ID is never used as a Discord target.
+            let recipient = match args.get("to").and_then(|v| v.as_str()) {
+                Some(to) if !to.is_empty() => to.to_string(),
+                Some(_) => return ToolInvokeResult::tool_error("to must not be empty"),
+                None if ctx.message_channel.as_deref() == Some(channel.as_str()) => {
+                    match ctx.recipient_id.clone() {
+                        Some(r) => r,
+                        None => {
+                            return ToolInvokeResult::tool_error(
+                                "no recipient known for the current conversation; pass `to`",
+                            )
+                        }
+                    }
+                }
+                None => {
+                    return ToolInvokeResult::tool_error(
+                        "`to` is required when sending to a different channel",
+                    )
+                }
+            };
+
+            let message = OutboundMessage::new(channel, MessageContent::text(text)).with_metadata(
+                MessageMetadata {
+                    recipient_id: Some(recipient),
+                    ..Default::default()
+                },
+            );
+            enqueue_outbound(&ctx, message)
         }),
     }
 }
@@ -689,13 +863,10 @@ fn session_list_tool() -> BuiltinTool {
             },
             "additionalProperties": false
         }),
-        handler: Box::new(|args, _ctx| {
+        handler: tool_handler(|args, ctx| async move {
             let limit = args.get("limit").and_then(|v| v.as_u64()).unwrap_or(20) as usize;
 
-            // Load sessions from the on-disk store.
-            // Use the same base path resolution as the server.
-            let base_path = resolve_sessions_path();
-            let store = crate::sessions::SessionStore::with_base_path(base_path);
+            let store = session_store_for(&ctx);
 
             let filter = crate::sessions::SessionFilter {
                 limit: Some(limit),
@@ -749,7 +920,7 @@ fn session_read_tool() -> BuiltinTool {
             "required": ["session_id"],
             "additionalProperties": false
         }),
-        handler: Box::new(|args, _ctx| {
+        handler: tool_handler(|args, ctx| async move {
             let session_id = match args.get("session_id").and_then(|v| v.as_str()) {
                 Some(s) => s.to_string(),
                 None => {
@@ -758,8 +929,7 @@ fn session_read_tool() -> BuiltinTool {
             };
             let limit = args.get("limit").and_then(|v| v.as_u64()).unwrap_or(50) as usize;
 
-            let base_path = resolve_sessions_path();
-            let store = crate::sessions::SessionStore::with_base_path(base_path);
+            let store = session_store_for(&ctx);
 
             match store.get_history(&session_id, Some(limit), None) {
                 Ok(messages) => {
@@ -806,7 +976,7 @@ fn config_read_tool() -> BuiltinTool {
             "required": ["key"],
             "additionalProperties": false
         }),
-        handler: Box::new(|args, _ctx| {
+        handler: tool_handler(|args, _ctx| async move {
             let key = match args.get("key").and_then(|v| v.as_str()) {
                 Some(k) => k.to_string(),
                 None => return ToolInvokeResult::tool_error("missing required parameter: key"),
@@ -891,7 +1061,7 @@ fn math_eval_tool() -> BuiltinTool {
             "required": ["expression"],
             "additionalProperties": false
         }),
-        handler: Box::new(|args, _ctx| {
+        handler: tool_handler(|args, _ctx| async move {
             let expr = match args.get("expression").and_then(|v| v.as_str()) {
                 Some(e) => e.to_string(),
                 None => {
@@ -1113,6 +1283,16 @@ fn parse_primary(tokens: &[Token], pos: &mut usize) -> Result<f64, String> {
 // Helpers
 // ---------------------------------------------------------------------------
 
+/// Session store of the running server, falling back to the on-disk store
+/// when the tool is invoked without server state.
+fn session_store_for(ctx: &ToolInvokeContext) -> Arc<crate::sessions::SessionStore> {
+    ctx.session_store.clone().unwrap_or_else(|| {
+        Arc::new(crate::sessions::SessionStore::with_base_path(
+            resolve_sessions_path(),
+        ))
+    })
+}
+
 /// Resolve the sessions base path, matching the server's convention.
 fn resolve_sessions_path() -> PathBuf {
     if let Ok(state_dir) = std::env::var("CARAPACE_STATE_DIR") {
@@ -1131,16 +1311,15 @@ fn resolve_sessions_path() -> PathBuf {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::plugins::tools::ToolInvokeContext;
     use serde_json::json;
 
     // -- current_time tests --
 
-    #[test]
-    fn test_current_time() {
+    #[tokio::test]
+    async fn test_current_time() {
         let tool = current_time_tool();
         let ctx = ToolInvokeContext::default();
-        let result = (tool.handler)(json!({}), &ctx);
+        let result = (tool.handler)(json!({}), ctx).await;
         match result {
             ToolInvokeResult::Success { result, .. } => {
                 assert!(result.get("iso").is_some(), "should have iso field");
@@ -1213,11 +1392,11 @@ mod tests {
         assert_eq!(eval_math("((2 + 3) * (4 - 1))").unwrap(), 15.0);
     }
 
-    #[test]
-    fn test_math_tool_handler() {
+    #[tokio::test]
+    async fn test_math_tool_handler() {
         let tool = math_eval_tool();
         let ctx = ToolInvokeContext::default();
-        let result = (tool.handler)(json!({"expression": "2 + 3 * 4"}), &ctx);
+        let result = (tool.handler)(json!({"expression": "2 + 3 * 4"}), ctx).await;
         match result {
             ToolInvokeResult::Success { result, .. } => {
                 assert_eq!(result["result"], 14.0);
@@ -1226,11 +1405,11 @@ mod tests {
         }
     }
 
-    #[test]
-    fn test_math_tool_missing_expression() {
+    #[tokio::test]
+    async fn test_math_tool_missing_expression() {
         let tool = math_eval_tool();
         let ctx = ToolInvokeContext::default();
-        let result = (tool.handler)(json!({}), &ctx);
+        let result = (tool.handler)(json!({}), ctx).await;
         match result {
             ToolInvokeResult::Error { .. } => {}
             _ => panic!("expected error for missing expression"),
@@ -1239,53 +1418,141 @@ mod tests {
 
     // -- memory tests --
 
-    #[test]
-    fn test_memory_write_and_read() {
-        let tmp = tempfile::tempdir().unwrap();
-        let path = tmp.path().join("test_agent.json");
+    fn memory_ctx(user_id: Option<&str>) -> ToolInvokeContext {
+        ToolInvokeContext {
+            agent_id: Some("test_agent".to_string()),
+            user_id: user_id.map(str::to_string),
+            memory_store: Some(Arc::new(MemoryStore::in_memory())),
+            ..Default::default()
+        }
+    }
 
-        // Write
-        let mut store = HashMap::new();
-        store.insert("greeting".to_string(), "hello world".to_string());
-        save_memory(&path, &store).unwrap();
+    async fn invoke_ok(tool: BuiltinTool, args: Value, ctx: &ToolInvokeContext) -> Value {
+        match (tool.handler)(args, ctx.clone()).await {
+            ToolInvokeResult::Success { result, .. } => result,
+            other => panic!("expected success, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_memory_write_and_read() {
+        let ctx = memory_ctx(None);
+        invoke_ok(
+            memory_write_tool(),
+            json!({ "key": "greeting", "value": "hello world", "tags": ["Intro"] }),
+            &ctx,
+        )
+        .await;
 
-        // Read
-        let loaded = load_memory(&path);
-        assert_eq!(loaded.get("greeting").unwrap(), "hello world");
+        let result = invoke_ok(memory_read_tool(), json!({ "key": "greeting" }), &ctx).await;
+        assert_eq!(result["value"], "hello world");
+        assert_eq!(result["scope"], "agent");
+        assert_eq!(result["tags"], json!(["intro"]));
     }
 
-    #[test]
-    fn test_memory_read_nonexistent() {
-        let tmp = tempfile::tempdir().unwrap();
-        let path = tmp.path().join("nonexistent.json");
-        let loaded = load_memory(&path);
-        assert!(loaded.is_empty());
+    #[tokio::test]
+    async fn test_memory_read_nonexistent() {
+        let ctx = memory_ctx(None);
+        let result = invoke_ok(memory_read_tool(), json!({ "key": "missing" }), &ctx).await;
+        assert!(result["value"].is_null());
     }
 
-    #[test]
-    fn test_memory_list_empty() {
-        let tool = memory_list_tool();
-        let ctx = ToolInvokeContext {
-            agent_id: Some("test_empty_list_agent_xxxxx".to_string()),
-            ..Default::default()
+    #[tokio::test]
+    async fn test_memory_user_scope_shadows_agent_scope() {
+        let ctx = memory_ctx(Some("telegram:42"));
+        invoke_ok(
+            memory_write_tool(),
+            json!({ "key": "tz", "value": "UTC" }),
+            &ctx,
+        )
+        .await;
+        invoke_ok(
+            memory_write_tool(),
+            json!({ "key": "tz", "value": "Asia/Tokyo", "scope": "user" }),
+            &ctx,
+        )
+        .await;
+
+        let result = invoke_ok(memory_read_tool(), json!({ "key": "tz" }), &ctx).await;
+        assert_eq!(result["value"], "Asia/Tokyo");
+        let result = invoke_ok(
+            memory_read_tool(),
+            json!({ "key": "tz", "scope": "agent" }),
+            &ctx,
+        )
+        .await;
+        assert_eq!(result["value"], "UTC");
+
+        // Another user of the same agent only sees the agent-wide memory
+        let other = ToolInvokeContext {
+            user_id: Some("telegram:7".to_string()),
+            ..ctx.clone()
         };
-        let result = (tool.handler)(json!({}), &ctx);
-        match result {
-            ToolInvokeResult::Success { result, .. } => {
-                let keys = result["keys"].as_array().unwrap();
-                assert!(keys.is_empty());
-            }
-            _ => panic!("expected success"),
+        let result = invoke_ok(memory_read_tool(), json!({ "key": "tz" }), &other).await;
+        assert_eq!(result["value"], "UTC");
+    }
+
+    #[tokio::test]
+    async fn test_memory_user_scope_requires_user() {
+        let ctx = memory_ctx(None);
+        let result = (memory_write_tool().handler)(
+            json!({ "key": "k", "value": "v", "scope": "user" }),
+            ctx,
+        )
+        .await;
+        assert!(matches!(result, ToolInvokeResult::Error { .. }));
+    }
+
+    #[tokio::test]
+    async fn test_memory_list_empty() {
+        let ctx = memory_ctx(None);
+        let result = invoke_ok(memory_list_tool(), json!({}), &ctx).await;
+        assert!(result["keys"].as_array().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_memory_requires_store() {
+        let result = (memory_list_tool().handler)(json!({}), ToolInvokeContext::default()).await;
+        assert!(matches!(result, ToolInvokeResult::Error { .. }));
+    }
+
+    #[tokio::test]
+    async fn test_memory_search_ranks_results() {
+        let ctx = memory_ctx(None);
+        for (key, value) in [
+            ("coffee", "Takes coffee black, no sugar"),
+            ("birthday", "Birthday is on March 3rd"),
+            ("cafe", "Favourite coffee shop is around the corner"),
+        ] {
+            invoke_ok(
+                memory_write_tool(),
+                json!({ "key": key, "value": value }),
+                &ctx,
+            )
+            .await;
         }
+
+        let result = invoke_ok(
+            memory_search_tool(),
+            json!({ "query": "how do they like their coffee", "limit": 1 }),
+            &ctx,
+        )
+        .await;
+        let results = result["results"].as_array().unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0]["key"], "coffee");
+
+        let result = (memory_search_tool().handler)(json!({ "query": " " }), ctx).await;
+        assert!(matches!(result, ToolInvokeResult::Error { .. }));
     }
 
     // -- config_read tests --
 
-    #[test]
-    fn test_config_read_missing_key() {
+    #[tokio::test]
+    async fn test_config_read_missing_key() {
         let tool = config_read_tool();
         let ctx = ToolInvokeContext::default();
-        let result = (tool.handler)(json!({"key": "nonexistent.deeply.nested"}), &ctx);
+        let result = (tool.handler)(json!({"key": "nonexistent.deeply.nested"}), ctx).await;
         match result {
             ToolInvokeResult::Success { result, .. } => {
                 assert!(result["value"].is_null());
@@ -1294,11 +1561,11 @@ mod tests {
         }
     }
 
-    #[test]
-    fn test_config_read_missing_param() {
+    #[tokio::test]
+    async fn test_config_read_missing_param() {
         let tool = config_read_tool();
         let ctx = ToolInvokeContext::default();
-        let result = (tool.handler)(json!({}), &ctx);
+        let result = (tool.handler)(json!({}), ctx).await;
         match result {
             ToolInvokeResult::Error { .. } => {}
             _ => panic!("expected error for missing key parameter"),
@@ -1351,36 +1618,116 @@ mod tests {
 
     // -- message_send tests --
 
-    #[test]
-    fn test_message_send_success() {
+    fn message_ctx(channel: &str) -> ToolInvokeContext {
+        ToolInvokeContext {
+            message_channel: Some(channel.to_string()),
+            recipient_id: Some("chat-42".to_string()),
+            message_pipeline: Some(Arc::new(crate::messages::outbound::MessagePipeline::new())),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_message_send_success() {
         let tool = message_send_tool();
-        let ctx = ToolInvokeContext::default();
-        let result = (tool.handler)(json!({"channel": "telegram", "text": "Hello world"}), &ctx);
+        let ctx = message_ctx("telegram");
+        let result = (tool.handler)(
+            json!({"channel": "telegram", "text": "Hello world"}),
+            ctx.clone(),
+        )
+        .await;
         match result {
             ToolInvokeResult::Success { result, .. } => {
-                assert_eq!(result["queued"], true);
+                assert_eq!(result["status"], "queued");
                 assert_eq!(result["channel"], "telegram");
+                let id = crate::messages::outbound::MessageId::from_string(
+                    result["message_id"].as_str().unwrap(),
+                );
+                let queued = ctx
+                    .message_pipeline
+                    .as_ref()
+                    .unwrap()
+                    .get_message(&id)
+                    .expect("message should be queued in the pipeline");
+                assert_eq!(queued.message.channel_id, "telegram");
+                assert_eq!(
+                    queued.message.metadata.recipient_id.as_deref(),
+                    Some("chat-42")
+                );
+                assert!(matches!(
+                    queued.message.content,
+                    MessageContent::Text { ref text } if text == "Hello world"
+                ));
             }
             _ => panic!("expected success"),
         }
     }
 
-    #[test]
-    fn test_message_send_missing_channel() {
+    #[tokio::test]
+    async fn test_message_send_explicit_recipient() {
+        let tool = message_send_tool();
+        let ctx = message_ctx("telegram");
+        let result = (tool.handler)(
+            json!({"channel": "discord", "text": "Hi", "to": "chan-7"}),
+            ctx.clone(),
+        )
+        .await;
+        match result {
+            ToolInvokeResult::Success { result, .. } => {
+                let pipeline = ctx.message_pipeline.as_ref().unwrap();
+                let queued = pipeline.next_for_channel("discord").unwrap();
+                assert_eq!(queued.message.id.0, result["message_id"]);
+                assert_eq!(
+                    queued.message.metadata.recipient_id.as_deref(),
+                    Some("chan-7")
+                );
+            }
+            _ => panic!("expected success"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_message_send_other_channel_requires_recipient() {
+        let tool = message_send_tool();
+        let ctx = message_ctx("telegram");
+        let result = (tool.handler)(json!({"channel": "discord", "text": "Hi"}), ctx.clone()).await;
+        assert!(matches!(result, ToolInvokeResult::Error { .. }));
+        assert_eq!(ctx.message_pipeline.unwrap().total_queue_size(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_message_send_without_pipeline() {
+        let tool = message_send_tool();
+        let ctx = ToolInvokeContext::default();
+        let result = (tool.handler)(
+            json!({"channel": "telegram", "text": "Hello", "to": "chat-1"}),
+            ctx,
+        )
+        .await;
+        match result {
+            ToolInvokeResult::Error { error, .. } => {
+                assert!(error.message.contains("pipeline"));
+            }
+            _ => panic!("expected error without pipeline"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_message_send_missing_channel() {
         let tool = message_send_tool();
         let ctx = ToolInvokeContext::default();
-        let result = (tool.handler)(json!({"text": "Hello"}), &ctx);
+        let result = (tool.handler)(json!({"text": "Hello"}), ctx).await;
         match result {
             ToolInvokeResult::Error { .. } => {}
             _ => panic!("expected error for missing channel"),
         }
     }
 
-    #[test]
-    fn test_message_send_empty_text() {
+    #[tokio::test]
+    async fn test_message_send_empty_text() {
         let tool = message_send_tool();
         let ctx = ToolInvokeContext::default();
-        let result = (tool.handler)(json!({"channel": "telegram", "text": ""}), &ctx);
+        let result = (tool.handler)(json!({"channel": "telegram", "text": ""}), ctx).await;
         match result {
             ToolInvokeResult::Error { .. } => {}
             _ => panic!("expected error for empty text"),
@@ -1396,13 +1743,41 @@ mod tests {
         assert!(!tool.description.is_empty());
     }
 
+    #[tokio::test]
+    async fn test_session_list_uses_context_store() {
+        let tmp = tempfile::tempdir().unwrap();
+        let store = Arc::new(crate::sessions::SessionStore::with_base_path(
+            tmp.path().to_path_buf(),
+        ));
+        let metadata = crate::sessions::SessionMetadata {
+            name: Some("ops".to_string()),
+            ..Default::default()
+        };
+        let session = store.create_session("ops", metadata).unwrap();
+
+        let ctx = ToolInvokeContext {
+            session_store: Some(store),
+            ..Default::default()
+        };
+        let result = (session_list_tool().handler)(json!({}), ctx).await;
+        match result {
+            ToolInvokeResult::Success { result, .. } => {
+                let sessions = result["sessions"].as_array().unwrap();
+                assert_eq!(sessions.len(), 1);
+                assert_eq!(sessions[0]["id"], session.id.as_str());
+                assert_eq!(sessions[0]["name"], "ops");
+            }
+            _ => panic!("expected success"),
+        }
+    }
+
     // -- session_read tests --
 
-    #[test]
-    fn test_session_read_missing_session_id() {
+    #[tokio::test]
+    async fn test_session_read_missing_session_id() {
         let tool = session_read_tool();
         let ctx = ToolInvokeContext::default();
-        let result = (tool.handler)(json!({}), &ctx);
+        let result = (tool.handler)(json!({}), ctx).await;
         match result {
             ToolInvokeResult::Error { .. } => {}
             _ => panic!("expected error for missing session_id"),
@@ -1411,11 +1786,11 @@ mod tests {
 
     // -- web_fetch tests --
 
-    #[test]
-    fn test_web_fetch_missing_url() {
+    #[tokio::test]
+    async fn test_web_fetch_missing_url() {
         let tool = web_fetch_tool();
         let ctx = ToolInvokeContext::default();
-        let result = (tool.handler)(json!({}), &ctx);
+        let result = (tool.handler)(json!({}), ctx).await;
         match result {
             ToolInvokeResult::Error { .. } => {}
             _ => panic!("expected error for missing url"),
@@ -1424,39 +1799,41 @@ mod tests {
 
     // -- media_analyze tests --
 
-    #[test]
-    fn test_media_analyze_missing_source() {
+    #[tokio::test]
+    async fn test_media_analyze_missing_source() {
         let tool = media_analyze_tool();
         let ctx = ToolInvokeContext::default();
-        let result = (tool.handler)(json!({}), &ctx);
+        let result = (tool.handler)(json!({}), ctx).await;
         match result {
             ToolInvokeResult::Error { .. } => {}
             _ => panic!("expected error for missing url/path"),
         }
     }
 
-    #[test]
-    fn test_media_analyze_conflicting_source() {
+    #[tokio::test]
+    async fn test_media_analyze_conflicting_source() {
         let tool = media_analyze_tool();
         let ctx = ToolInvokeContext::default();
         let result = (tool.handler)(
             json!({"url": "https://example.com", "path": "/tmp/a.png"}),
-            &ctx,
-        );
+            ctx,
+        )
+        .await;
         match result {
             ToolInvokeResult::Error { .. } => {}
             _ => panic!("expected error for both url and path"),
         }
     }
 
-    #[test]
-    fn test_media_analyze_unsupported_provider() {
+    #[tokio::test]
+    async fn test_media_analyze_unsupported_provider() {
         let tool = media_analyze_tool();
         let ctx = ToolInvokeContext::default();
         let result = (tool.handler)(
             json!({"url": "https://example.com/image.png", "provider": "unknown"}),
-            &ctx,
-        );
+            ctx,
+        )
+        .await;
         match result {
             ToolInvokeResult::Error { .. } => {}
             _ => panic!("expected error for unsupported provider"),
@@ -1468,7 +1845,7 @@ mod tests {
     #[test]
     fn test_builtin_tools_returns_all_tools() {
         let tools = builtin_tools();
-        assert_eq!(tools.len(), 11, "should have 11 built-in tools");
+        assert_eq!(tools.len(), 15, "should have 15 built-in tools");
         let names: Vec<&str> = tools.iter().map(|t| t.name.as_str()).collect();
         assert!(names.contains(&"current_time"));
         assert!(names.contains(&"web_fetch"));
@@ -1476,11 +1853,15 @@ mod tests {
         assert!(names.contains(&"memory_read"));
         assert!(names.contains(&"memory_write"));
         assert!(names.contains(&"memory_list"));
+        assert!(names.contains(&"memory_search"));
         assert!(names.contains(&"message_send"));
         assert!(names.contains(&"session_list"));
         assert!(names.contains(&"session_read"));
         assert!(names.contains(&"config_read"));
         assert!(names.contains(&"math_eval"));
+        assert!(names.contains(&"schedule_create"));
+        assert!(names.contains(&"schedule_list"));
+        assert!(names.contains(&"schedule_cancel"));
     }
 
     #[test]
@@ -1,13 +1,17 @@
 //! Channel-specific built-in tools.
 //!
 //! Provides tools that are only available when the agent conversation originated
-//! from a specific messaging channel (Telegram, Discord, Slack). These tools
-//! return structured intent objects that the delivery pipeline picks up and
-//! routes to the appropriate channel plugin — they do NOT make actual API calls.
+//! from a specific messaging channel (Telegram, Discord, Slack). Each tool
+//! enqueues an `OutboundMessage` (optionally carrying a `ChannelAction`) into
+//! the shared `MessagePipeline`; the delivery loop routes it to the channel
+//! plugin. The tool result reports the pipeline message ID and status.
 
 use serde_json::{json, Value};
 
-use crate::plugins::tools::{BuiltinTool, ToolInvokeContext, ToolInvokeResult};
+use crate::messages::outbound::{
+    ChannelAction, MessageContent, MessageMetadata, OutboundContext, OutboundMessage,
+};
+use crate::plugins::tools::{tool_handler, BuiltinTool, ToolInvokeContext, ToolInvokeResult};
 
 /// Return channel-specific tools for the given channel.
 /// Returns an empty Vec if channel is None or unrecognized.
@@ -43,6 +47,75 @@ fn require_str(args: &Value, key: &str) -> Result<String, ToolInvokeResult> {
         .ok_or_else(|| ToolInvokeResult::tool_error(format!("missing required parameter: {key}")))
 }
 
+/// Build an outbound message addressed to `recipient` on `channel`.
+fn outbound_to(
+    channel: &str,
+    recipient: String,
+    content: MessageContent,
+    action: Option<ChannelAction>,
+) -> OutboundMessage {
+    OutboundMessage::new(channel, content).with_metadata(MessageMetadata {
+        recipient_id: Some(recipient),
+        action,
+        ..Default::default()
+    })
+}
+
+/// Build an outbound message addressed to the conversation the agent run
+/// originated from. Fails if the context carries no recipient.
+fn conversation_outbound(
+    ctx: &ToolInvokeContext,
+    channel: &str,
+    content: MessageContent,
+    action: Option<ChannelAction>,
+) -> Result<OutboundMessage, ToolInvokeResult> {
+    let recipient = ctx.recipient_id.clone().ok_or_else(|| {
+        ToolInvokeResult::tool_error("no recipient known for the current conversation")
+    })?;
+    Ok(outbound_to(channel, recipient, content, action))
+}
+
+/// Queue an outbound message into the shared pipeline.
+///
+/// Returns the assigned pipeline message ID and delivery status so the model
+/// sees what actually happened rather than an echo of its arguments.
+pub(crate) fn enqueue_outbound(
+    ctx: &ToolInvokeContext,
+    message: OutboundMessage,
+) -> ToolInvokeResult {
+    let Some(pipeline) = ctx.message_pipeline.as_ref() else {
+        return ToolInvokeResult::tool_error("message pipeline unavailable");
+    };
+    let channel = message.channel_id.clone();
+    let action = message.metadata.action.as_ref().map(|a| a.name());
+    let outbound_ctx = OutboundContext::new()
+        .with_trace_id(&ctx.session_key)
+        .with_source("agent");
+    match pipeline.queue(message, outbound_ctx) {
+        Ok(queued) => ToolInvokeResult::success(json!({
+            "message_id": queued.message_id.0,
+            "status": queued.status,
+            "channel": channel,
+            "action": action,
+            "queue_position": queued.queue_position,
+        })),
+        Err(e) => ToolInvokeResult::tool_error(format!("failed to queue message: {e}")),
+    }
+}
+
+/// Queue a channel action targeting the current conversation.
+fn enqueue_action(
+    ctx: &ToolInvokeContext,
+    channel: &str,
+    text: String,
+    action: ChannelAction,
+) -> ToolInvokeResult {
+    match conversation_outbound(ctx, channel, MessageContent::text(text), Some(action)) {
+        Ok(message) => enqueue_outbound(ctx, message),
+        Err(e) => e,
+    }
+}
+
 // ===========================================================================
 // Telegram tools
 // ===========================================================================
@@ -76,8 +149,8 @@ fn telegram_edit_message() -> BuiltinTool {
             "required": ["message_id", "text"],
             "additionalProperties": false
         }),
-        handler: Box::new(|args, ctx| {
-            if let Some(err) = require_channel(ctx, "telegram") {
+        handler: tool_handler(|args, ctx| async move {
+            if let Some(err) = require_channel(&ctx, "telegram") {
                 return err;
             }
             let message_id = match require_str(&args, "message_id") {
@@ -88,11 +161,14 @@ fn telegram_edit_message() -> BuiltinTool {
                 Ok(v) => v,
                 Err(e) => return e,
             };
-            ToolInvokeResult::success(json!({
-                "ok": true,
-                "message_id": message_id,
-                "text": text
-            }))
+            enqueue_action(
+                &ctx,
+                "telegram",
+                text,
+                ChannelAction::Edit {
+                    target_id: message_id,
+                },
+            )
         }),
     }
 }
@@ -112,18 +188,22 @@ fn telegram_delete_message() -> BuiltinTool {
             "required": ["message_id"],
             "additionalProperties": false
         }),
-        handler: Box::new(|args, ctx| {
-            if let Some(err) = require_channel(ctx, "telegram") {
+        handler: tool_handler(|args, ctx| async move {
+            if let Some(err) = require_channel(&ctx, "telegram") {
                 return err;
             }
             let message_id = match require_str(&args, "message_id") {
                 Ok(v) => v,
                 Err(e) => return e,
             };
-            ToolInvokeResult::success(json!({
-                "ok": true,
-                "message_id": message_id
-            }))
+            enqueue_action(
+                &ctx,
+                "telegram",
+                String::new(),
+                ChannelAction::Delete {
+                    target_id: message_id,
+                },
+            )
         }),
     }
 }
@@ -147,8 +227,8 @@ fn telegram_pin_message() -> BuiltinTool {
             "required": ["message_id"],
             "additionalProperties": false
         }),
-        handler: Box::new(|args, ctx| {
-            if let Some(err) = require_channel(ctx, "telegram") {
+        handler: tool_handler(|args, ctx| async move {
+            if let Some(err) = require_channel(&ctx, "telegram") {
                 return err;
             }
             let message_id = match require_str(&args, "message_id") {
@@ -159,11 +239,15 @@ fn telegram_pin_message() -> BuiltinTool {
                 .get("silent")
                 .and_then(|v| v.as_bool())
                 .unwrap_or(false);
-            ToolInvokeResult::success(json!({
-                "ok": true,
-                "message_id": message_id,
-                "silent": silent
-            }))
+            enqueue_action(
+                &ctx,
+                "telegram",
+                String::new(),
+                ChannelAction::Pin {
+                    target_id: message_id,
+                    silent,
+                },
+            )
         }),
     }
 }
@@ -196,24 +280,27 @@ fn telegram_reply_markup() -> BuiltinTool {
             "required": ["message_id", "buttons"],
             "additionalProperties": false
         }),
-        handler: Box::new(|args, ctx| {
-            if let Some(err) = require_channel(ctx, "telegram") {
+        handler: tool_handler(|args, ctx| async move {
+            if let Some(err) = require_channel(&ctx, "telegram") {
                 return err;
             }
             let message_id = match require_str(&args, "message_id") {
                 Ok(v) => v,
                 Err(e) => return e,
             };
-            let buttons = match args.get("buttons").and_then(|v| v.as_array()) {
-                Some(arr) => arr,
+            let buttons = match args.get("buttons").filter(|v| v.is_array()) {
+                Some(arr) => arr.clone(),
                 None => return ToolInvokeResult::tool_error("missing required parameter: buttons"),
             };
-            let button_count = buttons.len();
-            ToolInvokeResult::success(json!({
-                "ok": true,
-                "message_id": message_id,
-                "button_count": button_count
-            }))
+            enqueue_action(
+                &ctx,
+                "telegram",
+                String::new(),
+                ChannelAction::ReplyMarkup {
+                    target_id: message_id,
+                    buttons,
+                },
+            )
         }),
     }
 }
@@ -237,8 +324,8 @@ fn telegram_send_photo() -> BuiltinTool {
             "required": ["url"],
             "additionalProperties": false
         }),
-        handler: Box::new(|args, ctx| {
-            if let Some(err) = require_channel(ctx, "telegram") {
+        handler: tool_handler(|args, ctx| async move {
+            if let Some(err) = require_channel(&ctx, "telegram") {
                 return err;
             }
             let url = match require_str(&args, "url") {
@@ -249,11 +336,15 @@ fn telegram_send_photo() -> BuiltinTool {
                 .get("caption")
                 .and_then(|v| v.as_str())
                 .map(|s| s.to_string());
-            ToolInvokeResult::success(json!({
-                "queued": true,
-                "url": url,
-                "caption": caption
-            }))
+            let content = MessageContent::Media {
+                caption,
+                media_ref: url,
+                mime_type: None,
+            };
+            match conversation_outbound(&ctx, "telegram", content, None) {
+                Ok(message) => enqueue_outbound(&ctx, message),
+                Err(e) => e,
+            }
         }),
     }
 }
@@ -291,8 +382,8 @@ fn discord_add_reaction() -> BuiltinTool {
             "required": ["message_id", "emoji"],
             "additionalProperties": false
         }),
-        handler: Box::new(|args, ctx| {
-            if let Some(err) = require_channel(ctx, "discord") {
+        handler: tool_handler(|args, ctx| async move {
+            if let Some(err) = require_channel(&ctx, "discord") {
                 return err;
             }
             let message_id = match require_str(&args, "message_id") {
@@ -303,11 +394,15 @@ fn discord_add_reaction() -> BuiltinTool {
                 Ok(v) => v,
                 Err(e) => return e,
             };
-            ToolInvokeResult::success(json!({
-                "ok": true,
-                "message_id": message_id,
-                "emoji": emoji
-            }))
+            enqueue_action(
+                &ctx,
+                "discord",
+                String::new(),
+                ChannelAction::React {
+                    target_id: message_id,
+                    emoji,
+                },
+            )
         }),
     }
 }
@@ -351,19 +446,24 @@ fn discord_send_embed() -> BuiltinTool {
             },
             "additionalProperties": false
         }),
-        handler: Box::new(|args, ctx| {
-            if let Some(err) = require_channel(ctx, "discord") {
+        handler: tool_handler(|args, ctx| async move {
+            if let Some(err) = require_channel(&ctx, "discord") {
                 return err;
             }
-            let embed_fields = args
-                .get("fields")
-                .and_then(|v| v.as_array())
-                .map(|a| a.len())
-                .unwrap_or(0);
-            ToolInvokeResult::success(json!({
-                "queued": true,
-                "embed_fields": embed_fields
-            }))
+            let fallback = embed_fallback_text(&args);
+            if fallback.is_empty() {
+                return ToolInvokeResult::tool_error(
+                    "embed requires at least a title, description or field",
+                );
+            }
+            enqueue_action(
+                &ctx,
+                "discord",
+                fallback,
+                ChannelAction::Embed {
+                    embed: args.clone(),
+                },
+            )
         }),
     }
 }
@@ -391,8 +491,8 @@ fn discord_create_thread() -> BuiltinTool {
             "required": ["message_id", "name"],
             "additionalProperties": false
         }),
-        handler: Box::new(|args, ctx| {
-            if let Some(err) = require_channel(ctx, "discord") {
+        handler: tool_handler(|args, ctx| async move {
+            if let Some(err) = require_channel(&ctx, "discord") {
                 return err;
             }
             let message_id = match require_str(&args, "message_id") {
@@ -403,15 +503,20 @@ fn discord_create_thread() -> BuiltinTool {
                 Ok(v) => v,
                 Err(e) => return e,
             };
-            let _auto_archive = args
+            let auto_archive_minutes = args
                 .get("auto_archive_minutes")
-                .and_then(|v| v.as_i64())
-                .unwrap_or(1440);
-            ToolInvokeResult::success(json!({
-                "ok": true,
-                "message_id": message_id,
-                "thread_name": name
-            }))
+                .and_then(|v| v.as_u64())
+                .map(|v| v.min(u32::MAX as u64) as u32);
+            enqueue_action(
+                &ctx,
+                "discord",
+                String::new(),
+                ChannelAction::CreateThread {
+                    target_id: message_id,
+                    name,
+                    auto_archive_minutes,
+                },
+            )
         }),
     }
 }
@@ -435,8 +540,8 @@ fn discord_edit_message() -> BuiltinTool {
             "required": ["message_id", "content"],
             "additionalProperties": false
         }),
-        handler: Box::new(|args, ctx| {
-            if let Some(err) = require_channel(ctx, "discord") {
+        handler: tool_handler(|args, ctx| async move {
+            if let Some(err) = require_channel(&ctx, "discord") {
                 return err;
             }
             let message_id = match require_str(&args, "message_id") {
@@ -447,11 +552,14 @@ fn discord_edit_message() -> BuiltinTool {
                 Ok(v) => v,
                 Err(e) => return e,
             };
-            ToolInvokeResult::success(json!({
-                "ok": true,
-                "message_id": message_id,
-                "content": content
-            }))
+            enqueue_action(
+                &ctx,
+                "discord",
+                content,
+                ChannelAction::Edit {
+                    target_id: message_id,
+                },
+            )
         }),
     }
 }
@@ -471,18 +579,22 @@ fn discord_delete_message() -> BuiltinTool {
             "required": ["message_id"],
             "additionalProperties": false
         }),
-        handler: Box::new(|args, ctx| {
-            if let Some(err) = require_channel(ctx, "discord") {
+        handler: tool_handler(|args, ctx| async move {
+            if let Some(err) = require_channel(&ctx, "discord") {
                 return err;
             }
             let message_id = match require_str(&args, "message_id") {
                 Ok(v) => v,
                 Err(e) => return e,
             };
-            ToolInvokeResult::success(json!({
-                "ok": true,
-                "message_id": message_id
-            }))
+            enqueue_action(
+                &ctx,
+                "discord",
+                String::new(),
+                ChannelAction::Delete {
+                    target_id: message_id,
+                },
+            )
         }),
     }
 }
@@ -520,24 +632,28 @@ fn slack_send_blocks() -> BuiltinTool {
             "required": ["channel", "blocks"],
             "additionalProperties": false
         }),
-        handler: Box::new(|args, ctx| {
-            if let Some(err) = require_channel(ctx, "slack") {
+        handler: tool_handler(|args, ctx| async move {
+            if let Some(err) = require_channel(&ctx, "slack") {
                 return err;
             }
             let channel = match require_str(&args, "channel") {
                 Ok(v) => v,
                 Err(e) => return e,
             };
-            let blocks = match args.get("blocks").and_then(|v| v.as_array()) {
-                Some(arr) => arr,
+            let blocks = match args.get("blocks").filter(|v| v.is_array()) {
+                Some(arr) => arr.clone(),
                 None => return ToolInvokeResult::tool_error("missing required parameter: blocks"),
             };
-            let block_count = blocks.len();
-            ToolInvokeResult::success(json!({
-                "queued": true,
-                "channel": channel,
-                "block_count": block_count
-            }))
+            let fallback = blocks_fallback_text(&blocks);
+            enqueue_outbound(
+                &ctx,
+                outbound_to(
+                    "slack",
+                    channel,
+                    MessageContent::text(fallback),
+                    Some(ChannelAction::Blocks { blocks }),
+                ),
+            )
         }),
     }
 }
@@ -566,8 +682,8 @@ fn slack_send_ephemeral() -> BuiltinTool {
             "required": ["channel", "user", "text"],
             "additionalProperties": false
         }),
-        handler: Box::new(|args, ctx| {
-            if let Some(err) = require_channel(ctx, "slack") {
+        handler: tool_handler(|args, ctx| async move {
+            if let Some(err) = require_channel(&ctx, "slack") {
                 return err;
             }
             let channel = match require_str(&args, "channel") {
@@ -582,12 +698,15 @@ fn slack_send_ephemeral() -> BuiltinTool {
                 Ok(v) => v,
                 Err(e) => return e,
             };
-            ToolInvokeResult::success(json!({
-                "queued": true,
-                "channel": channel,
-                "user": user,
-                "text": text
-            }))
+            enqueue_outbound(
+                &ctx,
+                outbound_to(
+                    "slack",
+                    channel,
+                    MessageContent::text(text),
+                    Some(ChannelAction::Ephemeral { user }),
+                ),
+            )
         }),
     }
 }
@@ -615,8 +734,8 @@ fn slack_add_reaction() -> BuiltinTool {
             "required": ["channel", "timestamp", "emoji"],
             "additionalProperties": false
         }),
-        handler: Box::new(|args, ctx| {
-            if let Some(err) = require_channel(ctx, "slack") {
+        handler: tool_handler(|args, ctx| async move {
+            if let Some(err) = require_channel(&ctx, "slack") {
                 return err;
             }
             let channel = match require_str(&args, "channel") {
@@ -631,12 +750,18 @@ fn slack_add_reaction() -> BuiltinTool {
                 Ok(v) => v,
                 Err(e) => return e,
             };
-            ToolInvokeResult::success(json!({
-                "ok": true,
-                "channel": channel,
-                "timestamp": timestamp,
-                "emoji": emoji
-            }))
+            enqueue_outbound(
+                &ctx,
+                outbound_to(
+                    "slack",
+                    channel,
+                    MessageContent::text(""),
+                    Some(ChannelAction::React {
+                        target_id: timestamp,
+                        emoji,
+                    }),
+                ),
+            )
         }),
     }
 }
@@ -664,8 +789,8 @@ fn slack_update_message() -> BuiltinTool {
             "required": ["channel", "timestamp", "text"],
             "additionalProperties": false
         }),
-        handler: Box::new(|args, ctx| {
-            if let Some(err) = require_channel(ctx, "slack") {
+        handler: tool_handler(|args, ctx| async move {
+            if let Some(err) = require_channel(&ctx, "slack") {
                 return err;
             }
             let channel = match require_str(&args, "channel") {
@@ -680,12 +805,17 @@ fn slack_update_message() -> BuiltinTool {
                 Ok(v) => v,
                 Err(e) => return e,
             };
-            ToolInvokeResult::success(json!({
-                "ok": true,
-                "channel": channel,
-                "timestamp": timestamp,
-                "text": text
-            }))
+            enqueue_outbound(
+                &ctx,
+                outbound_to(
+                    "slack",
+                    channel,
+                    MessageContent::text(text),
+                    Some(ChannelAction::Edit {
+                        target_id: timestamp,
+                    }),
+                ),
+            )
         }),
     }
 }
@@ -709,8 +839,8 @@ fn slack_delete_message() -> BuiltinTool {
             "required": ["channel", "timestamp"],
             "additionalProperties": false
         }),
-        handler: Box::new(|args, ctx| {
-            if let Some(err) = require_channel(ctx, "slack") {
+        handler: tool_handler(|args, ctx| async move {
+            if let Some(err) = require_channel(&ctx, "slack") {
                 return err;
             }
             let channel = match require_str(&args, "channel") {
@@ -721,15 +851,60 @@ fn slack_delete_message() -> BuiltinTool {
                 Ok(v) => v,
                 Err(e) => return e,
             };
-            ToolInvokeResult::success(json!({
-                "ok": true,
-                "channel": channel,
-                "timestamp": timestamp
-            }))
+            enqueue_outbound(
+                &ctx,
+                outbound_to(
+                    "slack",
+                    channel,
+                    MessageContent::text(""),
+                    Some(ChannelAction::Delete {
+                        target_id: timestamp,
+                    }),
+                ),
+            )
         }),
     }
 }
 
+/// Render a plain-text fallback for a Discord embed.
+fn embed_fallback_text(args: &Value) -> String {
+    let mut lines = Vec::new();
+    for key in ["title", "description"] {
+        if let Some(s) = args.get(key).and_then(|v| v.as_str()) {
+            if !s.is_empty() {
+                lines.push(s.to_string());
+            }
+        }
+    }
+    if let Some(fields) = args.get("fields").and_then(|v| v.as_array()) {
+        for field in fields {
+            let name = field.get("name").and_then(|v| v.as_str()).unwrap_or("");
+            let value = field.get("value").and_then(|v| v.as_str()).unwrap_or("");
+            lines.push(format!("{name}: {value}"));
+        }
+    }
+    if let Some(footer) = args.get("footer").and_then(|v| v.as_str()) {
+        if !footer.is_empty() {
+            lines.push(footer.to_string());
+        }
+    }
+    lines.join("\n")
+}
+
+/// Render a plain-text fallback for Slack Block Kit blocks by collecting the
+/// text of section/header blocks.
+fn blocks_fallback_text(blocks: &Value) -> String {
+    let Some(blocks) = blocks.as_array() else {
+        return String::new();
+    };
+    blocks
+        .iter()
+        .filter_map(|block| block.get("text"))
+        .filter_map(|text| text.get("text").and_then(|v| v.as_str()))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
 // ===========================================================================
 // Tests
 // ===========================================================================
@@ -737,12 +912,17 @@ fn slack_delete_message() -> BuiltinTool {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::messages::outbound::{DeliveryStatus, MessageId, MessagePipeline, QueuedMessage};
     use serde_json::json;
+    use std::sync::Arc;
 
-    /// Create a test context with the given channel.
+    /// Create a test context with the given channel, a fresh pipeline and a
+    /// known originating recipient.
     fn ctx_for(channel: &str) -> ToolInvokeContext {
         ToolInvokeContext {
             message_channel: Some(channel.to_string()),
+            recipient_id: Some("chat-1".to_string()),
+            message_pipeline: Some(Arc::new(MessagePipeline::new())),
             ..Default::default()
         }
     }
@@ -767,6 +947,17 @@ mod tests {
         }
     }
 
+    /// Assert the tool queued a message and return the pipeline entry.
+    fn queued(ctx: &ToolInvokeContext, val: &Value) -> QueuedMessage {
+        assert_eq!(val["status"], "queued");
+        let id = val["message_id"].as_str().expect("message_id");
+        ctx.message_pipeline
+            .as_ref()
+            .unwrap()
+            .get_message(&MessageId::from_string(id))
+            .expect("message should be in the pipeline")
+    }
+
     // -----------------------------------------------------------------------
     // Gating tests
     // -----------------------------------------------------------------------
@@ -828,69 +1019,140 @@ mod tests {
         }
     }
 
+    // -----------------------------------------------------------------------
+    // Pipeline integration tests
+    // -----------------------------------------------------------------------
+
+    #[tokio::test]
+    async fn test_channel_tool_requires_pipeline() {
+        let tool = telegram_edit_message();
+        let ctx = ToolInvokeContext {
+            message_pipeline: None,
+            ..ctx_for("telegram")
+        };
+        assert_error((tool.handler)(json!({"message_id": "42", "text": "x"}), ctx).await);
+    }
+
+    #[tokio::test]
+    async fn test_channel_tool_requires_recipient() {
+        let tool = telegram_send_photo();
+        let ctx = ToolInvokeContext {
+            recipient_id: None,
+            ..ctx_for("telegram")
+        };
+        assert_error(
+            (tool.handler)(json!({"url": "https://example.com/a.jpg"}), ctx.clone()).await,
+        );
+        assert_eq!(ctx.message_pipeline.unwrap().total_queue_size(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_channel_tool_message_reaches_pipeline() {
+        let tool = telegram_edit_message();
+        let ctx = ctx_for("telegram");
+        let val = unwrap_success(
+            (tool.handler)(json!({"message_id": "42", "text": "edited"}), ctx.clone()).await,
+        );
+        let pipeline = ctx.message_pipeline.as_ref().unwrap();
+        assert_eq!(pipeline.queue_size("telegram"), 1);
+        let msg = queued(&ctx, &val);
+        assert_eq!(msg.status, DeliveryStatus::Queued);
+        assert_eq!(msg.context.source.as_deref(), Some("agent"));
+        assert_eq!(
+            pipeline.next_for_channel("telegram").unwrap().message.id,
+            msg.message.id
+        );
+    }
+
     // -----------------------------------------------------------------------
     // Telegram tool tests
     // -----------------------------------------------------------------------
 
-    #[test]
-    fn test_telegram_edit_message_success() {
+    #[tokio::test]
+    async fn test_telegram_edit_message_success() {
         let tool = telegram_edit_message();
         let ctx = ctx_for("telegram");
-        let result = (tool.handler)(json!({"message_id": "42", "text": "edited"}), &ctx);
+        let result =
+            (tool.handler)(json!({"message_id": "42", "text": "edited"}), ctx.clone()).await;
         let val = unwrap_success(result);
-        assert_eq!(val["ok"], true);
-        assert_eq!(val["message_id"], "42");
-        assert_eq!(val["text"], "edited");
+        assert_eq!(val["channel"], "telegram");
+        assert_eq!(val["action"], "edit");
+        let msg = queued(&ctx, &val);
+        assert_eq!(msg.message.metadata.recipient_id.as_deref(), Some("chat-1"));
+        assert_eq!(
+            msg.message.metadata.action,
+            Some(ChannelAction::Edit {
+                target_id: "42".to_string()
+            })
+        );
+        assert!(matches!(
+            msg.message.content,
+            MessageContent::Text { ref text } if text == "edited"
+        ));
     }
 
-    #[test]
-    fn test_telegram_edit_message_missing_params() {
+    #[tokio::test]
+    async fn test_telegram_edit_message_missing_params() {
         let tool = telegram_edit_message();
         let ctx = ctx_for("telegram");
-        assert_error((tool.handler)(json!({}), &ctx));
-        assert_error((tool.handler)(json!({"message_id": "42"}), &ctx));
-        assert_error((tool.handler)(json!({"text": "hello"}), &ctx));
+        assert_error((tool.handler)(json!({}), ctx.clone()).await);
+        assert_error((tool.handler)(json!({"message_id": "42"}), ctx.clone()).await);
+        assert_error((tool.handler)(json!({"text": "hello"}), ctx.clone()).await);
     }
 
-    #[test]
-    fn test_telegram_delete_message_success() {
+    #[tokio::test]
+    async fn test_telegram_delete_message_success() {
         let tool = telegram_delete_message();
         let ctx = ctx_for("telegram");
-        let result = (tool.handler)(json!({"message_id": "99"}), &ctx);
+        let result = (tool.handler)(json!({"message_id": "99"}), ctx.clone()).await;
         let val = unwrap_success(result);
-        assert_eq!(val["ok"], true);
-        assert_eq!(val["message_id"], "99");
+        let msg = queued(&ctx, &val);
+        assert_eq!(
+            msg.message.metadata.action,
+            Some(ChannelAction::Delete {
+                target_id: "99".to_string()
+            })
+        );
     }
 
-    #[test]
-    fn test_telegram_delete_message_missing_params() {
+    #[tokio::test]
+    async fn test_telegram_delete_message_missing_params() {
         let tool = telegram_delete_message();
         let ctx = ctx_for("telegram");
-        assert_error((tool.handler)(json!({}), &ctx));
+        assert_error((tool.handler)(json!({}), ctx).await);
     }
 
-    #[test]
-    fn test_telegram_pin_message_success() {
+    #[tokio::test]
+    async fn test_telegram_pin_message_success() {
         let tool = telegram_pin_message();
         let ctx = ctx_for("telegram");
-        let result = (tool.handler)(json!({"message_id": "10", "silent": true}), &ctx);
+        let result = (tool.handler)(json!({"message_id": "10", "silent": true}), ctx.clone()).await;
         let val = unwrap_success(result);
-        assert_eq!(val["ok"], true);
-        assert_eq!(val["message_id"], "10");
-        assert_eq!(val["silent"], true);
+        let msg = queued(&ctx, &val);
+        assert_eq!(
+            msg.message.metadata.action,
+            Some(ChannelAction::Pin {
+                target_id: "10".to_string(),
+                silent: true
+            })
+        );
     }
 
-    #[test]
-    fn test_telegram_pin_message_default_silent() {
+    #[tokio::test]
+    async fn test_telegram_pin_message_default_silent() {
         let tool = telegram_pin_message();
         let ctx = ctx_for("telegram");
-        let result = (tool.handler)(json!({"message_id": "10"}), &ctx);
+        let result = (tool.handler)(json!({"message_id": "10"}), ctx.clone()).await;
         let val = unwrap_success(result);
-        assert_eq!(val["silent"], false);
+        let msg = queued(&ctx, &val);
+        assert!(matches!(
+            msg.message.metadata.action,
+            Some(ChannelAction::Pin { silent: false, .. })
+        ));
     }
 
-    #[test]
-    fn test_telegram_reply_markup_success() {
+    #[tokio::test]
+    async fn test_telegram_reply_markup_success() {
         let tool = telegram_reply_markup();
         let ctx = ctx_for("telegram");
         let result = (tool.handler)(
@@ -901,77 +1163,106 @@ mod tests {
                     {"text": "Visit", "url": "https://example.com"}
                 ]
             }),
-            &ctx,
-        );
+            ctx.clone(),
+        )
+        .await;
         let val = unwrap_success(result);
-        assert_eq!(val["ok"], true);
-        assert_eq!(val["message_id"], "5");
-        assert_eq!(val["button_count"], 2);
+        let msg = queued(&ctx, &val);
+        match msg.message.metadata.action {
+            Some(ChannelAction::ReplyMarkup { target_id, buttons }) => {
+                assert_eq!(target_id, "5");
+                assert_eq!(buttons.as_array().unwrap().len(), 2);
+            }
+            other => panic!("expected reply markup action, got {other:?}"),
+        }
     }
 
-    #[test]
-    fn test_telegram_reply_markup_missing_buttons() {
+    #[tokio::test]
+    async fn test_telegram_reply_markup_missing_buttons() {
         let tool = telegram_reply_markup();
         let ctx = ctx_for("telegram");
-        assert_error((tool.handler)(json!({"message_id": "5"}), &ctx));
+        assert_error((tool.handler)(json!({"message_id": "5"}), ctx).await);
     }
 
-    #[test]
-    fn test_telegram_send_photo_success() {
+    #[tokio::test]
+    async fn test_telegram_send_photo_success() {
         let tool = telegram_send_photo();
         let ctx = ctx_for("telegram");
         let result = (tool.handler)(
             json!({"url": "https://img.example.com/a.jpg", "caption": "A photo"}),
-            &ctx,
-        );
+            ctx.clone(),
+        )
+        .await;
         let val = unwrap_success(result);
-        assert_eq!(val["queued"], true);
-        assert_eq!(val["url"], "https://img.example.com/a.jpg");
-        assert_eq!(val["caption"], "A photo");
+        assert!(val["action"].is_null());
+        let msg = queued(&ctx, &val);
+        match msg.message.content {
+            MessageContent::Media {
+                caption, media_ref, ..
+            } => {
+                assert_eq!(media_ref, "https://img.example.com/a.jpg");
+                assert_eq!(caption.as_deref(), Some("A photo"));
+            }
+            other => panic!("expected media content, got {other:?}"),
+        }
     }
 
-    #[test]
-    fn test_telegram_send_photo_no_caption() {
+    #[tokio::test]
+    async fn test_telegram_send_photo_no_caption() {
         let tool = telegram_send_photo();
         let ctx = ctx_for("telegram");
-        let result = (tool.handler)(json!({"url": "https://img.example.com/a.jpg"}), &ctx);
+        let result =
+            (tool.handler)(json!({"url": "https://img.example.com/a.jpg"}), ctx.clone()).await;
         let val = unwrap_success(result);
-        assert_eq!(val["queued"], true);
-        assert!(val["caption"].is_null());
+        let msg = queued(&ctx, &val);
+        assert!(matches!(
+            msg.message.content,
+            MessageContent::Media { caption: None, .. }
+        ));
     }
 
-    #[test]
-    fn test_telegram_send_photo_missing_url() {
+    #[tokio::test]
+    async fn test_telegram_send_photo_missing_url() {
         let tool = telegram_send_photo();
         let ctx = ctx_for("telegram");
-        assert_error((tool.handler)(json!({}), &ctx));
+        assert_error((tool.handler)(json!({}), ctx).await);
     }
 
     // -----------------------------------------------------------------------
     // Discord tool tests
     // -----------------------------------------------------------------------
 
-    #[test]
-    fn test_discord_add_reaction_success() {
+    #[tokio::test]
+    async fn test_discord_add_reaction_success() {
         let tool = discord_add_reaction();
         let ctx = ctx_for("discord");
-        let result = (tool.handler)(json!({"message_id": "1001", "emoji": "thumbsup"}), &ctx);
+        let result = (tool.handler)(
+            json!({"message_id": "1001", "emoji": "thumbsup"}),
+            ctx.clone(),
+        )
+        .await;
         let val = unwrap_success(result);
-        assert_eq!(val["ok"], true);
-        assert_eq!(val["message_id"], "1001");
-        assert_eq!(val["emoji"], "thumbsup");
+        let msg = queued(&ctx, &val);
+        assert_eq!(msg.message.channel_id, "discord");
+        assert_eq!(
+            msg.message.metadata.action,
+            Some(ChannelAction::React {
+                target_id: "1001".to_string(),
+                emoji: "thumbsup".to_string()
+            })
+        );
     }
 
-    #[test]
-    fn test_discord_add_reaction_missing_params() {
+    #[tokio::test]
+    async fn test_discord_add_reaction_missing_params() {
         let tool = discord_add_reaction();
         let ctx = ctx_for("discord");
-        assert_error((tool.handler)(json!({}), &ctx));
-        assert_error((tool.handler)(json!({"message_id": "1001"}), &ctx));
+        assert_error((tool.handler)(json!({}), ctx.clone()).await);
+        assert_error((tool.handler)(json!({"message_id": "1001"}), ctx.clone()).await);
     }
 
-    #[test]
-    fn test_discord_send_embed_success() {
+    #[tokio::test]
+    async fn test_discord_send_embed_success() {
         let tool = discord_send_embed();
         let ctx = ctx_for("discord");
         let result = (tool.handler)(
@@ -982,69 +1273,114 @@ mod tests {
                 "fields": [{"name": "f1", "value": "v1"}],
                 "footer": "foot"
             }),
-            &ctx,
-        );
+            ctx.clone(),
+        )
+        .await;
         let val = unwrap_success(result);
-        assert_eq!(val["queued"], true);
-        assert_eq!(val["embed_fields"], 1);
+        let msg = queued(&ctx, &val);
+        assert!(matches!(
+            msg.message.content,
+            MessageContent::Text { ref text } if text == "Hello\nWorld\nf1: v1\nfoot"
+        ));
+        match msg.message.metadata.action {
+            Some(ChannelAction::Embed { embed }) => assert_eq!(embed["color"], 0xFF0000),
+            other => panic!("expected embed action, got {other:?}"),
+        }
     }
 
-    #[test]
-    fn test_discord_send_embed_no_fields() {
+    #[tokio::test]
+    async fn test_discord_send_embed_no_fields() {
         let tool = discord_send_embed();
         let ctx = ctx_for("discord");
-        let result = (tool.handler)(json!({"title": "Hello"}), &ctx);
+        let result = (tool.handler)(json!({"title": "Hello"}), ctx.clone()).await;
         let val = unwrap_success(result);
-        assert_eq!(val["queued"], true);
-        assert_eq!(val["embed_fields"], 0);
+        let msg = queued(&ctx, &val);
+        assert!(matches!(
+            msg.message.content,
+            MessageContent::Text { ref text } if text == "Hello"
+        ));
     }
 
-    #[test]
-    fn test_discord_create_thread_success() {
+    #[tokio::test]
+    async fn test_discord_send_embed_empty_rejected() {
+        let tool = discord_send_embed();
+        let ctx = ctx_for("discord");
+        assert_error((tool.handler)(json!({"color": 1}), ctx).await);
+    }
+
+    #[tokio::test]
+    async fn test_discord_create_thread_success() {
         let tool = discord_create_thread();
         let ctx = ctx_for("discord");
-        let result = (tool.handler)(json!({"message_id": "200", "name": "my-thread"}), &ctx);
+        let result = (tool.handler)(
+            json!({"message_id": "200", "name": "my-thread"}),
+            ctx.clone(),
+        )
+        .await;
         let val = unwrap_success(result);
-        assert_eq!(val["ok"], true);
-        assert_eq!(val["message_id"], "200");
-        assert_eq!(val["thread_name"], "my-thread");
+        let msg = queued(&ctx, &val);
+        assert_eq!(
+            msg.message.metadata.action,
+            Some(ChannelAction::CreateThread {
+                target_id: "200".to_string(),
+                name: "my-thread".to_string(),
+                auto_archive_minutes: None,
+            })
+        );
     }
 
-    #[test]
-    fn test_discord_create_thread_missing_params() {
+    #[tokio::test]
+    async fn test_discord_create_thread_missing_params() {
         let tool = discord_create_thread();
         let ctx = ctx_for("discord");
-        assert_error((tool.handler)(json!({}), &ctx));
-        assert_error((tool.handler)(json!({"message_id": "200"}), &ctx));
+        assert_error((tool.handler)(json!({}), ctx.clone()).await);
+        assert_error((tool.handler)(json!({"message_id": "200"}), ctx.clone()).await);
     }
 
-    #[test]
-    fn test_discord_edit_message_success() {
+    #[tokio::test]
+    async fn test_discord_edit_message_success() {
         let tool = discord_edit_message();
         let ctx = ctx_for("discord");
-        let result = (tool.handler)(json!({"message_id": "300", "content": "new content"}), &ctx);
+        let result = (tool.handler)(
+            json!({"message_id": "300", "content": "new content"}),
+            ctx.clone(),
+        )
+        .await;
         let val = unwrap_success(result);
-        assert_eq!(val["ok"], true);
-        assert_eq!(val["message_id"], "300");
-        assert_eq!(val["content"], "new content");
+        let msg = queued(&ctx, &val);
+        assert_eq!(
+            msg.message.metadata.action,
+            Some(ChannelAction::Edit {
+                target_id: "300".to_string()
+            })
+        );
+        assert!(matches!(
+            msg.message.content,
+            MessageContent::Text { ref text } if text == "new content"
+        ));
     }
 
-    #[test]
-    fn test_discord_delete_message_success() {
+    #[tokio::test]
+    async fn test_discord_delete_message_success() {
         let tool = discord_delete_message();
         let ctx = ctx_for("discord");
-        let result = (tool.handler)(json!({"message_id": "400"}), &ctx);
+        let result = (tool.handler)(json!({"message_id": "400"}), ctx.clone()).await;
         let val = unwrap_success(result);
-        assert_eq!(val["ok"], true);
-        assert_eq!(val["message_id"], "400");
+        let msg = queued(&ctx, &val);
+        assert_eq!(
+            msg.message.metadata.action,
+            Some(ChannelAction::Delete {
+                target_id: "400".to_string()
+            })
+        );
     }
 
     // -----------------------------------------------------------------------
     // Slack tool tests
     // -----------------------------------------------------------------------
 
-    #[test]
-    fn test_slack_send_blocks_success() {
+    #[tokio::test]
+    async fn test_slack_send_blocks_success() {
         let tool = slack_send_blocks();
         let ctx = ctx_for("slack");
         let result = (tool.handler)(
@@ -1052,126 +1388,158 @@ mod tests {
                 "channel": "C123",
                 "blocks": [{"type": "section", "text": {"type": "mrkdwn", "text": "hi"}}]
             }),
-            &ctx,
-        );
+            ctx.clone(),
+        )
+        .await;
         let val = unwrap_success(result);
-        assert_eq!(val["queued"], true);
-        assert_eq!(val["channel"], "C123");
-        assert_eq!(val["block_count"], 1);
+        assert_eq!(val["channel"], "slack");
+        let msg = queued(&ctx, &val);
+        assert_eq!(msg.message.metadata.recipient_id.as_deref(), Some("C123"));
+        assert!(matches!(
+            msg.message.content,
+            MessageContent::Text { ref text } if text == "hi"
+        ));
+        match msg.message.metadata.action {
+            Some(ChannelAction::Blocks { blocks }) => {
+                assert_eq!(blocks.as_array().unwrap().len(), 1)
+            }
+            other => panic!("expected blocks action, got {other:?}"),
+        }
     }
 
-    #[test]
-    fn test_slack_send_blocks_missing_params() {
+    #[tokio::test]
+    async fn test_slack_send_blocks_missing_params() {
         let tool = slack_send_blocks();
         let ctx = ctx_for("slack");
-        assert_error((tool.handler)(json!({}), &ctx));
-        assert_error((tool.handler)(json!({"channel": "C123"}), &ctx));
+        assert_error((tool.handler)(json!({}), ctx.clone()).await);
+        assert_error((tool.handler)(json!({"channel": "C123"}), ctx.clone()).await);
     }
 
-    #[test]
-    fn test_slack_send_ephemeral_success() {
+    #[tokio::test]
+    async fn test_slack_send_ephemeral_success() {
         let tool = slack_send_ephemeral();
         let ctx = ctx_for("slack");
         let result = (tool.handler)(
             json!({"channel": "C123", "user": "U456", "text": "secret message"}),
-            &ctx,
-        );
+            ctx.clone(),
+        )
+        .await;
         let val = unwrap_success(result);
-        assert_eq!(val["queued"], true);
-        assert_eq!(val["channel"], "C123");
-        assert_eq!(val["user"], "U456");
-        assert_eq!(val["text"], "secret message");
+        let msg = queued(&ctx, &val);
+        assert_eq!(msg.message.metadata.recipient_id.as_deref(), Some("C123"));
+        assert_eq!(
+            msg.message.metadata.action,
+            Some(ChannelAction::Ephemeral {
+                user: "U456".to_string()
+            })
+        );
     }
 
-    #[test]
-    fn test_slack_send_ephemeral_missing_params() {
+    #[tokio::test]
+    async fn test_slack_send_ephemeral_missing_params() {
         let tool = slack_send_ephemeral();
         let ctx = ctx_for("slack");
-        assert_error((tool.handler)(json!({}), &ctx));
-        assert_error((tool.handler)(json!({"channel": "C123"}), &ctx));
-        assert_error((tool.handler)(
-            json!({"channel": "C123", "user": "U456"}),
-            &ctx,
-        ));
+        assert_error((tool.handler)(json!({}), ctx.clone()).await);
+        assert_error((tool.handler)(json!({"channel": "C123"}), ctx.clone()).await);
+        assert_error((tool.handler)(json!({"channel": "C123", "user": "U456"}), ctx.clone()).await);
     }
 
-    #[test]
-    fn test_slack_add_reaction_success() {
+    #[tokio::test]
+    async fn test_slack_add_reaction_success() {
         let tool = slack_add_reaction();
         let ctx = ctx_for("slack");
         let result = (tool.handler)(
             json!({"channel": "C123", "timestamp": "1234567890.123456", "emoji": "thumbsup"}),
-            &ctx,
-        );
+            ctx.clone(),
+        )
+        .await;
         let val = unwrap_success(result);
-        assert_eq!(val["ok"], true);
-        assert_eq!(val["channel"], "C123");
-        assert_eq!(val["timestamp"], "1234567890.123456");
-        assert_eq!(val["emoji"], "thumbsup");
+        let msg = queued(&ctx, &val);
+        assert_eq!(msg.message.metadata.recipient_id.as_deref(), Some("C123"));
+        assert_eq!(
+            msg.message.metadata.action,
+            Some(ChannelAction::React {
+                target_id: "1234567890.123456".to_string(),
+                emoji: "thumbsup".to_string()
+            })
+        );
     }
 
-    #[test]
-    fn test_slack_update_message_success() {
+    #[tokio::test]
+    async fn test_slack_update_message_success() {
         let tool = slack_update_message();
         let ctx = ctx_for("slack");
         let result = (tool.handler)(
             json!({"channel": "C123", "timestamp": "1234567890.123456", "text": "updated"}),
-            &ctx,
-        );
+            ctx.clone(),
+        )
+        .await;
         let val = unwrap_success(result);
-        assert_eq!(val["ok"], true);
-        assert_eq!(val["channel"], "C123");
-        assert_eq!(val["timestamp"], "1234567890.123456");
-        assert_eq!(val["text"], "updated");
+        let msg = queued(&ctx, &val);
+        assert_eq!(
+            msg.message.metadata.action,
+            Some(ChannelAction::Edit {
+                target_id: "1234567890.123456".to_string()
+            })
+        );
+        assert!(matches!(
+            msg.message.content,
+            MessageContent::Text { ref text } if text == "updated"
+        ));
     }
 
-    #[test]
-    fn test_slack_delete_message_success() {
+    #[tokio::test]
+    async fn test_slack_delete_message_success() {
         let tool = slack_delete_message();
         let ctx = ctx_for("slack");
         let result = (tool.handler)(
             json!({"channel": "C123", "timestamp": "1234567890.123456"}),
-            &ctx,
-        );
+            ctx.clone(),
+        )
+        .await;
         let val = unwrap_success(result);
-        assert_eq!(val["ok"], true);
-        assert_eq!(val["channel"], "C123");
-        assert_eq!(val["timestamp"], "1234567890.123456");
+        let msg = queued(&ctx, &val);
+        assert_eq!(
+            msg.message.metadata.action,
+            Some(ChannelAction::Delete {
+                target_id: "1234567890.123456".to_string()
+            })
+        );
     }
 
-    #[test]
-    fn test_slack_delete_message_missing_params() {
+    #[tokio::test]
+    async fn test_slack_delete_message_missing_params() {
         let tool = slack_delete_message();
         let ctx = ctx_for("slack");
-        assert_error((tool.handler)(json!({}), &ctx));
-        assert_error((tool.handler)(json!({"channel": "C123"}), &ctx));
+        assert_error((tool.handler)(json!({}), ctx.clone()).await);
+        assert_error((tool.handler)(json!({"channel": "C123"}), ctx.clone()).await);
     }
 
     // -----------------------------------------------------------------------
     // Channel mismatch (defense in depth) tests
     // -----------------------------------------------------------------------
 
-    #[test]
-    fn test_telegram_tool_rejects_discord_channel() {
+    #[tokio::test]
+    async fn test_telegram_tool_rejects_discord_channel() {
         let tool = telegram_edit_message();
         let ctx = ctx_for("discord");
-        let result = (tool.handler)(json!({"message_id": "1", "text": "x"}), &ctx);
+        let result = (tool.handler)(json!({"message_id": "1", "text": "x"}), ctx).await;
         assert_error(result);
     }
 
-    #[test]
-    fn test_discord_tool_rejects_telegram_channel() {
+    #[tokio::test]
+    async fn test_discord_tool_rejects_telegram_channel() {
         let tool = discord_add_reaction();
         let ctx = ctx_for("telegram");
-        let result = (tool.handler)(json!({"message_id": "1", "emoji": "x"}), &ctx);
+        let result = (tool.handler)(json!({"message_id": "1", "emoji": "x"}), ctx).await;
         assert_error(result);
     }
 
-    #[test]
-    fn test_slack_tool_rejects_no_channel() {
+    #[tokio::test]
+    async fn test_slack_tool_rejects_no_channel() {
         let tool = slack_send_blocks();
         let ctx = ToolInvokeContext::default(); // message_channel is None
-        let result = (tool.handler)(json!({"channel": "C1", "blocks": []}), &ctx);
+        let result = (tool.handler)(json!({"channel": "C1", "blocks": []}), ctx).await;
         assert_error(result);
     }
 }
@@ -0,0 +1,628 @@
+//! Session compaction with LLM-generated summaries.
+//!
+//! Once a session's history crosses its compaction threshold, the older part
+//! of the conversation is replaced by a structured summary written by the
+//! agent's model (or a cheaper, dedicated `compaction.model`). The span is
+//! summarized first and then swapped out with
+//! [`SessionStore::compact_session_through`](crate::sessions::SessionStore::compact_session_through),
+//! so messages appended while the model is working are kept.
+//!
+//! Plugins observe compaction through the `before_compaction` hook (which
+//! may cancel it) and the `after_compaction` hook.
+
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::agent::provider::{CompletionRequest, ContentBlock, LlmMessage, LlmRole, StreamEvent};
+use crate::agent::{AgentError, LlmProvider};
+use crate::plugins::hook_utils;
+use crate::server::ws::WsServerState;
+use crate::sessions::{ChatMessage, CompactionMetadata, MessageRole, Session};
+
+/// Upper bound on a single summarization call.
+const SUMMARY_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Maximum characters of a single message included in the transcript.
+const MAX_MESSAGE_CHARS: usize = 4_000;
+
+/// Maximum characters of transcript sent to the summarizer. Older messages
+/// are dropped first; a leading earlier summary is always kept.
+const MAX_TRANSCRIPT_CHARS: usize = 200_000;
+
+/// Messages quoted in the fallback summary when no model is available.
+const FALLBACK_EXCERPT_MESSAGES: usize = 20;
+
+/// Characters per message quoted in the fallback summary.
+const FALLBACK_EXCERPT_CHARS: usize = 200;
+
+// ---------------------------------------------------------------------------
+// Configuration
+// ---------------------------------------------------------------------------
+
+/// Compaction configuration (`agents.defaults.compaction` or per agent).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompactionConfig {
+    /// Compact automatically after a run once the session crosses its
+    /// threshold.  Default: `true`.
+    #[serde(default = "default_auto")]
+    pub auto: bool,
+
+    /// Model used to write summaries (e.g. a cheaper model than the agent's).
+    /// If empty, uses the agent's model.
+    #[serde(default)]
+    pub model: String,
+
+    /// Number of most recent messages kept verbatim.  Default: `20`.
+    #[serde(default = "default_keep_recent")]
+    pub keep_recent: usize,
+
+    /// Message count that triggers automatic compaction.  Defaults to the
+    /// session store threshold.
+    #[serde(default)]
+    pub threshold: Option<usize>,
+
+    /// Maximum output tokens for a summary.  Default: `2048`.
+    #[serde(default = "default_max_tokens")]
+    pub max_tokens: u32,
+}
+
+fn default_auto() -> bool {
+    true
+}
+
+fn default_keep_recent() -> usize {
+    20
+}
+
+fn default_max_tokens() -> u32 {
+    2048
+}
+
+impl Default for CompactionConfig {
+    fn default() -> Self {
+        Self {
+            auto: default_auto(),
+            model: String::new(),
+            keep_recent: default_keep_recent(),
+            threshold: None,
+            max_tokens: default_max_tokens(),
+        }
+    }
+}
+
+impl CompactionConfig {
+    /// The model used for summaries, falling back to `agent_model`.
+    pub fn summary_model<'a>(&'a self, agent_model: &'a str) -> &'a str {
+        if self.model.trim().is_empty() {
+            agent_model
+        } else {
+            &self.model
+        }
+    }
+}
+
+/// What started a compaction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompactionTrigger {
+    /// `sessions.compact` request
+    Manual,
+    /// Session crossed its threshold after an agent run
+    Auto,
+}
+
+impl CompactionTrigger {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Manual => "manual",
+            Self::Auto => "auto",
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Summarization
+// ---------------------------------------------------------------------------
+
+const COMPACTION_SYSTEM_PROMPT: &str = r#"You compress conversation history for an AI assistant. The transcript below will be deleted and replaced by your summary, so the assistant must be able to continue the conversation from the summary alone.
+
+Write a concise summary using these sections, omitting any that would be empty:
+
+## Context
+Who the user is and what the conversation is about.
+
+## Decisions
+Conclusions reached and choices made, with the reasoning when it matters.
+
+## Facts and preferences
+Names, identifiers, file paths, URLs, numbers, and user preferences worth remembering. Quote them exactly.
+
+## Tool results
+Outcomes of tool calls the assistant may need again.
+
+## Open tasks
+Unfinished requests, promised follow-ups, and unanswered questions.
+
+If the transcript starts with an earlier summary, merge it into yours. Do not invent details. Respond with the summary only."#;
+
+/// Summarize `messages` with `model`.
+pub async fn summarize_messages(
+    messages: &[ChatMessage],
+    provider: &dyn LlmProvider,
+    model: &str,
+    max_tokens: u32,
+) -> Result<String, AgentError> {
+    let request = CompletionRequest {
+        model: model.to_string(),
+        messages: vec![LlmMessage {
+            role: LlmRole::User,
+            content: vec![ContentBlock::Text {
+                text: format_transcript(messages),
+            }],
+        }],
+        system: Some(COMPACTION_SYSTEM_PROMPT.to_string()),
+        tools: vec![],
+        max_tokens,
+        temperature: Some(0.0),
+        thinking: None,
+        extra: None,
+    };
+
+    let collect = async {
+        let mut rx = provider
+            .complete(request, tokio_util::sync::CancellationToken::new())
+            .await?;
+        let mut text = String::new();
+        while let Some(event) = rx.recv().await {
+            match event {
+                StreamEvent::TextDelta { text: delta } => text.push_str(&delta),
+                StreamEvent::Stop { .. } => break,
+                StreamEvent::Error { message } => return Err(AgentError::Provider(message)),
+                _ => {}
+            }
+        }
+        Ok(text)
+    };
+
+    let text = tokio::time::timeout(SUMMARY_TIMEOUT, collect)
+        .await
+        .map_err(|_| {
+            AgentError::Provider(format!(
+                "compaction summary timed out after {}s",
+                SUMMARY_TIMEOUT.as_secs()
+            ))
+        })??;
+
+    let text = text.trim();
+    if text.is_empty() {
+        return Err(AgentError::Provider(
+            "compaction summary was empty".to_string(),
+        ));
+    }
+    Ok(format!(
+        "Summary of {} earlier messages:\n\n{}",
+        messages.len(),
+        text
+    ))
+}
+
+/// Render messages as a plain-text transcript for the summarizer.
+fn format_transcript(messages: &[ChatMessage]) -> String {
+    let lines: Vec<String> = messages.iter().map(transcript_line).collect();
+
+    // Keep a leading earlier summary, then as many recent lines as fit
+    let (head, rest) = match messages.first() {
+        Some(first) if first.role == MessageRole::System => (lines.first(), &lines[1..]),
+        _ => (None, &lines[..]),
+    };
+    let mut budget = MAX_TRANSCRIPT_CHARS.saturating_sub(head.map_or(0, |h| h.len()));
+    let mut start = rest.len();
+    while start > 0 && rest[start - 1].len() <= budget {
+        budget -= rest[start - 1].len();
+        start -= 1;
+    }
+
+    let mut out = String::new();
+    if let Some(head) = head {
+        out.push_str(head);
+    }
+    if start > 0 {
+        out.push_str(&format!("[{start} older messages omitted]\n\n"));
+    }
+    for line in &rest[start..] {
+        out.push_str(line);
+    }
+    out
+}
+
+fn transcript_line(msg: &ChatMessage) -> String {
+    let label = match (&msg.role, msg.tool_name.as_deref()) {
+        (MessageRole::Tool, Some(tool)) => format!("tool result ({tool})"),
+        (MessageRole::System, _) => "earlier summary".to_string(),
+        (role, _) => role.to_string(),
+    };
+    format!(
+        "[{label}]\n{}\n\n",
+        truncate_chars(&msg.content, MAX_MESSAGE_CHARS)
+    )
+}
+
+fn truncate_chars(text: &str, max: usize) -> String {
+    match text.char_indices().nth(max) {
+        Some((idx, _)) => format!("{}…", &text[..idx]),
+        None => text.to_string(),
+    }
+}
+
+/// Extractive summary used when no LLM provider is configured.
+///
+/// Quotes the start of the most recent user and assistant messages so the
+/// gist of the conversation survives compaction.
+pub fn fallback_summary(messages: &[ChatMessage]) -> String {
+    let excerpts: Vec<String> = messages
+        .iter()
+        .filter(|m| matches!(m.role, MessageRole::User | MessageRole::Assistant))
+        .filter(|m| !m.content.trim().is_empty())
+        .rev()
+        .take(FALLBACK_EXCERPT_MESSAGES)
+        .map(|m| {
+            format!(
+                "- {}: {}",
+                m.role,
+                truncate_chars(m.content.trim(), FALLBACK_EXCERPT_CHARS).replace('\n', " ")
+            )
+        })
+        .collect();
+
+    let mut summary = format!("Compacted {} messages.", messages.len());
+    if !excerpts.is_empty() {
+        summary.push_str("\n\nMost recent excerpts:\n");
+        summary.push_str(&excerpts.into_iter().rev().collect::<Vec<_>>().join("\n"));
+    }
+    summary
+}
+
+// ---------------------------------------------------------------------------
+// Compaction
+// ---------------------------------------------------------------------------
+
+fn dispatch_hook(state: &WsServerState, hook_name: &str, payload: &Value) -> bool {
+    let Some(registry) = state.plugin_registry() else {
+        return false;
+    };
+    hook_utils::dispatch_hook(registry.clone(), hook_name, payload).is_some_and(|r| r.cancelled)
+}
+
+/// Compact `session`, keeping its `keep_recent` most recent messages.
+///
+/// The summary is written by `provider` using `model`; without a provider an
+/// extractive [`fallback_summary`] is stored instead. Returns `None` when
+/// there is nothing to compact or a `before_compaction` hook cancelled it.
+#[allow(clippy::too_many_arguments)]
+pub async fn compact_session(
+    state: &WsServerState,
+    session: &Session,
+    provider: Option<&dyn LlmProvider>,
+    model: &str,
+    max_tokens: u32,
+    keep_recent: usize,
+    trigger: CompactionTrigger,
+) -> Result<Option<CompactionMetadata>, AgentError> {
+    let store = state.session_store();
+    let history = store
+        .get_history(&session.id, None, None)
+        .map_err(|e| AgentError::SessionStore(e.to_string()))?;
+    if history.len() <= keep_recent {
+        return Ok(None);
+    }
+    let span = &history[..history.len() - keep_recent];
+    let Some(last) = span.last() else {
+        return Ok(None);
+    };
+
+    let cancelled = dispatch_hook(
+        state,
+        "before_compaction",
+        &json!({
+            "sessionKey": &session.session_key,
+            "sessionId": &session.id,
+            "trigger": trigger.as_str(),
+            "messageCount": history.len(),
+            "compactCount": span.len(),
+            "keepRecent": keep_recent,
+            "model": model,
+        }),
+    );
+    if cancelled {
+        tracing::info!(session_key = %session.session_key, "compaction cancelled by plugin hook");
+        return Ok(None);
+    }
+
+    let summary = match provider {
+        Some(provider) => summarize_messages(span, provider, model, max_tokens).await?,
+        None => fallback_summary(span),
+    };
+
+    let metadata = store
+        .compact_session_through(&session.id, &last.id, summary)
+        .map_err(|e| AgentError::SessionStore(e.to_string()))?;
+
+    dispatch_hook(
+        state,
+        "after_compaction",
+        &json!({
+            "sessionKey": &session.session_key,
+            "sessionId": &session.id,
+            "trigger": trigger.as_str(),
+            "messageCount": history.len() - span.len() + 1,
+            "compactedCount": span.len(),
+            "compactionCount": metadata.compaction_count,
+            "summary": &metadata.last_summary,
+        }),
+    );
+
+    Ok(Some(metadata))
+}
+
+/// Compact the session at `session_key` if it crossed its threshold.
+///
+/// Failures are logged and leave the history untouched.
+pub async fn auto_compact_if_needed(
+    state: &WsServerState,
+    session_key: &str,
+    provider: &dyn LlmProvider,
+    agent_model: &str,
+    config: &CompactionConfig,
+) -> Option<CompactionMetadata> {
+    if !config.auto {
+        return None;
+    }
+    let store = state.session_store();
+    let session = store.get_session_by_key(session_key).ok()?;
+    let threshold = config
+        .threshold
+        .unwrap_or_else(|| store.compact_threshold())
+        .max(1);
+    if session.message_count < threshold {
+        return None;
+    }
+
+    let model = config.summary_model(agent_model);
+    match compact_session(
+        state,
+        &session,
+        Some(provider),
+        model,
+        config.max_tokens,
+        config.keep_recent,
+        CompactionTrigger::Auto,
+    )
+    .await
+    {
+        Ok(metadata) => metadata,
+        Err(e) => {
+            tracing::warn!(
+                session_key = %session_key,
+                model = %model,
+                error = %e,
+                "automatic session compaction failed"
+            );
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::provider::{StopReason, TokenUsage};
+    use crate::server::ws::WsServerConfig;
+    use crate::sessions::{SessionMetadata, SessionStore};
+    use async_trait::async_trait;
+    use std::sync::Arc;
+    use tokio::sync::mpsc;
+
+    struct SummaryProvider {
+        reply: Result<String, String>,
+        requests: parking_lot::Mutex<Vec<CompletionRequest>>,
+    }
+
+    impl SummaryProvider {
+        fn new(reply: Result<&str, &str>) -> Self {
+            Self {
+                reply: reply.map(String::from).map_err(String::from),
+                requests: parking_lot::Mutex::new(Vec::new()),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl LlmProvider for SummaryProvider {
+        async fn complete(
+            &self,
+            request: CompletionRequest,
+            _cancel_token: tokio_util::sync::CancellationToken,
+        ) -> Result<mpsc::Receiver<StreamEvent>, AgentError> {
+            self.requests.lock().push(request);
+            let event = match &self.reply {
+                Ok(text) => StreamEvent::TextDelta { text: text.clone() },
+                Err(message) => StreamEvent::Error {
+                    message: message.clone(),
+                },
+            };
+            let (tx, rx) = mpsc::channel(4);
+            tokio::spawn(async move {
+                let _ = tx.send(event).await;
+                let _ = tx
+                    .send(StreamEvent::Stop {
+                        reason: StopReason::EndTurn,
+                        usage: TokenUsage::default(),
+                    })
+                    .await;
+            });
+            Ok(rx)
+        }
+    }
+
+    fn make_state(threshold: usize) -> (WsServerState, Session, tempfile::TempDir) {
+        let tmp = tempfile::tempdir().unwrap();
+        let store = Arc::new(
+            SessionStore::with_base_path(tmp.path().join("sessions"))
+                .with_compact_threshold(threshold),
+        );
+        let session = store
+            .get_or_create_session("agent:main:test", SessionMetadata::default())
+            .unwrap();
+        for i in 0..6 {
+            store
+                .append_message(ChatMessage::user(&session.id, format!("question {i}")))
+                .unwrap();
+            store
+                .append_message(ChatMessage::assistant(&session.id, format!("answer {i}")))
+                .unwrap();
+        }
+        let state = WsServerState::new(WsServerConfig::default()).with_session_store(store);
+        (state, session, tmp)
+    }
+
+    #[test]
+    fn test_config_defaults_and_parse() {
+        let cfg: CompactionConfig = serde_json::from_value(json!({
+            "mode": "safeguard",
+            "model": "claude-haiku-4-5",
+            "keepRecent": 8,
+            "threshold": 60
+        }))
+        .unwrap();
+        assert!(cfg.auto);
+        assert_eq!(cfg.keep_recent, 8);
+        assert_eq!(cfg.threshold, Some(60));
+        assert_eq!(cfg.summary_model("claude-sonnet-4"), "claude-haiku-4-5");
+        assert_eq!(
+            CompactionConfig::default().summary_model("claude-sonnet-4"),
+            "claude-sonnet-4"
+        );
+    }
+
+    #[test]
+    fn test_format_transcript_keeps_earlier_summary() {
+        let mut messages = vec![ChatMessage::system("s", "old summary")];
+        messages.push(ChatMessage::user("s", "hi"));
+        messages.push(ChatMessage::tool("s", "weather", "call_1", "sunny"));
+
+        let transcript = format_transcript(&messages);
+        assert!(transcript.starts_with("[earlier summary]\nold summary"));
+        assert!(transcript.contains("[user]\nhi"));
+        assert!(transcript.contains("[tool result (weather)]\nsunny"));
+
+        let long = "x".repeat(MAX_MESSAGE_CHARS + 10);
+        let line = transcript_line(&ChatMessage::user("s", long));
+        assert!(line.ends_with("…\n\n"));
+    }
+
+    #[test]
+    fn test_fallback_summary_quotes_recent_messages() {
+        let messages = vec![
+            ChatMessage::user("s", "book a table\nfor two"),
+            ChatMessage::assistant("s", "Done, 7pm at Luigi's"),
+        ];
+        let summary = fallback_summary(&messages);
+        assert!(summary.starts_with("Compacted 2 messages."));
+        assert!(summary.contains("- user: book a table for two"));
+        assert!(summary.contains("- assistant: Done, 7pm at Luigi's"));
+    }
+
+    #[tokio::test]
+    async fn test_compact_session_stores_llm_summary() {
+        let (state, session, _tmp) = make_state(100);
+        let provider = SummaryProvider::new(Ok("## Context\nSix questions."));
+
+        let metadata = compact_session(
+            &state,
+            &session,
+            Some(&provider),
+            "cheap-model",
+            512,
+            4,
+            CompactionTrigger::Manual,
+        )
+        .await
+        .unwrap()
+        .unwrap();
+
+        assert_eq!(metadata.messages_compacted, 8);
+        let summary = metadata.last_summary.unwrap();
+        assert!(summary.starts_with("Summary of 8 earlier messages"));
+        assert!(summary.contains("Six questions."));
+
+        let request = provider.requests.lock().pop().unwrap();
+        assert_eq!(request.model, "cheap-model");
+        assert_eq!(request.max_tokens, 512);
+
+        let history = state
+            .session_store()
+            .get_history(&session.id, None, None)
+            .unwrap();
+        assert_eq!(history.len(), 5);
+        assert_eq!(history[0].role, MessageRole::System);
+        assert_eq!(history[1].content, "question 4");
+    }
+
+    #[tokio::test]
+    async fn test_compact_session_error_keeps_history() {
+        let (state, session, _tmp) = make_state(100);
+        let provider = SummaryProvider::new(Err("overloaded"));
+
+        let result = compact_session(
+            &state,
+            &session,
+            Some(&provider),
+            "model",
+            512,
+            4,
+            CompactionTrigger::Manual,
+        )
+        .await;
+        assert!(matches!(result, Err(AgentError::Provider(_))));
+        let history = state
+            .session_store()
+            .get_history(&session.id, None, None)
+            .unwrap();
+        assert_eq!(history.len(), 12);
+    }
+
+    #[tokio::test]
+    async fn test_auto_compact_respects_threshold() {
+        let provider = SummaryProvider::new(Ok("summary"));
+        let config = CompactionConfig {
+            keep_recent: 2,
+            ..Default::default()
+        };
+
+        let (state, session, _tmp) = make_state(20);
+        let result =
+            auto_compact_if_needed(&state, &session.session_key, &provider, "m", &config).await;
+        assert!(result.is_none());
+        assert!(provider.requests.lock().is_empty());
+
+        let (state, session, _tmp) = make_state(12);
+        let metadata =
+            auto_compact_if_needed(&state, &session.session_key, &provider, "m", &config)
+                .await
+                .unwrap();
+        assert_eq!(metadata.messages_compacted, 10);
+
+        let disabled = CompactionConfig {
+            auto: false,
+            ..config
+        };
+        let (state, session, _tmp) = make_state(12);
+        assert!(
+            auto_compact_if_needed(&state, &session.session_key, &provider, "m", &disabled)
+                .await
+                .is_none()
+        );
+    }
+}
@@ -1,10 +1,22 @@
-//! Context builder: converts session history into LLM messages.
+//! Context builder: converts session history into LLM messages and fits
+//! them into the model's context window.
+
+use std::collections::HashMap;
+use std::sync::LazyLock;
+
+use parking_lot::RwLock;
+use serde::Serialize;
 
 use crate::agent::prompt_guard::tagging::{self, ContentSource};
 use crate::agent::prompt_guard::TaggingConfig;
-use crate::agent::provider::{ContentBlock, LlmMessage, LlmRole};
+use crate::agent::provider::{ContentBlock, LlmMessage, LlmRole, ToolDefinition};
+use crate::links::links_from_metadata;
+use crate::media::attachments::{attachments_from_metadata, AttachmentKind};
 use crate::sessions::{ChatMessage, MessageRole};
 
+/// Heading for group messages kept as passive context.
+const PASSIVE_CONTEXT_HEADING: &str = "Earlier messages in this group (not addressed to you):";
+
 /// Convert session chat history into LLM messages.
 ///
 /// Maps `ChatMessage` entries from the session store into the `LlmMessage`
@@ -26,6 +38,8 @@ pub fn build_context_with_tagging(
 ) -> (Option<String>, Vec<LlmMessage>) {
     let mut system_parts: Vec<String> = Vec::new();
     let mut messages: Vec<LlmMessage> = Vec::new();
+    // Passive group messages waiting for the next user turn
+    let mut passive: Vec<String> = Vec::new();
 
     if let Some(prompt) = system_prompt {
         system_parts.push(prompt.to_string());
@@ -37,30 +51,44 @@ pub fn build_context_with_tagging(
                 // System messages get prepended to the system prompt
                 system_parts.push(msg.content.clone());
             }
+            MessageRole::User if is_passive(msg) => {
+                passive.push(passive_line(msg));
+            }
             MessageRole::User => {
+                // Passive group context, then attachments and linked pages,
+                // then the text, which usually refers to them
+                let mut content: Vec<ContentBlock> =
+                    passive_block(&mut passive).into_iter().collect();
+                let attachments = attachment_blocks(msg);
+                let has_attachments = !attachments.is_empty();
+                content.extend(attachments);
+                content.extend(link_blocks(msg, tagging_config));
+                if !has_attachments || !msg.content.is_empty() {
+                    content.push(ContentBlock::Text {
+                        text: msg.content.clone(),
+                    });
+                }
                 messages.push(LlmMessage {
                     role: LlmRole::User,
-                    content: vec![ContentBlock::Text {
-                        text: msg.content.clone(),
-                    }],
+                    content,
                 });
             }
             MessageRole::Assistant => {
+                // Thinking blocks precede the answer, as the model produced them
+                let mut content = thinking_blocks(msg);
                 // Check if the content looks like a tool_use block (JSON with tool calls)
                 // Otherwise treat as plain text
                 if let Some(tool_blocks) = try_parse_assistant_tool_use(&msg.content) {
-                    messages.push(LlmMessage {
-                        role: LlmRole::Assistant,
-                        content: tool_blocks,
-                    });
+                    content.extend(tool_blocks);
                 } else {
-                    messages.push(LlmMessage {
-                        role: LlmRole::Assistant,
-                        content: vec![ContentBlock::Text {
-                            text: msg.content.clone(),
-                        }],
+                    content.push(ContentBlock::Text {
+                        text: msg.content.clone(),
                     });
                 }
+                messages.push(LlmMessage {
+                    role: LlmRole::Assistant,
+                    content,
+                });
             }
             MessageRole::Tool => {
                 // Tool results get appended as a user message with ToolResult block.
@@ -88,6 +116,17 @@ pub fn build_context_with_tagging(
         }
     }
 
+    // Passive messages after the last user turn join it, or form their own
+    if let Some(block) = passive_block(&mut passive) {
+        match messages.last_mut() {
+            Some(last) if last.role == LlmRole::User => last.content.push(block),
+            _ => messages.push(LlmMessage {
+                role: LlmRole::User,
+                content: vec![block],
+            }),
+        }
+    }
+
     let system = if system_parts.is_empty() {
         None
     } else {
@@ -97,6 +136,94 @@ pub fn build_context_with_tagging(
     (system, messages)
 }
 
+/// Whether a user message is a group message kept only as passive context.
+pub(crate) fn is_passive(msg: &ChatMessage) -> bool {
+    msg.metadata
+        .as_ref()
+        .and_then(|m| m.get("passive"))
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false)
+}
+
+/// Render a passive message as `[sender] text`.
+fn passive_line(msg: &ChatMessage) -> String {
+    let sender = msg
+        .metadata
+        .as_ref()
+        .and_then(|m| m.get("senderId"))
+        .and_then(|v| v.as_str())
+        .unwrap_or("unknown");
+    format!("[{}] {}", sender, msg.content)
+}
+
+/// Drain buffered passive messages into a single text block.
+fn passive_block(passive: &mut Vec<String>) -> Option<ContentBlock> {
+    if passive.is_empty() {
+        return None;
+    }
+    let lines = std::mem::take(passive).join("\n");
+    Some(ContentBlock::Text {
+        text: format!("{}\n{}", PASSIVE_CONTEXT_HEADING, lines),
+    })
+}
+
+/// Reconstruct thinking blocks stored in an assistant message's
+/// `metadata.thinking` array.
+fn thinking_blocks(msg: &ChatMessage) -> Vec<ContentBlock> {
+    msg.metadata
+        .as_ref()
+        .and_then(|m| m.get("thinking"))
+        .and_then(|v| v.as_array())
+        .map(|blocks| {
+            blocks
+                .iter()
+                .map(|b| ContentBlock::Thinking {
+                    thinking: b["thinking"].as_str().unwrap_or("").to_string(),
+                    signature: b["signature"].as_str().map(String::from),
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Resolve the media-store references in a message's `metadata.attachments`
+/// into image and document blocks.
+///
+/// Files the store has since expired are replaced by a short note so the
+/// model still knows something was attached.
+fn attachment_blocks(msg: &ChatMessage) -> Vec<ContentBlock> {
+    attachments_from_metadata(msg.metadata.as_ref())
+        .into_iter()
+        .map(|attachment| match attachment.read_base64() {
+            Some(data) => match attachment.kind {
+                AttachmentKind::Image => ContentBlock::Image {
+                    media_type: attachment.media_type,
+                    data,
+                },
+                AttachmentKind::Document => ContentBlock::Document {
+                    media_type: attachment.media_type,
+                    data,
+                    name: attachment.name,
+                },
+            },
+            None => ContentBlock::Text {
+                text: format!("[{} is no longer available]", attachment.label()),
+            },
+        })
+        .collect()
+}
+
+/// Render the fetched pages in a message's `metadata.links` as text blocks,
+/// tagged as untrusted fetched content.
+fn link_blocks(msg: &ChatMessage, tagging_config: &TaggingConfig) -> Vec<ContentBlock> {
+    links_from_metadata(msg.metadata.as_ref())
+        .iter()
+        .map(|link| ContentBlock::Text {
+            text: tagging::tag_content(&link.render(), ContentSource::FetchedUrl, tagging_config),
+        })
+        .collect()
+}
+
 /// Try to parse assistant content as tool_use blocks.
 ///
 /// If the content is a JSON array of tool_use objects (stored from a previous
@@ -136,6 +263,336 @@ fn try_parse_assistant_tool_use(content: &str) -> Option<Vec<ContentBlock>> {
     }
 }
 
+// ---------------------------------------------------------------------------
+// Context window management
+// ---------------------------------------------------------------------------
+
+/// Context window assumed for models with no known metadata.
+pub const DEFAULT_CONTEXT_WINDOW: u32 = 128_000;
+
+/// Context window assumed for local and open-weight models served without
+/// metadata (Ollama, Venice), whose effective window is often small.
+const DEFAULT_LOCAL_CONTEXT_WINDOW: u32 = 32_768;
+
+/// Fixed per-message overhead for role markers and block framing.
+const MESSAGE_OVERHEAD_TOKENS: u32 = 4;
+
+/// Estimated cost of an image block. Providers downscale large images, so
+/// this is roughly the ceiling for one image.
+const IMAGE_TOKENS: u32 = 1_600;
+
+/// Safety margin applied to the input budget, in percent, to absorb
+/// estimation error.
+const ESTIMATE_MARGIN_PERCENT: u32 = 10;
+
+/// Tool results in older turns longer than this are truncated before whole
+/// turns are dropped.
+const MAX_OLD_TOOL_RESULT_TOKENS: u32 = 2_000;
+
+/// Context windows advertised by provider plugins, keyed by lowercase
+/// model ID.
+static PLUGIN_CONTEXT_WINDOWS: LazyLock<RwLock<HashMap<String, u32>>> =
+    LazyLock::new(|| RwLock::new(HashMap::new()));
+
+/// Register the context window of a model served by a provider plugin.
+///
+/// Takes precedence over the built-in table.
+pub fn register_context_window(model: &str, tokens: u32) {
+    if tokens > 0 {
+        PLUGIN_CONTEXT_WINDOWS
+            .write()
+            .insert(model.to_lowercase(), tokens);
+    }
+}
+
+/// Context window (input plus output tokens) of `model`.
+///
+/// Uses plugin-advertised metadata when available, then a table of known
+/// model families, then a conservative default.
+pub fn model_context_window(model: &str) -> u32 {
+    let lower = model.to_lowercase();
+    if let Some(tokens) = PLUGIN_CONTEXT_WINDOWS.read().get(&lower) {
+        return *tokens;
+    }
+    builtin_context_window(&lower)
+}
+
+fn builtin_context_window(lower: &str) -> u32 {
+    let name = lower
+        .rsplit_once(['/', ':'])
+        .map_or(lower, |(_, name)| name);
+
+    if name.contains("claude") {
+        200_000
+    } else if name.starts_with("gpt-4.1") {
+        1_047_576
+    } else if name.starts_with("gpt-5") {
+        400_000
+    } else if name.starts_with("o1") || name.starts_with("o3") || name.starts_with("o4") {
+        200_000
+    } else if name.starts_with("gpt-4o")
+        || name.starts_with("gpt-4-turbo")
+        || name.starts_with("chatgpt-")
+    {
+        128_000
+    } else if name.starts_with("gpt-4") {
+        8_192
+    } else if name.starts_with("gpt-3.5") {
+        16_385
+    } else if name.starts_with("gemini-1.5-pro") {
+        2_097_152
+    } else if name.starts_with("gemini-") {
+        1_048_576
+    } else if lower.starts_with("amazon.titan") {
+        8_192
+    } else if lower.starts_with("ollama") || lower.starts_with("venice") {
+        DEFAULT_LOCAL_CONTEXT_WINDOW
+    } else {
+        DEFAULT_CONTEXT_WINDOW
+    }
+}
+
+/// Rough token count for `text`.
+///
+/// Uses ~4 UTF-8 bytes per token, which tracks English prose and code and
+/// over-counts rather than under-counts for non-Latin scripts.
+pub fn estimate_tokens(text: &str) -> u32 {
+    text.len().div_ceil(4).min(u32::MAX as usize) as u32
+}
+
+/// Rough token count for one content block.
+pub fn estimate_block_tokens(block: &ContentBlock) -> u32 {
+    match block {
+        ContentBlock::Text { text } => estimate_tokens(text),
+        ContentBlock::Thinking { thinking, .. } => estimate_tokens(thinking),
+        ContentBlock::ToolUse { name, input, .. } => {
+            estimate_tokens(name) + estimate_tokens(&input.to_string())
+        }
+        ContentBlock::ToolResult { content, .. } => estimate_tokens(content),
+        ContentBlock::Image { .. } => IMAGE_TOKENS,
+        // Extracted document text is about as long as the decoded file
+        ContentBlock::Document { data, .. } => estimate_tokens(data).saturating_mul(3) / 4,
+    }
+}
+
+/// Rough token count for one message, including framing overhead.
+pub fn estimate_message_tokens(message: &LlmMessage) -> u32 {
+    message
+        .content
+        .iter()
+        .map(estimate_block_tokens)
+        .fold(MESSAGE_OVERHEAD_TOKENS, u32::saturating_add)
+}
+
+/// Rough token count for the tool definitions sent with a request.
+pub fn estimate_tool_tokens(tools: &[ToolDefinition]) -> u32 {
+    tools
+        .iter()
+        .map(|tool| {
+            estimate_tokens(&tool.name)
+                + estimate_tokens(&tool.description)
+                + estimate_tokens(&tool.input_schema.to_string())
+        })
+        .fold(0, u32::saturating_add)
+}
+
+/// Token budget for a single request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ContextBudget {
+    /// Model context window (input plus output).
+    pub context_window: u32,
+    /// Tokens reserved for the response (`max_tokens`, plus the thinking
+    /// budget where it is counted separately).
+    pub reserved_output: u32,
+}
+
+impl ContextBudget {
+    /// Tokens available for the system prompt, tools and messages.
+    ///
+    /// At most half the window is reserved for output, so a `max_tokens`
+    /// close to the window size still leaves room for the conversation.
+    pub fn input_budget(&self) -> u32 {
+        let reserved = self.reserved_output.min(self.context_window / 2);
+        let available = self.context_window - reserved;
+        available - available / 100 * ESTIMATE_MARGIN_PERCENT
+    }
+}
+
+/// How much of the budget an assembled request uses.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContextUsage {
+    pub context_window: u32,
+    pub reserved_output: u32,
+    pub input_budget: u32,
+    /// Estimated input tokens of the request as sent.
+    pub estimated_tokens: u32,
+    pub system_tokens: u32,
+    pub tool_tokens: u32,
+    pub message_tokens: u32,
+    /// Messages in the full history.
+    pub messages_total: usize,
+    /// Messages sent to the model.
+    pub messages_included: usize,
+    /// Older messages left out to fit the window.
+    pub messages_dropped: usize,
+    /// Older tool results shortened to fit the window.
+    pub tool_results_truncated: usize,
+}
+
+impl ContextUsage {
+    /// Whether the request still exceeds the budget after trimming (the
+    /// most recent turn alone is too large).
+    pub fn over_budget(&self) -> bool {
+        self.estimated_tokens > self.input_budget
+    }
+}
+
+/// Fit a conversation into `budget`.
+///
+/// Messages are grouped into units that must stay together: an assistant
+/// message with tool calls plus the tool results that answer it. While the
+/// request is over budget, long tool results in older units are truncated
+/// first, then the oldest units are dropped. The most recent unit is always
+/// kept, the conversation always starts with a user message, and a note in
+/// the system prompt tells the model that earlier turns were left out.
+/// Compaction summaries live in the system prompt and are never dropped.
+pub fn assemble_context(
+    system: Option<String>,
+    messages: Vec<LlmMessage>,
+    tools: &[ToolDefinition],
+    budget: ContextBudget,
+) -> (Option<String>, Vec<LlmMessage>, ContextUsage) {
+    let input_budget = budget.input_budget();
+    let tool_tokens = estimate_tool_tokens(tools);
+    let messages_total = messages.len();
+
+    let mut units = group_units(messages);
+    let mut unit_tokens: Vec<u32> = units.iter().map(|u| unit_token_count(u)).collect();
+    let fixed = tool_tokens + system.as_deref().map_or(0, estimate_tokens);
+    let total = |tokens: &[u32]| tokens.iter().fold(fixed, |acc, t| acc.saturating_add(*t));
+
+    // Pass 1: shorten long tool results in older units
+    let mut tool_results_truncated = 0;
+    for i in 0..units.len().saturating_sub(1) {
+        if total(&unit_tokens) <= input_budget {
+            break;
+        }
+        let truncated = truncate_tool_results(&mut units[i], MAX_OLD_TOOL_RESULT_TOKENS);
+        if truncated > 0 {
+            tool_results_truncated += truncated;
+            unit_tokens[i] = unit_token_count(&units[i]);
+        }
+    }
+
+    // Pass 2: drop the oldest units, keeping the latest one
+    let mut first_kept = 0;
+    while first_kept + 1 < units.len() && total(&unit_tokens[first_kept..]) > input_budget {
+        first_kept += 1;
+    }
+    // The conversation must open with a user turn
+    while first_kept + 1 < units.len() && !starts_with_user(&units[first_kept]) {
+        first_kept += 1;
+    }
+
+    let messages_dropped: usize = units[..first_kept].iter().map(Vec::len).sum();
+    let message_tokens = unit_tokens[first_kept..]
+        .iter()
+        .fold(0u32, |acc, t| acc.saturating_add(*t));
+    let messages: Vec<LlmMessage> = units.into_iter().skip(first_kept).flatten().collect();
+
+    let system = if messages_dropped > 0 {
+        let note = format!(
+            "[{messages_dropped} earlier messages of this conversation were omitted to fit the \
+             model's context window.]"
+        );
+        Some(match system {
+            Some(prompt) => format!("{prompt}\n\n{note}"),
+            None => note,
+        })
+    } else {
+        system
+    };
+    let system_tokens = system.as_deref().map_or(0, estimate_tokens);
+
+    let usage = ContextUsage {
+        context_window: budget.context_window,
+        reserved_output: budget.reserved_output,
+        input_budget,
+        estimated_tokens: system_tokens
+            .saturating_add(tool_tokens)
+            .saturating_add(message_tokens),
+        system_tokens,
+        tool_tokens,
+        message_tokens,
+        messages_total,
+        messages_included: messages.len(),
+        messages_dropped,
+        tool_results_truncated,
+    };
+    (system, messages, usage)
+}
+
+/// Split messages into units that cannot be separated: each message starts
+/// a unit, except user messages made only of tool results, which join the
+/// unit holding the tool calls they answer.
+fn group_units(messages: Vec<LlmMessage>) -> Vec<Vec<LlmMessage>> {
+    let mut units: Vec<Vec<LlmMessage>> = Vec::new();
+    for message in messages {
+        let is_tool_result = message.role == LlmRole::User
+            && !message.content.is_empty()
+            && message
+                .content
+                .iter()
+                .all(|b| matches!(b, ContentBlock::ToolResult { .. }));
+        match units.last_mut() {
+            Some(unit) if is_tool_result => unit.push(message),
+            _ => units.push(vec![message]),
+        }
+    }
+    units
+}
+
+fn unit_token_count(unit: &[LlmMessage]) -> u32 {
+    unit.iter()
+        .map(estimate_message_tokens)
+        .fold(0, u32::saturating_add)
+}
+
+fn starts_with_user(unit: &[LlmMessage]) -> bool {
+    unit.first().is_some_and(|m| {
+        m.role == LlmRole::User
+            && !m
+                .content
+                .iter()
+                .any(|b| matches!(b, ContentBlock::ToolResult { .. }))
+    })
+}
+
+/// Truncate tool results in `unit` longer than `max_tokens`. Returns how
+/// many were shortened.
+fn truncate_tool_results(unit: &mut [LlmMessage], max_tokens: u32) -> usize {
+    let max_bytes = max_tokens as usize * 4;
+    let mut truncated = 0;
+    for block in unit.iter_mut().flat_map(|m| m.content.iter_mut()) {
+        if let ContentBlock::ToolResult { content, .. } = block {
+            if content.len() > max_bytes {
+                let mut cut = max_bytes;
+                while !content.is_char_boundary(cut) {
+                    cut -= 1;
+                }
+                let omitted = content.len() - cut;
+                content.truncate(cut);
+                content.push_str(&format!(
+                    "\n[… {omitted} bytes truncated to fit the context window]"
+                ));
+                truncated += 1;
+            }
+        }
+    }
+    truncated
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -162,6 +619,44 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_passive_group_messages_prefix_next_user_turn() {
+        let passive = |sender: &str, text: &str| {
+            ChatMessage::user("sess1", text)
+                .with_metadata(serde_json::json!({ "passive": true, "senderId": sender }))
+        };
+        let history = vec![
+            passive("alice", "lunch at noon?"),
+            passive("bob", "sure"),
+            ChatMessage::user("sess1", "@bot where should we go?"),
+            ChatMessage::assistant("sess1", "Try the deli."),
+            passive("alice", "good call"),
+        ];
+
+        let (_, messages) = build_context(&history, None);
+        assert_eq!(messages.len(), 3);
+        assert_eq!(messages[0].role, LlmRole::User);
+        assert_eq!(messages[0].content.len(), 2);
+        match &messages[0].content[0] {
+            ContentBlock::Text { text } => {
+                assert!(text.starts_with(PASSIVE_CONTEXT_HEADING));
+                assert!(text.ends_with("[alice] lunch at noon?\n[bob] sure"));
+            }
+            _ => panic!("expected Text block"),
+        }
+        match &messages[0].content[1] {
+            ContentBlock::Text { text } => assert_eq!(text, "@bot where should we go?"),
+            _ => panic!("expected Text block"),
+        }
+
+        // Trailing passive messages form their own user turn
+        assert_eq!(messages[2].role, LlmRole::User);
+        match &messages[2].content[0] {
+            ContentBlock::Text { text } => assert!(text.ends_with("[alice] good call")),
+            _ => panic!("expected Text block"),
+        }
+    }
+
     #[test]
     fn test_system_messages_merge_into_system_prompt() {
         let history = vec![
@@ -254,4 +749,274 @@ mod tests {
             _ => panic!("expected ToolUse block"),
         }
     }
+
+    #[test]
+    fn test_user_attachments_resolved_from_media_store() {
+        let dir = tempfile::tempdir().unwrap();
+        let image_path = dir.path().join("cat.png");
+        std::fs::write(&image_path, b"png").unwrap();
+        let history =
+            vec![
+                ChatMessage::user("sess1", "What is this?").with_metadata(serde_json::json!({
+                    "attachments": [
+                        {"type": "image", "mediaType": "image/png", "path": image_path, "size": 3},
+                        {"type": "document", "mediaType": "application/pdf",
+                         "path": dir.path().join("gone.pdf"), "name": "gone.pdf"}
+                    ]
+                })),
+            ];
+
+        let (_, messages) = build_context(&history, None);
+        let content = &messages[0].content;
+        assert_eq!(content.len(), 3);
+        match &content[0] {
+            ContentBlock::Image { media_type, data } => {
+                assert_eq!(media_type, "image/png");
+                assert_eq!(data, "cG5n");
+            }
+            other => panic!("expected Image block, got {other:?}"),
+        }
+        assert!(
+            matches!(&content[1], ContentBlock::Text { text } if text.contains("\"gone.pdf\" is no longer available"))
+        );
+        assert!(matches!(&content[2], ContentBlock::Text { text } if text == "What is this?"));
+    }
+
+    #[test]
+    fn test_user_link_context_tagged_before_text() {
+        let metadata = serde_json::json!({
+            "links": [{ "url": "https://example.com", "title": "Example", "text": "Body" }]
+        });
+        let history = vec![
+            ChatMessage::user("sess1", "what is https://example.com?").with_metadata(metadata)
+        ];
+
+        let (_, messages) =
+            build_context_with_tagging(&history, None, &TaggingConfig { enabled: true });
+        assert_eq!(messages[0].content.len(), 2);
+        match &messages[0].content[0] {
+            ContentBlock::Text { text } => {
+                assert!(text.starts_with(tagging::UNTRUSTED_START));
+                assert!(text.contains("Linked page: https://example.com\nTitle: Example"));
+            }
+            _ => panic!("expected Text block"),
+        }
+        // The user text itself is never rewritten or tagged
+        match &messages[0].content[1] {
+            ContentBlock::Text { text } => assert_eq!(text, "what is https://example.com?"),
+            _ => panic!("expected Text block"),
+        }
+    }
+
+    #[test]
+    fn test_assistant_thinking_metadata_replayed_before_answer() {
+        let history = vec![
+            ChatMessage::user("sess1", "Hi"),
+            ChatMessage::assistant("sess1", "Hello!").with_metadata(serde_json::json!({
+                "thinking": [{"thinking": "greet back", "signature": "sig"}]
+            })),
+        ];
+
+        let (_, messages) = build_context(&history, None);
+        assert_eq!(messages[1].content.len(), 2);
+        match &messages[1].content[0] {
+            ContentBlock::Thinking {
+                thinking,
+                signature,
+            } => {
+                assert_eq!(thinking, "greet back");
+                assert_eq!(signature.as_deref(), Some("sig"));
+            }
+            other => panic!("expected Thinking block, got {other:?}"),
+        }
+        assert!(matches!(&messages[1].content[1], ContentBlock::Text { text } if text == "Hello!"));
+    }
+
+    fn text_message(role: LlmRole, text: &str) -> LlmMessage {
+        LlmMessage {
+            role,
+            content: vec![ContentBlock::Text {
+                text: text.to_string(),
+            }],
+        }
+    }
+
+    fn tool_call(id: &str) -> LlmMessage {
+        LlmMessage {
+            role: LlmRole::Assistant,
+            content: vec![ContentBlock::ToolUse {
+                id: id.to_string(),
+                name: "search".to_string(),
+                input: serde_json::json!({}),
+            }],
+        }
+    }
+
+    fn tool_result(id: &str, content: &str) -> LlmMessage {
+        LlmMessage {
+            role: LlmRole::User,
+            content: vec![ContentBlock::ToolResult {
+                tool_use_id: id.to_string(),
+                content: content.to_string(),
+                is_error: false,
+            }],
+        }
+    }
+
+    #[test]
+    fn test_model_context_window_table() {
+        assert_eq!(model_context_window("claude-sonnet-4-20250514"), 200_000);
+        assert_eq!(model_context_window("bedrock:anthropic.claude-3"), 200_000);
+        assert_eq!(model_context_window("gpt-4o-mini"), 128_000);
+        assert_eq!(model_context_window("gpt-4"), 8_192);
+        assert_eq!(model_context_window("gemini-2.0-flash"), 1_048_576);
+        assert_eq!(model_context_window("ollama:llama3"), 32_768);
+        assert_eq!(
+            model_context_window("mystery-model"),
+            DEFAULT_CONTEXT_WINDOW
+        );
+
+        register_context_window("CtxTest:Tiny", 4_096);
+        assert_eq!(model_context_window("ctxtest:tiny"), 4_096);
+    }
+
+    #[test]
+    fn test_estimate_tokens() {
+        assert_eq!(estimate_tokens(""), 0);
+        assert_eq!(estimate_tokens("abcd"), 1);
+        assert_eq!(estimate_tokens("abcde"), 2);
+        let message = text_message(LlmRole::User, &"x".repeat(400));
+        assert_eq!(
+            estimate_message_tokens(&message),
+            100 + MESSAGE_OVERHEAD_TOKENS
+        );
+        let image = ContentBlock::Image {
+            media_type: "image/png".to_string(),
+            data: "x".repeat(1_000_000),
+        };
+        assert_eq!(estimate_block_tokens(&image), IMAGE_TOKENS);
+    }
+
+    #[test]
+    fn test_input_budget_reserves_output() {
+        let budget = ContextBudget {
+            context_window: 10_000,
+            reserved_output: 2_000,
+        };
+        assert_eq!(budget.input_budget(), 7_200);
+
+        // At most half the window goes to output
+        let budget = ContextBudget {
+            context_window: 8_000,
+            reserved_output: 8_000,
+        };
+        assert_eq!(budget.input_budget(), 3_600);
+    }
+
+    #[test]
+    fn test_assemble_context_within_budget_is_unchanged() {
+        let messages = vec![
+            text_message(LlmRole::User, "Hi"),
+            text_message(LlmRole::Assistant, "Hello"),
+        ];
+        let budget = ContextBudget {
+            context_window: 200_000,
+            reserved_output: 8_192,
+        };
+        let (system, messages, usage) =
+            assemble_context(Some("prompt".to_string()), messages, &[], budget);
+        assert_eq!(system.as_deref(), Some("prompt"));
+        assert_eq!(messages.len(), 2);
+        assert_eq!(usage.messages_dropped, 0);
+        assert_eq!(usage.messages_included, 2);
+        assert_eq!(
+            usage.estimated_tokens,
+            usage.system_tokens + usage.message_tokens
+        );
+        assert!(!usage.over_budget());
+    }
+
+    #[test]
+    fn test_assemble_context_drops_oldest_turns_keeping_tool_pairs() {
+        let filler = "x".repeat(2_000);
+        let messages = vec![
+            text_message(LlmRole::User, &filler),
+            text_message(LlmRole::Assistant, &filler),
+            text_message(LlmRole::User, "search please"),
+            tool_call("call_1"),
+            tool_result("call_1", "result"),
+            text_message(LlmRole::Assistant, "found it"),
+            text_message(LlmRole::User, "thanks"),
+        ];
+        // Room for the last five messages but not the filler
+        let budget = ContextBudget {
+            context_window: 1_000,
+            reserved_output: 100,
+        };
+        let (system, messages, usage) = assemble_context(None, messages, &[], budget);
+
+        assert_eq!(usage.messages_dropped, 2);
+        assert_eq!(messages.len(), 5);
+        assert_eq!(messages[0].role, LlmRole::User);
+        assert!(
+            matches!(&messages[1].content[0], ContentBlock::ToolUse { id, .. } if id == "call_1")
+        );
+        assert!(
+            matches!(&messages[2].content[0], ContentBlock::ToolResult { tool_use_id, .. } if tool_use_id == "call_1")
+        );
+        assert!(system.unwrap().contains("2 earlier messages"));
+        assert!(!usage.over_budget());
+    }
+
+    #[test]
+    fn test_assemble_context_never_splits_tool_result_from_call() {
+        let messages = vec![
+            text_message(LlmRole::User, "go"),
+            tool_call("call_1"),
+            tool_result("call_1", &"r".repeat(3_000)),
+            tool_call("call_2"),
+            tool_result("call_2", "small"),
+        ];
+        let budget = ContextBudget {
+            context_window: 400,
+            reserved_output: 0,
+        };
+        let (_, messages, usage) = assemble_context(None, messages, &[], budget);
+
+        // Only the latest unit fits; it starts with a tool call, so it is
+        // kept whole even though it cannot open the conversation alone
+        assert_eq!(usage.messages_dropped, 3);
+        assert!(
+            matches!(&messages[0].content[0], ContentBlock::ToolUse { id, .. } if id == "call_2")
+        );
+        assert_eq!(messages.len(), 2);
+    }
+
+    #[test]
+    fn test_assemble_context_truncates_old_tool_results_first() {
+        let big = "r".repeat(MAX_OLD_TOOL_RESULT_TOKENS as usize * 4 * 6);
+        let messages = vec![
+            text_message(LlmRole::User, "go"),
+            tool_call("call_1"),
+            tool_result("call_1", &big),
+            text_message(LlmRole::Assistant, "done"),
+            text_message(LlmRole::User, "next"),
+        ];
+        let budget = ContextBudget {
+            context_window: 12_000,
+            reserved_output: 2_000,
+        };
+        let (system, messages, usage) = assemble_context(None, messages, &[], budget);
+
+        assert_eq!(usage.tool_results_truncated, 1);
+        assert_eq!(usage.messages_dropped, 0);
+        assert!(system.is_none());
+        match &messages[2].content[0] {
+            ContentBlock::ToolResult { content, .. } => {
+                assert!(content.len() < big.len());
+                assert!(content.ends_with("truncated to fit the context window]"));
+            }
+            other => panic!("expected ToolResult block, got {other:?}"),
+        }
+    }
 }
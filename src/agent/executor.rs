@@ -9,12 +9,17 @@ use std::time::Duration;
 
 use serde_json::{json, Value};
 
-use crate::agent::context::{build_context, build_context_with_tagging};
+use crate::agent::compaction;
+use crate::agent::context::{
+    assemble_context, build_context, build_context_with_tagging, estimate_message_tokens,
+    estimate_tokens, estimate_tool_tokens, is_passive, ContextBudget, ContextUsage,
+};
 use crate::agent::prompt_guard::{postflight, preflight};
 use crate::agent::provider::*;
 use crate::agent::tools::{self, ToolCallResult};
 use crate::agent::{AgentConfig, AgentError};
 use crate::plugins::hook_utils;
+use crate::plugins::tools::ToolInvokeContext;
 use crate::plugins::HookDispatchResult;
 use crate::server::ws::{broadcast_agent_event, broadcast_chat_event, WsServerState};
 
@@ -34,11 +39,32 @@ use tokio_util::sync::CancellationToken;
 /// Result of processing an LLM stream for a single turn.
 struct StreamResult {
     turn_text: String,
+    /// Thinking blocks as `(text, signature)`, in stream order.
+    thinking: Vec<(String, Option<String>)>,
     pending_tool_calls: Vec<(String, String, Value)>,
     stop_reason: StopReason,
     turn_usage: TokenUsage,
 }
 
+/// What a single model attempt produced before it stopped or failed.
+#[derive(Debug, Default)]
+struct AttemptProgress {
+    /// Set once any text or tool-use event has been broadcast.
+    streamed: bool,
+    /// Estimated output tokens received so far. Providers only report usage
+    /// on the stop event, so a failed attempt is booked from this estimate.
+    output_tokens: u64,
+}
+
+/// Per-attempt options for stream processing.
+#[derive(Debug, Clone, Copy)]
+struct StreamOptions {
+    /// A fallback model remains, so retryable errors are not broadcast.
+    can_fail_over: bool,
+    /// Broadcast thinking deltas to clients as the `thinking` stream.
+    stream_thinking: bool,
+}
+
 fn dispatch_plugin_hook(
     state: &Arc<WsServerState>,
     hook_name: &str,
@@ -128,6 +154,7 @@ fn handle_stream_event(
     run_id: &str,
     session_key: &str,
     seq: &AtomicU64,
+    opts: StreamOptions,
 ) -> Result<bool, AgentError> {
     match event {
         StreamEvent::TextDelta { text } => {
@@ -155,6 +182,32 @@ fn handle_stream_event(
             Ok(false)
         }
 
+        StreamEvent::ThinkingDelta { text } => {
+            match result.thinking.last_mut() {
+                Some((block, None)) => block.push_str(&text),
+                _ => result.thinking.push((text.clone(), None)),
+            }
+            if opts.stream_thinking {
+                broadcast_agent_event(
+                    state,
+                    run_id,
+                    seq.fetch_add(1, Ordering::Relaxed),
+                    "thinking",
+                    json!({ "delta": &text }),
+                );
+            }
+            Ok(false)
+        }
+
+        StreamEvent::ThinkingSignature { signature } => {
+            match result.thinking.last_mut() {
+                Some((_, sig @ None)) => *sig = Some(signature),
+                // Signature without streamed text (e.g. fully redacted thinking)
+                _ => result.thinking.push((String::new(), Some(signature))),
+            }
+            Ok(false)
+        }
+
         StreamEvent::ToolUse { id, name, input } => {
             broadcast_agent_event(
                 state,
@@ -179,6 +232,11 @@ fn handle_stream_event(
 
         StreamEvent::Error { message } => {
             let safe_message = sanitize_provider_error(&message);
+            // A retryable error with a fallback model left is reported as a
+            // `failover` event by the caller instead.
+            if opts.can_fail_over && is_retryable_message(&safe_message) {
+                return Err(AgentError::Provider(safe_message));
+            }
             broadcast_agent_event(
                 state,
                 run_id,
@@ -208,6 +266,10 @@ fn handle_stream_event(
 /// Reads events from `rx`, accumulates text deltas and tool-use blocks,
 /// broadcasts events to clients, and checks for cancellation. Returns the
 /// accumulated turn data or an error on cancellation / stream failure.
+///
+/// `progress` tracks what has been received, so the caller knows whether a
+/// failed attempt left partial output behind and what to book for it.
+#[allow(clippy::too_many_arguments)]
 async fn process_llm_stream(
     rx: &mut mpsc::Receiver<StreamEvent>,
     state: &Arc<WsServerState>,
@@ -215,9 +277,12 @@ async fn process_llm_stream(
     session_key: &str,
     seq: &AtomicU64,
     cancel_token: &CancellationToken,
+    opts: StreamOptions,
+    progress: &mut AttemptProgress,
 ) -> Result<StreamResult, AgentError> {
     let mut result = StreamResult {
         turn_text: String::new(),
+        thinking: Vec::new(),
         pending_tool_calls: Vec::new(),
         stop_reason: StopReason::EndTurn,
         turn_usage: TokenUsage::default(),
@@ -249,7 +314,23 @@ async fn process_llm_stream(
             }
         };
 
-        got_stop = handle_stream_event(event, &mut result, state, run_id, session_key, seq)?;
+        let output_tokens = match &event {
+            StreamEvent::TextDelta { text } | StreamEvent::ThinkingDelta { text } => {
+                estimate_tokens(text)
+            }
+            StreamEvent::ToolUse { name, input, .. } => {
+                estimate_tokens(name) + estimate_tokens(&input.to_string())
+            }
+            _ => 0,
+        };
+        progress.output_tokens += u64::from(output_tokens);
+        if matches!(
+            event,
+            StreamEvent::TextDelta { .. } | StreamEvent::ToolUse { .. }
+        ) {
+            progress.streamed = true;
+        }
+        got_stop = handle_stream_event(event, &mut result, state, run_id, session_key, seq, opts)?;
         if got_stop {
             break;
         }
@@ -267,11 +348,14 @@ async fn process_llm_stream(
 
 /// Build an assistant history message from accumulated turn text and tool-use blocks.
 ///
-/// Returns the `ChatMessage` ready for persistence, or `None` if there is
-/// nothing to record (empty text and no tool calls).
+/// Thinking blocks, if any, are attached as `metadata.thinking` so the
+/// context builder can replay them. Returns the `ChatMessage` ready for
+/// persistence, or `None` if there is nothing to record (empty text and no
+/// tool calls).
 fn build_assistant_message(
     session_id: &str,
     turn_text: &str,
+    thinking: &[(String, Option<String>)],
     pending_tool_calls: &[(String, String, Value)],
     output_tokens: u64,
 ) -> Option<ChatMessage> {
@@ -298,19 +382,28 @@ fn build_assistant_message(
         serde_json::to_string(&blocks).unwrap_or_else(|_| turn_text.to_string())
     };
 
-    Some(ChatMessage::assistant(session_id, &content).with_tokens(output_tokens))
+    let msg = ChatMessage::assistant(session_id, &content).with_tokens(output_tokens);
+    if thinking.is_empty() {
+        return Some(msg);
+    }
+    let blocks: Vec<Value> = thinking
+        .iter()
+        .map(|(text, signature)| json!({ "thinking": text, "signature": signature }))
+        .collect();
+    Some(msg.with_metadata(json!({ "thinking": blocks })))
 }
 
 /// Execute pending tool calls with exfiltration guard and tool-policy checks,
 /// broadcast results, and return the corresponding history messages.
 #[allow(clippy::too_many_arguments)]
-fn execute_tools_with_guards(
+async fn execute_tools_with_guards(
     pending_tool_calls: &[(String, String, Value)],
     config: &AgentConfig,
     state: &Arc<WsServerState>,
     session_id: &str,
     session_key: &str,
     message_channel: Option<&str>,
+    tool_ctx: &ToolInvokeContext,
     run_id: &str,
     seq: &AtomicU64,
 ) -> Vec<ChatMessage> {
@@ -365,46 +458,10 @@ fn execute_tools_with_guards(
                     }
                 }
 
-                if let Some(tools_registry) = state.tools_registry() {
-                    let sandbox = if config.process_sandbox.enabled {
-                        Some(&config.process_sandbox)
-                    } else {
-                        None
-                    };
-                    tools::execute_tool_call_with_sandbox(
-                        tool_name,
-                        tool_input.clone(),
-                        tools_registry,
-                        session_key,
-                        None,
-                        message_channel,
-                        sandbox,
-                    )
-                } else {
-                    ToolCallResult::Error {
-                        message: "no tools registry available".to_string(),
-                    }
-                }
+                invoke_registry_tool(config, state, tool_name, &tool_input, tool_ctx).await
             }
-        } else if let Some(tools_registry) = state.tools_registry() {
-            let sandbox = if config.process_sandbox.enabled {
-                Some(&config.process_sandbox)
-            } else {
-                None
-            };
-            tools::execute_tool_call_with_sandbox(
-                tool_name,
-                tool_input.clone(),
-                tools_registry,
-                session_key,
-                None,
-                message_channel,
-                sandbox,
-            )
         } else {
-            ToolCallResult::Error {
-                message: "no tools registry available".to_string(),
-            }
+            invoke_registry_tool(config, state, tool_name, &tool_input, tool_ctx).await
         };
 
         let (mut result_content, mut is_error) = match &tool_result {
@@ -475,18 +532,48 @@ fn execute_tools_with_guards(
     tool_msgs
 }
 
-/// Record token usage for a single turn via the usage tracker.
-fn record_turn_usage(session_key: &str, model: &str, usage: &TokenUsage) {
-    let provider_name = if crate::agent::venice::is_venice_model(model) {
-        "venice"
-    } else if crate::agent::openai::is_openai_model(model) {
-        "openai"
+/// Invoke a tool through the shared tools registry.
+///
+/// `tool_ctx` carries the conversation identity, the run's cancellation
+/// token and handles to the server subsystems.
+async fn invoke_registry_tool(
+    config: &AgentConfig,
+    state: &Arc<WsServerState>,
+    tool_name: &str,
+    tool_input: &Value,
+    tool_ctx: &ToolInvokeContext,
+) -> ToolCallResult {
+    let Some(tools_registry) = state.tools_registry() else {
+        return ToolCallResult::Error {
+            message: "no tools registry available".to_string(),
+        };
+    };
+    let sandbox = if config.process_sandbox.enabled {
+        Some(&config.process_sandbox)
     } else {
-        "anthropic"
+        None
     };
+    tools::execute_tool_call_with_context(
+        tool_name,
+        tool_input.clone(),
+        tools_registry,
+        tool_ctx.clone(),
+        sandbox,
+    )
+    .await
+}
+
+/// Record token usage for a single turn via the usage tracker, booked
+/// against the backend the provider routes `model` to.
+fn record_turn_usage(
+    provider: &Arc<dyn LlmProvider>,
+    session_key: &str,
+    model: &str,
+    usage: &TokenUsage,
+) {
     crate::server::ws::record_usage(
         session_key,
-        provider_name,
+        provider.provider_name(model),
         model,
         usage.input_tokens,
         usage.output_tokens,
@@ -494,12 +581,17 @@ fn record_turn_usage(session_key: &str, model: &str, usage: &TokenUsage) {
 }
 
 /// Build the `CompletionRequest` for a single turn from in-memory history.
+///
+/// The conversation is fitted into the smallest context window among the
+/// models still available to the turn (the active model and its remaining
+/// fallbacks), with room reserved for the response.
 fn build_turn_request(
     history: &[ChatMessage],
     config: &AgentConfig,
     state: &Arc<WsServerState>,
     message_channel: Option<&str>,
-) -> CompletionRequest {
+    active_model: usize,
+) -> (CompletionRequest, ContextUsage) {
     let (system, messages) = if config.prompt_guard.enabled && config.prompt_guard.tagging.enabled {
         build_context_with_tagging(
             history,
@@ -519,15 +611,29 @@ fn build_turn_request(
         vec![]
     };
 
-    CompletionRequest {
+    let budget = ContextBudget {
+        context_window: config.model_chain()[active_model..]
+            .iter()
+            .map(|model| config.context_window(model))
+            .min()
+            .unwrap_or_else(|| config.context_window(&config.model)),
+        reserved_output: config.thinking.map_or(config.max_tokens, |level| {
+            level.budget_with_max_tokens(config.max_tokens).1
+        }),
+    };
+    let (system, messages, usage) = assemble_context(system, messages, &tools, budget);
+
+    let request = CompletionRequest {
         model: config.model.clone(),
         messages,
         system,
         tools,
         max_tokens: config.max_tokens,
         temperature: config.temperature,
+        thinking: config.thinking,
         extra: config.extra.clone(),
-    }
+    };
+    (request, usage)
 }
 
 /// Broadcast run-completion events and mark the run as completed in the registry.
@@ -537,6 +643,7 @@ fn finalize_run(
     run_id: &str,
     session_key: &str,
     seq: &AtomicU64,
+    model: &str,
     final_stop_reason: StopReason,
     total_input_tokens: u64,
     total_output_tokens: u64,
@@ -555,6 +662,7 @@ fn finalize_run(
         seq.fetch_add(1, Ordering::Relaxed),
         "complete",
         json!({
+            "model": model,
             "stopReason": stop_reason_str,
             "usage": {
                 "inputTokens": total_input_tokens,
@@ -590,6 +698,160 @@ fn finalize_run(
     registry.mark_completed(run_id, accumulated_text);
 }
 
+/// Call the provider and stream one turn for a single model.
+#[allow(clippy::too_many_arguments)]
+async fn stream_turn(
+    request: CompletionRequest,
+    provider: &Arc<dyn LlmProvider>,
+    cancel_token: &CancellationToken,
+    state: &Arc<WsServerState>,
+    run_id: &str,
+    session_key: &str,
+    seq: &AtomicU64,
+    opts: StreamOptions,
+    progress: &mut AttemptProgress,
+) -> Result<StreamResult, AgentError> {
+    // Call LLM (with per-turn timeout)
+    let mut rx = match tokio::time::timeout(
+        TURN_TIMEOUT,
+        provider.complete(request, cancel_token.clone()),
+    )
+    .await
+    {
+        Ok(Ok(rx)) => rx,
+        Ok(Err(AgentError::Cancelled)) => return Err(AgentError::Cancelled),
+        Ok(Err(e)) => return Err(AgentError::Provider(e.to_string())),
+        Err(_) => {
+            return Err(AgentError::Provider(format!(
+                "LLM turn timed out after {}s",
+                TURN_TIMEOUT.as_secs()
+            )));
+        }
+    };
+
+    process_llm_stream(
+        &mut rx,
+        state,
+        run_id,
+        session_key,
+        seq,
+        cancel_token,
+        opts,
+        progress,
+    )
+    .await
+}
+
+/// Estimated usage of an attempt that failed before reporting its own.
+///
+/// Input is only counted once the model produced output, since requests
+/// rejected up front are not billed.
+fn failed_attempt_usage(request: &CompletionRequest, progress: &AttemptProgress) -> TokenUsage {
+    if progress.output_tokens == 0 {
+        return TokenUsage::default();
+    }
+    let input_tokens = request.messages.iter().map(estimate_message_tokens).fold(
+        request.system.as_deref().map_or(0, estimate_tokens) + estimate_tool_tokens(&request.tools),
+        u32::saturating_add,
+    );
+    TokenUsage {
+        input_tokens: u64::from(input_tokens),
+        output_tokens: progress.output_tokens,
+    }
+}
+
+/// Stream one turn, walking the agent's model fallback chain on retryable
+/// provider errors.
+///
+/// Starts at `chain[*active_model]` and leaves `active_model` pointing at
+/// the model that answered, so later turns of the run stay on it. Each
+/// failed attempt is recorded in the audit log and announced with a
+/// `failover` agent event; `partial: true` tells clients to discard output
+/// already streamed by the failed model. Tokens a failed attempt consumed
+/// are estimated and booked against its own model.
+#[allow(clippy::too_many_arguments)]
+async fn stream_turn_with_failover(
+    request: CompletionRequest,
+    chain: &[&str],
+    active_model: &mut usize,
+    provider: &Arc<dyn LlmProvider>,
+    cancel_token: &CancellationToken,
+    state: &Arc<WsServerState>,
+    run_id: &str,
+    session_key: &str,
+    seq: &AtomicU64,
+    stream_thinking: bool,
+) -> Result<StreamResult, AgentError> {
+    loop {
+        let model = chain[*active_model];
+        let next_model = chain.get(*active_model + 1).copied();
+
+        let mut attempt = request.clone();
+        attempt.model = model.to_string();
+        let mut progress = AttemptProgress::default();
+        let err = match stream_turn(
+            attempt,
+            provider,
+            cancel_token,
+            state,
+            run_id,
+            session_key,
+            seq,
+            StreamOptions {
+                can_fail_over: next_model.is_some(),
+                stream_thinking,
+            },
+            &mut progress,
+        )
+        .await
+        {
+            Ok(result) => return Ok(result),
+            Err(e) => e,
+        };
+
+        let usage = failed_attempt_usage(&request, &progress);
+        if usage.output_tokens > 0 {
+            record_turn_usage(provider, session_key, model, &usage);
+        }
+        let streamed = progress.streamed;
+
+        let next_model = match next_model {
+            Some(next) if is_retryable_error(&err) => next,
+            _ => return Err(err),
+        };
+
+        let reason = sanitize_provider_error(&err.to_string());
+        tracing::warn!(
+            run_id = %run_id,
+            from_model = %model,
+            to_model = %next_model,
+            partial = streamed,
+            error = %reason,
+            "LLM call failed; failing over to next model"
+        );
+        crate::logging::audit::audit(crate::logging::audit::AuditEvent::ProviderFailover {
+            run_id: run_id.to_string(),
+            from_model: model.to_string(),
+            to_model: next_model.to_string(),
+            reason: reason.clone(),
+        });
+        broadcast_agent_event(
+            state,
+            run_id,
+            seq.fetch_add(1, Ordering::Relaxed),
+            "failover",
+            json!({
+                "fromModel": model,
+                "toModel": next_model,
+                "reason": reason,
+                "partial": streamed,
+            }),
+        );
+
+        *active_model += 1;
+    }
+}
+
 /// Execute a single LLM turn: call the provider, stream the response,
 /// record usage, persist the assistant message, and optionally execute tools.
 ///
@@ -605,7 +867,9 @@ async fn execute_single_turn(
     session_key: &str,
     session_id: &str,
     message_channel: Option<&str>,
+    tool_ctx: &ToolInvokeContext,
     seq: &AtomicU64,
+    active_model: &mut usize,
     history: &mut Vec<ChatMessage>,
     accumulated_text: &mut String,
     total_input_tokens: &mut u64,
@@ -624,37 +888,55 @@ async fn execute_single_turn(
         return Err(AgentError::Cancelled);
     }
 
-    let request = build_turn_request(history, config, state, message_channel);
-
-    // Call LLM (with per-turn timeout)
-    let mut rx = match tokio::time::timeout(
-        TURN_TIMEOUT,
-        provider.complete(request, cancel_token.clone()),
-    )
-    .await
-    {
-        Ok(Ok(rx)) => rx,
-        Ok(Err(AgentError::Cancelled)) => return Err(AgentError::Cancelled),
-        Ok(Err(e)) => return Err(AgentError::Provider(e.to_string())),
-        Err(_) => {
-            return Err(AgentError::Provider(format!(
-                "LLM turn timed out after {}s",
-                TURN_TIMEOUT.as_secs()
-            )));
-        }
-    };
+    let (request, context_usage) =
+        build_turn_request(history, config, state, message_channel, *active_model);
+    if context_usage.messages_dropped > 0 || context_usage.over_budget() {
+        tracing::warn!(
+            run_id = %run_id,
+            dropped = context_usage.messages_dropped,
+            estimated_tokens = context_usage.estimated_tokens,
+            input_budget = context_usage.input_budget,
+            "conversation exceeded the context budget"
+        );
+    }
+    let chain = config.model_chain();
 
     let StreamResult {
         turn_text,
+        thinking,
         pending_tool_calls,
         stop_reason,
         turn_usage,
-    } = process_llm_stream(&mut rx, state, run_id, session_key, seq, cancel_token).await?;
+    } = stream_turn_with_failover(
+        request,
+        &chain,
+        active_model,
+        provider,
+        cancel_token,
+        state,
+        run_id,
+        session_key,
+        seq,
+        config.stream_thinking,
+    )
+    .await?;
 
-    // Track usage
+    // Track usage against the model that actually answered
     *total_input_tokens += turn_usage.input_tokens;
     *total_output_tokens += turn_usage.output_tokens;
-    record_turn_usage(session_key, &config.model, &turn_usage);
+    record_turn_usage(provider, session_key, chain[*active_model], &turn_usage);
+
+    // Report how much of the context budget the turn used
+    let mut context_event = serde_json::to_value(&context_usage).unwrap_or_else(|_| json!({}));
+    context_event["model"] = json!(chain[*active_model]);
+    context_event["inputTokens"] = json!(turn_usage.input_tokens);
+    broadcast_agent_event(
+        state,
+        run_id,
+        seq.fetch_add(1, Ordering::Relaxed),
+        "context",
+        context_event,
+    );
 
     // Post-flight filtering — MUST run before persistence to avoid storing
     // unfiltered PII/credentials in session history.
@@ -701,16 +983,31 @@ async fn execute_single_turn(
         turn_text
     };
 
-    // Append assistant message to history (after post-flight filtering)
+    // Append assistant message to history (after post-flight filtering).
+    // Thinking stays in the in-memory history so tool-use follow-ups can echo
+    // it back, but is only written to the session store when configured.
     if let Some(msg) = build_assistant_message(
         session_id,
         &turn_text,
+        &thinking,
         &pending_tool_calls,
         turn_usage.output_tokens,
     ) {
+        let stored = if config.persist_thinking || thinking.is_empty() {
+            msg.clone()
+        } else {
+            build_assistant_message(
+                session_id,
+                &turn_text,
+                &[],
+                &pending_tool_calls,
+                turn_usage.output_tokens,
+            )
+            .unwrap_or_else(|| msg.clone())
+        };
         state
             .session_store()
-            .append_message(msg.clone())
+            .append_message(stored)
             .map_err(|e| AgentError::SessionStore(e.to_string()))?;
         history.push(msg);
     }
@@ -727,9 +1024,11 @@ async fn execute_single_turn(
             session_id,
             session_key,
             message_channel,
+            tool_ctx,
             run_id,
             seq,
-        );
+        )
+        .await;
         state
             .session_store()
             .append_messages(&tool_msgs)
@@ -744,7 +1043,8 @@ async fn execute_single_turn(
 /// Execute an agent run to completion.
 ///
 /// This is the core loop: load history → call LLM → stream results →
-/// handle tool calls → append to history → mark complete.
+/// handle tool calls → append to history → mark complete → compact history
+/// if the session crossed its threshold.
 pub async fn execute_run(
     run_id: String,
     session_key: String,
@@ -778,6 +1078,16 @@ pub async fn execute_run(
         .map_err(|e| AgentError::SessionNotFound(format!("{session_key}: {e}")))?;
     let message_channel = session.metadata.channel.clone();
 
+    // Session thinking level (sessions.patch / cron) overrides the agent default
+    if let Some(level) = session.metadata.thinking_level.as_deref() {
+        match ThinkingLevel::parse(level) {
+            Ok(thinking) => config.thinking = thinking,
+            Err(e) => {
+                tracing::warn!(run_id = %run_id, error = %e, "ignoring session thinkingLevel")
+            }
+        }
+    }
+
     if let Some(result) = dispatch_plugin_hook(
         &state,
         "before_agent_start",
@@ -844,7 +1154,7 @@ pub async fn execute_run(
                     history
                         .iter()
                         .rev()
-                        .find(|m| m.role == MessageRole::User)
+                        .find(|m| m.role == MessageRole::User && !is_passive(m))
                         .map(|m| m.content.clone())
                 });
 
@@ -912,6 +1222,32 @@ pub async fn execute_run(
         .get_history(&session.id, None, None)
         .map_err(|e| AgentError::SessionStore(e.to_string()))?;
 
+    let memory_user = crate::memory::user_namespace(
+        message_channel.as_deref(),
+        session.metadata.user_id.as_deref(),
+    );
+    if config.memory.inject {
+        inject_memories(
+            &mut config,
+            &state,
+            session.metadata.agent_id.as_deref(),
+            memory_user.as_deref(),
+            &history,
+        );
+    }
+
+    let tool_ctx = ToolInvokeContext {
+        agent_id: session.metadata.agent_id.clone(),
+        session_key: session_key.clone(),
+        message_channel: message_channel.clone(),
+        recipient_id: session.metadata.chat_id.clone(),
+        user_id: memory_user,
+        cancel_token: cancel_token.clone(),
+        ..Default::default()
+    }
+    .with_server_state(&state);
+
+    let mut active_model = 0;
     for _turn in 0..config.max_turns {
         let should_continue = execute_single_turn(
             &config,
@@ -922,7 +1258,9 @@ pub async fn execute_run(
             &session_key,
             &session.id,
             message_channel.as_deref(),
+            &tool_ctx,
             &seq,
+            &mut active_model,
             &mut history,
             &mut accumulated_text,
             &mut total_input_tokens,
@@ -948,6 +1286,7 @@ pub async fn execute_run(
         &run_id,
         &session_key,
         &seq,
+        config.model_chain()[active_model],
         final_stop_reason,
         total_input_tokens,
         total_output_tokens,
@@ -984,9 +1323,53 @@ pub async fn execute_run(
         }
     }
 
+    // 6. Summarize older history once the session crosses its threshold
+    compaction::auto_compact_if_needed(
+        &state,
+        &session_key,
+        provider.as_ref(),
+        config.model_chain()[active_model],
+        &config.compaction,
+    )
+    .await;
+
     Ok(())
 }
 
+/// Append memories relevant to the latest user message to the system prompt.
+fn inject_memories(
+    config: &mut AgentConfig,
+    state: &WsServerState,
+    agent_id: Option<&str>,
+    user_id: Option<&str>,
+    history: &[ChatMessage],
+) {
+    let Some(query) = history
+        .iter()
+        .rev()
+        .find(|m| m.role == MessageRole::User && !is_passive(m))
+        .map(|m| m.content.as_str())
+    else {
+        return;
+    };
+    let Some(section) = crate::memory::recall_section(
+        state.memory_store(),
+        agent_id.unwrap_or(crate::memory::DEFAULT_AGENT_ID),
+        user_id,
+        query,
+        config.memory.inject_limit,
+        &crate::agent::prompt_guard::TaggingConfig {
+            enabled: config.prompt_guard.enabled && config.prompt_guard.tagging.enabled,
+        },
+    ) else {
+        return;
+    };
+    config.system = Some(match config.system.take() {
+        Some(system) if !system.trim().is_empty() => format!("{system}\n\n{section}"),
+        _ => section,
+    });
+}
+
 /// Sanitize a provider error message before sending to clients.
 ///
 /// Strips potential secrets (API keys, internal URLs with auth) from error
@@ -1295,6 +1678,86 @@ mod tests {
         assert_eq!(run.response, "The time is now.");
     }
 
+    #[tokio::test]
+    async fn test_message_send_tool_enqueues_into_pipeline() {
+        let (state, _tmp) = make_test_state_with_tools();
+        let run_id = "run-message-send";
+        let session_key = "test-session-message-send";
+        let session = setup_session_and_run(&state, session_key, run_id);
+        let mut metadata = session.metadata.clone();
+        metadata.channel = Some("telegram".to_string());
+        metadata.chat_id = Some("chat-99".to_string());
+        state
+            .session_store()
+            .patch_session(&session.id, metadata)
+            .unwrap();
+
+        let provider = Arc::new(MockProvider::new(vec![
+            vec![
+                StreamEvent::ToolUse {
+                    id: "tool_1".to_string(),
+                    name: "message_send".to_string(),
+                    input: serde_json::json!({"channel": "telegram", "text": "on my way"}),
+                },
+                StreamEvent::Stop {
+                    reason: StopReason::ToolUse,
+                    usage: TokenUsage::default(),
+                },
+            ],
+            vec![
+                StreamEvent::TextDelta {
+                    text: "Sent.".to_string(),
+                },
+                StreamEvent::Stop {
+                    reason: StopReason::EndTurn,
+                    usage: TokenUsage::default(),
+                },
+            ],
+        ]));
+        let config = AgentConfig {
+            max_turns: 5,
+            ..Default::default()
+        };
+
+        let result = execute_run(
+            run_id.to_string(),
+            session_key.to_string(),
+            config,
+            state.clone(),
+            provider,
+            CancellationToken::new(),
+        )
+        .await;
+        assert!(result.is_ok(), "execute_run failed: {:?}", result.err());
+
+        let queued = state
+            .message_pipeline()
+            .next_for_channel("telegram")
+            .expect("message_send should enqueue into the shared pipeline");
+        assert_eq!(
+            queued.message.metadata.recipient_id.as_deref(),
+            Some("chat-99")
+        );
+        assert!(matches!(
+            queued.message.content,
+            crate::messages::outbound::MessageContent::Text { ref text } if text == "on my way"
+        ));
+
+        let history = state
+            .session_store()
+            .get_history(&session.id, None, None)
+            .unwrap();
+        let tool_msg = history
+            .iter()
+            .find(|m| m.role == MessageRole::Tool)
+            .expect("tool result should be persisted");
+        assert!(
+            tool_msg.content.contains(&queued.message.id.0),
+            "tool result should report the pipeline message ID: {}",
+            tool_msg.content
+        );
+    }
+
     #[tokio::test]
     async fn test_empty_response_handling() {
         let (state, _tmp) = make_test_state();
@@ -1694,6 +2157,350 @@ mod tests {
         );
     }
 
+    // ============== Model Failover Tests ==============
+
+    /// Provider whose behaviour depends on the requested model: unknown
+    /// models answer with text, listed ones fail with the given outcome.
+    struct PerModelProvider {
+        seen: parking_lot::Mutex<Vec<String>>,
+        /// `(model, Err(message))` fails before streaming; `(model, Ok(message))`
+        /// streams a text delta and then an error event.
+        failures: Vec<(&'static str, Result<&'static str, &'static str>)>,
+    }
+
+    impl PerModelProvider {
+        fn new(failures: Vec<(&'static str, Result<&'static str, &'static str>)>) -> Self {
+            Self {
+                seen: parking_lot::Mutex::new(Vec::new()),
+                failures,
+            }
+        }
+    }
+
+    #[async_trait]
+    impl LlmProvider for PerModelProvider {
+        async fn complete(
+            &self,
+            request: crate::agent::provider::CompletionRequest,
+            _cancel_token: CancellationToken,
+        ) -> Result<mpsc::Receiver<StreamEvent>, crate::agent::AgentError> {
+            self.seen.lock().push(request.model.clone());
+            let failure = self
+                .failures
+                .iter()
+                .find(|(m, _)| *m == request.model)
+                .map(|(_, f)| *f);
+            let events = match failure {
+                Some(Err(message)) => return Err(AgentError::Provider(message.to_string())),
+                Some(Ok(message)) => vec![
+                    StreamEvent::TextDelta {
+                        text: "partial ".to_string(),
+                    },
+                    StreamEvent::Error {
+                        message: message.to_string(),
+                    },
+                ],
+                None => vec![
+                    StreamEvent::TextDelta {
+                        text: format!("from {}", request.model),
+                    },
+                    StreamEvent::Stop {
+                        reason: StopReason::EndTurn,
+                        usage: TokenUsage::default(),
+                    },
+                ],
+            };
+            let (tx, rx) = mpsc::channel(64);
+            tokio::spawn(async move {
+                for event in events {
+                    let _ = tx.send(event).await;
+                }
+            });
+            Ok(rx)
+        }
+    }
+
+    async fn run_with_chain(
+        run_id: &str,
+        provider: Arc<PerModelProvider>,
+        fallbacks: &[&str],
+    ) -> (
+        Arc<WsServerState>,
+        Result<(), AgentError>,
+        tempfile::TempDir,
+    ) {
+        let (state, tmp) = make_test_state();
+        let session_key = format!("session-{run_id}");
+        setup_session_and_run(&state, &session_key, run_id);
+        let config = AgentConfig {
+            model: "primary".to_string(),
+            fallback_models: fallbacks.iter().map(|m| m.to_string()).collect(),
+            max_turns: 1,
+            ..Default::default()
+        };
+        let result = execute_run(
+            run_id.to_string(),
+            session_key,
+            config,
+            state.clone(),
+            provider,
+            CancellationToken::new(),
+        )
+        .await;
+        (state, result, tmp)
+    }
+
+    #[tokio::test]
+    async fn test_failover_on_rate_limit() {
+        let provider = Arc::new(PerModelProvider::new(vec![
+            (
+                "primary",
+                Err("API returned 429 Too Many Requests: slow down"),
+            ),
+            ("second", Err("API returned 503 Service Unavailable: ")),
+        ]));
+        let (state, result, _tmp) =
+            run_with_chain("run-failover-429", provider.clone(), &["second", "third"]).await;
+        assert!(result.is_ok(), "execute_run failed: {:?}", result.err());
+        assert_eq!(*provider.seen.lock(), vec!["primary", "second", "third"]);
+
+        let registry = state.agent_run_registry.lock();
+        let run = registry.get("run-failover-429").unwrap();
+        assert_eq!(run.status, crate::server::ws::AgentRunStatus::Completed);
+        assert_eq!(run.response, "from third");
+    }
+
+    #[tokio::test]
+    async fn test_failover_after_stream_started_discards_partial_output() {
+        let provider = Arc::new(PerModelProvider::new(vec![(
+            "primary",
+            Ok("overloaded_error: Overloaded"),
+        )]));
+        let (state, result, _tmp) =
+            run_with_chain("run-failover-partial", provider.clone(), &["backup"]).await;
+        assert!(result.is_ok(), "execute_run failed: {:?}", result.err());
+        assert_eq!(*provider.seen.lock(), vec!["primary", "backup"]);
+
+        let registry = state.agent_run_registry.lock();
+        let run = registry.get("run-failover-partial").unwrap();
+        assert_eq!(run.response, "from backup");
+    }
+
+    #[tokio::test]
+    async fn test_no_failover_on_auth_error() {
+        let provider = Arc::new(PerModelProvider::new(vec![(
+            "primary",
+            Err("API returned 401 Unauthorized: invalid x-api-key"),
+        )]));
+        let (_state, result, _tmp) =
+            run_with_chain("run-failover-auth", provider.clone(), &["backup"]).await;
+        assert!(matches!(result, Err(AgentError::Provider(_))));
+        assert_eq!(*provider.seen.lock(), vec!["primary"]);
+    }
+
+    #[tokio::test]
+    async fn test_failover_chain_exhausted_returns_last_error() {
+        let provider = Arc::new(PerModelProvider::new(vec![
+            ("primary", Err("API returned 500 Internal Server Error: a")),
+            ("backup", Err("API returned 502 Bad Gateway: b")),
+        ]));
+        let (_state, result, _tmp) =
+            run_with_chain("run-failover-exhausted", provider.clone(), &["backup"]).await;
+        assert!(
+            matches!(&result, Err(AgentError::Provider(msg)) if msg.contains("502")),
+            "expected last provider error, got: {result:?}"
+        );
+        assert_eq!(*provider.seen.lock(), vec!["primary", "backup"]);
+    }
+
+    #[test]
+    fn test_failed_attempt_usage_books_partial_output() {
+        let request = CompletionRequest {
+            model: "primary".to_string(),
+            messages: vec![LlmMessage {
+                role: LlmRole::User,
+                content: vec![ContentBlock::Text {
+                    text: "hello there".to_string(),
+                }],
+            }],
+            system: Some("be brief".to_string()),
+            tools: Vec::new(),
+            max_tokens: 1024,
+            temperature: None,
+            thinking: None,
+            extra: None,
+        };
+
+        let rejected = failed_attempt_usage(&request, &AttemptProgress::default());
+        assert_eq!(rejected.input_tokens, 0);
+        assert_eq!(rejected.output_tokens, 0);
+
+        let progress = AttemptProgress {
+            streamed: true,
+            output_tokens: 12,
+        };
+        let partial = failed_attempt_usage(&request, &progress);
+        assert!(partial.input_tokens > 0);
+        assert_eq!(partial.output_tokens, 12);
+    }
+
+    // ============== Extended Thinking Tests ==============
+
+    /// Provider that records the requested thinking level and replies with a
+    /// signed thinking block followed by text.
+    struct ThinkingProvider {
+        seen: parking_lot::Mutex<Vec<Option<ThinkingLevel>>>,
+    }
+
+    #[async_trait]
+    impl LlmProvider for ThinkingProvider {
+        async fn complete(
+            &self,
+            request: crate::agent::provider::CompletionRequest,
+            _cancel_token: CancellationToken,
+        ) -> Result<mpsc::Receiver<StreamEvent>, crate::agent::AgentError> {
+            self.seen.lock().push(request.thinking);
+            let (tx, rx) = mpsc::channel(64);
+            tokio::spawn(async move {
+                for event in [
+                    StreamEvent::ThinkingDelta {
+                        text: "Let me ".to_string(),
+                    },
+                    StreamEvent::ThinkingDelta {
+                        text: "think".to_string(),
+                    },
+                    StreamEvent::ThinkingSignature {
+                        signature: "sig".to_string(),
+                    },
+                    StreamEvent::TextDelta {
+                        text: "Answer".to_string(),
+                    },
+                    StreamEvent::Stop {
+                        reason: StopReason::EndTurn,
+                        usage: TokenUsage::default(),
+                    },
+                ] {
+                    let _ = tx.send(event).await;
+                }
+            });
+            Ok(rx)
+        }
+    }
+
+    async fn run_thinking(
+        run_id: &str,
+        persist_thinking: bool,
+    ) -> (Arc<ThinkingProvider>, Vec<ChatMessage>, tempfile::TempDir) {
+        let (state, tmp) = make_test_state();
+        let session_key = format!("session-{run_id}");
+        let session = setup_session_and_run(&state, &session_key, run_id);
+        state
+            .session_store()
+            .patch_session(
+                &session.id,
+                sessions::SessionMetadata {
+                    thinking_level: Some("high".to_string()),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+
+        let provider = Arc::new(ThinkingProvider {
+            seen: parking_lot::Mutex::new(Vec::new()),
+        });
+        let config = AgentConfig {
+            thinking: Some(ThinkingLevel::Low),
+            persist_thinking,
+            max_turns: 1,
+            ..Default::default()
+        };
+        execute_run(
+            run_id.to_string(),
+            session_key,
+            config,
+            state.clone(),
+            provider.clone(),
+            CancellationToken::new(),
+        )
+        .await
+        .unwrap();
+
+        let history = state
+            .session_store()
+            .get_history(&session.id, None, None)
+            .unwrap();
+        (provider, history, tmp)
+    }
+
+    #[tokio::test]
+    async fn test_session_thinking_level_overrides_config() {
+        let (provider, _history, _tmp) = run_thinking("run-thinking-level", false).await;
+        assert_eq!(*provider.seen.lock(), vec![Some(ThinkingLevel::High)]);
+    }
+
+    #[tokio::test]
+    async fn test_thinking_not_persisted_by_default() {
+        let (_provider, history, _tmp) = run_thinking("run-thinking-redact", false).await;
+        let reply = history.last().unwrap();
+        assert_eq!(reply.content, "Answer");
+        assert!(reply.metadata.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_thinking_persisted_when_configured() {
+        let (_provider, history, _tmp) = run_thinking("run-thinking-persist", true).await;
+        let reply = history.last().unwrap();
+        assert_eq!(reply.content, "Answer");
+        let thinking = &reply.metadata.as_ref().unwrap()["thinking"];
+        assert_eq!(thinking[0]["thinking"], "Let me think");
+        assert_eq!(thinking[0]["signature"], "sig");
+    }
+
+    #[test]
+    fn test_inject_memories_appends_relevant_section() {
+        let (state, _tmp) = make_test_state();
+        state
+            .memory_store()
+            .put(crate::memory::MemoryWrite {
+                agent_id: "main".to_string(),
+                user_id: Some("telegram:42".to_string()),
+                key: "diet".to_string(),
+                content: "Vegetarian".to_string(),
+                ..Default::default()
+            })
+            .unwrap();
+        let history = vec![
+            ChatMessage::user("s", "What should I cook for my diet?"),
+            ChatMessage::assistant("s", "Let me check."),
+        ];
+
+        let mut config = AgentConfig {
+            system: Some("Be brief.".to_string()),
+            ..Default::default()
+        };
+        inject_memories(
+            &mut config,
+            &state,
+            Some("main"),
+            Some("telegram:42"),
+            &history,
+        );
+        let system = config.system.unwrap();
+        assert!(system.starts_with("Be brief.\n\n## Relevant memories"));
+        assert!(system.contains("- diet: Vegetarian"));
+
+        // Other users never see the memory
+        let mut config = AgentConfig::default();
+        inject_memories(
+            &mut config,
+            &state,
+            Some("main"),
+            Some("telegram:7"),
+            &history,
+        );
+        assert!(config.system.is_none());
+    }
+
     // ============== Tool Policy Enforcement Tests ==============
 
     #[tokio::test]
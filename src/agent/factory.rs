@@ -11,6 +11,7 @@ use tracing::{info, warn};
 
 use crate::agent;
 use crate::agent::provider::MultiProvider;
+use crate::plugins::PluginRegistry;
 
 /// Try to build a provider from an API key + optional base URL.
 ///
@@ -123,8 +124,12 @@ fn try_build_ollama_provider(
 
 /// Build all configured LLM providers from the config and environment.
 ///
+/// Provider plugins in `plugins` are added alongside the built-in providers.
 /// Returns `None` if no providers are configured.
-pub fn build_providers(cfg: &Value) -> Result<Option<MultiProvider>, Box<dyn std::error::Error>> {
+pub fn build_providers(
+    cfg: &Value,
+    plugins: Option<&PluginRegistry>,
+) -> Result<Option<MultiProvider>, Box<dyn std::error::Error>> {
     // Anthropic
     let anthropic_api_key = std::env::var("ANTHROPIC_API_KEY").ok().or_else(|| {
         cfg.get("anthropic")
@@ -276,11 +281,14 @@ pub fn build_providers(cfg: &Value) -> Result<Option<MultiProvider>, Box<dyn std
     };
 
     // Build multi-provider dispatcher
-    let multi_provider = MultiProvider::new(anthropic_provider, openai_provider)
+    let mut multi_provider = MultiProvider::new(anthropic_provider, openai_provider)
         .with_ollama(ollama_provider)
         .with_gemini(gemini_provider)
         .with_venice(venice_provider)
         .with_bedrock(bedrock);
+    if let Some(registry) = plugins {
+        multi_provider = attach_plugin_providers(multi_provider, registry);
+    }
 
     if multi_provider.has_any_provider() {
         Ok(Some(multi_provider))
@@ -289,6 +297,43 @@ pub fn build_providers(cfg: &Value) -> Result<Option<MultiProvider>, Box<dyn std
     }
 }
 
+/// Add every registered provider plugin to `multi`, routed by plugin ID.
+///
+/// Each plugin model's cost and context window are registered under both
+/// `<plugin-id>:<model>` and `<plugin-id>/<model>`. Plugins whose `get-info`
+/// fails are skipped.
+pub fn attach_plugin_providers(
+    mut multi: MultiProvider,
+    registry: &PluginRegistry,
+) -> MultiProvider {
+    let models: std::collections::HashMap<_, _> =
+        registry.get_provider_models().into_iter().collect();
+    for (plugin_id, plugin) in registry.get_providers() {
+        let provider = match agent::plugin_provider::PluginProvider::new(plugin_id.clone(), plugin)
+        {
+            Ok(p) => p,
+            Err(e) => {
+                warn!(plugin_id = %plugin_id, "Skipping provider plugin: {}", e);
+                continue;
+            }
+        };
+        for model in models.get(&plugin_id).into_iter().flatten() {
+            let pricing = crate::usage::ModelPricing {
+                input_cost_per_mtok: model.cost.input,
+                output_cost_per_mtok: model.cost.output,
+            };
+            for sep in [':', '/'] {
+                let full_id = format!("{plugin_id}{sep}{}", model.id);
+                crate::usage::register_model_pricing(&full_id, pricing.clone());
+                agent::context::register_context_window(&full_id, model.context_window);
+            }
+        }
+        info!(plugin_id = %plugin_id, "Provider plugin configured");
+        multi = multi.with_plugin_provider(plugin_id, Arc::new(provider));
+    }
+    multi
+}
+
 /// A fingerprint of the provider configuration, used for change detection.
 ///
 /// API keys are hashed (SHA-256 prefix) rather than stored.
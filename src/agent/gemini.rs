@@ -106,6 +106,26 @@ impl GeminiProvider {
                             }
                         }));
                     }
+                    // Gemini thought summaries are not replayed
+                    ContentBlock::Thinking { .. } => {}
+                    ContentBlock::Image { media_type, data } => {
+                        parts.push(json!({
+                            "inlineData": { "mimeType": media_type, "data": data }
+                        }));
+                    }
+                    ContentBlock::Document {
+                        media_type, data, ..
+                    } => {
+                        if media_type == "application/pdf" || media_type.starts_with("text/") {
+                            parts.push(json!({
+                                "inlineData": { "mimeType": media_type, "data": data }
+                            }));
+                        } else {
+                            parts.push(json!({
+                                "text": block.attachment_placeholder().unwrap_or_default()
+                            }));
+                        }
+                    }
                 }
             }
 
@@ -144,6 +164,12 @@ impl GeminiProvider {
         if let Some(temp) = request.temperature {
             generation_config["temperature"] = json!(temp);
         }
+        if let Some(level) = request.thinking {
+            generation_config["thinkingConfig"] = json!({
+                "thinkingBudget": level.budget_tokens(),
+                "includeThoughts": true,
+            });
+        }
         body["generationConfig"] = generation_config;
 
         body
@@ -233,6 +259,96 @@ impl LlmProvider for GeminiProvider {
 
         Ok(rx)
     }
+
+    async fn embed(&self, request: EmbeddingRequest) -> Result<EmbeddingResponse, AgentError> {
+        let model_name = strip_gemini_prefix(&request.model);
+        let url = format!(
+            "{}/v1beta/models/{}:batchEmbedContents?key={}",
+            self.base_url, model_name, self.api_key
+        );
+
+        let response = self
+            .client
+            .post(&url)
+            .header("content-type", "application/json")
+            .json(&build_embeddings_body(model_name, &request))
+            .send()
+            .await
+            .map_err(|e| AgentError::Provider(format!("HTTP request failed: {e}")))?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response
+                .text()
+                .await
+                .unwrap_or_else(|_| "<unreadable>".to_string());
+            return Err(AgentError::Provider(format!(
+                "API returned {status}: {body}"
+            )));
+        }
+
+        let body: Value = response.json().await.map_err(|e| {
+            AgentError::Provider(format!("failed to parse embeddings response: {e}"))
+        })?;
+        let embeddings = parse_embeddings_response(&body)?;
+
+        // batchEmbedContents does not report usage; estimate it like the
+        // context-window budget does.
+        let input_tokens = request
+            .input
+            .iter()
+            .map(|text| u64::from(crate::agent::context::estimate_tokens(text)))
+            .sum();
+        Ok(EmbeddingResponse {
+            embeddings,
+            input_tokens,
+        })
+    }
+
+    fn provider_name(&self, _model: &str) -> &str {
+        "gemini"
+    }
+}
+
+/// Build the `batchEmbedContents` body: one embed request per input.
+fn build_embeddings_body(model_name: &str, request: &EmbeddingRequest) -> Value {
+    let requests = request
+        .input
+        .iter()
+        .map(|text| {
+            let mut item = json!({
+                "model": format!("models/{model_name}"),
+                "content": { "parts": [{ "text": text }] },
+            });
+            if let Some(dimensions) = request.dimensions {
+                item["outputDimensionality"] = json!(dimensions);
+            }
+            item
+        })
+        .collect::<Vec<_>>();
+    json!({ "requests": requests })
+}
+
+/// Extract the vectors from a `batchEmbedContents` response.
+fn parse_embeddings_response(body: &Value) -> Result<Vec<Vec<f32>>, AgentError> {
+    body.get("embeddings")
+        .and_then(|v| v.as_array())
+        .ok_or_else(|| AgentError::Provider("embeddings response has no embeddings".to_string()))?
+        .iter()
+        .map(|item| {
+            item.get("values")
+                .and_then(|v| v.as_array())
+                .map(|values| {
+                    values
+                        .iter()
+                        .filter_map(|v| v.as_f64().map(|f| f as f32))
+                        .collect()
+                })
+                .ok_or_else(|| {
+                    AgentError::Provider("embeddings response item has no values".to_string())
+                })
+        })
+        .collect()
 }
 
 /// Maximum SSE line buffer size (1 MB). If a single SSE line exceeds this,
@@ -440,14 +556,22 @@ fn parse_gemini_finish_chunk(
     events
 }
 
-/// Collect stream events from Gemini content parts (text deltas and function calls).
+/// Collect stream events from Gemini content parts (text and thought deltas,
+/// function calls).
 fn collect_gemini_part_events(parts: &[Value]) -> Vec<StreamEvent> {
     let mut events = Vec::new();
     for part in parts {
         if let Some(text) = part.get("text").and_then(|v| v.as_str()) {
+            let is_thought = part
+                .get("thought")
+                .and_then(|v| v.as_bool())
+                .unwrap_or(false);
             if !text.is_empty() {
-                events.push(StreamEvent::TextDelta {
-                    text: text.to_string(),
+                let text = text.to_string();
+                events.push(if is_thought {
+                    StreamEvent::ThinkingDelta { text }
+                } else {
+                    StreamEvent::TextDelta { text }
                 });
             }
         }
@@ -582,6 +706,7 @@ mod tests {
             tools: vec![],
             max_tokens: 1024,
             temperature: Some(0.7),
+            thinking: None,
             extra: None,
         };
         let body = provider.build_body(&request);
@@ -606,6 +731,45 @@ mod tests {
         assert!(body.get("tools").is_none());
     }
 
+    #[test]
+    fn test_build_body_inline_attachments() {
+        let provider = GeminiProvider::new("test-key".to_string()).unwrap();
+        let request = CompletionRequest {
+            model: "gemini-2.0-flash".to_string(),
+            messages: vec![LlmMessage {
+                role: LlmRole::User,
+                content: vec![
+                    ContentBlock::Image {
+                        media_type: "image/webp".to_string(),
+                        data: "UklG".to_string(),
+                    },
+                    ContentBlock::Document {
+                        media_type: "application/pdf".to_string(),
+                        data: "JVBE".to_string(),
+                        name: None,
+                    },
+                    ContentBlock::Document {
+                        media_type: "application/zip".to_string(),
+                        data: "UEsD".to_string(),
+                        name: Some("src.zip".to_string()),
+                    },
+                ],
+            }],
+            system: None,
+            tools: vec![],
+            max_tokens: 1024,
+            temperature: None,
+            thinking: None,
+            extra: None,
+        };
+        let body = provider.build_body(&request);
+        let parts = &body["contents"][0]["parts"];
+        assert_eq!(parts[0]["inlineData"]["mimeType"], "image/webp");
+        assert_eq!(parts[0]["inlineData"]["data"], "UklG");
+        assert_eq!(parts[1]["inlineData"]["mimeType"], "application/pdf");
+        assert!(parts[2]["text"].as_str().unwrap().contains("src.zip"));
+    }
+
     #[test]
     fn test_build_body_no_system() {
         let provider = GeminiProvider::new("test-key".to_string()).unwrap();
@@ -621,6 +785,7 @@ mod tests {
             tools: vec![],
             max_tokens: 1024,
             temperature: None,
+            thinking: None,
             extra: None,
         };
         let body = provider.build_body(&request);
@@ -656,6 +821,7 @@ mod tests {
             }],
             max_tokens: 4096,
             temperature: None,
+            thinking: None,
             extra: None,
         };
         let body = provider.build_body(&request);
@@ -703,6 +869,7 @@ mod tests {
             tools: vec![],
             max_tokens: 1024,
             temperature: None,
+            thinking: None,
             extra: None,
         };
         let body = provider.build_body(&request);
@@ -765,6 +932,7 @@ mod tests {
             tools: vec![],
             max_tokens: 1024,
             temperature: None,
+            thinking: None,
             extra: None,
         };
         let body = provider.build_body(&request);
@@ -793,6 +961,7 @@ mod tests {
             tools: vec![],
             max_tokens: 1024,
             temperature: None,
+            thinking: None,
             extra: None,
         };
         let body = provider.build_body(&request);
@@ -800,6 +969,25 @@ mod tests {
         assert_eq!(contents[0]["role"], "model");
     }
 
+    #[test]
+    fn test_build_body_with_thinking() {
+        let provider = GeminiProvider::new("test-key".to_string()).unwrap();
+        let request = CompletionRequest {
+            model: "gemini-2.5-flash".to_string(),
+            messages: vec![],
+            system: None,
+            tools: vec![],
+            max_tokens: 1024,
+            temperature: None,
+            thinking: Some(ThinkingLevel::High),
+            extra: None,
+        };
+        let body = provider.build_body(&request);
+        let thinking = &body["generationConfig"]["thinkingConfig"];
+        assert_eq!(thinking["thinkingBudget"], 32_000);
+        assert_eq!(thinking["includeThoughts"], true);
+    }
+
     // ==================== SSE parsing tests ====================
 
     #[test]
@@ -819,6 +1007,21 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_parse_thought_part_as_thinking_delta() {
+        let mut usage = TokenUsage::default();
+        let mut finish_reason = None;
+        let events = parse_gemini_sse_data(
+            r#"{"candidates":[{"content":{"parts":[{"text":"Considering","thought":true},{"text":"Answer"}],"role":"model"}}]}"#,
+            &mut usage,
+            &mut finish_reason,
+        )
+        .unwrap();
+        assert_eq!(events.len(), 2);
+        assert!(matches!(&events[0], StreamEvent::ThinkingDelta { text } if text == "Considering"));
+        assert!(matches!(&events[1], StreamEvent::TextDelta { text } if text == "Answer"));
+    }
+
     #[test]
     fn test_parse_tool_call() {
         let mut usage = TokenUsage::default();
@@ -1185,4 +1388,31 @@ mod tests {
             events,
         );
     }
+
+    // ==================== embeddings tests ====================
+
+    #[test]
+    fn test_build_embeddings_body() {
+        let request = EmbeddingRequest {
+            model: "gemini/text-embedding-004".to_string(),
+            input: vec!["hello".to_string(), "world".to_string()],
+            dimensions: Some(128),
+        };
+        let body = build_embeddings_body("text-embedding-004", &request);
+        let requests = body["requests"].as_array().unwrap();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[0]["model"], "models/text-embedding-004");
+        assert_eq!(requests[1]["content"]["parts"][0]["text"], "world");
+        assert_eq!(requests[0]["outputDimensionality"], 128);
+    }
+
+    #[test]
+    fn test_parse_embeddings_response() {
+        let body = json!({
+            "embeddings": [{"values": [0.1, 0.2]}, {"values": [0.3]}]
+        });
+        let embeddings = parse_embeddings_response(&body).unwrap();
+        assert_eq!(embeddings, vec![vec![0.1, 0.2], vec![0.3]]);
+        assert!(parse_embeddings_response(&json!({})).is_err());
+    }
 }
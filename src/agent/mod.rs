@@ -8,6 +8,7 @@ pub mod bedrock;
 pub mod builtin_tools;
 pub mod channel_tools;
 pub mod classifier;
+pub mod compaction;
 pub mod context;
 pub mod executor;
 pub mod exfiltration;
@@ -16,13 +17,17 @@ pub mod gemini;
 pub mod ollama;
 pub mod openai;
 pub mod output_sanitizer;
+pub mod plugin_provider;
 pub mod prompt_guard;
 pub mod provider;
+pub mod routing;
 pub mod sandbox;
+pub mod schedule_tools;
 pub mod tool_policy;
 pub mod tools;
 pub mod venice;
 
+use std::collections::HashMap;
 use std::sync::Arc;
 
 use futures_util::FutureExt;
@@ -77,6 +82,9 @@ pub enum AgentError {
 pub struct AgentConfig {
     /// LLM model identifier (e.g., "claude-sonnet-4-20250514").
     pub model: String,
+    /// Ordered fallback models tried when `model` fails with a retryable
+    /// provider error (rate limit, 5xx, timeout, missing provider).
+    pub fallback_models: Vec<String>,
     /// Optional system prompt prepended to context.
     pub system: Option<String>,
     /// Maximum agentic turns (LLM round-trips). Default 25.
@@ -85,6 +93,13 @@ pub struct AgentConfig {
     pub max_tokens: u32,
     /// Sampling temperature. None means provider default.
     pub temperature: Option<f64>,
+    /// Extended thinking level. None disables thinking.
+    pub thinking: Option<provider::ThinkingLevel>,
+    /// Broadcast thinking deltas as the `thinking` agent stream. Default `true`.
+    pub stream_thinking: bool,
+    /// Store thinking blocks in session history. When `false` (default),
+    /// thinking is kept only for the duration of the run.
+    pub persist_thinking: bool,
     /// Whether to deliver the final message via the channel pipeline.
     pub deliver: bool,
     /// Tool policy controlling which tools this agent may invoke.
@@ -103,6 +118,19 @@ pub struct AgentConfig {
     pub output_sanitizer: output_sanitizer::OutputSanitizerConfig,
     /// Inbound message classifier configuration (off by default).
     pub classifier: Option<classifier::ClassifierConfig>,
+    /// Session compaction settings (summary model, threshold, kept messages).
+    pub compaction: compaction::CompactionConfig,
+    /// Long-term memory settings (prompt injection).
+    pub memory: crate::memory::MemoryConfig,
+    /// The agent's workspace directory, from `workspace`. Sandboxed tool
+    /// processes are allowed to access it.
+    pub workspace: Option<String>,
+    /// Cap on the context window from `contextTokens`. `None` uses the
+    /// model's full window.
+    pub context_tokens: Option<u32>,
+    /// Context windows declared under `models.providers.*.models`, keyed by
+    /// lowercase model ID (both `<provider>:<model>` and the bare model).
+    pub context_windows: HashMap<String, u32>,
     /// Provider-specific parameters injected into the request body.
     ///
     /// Populated from the `venice_parameters` key in WS/HTTP requests.
@@ -115,10 +143,14 @@ impl Default for AgentConfig {
     fn default() -> Self {
         Self {
             model: DEFAULT_MODEL.to_string(),
+            fallback_models: Vec::new(),
             system: None,
             max_turns: 25,
             max_tokens: 8192,
             temperature: None,
+            thinking: None,
+            stream_thinking: true,
+            persist_thinking: false,
             deliver: false,
             tool_policy: ToolPolicy::default(),
             exfiltration_guard: false,
@@ -126,11 +158,40 @@ impl Default for AgentConfig {
             process_sandbox: sandbox::ProcessSandboxConfig::default(),
             output_sanitizer: output_sanitizer::OutputSanitizerConfig::default(),
             classifier: None,
+            compaction: compaction::CompactionConfig::default(),
+            memory: crate::memory::MemoryConfig::default(),
+            workspace: None,
+            context_tokens: None,
+            context_windows: HashMap::new(),
             extra: None,
         }
     }
 }
 
+impl AgentConfig {
+    /// The primary model followed by its fallbacks, without duplicates.
+    pub fn model_chain(&self) -> Vec<&str> {
+        let mut chain: Vec<&str> = vec![self.model.as_str()];
+        for model in &self.fallback_models {
+            if !model.trim().is_empty() && !chain.contains(&model.as_str()) {
+                chain.push(model.as_str());
+            }
+        }
+        chain
+    }
+
+    /// Context window for `model`: configured metadata, then the built-in
+    /// table, capped by `context_tokens`.
+    pub fn context_window(&self, model: &str) -> u32 {
+        let window = self
+            .context_windows
+            .get(&model.to_lowercase())
+            .copied()
+            .unwrap_or_else(|| context::model_context_window(model));
+        self.context_tokens.map_or(window, |cap| window.min(cap))
+    }
+}
+
 /// Apply agent config overrides from the global configuration object.
 ///
 /// This allows runtime toggles for safety features, tool policy, and classifier
@@ -151,6 +212,14 @@ pub fn apply_agent_config_from_settings(
         }
     }
 
+    if let Some(providers) = settings
+        .get("models")
+        .and_then(|v| v.get("providers"))
+        .and_then(|v| v.as_object())
+    {
+        config.context_windows = configured_context_windows(providers);
+    }
+
     let agents = match settings.get("agents").and_then(|v| v.as_object()) {
         Some(a) => a,
         None => return,
@@ -194,9 +263,44 @@ pub fn apply_agent_config_from_settings(
     if let Some(entry) = select_agent_entry(agents, agent_id) {
         apply_agent_overrides(config, entry);
     }
+
+    if let Some(workspace) = &config.workspace {
+        if !config.process_sandbox.allowed_paths.contains(workspace) {
+            config.process_sandbox.allowed_paths.push(workspace.clone());
+        }
+    }
+}
+
+/// Collect `contextWindow` values from `models.providers.<provider>.models`,
+/// which may be a map keyed by model ID or a list of `{ id, ... }` entries.
+fn configured_context_windows(providers: &serde_json::Map<String, Value>) -> HashMap<String, u32> {
+    let mut windows = HashMap::new();
+    for (provider_id, provider) in providers {
+        let entries: Vec<(&str, &Value)> = match provider.get("models") {
+            Some(Value::Object(models)) => models.iter().map(|(id, m)| (id.as_str(), m)).collect(),
+            Some(Value::Array(models)) => models
+                .iter()
+                .filter_map(|m| Some((m.get("id")?.as_str()?, m)))
+                .collect(),
+            _ => continue,
+        };
+        for (model_id, model) in entries {
+            let Some(tokens) = model
+                .get("contextWindow")
+                .and_then(|v| v.as_u64())
+                .filter(|t| *t > 0)
+            else {
+                continue;
+            };
+            let tokens = tokens.min(u32::MAX as u64) as u32;
+            windows.insert(format!("{provider_id}:{model_id}").to_lowercase(), tokens);
+            windows.insert(model_id.to_lowercase(), tokens);
+        }
+    }
+    windows
 }
 
-fn select_agent_entry<'a>(
+pub(crate) fn select_agent_entry<'a>(
     agents: &'a serde_json::Map<String, Value>,
     agent_id: Option<&str>,
 ) -> Option<&'a serde_json::Map<String, Value>> {
@@ -230,10 +334,28 @@ fn select_agent_entry<'a>(
 }
 
 fn apply_agent_overrides(config: &mut AgentConfig, agent_obj: &serde_json::Map<String, Value>) {
-    if let Some(model) = agent_obj.get("model").and_then(|v| v.as_str()) {
-        if !model.trim().is_empty() {
+    // `model` is either a model ID or `{ "primary": ..., "fallbacks": [...] }`
+    match agent_obj.get("model") {
+        Some(Value::String(model)) if !model.trim().is_empty() => {
             config.model = model.to_string();
+            config.fallback_models.clear();
+        }
+        Some(Value::Object(model_obj)) => {
+            if let Some(primary) = model_obj.get("primary").and_then(|v| v.as_str()) {
+                if !primary.trim().is_empty() {
+                    config.model = primary.to_string();
+                }
+            }
+            if let Some(fallbacks) = model_obj.get("fallbacks").and_then(|v| v.as_array()) {
+                config.fallback_models = fallbacks
+                    .iter()
+                    .filter_map(|v| v.as_str())
+                    .filter(|m| !m.trim().is_empty())
+                    .map(String::from)
+                    .collect();
+            }
         }
+        _ => {}
     }
 
     if let Some(system) = agent_obj.get("system").and_then(|v| v.as_str()) {
@@ -262,10 +384,47 @@ fn apply_agent_overrides(config: &mut AgentConfig, agent_obj: &serde_json::Map<S
         }
     }
 
+    if let Some(context_tokens) = agent_obj
+        .get("contextTokens")
+        .or_else(|| agent_obj.get("context_tokens"))
+        .and_then(|v| v.as_u64())
+    {
+        if context_tokens > 0 {
+            config.context_tokens = Some(context_tokens.min(u32::MAX as u64) as u32);
+        }
+    }
+
     if let Some(temp) = agent_obj.get("temperature").and_then(|v| v.as_f64()) {
         config.temperature = Some(temp);
     }
 
+    if let Some(level) = agent_obj
+        .get("thinking")
+        .or_else(|| agent_obj.get("thinkingDefault"))
+        .and_then(|v| v.as_str())
+    {
+        match provider::ThinkingLevel::parse(level) {
+            Ok(thinking) => config.thinking = thinking,
+            Err(e) => warn!(error = %e, "invalid agent thinking level; ignoring"),
+        }
+    }
+
+    if let Some(stream) = agent_obj
+        .get("streamThinking")
+        .or_else(|| agent_obj.get("stream_thinking"))
+        .and_then(|v| v.as_bool())
+    {
+        config.stream_thinking = stream;
+    }
+
+    if let Some(persist) = agent_obj
+        .get("persistThinking")
+        .or_else(|| agent_obj.get("persist_thinking"))
+        .and_then(|v| v.as_bool())
+    {
+        config.persist_thinking = persist;
+    }
+
     if let Some(deliver) = agent_obj.get("deliver").and_then(|v| v.as_bool()) {
         config.deliver = deliver;
     }
@@ -321,6 +480,26 @@ fn apply_agent_overrides(config: &mut AgentConfig, agent_obj: &serde_json::Map<S
             Err(e) => warn!(error = %e, "invalid agent classifier config; classifier disabled"),
         }
     }
+
+    if let Some(compaction_value) = agent_obj.get("compaction") {
+        match serde_json::from_value(compaction_value.clone()) {
+            Ok(cfg) => config.compaction = cfg,
+            Err(e) => warn!(error = %e, "invalid agent compaction config; using defaults"),
+        }
+    }
+
+    if let Some(memory_value) = agent_obj.get("memory") {
+        match serde_json::from_value(memory_value.clone()) {
+            Ok(cfg) => config.memory = cfg,
+            Err(e) => warn!(error = %e, "invalid agent memory config; using defaults"),
+        }
+    }
+
+    if let Some(workspace) = agent_obj.get("workspace").and_then(|v| v.as_str()) {
+        if !workspace.trim().is_empty() {
+            config.workspace = Some(workspace.to_string());
+        }
+    }
 }
 
 fn parse_tool_policy_string(value: &str) -> Option<ToolPolicy> {
@@ -647,4 +826,140 @@ mod tests {
             "run must not be stuck in Queued after panic"
         );
     }
+
+    #[test]
+    fn test_model_fallback_chain_from_settings() {
+        let settings = serde_json::json!({
+            "agents": {
+                "defaults": {
+                    "model": {
+                        "primary": "claude-sonnet-4-20250514",
+                        "fallbacks": ["gpt-4o", "ollama:llama3", "gpt-4o", ""]
+                    }
+                },
+                "list": [{ "id": "local", "model": "ollama:llama3" }]
+            }
+        });
+
+        let mut config = AgentConfig::default();
+        apply_agent_config_from_settings(&mut config, &settings, None);
+        assert_eq!(config.model, "claude-sonnet-4-20250514");
+        assert_eq!(
+            config.model_chain(),
+            vec!["claude-sonnet-4-20250514", "gpt-4o", "ollama:llama3"]
+        );
+
+        // A plain model string on the agent entry replaces the inherited chain
+        let mut config = AgentConfig::default();
+        apply_agent_config_from_settings(&mut config, &settings, Some("local"));
+        assert_eq!(config.model_chain(), vec!["ollama:llama3"]);
+    }
+
+    #[test]
+    fn test_thinking_settings_from_agent_defaults() {
+        let settings = serde_json::json!({
+            "agents": {
+                "defaults": {
+                    "thinkingDefault": "medium",
+                    "streamThinking": false,
+                    "persistThinking": true
+                }
+            }
+        });
+        let mut config = AgentConfig::default();
+        apply_agent_config_from_settings(&mut config, &settings, None);
+        assert_eq!(config.thinking, Some(provider::ThinkingLevel::Medium));
+        assert!(!config.stream_thinking);
+        assert!(config.persist_thinking);
+    }
+
+    #[test]
+    fn test_compaction_settings_from_agent_defaults() {
+        let settings = serde_json::json!({
+            "agents": {
+                "defaults": {
+                    "compaction": { "mode": "safeguard", "model": "claude-haiku-4-5", "keepRecent": 10 }
+                },
+                "list": [{ "id": "quiet", "compaction": { "auto": false } }]
+            }
+        });
+        let mut config = AgentConfig::default();
+        apply_agent_config_from_settings(&mut config, &settings, None);
+        assert_eq!(config.compaction.model, "claude-haiku-4-5");
+        assert_eq!(config.compaction.keep_recent, 10);
+        assert!(config.compaction.auto);
+
+        let mut config = AgentConfig::default();
+        apply_agent_config_from_settings(&mut config, &settings, Some("quiet"));
+        assert!(!config.compaction.auto);
+    }
+
+    #[test]
+    fn test_memory_settings_from_agent_defaults() {
+        let settings = serde_json::json!({
+            "agents": {
+                "defaults": { "memory": { "inject": true, "injectLimit": 3 } },
+                "list": [{ "id": "forgetful", "memory": { "inject": false } }]
+            }
+        });
+        let mut config = AgentConfig::default();
+        apply_agent_config_from_settings(&mut config, &settings, None);
+        assert!(config.memory.inject);
+        assert_eq!(config.memory.inject_limit, 3);
+
+        let mut config = AgentConfig::default();
+        apply_agent_config_from_settings(&mut config, &settings, Some("forgetful"));
+        assert!(!config.memory.inject);
+    }
+
+    #[test]
+    fn test_workspace_from_agent_entry_is_sandbox_readable() {
+        let settings = serde_json::json!({
+            "agents": {
+                "list": [
+                    { "id": "main", "default": true },
+                    { "id": "support", "workspace": "/srv/support" }
+                ]
+            }
+        });
+        let mut config = AgentConfig::default();
+        apply_agent_config_from_settings(&mut config, &settings, None);
+        assert!(config.workspace.is_none());
+
+        let mut config = AgentConfig::default();
+        apply_agent_config_from_settings(&mut config, &settings, Some("support"));
+        assert_eq!(config.workspace.as_deref(), Some("/srv/support"));
+        assert!(config
+            .process_sandbox
+            .allowed_paths
+            .contains(&"/srv/support".to_string()));
+    }
+
+    #[test]
+    fn test_context_window_from_settings() {
+        let settings = serde_json::json!({
+            "models": {
+                "providers": {
+                    "local": { "models": { "tiny": { "contextWindow": 8192 } } },
+                    "gw": { "models": [{ "id": "big", "contextWindow": 1000000 }] }
+                }
+            },
+            "agents": {
+                "defaults": { "contextTokens": 200000 },
+                "list": [{ "id": "capped", "contextTokens": 50000 }]
+            }
+        });
+        let mut config = AgentConfig::default();
+        apply_agent_config_from_settings(&mut config, &settings, None);
+        assert_eq!(config.context_window("local:tiny"), 8192);
+        assert_eq!(config.context_window("TINY"), 8192);
+        // contextTokens caps even a larger declared window
+        assert_eq!(config.context_window("gw:big"), 200_000);
+        assert_eq!(config.context_window("gpt-4o"), 128_000);
+
+        let mut config = AgentConfig::default();
+        apply_agent_config_from_settings(&mut config, &settings, Some("capped"));
+        assert_eq!(config.context_window("claude-sonnet-4-20250514"), 50_000);
+        assert_eq!(config.context_window("local:tiny"), 8192);
+    }
 }
@@ -115,6 +115,16 @@ impl OllamaProvider {
             body["temperature"] = json!(temp);
         }
 
+        // Ollama's OpenAI-compat endpoint accepts low/medium/high and streams
+        // the model's thinking as `reasoning` deltas
+        if let Some(level) = request.thinking {
+            let effort = match level {
+                ThinkingLevel::Minimal => "low",
+                other => other.reasoning_effort(),
+            };
+            body["reasoning_effort"] = json!(effort);
+        }
+
         append_tools_ollama(&request.tools, &mut body);
 
         body
@@ -204,6 +214,16 @@ fn convert_user_message_ollama(msg: &LlmMessage, messages: &mut Vec<Value>) {
                 _ => {}
             }
         }
+    } else if msg.content.iter().any(|b| {
+        matches!(
+            b,
+            ContentBlock::Image { .. } | ContentBlock::Document { .. }
+        )
+    }) {
+        messages.push(json!({
+            "role": "user",
+            "content": user_content_parts_ollama(&msg.content),
+        }));
     } else {
         let text = collect_text_blocks_ollama(&msg.content);
         if !text.is_empty() {
@@ -215,6 +235,34 @@ fn convert_user_message_ollama(msg: &LlmMessage, messages: &mut Vec<Value>) {
     }
 }
 
+/// Build the content-parts array for a user message with attachments.
+///
+/// Ollama accepts images as base64 data URLs; text documents are inlined and
+/// other documents are replaced by a placeholder.
+fn user_content_parts_ollama(content: &[ContentBlock]) -> Vec<Value> {
+    use serde_json::json;
+
+    content
+        .iter()
+        .filter_map(|block| match block {
+            ContentBlock::Text { text } => Some(json!({"type": "text", "text": text})),
+            ContentBlock::Image { media_type, data } => Some(json!({
+                "type": "image_url",
+                "image_url": {"url": format!("data:{media_type};base64,{data}")},
+            })),
+            ContentBlock::Document {
+                media_type, data, ..
+            } => {
+                let text = decode_text_document(media_type, data)
+                    .or_else(|| block.attachment_placeholder())
+                    .unwrap_or_default();
+                Some(json!({"type": "text", "text": text}))
+            }
+            _ => None,
+        })
+        .collect()
+}
+
 /// Convert an assistant-role `LlmMessage` into an OpenAI-compat message.
 fn convert_assistant_message_ollama(msg: &LlmMessage, messages: &mut Vec<Value>) {
     use serde_json::json;
@@ -361,6 +409,22 @@ impl LlmProvider for OllamaProvider {
 
         Ok(rx)
     }
+
+    /// Embeddings go through Ollama's OpenAI-compatible `/v1/embeddings`.
+    async fn embed(&self, request: EmbeddingRequest) -> Result<EmbeddingResponse, AgentError> {
+        let url = format!("{}/v1/embeddings", self.base_url);
+        crate::agent::openai::request_embeddings(
+            &self.client,
+            &url,
+            self.api_key.as_deref(),
+            &request,
+        )
+        .await
+    }
+
+    fn provider_name(&self, _model: &str) -> &str {
+        "ollama"
+    }
 }
 
 /// Determine whether a model identifier should route to the Ollama provider.
@@ -550,6 +614,7 @@ mod tests {
             tools: vec![],
             max_tokens: 2048,
             temperature: Some(0.7),
+            thinking: None,
             extra: None,
         };
         let body = provider.build_body(&request);
@@ -588,6 +653,7 @@ mod tests {
             }],
             max_tokens: 4096,
             temperature: None,
+            thinking: None,
             extra: None,
         };
         let body = provider.build_body(&request);
@@ -637,6 +703,7 @@ mod tests {
             tools: vec![],
             max_tokens: 1024,
             temperature: None,
+            thinking: None,
             extra: None,
         };
         let body = provider.build_body(&request);
@@ -662,6 +729,45 @@ mod tests {
         assert_eq!(messages[2]["content"], "72F and sunny");
     }
 
+    #[test]
+    fn test_build_body_user_image_as_data_url() {
+        let provider = OllamaProvider::new().unwrap();
+        let request = CompletionRequest {
+            model: "llava".to_string(),
+            messages: vec![LlmMessage {
+                role: LlmRole::User,
+                content: vec![
+                    ContentBlock::Image {
+                        media_type: "image/jpeg".to_string(),
+                        data: "/9j/".to_string(),
+                    },
+                    ContentBlock::Document {
+                        media_type: "application/pdf".to_string(),
+                        data: "JVBE".to_string(),
+                        name: None,
+                    },
+                    ContentBlock::Text {
+                        text: "Describe".to_string(),
+                    },
+                ],
+            }],
+            system: None,
+            tools: vec![],
+            max_tokens: 1024,
+            temperature: None,
+            thinking: None,
+            extra: None,
+        };
+        let body = provider.build_body(&request);
+        let parts = body["messages"][0]["content"].as_array().unwrap();
+        assert_eq!(parts[0]["image_url"]["url"], "data:image/jpeg;base64,/9j/");
+        assert!(parts[1]["text"]
+            .as_str()
+            .unwrap()
+            .contains("application/pdf"));
+        assert_eq!(parts[2]["text"], "Describe");
+    }
+
     #[test]
     fn test_build_body_no_system() {
         let provider = OllamaProvider::new().unwrap();
@@ -677,6 +783,7 @@ mod tests {
             tools: vec![],
             max_tokens: 1024,
             temperature: None,
+            thinking: None,
             extra: None,
         };
         let body = provider.build_body(&request);
@@ -695,6 +802,7 @@ mod tests {
             tools: vec![],
             max_tokens: 1024,
             temperature: None,
+            thinking: None,
             extra: None,
         };
         let body = provider.build_body(&request);
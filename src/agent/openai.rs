@@ -89,6 +89,12 @@ impl OpenAiProvider {
             body["temperature"] = json!(temp);
         }
 
+        if let Some(level) = request.thinking {
+            if supports_reasoning_effort(&request.model) {
+                body["reasoning_effort"] = json!(level.reasoning_effort());
+            }
+        }
+
         append_tools_openai(&request.tools, &mut body);
 
         body
@@ -186,6 +192,11 @@ fn convert_user_message_openai(msg: &LlmMessage, messages: &mut Vec<Value>) {
                 _ => {}
             }
         }
+    } else if has_attachments(&msg.content) {
+        messages.push(json!({
+            "role": "user",
+            "content": user_content_parts(&msg.content),
+        }));
     } else {
         let text = collect_text_blocks(&msg.content);
         if !text.is_empty() {
@@ -197,6 +208,54 @@ fn convert_user_message_openai(msg: &LlmMessage, messages: &mut Vec<Value>) {
     }
 }
 
+/// Whether a content slice carries any image or document blocks.
+fn has_attachments(content: &[ContentBlock]) -> bool {
+    content.iter().any(|b| {
+        matches!(
+            b,
+            ContentBlock::Image { .. } | ContentBlock::Document { .. }
+        )
+    })
+}
+
+/// Build the content-parts array for a user message with attachments.
+///
+/// Images become `image_url` data URLs and PDFs `file` parts; text documents
+/// are inlined and anything else is replaced by a placeholder.
+fn user_content_parts(content: &[ContentBlock]) -> Vec<Value> {
+    content
+        .iter()
+        .filter_map(|block| match block {
+            ContentBlock::Text { text } => Some(json!({"type": "text", "text": text})),
+            ContentBlock::Image { media_type, data } => Some(json!({
+                "type": "image_url",
+                "image_url": {"url": format!("data:{media_type};base64,{data}")},
+            })),
+            ContentBlock::Document {
+                media_type,
+                data,
+                name,
+            } => {
+                if media_type == "application/pdf" {
+                    Some(json!({
+                        "type": "file",
+                        "file": {
+                            "filename": name.as_deref().unwrap_or("document.pdf"),
+                            "file_data": format!("data:{media_type};base64,{data}"),
+                        },
+                    }))
+                } else {
+                    let text = decode_text_document(media_type, data)
+                        .or_else(|| block.attachment_placeholder())
+                        .unwrap_or_default();
+                    Some(json!({"type": "text", "text": text}))
+                }
+            }
+            _ => None,
+        })
+        .collect()
+}
+
 /// Convert an assistant-role `LlmMessage` into an OpenAI-format message.
 fn convert_assistant_message_openai(msg: &LlmMessage, messages: &mut Vec<Value>) {
     let has_tool_use = msg
@@ -282,6 +341,104 @@ impl LlmProvider for OpenAiProvider {
         let body = self.build_body(&request);
         self.complete_with_body(body, cancel_token).await
     }
+
+    async fn embed(&self, request: EmbeddingRequest) -> Result<EmbeddingResponse, AgentError> {
+        let url = format!("{}/v1/embeddings", self.base_url);
+        request_embeddings(&self.client, &url, Some(&self.api_key), &request).await
+    }
+
+    fn provider_name(&self, _model: &str) -> &str {
+        "openai"
+    }
+}
+
+/// Call an OpenAI-compatible `/v1/embeddings` endpoint.
+///
+/// Shared with the Ollama provider, which serves the same API shape.
+pub(crate) async fn request_embeddings(
+    client: &reqwest::Client,
+    url: &str,
+    api_key: Option<&str>,
+    request: &EmbeddingRequest,
+) -> Result<EmbeddingResponse, AgentError> {
+    let mut http_request = client.post(url).header("content-type", "application/json");
+    if let Some(key) = api_key {
+        http_request = http_request.header("authorization", format!("Bearer {key}"));
+    }
+
+    let response = http_request
+        .json(&build_embeddings_body(request))
+        .send()
+        .await
+        .map_err(|e| AgentError::Provider(format!("HTTP request failed: {e}")))?;
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let body = response
+            .text()
+            .await
+            .unwrap_or_else(|_| "<unreadable>".to_string());
+        return Err(AgentError::Provider(format!(
+            "API returned {status}: {body}"
+        )));
+    }
+
+    let body: Value = response
+        .json()
+        .await
+        .map_err(|e| AgentError::Provider(format!("failed to parse embeddings response: {e}")))?;
+    parse_embeddings_response(&body)
+}
+
+/// Build the JSON body for an OpenAI-compatible embeddings request.
+fn build_embeddings_body(request: &EmbeddingRequest) -> Value {
+    let mut body = json!({
+        "model": request.model,
+        "input": request.input,
+        "encoding_format": "float",
+    });
+    if let Some(dimensions) = request.dimensions {
+        body["dimensions"] = json!(dimensions);
+    }
+    body
+}
+
+/// Parse an OpenAI-compatible embeddings response, ordering vectors by their
+/// `index` so they line up with the request inputs.
+fn parse_embeddings_response(body: &Value) -> Result<EmbeddingResponse, AgentError> {
+    let data = body
+        .get("data")
+        .and_then(|v| v.as_array())
+        .ok_or_else(|| AgentError::Provider("embeddings response has no data".to_string()))?;
+
+    let mut indexed = Vec::with_capacity(data.len());
+    for (position, item) in data.iter().enumerate() {
+        let index = item
+            .get("index")
+            .and_then(|v| v.as_u64())
+            .map(|i| i as usize)
+            .unwrap_or(position);
+        let embedding = item
+            .get("embedding")
+            .and_then(|v| v.as_array())
+            .ok_or_else(|| {
+                AgentError::Provider("embeddings response item has no vector".to_string())
+            })?
+            .iter()
+            .filter_map(|v| v.as_f64().map(|f| f as f32))
+            .collect::<Vec<_>>();
+        indexed.push((index, embedding));
+    }
+    indexed.sort_by_key(|(index, _)| *index);
+
+    Ok(EmbeddingResponse {
+        embeddings: indexed.into_iter().map(|(_, e)| e).collect(),
+        input_tokens: body
+            .get("usage")
+            .and_then(|u| u.get("prompt_tokens"))
+            .and_then(|v| v.as_u64())
+            .unwrap_or(0),
+    })
 }
 
 /// Maximum SSE line buffer size (1 MB). If a single SSE line exceeds this,
@@ -461,6 +618,20 @@ fn parse_sse_data(
     // Process delta
     let delta = choice.get("delta")?;
 
+    // Reasoning content from OpenAI-compatible servers (`reasoning_content`
+    // on DeepSeek/vLLM, `reasoning` on Ollama/OpenRouter)
+    if let Some(reasoning) = delta
+        .get("reasoning_content")
+        .or_else(|| delta.get("reasoning"))
+        .and_then(|v| v.as_str())
+    {
+        if !reasoning.is_empty() {
+            return Some(StreamEvent::ThinkingDelta {
+                text: reasoning.to_string(),
+            });
+        }
+    }
+
     // Text content
     if let Some(content) = delta.get("content").and_then(|v| v.as_str()) {
         if !content.is_empty() {
@@ -543,6 +714,14 @@ async fn flush_tool_calls_and_stop(
     })
 }
 
+/// Whether an OpenAI model accepts `reasoning_effort` (o-series and GPT-5).
+fn supports_reasoning_effort(model: &str) -> bool {
+    let lower = model.to_lowercase();
+    ["o1", "o3", "o4", "gpt-5"]
+        .iter()
+        .any(|prefix| lower.starts_with(prefix))
+}
+
 /// Determine whether a model identifier should route to the OpenAI provider.
 pub fn is_openai_model(model: &str) -> bool {
     let lower = model.to_lowercase();
@@ -575,6 +754,7 @@ mod tests {
             tools: vec![],
             max_tokens: 1024,
             temperature: Some(0.7),
+            thinking: None,
             extra: None,
         };
         let body = provider.build_body(&request);
@@ -612,6 +792,7 @@ mod tests {
             }],
             max_tokens: 4096,
             temperature: None,
+            thinking: None,
             extra: None,
         };
         let body = provider.build_body(&request);
@@ -665,6 +846,7 @@ mod tests {
             tools: vec![],
             max_tokens: 1024,
             temperature: None,
+            thinking: None,
             extra: None,
         };
         let body = provider.build_body(&request);
@@ -690,6 +872,54 @@ mod tests {
         assert_eq!(messages[2]["content"], "72F and sunny");
     }
 
+    #[test]
+    fn test_build_body_user_attachments_as_content_parts() {
+        let provider = OpenAiProvider::new("test-key".to_string()).unwrap();
+        let request = CompletionRequest {
+            model: "gpt-4o".to_string(),
+            messages: vec![LlmMessage {
+                role: LlmRole::User,
+                content: vec![
+                    ContentBlock::Image {
+                        media_type: "image/png".to_string(),
+                        data: "iVBO".to_string(),
+                    },
+                    ContentBlock::Document {
+                        media_type: "application/pdf".to_string(),
+                        data: "JVBE".to_string(),
+                        name: Some("report.pdf".to_string()),
+                    },
+                    ContentBlock::Document {
+                        media_type: "text/plain".to_string(),
+                        data: "aGVsbG8=".to_string(),
+                        name: None,
+                    },
+                    ContentBlock::Text {
+                        text: "Summarize".to_string(),
+                    },
+                ],
+            }],
+            system: None,
+            tools: vec![],
+            max_tokens: 1024,
+            temperature: None,
+            thinking: None,
+            extra: None,
+        };
+        let body = provider.build_body(&request);
+        let parts = body["messages"][0]["content"].as_array().unwrap();
+        assert_eq!(parts[0]["type"], "image_url");
+        assert_eq!(parts[0]["image_url"]["url"], "data:image/png;base64,iVBO");
+        assert_eq!(parts[1]["type"], "file");
+        assert_eq!(parts[1]["file"]["filename"], "report.pdf");
+        assert_eq!(
+            parts[1]["file"]["file_data"],
+            "data:application/pdf;base64,JVBE"
+        );
+        assert_eq!(parts[2]["text"], "hello");
+        assert_eq!(parts[3]["text"], "Summarize");
+    }
+
     #[test]
     fn test_build_body_no_system() {
         let provider = OpenAiProvider::new("test-key".to_string()).unwrap();
@@ -705,6 +935,7 @@ mod tests {
             tools: vec![],
             max_tokens: 1024,
             temperature: None,
+            thinking: None,
             extra: None,
         };
         let body = provider.build_body(&request);
@@ -731,6 +962,42 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_parse_reasoning_delta() {
+        let mut tool_calls = std::collections::HashMap::new();
+        let mut usage = TokenUsage::default();
+        for data in [
+            r#"{"choices":[{"index":0,"delta":{"reasoning_content":"Hmm"},"finish_reason":null}]}"#,
+            r#"{"choices":[{"index":0,"delta":{"reasoning":"Hmm","content":""},"finish_reason":null}]}"#,
+        ] {
+            match parse_sse_data(data, &mut tool_calls, &mut usage) {
+                Some(StreamEvent::ThinkingDelta { text }) => assert_eq!(text, "Hmm"),
+                other => panic!("expected ThinkingDelta, got {other:?}"),
+            }
+        }
+    }
+
+    #[test]
+    fn test_build_body_reasoning_effort_only_for_reasoning_models() {
+        let provider = OpenAiProvider::new("test-key".to_string()).unwrap();
+        let mut request = CompletionRequest {
+            model: "o3-mini".to_string(),
+            messages: vec![],
+            system: None,
+            tools: vec![],
+            max_tokens: 1024,
+            temperature: None,
+            thinking: Some(ThinkingLevel::Low),
+            extra: None,
+        };
+        let body = provider.build_body(&request);
+        assert_eq!(body["reasoning_effort"], "low");
+
+        request.model = "gpt-4o".to_string();
+        let body = provider.build_body(&request);
+        assert!(body.get("reasoning_effort").is_none());
+    }
+
     #[test]
     fn test_parse_stop_reason_end_turn() {
         let mut tool_calls = std::collections::HashMap::new();
@@ -1141,4 +1408,45 @@ mod tests {
         assert!(!is_openai_model("claude-3-opus"));
         assert!(!is_openai_model("some-other-model"));
     }
+
+    // ==================== embeddings tests ====================
+
+    #[test]
+    fn test_build_embeddings_body() {
+        let request = EmbeddingRequest {
+            model: "text-embedding-3-small".to_string(),
+            input: vec!["a".to_string(), "b".to_string()],
+            dimensions: Some(256),
+        };
+        let body = build_embeddings_body(&request);
+        assert_eq!(body["model"], "text-embedding-3-small");
+        assert_eq!(body["input"], json!(["a", "b"]));
+        assert_eq!(body["dimensions"], 256);
+        assert_eq!(body["encoding_format"], "float");
+
+        let body = build_embeddings_body(&EmbeddingRequest {
+            dimensions: None,
+            ..request
+        });
+        assert!(body.get("dimensions").is_none());
+    }
+
+    #[test]
+    fn test_parse_embeddings_response_orders_by_index() {
+        let body = json!({
+            "data": [
+                {"object": "embedding", "index": 1, "embedding": [0.5, 0.25]},
+                {"object": "embedding", "index": 0, "embedding": [1.0, -1.0]}
+            ],
+            "usage": {"prompt_tokens": 7, "total_tokens": 7}
+        });
+        let response = parse_embeddings_response(&body).unwrap();
+        assert_eq!(response.embeddings, vec![vec![1.0, -1.0], vec![0.5, 0.25]]);
+        assert_eq!(response.input_tokens, 7);
+    }
+
+    #[test]
+    fn test_parse_embeddings_response_missing_data() {
+        assert!(parse_embeddings_response(&json!({"error": "nope"})).is_err());
+    }
 }
@@ -0,0 +1,593 @@
+//! LLM provider backed by a WASM provider plugin.
+//!
+//! Adapts the WIT `provider` interface to [`LlmProvider`] so plugins can
+//! supply additional backends (OpenRouter, internal gateways, ...). Models are
+//! addressed as `<plugin-id>:<model>` or `<plugin-id>/<model>`; `MultiProvider`
+//! strips the prefix before the request reaches the plugin.
+//!
+//! The WIT `chat-message` record has no tool-call field, so assistant tool
+//! calls are sent as an assistant message named `tool_calls` whose content is
+//! the OpenAI-style JSON tool-call array. Tool results are sent with role
+//! `tool`, the originating `tool-call-id` and the tool name.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
+
+use crate::agent::provider::*;
+use crate::agent::AgentError;
+use crate::plugins::{
+    ProviderChatMessage, ProviderCompletionRequest, ProviderPluginInstance, ProviderUsageInfo,
+};
+
+/// LLM provider that forwards completions to a WASM provider plugin.
+pub struct PluginProvider {
+    plugin_id: String,
+    plugin: Arc<dyn ProviderPluginInstance>,
+    supports_streaming: bool,
+}
+
+impl std::fmt::Debug for PluginProvider {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PluginProvider")
+            .field("plugin_id", &self.plugin_id)
+            .field("supports_streaming", &self.supports_streaming)
+            .finish()
+    }
+}
+
+impl PluginProvider {
+    /// Create a provider for a registered plugin, reading its `provider-info`.
+    pub fn new(
+        plugin_id: String,
+        plugin: Arc<dyn ProviderPluginInstance>,
+    ) -> Result<Self, AgentError> {
+        let info = plugin.get_info().map_err(|e| {
+            AgentError::Provider(format!(
+                "provider plugin \"{plugin_id}\" get-info failed: {e}"
+            ))
+        })?;
+        Ok(Self {
+            plugin_id,
+            plugin,
+            supports_streaming: info.supports_streaming,
+        })
+    }
+
+    /// The plugin ID used as this provider's model prefix.
+    pub fn plugin_id(&self) -> &str {
+        &self.plugin_id
+    }
+}
+
+/// Convert an agent completion request into the WIT request shape.
+fn build_request(request: &CompletionRequest, stream: bool) -> ProviderCompletionRequest {
+    let mut messages = Vec::new();
+    if let Some(system) = request.system.as_ref().filter(|s| !s.is_empty()) {
+        messages.push(chat_message("system", system.clone()));
+    }
+
+    // Tool results only carry the call ID; remember names from earlier calls.
+    let mut tool_names: HashMap<&str, &str> = HashMap::new();
+    for msg in &request.messages {
+        match msg.role {
+            LlmRole::User => {
+                let mut text = String::new();
+                for block in &msg.content {
+                    match block {
+                        ContentBlock::Text { text: t } => text.push_str(t),
+                        ContentBlock::ToolResult {
+                            tool_use_id,
+                            content,
+                            ..
+                        } => messages.push(ProviderChatMessage {
+                            role: "tool".to_string(),
+                            content: content.clone(),
+                            name: tool_names.get(tool_use_id.as_str()).map(|n| n.to_string()),
+                            tool_call_id: Some(tool_use_id.clone()),
+                        }),
+                        // The WIT message shape is text-only
+                        ContentBlock::Image { .. } | ContentBlock::Document { .. } => {
+                            text.push_str(&block.attachment_placeholder().unwrap_or_default())
+                        }
+                        ContentBlock::ToolUse { .. } | ContentBlock::Thinking { .. } => {}
+                    }
+                }
+                if !text.is_empty() {
+                    messages.push(chat_message("user", text));
+                }
+            }
+            LlmRole::Assistant => {
+                let mut text = String::new();
+                let mut tool_calls = Vec::new();
+                for block in &msg.content {
+                    match block {
+                        ContentBlock::Text { text: t } => text.push_str(t),
+                        ContentBlock::ToolUse { id, name, input } => {
+                            tool_names.insert(id, name);
+                            tool_calls.push(json!({
+                                "id": id,
+                                "type": "function",
+                                "function": {
+                                    "name": name,
+                                    "arguments": input.to_string(),
+                                }
+                            }));
+                        }
+                        ContentBlock::ToolResult { .. }
+                        | ContentBlock::Thinking { .. }
+                        | ContentBlock::Image { .. }
+                        | ContentBlock::Document { .. } => {}
+                    }
+                }
+                if !text.is_empty() {
+                    messages.push(chat_message("assistant", text));
+                }
+                if !tool_calls.is_empty() {
+                    messages.push(ProviderChatMessage {
+                        role: "assistant".to_string(),
+                        content: Value::Array(tool_calls).to_string(),
+                        name: Some("tool_calls".to_string()),
+                        tool_call_id: None,
+                    });
+                }
+            }
+        }
+    }
+
+    let tools = (!request.tools.is_empty()).then(|| {
+        let defs: Vec<Value> = request
+            .tools
+            .iter()
+            .map(|t| {
+                json!({
+                    "type": "function",
+                    "function": {
+                        "name": t.name,
+                        "description": t.description,
+                        "parameters": t.input_schema,
+                    }
+                })
+            })
+            .collect();
+        Value::Array(defs).to_string()
+    });
+
+    ProviderCompletionRequest {
+        model: request.model.clone(),
+        messages,
+        max_tokens: Some(request.max_tokens),
+        temperature: request.temperature.map(|t| t as f32),
+        stream,
+        tools,
+    }
+}
+
+fn chat_message(role: &str, content: String) -> ProviderChatMessage {
+    ProviderChatMessage {
+        role: role.to_string(),
+        content,
+        name: None,
+        tool_call_id: None,
+    }
+}
+
+/// Parse a JSON tool-call array returned by a plugin into `ToolUse` events.
+///
+/// Accepts both the OpenAI shape (`{id, function: {name, arguments}}`) and a
+/// flat `{id, name, input}` shape; `arguments` may be a JSON string or object.
+fn parse_tool_calls(raw: &str) -> Result<Vec<StreamEvent>, String> {
+    let calls: Vec<Value> =
+        serde_json::from_str(raw).map_err(|e| format!("invalid tool-calls JSON: {e}"))?;
+    calls
+        .iter()
+        .map(|call| {
+            let func = call.get("function").unwrap_or(call);
+            let name = func
+                .get("name")
+                .and_then(|v| v.as_str())
+                .ok_or("tool call is missing a name")?;
+            let id = call
+                .get("id")
+                .and_then(|v| v.as_str())
+                .unwrap_or(name)
+                .to_string();
+            let input = match func.get("arguments").or_else(|| func.get("input")) {
+                Some(Value::String(s)) if s.trim().is_empty() => json!({}),
+                Some(Value::String(s)) => serde_json::from_str(s)
+                    .map_err(|e| format!("invalid arguments for tool \"{name}\": {e}"))?,
+                Some(v) => v.clone(),
+                None => json!({}),
+            };
+            Ok(StreamEvent::ToolUse {
+                id,
+                name: name.to_string(),
+                input,
+            })
+        })
+        .collect()
+}
+
+/// Map a WIT finish reason to a stop reason (`None` for `"error"`).
+fn stop_reason(finish_reason: Option<&str>, has_tool_calls: bool) -> Option<StopReason> {
+    match finish_reason {
+        Some("error") => None,
+        _ if has_tool_calls => Some(StopReason::ToolUse),
+        Some("tool_calls") => Some(StopReason::ToolUse),
+        Some("length") => Some(StopReason::MaxTokens),
+        _ => Some(StopReason::EndTurn),
+    }
+}
+
+fn token_usage(usage: Option<ProviderUsageInfo>) -> TokenUsage {
+    usage
+        .map(|u| TokenUsage {
+            input_tokens: u64::from(u.prompt_tokens),
+            output_tokens: u64::from(u.completion_tokens),
+        })
+        .unwrap_or_default()
+}
+
+/// Send the trailing tool-use and stop events for a finished completion.
+fn finish(
+    tx: &mpsc::Sender<StreamEvent>,
+    tool_calls: Vec<StreamEvent>,
+    finish_reason: Option<&str>,
+    usage: TokenUsage,
+) {
+    let Some(reason) = stop_reason(finish_reason, !tool_calls.is_empty()) else {
+        let _ = tx.blocking_send(StreamEvent::Error {
+            message: "provider plugin finished with an error".to_string(),
+        });
+        return;
+    };
+    for event in tool_calls {
+        let _ = tx.blocking_send(event);
+    }
+    let _ = tx.blocking_send(StreamEvent::Stop { reason, usage });
+}
+
+/// Run a non-streaming completion and replay it as stream events.
+fn run_complete(
+    plugin: &dyn ProviderPluginInstance,
+    request: ProviderCompletionRequest,
+    tx: &mpsc::Sender<StreamEvent>,
+) -> Result<(), String> {
+    let response = plugin.complete(request).map_err(|e| e.to_string())?;
+    let tool_calls = match response.tool_calls.as_deref() {
+        Some(raw) => parse_tool_calls(raw)?,
+        None => Vec::new(),
+    };
+    if let Some(text) = response.content.filter(|t| !t.is_empty()) {
+        let _ = tx.blocking_send(StreamEvent::TextDelta { text });
+    }
+    finish(
+        tx,
+        tool_calls,
+        Some(&response.finish_reason),
+        token_usage(response.usage),
+    );
+    Ok(())
+}
+
+/// Drain a plugin stream, forwarding text deltas as they arrive.
+fn run_stream(
+    plugin: &dyn ProviderPluginInstance,
+    request: ProviderCompletionRequest,
+    tx: &mpsc::Sender<StreamEvent>,
+    cancel: &CancellationToken,
+) -> Result<(), String> {
+    let stream_id = plugin.complete_stream(request).map_err(|e| e.to_string())?;
+    let mut tool_calls = Vec::new();
+    let mut finish_reason = None;
+    loop {
+        if cancel.is_cancelled() || tx.is_closed() {
+            if let Err(e) = plugin.stream_cancel(&stream_id) {
+                tracing::debug!(stream_id = %stream_id, error = %e, "provider stream cancel failed");
+            }
+            return Ok(());
+        }
+        let Some(chunk) = plugin.stream_next(&stream_id).map_err(|e| e.to_string())? else {
+            break;
+        };
+        if let Some(text) = chunk.delta_content.filter(|t| !t.is_empty()) {
+            let _ = tx.blocking_send(StreamEvent::TextDelta { text });
+        }
+        if let Some(raw) = chunk.delta_tool_calls.as_deref() {
+            tool_calls.extend(parse_tool_calls(raw)?);
+        }
+        if chunk.finish_reason.is_some() {
+            finish_reason = chunk.finish_reason;
+        }
+    }
+    finish(
+        tx,
+        tool_calls,
+        finish_reason.as_deref(),
+        TokenUsage::default(),
+    );
+    Ok(())
+}
+
+#[async_trait]
+impl LlmProvider for PluginProvider {
+    async fn complete(
+        &self,
+        request: CompletionRequest,
+        cancel_token: CancellationToken,
+    ) -> Result<mpsc::Receiver<StreamEvent>, AgentError> {
+        if cancel_token.is_cancelled() {
+            return Err(AgentError::Cancelled);
+        }
+        let stream = self.supports_streaming;
+        let plugin_request = build_request(&request, stream);
+        let plugin = self.plugin.clone();
+        let (tx, rx) = mpsc::channel(64);
+
+        // Plugin exports are synchronous; run them on the blocking pool.
+        tokio::task::spawn_blocking(move || {
+            let result = if stream {
+                run_stream(plugin.as_ref(), plugin_request, &tx, &cancel_token)
+            } else {
+                run_complete(plugin.as_ref(), plugin_request, &tx)
+            };
+            if let Err(message) = result {
+                let _ = tx.blocking_send(StreamEvent::Error { message });
+            }
+        });
+
+        Ok(rx)
+    }
+
+    fn provider_name(&self, _model: &str) -> &str {
+        &self.plugin_id
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugins::{
+        BindingError, ModelDefinition, ProviderCompletionResponse, ProviderInfo,
+        ProviderStreamChunk,
+    };
+    use parking_lot::Mutex;
+
+    struct MockProvider {
+        streaming: bool,
+        chunks: Mutex<Vec<ProviderStreamChunk>>,
+        last_request: Mutex<Option<ProviderCompletionRequest>>,
+    }
+
+    impl MockProvider {
+        fn new(streaming: bool, mut chunks: Vec<ProviderStreamChunk>) -> Self {
+            chunks.reverse();
+            Self {
+                streaming,
+                chunks: Mutex::new(chunks),
+                last_request: Mutex::new(None),
+            }
+        }
+    }
+
+    impl ProviderPluginInstance for MockProvider {
+        fn get_info(&self) -> Result<ProviderInfo, BindingError> {
+            Ok(ProviderInfo {
+                id: "mock".to_string(),
+                label: "Mock".to_string(),
+                model_family: "openai".to_string(),
+                docs_path: String::new(),
+                supports_streaming: self.streaming,
+                supports_tools: true,
+                supports_vision: false,
+            })
+        }
+
+        fn list_models(&self) -> Result<Vec<ModelDefinition>, BindingError> {
+            Ok(Vec::new())
+        }
+
+        fn is_ready(&self) -> Result<bool, BindingError> {
+            Ok(true)
+        }
+
+        fn complete(
+            &self,
+            req: ProviderCompletionRequest,
+        ) -> Result<ProviderCompletionResponse, BindingError> {
+            *self.last_request.lock() = Some(req);
+            Ok(ProviderCompletionResponse {
+                content: Some("hello".to_string()),
+                tool_calls: Some(
+                    r#"[{"id":"c1","function":{"name":"get_time","arguments":"{\"tz\":\"UTC\"}"}}]"#
+                        .to_string(),
+                ),
+                finish_reason: "tool_calls".to_string(),
+                usage: Some(ProviderUsageInfo {
+                    prompt_tokens: 12,
+                    completion_tokens: 3,
+                    total_tokens: 15,
+                }),
+            })
+        }
+
+        fn complete_stream(&self, req: ProviderCompletionRequest) -> Result<String, BindingError> {
+            *self.last_request.lock() = Some(req);
+            Ok("s1".to_string())
+        }
+
+        fn stream_next(
+            &self,
+            _stream_id: &str,
+        ) -> Result<Option<ProviderStreamChunk>, BindingError> {
+            Ok(self.chunks.lock().pop())
+        }
+
+        fn stream_cancel(&self, _stream_id: &str) -> Result<(), BindingError> {
+            Ok(())
+        }
+    }
+
+    fn request() -> CompletionRequest {
+        CompletionRequest {
+            model: "qwen-max".to_string(),
+            messages: vec![
+                LlmMessage {
+                    role: LlmRole::User,
+                    content: vec![ContentBlock::Text {
+                        text: "what time is it?".to_string(),
+                    }],
+                },
+                LlmMessage {
+                    role: LlmRole::Assistant,
+                    content: vec![ContentBlock::ToolUse {
+                        id: "c0".to_string(),
+                        name: "get_time".to_string(),
+                        input: json!({}),
+                    }],
+                },
+                LlmMessage {
+                    role: LlmRole::User,
+                    content: vec![ContentBlock::ToolResult {
+                        tool_use_id: "c0".to_string(),
+                        content: "12:00".to_string(),
+                        is_error: false,
+                    }],
+                },
+            ],
+            system: Some("be brief".to_string()),
+            tools: vec![ToolDefinition {
+                name: "get_time".to_string(),
+                description: "Current time".to_string(),
+                input_schema: json!({"type": "object"}),
+            }],
+            max_tokens: 256,
+            temperature: Some(0.5),
+            thinking: None,
+            extra: None,
+        }
+    }
+
+    async fn collect(mut rx: mpsc::Receiver<StreamEvent>) -> Vec<StreamEvent> {
+        let mut events = Vec::new();
+        while let Some(event) = rx.recv().await {
+            events.push(event);
+        }
+        events
+    }
+
+    #[test]
+    fn test_build_request_converts_messages() {
+        let req = build_request(&request(), false);
+        let roles: Vec<&str> = req.messages.iter().map(|m| m.role.as_str()).collect();
+        assert_eq!(roles, vec!["system", "user", "assistant", "tool"]);
+        assert_eq!(req.messages[2].name.as_deref(), Some("tool_calls"));
+        assert_eq!(req.messages[3].tool_call_id.as_deref(), Some("c0"));
+        assert_eq!(req.messages[3].name.as_deref(), Some("get_time"));
+        assert_eq!(req.max_tokens, Some(256));
+        let tools: Value = serde_json::from_str(req.tools.as_deref().unwrap()).unwrap();
+        assert_eq!(tools[0]["function"]["name"], "get_time");
+    }
+
+    #[test]
+    fn test_parse_tool_calls_flat_shape() {
+        let events =
+            parse_tool_calls(r#"[{"id":"x","name":"search","input":{"q":"rust"}}]"#).unwrap();
+        match &events[0] {
+            StreamEvent::ToolUse { id, name, input } => {
+                assert_eq!(id, "x");
+                assert_eq!(name, "search");
+                assert_eq!(input["q"], "rust");
+            }
+            other => panic!("unexpected event: {other:?}"),
+        }
+        assert!(parse_tool_calls("not json").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_complete_non_streaming() {
+        let mock = Arc::new(MockProvider::new(false, Vec::new()));
+        let provider = PluginProvider::new("mock".to_string(), mock.clone()).unwrap();
+        let rx = provider
+            .complete(request(), CancellationToken::new())
+            .await
+            .unwrap();
+        let events = collect(rx).await;
+
+        assert!(matches!(&events[0], StreamEvent::TextDelta { text } if text == "hello"));
+        assert!(
+            matches!(&events[1], StreamEvent::ToolUse { name, input, .. } if name == "get_time" && input["tz"] == "UTC")
+        );
+        match &events[2] {
+            StreamEvent::Stop { reason, usage } => {
+                assert_eq!(*reason, StopReason::ToolUse);
+                assert_eq!(usage.input_tokens, 12);
+                assert_eq!(usage.output_tokens, 3);
+            }
+            other => panic!("unexpected event: {other:?}"),
+        }
+        assert!(!mock.last_request.lock().as_ref().unwrap().stream);
+    }
+
+    #[tokio::test]
+    async fn test_complete_streaming() {
+        let chunks = vec![
+            ProviderStreamChunk {
+                delta_content: Some("Hel".to_string()),
+                ..Default::default()
+            },
+            ProviderStreamChunk {
+                delta_content: Some("lo".to_string()),
+                finish_reason: Some("length".to_string()),
+                ..Default::default()
+            },
+        ];
+        let mock = Arc::new(MockProvider::new(true, chunks));
+        let provider = PluginProvider::new("mock".to_string(), mock.clone()).unwrap();
+        let rx = provider
+            .complete(request(), CancellationToken::new())
+            .await
+            .unwrap();
+        let events = collect(rx).await;
+
+        let text: String = events
+            .iter()
+            .filter_map(|e| match e {
+                StreamEvent::TextDelta { text } => Some(text.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(text, "Hello");
+        assert!(matches!(
+            events.last(),
+            Some(StreamEvent::Stop {
+                reason: StopReason::MaxTokens,
+                ..
+            })
+        ));
+        assert!(mock.last_request.lock().as_ref().unwrap().stream);
+    }
+
+    #[tokio::test]
+    async fn test_complete_error_finish_reason() {
+        let chunks = vec![ProviderStreamChunk {
+            finish_reason: Some("error".to_string()),
+            ..Default::default()
+        }];
+        let provider = PluginProvider::new(
+            "mock".to_string(),
+            Arc::new(MockProvider::new(true, chunks)),
+        )
+        .unwrap();
+        let rx = provider
+            .complete(request(), CancellationToken::new())
+            .await
+            .unwrap();
+        let events = collect(rx).await;
+        assert!(matches!(events.last(), Some(StreamEvent::Error { .. })));
+    }
+}
@@ -19,6 +19,8 @@ pub enum ContentSource {
     FetchedUrl,
     /// Message from an external service or webhook.
     ExternalMessage,
+    /// Memory saved in an earlier conversation.
+    Memory,
     /// User input — NOT tagged (operator-controlled).
     UserInput,
     /// System prompt — NOT tagged (operator-controlled).
@@ -30,7 +32,10 @@ impl ContentSource {
     pub fn is_untrusted(self) -> bool {
         matches!(
             self,
-            ContentSource::ToolResult | ContentSource::FetchedUrl | ContentSource::ExternalMessage
+            ContentSource::ToolResult
+                | ContentSource::FetchedUrl
+                | ContentSource::ExternalMessage
+                | ContentSource::Memory
         )
     }
 }
@@ -158,6 +163,7 @@ mod tests {
         assert!(ContentSource::ToolResult.is_untrusted());
         assert!(ContentSource::FetchedUrl.is_untrusted());
         assert!(ContentSource::ExternalMessage.is_untrusted());
+        assert!(ContentSource::Memory.is_untrusted());
     }
 
     #[test]
@@ -13,6 +13,14 @@ pub enum StreamEvent {
     /// Incremental text output.
     TextDelta { text: String },
 
+    /// Incremental extended-thinking / reasoning output.
+    ThinkingDelta { text: String },
+
+    /// Signature closing the current thinking block. Providers that verify
+    /// thinking on later turns (Anthropic, Bedrock) require it to be echoed
+    /// back with the block.
+    ThinkingSignature { signature: String },
+
     /// The model wants to call a tool.
     ToolUse {
         id: String,
@@ -46,6 +54,67 @@ pub struct TokenUsage {
     pub output_tokens: u64,
 }
 
+/// Extended thinking / reasoning effort requested for a completion.
+///
+/// Parsed from session `thinkingLevel`, `agents.defaults.thinkingDefault`
+/// and cron `thinking` values; `"off"` maps to no thinking at all.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ThinkingLevel {
+    Minimal,
+    Low,
+    Medium,
+    High,
+}
+
+impl ThinkingLevel {
+    /// Parse a thinking level. Returns `Ok(None)` for `"off"` and `Err` for
+    /// unrecognised values.
+    pub fn parse(value: &str) -> Result<Option<Self>, String> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "" | "off" | "none" | "false" => Ok(None),
+            "minimal" => Ok(Some(Self::Minimal)),
+            "low" => Ok(Some(Self::Low)),
+            "medium" | "on" | "true" => Ok(Some(Self::Medium)),
+            "high" | "xhigh" | "max" => Ok(Some(Self::High)),
+            other => Err(format!("unknown thinking level \"{other}\"")),
+        }
+    }
+
+    /// Thinking token budget for budget-based APIs (Anthropic, Bedrock,
+    /// Gemini).
+    pub fn budget_tokens(self) -> u32 {
+        match self {
+            Self::Minimal => 1024,
+            Self::Low => 4096,
+            Self::Medium => 10_000,
+            Self::High => 32_000,
+        }
+    }
+
+    /// Budget plus the `max_tokens` to send with it. Anthropic requires
+    /// `max_tokens` to exceed the thinking budget, so the answer allowance is
+    /// added on top when it would not.
+    pub fn budget_with_max_tokens(self, max_tokens: u32) -> (u32, u32) {
+        let budget = self.budget_tokens();
+        if max_tokens > budget {
+            (budget, max_tokens)
+        } else {
+            (budget, budget.saturating_add(max_tokens))
+        }
+    }
+
+    /// OpenAI `reasoning_effort` value.
+    pub fn reasoning_effort(self) -> &'static str {
+        match self {
+            Self::Minimal => "minimal",
+            Self::Low => "low",
+            Self::Medium => "medium",
+            Self::High => "high",
+        }
+    }
+}
+
 /// A request to the LLM.
 #[derive(Debug, Clone)]
 pub struct CompletionRequest {
@@ -55,6 +124,8 @@ pub struct CompletionRequest {
     pub tools: Vec<ToolDefinition>,
     pub max_tokens: u32,
     pub temperature: Option<f64>,
+    /// Extended thinking level; `None` disables thinking.
+    pub thinking: Option<ThinkingLevel>,
     /// Provider-specific extension payload (e.g. Venice's `venice_parameters`).
     /// Providers that don't recognise it simply ignore it.
     pub extra: Option<serde_json::Value>,
@@ -90,6 +161,58 @@ pub enum ContentBlock {
         content: String,
         is_error: bool,
     },
+    /// Reasoning produced by the model before its answer. Only providers
+    /// that accept thinking in history send it back; others skip it.
+    Thinking {
+        thinking: String,
+        signature: Option<String>,
+    },
+    /// An image, base64-encoded.
+    Image {
+        media_type: String,
+        data: String,
+    },
+    /// A file such as a PDF or text document, base64-encoded.
+    Document {
+        media_type: String,
+        data: String,
+        name: Option<String>,
+    },
+}
+
+impl ContentBlock {
+    /// Text stand-in for an image or document the provider cannot accept
+    /// natively, so the model at least knows something was attached.
+    pub fn attachment_placeholder(&self) -> Option<String> {
+        match self {
+            ContentBlock::Image { media_type, .. } => {
+                Some(format!("[{media_type} image omitted: not supported here]"))
+            }
+            ContentBlock::Document {
+                media_type, name, ..
+            } => Some(match name {
+                Some(name) => {
+                    format!("[document \"{name}\" ({media_type}) omitted: not supported here]")
+                }
+                None => format!("[{media_type} document omitted: not supported here]"),
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// Decode a base64 `text/*` document so providers without native document
+/// support can inline it as text.
+pub fn decode_text_document(media_type: &str, data: &str) -> Option<String> {
+    use base64::Engine as _;
+
+    if !media_type.starts_with("text/") {
+        return None;
+    }
+    let bytes = base64::engine::general_purpose::STANDARD
+        .decode(data)
+        .ok()?;
+    String::from_utf8(bytes).ok()
 }
 
 /// A tool definition for the LLM.
@@ -100,6 +223,23 @@ pub struct ToolDefinition {
     pub input_schema: serde_json::Value,
 }
 
+/// A request to embed one or more inputs.
+#[derive(Debug, Clone)]
+pub struct EmbeddingRequest {
+    pub model: String,
+    pub input: Vec<String>,
+    /// Requested output dimensionality, for models that support truncation.
+    pub dimensions: Option<u32>,
+}
+
+/// Embedding vectors, one per input in request order.
+#[derive(Debug, Clone, Default)]
+pub struct EmbeddingResponse {
+    pub embeddings: Vec<Vec<f32>>,
+    /// Input tokens billed by the provider, when it reports them.
+    pub input_tokens: u64,
+}
+
 /// Trait for LLM providers (Anthropic, OpenAI, etc.).
 ///
 /// Implementations send a completion request and return a channel that
@@ -111,10 +251,24 @@ pub trait LlmProvider: Send + Sync {
         request: CompletionRequest,
         cancel_token: CancellationToken,
     ) -> Result<mpsc::Receiver<StreamEvent>, AgentError>;
+
+    /// Embed the request inputs. Providers without an embeddings API keep
+    /// the default, which rejects the request.
+    async fn embed(&self, request: EmbeddingRequest) -> Result<EmbeddingResponse, AgentError> {
+        Err(AgentError::Provider(format!(
+            "model \"{}\" does not support embeddings",
+            request.model
+        )))
+    }
+
+    /// Name of the backend that serves `model`, used to book token usage.
+    fn provider_name(&self, _model: &str) -> &str {
+        "unknown"
+    }
 }
 
-/// A provider that dispatches to Anthropic, OpenAI, Ollama, Gemini, or Bedrock
-/// based on the model identifier in the request.
+/// A provider that dispatches to Anthropic, OpenAI, Ollama, Gemini, Bedrock,
+/// Venice, or a provider plugin based on the model identifier in the request.
 ///
 /// This allows the system to hold a single `Arc<dyn LlmProvider>` while
 /// supporting multiple backend providers transparently.
@@ -125,6 +279,8 @@ pub struct MultiProvider {
     gemini: Option<std::sync::Arc<dyn LlmProvider>>,
     bedrock: Option<std::sync::Arc<dyn LlmProvider>>,
     venice: Option<std::sync::Arc<dyn LlmProvider>>,
+    /// Provider plugins keyed by model prefix (the plugin ID).
+    plugins: Vec<(String, std::sync::Arc<dyn LlmProvider>)>,
 }
 
 impl std::fmt::Debug for MultiProvider {
@@ -136,6 +292,10 @@ impl std::fmt::Debug for MultiProvider {
             .field("gemini", &self.gemini.is_some())
             .field("bedrock", &self.bedrock.is_some())
             .field("venice", &self.venice.is_some())
+            .field(
+                "plugins",
+                &self.plugins.iter().map(|(p, _)| p).collect::<Vec<_>>(),
+            )
             .finish()
     }
 }
@@ -156,6 +316,7 @@ impl MultiProvider {
             gemini: None,
             bedrock: None,
             venice: None,
+            plugins: Vec::new(),
         }
     }
 
@@ -183,6 +344,43 @@ impl MultiProvider {
         self
     }
 
+    /// Add a provider plugin, routed for models prefixed with `<prefix>:` or
+    /// `<prefix>/`.
+    ///
+    /// Plugin prefixes take precedence over the built-in routing rules. A
+    /// later plugin with the same prefix replaces the earlier one.
+    pub fn with_plugin_provider(
+        mut self,
+        prefix: impl Into<String>,
+        provider: std::sync::Arc<dyn LlmProvider>,
+    ) -> Self {
+        let prefix = prefix.into().to_lowercase();
+        self.plugins.retain(|(p, _)| *p != prefix);
+        self.plugins.push((prefix, provider));
+        self
+    }
+
+    /// Find the provider plugin whose prefix matches `model`, returning it
+    /// together with the bare model name.
+    fn select_plugin<'a>(&self, model: &'a str) -> Option<(&dyn LlmProvider, &'a str)> {
+        self.select_plugin_entry(model)
+            .map(|(_, provider, bare)| (provider, bare))
+    }
+
+    /// Like [`select_plugin`](Self::select_plugin), also returning the
+    /// matching prefix.
+    fn select_plugin_entry<'a>(&self, model: &'a str) -> Option<(&str, &dyn LlmProvider, &'a str)> {
+        self.plugins.iter().find_map(|(prefix, provider)| {
+            let head = model.get(..prefix.len())?;
+            let rest = model.get(prefix.len()..)?;
+            if !head.eq_ignore_ascii_case(prefix) {
+                return None;
+            }
+            let bare = rest.strip_prefix(':').or_else(|| rest.strip_prefix('/'))?;
+            Some((prefix.as_str(), provider.as_ref(), bare))
+        })
+    }
+
     /// Returns `true` if at least one provider is configured.
     pub fn has_any_provider(&self) -> bool {
         self.anthropic.is_some()
@@ -191,6 +389,7 @@ impl MultiProvider {
             || self.gemini.is_some()
             || self.bedrock.is_some()
             || self.venice.is_some()
+            || !self.plugins.is_empty()
     }
 
     /// Select the appropriate backend provider for the given model.
@@ -244,6 +443,96 @@ impl MultiProvider {
     }
 }
 
+/// Name of the built-in backend that serves embeddings for `model`.
+///
+/// Follows the completion routing for Ollama, Gemini and Bedrock (plus
+/// Bedrock's `cohere.embed-*` models); everything else is sent to OpenAI,
+/// since Anthropic has no embeddings API.
+pub fn embedding_provider_name(model: &str) -> &'static str {
+    if crate::agent::ollama::is_ollama_model(model) {
+        "ollama"
+    } else if crate::agent::gemini::is_gemini_model(model) {
+        "gemini"
+    } else if crate::agent::bedrock::is_bedrock_model(model)
+        || model.to_lowercase().starts_with("cohere.embed-")
+    {
+        "bedrock"
+    } else {
+        "openai"
+    }
+}
+
+/// Returns `true` if a provider error is transient and the request should be
+/// retried against the next model in a fallback chain.
+///
+/// Retryable: HTTP 408/409/425/429/5xx, transport failures, timeouts, stalled
+/// or truncated streams, overload/rate-limit errors, and models whose
+/// provider is not configured. Everything else (auth, bad request,
+/// cancellation) is treated as final.
+pub fn is_retryable_error(err: &AgentError) -> bool {
+    match err {
+        AgentError::Provider(message) | AgentError::Stream(message) => {
+            is_retryable_message(message)
+        }
+        _ => false,
+    }
+}
+
+/// Classify a provider error message; see [`is_retryable_error`].
+pub fn is_retryable_message(message: &str) -> bool {
+    let lower = message.to_ascii_lowercase();
+
+    if let Some(status) = http_status_from_message(&lower) {
+        return matches!(status, 408 | 409 | 425 | 429 | 500..=599);
+    }
+
+    const FATAL_MARKERS: &[&str] = &[
+        "authentication",
+        "unauthorized",
+        "invalid api key",
+        "invalid x-api-key",
+        "permission",
+        "forbidden",
+    ];
+    if FATAL_MARKERS.iter().any(|m| lower.contains(m)) {
+        return false;
+    }
+
+    // A model routed to a backend that is not configured
+    if lower.contains("requires ") && lower.contains(" provider, but ") {
+        return true;
+    }
+
+    const RETRYABLE_MARKERS: &[&str] = &[
+        "http request failed",
+        "timed out",
+        "timeout",
+        "stalled",
+        "stream ended without stop event",
+        "overloaded",
+        "rate limit",
+        "rate_limit",
+        "too many requests",
+        "connection",
+        "service unavailable",
+        "internal server error",
+        "bad gateway",
+    ];
+    RETRYABLE_MARKERS.iter().any(|m| lower.contains(m))
+}
+
+/// Extract the HTTP status from provider errors of the form
+/// `"API returned 429 Too Many Requests: ..."`.
+fn http_status_from_message(lower: &str) -> Option<u16> {
+    let idx = lower.find("api returned ")?;
+    let rest = &lower[idx + "api returned ".len()..];
+    let digits: String = rest.chars().take_while(|c| c.is_ascii_digit()).collect();
+    if digits.len() != 3 {
+        return None;
+    }
+    digits.parse().ok()
+}
+
 #[async_trait]
 impl LlmProvider for MultiProvider {
     async fn complete(
@@ -251,6 +540,11 @@ impl LlmProvider for MultiProvider {
         mut request: CompletionRequest,
         cancel_token: CancellationToken,
     ) -> Result<mpsc::Receiver<StreamEvent>, AgentError> {
+        if let Some((provider, bare)) = self.select_plugin(&request.model) {
+            request.model = bare.to_string();
+            return provider.complete(request, cancel_token).await;
+        }
+
         let provider = self.select_provider(&request.model)?;
 
         // Strip the ollama: or ollama/ prefix before forwarding to the provider,
@@ -279,6 +573,47 @@ impl LlmProvider for MultiProvider {
 
         provider.complete(request, cancel_token).await
     }
+
+    /// Plugin prefix, or the name of the built-in provider routed to.
+    fn provider_name(&self, model: &str) -> &str {
+        if let Some((prefix, _, _)) = self.select_plugin_entry(model) {
+            return prefix;
+        }
+        self.select_provider(model)
+            .map_or("unknown", |provider| provider.provider_name(model))
+    }
+
+    /// Route an embeddings request; see [`embedding_provider_name`].
+    async fn embed(&self, mut request: EmbeddingRequest) -> Result<EmbeddingResponse, AgentError> {
+        if let Some((provider, bare)) = self.select_plugin(&request.model) {
+            request.model = bare.to_string();
+            return provider.embed(request).await;
+        }
+
+        let model = request.model.clone();
+        let (provider, bare) = match embedding_provider_name(&model) {
+            "ollama" => (
+                self.ollama.as_deref(),
+                crate::agent::ollama::strip_ollama_prefix(&model),
+            ),
+            "gemini" => (
+                self.gemini.as_deref(),
+                crate::agent::gemini::strip_gemini_prefix(&model),
+            ),
+            "bedrock" => (
+                self.bedrock.as_deref(),
+                crate::agent::bedrock::strip_bedrock_prefix(&model),
+            ),
+            _ => (self.openai.as_deref(), model.as_str()),
+        };
+        let provider = provider.ok_or_else(|| {
+            AgentError::Provider(format!(
+                "no configured provider serves embedding model \"{model}\""
+            ))
+        })?;
+        request.model = bare.to_string();
+        provider.embed(request).await
+    }
 }
 
 #[cfg(test)]
@@ -329,6 +664,38 @@ mod tests {
         assert!(msg.contains("OpenAI"), "expected OpenAI in error: {msg}");
     }
 
+    #[test]
+    fn test_embedding_provider_name() {
+        assert_eq!(embedding_provider_name("text-embedding-3-small"), "openai");
+        assert_eq!(embedding_provider_name("ollama:nomic-embed-text"), "ollama");
+        assert_eq!(
+            embedding_provider_name("gemini/text-embedding-004"),
+            "gemini"
+        );
+        assert_eq!(
+            embedding_provider_name("amazon.titan-embed-text-v2:0"),
+            "bedrock"
+        );
+        assert_eq!(
+            embedding_provider_name("cohere.embed-english-v3"),
+            "bedrock"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_multi_provider_embed_unconfigured() {
+        let provider = MultiProvider::new(None, None);
+        let err = provider
+            .embed(EmbeddingRequest {
+                model: "text-embedding-3-small".to_string(),
+                input: vec!["hello".to_string()],
+                dimensions: None,
+            })
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("text-embedding-3-small"));
+    }
+
     #[test]
     fn test_multi_provider_select_ollama_model_colon() {
         let provider = MultiProvider::new(None, None);
@@ -497,4 +864,175 @@ mod tests {
             MultiProvider::new(None, None).with_bedrock(Some(std::sync::Arc::new(bedrock)));
         assert!(provider.has_any_provider());
     }
+
+    // ==================== Plugin routing tests ====================
+
+    /// Records the model it was asked for.
+    struct RecordingProvider(parking_lot::Mutex<Option<String>>);
+
+    #[async_trait]
+    impl LlmProvider for RecordingProvider {
+        async fn complete(
+            &self,
+            request: CompletionRequest,
+            _cancel_token: CancellationToken,
+        ) -> Result<mpsc::Receiver<StreamEvent>, AgentError> {
+            *self.0.lock() = Some(request.model);
+            let (_tx, rx) = mpsc::channel(1);
+            Ok(rx)
+        }
+    }
+
+    fn request_for(model: &str) -> CompletionRequest {
+        CompletionRequest {
+            model: model.to_string(),
+            messages: Vec::new(),
+            system: None,
+            tools: Vec::new(),
+            max_tokens: 16,
+            temperature: None,
+            thinking: None,
+            extra: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_multi_provider_plugin_prefix_dispatch_strips_prefix() {
+        let plugin = std::sync::Arc::new(RecordingProvider(parking_lot::Mutex::new(None)));
+        let provider =
+            MultiProvider::new(None, None).with_plugin_provider("openrouter", plugin.clone());
+        assert!(provider.has_any_provider());
+
+        provider
+            .complete(
+                request_for("openrouter/anthropic/claude-3"),
+                CancellationToken::new(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(plugin.0.lock().as_deref(), Some("anthropic/claude-3"));
+
+        provider
+            .complete(request_for("OpenRouter:qwen-max"), CancellationToken::new())
+            .await
+            .unwrap();
+        assert_eq!(plugin.0.lock().as_deref(), Some("qwen-max"));
+    }
+
+    #[test]
+    fn test_multi_provider_plugin_prefix_requires_separator() {
+        let plugin = std::sync::Arc::new(RecordingProvider(parking_lot::Mutex::new(None)));
+        let provider = MultiProvider::new(None, None).with_plugin_provider("gw", plugin);
+        assert!(provider.select_plugin("gw:model").is_some());
+        assert!(provider.select_plugin("gwmodel").is_none());
+        assert!(provider.select_plugin("gpt-4o").is_none());
+    }
+
+    /// Reports a fixed backend name.
+    struct NamedProvider(&'static str);
+
+    #[async_trait]
+    impl LlmProvider for NamedProvider {
+        async fn complete(
+            &self,
+            _request: CompletionRequest,
+            _cancel_token: CancellationToken,
+        ) -> Result<mpsc::Receiver<StreamEvent>, AgentError> {
+            let (_tx, rx) = mpsc::channel(1);
+            Ok(rx)
+        }
+
+        fn provider_name(&self, _model: &str) -> &str {
+            self.0
+        }
+    }
+
+    #[test]
+    fn test_multi_provider_name_follows_resolved_provider() {
+        let plugin = std::sync::Arc::new(RecordingProvider(parking_lot::Mutex::new(None)));
+        let provider = MultiProvider::new(
+            Some(std::sync::Arc::new(NamedProvider("anthropic"))),
+            Some(std::sync::Arc::new(NamedProvider("openai"))),
+        )
+        .with_plugin_provider("gw", plugin);
+        assert_eq!(provider.provider_name("gw:claude-3"), "gw");
+        assert_eq!(provider.provider_name("gpt-4o"), "openai");
+        assert_eq!(provider.provider_name("claude-sonnet-4"), "anthropic");
+        assert_eq!(provider.provider_name("ollama:llama3"), "unknown");
+    }
+
+    #[test]
+    fn test_retryable_http_statuses() {
+        assert!(is_retryable_message(
+            "API returned 429 Too Many Requests: slow down"
+        ));
+        assert!(is_retryable_message(
+            "API returned 503 Service Unavailable: "
+        ));
+        assert!(is_retryable_message(
+            "Ollama API returned 500 Internal Server Error: x"
+        ));
+        assert!(!is_retryable_message(
+            "API returned 401 Unauthorized: bad key"
+        ));
+        assert!(!is_retryable_message(
+            "API returned 400 Bad Request: invalid"
+        ));
+        // The executor wraps errors with the AgentError display prefix
+        assert!(is_retryable_error(&AgentError::Provider(
+            "LLM provider error: API returned 529 <unknown status code>: overloaded".into()
+        )));
+    }
+
+    #[test]
+    fn test_retryable_transport_and_missing_provider() {
+        let multi = MultiProvider::new(None, None);
+        let err = match multi.select_provider("gpt-4o") {
+            Err(e) => e,
+            Ok(_) => panic!("expected missing-provider error"),
+        };
+        assert!(is_retryable_error(&err));
+        assert!(is_retryable_message(
+            "HTTP request failed: connection refused"
+        ));
+        assert!(is_retryable_message("LLM turn timed out after 600s"));
+        assert!(is_retryable_error(&AgentError::Stream(
+            "stream ended without stop event".into()
+        )));
+        assert!(is_retryable_message("overloaded_error: Overloaded"));
+    }
+
+    #[test]
+    fn test_non_retryable_errors() {
+        assert!(!is_retryable_error(&AgentError::Cancelled));
+        assert!(!is_retryable_error(&AgentError::InvalidApiKey("x".into())));
+        assert!(!is_retryable_message(
+            "authentication_error: invalid x-api-key"
+        ));
+        assert!(!is_retryable_message("something unexpected"));
+    }
+
+    #[test]
+    fn test_thinking_level_parse_and_budget() {
+        assert_eq!(ThinkingLevel::parse("off"), Ok(None));
+        assert_eq!(
+            ThinkingLevel::parse(" High "),
+            Ok(Some(ThinkingLevel::High))
+        );
+        assert_eq!(ThinkingLevel::parse("xhigh"), Ok(Some(ThinkingLevel::High)));
+        assert_eq!(
+            ThinkingLevel::parse("minimal"),
+            Ok(Some(ThinkingLevel::Minimal))
+        );
+        assert!(ThinkingLevel::parse("deep").is_err());
+
+        assert_eq!(
+            ThinkingLevel::Low.budget_with_max_tokens(8192),
+            (4096, 8192)
+        );
+        assert_eq!(
+            ThinkingLevel::High.budget_with_max_tokens(8192),
+            (32_000, 40_192)
+        );
+    }
 }
@@ -0,0 +1,323 @@
+//! Multi-agent routing.
+//!
+//! `agents.routes` is an ordered table binding inbound traffic to agents in
+//! `agents.list`. Each route names an `agentId` and a `match` object; every
+//! condition it sets must hold, and the first matching route wins. Traffic no
+//! route matches goes to the default agent.
+//!
+//! ```json5
+//! agents: {
+//!   list: [{ id: "main", default: true }, { id: "support", model: "..." }],
+//!   routes: [
+//!     { agentId: "support", match: { channel: "telegram", groupId: "-100123" } },
+//!     { agentId: "support", match: { prefix: "/support" } },
+//!   ]
+//! }
+//! ```
+//!
+//! A routed conversation gets its own session, keyed by agent, and the
+//! session records the agent ID so later runs stay with the same agent.
+
+use serde_json::Value;
+use tracing::debug;
+
+/// Conditions a route places on inbound traffic. Unset fields match anything.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RouteMatch {
+    pub channel: Option<String>,
+    pub account_id: Option<String>,
+    pub group_id: Option<String>,
+    pub sender_id: Option<String>,
+    /// Text prefix, e.g. `/support`; compared case-insensitively.
+    pub prefix: Option<String>,
+}
+
+/// A single entry in the routing table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AgentRoute {
+    pub agent_id: String,
+    pub matcher: RouteMatch,
+}
+
+/// The inbound traffic a route is matched against.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RouteRequest<'a> {
+    pub channel: &'a str,
+    pub account_id: Option<&'a str>,
+    pub group_id: Option<&'a str>,
+    pub sender_id: &'a str,
+    pub text: &'a str,
+}
+
+impl RouteMatch {
+    fn matches(&self, request: &RouteRequest<'_>) -> bool {
+        let field = |expected: &Option<String>, actual: Option<&str>| match expected {
+            Some(expected) => actual.is_some_and(|actual| actual == expected),
+            None => true,
+        };
+        field(&self.channel, Some(request.channel))
+            && field(&self.account_id, request.account_id)
+            && field(&self.group_id, request.group_id)
+            && field(&self.sender_id, Some(request.sender_id))
+            && self
+                .prefix
+                .as_deref()
+                .is_none_or(|prefix| has_prefix(request.text, prefix))
+    }
+}
+
+/// Whether `text` starts with `prefix` as a whole word, ignoring case.
+fn has_prefix(text: &str, prefix: &str) -> bool {
+    let text = text.trim_start();
+    text.get(..prefix.len())
+        .is_some_and(|head| head.eq_ignore_ascii_case(prefix))
+        && text[prefix.len()..]
+            .chars()
+            .next()
+            .is_none_or(|c| !c.is_alphanumeric())
+}
+
+/// Ordered routing table from `agents.routes`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RoutingTable {
+    pub routes: Vec<AgentRoute>,
+}
+
+impl RoutingTable {
+    /// Parse `agents.routes`. Routes without an `agentId`, without any match
+    /// condition, or naming an agent missing from `agents.list` are skipped
+    /// (config validation reports them).
+    pub fn from_config(cfg: &Value) -> Self {
+        let agents = cfg.get("agents");
+        let Some(entries) = agents
+            .and_then(|a| a.get("routes"))
+            .and_then(|v| v.as_array())
+        else {
+            return Self::default();
+        };
+        let known = agent_ids(cfg);
+
+        let routes = entries
+            .iter()
+            .enumerate()
+            .filter_map(|(index, entry)| {
+                let route = parse_route(entry);
+                match &route {
+                    None => debug!(index, "ignoring invalid agents.routes entry"),
+                    Some(route) if !known.contains(&route.agent_id.as_str()) => {
+                        debug!(
+                            index,
+                            agent_id = %route.agent_id,
+                            "agents.routes entry names an unknown agent; ignoring"
+                        );
+                        return None;
+                    }
+                    Some(_) => {}
+                }
+                route
+            })
+            .collect();
+        Self { routes }
+    }
+
+    /// The agent the first matching route names, if any.
+    pub fn resolve(&self, request: &RouteRequest<'_>) -> Option<&str> {
+        self.routes
+            .iter()
+            .find(|route| route.matcher.matches(request))
+            .map(|route| route.agent_id.as_str())
+    }
+}
+
+/// IDs of the agents in `agents.list`.
+pub fn agent_ids(cfg: &Value) -> Vec<&str> {
+    cfg.get("agents")
+        .and_then(|a| a.get("list"))
+        .and_then(|v| v.as_array())
+        .map(|list| {
+            list.iter()
+                .filter_map(|entry| entry.get("id").and_then(|v| v.as_str()))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// The default agent: the `agents.list` entry marked `default`, else the
+/// first entry.
+pub fn default_agent_id(cfg: &Value) -> Option<&str> {
+    let list = cfg
+        .get("agents")
+        .and_then(|a| a.get("list"))
+        .and_then(|v| v.as_array())?;
+    list.iter()
+        .find(|entry| entry.get("default").and_then(|v| v.as_bool()) == Some(true))
+        .or_else(|| list.first())
+        .and_then(|entry| entry.get("id"))
+        .and_then(|v| v.as_str())
+}
+
+fn parse_route(entry: &Value) -> Option<AgentRoute> {
+    let agent_id = entry
+        .get("agentId")
+        .and_then(|v| v.as_str())
+        .map(str::trim)
+        .filter(|s| !s.is_empty())?;
+    let conditions = entry.get("match")?;
+    let field = |key: &str| {
+        conditions
+            .get(key)
+            .and_then(|v| match v {
+                Value::String(s) => Some(s.trim().to_string()),
+                Value::Number(n) => Some(n.to_string()),
+                _ => None,
+            })
+            .filter(|s| !s.is_empty())
+    };
+    let matcher = RouteMatch {
+        channel: field("channel"),
+        account_id: field("accountId"),
+        group_id: field("groupId"),
+        sender_id: field("senderId"),
+        prefix: field("prefix"),
+    };
+    if matcher == RouteMatch::default() {
+        return None;
+    }
+    Some(AgentRoute {
+        agent_id: agent_id.to_string(),
+        matcher,
+    })
+}
+
+/// Resolve the agent for inbound traffic from the configured routing table.
+pub fn route_agent(cfg: &Value, request: &RouteRequest<'_>) -> Option<String> {
+    RoutingTable::from_config(cfg)
+        .resolve(request)
+        .map(String::from)
+}
+
+/// Session key for a conversation bound to a routed agent.
+///
+/// Conversations the routing table sends to an agent get a session of their
+/// own, so a prefix-routed message does not land in (and rebind) the
+/// conversation's default session.
+pub fn agent_session_key(agent_id: &str, session_key: &str) -> String {
+    format!("agent:{}:{}", agent_id, session_key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn config() -> Value {
+        json!({
+            "agents": {
+                "list": [
+                    { "id": "main", "default": true },
+                    { "id": "support" },
+                    { "id": "ops" }
+                ],
+                "routes": [
+                    { "agentId": "ops", "match": { "channel": "slack", "groupId": "C-OPS" } },
+                    { "agentId": "support", "match": { "prefix": "/support" } },
+                    { "agentId": "support", "match": { "channel": "telegram", "senderId": 42 } },
+                    { "agentId": "ghost", "match": { "channel": "discord" } },
+                    { "agentId": "ops", "match": {} },
+                    { "match": { "channel": "signal" } }
+                ]
+            }
+        })
+    }
+
+    #[test]
+    fn test_routing_table_skips_invalid_routes() {
+        let table = RoutingTable::from_config(&config());
+        assert_eq!(table.routes.len(), 3);
+        assert_eq!(table.routes[2].matcher.sender_id.as_deref(), Some("42"));
+        assert!(RoutingTable::from_config(&json!({})).routes.is_empty());
+    }
+
+    #[test]
+    fn test_routing_first_match_wins() {
+        let table = RoutingTable::from_config(&config());
+        let request = RouteRequest {
+            channel: "slack",
+            group_id: Some("C-OPS"),
+            sender_id: "U1",
+            text: "/support the build is red",
+            ..Default::default()
+        };
+        assert_eq!(table.resolve(&request), Some("ops"));
+
+        let request = RouteRequest {
+            channel: "slack",
+            group_id: Some("C-OTHER"),
+            sender_id: "U1",
+            text: "  /SUPPORT help",
+            ..Default::default()
+        };
+        assert_eq!(table.resolve(&request), Some("support"));
+
+        let request = RouteRequest {
+            channel: "slack",
+            sender_id: "U1",
+            text: "/supportive words",
+            ..Default::default()
+        };
+        assert_eq!(table.resolve(&request), None);
+
+        let request = RouteRequest {
+            channel: "telegram",
+            sender_id: "42",
+            text: "hello",
+            ..Default::default()
+        };
+        assert_eq!(table.resolve(&request), Some("support"));
+
+        let request = RouteRequest {
+            channel: "discord",
+            sender_id: "42",
+            text: "hello",
+            ..Default::default()
+        };
+        assert_eq!(table.resolve(&request), None);
+    }
+
+    #[test]
+    fn test_route_match_requires_every_condition() {
+        let matcher = RouteMatch {
+            channel: Some("telegram".into()),
+            account_id: Some("bot-1".into()),
+            ..Default::default()
+        };
+        let mut request = RouteRequest {
+            channel: "telegram",
+            sender_id: "1",
+            ..Default::default()
+        };
+        assert!(!matcher.matches(&request));
+        request.account_id = Some("bot-1");
+        assert!(matcher.matches(&request));
+        request.account_id = Some("bot-2");
+        assert!(!matcher.matches(&request));
+    }
+
+    #[test]
+    fn test_default_agent_id() {
+        assert_eq!(default_agent_id(&config()), Some("main"));
+        let cfg = json!({ "agents": { "list": [{ "id": "a" }, { "id": "b", "default": true }] } });
+        assert_eq!(default_agent_id(&cfg), Some("b"));
+        let cfg = json!({ "agents": { "list": [{ "id": "a" }, { "id": "b" }] } });
+        assert_eq!(default_agent_id(&cfg), Some("a"));
+        assert_eq!(default_agent_id(&json!({})), None);
+    }
+
+    #[test]
+    fn test_agent_session_key() {
+        assert_eq!(
+            agent_session_key("support", "telegram:42"),
+            "agent:support:telegram:42"
+        );
+    }
+}
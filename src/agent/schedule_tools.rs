@@ -0,0 +1,821 @@
+//! Scheduling built-in tools.
+//!
+//! Lets the agent schedule follow-up turns for the conversation it is in
+//! ("remind me tomorrow at 9", "check the build every morning"). Each job is
+//! a `main`-target `agentTurn` on the shared [`CronScheduler`], recorded as
+//! created by the calling session: it runs in that session, delivers to the
+//! conversation's channel and recipient, and can only be listed or cancelled
+//! from there. Jobs show up in `cron.list` with a `createdBy` marker.
+//!
+//! Times without an offset are read in the user's timezone: the tool's
+//! `timezone` argument, else `userTimezone` from the agent's entry or
+//! `agents.defaults`, else UTC.
+//!
+//! [`CronScheduler`]: crate::cron::CronScheduler
+
+use chrono::{DateTime, NaiveDateTime, Offset, TimeZone};
+use serde_json::{json, Value};
+
+use crate::cron::{
+    CronError, CronExpr, CronJob, CronJobCreate, CronPayload, CronSchedule, CronSessionTarget,
+    CronWakeMode,
+};
+use crate::plugins::tools::{tool_handler, BuiltinTool, ToolInvokeContext, ToolInvokeResult};
+
+/// Default cap on jobs a single session may have scheduled at once.
+pub const DEFAULT_MAX_JOBS_PER_SESSION: usize = 10;
+
+/// Default shortest interval, in minutes, between runs of a recurring job.
+pub const DEFAULT_MIN_INTERVAL_MINUTES: u64 = 5;
+
+/// Days of upcoming firings checked against the minimum interval: four
+/// years, so month ends, weekdays, DST changes and leap days all come round.
+const INTERVAL_CHECK_DAYS: i64 = 4 * 365 + 1;
+
+/// Formats accepted for `at` times without an offset.
+const LOCAL_TIME_FORMATS: &[&str] = &[
+    "%Y-%m-%dT%H:%M:%S",
+    "%Y-%m-%dT%H:%M",
+    "%Y-%m-%d %H:%M:%S",
+    "%Y-%m-%d %H:%M",
+];
+
+/// Return the scheduling tools.
+pub fn schedule_tools() -> Vec<BuiltinTool> {
+    vec![
+        schedule_create_tool(),
+        schedule_list_tool(),
+        schedule_cancel_tool(),
+    ]
+}
+
+// ---------------------------------------------------------------------------
+// Helpers
+// ---------------------------------------------------------------------------
+
+/// The user's timezone for `agent_id`: `userTimezone` from the agent's entry
+/// in `agents.list`, else from `agents.defaults`.
+fn configured_timezone(cfg: &Value, agent_id: Option<&str>) -> Option<String> {
+    let agents = cfg.get("agents")?.as_object()?;
+    crate::agent::select_agent_entry(agents, agent_id)
+        .and_then(|entry| entry.get("userTimezone"))
+        .or_else(|| agents.get("defaults")?.get("userTimezone"))
+        .and_then(|v| v.as_str())
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+}
+
+/// Resolve the timezone for a call, preferring the explicit argument.
+fn resolve_timezone(
+    args: &Value,
+    cfg: &Value,
+    agent_id: Option<&str>,
+) -> Result<chrono_tz::Tz, ToolInvokeResult> {
+    let name = args
+        .get("timezone")
+        .and_then(|v| v.as_str())
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+        .or_else(|| configured_timezone(cfg, agent_id))
+        .unwrap_or_else(|| "UTC".to_string());
+    name.parse::<chrono_tz::Tz>()
+        .map_err(|_| ToolInvokeResult::tool_error(format!("unknown timezone: {name}")))
+}
+
+/// Parse an `at` time: RFC 3339, or a local date and time in `tz`.
+fn parse_at(value: &str, tz: chrono_tz::Tz) -> Result<u64, String> {
+    let value = value.trim();
+    let instant = match DateTime::parse_from_rfc3339(value) {
+        Ok(dt) => dt.timestamp_millis(),
+        Err(_) => {
+            let naive = LOCAL_TIME_FORMATS
+                .iter()
+                .find_map(|fmt| NaiveDateTime::parse_from_str(value, fmt).ok())
+                .ok_or_else(|| {
+                    format!("invalid time \"{value}\"; use YYYY-MM-DDTHH:MM or RFC 3339")
+                })?;
+            tz.from_local_datetime(&naive)
+                .earliest()
+                .ok_or_else(|| format!("{value} does not exist in {tz}"))?
+                .timestamp_millis()
+        }
+    };
+    u64::try_from(instant).map_err(|_| format!("time out of range: {value}"))
+}
+
+/// Build the schedule from exactly one of `at`, `in_minutes`,
+/// `every_minutes` and `cron`. Returns the schedule and whether the job is
+/// one-shot.
+///
+/// Recurring schedules may not run more often than every `min_interval`
+/// minutes.
+fn parse_schedule(
+    args: &Value,
+    tz: chrono_tz::Tz,
+    now: u64,
+    min_interval: u64,
+) -> Result<(CronSchedule, bool), String> {
+    let given: Vec<&str> = ["at", "in_minutes", "every_minutes", "cron"]
+        .into_iter()
+        .filter(|key| args.get(*key).is_some_and(|v| !v.is_null()))
+        .collect();
+    if given.len() != 1 {
+        return Err("specify exactly one of: at, in_minutes, every_minutes, cron".to_string());
+    }
+
+    match given[0] {
+        "at" => {
+            let at = args["at"].as_str().ok_or("at must be a string")?;
+            let at_ms = parse_at(at, tz)?;
+            if at_ms <= now {
+                return Err(format!("{at} is in the past"));
+            }
+            Ok((CronSchedule::At { at_ms }, true))
+        }
+        "in_minutes" => {
+            let minutes = args["in_minutes"]
+                .as_u64()
+                .filter(|m| *m >= 1)
+                .ok_or("in_minutes must be a positive integer")?;
+            let at_ms = now.saturating_add(minutes.saturating_mul(60_000));
+            Ok((CronSchedule::At { at_ms }, true))
+        }
+        "every_minutes" => {
+            let minutes = args["every_minutes"]
+                .as_u64()
+                .filter(|m| *m >= 1)
+                .ok_or("every_minutes must be a positive integer")?;
+            if minutes < min_interval {
+                return Err(format!("every_minutes must be at least {min_interval}"));
+            }
+            Ok((
+                CronSchedule::Every {
+                    every_ms: minutes.saturating_mul(60_000),
+                    anchor_ms: Some(now),
+                },
+                false,
+            ))
+        }
+        _ => {
+            let expr = args["cron"].as_str().ok_or("cron must be a string")?.trim();
+            let parsed =
+                CronExpr::parse(expr).map_err(|e| format!("invalid cron expression: {e}"))?;
+            if parsed.reboot {
+                return Err("@reboot is not supported for scheduled messages".to_string());
+            }
+            check_cron_interval(&parsed, tz, now, min_interval)?;
+            Ok((
+                CronSchedule::Cron {
+                    expr: expr.to_string(),
+                    tz: Some(tz.name().to_string()),
+                },
+                false,
+            ))
+        }
+    }
+}
+
+/// Reject cron expressions with any two runs closer together than
+/// `min_interval` minutes.
+///
+/// Gaps within a day come from the time fields alone. Gaps across midnight
+/// and DST changes are found by walking the days the expression runs on for
+/// [`INTERVAL_CHECK_DAYS`], stepping through every run only on days whose
+/// UTC offset changes.
+fn check_cron_interval(
+    expr: &CronExpr,
+    tz: chrono_tz::Tz,
+    now: u64,
+    min_interval: u64,
+) -> Result<(), String> {
+    let too_often = || {
+        Err(format!(
+            "cron expression runs more often than every {min_interval} minutes"
+        ))
+    };
+    let min_gap = chrono::Duration::minutes(i64::try_from(min_interval).unwrap_or(i64::MAX));
+
+    // Seconds of the day the expression runs at, in order
+    let times: Vec<u32> = expr
+        .hours
+        .iter()
+        .flat_map(|h| {
+            expr.minutes
+                .iter()
+                .flat_map(move |m| expr.seconds.iter().map(move |s| h * 3600 + m * 60 + s))
+        })
+        .collect();
+    if times
+        .windows(2)
+        .any(|w| chrono::Duration::seconds(i64::from(w[1] - w[0])) < min_gap)
+    {
+        return too_often();
+    }
+    let Some(&last_time) = times.last() else {
+        return Ok(());
+    };
+
+    let Some(start) = DateTime::from_timestamp_millis(now as i64) else {
+        return Ok(());
+    };
+    let end = start + chrono::Duration::days(INTERVAL_CHECK_DAYS);
+    let Some(mut prev) = expr.next_after_tz(&start, &tz) else {
+        return Ok(());
+    };
+    while prev < end {
+        let Some(next) = expr.next_after_tz(&prev, &tz) else {
+            break;
+        };
+        if next - prev < min_gap {
+            return too_often();
+        }
+        // The rest of a day without an offset change was checked above;
+        // resume from its last run
+        let day = next.with_timezone(&tz).date_naive();
+        let offset_at = |date: chrono::NaiveDate| {
+            tz.from_local_datetime(&date.and_hms_opt(0, 0, 0)?)
+                .single()
+                .map(|dt| dt.offset().fix())
+        };
+        let last_run = day
+            .and_hms_opt(last_time / 3600, last_time / 60 % 60, last_time % 60)
+            .and_then(|local| tz.from_local_datetime(&local).single());
+        prev = match (offset_at(day), day.succ_opt().and_then(offset_at), last_run) {
+            (Some(a), Some(b), Some(last_run)) if a == b => last_run.to_utc().max(next),
+            _ => next,
+        };
+    }
+    Ok(())
+}
+
+/// Shortest interval between recurring runs from `cron.minIntervalMinutes`.
+fn min_interval_minutes(cfg: &Value) -> u64 {
+    cfg.get("cron")
+        .and_then(|c| c.get("minIntervalMinutes"))
+        .and_then(|v| v.as_u64())
+        .filter(|n| *n > 0)
+        .unwrap_or(DEFAULT_MIN_INTERVAL_MINUTES)
+}
+
+/// Per-session job cap from `cron.maxJobsPerSession`.
+fn max_jobs_per_session(cfg: &Value) -> usize {
+    cfg.get("cron")
+        .and_then(|c| c.get("maxJobsPerSession"))
+        .and_then(|v| v.as_u64())
+        .filter(|n| *n > 0)
+        .map(|n| n as usize)
+        .unwrap_or(DEFAULT_MAX_JOBS_PER_SESSION)
+}
+
+/// Format a timestamp as RFC 3339 in `tz`.
+fn format_ms(ms: u64, tz: chrono_tz::Tz) -> Option<String> {
+    let dt = DateTime::from_timestamp_millis(i64::try_from(ms).ok()?)?;
+    Some(dt.with_timezone(&tz).to_rfc3339())
+}
+
+/// Summarize a job for the model.
+fn job_summary(job: &CronJob, tz: chrono_tz::Tz) -> Value {
+    let message = match &job.payload {
+        CronPayload::AgentTurn { message, .. } => message.as_str(),
+        CronPayload::SystemEvent { text } => text.as_str(),
+    };
+    json!({
+        "id": job.id,
+        "name": job.name,
+        "message": message,
+        "schedule": job.schedule,
+        "enabled": job.enabled,
+        "nextRun": job.state.next_run_at_ms.and_then(|ms| format_ms(ms, tz)),
+    })
+}
+
+fn scheduler_state(
+    ctx: &ToolInvokeContext,
+) -> Result<&crate::server::ws::WsServerState, ToolInvokeResult> {
+    let state = ctx
+        .state
+        .as_deref()
+        .ok_or_else(|| ToolInvokeResult::tool_error("scheduler not available"))?;
+    if !state.cron_scheduler.is_enabled() {
+        return Err(ToolInvokeResult::tool_error("scheduling is disabled"));
+    }
+    Ok(state)
+}
+
+fn load_config() -> Value {
+    crate::config::load_config().unwrap_or_else(|_| json!({}))
+}
+
+// ---------------------------------------------------------------------------
+// schedule_create
+// ---------------------------------------------------------------------------
+
+fn schedule_create_tool() -> BuiltinTool {
+    BuiltinTool {
+        name: "schedule_create".to_string(),
+        description: "Schedule a message to yourself in this conversation: once at a time, \
+                       after a delay, on an interval, or on a cron schedule. When it fires you \
+                       run with the message and your reply goes to the user. Times without an \
+                       offset are in the user's timezone."
+            .to_string(),
+        input_schema: json!({
+            "type": "object",
+            "properties": {
+                "message": {
+                    "type": "string",
+                    "description": "Instruction you will receive when the job fires, e.g. \"Remind the user to call the dentist\"."
+                },
+                "name": {
+                    "type": "string",
+                    "description": "Short label for the job."
+                },
+                "at": {
+                    "type": "string",
+                    "description": "Run once at this time: YYYY-MM-DDTHH:MM in the user's timezone, or RFC 3339."
+                },
+                "in_minutes": {
+                    "type": "integer",
+                    "description": "Run once after this many minutes."
+                },
+                "every_minutes": {
+                    "type": "integer",
+                    "description": "Run repeatedly at this interval in minutes (at least the configured minimum, 5 by default)."
+                },
+                "cron": {
+                    "type": "string",
+                    "description": "Run on a cron expression in the user's timezone: 5 fields (minute hour day month weekday), an optional leading seconds field, or a macro like @daily. Supports names (MON-FRI), L (last day), W (nearest weekday) and # (nth weekday, e.g. 5#1)."
+                },
+                "timezone": {
+                    "type": "string",
+                    "description": "IANA timezone (e.g. Europe/Berlin) overriding the user's configured one."
+                }
+            },
+            "required": ["message"],
+            "additionalProperties": false
+        }),
+        handler: tool_handler(|args, ctx| async move { handle_schedule_create(args, &ctx) }),
+    }
+}
+
+fn handle_schedule_create(args: Value, ctx: &ToolInvokeContext) -> ToolInvokeResult {
+    let state = match scheduler_state(ctx) {
+        Ok(state) => state,
+        Err(err) => return err,
+    };
+    let message = match args
+        .get("message")
+        .and_then(|v| v.as_str())
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+    {
+        Some(m) => m.to_string(),
+        None => return ToolInvokeResult::tool_error("missing required parameter: message"),
+    };
+
+    let cfg = load_config();
+    let tz = match resolve_timezone(&args, &cfg, ctx.agent_id.as_deref()) {
+        Ok(tz) => tz,
+        Err(err) => return err,
+    };
+    let (schedule, one_shot) =
+        match parse_schedule(&args, tz, crate::cron::now_ms(), min_interval_minutes(&cfg)) {
+            Ok(parsed) => parsed,
+            Err(msg) => return ToolInvokeResult::tool_error(msg),
+        };
+    let name = args
+        .get("name")
+        .and_then(|v| v.as_str())
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+        .unwrap_or_else(|| message.chars().take(60).collect());
+
+    let deliver = ctx.message_channel.is_some() && ctx.recipient_id.is_some();
+    let input = CronJobCreate {
+        name,
+        agent_id: ctx.agent_id.clone(),
+        description: None,
+        enabled: true,
+        delete_after_run: one_shot.then_some(true),
+        schedule,
+        session_target: CronSessionTarget::Main,
+        wake_mode: CronWakeMode::Now,
+        payload: CronPayload::AgentTurn {
+            message,
+            model: None,
+            thinking: None,
+            timeout_seconds: None,
+            allow_unsafe_external_content: None,
+            deliver: deliver.then_some(true),
+            channel: ctx.message_channel.clone().filter(|_| deliver),
+            to: ctx.recipient_id.clone().filter(|_| deliver),
+            best_effort_deliver: None,
+        },
+        isolation: None,
+        policy: Default::default(),
+    };
+
+    let max = max_jobs_per_session(&cfg);
+    match state
+        .cron_scheduler
+        .add_for_session(input, &ctx.session_key, max)
+    {
+        Ok(job) => ToolInvokeResult::success(job_summary(&job, tz)),
+        Err(CronError::SessionLimitExceeded(max)) => ToolInvokeResult::tool_error(format!(
+            "this conversation already has {max} scheduled jobs; cancel one first"
+        )),
+        Err(e) => ToolInvokeResult::tool_error(e.to_string()),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// schedule_list
+// ---------------------------------------------------------------------------
+
+fn schedule_list_tool() -> BuiltinTool {
+    BuiltinTool {
+        name: "schedule_list".to_string(),
+        description: "List the jobs scheduled from this conversation.".to_string(),
+        input_schema: json!({
+            "type": "object",
+            "properties": {},
+            "additionalProperties": false
+        }),
+        handler: tool_handler(|_args, ctx| async move {
+            let state = match scheduler_state(&ctx) {
+                Ok(state) => state,
+                Err(err) => return err,
+            };
+            let cfg = load_config();
+            let tz = match resolve_timezone(&json!({}), &cfg, ctx.agent_id.as_deref()) {
+                Ok(tz) => tz,
+                Err(err) => return err,
+            };
+            let jobs: Vec<Value> = state
+                .cron_scheduler
+                .list_for_session(&ctx.session_key)
+                .iter()
+                .map(|job| job_summary(job, tz))
+                .collect();
+            ToolInvokeResult::success(json!({
+                "timezone": tz.name(),
+                "count": jobs.len(),
+                "jobs": jobs,
+            }))
+        }),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// schedule_cancel
+// ---------------------------------------------------------------------------
+
+fn schedule_cancel_tool() -> BuiltinTool {
+    BuiltinTool {
+        name: "schedule_cancel".to_string(),
+        description: "Cancel a job scheduled from this conversation by its ID.".to_string(),
+        input_schema: json!({
+            "type": "object",
+            "properties": {
+                "id": {
+                    "type": "string",
+                    "description": "Job ID returned by schedule_create or schedule_list."
+                }
+            },
+            "required": ["id"],
+            "additionalProperties": false
+        }),
+        handler: tool_handler(|args, ctx| async move {
+            let state = match scheduler_state(&ctx) {
+                Ok(state) => state,
+                Err(err) => return err,
+            };
+            let Some(id) = args.get("id").and_then(|v| v.as_str()) else {
+                return ToolInvokeResult::tool_error("missing required parameter: id");
+            };
+            let owned = state
+                .cron_scheduler
+                .get(id)
+                .and_then(|job| job.created_by)
+                .is_some_and(|creator| creator.session_key() == ctx.session_key);
+            if !owned {
+                return ToolInvokeResult::tool_error(format!(
+                    "no job {id} was scheduled from this conversation"
+                ));
+            }
+            let removed = state.cron_scheduler.remove(id).removed;
+            ToolInvokeResult::success(json!({ "id": id, "cancelled": removed }))
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::server::ws::{WsServerConfig, WsServerState};
+    use std::sync::Arc;
+
+    fn schedule_ctx(session_key: &str) -> ToolInvokeContext {
+        ToolInvokeContext {
+            agent_id: Some("main".to_string()),
+            session_key: session_key.to_string(),
+            message_channel: Some("telegram".to_string()),
+            recipient_id: Some("chat-42".to_string()),
+            state: Some(Arc::new(WsServerState::new(WsServerConfig::default()))),
+            ..Default::default()
+        }
+    }
+
+    async fn invoke(tool: BuiltinTool, args: Value, ctx: &ToolInvokeContext) -> ToolInvokeResult {
+        (tool.handler)(args, ctx.clone()).await
+    }
+
+    fn success(result: ToolInvokeResult) -> Value {
+        match result {
+            ToolInvokeResult::Success { result, .. } => result,
+            other => panic!("expected success, got {other:?}"),
+        }
+    }
+
+    fn error(result: ToolInvokeResult) -> String {
+        match result {
+            ToolInvokeResult::Error { error, .. } => error.message,
+            other => panic!("expected error, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_create_binds_job_to_conversation() {
+        let ctx = schedule_ctx("telegram:dm:42");
+        let created = success(
+            invoke(
+                schedule_create_tool(),
+                json!({ "message": "Remind the user to stretch", "in_minutes": 30 }),
+                &ctx,
+            )
+            .await,
+        );
+        let id = created["id"].as_str().unwrap();
+
+        let job = ctx.state.as_ref().unwrap().cron_scheduler.get(id).unwrap();
+        assert_eq!(job.agent_id.as_deref(), Some("main"));
+        assert_eq!(job.session_target, CronSessionTarget::Main);
+        assert_eq!(job.delete_after_run, Some(true));
+        assert_eq!(
+            job.created_by.as_ref().map(|c| c.session_key()),
+            Some("telegram:dm:42")
+        );
+        match &job.payload {
+            CronPayload::AgentTurn {
+                deliver,
+                channel,
+                to,
+                ..
+            } => {
+                assert_eq!(*deliver, Some(true));
+                assert_eq!(channel.as_deref(), Some("telegram"));
+                assert_eq!(to.as_deref(), Some("chat-42"));
+            }
+            other => panic!("unexpected payload {other:?}"),
+        }
+        let listed = serde_json::to_value(&job).unwrap();
+        assert_eq!(listed["createdBy"]["kind"], "agent");
+    }
+
+    #[tokio::test]
+    async fn test_list_and_cancel_are_scoped_to_session() {
+        let ctx = schedule_ctx("telegram:dm:42");
+        let created = success(
+            invoke(
+                schedule_create_tool(),
+                json!({ "message": "Daily digest", "cron": "0 9 * * *", "timezone": "Europe/Berlin" }),
+                &ctx,
+            )
+            .await,
+        );
+        let id = created["id"].as_str().unwrap().to_string();
+        assert_eq!(created["schedule"]["tz"], "Europe/Berlin");
+
+        let other = ToolInvokeContext {
+            session_key: "telegram:dm:7".to_string(),
+            ..ctx.clone()
+        };
+        let listed = success(invoke(schedule_list_tool(), json!({}), &other).await);
+        assert_eq!(listed["count"], 0);
+        let err = error(invoke(schedule_cancel_tool(), json!({ "id": id }), &other).await);
+        assert!(err.contains("no job"));
+
+        let listed = success(invoke(schedule_list_tool(), json!({}), &ctx).await);
+        assert_eq!(listed["count"], 1);
+        assert_eq!(listed["jobs"][0]["id"], id);
+
+        let cancelled = success(invoke(schedule_cancel_tool(), json!({ "id": id }), &ctx).await);
+        assert_eq!(cancelled["cancelled"], true);
+        let listed = success(invoke(schedule_list_tool(), json!({}), &ctx).await);
+        assert_eq!(listed["count"], 0);
+    }
+
+    #[tokio::test]
+    async fn test_create_enforces_session_quota() {
+        let ctx = schedule_ctx("telegram:dm:42");
+        for i in 0..DEFAULT_MAX_JOBS_PER_SESSION {
+            success(
+                invoke(
+                    schedule_create_tool(),
+                    json!({ "message": format!("job {i}"), "every_minutes": 60 }),
+                    &ctx,
+                )
+                .await,
+            );
+        }
+        let err = error(
+            invoke(
+                schedule_create_tool(),
+                json!({ "message": "one too many", "every_minutes": 60 }),
+                &ctx,
+            )
+            .await,
+        );
+        assert!(err.contains("cancel one first"));
+
+        // Other conversations have their own quota
+        let other = ToolInvokeContext {
+            session_key: "telegram:dm:7".to_string(),
+            ..ctx.clone()
+        };
+        success(
+            invoke(
+                schedule_create_tool(),
+                json!({ "message": "mine", "every_minutes": 60 }),
+                &other,
+            )
+            .await,
+        );
+    }
+
+    #[tokio::test]
+    async fn test_create_rejects_bad_schedules() {
+        let ctx = schedule_ctx("main");
+        let err = error(invoke(schedule_create_tool(), json!({ "message": "x" }), &ctx).await);
+        assert!(err.contains("exactly one"));
+        let err = error(
+            invoke(
+                schedule_create_tool(),
+                json!({ "message": "x", "in_minutes": 5, "cron": "* * * * *" }),
+                &ctx,
+            )
+            .await,
+        );
+        assert!(err.contains("exactly one"));
+        let err = error(
+            invoke(
+                schedule_create_tool(),
+                json!({ "message": "x", "at": "2001-01-01T09:00" }),
+                &ctx,
+            )
+            .await,
+        );
+        assert!(err.contains("in the past"));
+        let err = error(
+            invoke(
+                schedule_create_tool(),
+                json!({ "message": "x", "cron": "not cron" }),
+                &ctx,
+            )
+            .await,
+        );
+        assert!(err.contains("invalid cron expression"));
+        let err = error(
+            invoke(
+                schedule_create_tool(),
+                json!({ "message": "x", "cron": "@reboot" }),
+                &ctx,
+            )
+            .await,
+        );
+        assert!(err.contains("@reboot"));
+        let err = error(
+            invoke(
+                schedule_create_tool(),
+                json!({ "message": "x", "in_minutes": 5, "timezone": "Mars/Olympus" }),
+                &ctx,
+            )
+            .await,
+        );
+        assert!(err.contains("unknown timezone"));
+    }
+
+    #[tokio::test]
+    async fn test_requires_scheduler() {
+        let ctx = ToolInvokeContext::default();
+        let err = error(invoke(schedule_list_tool(), json!({}), &ctx).await);
+        assert!(err.contains("not available"));
+    }
+
+    #[test]
+    fn test_parse_at_uses_timezone() {
+        let berlin: chrono_tz::Tz = "Europe/Berlin".parse().unwrap();
+        // 09:00 in Berlin (CEST, UTC+2) is 07:00 UTC
+        let ms = parse_at("2030-06-01T09:00", berlin).unwrap();
+        assert_eq!(
+            ms,
+            parse_at("2030-06-01T07:00:00Z", chrono_tz::UTC).unwrap()
+        );
+        assert_eq!(parse_at("2030-06-01 09:00", berlin).unwrap(), ms);
+        // An explicit offset wins over the timezone
+        assert_eq!(
+            parse_at("2030-06-01T09:00:00+02:00", chrono_tz::UTC).unwrap(),
+            ms
+        );
+        // Local times skipped by a DST change do not exist
+        assert!(parse_at("2030-03-31T02:30", berlin).is_err());
+        assert!(parse_at("tomorrow", berlin).is_err());
+    }
+
+    #[test]
+    fn test_configured_timezone() {
+        let cfg = json!({
+            "agents": {
+                "defaults": { "userTimezone": "America/New_York" },
+                "list": [{ "id": "ops", "userTimezone": "Asia/Tokyo" }]
+            }
+        });
+        assert_eq!(
+            configured_timezone(&cfg, Some("ops")).as_deref(),
+            Some("Asia/Tokyo")
+        );
+        assert_eq!(
+            configured_timezone(&cfg, Some("other")).as_deref(),
+            Some("America/New_York")
+        );
+        assert_eq!(configured_timezone(&json!({}), None), None);
+
+        let tz = resolve_timezone(&json!({ "timezone": "UTC" }), &cfg, Some("ops")).unwrap();
+        assert_eq!(tz, chrono_tz::UTC);
+    }
+
+    #[test]
+    fn test_max_jobs_per_session() {
+        assert_eq!(
+            max_jobs_per_session(&json!({})),
+            DEFAULT_MAX_JOBS_PER_SESSION
+        );
+        assert_eq!(
+            max_jobs_per_session(&json!({ "cron": { "maxJobsPerSession": 3 } })),
+            3
+        );
+    }
+
+    #[test]
+    fn test_min_interval_applies_to_every_and_cron() {
+        let now = 1_700_000_000_000;
+        let tz = chrono_tz::UTC;
+        assert_eq!(
+            min_interval_minutes(&json!({})),
+            DEFAULT_MIN_INTERVAL_MINUTES
+        );
+        assert_eq!(
+            min_interval_minutes(&json!({ "cron": { "minIntervalMinutes": 15 } })),
+            15
+        );
+
+        let err = parse_schedule(&json!({ "every_minutes": 4 }), tz, now, 5).unwrap_err();
+        assert!(err.contains("at least 5"));
+        assert!(parse_schedule(&json!({ "every_minutes": 5 }), tz, now, 5).is_ok());
+
+        for expr in ["* * * * *", "*/30 * * * * *", "0,2 9 * * *"] {
+            let err = parse_schedule(&json!({ "cron": expr }), tz, now, 5).unwrap_err();
+            assert!(err.contains("more often"), "{expr}: {err}");
+        }
+        for expr in ["*/5 * * * *", "0 9 * * MON-FRI", "@hourly"] {
+            assert!(parse_schedule(&json!({ "cron": expr }), tz, now, 5).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_min_interval_finds_rare_short_gaps() {
+        // 2023-11-14: the first short gap is months away
+        let now = 1_700_000_000_000;
+        let utc = chrono_tz::UTC;
+
+        // Odd days at 00:00 and 23:00: only a month ending on the 31st runs
+        // an hour before the next month's 1st
+        let odd_days = json!({ "cron": "0 0,23 */2 * *" });
+        let err = parse_schedule(&odd_days, utc, now, 90).unwrap_err();
+        assert!(err.contains("more often"), "{err}");
+        assert!(parse_schedule(&odd_days, utc, now, 60).is_ok());
+
+        // 01:00 and 03:00 are an hour apart when clocks spring forward
+        let new_york: chrono_tz::Tz = "America/New_York".parse().unwrap();
+        let early = json!({ "cron": "0 1,3 * * *" });
+        let err = parse_schedule(&early, new_york, now, 90).unwrap_err();
+        assert!(err.contains("more often"), "{err}");
+        assert!(parse_schedule(&early, utc, now, 90).is_ok());
+
+        // Twice on the first of the month
+        let monthly = json!({ "cron": "0,1 9 1 * *" });
+        assert!(parse_schedule(&monthly, utc, now, 5).is_err());
+    }
+}
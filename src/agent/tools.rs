@@ -20,7 +20,7 @@ pub enum ToolCallResult {
 /// When `sandbox_config` is provided and enabled, the `sandboxed` flag is set
 /// on the `ToolInvokeContext` so that tool handlers can apply OS-level
 /// sandboxing to spawned subprocesses.
-pub fn execute_tool_call(
+pub async fn execute_tool_call(
     tool_name: &str,
     tool_input: Value,
     tools_registry: &ToolsRegistry,
@@ -36,6 +36,7 @@ pub fn execute_tool_call(
         None,
         None,
     )
+    .await
 }
 
 /// Execute a tool call with optional sandbox configuration.
@@ -43,7 +44,7 @@ pub fn execute_tool_call(
 /// This is the full-featured entry point that accepts an optional
 /// `ProcessSandboxConfig`. The `sandboxed` flag on the invoke context
 /// reflects whether sandboxing is active.
-pub fn execute_tool_call_with_sandbox(
+pub async fn execute_tool_call_with_sandbox(
     tool_name: &str,
     tool_input: Value,
     tools_registry: &ToolsRegistry,
@@ -51,6 +52,29 @@ pub fn execute_tool_call_with_sandbox(
     agent_id: Option<&str>,
     message_channel: Option<&str>,
     sandbox_config: Option<&ProcessSandboxConfig>,
+) -> ToolCallResult {
+    let ctx = ToolInvokeContext {
+        agent_id: agent_id.map(|s| s.to_string()),
+        session_key: session_key.to_string(),
+        message_channel: message_channel.map(|s| s.to_string()),
+        ..Default::default()
+    };
+
+    execute_tool_call_with_context(tool_name, tool_input, tools_registry, ctx, sandbox_config).await
+}
+
+/// Execute a tool call with a caller-supplied invoke context.
+///
+/// Used by the agent executor, which threads the originating conversation,
+/// the run's cancellation token and the server state through to tool
+/// handlers. The `sandboxed` flag on `ctx` is overwritten to reflect
+/// `sandbox_config`.
+pub async fn execute_tool_call_with_context(
+    tool_name: &str,
+    tool_input: Value,
+    tools_registry: &ToolsRegistry,
+    mut ctx: ToolInvokeContext,
+    sandbox_config: Option<&ProcessSandboxConfig>,
 ) -> ToolCallResult {
     let sandboxed = sandbox_config.is_some_and(|c| c.enabled);
 
@@ -64,16 +88,9 @@ pub fn execute_tool_call_with_sandbox(
         );
     }
 
-    let ctx = ToolInvokeContext {
-        agent_id: agent_id.map(|s| s.to_string()),
-        session_key: session_key.to_string(),
-        message_channel: message_channel.map(|s| s.to_string()),
-        account_id: None,
-        sandboxed,
-        dry_run: false,
-    };
+    ctx.sandboxed = sandboxed;
 
-    let result = tools_registry.invoke(tool_name, tool_input, &ctx);
+    let result = tools_registry.invoke(tool_name, tool_input, &ctx).await;
 
     match result {
         ToolInvokeResult::Success { result, .. } => ToolCallResult::Ok {
@@ -106,10 +123,11 @@ mod tests {
     use super::*;
     use serde_json::json;
 
-    #[test]
-    fn test_execute_unknown_tool() {
+    #[tokio::test]
+    async fn test_execute_unknown_tool() {
         let registry = ToolsRegistry::new();
-        let result = execute_tool_call("nonexistent_tool", json!({}), &registry, "sess-1", None);
+        let result =
+            execute_tool_call("nonexistent_tool", json!({}), &registry, "sess-1", None).await;
         match result {
             ToolCallResult::Error { message } => {
                 assert!(
@@ -121,11 +139,11 @@ mod tests {
         }
     }
 
-    #[test]
-    fn test_execute_builtin_tool() {
+    #[tokio::test]
+    async fn test_execute_builtin_tool() {
         // The default registry ships with a "time" builtin
         let registry = ToolsRegistry::new();
-        let result = execute_tool_call("time", json!({}), &registry, "sess-1", None);
+        let result = execute_tool_call("time", json!({}), &registry, "sess-1", None).await;
         match result {
             ToolCallResult::Ok { output } => {
                 assert!(!output.is_empty(), "time tool should return output");
@@ -159,18 +177,18 @@ mod tests {
         );
     }
 
-    #[test]
-    fn test_execute_with_empty_name() {
+    #[tokio::test]
+    async fn test_execute_with_empty_name() {
         let registry = ToolsRegistry::new();
-        let result = execute_tool_call("", json!({}), &registry, "sess-1", None);
+        let result = execute_tool_call("", json!({}), &registry, "sess-1", None).await;
         match result {
             ToolCallResult::Error { .. } => {} // expected
             ToolCallResult::Ok { .. } => panic!("expected error for empty tool name"),
         }
     }
 
-    #[test]
-    fn test_execute_with_valid_json_args() {
+    #[tokio::test]
+    async fn test_execute_with_valid_json_args() {
         let registry = ToolsRegistry::new();
         // "time" tool ignores its args, but it should still succeed with arbitrary JSON
         let result = execute_tool_call(
@@ -179,7 +197,8 @@ mod tests {
             &registry,
             "sess-1",
             Some("agent-1"),
-        );
+        )
+        .await;
         match result {
             ToolCallResult::Ok { output } => {
                 assert!(output.contains("UTC"), "time tool returns UTC: {output}");
@@ -58,6 +58,10 @@ impl LlmProvider for VeniceProvider {
         let body = self.build_venice_body(&request);
         self.inner.complete_with_body(body, cancel_token).await
     }
+
+    fn provider_name(&self, _model: &str) -> &str {
+        "venice"
+    }
 }
 
 /// Determine whether a model identifier should route to the Venice provider.
@@ -155,6 +159,7 @@ mod tests {
             tools: vec![],
             max_tokens: 1024,
             temperature: None,
+            thinking: None,
             extra: None,
         };
         let body = provider.inner.build_body(&request);
@@ -177,6 +182,7 @@ mod tests {
             tools: vec![],
             max_tokens: 4096,
             temperature: Some(0.3),
+            thinking: None,
             extra: None,
         };
         let body = provider.inner.build_body(&request);
@@ -206,6 +212,7 @@ mod tests {
             }],
             max_tokens: 2048,
             temperature: None,
+            thinking: None,
             extra: None,
         };
         let body = provider.inner.build_body(&request);
@@ -233,6 +240,7 @@ mod tests {
             tools: vec![],
             max_tokens: 1024,
             temperature: None,
+            thinking: None,
             extra: Some(venice_params.clone()),
         };
         let body = provider.build_venice_body(&request);
@@ -259,6 +267,7 @@ mod tests {
             tools: vec![],
             max_tokens: 1024,
             temperature: None,
+            thinking: None,
             extra: None,
         };
         let body = provider.build_venice_body(&request);
@@ -0,0 +1,1020 @@
+//! Inbound sender access control
+//!
+//! Each messaging channel can restrict who may talk to the agent via
+//! `channels.<channel>.access`:
+//!
+//! - `open` (default): every sender is dispatched.
+//! - `allowlist`: only senders listed in `allowFrom` or approved through
+//!   pairing are dispatched; everyone else is dropped.
+//! - `pairing`: like `allowlist`, but an unknown sender receives a one-time
+//!   code that an operator approves via `channels.pair.approve` or
+//!   `cara senders approve`.
+//!
+//! Approved senders and pending pairing codes are persisted in
+//! `channel-senders.json` under the state directory. Every allowlist/pairing
+//! decision is written to the audit log. If the config cannot be loaded,
+//! access fails closed: only senders approved through pairing are admitted.
+
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::Write as IoWrite;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tracing::{debug, warn};
+
+use crate::devices::PairingState;
+use crate::logging::audit::{audit, AuditEvent};
+use crate::server::ws::WsServerState;
+
+/// Maximum number of approved senders across all channels
+pub const MAX_APPROVED_SENDERS: usize = 1000;
+
+/// Maximum number of pending pairing requests per channel; a new request
+/// beyond it expires the channel's oldest pending request
+pub const MAX_PENDING_REQUESTS: usize = 50;
+
+/// Pairing code expiry (1 hour)
+pub const PAIRING_CODE_EXPIRY_MS: u64 = 60 * 60 * 1000;
+
+/// Length of a pairing code
+pub const PAIRING_CODE_LEN: usize = 8;
+
+/// Pairing code alphabet: uppercase letters and digits without the easily
+/// confused `0`/`O`, `1`/`I`.
+const PAIRING_CODE_ALPHABET: &[u8] = b"ABCDEFGHJKLMNPQRSTUVWXYZ23456789";
+
+/// Per-channel inbound access mode
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AccessMode {
+    #[default]
+    Open,
+    Allowlist,
+    Pairing,
+}
+
+impl AccessMode {
+    /// Parse a config value (`open`, `allowlist`, `pairing`).
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "open" => Some(Self::Open),
+            "allowlist" => Some(Self::Allowlist),
+            "pairing" => Some(Self::Pairing),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Open => "open",
+            Self::Allowlist => "allowlist",
+            Self::Pairing => "pairing",
+        }
+    }
+}
+
+/// Access policy for one channel, read from `channels.<channel>.access`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ChannelAccessPolicy {
+    pub mode: AccessMode,
+    /// Sender IDs that are always allowed.
+    pub allow_from: Vec<String>,
+}
+
+impl ChannelAccessPolicy {
+    /// Read the policy for `channel` from config.
+    ///
+    /// An unrecognized mode fails closed to `allowlist`.
+    pub fn from_config(cfg: &Value, channel: &str) -> Self {
+        let section = cfg
+            .get("channels")
+            .and_then(|c| c.get(channel))
+            .and_then(|c| c.get("access"));
+        let mode = match section.and_then(|s| s.get("mode")).and_then(|v| v.as_str()) {
+            None => AccessMode::Open,
+            Some(raw) => AccessMode::parse(raw).unwrap_or_else(|| {
+                warn!(
+                    channel = %channel,
+                    mode = %raw,
+                    "unknown channel access mode; treating as allowlist"
+                );
+                AccessMode::Allowlist
+            }),
+        };
+        let allow_from = section
+            .and_then(|s| s.get("allowFrom"))
+            .and_then(|v| v.as_array())
+            .map(|entries| {
+                entries
+                    .iter()
+                    .filter_map(|entry| match entry {
+                        Value::String(s) => Some(s.trim().to_string()),
+                        Value::Number(n) => Some(n.to_string()),
+                        _ => None,
+                    })
+                    .filter(|s| !s.is_empty())
+                    .collect()
+            })
+            .unwrap_or_default();
+        Self { mode, allow_from }
+    }
+
+    /// Policy used when the config cannot be loaded: only senders approved
+    /// through pairing are admitted and no pairing codes are issued.
+    pub fn fail_closed() -> Self {
+        Self {
+            mode: AccessMode::Allowlist,
+            allow_from: Vec::new(),
+        }
+    }
+
+    /// Read the policy for `channel` from a config load result, failing
+    /// closed when the config could not be loaded.
+    pub fn from_loaded<E: std::fmt::Display>(cfg: Result<Arc<Value>, E>, channel: &str) -> Self {
+        match cfg {
+            Ok(cfg) => Self::from_config(cfg.as_ref(), channel),
+            Err(err) => {
+                warn!(
+                    channel = %channel,
+                    error = %err,
+                    "config unavailable; denying unapproved senders"
+                );
+                Self::fail_closed()
+            }
+        }
+    }
+
+    fn allows(&self, sender_id: &str) -> bool {
+        self.allow_from.iter().any(|id| id == sender_id)
+    }
+}
+
+/// A pending (or recently resolved) sender pairing request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SenderPairingRequest {
+    /// One-time pairing code sent to the sender
+    pub code: String,
+    /// Channel the sender wrote from
+    pub channel: String,
+    /// Channel-specific sender ID
+    pub sender_id: String,
+    /// Direct-message chat the code was sent to; the sender's user ID when
+    /// unset
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub chat_id: Option<String>,
+    /// Current state of the request
+    pub state: PairingState,
+    /// Timestamp when request was created (Unix ms)
+    pub created_at_ms: u64,
+    /// Timestamp when request was resolved (Unix ms)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resolved_at_ms: Option<u64>,
+}
+
+impl SenderPairingRequest {
+    /// Check if the request has expired
+    pub fn is_expired(&self) -> bool {
+        if self.state != PairingState::Pending {
+            return false;
+        }
+        now_ms().saturating_sub(self.created_at_ms) > PAIRING_CODE_EXPIRY_MS
+    }
+
+    /// Timestamp when the code stops being accepted (Unix ms)
+    pub fn expires_at_ms(&self) -> u64 {
+        self.created_at_ms + PAIRING_CODE_EXPIRY_MS
+    }
+}
+
+/// A sender approved through pairing
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApprovedSender {
+    /// Channel the sender is approved on
+    pub channel: String,
+    /// Channel-specific sender ID
+    pub sender_id: String,
+    /// Timestamp when approved (Unix ms)
+    pub approved_at_ms: u64,
+}
+
+/// Persistent store for sender access data
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SenderAccessStore {
+    /// Version for schema migration
+    pub version: u32,
+    /// Pairing requests by code
+    #[serde(default)]
+    pub pending_requests: HashMap<String, SenderPairingRequest>,
+    /// Approved senders by `channel:sender_id`
+    #[serde(default)]
+    pub approved_senders: HashMap<String, ApprovedSender>,
+}
+
+impl SenderAccessStore {
+    pub const VERSION: u32 = 1;
+
+    pub fn new() -> Self {
+        Self {
+            version: Self::VERSION,
+            pending_requests: HashMap::new(),
+            approved_senders: HashMap::new(),
+        }
+    }
+}
+
+/// Errors that can occur during sender access operations
+#[derive(Debug, Clone, PartialEq)]
+pub enum SenderAccessError {
+    /// No pairing request with this code on this channel
+    RequestNotFound,
+    /// Request already resolved
+    RequestAlreadyResolved,
+    /// Request expired
+    RequestExpired,
+    /// Sender is not approved
+    SenderNotApproved,
+    /// Too many approved senders
+    TooManyApprovedSenders,
+    /// I/O error
+    IoError(String),
+    /// JSON error
+    JsonError(String),
+}
+
+impl std::fmt::Display for SenderAccessError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::RequestNotFound => write!(f, "pairing code not found"),
+            Self::RequestAlreadyResolved => write!(f, "pairing code already resolved"),
+            Self::RequestExpired => write!(f, "pairing code expired"),
+            Self::SenderNotApproved => write!(f, "sender not approved"),
+            Self::TooManyApprovedSenders => write!(f, "too many approved senders"),
+            Self::IoError(msg) => write!(f, "I/O error: {}", msg),
+            Self::JsonError(msg) => write!(f, "JSON error: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for SenderAccessError {}
+
+/// Result of a pairing request for a sender
+pub struct SenderPairingOutcome {
+    pub request: SenderPairingRequest,
+    /// False when a pending request already existed for the sender.
+    pub created: bool,
+}
+
+/// Thread-safe sender access registry with persistence
+pub struct SenderAccessRegistry {
+    /// In-memory store
+    store: RwLock<SenderAccessStore>,
+    /// Path to persistent storage
+    storage_path: PathBuf,
+    /// Whether to auto-save on changes
+    auto_save: bool,
+}
+
+impl std::fmt::Debug for SenderAccessRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SenderAccessRegistry")
+            .field("storage_path", &self.storage_path)
+            .field("auto_save", &self.auto_save)
+            .finish()
+    }
+}
+
+impl SenderAccessRegistry {
+    /// Create a new registry with the given storage path
+    pub fn new(storage_path: PathBuf) -> Result<Self, SenderAccessError> {
+        let store = Self::load_or_create(&storage_path)?;
+        Ok(Self {
+            store: RwLock::new(store),
+            storage_path,
+            auto_save: true,
+        })
+    }
+
+    /// Create an in-memory only registry (for testing)
+    pub fn in_memory() -> Self {
+        Self {
+            store: RwLock::new(SenderAccessStore::new()),
+            storage_path: PathBuf::new(),
+            auto_save: false,
+        }
+    }
+
+    /// Load store from disk or create a new one
+    fn load_or_create(path: &PathBuf) -> Result<SenderAccessStore, SenderAccessError> {
+        if !path.exists() {
+            return Ok(SenderAccessStore::new());
+        }
+
+        let content =
+            fs::read_to_string(path).map_err(|e| SenderAccessError::IoError(e.to_string()))?;
+
+        serde_json::from_str(&content).map_err(|e| {
+            // If corrupted, backup and create new
+            let backup = path.with_extension(format!("corrupt.{}.json", now_ms()));
+            if let Err(err) = fs::rename(path, &backup) {
+                warn!(
+                    path = %path.display(),
+                    backup = %backup.display(),
+                    error = %err,
+                    "failed to backup corrupted sender access store"
+                );
+            } else {
+                warn!(
+                    path = %path.display(),
+                    backup = %backup.display(),
+                    "backed up corrupted sender access store"
+                );
+            }
+            SenderAccessError::JsonError(e.to_string())
+        })
+    }
+
+    /// Save store to disk
+    fn save(&self) -> Result<(), SenderAccessError> {
+        if !self.auto_save || self.storage_path.as_os_str().is_empty() {
+            return Ok(());
+        }
+
+        let store = self.store.read();
+        let content = serde_json::to_string_pretty(&*store)
+            .map_err(|e| SenderAccessError::JsonError(e.to_string()))?;
+        drop(store);
+
+        if let Some(parent) = self.storage_path.parent() {
+            fs::create_dir_all(parent).map_err(|e| SenderAccessError::IoError(e.to_string()))?;
+        }
+
+        // Write atomically
+        let temp_path = self.storage_path.with_extension("tmp");
+        let mut file =
+            File::create(&temp_path).map_err(|e| SenderAccessError::IoError(e.to_string()))?;
+        IoWrite::write_all(&mut file, content.as_bytes())
+            .map_err(|e| SenderAccessError::IoError(e.to_string()))?;
+        file.sync_all()
+            .map_err(|e| SenderAccessError::IoError(e.to_string()))?;
+        fs::rename(&temp_path, &self.storage_path)
+            .map_err(|e| SenderAccessError::IoError(e.to_string()))?;
+
+        Ok(())
+    }
+
+    /// Expire stale codes and drop requests resolved more than an hour ago
+    fn cleanup_expired(&self) {
+        let mut store = self.store.write();
+        let now = now_ms();
+        for request in store.pending_requests.values_mut() {
+            if request.is_expired() {
+                request.state = PairingState::Expired;
+                request.resolved_at_ms = Some(now);
+            }
+        }
+        let cutoff = now.saturating_sub(PAIRING_CODE_EXPIRY_MS);
+        store.pending_requests.retain(|_, req| {
+            req.state == PairingState::Pending
+                || req.resolved_at_ms.map(|t| t > cutoff).unwrap_or(true)
+        });
+    }
+
+    /// Whether `sender_id` was approved on `channel`
+    pub fn is_approved(&self, channel: &str, sender_id: &str) -> bool {
+        self.store
+            .read()
+            .approved_senders
+            .contains_key(&sender_key(channel, sender_id))
+    }
+
+    /// Issue a pairing code for an unknown sender.
+    ///
+    /// A sender with a live pending code keeps it; `created` is false then.
+    pub fn request_pairing(
+        &self,
+        channel: &str,
+        sender_id: &str,
+        chat_id: Option<&str>,
+    ) -> Result<SenderPairingOutcome, SenderAccessError> {
+        self.cleanup_expired();
+
+        let mut store = self.store.write();
+        if let Some(existing) = store.pending_requests.values().find(|r| {
+            r.state == PairingState::Pending && r.channel == channel && r.sender_id == sender_id
+        }) {
+            return Ok(SenderPairingOutcome {
+                request: existing.clone(),
+                created: false,
+            });
+        }
+
+        // Make room on this channel by expiring its oldest codes, so a burst
+        // of unknown senders cannot lock out newer ones (or other channels)
+        let now = now_ms();
+        let mut pending: Vec<&mut SenderPairingRequest> = store
+            .pending_requests
+            .values_mut()
+            .filter(|r| r.state == PairingState::Pending && r.channel == channel)
+            .collect();
+        if pending.len() >= MAX_PENDING_REQUESTS {
+            pending.sort_by_key(|r| r.created_at_ms);
+            let excess = pending.len() + 1 - MAX_PENDING_REQUESTS;
+            for request in pending.into_iter().take(excess) {
+                request.state = PairingState::Expired;
+                request.resolved_at_ms = Some(now);
+            }
+        }
+
+        let code = loop {
+            let code = generate_pairing_code()?;
+            if !store.pending_requests.contains_key(&code) {
+                break code;
+            }
+        };
+        let request = SenderPairingRequest {
+            code: code.clone(),
+            channel: channel.to_string(),
+            sender_id: sender_id.to_string(),
+            chat_id: chat_id.map(String::from),
+            state: PairingState::Pending,
+            created_at_ms: now,
+            resolved_at_ms: None,
+        };
+        store.pending_requests.insert(code, request.clone());
+        drop(store);
+
+        self.save()?;
+        Ok(SenderPairingOutcome {
+            request,
+            created: true,
+        })
+    }
+
+    /// Look up the pending request for `code` on `channel`
+    fn find_pending(
+        store: &SenderAccessStore,
+        channel: &str,
+        code: &str,
+    ) -> Result<SenderPairingRequest, SenderAccessError> {
+        let request = store
+            .pending_requests
+            .get(&normalize_code(code))
+            .filter(|r| r.channel == channel)
+            .ok_or(SenderAccessError::RequestNotFound)?;
+        match request.state {
+            PairingState::Pending => Ok(request.clone()),
+            PairingState::Expired => Err(SenderAccessError::RequestExpired),
+            PairingState::Approved | PairingState::Rejected => {
+                Err(SenderAccessError::RequestAlreadyResolved)
+            }
+        }
+    }
+
+    /// Approve the sender behind a pairing code
+    pub fn approve(&self, channel: &str, code: &str) -> Result<ApprovedSender, SenderAccessError> {
+        self.cleanup_expired();
+
+        let mut store = self.store.write();
+        let request = Self::find_pending(&store, channel, code)?;
+        let key = sender_key(channel, &request.sender_id);
+        if !store.approved_senders.contains_key(&key)
+            && store.approved_senders.len() >= MAX_APPROVED_SENDERS
+        {
+            return Err(SenderAccessError::TooManyApprovedSenders);
+        }
+
+        let now = now_ms();
+        if let Some(entry) = store.pending_requests.get_mut(&request.code) {
+            entry.state = PairingState::Approved;
+            entry.resolved_at_ms = Some(now);
+        }
+        let sender = ApprovedSender {
+            channel: channel.to_string(),
+            sender_id: request.sender_id,
+            approved_at_ms: now,
+        };
+        store.approved_senders.insert(key, sender.clone());
+        drop(store);
+
+        self.save()?;
+        Ok(sender)
+    }
+
+    /// Reject a pairing code; the sender stays unapproved
+    pub fn reject(
+        &self,
+        channel: &str,
+        code: &str,
+    ) -> Result<SenderPairingRequest, SenderAccessError> {
+        self.cleanup_expired();
+
+        let mut store = self.store.write();
+        let mut request = Self::find_pending(&store, channel, code)?;
+        request.state = PairingState::Rejected;
+        request.resolved_at_ms = Some(now_ms());
+        store
+            .pending_requests
+            .insert(request.code.clone(), request.clone());
+        drop(store);
+
+        self.save()?;
+        Ok(request)
+    }
+
+    /// Remove a previously approved sender
+    pub fn revoke(
+        &self,
+        channel: &str,
+        sender_id: &str,
+    ) -> Result<ApprovedSender, SenderAccessError> {
+        let removed = self
+            .store
+            .write()
+            .approved_senders
+            .remove(&sender_key(channel, sender_id))
+            .ok_or(SenderAccessError::SenderNotApproved)?;
+        self.save()?;
+        Ok(removed)
+    }
+
+    /// List pending requests, oldest first
+    pub fn list_pending(&self) -> Vec<SenderPairingRequest> {
+        self.cleanup_expired();
+        let mut pending: Vec<_> = self
+            .store
+            .read()
+            .pending_requests
+            .values()
+            .filter(|r| r.state == PairingState::Pending)
+            .cloned()
+            .collect();
+        pending.sort_by_key(|r| r.created_at_ms);
+        pending
+    }
+
+    /// List approved senders, by channel then approval time
+    pub fn list_approved(&self) -> Vec<ApprovedSender> {
+        let mut approved: Vec<_> = self
+            .store
+            .read()
+            .approved_senders
+            .values()
+            .cloned()
+            .collect();
+        approved.sort_by(|a, b| {
+            a.channel
+                .cmp(&b.channel)
+                .then(a.approved_at_ms.cmp(&b.approved_at_ms))
+        });
+        approved
+    }
+}
+
+/// Outcome of checking an inbound sender against its channel policy
+#[derive(Debug, Clone)]
+pub enum AccessDecision {
+    /// Dispatch the message
+    Allowed,
+    /// Drop the message
+    Denied,
+    /// Drop the message; a new pairing code was issued to the sender
+    PairingRequested(SenderPairingRequest),
+    /// Drop the message; the sender already holds a pending code
+    PairingPending,
+}
+
+impl AccessDecision {
+    pub fn is_allowed(&self) -> bool {
+        matches!(self, Self::Allowed)
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Allowed => "allowed",
+            Self::Denied => "denied",
+            Self::PairingRequested(_) => "pairing_requested",
+            Self::PairingPending => "pairing_pending",
+        }
+    }
+}
+
+/// Decide whether a sender may reach the agent on `channel`.
+///
+/// Pairing codes only go out by direct message: to the DM chat for direct
+/// messages, and to the sender's user ID for group messages. On channels
+/// that cannot message a user by ID, unknown senders in groups are denied
+/// without a code.
+pub fn decide(
+    policy: &ChannelAccessPolicy,
+    registry: &SenderAccessRegistry,
+    channel: &str,
+    sender_id: &str,
+    chat_id: Option<&str>,
+    is_group: bool,
+) -> AccessDecision {
+    if is_admitted(policy, registry, channel, sender_id) {
+        return AccessDecision::Allowed;
+    }
+    if policy.mode == AccessMode::Allowlist || sender_id.is_empty() {
+        return AccessDecision::Denied;
+    }
+    let dm_chat = if is_group {
+        if !can_dm_sender(channel) {
+            return AccessDecision::Denied;
+        }
+        None
+    } else {
+        chat_id
+    };
+    match registry.request_pairing(channel, sender_id, dm_chat) {
+        Ok(outcome) if outcome.created => AccessDecision::PairingRequested(outcome.request),
+        Ok(_) => AccessDecision::PairingPending,
+        Err(err) => {
+            warn!(channel = %channel, error = %err, "failed to issue sender pairing code");
+            AccessDecision::Denied
+        }
+    }
+}
+
+/// Whether `channel` can send a direct message to a sender given only their
+/// user ID. Discord needs a DM channel to be opened first.
+fn can_dm_sender(channel: &str) -> bool {
+    matches!(channel, "telegram" | "slack" | "signal")
+}
+
+/// Whether the policy admits a sender outright, without issuing a pairing
+/// code.
+pub fn is_admitted(
+    policy: &ChannelAccessPolicy,
+    registry: &SenderAccessRegistry,
+    channel: &str,
+    sender_id: &str,
+) -> bool {
+    policy.mode == AccessMode::Open
+        || policy.allows(sender_id)
+        || registry.is_approved(channel, sender_id)
+}
+
+/// Check an inbound sender against the channel's access policy without
+/// side effects: nothing is audited and no pairing code is issued. Used for
+/// messages that are only recorded, never answered.
+pub fn is_sender_admitted(state: &Arc<WsServerState>, channel: &str, sender_id: &str) -> bool {
+    let policy = ChannelAccessPolicy::from_loaded(crate::config::load_config_shared(), channel);
+    is_admitted(&policy, state.sender_access(), channel, sender_id)
+}
+
+/// Check an inbound sender against the channel's access policy.
+///
+/// Allowlist and pairing decisions are audited. When a pairing code is
+/// issued, it is sent back to the sender and announced to operators with a
+/// `channels.pair.requested` event. Returns whether the message may be
+/// dispatched.
+pub fn authorize_sender(
+    state: &Arc<WsServerState>,
+    channel: &str,
+    sender_id: &str,
+    chat_id: Option<&str>,
+    is_group: bool,
+) -> bool {
+    let policy = ChannelAccessPolicy::from_loaded(crate::config::load_config_shared(), channel);
+    if policy.mode == AccessMode::Open {
+        return true;
+    }
+
+    let decision = decide(
+        &policy,
+        state.sender_access(),
+        channel,
+        sender_id,
+        chat_id,
+        is_group,
+    );
+    audit(AuditEvent::ChannelAccessDecision {
+        channel: channel.to_string(),
+        sender_id: sender_id.to_string(),
+        mode: policy.mode.as_str().to_string(),
+        decision: decision.as_str().to_string(),
+    });
+    debug!(
+        channel = %channel,
+        sender = %sender_id,
+        decision = decision.as_str(),
+        "Inbound sender access checked"
+    );
+
+    if let AccessDecision::PairingRequested(request) = &decision {
+        send_pairing_code(state, request);
+        crate::server::ws::broadcast_channel_pair_requested(state, request);
+    }
+    decision.is_allowed()
+}
+
+/// Send an unknown sender their pairing code by direct message.
+fn send_pairing_code(state: &WsServerState, request: &SenderPairingRequest) {
+    use crate::messages::outbound::{
+        MessageContent, MessageMetadata, OutboundContext, OutboundMessage,
+    };
+
+    let text = format!(
+        "This assistant only answers approved senders. Your pairing code is {}. \
+         Ask the operator to approve it; the code expires in 1 hour.",
+        request.code
+    );
+    let recipient = request
+        .chat_id
+        .clone()
+        .unwrap_or_else(|| request.sender_id.clone());
+    let outbound = OutboundMessage::new(request.channel.clone(), MessageContent::text(text))
+        .with_metadata(MessageMetadata {
+            recipient_id: Some(recipient),
+            ..Default::default()
+        });
+    let ctx = OutboundContext::new()
+        .with_trace_id(&request.code)
+        .with_source("channel-access");
+    if let Err(err) = state.message_pipeline().queue(outbound, ctx) {
+        warn!(
+            channel = %request.channel,
+            error = %err,
+            "failed to queue sender pairing code"
+        );
+    }
+}
+
+/// Canonical form of a user-typed pairing code
+pub fn normalize_code(code: &str) -> String {
+    code.chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_uppercase())
+        .collect()
+}
+
+fn generate_pairing_code() -> Result<String, SenderAccessError> {
+    let mut bytes = [0u8; PAIRING_CODE_LEN];
+    getrandom::fill(&mut bytes).map_err(|e| SenderAccessError::IoError(e.to_string()))?;
+    Ok(bytes
+        .iter()
+        .map(|b| PAIRING_CODE_ALPHABET[*b as usize % PAIRING_CODE_ALPHABET.len()] as char)
+        .collect())
+}
+
+fn sender_key(channel: &str, sender_id: &str) -> String {
+    format!("{}:{}", channel, sender_id)
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or(Duration::ZERO)
+        .as_millis() as u64
+}
+
+/// Create a shared sender access registry
+pub fn create_registry(state_dir: PathBuf) -> Result<Arc<SenderAccessRegistry>, SenderAccessError> {
+    let storage_path = state_dir.join("channel-senders.json");
+    Ok(Arc::new(SenderAccessRegistry::new(storage_path)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn pairing_policy() -> ChannelAccessPolicy {
+        ChannelAccessPolicy {
+            mode: AccessMode::Pairing,
+            allow_from: vec!["42".to_string()],
+        }
+    }
+
+    #[test]
+    fn test_policy_from_config() {
+        assert_eq!(
+            ChannelAccessPolicy::from_config(&json!({}), "telegram"),
+            ChannelAccessPolicy::default()
+        );
+
+        let cfg = json!({
+            "channels": {
+                "telegram": { "access": { "mode": "pairing", "allowFrom": ["12", 34, "", true] } }
+            }
+        });
+        let policy = ChannelAccessPolicy::from_config(&cfg, "telegram");
+        assert_eq!(policy.mode, AccessMode::Pairing);
+        assert_eq!(policy.allow_from, vec!["12", "34"]);
+
+        // Channel credentials live at the top level; access does not
+        let top_level = json!({ "telegram": { "access": { "mode": "pairing" } } });
+        assert_eq!(
+            ChannelAccessPolicy::from_config(&top_level, "telegram").mode,
+            AccessMode::Open
+        );
+
+        let unknown = json!({ "channels": { "slack": { "access": { "mode": "closed" } } } });
+        let policy = ChannelAccessPolicy::from_config(&unknown, "slack");
+        assert_eq!(policy.mode, AccessMode::Allowlist);
+    }
+
+    #[test]
+    fn test_policy_fails_closed_when_config_fails_to_load() {
+        let registry = SenderAccessRegistry::in_memory();
+        let policy = ChannelAccessPolicy::from_loaded::<&str>(Err("unreadable"), "telegram");
+        assert_eq!(policy, ChannelAccessPolicy::fail_closed());
+        assert!(matches!(
+            decide(&policy, &registry, "telegram", "7", Some("7"), false),
+            AccessDecision::Denied
+        ));
+        assert!(registry.list_pending().is_empty());
+
+        let loaded = ChannelAccessPolicy::from_loaded::<&str>(Ok(Arc::new(json!({}))), "telegram");
+        assert_eq!(loaded.mode, AccessMode::Open);
+    }
+
+    #[test]
+    fn test_decide_open_and_allowlist() {
+        let registry = SenderAccessRegistry::in_memory();
+        let open = ChannelAccessPolicy::default();
+        assert!(decide(&open, &registry, "slack", "U1", None, false).is_allowed());
+
+        let allowlist = ChannelAccessPolicy {
+            mode: AccessMode::Allowlist,
+            allow_from: vec!["U1".to_string()],
+        };
+        assert!(decide(&allowlist, &registry, "slack", "U1", None, false).is_allowed());
+        assert!(matches!(
+            decide(&allowlist, &registry, "slack", "U2", None, false),
+            AccessDecision::Denied
+        ));
+        assert!(registry.list_pending().is_empty());
+    }
+
+    #[test]
+    fn test_pairing_issues_one_code_per_sender() {
+        let registry = SenderAccessRegistry::in_memory();
+        let policy = pairing_policy();
+
+        assert!(decide(&policy, &registry, "telegram", "42", Some("42"), false).is_allowed());
+
+        let code = match decide(&policy, &registry, "telegram", "7", Some("c7"), false) {
+            AccessDecision::PairingRequested(request) => {
+                assert_eq!(request.chat_id.as_deref(), Some("c7"));
+                request.code
+            }
+            other => panic!("expected pairing request, got {:?}", other),
+        };
+        assert_eq!(code.len(), PAIRING_CODE_LEN);
+        assert!(code.bytes().all(|b| PAIRING_CODE_ALPHABET.contains(&b)));
+
+        assert!(matches!(
+            decide(&policy, &registry, "telegram", "7", Some("c7"), false),
+            AccessDecision::PairingPending
+        ));
+        assert_eq!(registry.list_pending().len(), 1);
+    }
+
+    #[test]
+    fn test_group_pairing_codes_go_by_direct_message() {
+        let registry = SenderAccessRegistry::in_memory();
+        let policy = pairing_policy();
+
+        match decide(&policy, &registry, "telegram", "7", Some("-100"), true) {
+            AccessDecision::PairingRequested(request) => assert_eq!(request.chat_id, None),
+            other => panic!("expected pairing request, got {:?}", other),
+        }
+
+        // Discord cannot DM a user ID: no code is posted into the group
+        assert!(matches!(
+            decide(&policy, &registry, "discord", "9", Some("chan-1"), true),
+            AccessDecision::Denied
+        ));
+        assert_eq!(registry.list_pending().len(), 1);
+        assert!(matches!(
+            decide(&policy, &registry, "discord", "9", Some("dm-9"), false),
+            AccessDecision::PairingRequested(_)
+        ));
+    }
+
+    #[test]
+    fn test_pending_cap_is_per_channel_and_evicts_oldest() {
+        let registry = SenderAccessRegistry::in_memory();
+        let oldest = registry
+            .request_pairing("telegram", "sender-0", None)
+            .unwrap()
+            .request
+            .code;
+        registry
+            .store
+            .write()
+            .pending_requests
+            .get_mut(&oldest)
+            .unwrap()
+            .created_at_ms -= 1000;
+        for i in 1..MAX_PENDING_REQUESTS {
+            registry
+                .request_pairing("telegram", &format!("sender-{}", i), None)
+                .unwrap();
+        }
+        registry.request_pairing("slack", "other", None).unwrap();
+        assert_eq!(registry.list_pending().len(), MAX_PENDING_REQUESTS + 1);
+
+        let outcome = registry
+            .request_pairing("telegram", "newest", None)
+            .unwrap();
+        assert!(outcome.created);
+        assert_eq!(registry.list_pending().len(), MAX_PENDING_REQUESTS + 1);
+        assert!(matches!(
+            registry.approve("telegram", &oldest),
+            Err(SenderAccessError::RequestExpired)
+        ));
+        assert!(registry.approve("telegram", &outcome.request.code).is_ok());
+    }
+
+    #[test]
+    fn test_approve_reject_and_revoke() {
+        let registry = SenderAccessRegistry::in_memory();
+        let first = registry.request_pairing("signal", "+1555", None).unwrap();
+        let second = registry.request_pairing("signal", "+1666", None).unwrap();
+
+        assert_eq!(
+            registry
+                .approve("telegram", &first.request.code)
+                .unwrap_err(),
+            SenderAccessError::RequestNotFound
+        );
+        let lowercase = first.request.code.to_lowercase();
+        let approved = registry.approve("signal", &lowercase).unwrap();
+        assert_eq!(approved.sender_id, "+1555");
+        assert!(registry.is_approved("signal", "+1555"));
+        assert!(!registry.is_approved("telegram", "+1555"));
+        assert_eq!(
+            registry.approve("signal", &first.request.code).unwrap_err(),
+            SenderAccessError::RequestAlreadyResolved
+        );
+
+        let rejected = registry.reject("signal", &second.request.code).unwrap();
+        assert_eq!(rejected.state, PairingState::Rejected);
+        assert!(!registry.is_approved("signal", "+1666"));
+        assert!(registry.list_pending().is_empty());
+
+        assert_eq!(registry.list_approved().len(), 1);
+        registry.revoke("signal", "+1555").unwrap();
+        assert!(!registry.is_approved("signal", "+1555"));
+        assert_eq!(
+            registry.revoke("signal", "+1555").unwrap_err(),
+            SenderAccessError::SenderNotApproved
+        );
+    }
+
+    #[test]
+    fn test_expired_code_cannot_be_approved() {
+        let registry = SenderAccessRegistry::in_memory();
+        let outcome = registry.request_pairing("discord", "u1", None).unwrap();
+        registry
+            .store
+            .write()
+            .pending_requests
+            .get_mut(&outcome.request.code)
+            .unwrap()
+            .created_at_ms = now_ms() - PAIRING_CODE_EXPIRY_MS - 1;
+
+        assert_eq!(
+            registry
+                .approve("discord", &outcome.request.code)
+                .unwrap_err(),
+            SenderAccessError::RequestExpired
+        );
+        // A fresh code is issued once the old one has expired.
+        let renewed = registry.request_pairing("discord", "u1", None).unwrap();
+        assert!(renewed.created);
+    }
+
+    #[test]
+    fn test_persistence_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let registry = create_registry(dir.path().to_path_buf()).unwrap();
+        let outcome = registry.request_pairing("slack", "U9", Some("D9")).unwrap();
+        registry.approve("slack", &outcome.request.code).unwrap();
+        let pending = registry.request_pairing("slack", "U10", None).unwrap();
+
+        let reloaded = create_registry(dir.path().to_path_buf()).unwrap();
+        assert!(reloaded.is_approved("slack", "U9"));
+        let codes: Vec<_> = reloaded
+            .list_pending()
+            .into_iter()
+            .map(|r| r.code)
+            .collect();
+        assert_eq!(codes, vec![pending.request.code]);
+    }
+
+    #[test]
+    fn test_normalize_code() {
+        assert_eq!(normalize_code(" abcd-2345 "), "ABCD2345");
+    }
+}
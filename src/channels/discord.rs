@@ -8,14 +8,16 @@ use reqwest::StatusCode;
 use serde_json::{json, Value};
 
 use crate::channels::media_fetch::fetch_media_bytes;
-use crate::channels::{ChannelAuthError, ChannelAuthResult};
+use crate::channels::{retry_after_header_ms, seconds_to_ms, ChannelAuthError, ChannelAuthResult};
 use crate::plugins::{
     BindingError, ChannelCapabilities, ChannelInfo, ChannelPluginInstance, ChatType,
-    DeliveryResult, OutboundContext,
+    DeliveryResult, MessageTarget, OutboundContext, PollContext,
 };
 
 /// Maximum media size to fetch and upload (25 MB).
 const MAX_MEDIA_BYTES: u64 = 25 * 1024 * 1024;
+/// Poll duration in hours.
+const POLL_DURATION_HOURS: u32 = 24;
 #[allow(dead_code)]
 const VALIDATION_TIMEOUT_SECS: u64 = 5;
 
@@ -81,6 +83,21 @@ impl DiscordChannel {
         Err(ChannelAuthError::auth(message.to_string()))
     }
 
+    /// Send an authenticated request to an API path.
+    fn call(&self, method: reqwest::Method, path: &str, body: Option<&Value>) -> DeliveryResult {
+        let mut request = self.client.request(method, self.api_url(path)).header(
+            reqwest::header::AUTHORIZATION,
+            format!("Bot {}", self.bot_token),
+        );
+        if let Some(body) = body {
+            request = request.json(body);
+        }
+        match request.send() {
+            Ok(resp) => Self::parse_response(resp),
+            Err(e) => error_result(format!("request failed: {}", e), true),
+        }
+    }
+
     #[allow(clippy::result_large_err)]
     fn fetch_media(&self, media_url: &str) -> Result<Vec<u8>, DeliveryResult> {
         fetch_media_bytes(media_url, MAX_MEDIA_BYTES)
@@ -88,6 +105,7 @@ impl DiscordChannel {
 
     fn parse_response(resp: reqwest::blocking::Response) -> DeliveryResult {
         let status = resp.status();
+        let retry_after_header = retry_after_header_ms(resp.headers());
         let body_text = resp.text().unwrap_or_default();
         let parsed: Value = serde_json::from_str(&body_text).unwrap_or(Value::Null);
 
@@ -109,10 +127,19 @@ impl DiscordChannel {
             })
             .unwrap_or_else(|| format!("HTTP {}", status));
 
-        error_result(
+        let mut result = error_result(
             error,
             status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS,
-        )
+        );
+        // 429 bodies carry a fractional `retry_after` in seconds
+        if status == StatusCode::TOO_MANY_REQUESTS {
+            result.retry_after_ms = parsed
+                .get("retry_after")
+                .and_then(|v| v.as_f64())
+                .and_then(seconds_to_ms)
+                .or(retry_after_header);
+        }
+        result
     }
 }
 
@@ -136,6 +163,10 @@ impl ChannelPluginInstance for DiscordChannel {
                 ChatType::Channel,
                 ChatType::Thread,
             ],
+            polls: true,
+            reactions: true,
+            edit: true,
+            unsend: true,
             media: true,
             reply: true,
             threads: true,
@@ -226,6 +257,173 @@ impl ChannelPluginInstance for DiscordChannel {
             Err(e) => Ok(error_result(format!("request failed: {}", e), true)),
         }
     }
+
+    fn send_poll(&self, ctx: PollContext) -> Result<DeliveryResult, BindingError> {
+        let answers: Vec<Value> = ctx
+            .poll
+            .options
+            .iter()
+            .map(|option| json!({ "poll_media": { "text": option } }))
+            .collect();
+        let body = json!({
+            "poll": {
+                "question": { "text": ctx.poll.question },
+                "answers": answers,
+                "duration": POLL_DURATION_HOURS,
+                "allow_multiselect": ctx.poll.allow_multiple,
+            }
+        });
+        let mut result = self.call(
+            reqwest::Method::POST,
+            &format!("channels/{}/messages", ctx.to),
+            Some(&body),
+        );
+        // Discord polls are identified by the message that carries them.
+        result.poll_id = result.message_id.clone();
+        Ok(result)
+    }
+
+    fn edit_message(
+        &self,
+        target: MessageTarget,
+        new_text: String,
+    ) -> Result<DeliveryResult, BindingError> {
+        if new_text.is_empty() {
+            return Ok(error_result("text must not be empty", false));
+        }
+        if let Err(e) = validate_snowflake(&target.message_id) {
+            return Ok(e);
+        }
+        Ok(self.call(
+            reqwest::Method::PATCH,
+            &format!("channels/{}/messages/{}", target.to, target.message_id),
+            Some(&json!({ "content": new_text })),
+        ))
+    }
+
+    fn delete_message(&self, target: MessageTarget) -> Result<DeliveryResult, BindingError> {
+        if let Err(e) = validate_snowflake(&target.message_id) {
+            return Ok(e);
+        }
+        let result = self.call(
+            reqwest::Method::DELETE,
+            &format!("channels/{}/messages/{}", target.to, target.message_id),
+            None,
+        );
+        Ok(with_message_id(result, target.message_id))
+    }
+
+    fn react(&self, target: MessageTarget, emoji: String) -> Result<DeliveryResult, BindingError> {
+        if let Err(e) = validate_snowflake(&target.message_id) {
+            return Ok(e);
+        }
+        if emoji.trim().is_empty() {
+            return Ok(error_result("emoji must not be empty", false));
+        }
+        let result = self.call(
+            reqwest::Method::PUT,
+            &format!(
+                "channels/{}/messages/{}/reactions/{}/@me",
+                target.to,
+                target.message_id,
+                urlencoding::encode(emoji.trim())
+            ),
+            None,
+        );
+        Ok(with_message_id(result, target.message_id))
+    }
+
+    fn create_thread(
+        &self,
+        target: MessageTarget,
+        name: String,
+        auto_archive_minutes: Option<u32>,
+    ) -> Result<DeliveryResult, BindingError> {
+        if let Err(e) = validate_snowflake(&target.message_id) {
+            return Ok(e);
+        }
+        if name.trim().is_empty() {
+            return Ok(error_result("thread name must not be empty", false));
+        }
+        let body = json!({
+            "name": name.trim(),
+            "auto_archive_duration": auto_archive_duration(auto_archive_minutes),
+        });
+        Ok(self.call(
+            reqwest::Method::POST,
+            &format!(
+                "channels/{}/messages/{}/threads",
+                target.to, target.message_id
+            ),
+            Some(&body),
+        ))
+    }
+
+    fn send_embed(
+        &self,
+        ctx: OutboundContext,
+        embed: Value,
+    ) -> Result<DeliveryResult, BindingError> {
+        let channel_id = ctx
+            .thread_id
+            .as_deref()
+            .filter(|id| !id.is_empty())
+            .unwrap_or(&ctx.to);
+
+        let mut body = json!({ "embeds": [discord_embed(embed)] });
+        if let Some(reply_to) = ctx.reply_to_id.as_deref() {
+            body["message_reference"] = json!({ "message_id": reply_to });
+        }
+        Ok(self.call(
+            reqwest::Method::POST,
+            &format!("channels/{}/messages", channel_id),
+            Some(&body),
+        ))
+    }
+}
+
+/// Thread auto-archive durations Discord accepts, in minutes
+const AUTO_ARCHIVE_DURATIONS: [u32; 4] = [60, 1440, 4320, 10080];
+
+/// Round a requested auto-archive duration up to one Discord accepts,
+/// defaulting to 24 hours.
+fn auto_archive_duration(minutes: Option<u32>) -> u32 {
+    let minutes = minutes.unwrap_or(1440);
+    AUTO_ARCHIVE_DURATIONS
+        .into_iter()
+        .find(|d| *d >= minutes)
+        .unwrap_or(10080)
+}
+
+/// Build a Discord embed object from tool arguments, turning a plain
+/// `footer` string into `{ "text": … }`.
+fn discord_embed(mut embed: Value) -> Value {
+    if let Some(footer) = embed.get("footer").and_then(Value::as_str) {
+        embed["footer"] = json!({ "text": footer });
+    }
+    embed
+}
+
+/// Discord IDs are numeric snowflakes; reject anything else before it is
+/// interpolated into a request path.
+#[allow(clippy::result_large_err)]
+fn validate_snowflake(id: &str) -> Result<(), DeliveryResult> {
+    if !id.is_empty() && id.bytes().all(|b| b.is_ascii_digit()) {
+        Ok(())
+    } else {
+        Err(error_result(
+            "invalid message_id: expected snowflake",
+            false,
+        ))
+    }
+}
+
+/// Report the target message ID on successful calls that return no message.
+fn with_message_id(mut result: DeliveryResult, message_id: String) -> DeliveryResult {
+    if result.ok && result.message_id.is_none() {
+        result.message_id = Some(message_id);
+    }
+    result
 }
 
 fn value_to_string(value: &Value) -> Option<String> {
@@ -255,6 +453,7 @@ fn success_result(message_id: Option<String>) -> DeliveryResult {
         conversation_id: None,
         to_jid: None,
         poll_id: None,
+        retry_after_ms: None,
     }
 }
 
@@ -267,6 +466,7 @@ fn error_result(error: impl Into<String>, retryable: bool) -> DeliveryResult {
         conversation_id: None,
         to_jid: None,
         poll_id: None,
+        retry_after_ms: None,
     }
 }
 
@@ -295,6 +495,10 @@ mod tests {
         assert!(caps.media);
         assert!(caps.reply);
         assert!(caps.threads);
+        assert!(caps.polls);
+        assert!(caps.reactions);
+        assert!(caps.edit);
+        assert!(caps.unsend);
         assert_eq!(
             caps.chat_types,
             vec![
@@ -349,4 +553,83 @@ mod tests {
         assert!(!result.ok);
         assert!(result.retryable);
     }
+
+    fn target(message_id: &str) -> MessageTarget {
+        MessageTarget {
+            to: "123".to_string(),
+            message_id: message_id.to_string(),
+            account_id: None,
+        }
+    }
+
+    #[test]
+    fn test_discord_operations_reject_invalid_message_id() {
+        let ch = test_channel();
+        for id in ["", "abc", "1/../2"] {
+            assert!(!ch.delete_message(target(id)).unwrap().ok);
+            assert!(!ch.edit_message(target(id), "x".to_string()).unwrap().ok);
+            assert!(!ch.react(target(id), "👍".to_string()).unwrap().ok);
+        }
+    }
+
+    #[test]
+    fn test_discord_react_rejects_empty_emoji() {
+        let ch = test_channel();
+        let result = ch.react(target("42"), " ".to_string()).unwrap();
+        assert!(!result.ok);
+        assert!(!result.retryable);
+    }
+
+    #[test]
+    fn test_discord_operations_connection_failure() {
+        let ch = DiscordChannel::new("http://192.0.2.1:1".to_string(), "token".to_string());
+        let result = ch.edit_message(target("42"), "edited".to_string()).unwrap();
+        assert!(!result.ok);
+        assert!(result.retryable);
+
+        let poll = PollContext {
+            to: "123".to_string(),
+            poll: crate::plugins::PollInput {
+                question: "Lunch?".to_string(),
+                options: vec!["Pizza".to_string(), "Sushi".to_string()],
+                allow_multiple: true,
+            },
+            account_id: None,
+        };
+        let result = ch.send_poll(poll).unwrap();
+        assert!(!result.ok);
+        assert!(result.poll_id.is_none());
+    }
+
+    #[test]
+    fn test_discord_create_thread_validates_input() {
+        let ch = test_channel();
+        assert!(
+            !ch.create_thread(target("abc"), "Topic".to_string(), None)
+                .unwrap()
+                .ok
+        );
+        let result = ch
+            .create_thread(target("123"), " ".to_string(), None)
+            .unwrap();
+        assert!(!result.ok);
+        assert!(!result.retryable);
+    }
+
+    #[test]
+    fn test_auto_archive_duration_rounds_up() {
+        assert_eq!(auto_archive_duration(None), 1440);
+        assert_eq!(auto_archive_duration(Some(1)), 60);
+        assert_eq!(auto_archive_duration(Some(1441)), 4320);
+        assert_eq!(auto_archive_duration(Some(100_000)), 10080);
+    }
+
+    #[test]
+    fn test_discord_embed_wraps_footer() {
+        let embed = discord_embed(json!({ "title": "Build", "footer": "ci" }));
+        assert_eq!(
+            embed,
+            json!({ "title": "Build", "footer": { "text": "ci" } })
+        );
+    }
 }
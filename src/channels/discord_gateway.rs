@@ -9,9 +9,11 @@ use serde::Deserialize;
 use serde_json::{json, Value};
 use tokio::sync::Mutex;
 use tokio_tungstenite::tungstenite::Message;
-use tracing::{debug, error, info, warn};
+use tracing::{debug, info, warn};
 
-use crate::channels::inbound::dispatch_inbound_text;
+use crate::channels::inbound::{
+    dispatch_inbound, InboundGroup, InboundMedia, InboundMediaSource, InboundMessage,
+};
 use crate::channels::{ChannelRegistry, ChannelStatus};
 use crate::server::ws::WsServerState;
 
@@ -293,21 +295,92 @@ fn handle_message_create(state: &Arc<WsServerState>, data: &Value, bot_user_id:
         None => return,
     };
 
-    let content = match data.get("content").and_then(|v| v.as_str()) {
-        Some(text) if !text.is_empty() => text,
-        _ => return,
-    };
+    let content = data
+        .get("content")
+        .and_then(|v| v.as_str())
+        .unwrap_or("")
+        .to_string();
+    let media = extract_attachments(data);
+    if content.is_empty() && media.is_empty() {
+        return;
+    }
 
-    if let Err(err) = dispatch_inbound_text(
+    dispatch_inbound(
         state,
-        "discord",
-        sender_id,
-        channel_id,
-        content,
-        Some(channel_id.to_string()),
-    ) {
-        error!("Discord inbound dispatch failed: {}", err);
-    }
+        InboundMessage {
+            channel: "discord".to_string(),
+            sender_id: sender_id.to_string(),
+            peer_id: channel_id.to_string(),
+            text: content,
+            chat_id: Some(channel_id.to_string()),
+            account_id: bot_user_id.map(String::from),
+            media,
+            group: extract_group(data, channel_id, bot_user_id),
+        },
+    );
+}
+
+/// Group context for guild messages; `None` for direct messages. The group
+/// ID is the guild channel ID.
+fn extract_group(
+    data: &Value,
+    channel_id: &str,
+    bot_user_id: Option<&str>,
+) -> Option<InboundGroup> {
+    data.get("guild_id")?;
+    let mentioned = bot_user_id.is_some_and(|bot| {
+        data.get("mentions")
+            .and_then(|v| v.as_array())
+            .is_some_and(|mentions| {
+                mentions
+                    .iter()
+                    .any(|m| m.get("id").and_then(|v| v.as_str()) == Some(bot))
+            })
+    });
+    let replied_to_bot = bot_user_id.is_some_and(|bot| {
+        data.get("referenced_message")
+            .and_then(|m| m.get("author"))
+            .and_then(|a| a.get("id"))
+            .and_then(|v| v.as_str())
+            == Some(bot)
+    });
+    Some(InboundGroup {
+        group_id: channel_id.to_string(),
+        mentioned,
+        replied_to_bot,
+    })
+}
+
+/// Discord message flag marking a voice message.
+const DISCORD_FLAG_IS_VOICE_MESSAGE: u64 = 1 << 13;
+
+/// Collect the attachments on a Discord MESSAGE_CREATE payload.
+fn extract_attachments(data: &Value) -> Vec<InboundMedia> {
+    let voice_message = data
+        .get("flags")
+        .and_then(|v| v.as_u64())
+        .is_some_and(|flags| flags & DISCORD_FLAG_IS_VOICE_MESSAGE != 0);
+    let Some(attachments) = data.get("attachments").and_then(|v| v.as_array()) else {
+        return Vec::new();
+    };
+    attachments
+        .iter()
+        .filter_map(|attachment| {
+            let url = attachment.get("url").and_then(|v| v.as_str())?;
+            Some(InboundMedia {
+                source: InboundMediaSource::Url(url.to_string()),
+                mime_type: attachment
+                    .get("content_type")
+                    .and_then(|v| v.as_str())
+                    .map(String::from),
+                file_name: attachment
+                    .get("filename")
+                    .and_then(|v| v.as_str())
+                    .map(String::from),
+                voice: voice_message,
+            })
+        })
+        .collect()
 }
 
 fn format_bot_token(token: &str) -> String {
@@ -317,3 +390,34 @@ fn format_bot_token(token: &str) -> String {
         format!("Bot {}", token)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_group() {
+        let dm = json!({ "channel_id": "D1", "mentions": [{ "id": "BOT" }] });
+        assert!(extract_group(&dm, "D1", Some("BOT")).is_none());
+
+        let mention = json!({
+            "guild_id": "G1",
+            "channel_id": "C1",
+            "mentions": [{ "id": "U2" }, { "id": "BOT" }]
+        });
+        let group = extract_group(&mention, "C1", Some("BOT")).unwrap();
+        assert_eq!(group.group_id, "C1");
+        assert!(group.mentioned);
+        assert!(!group.replied_to_bot);
+
+        let reply = json!({
+            "guild_id": "G1",
+            "channel_id": "C1",
+            "mentions": [],
+            "referenced_message": { "author": { "id": "BOT" } }
+        });
+        let group = extract_group(&reply, "C1", Some("BOT")).unwrap();
+        assert!(!group.mentioned);
+        assert!(group.replied_to_bot);
+    }
+}
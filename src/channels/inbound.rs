@@ -1,14 +1,241 @@
 //! Shared inbound channel dispatch helpers.
 //!
-//! Routes inbound text messages into the session + agent pipeline.
+//! Routes inbound messages into the session + agent pipeline. Group messages
+//! are first checked against the channel's activation policy (see
+//! [`crate::sessions::scoping::GroupActivation`]); messages that do not
+//! activate the agent are dropped or kept as passive session context. Senders
+//! are then checked against the channel's access policy (see
+//! [`super::access`]).
+//! Media attached to an inbound message is downloaded, written to the media
+//! store and referenced from the user `ChatMessage`; voice notes are
+//! transcribed into the message text. When link understanding is enabled for
+//! the channel, URLs in the text are fetched and their content attached to
+//! the message as [`crate::links::LinkContext`].
+//! Each conversation is run by the agent `agents.routes` binds it to (see
+//! [`crate::agent::routing`]), or by the default agent.
 
-use std::sync::Arc;
+use std::sync::{Arc, LazyLock};
+use std::time::Duration;
 
 use serde_json::Value;
-use tracing::debug;
+use tracing::{debug, warn};
 
+use super::access::{authorize_sender, is_sender_admitted};
+use crate::agent::routing::{self, RouteRequest};
+use crate::links::{
+    link_context, links_metadata, LinkConfig, LinkContext, LinkUnderstanding, MAX_URLS_PER_MESSAGE,
+};
+use crate::media::attachments::{attachments_metadata, store_attachment, AttachmentRef};
+use crate::media::{FetchConfig, MediaFetcher, MediaStore, StoreConfig};
 use crate::server::ws::{AgentRun, AgentRunStatus, WsServerState};
-use crate::sessions::{get_or_create_scoped_session, ChatMessage, SessionMetadata};
+use crate::sessions::scoping::ChannelSessionConfig;
+use crate::sessions::{
+    get_or_create_scoped_session, resolve_scoped_session_key, ChatMessage, Session, SessionMetadata,
+};
+
+/// Default cap on a single inbound attachment (20 MB).
+pub const DEFAULT_INBOUND_MEDIA_MAX_BYTES: u64 = 20 * 1024 * 1024;
+
+/// Timeout for downloads from operator-configured channel endpoints.
+const CHANNEL_API_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Default number of links fetched per inbound message.
+pub const DEFAULT_INBOUND_MAX_LINKS: usize = 3;
+
+/// Default bytes of page text kept per link.
+pub const DEFAULT_INBOUND_LINK_MAX_BYTES: usize = 2000;
+
+/// Default bytes of page text kept across all links in a message.
+pub const DEFAULT_INBOUND_LINKS_TOTAL_BYTES: usize = 6000;
+
+/// Upper bound on page text kept per link; the shared fetcher keeps previews
+/// this long so channel budgets up to it can be honoured.
+const MAX_INBOUND_LINK_BYTES: usize = 16 * 1024;
+
+/// Link fetcher shared by all channels, so its cache is too.
+static LINKS: LazyLock<LinkUnderstanding> = LazyLock::new(|| {
+    LinkUnderstanding::new(LinkConfig {
+        text_preview_len: MAX_INBOUND_LINK_BYTES,
+        ..Default::default()
+    })
+});
+
+/// Where an inbound attachment can be downloaded from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InboundMediaSource {
+    /// A URL taken from the inbound payload, fetched through the
+    /// SSRF-protected [`MediaFetcher`].
+    Url(String),
+    /// A Slack private file URL; fetched like [`Self::Url`] but with the bot
+    /// token as bearer authorization.
+    SlackFile(String),
+    /// A Telegram `file_id`, resolved through the Bot API `getFile` method.
+    Telegram(String),
+    /// An attachment held by the operator's signal-cli REST API.
+    Signal {
+        base_url: String,
+        attachment_id: String,
+    },
+}
+
+/// Media attached to an inbound channel message, not yet downloaded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InboundMedia {
+    pub source: InboundMediaSource,
+    pub mime_type: Option<String>,
+    pub file_name: Option<String>,
+    /// Set for voice notes, which are transcribed rather than attached.
+    pub voice: bool,
+}
+
+impl InboundMedia {
+    fn is_audio(&self) -> bool {
+        self.voice
+            || self
+                .mime_type
+                .as_deref()
+                .is_some_and(|m| m.starts_with("audio/"))
+    }
+}
+
+/// Group chat context for an inbound message, as detected by the channel's
+/// parser.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct InboundGroup {
+    pub group_id: String,
+    /// The message mentions the bot.
+    pub mentioned: bool,
+    /// The message replies to one of the bot's messages.
+    pub replied_to_bot: bool,
+}
+
+/// An inbound channel message with any attached media.
+#[derive(Debug, Clone)]
+pub struct InboundMessage {
+    pub channel: String,
+    pub sender_id: String,
+    pub peer_id: String,
+    pub text: String,
+    pub chat_id: Option<String>,
+    /// The bot account that received the message (bot ID, workspace or
+    /// phone number), matched by `agents.routes` `accountId`.
+    pub account_id: Option<String>,
+    pub media: Vec<InboundMedia>,
+    /// Set for group messages; `None` for direct messages.
+    pub group: Option<InboundGroup>,
+}
+
+/// Where an inbound message came from: the inputs to session scoping and
+/// agent routing.
+#[derive(Debug, Clone, Copy, Default)]
+struct InboundOrigin<'a> {
+    channel: &'a str,
+    sender_id: &'a str,
+    peer_id: &'a str,
+    chat_id: Option<&'a str>,
+    account_id: Option<&'a str>,
+    group_id: Option<&'a str>,
+}
+
+impl<'a> InboundOrigin<'a> {
+    fn of(message: &'a InboundMessage) -> Self {
+        Self {
+            channel: &message.channel,
+            sender_id: &message.sender_id,
+            peer_id: &message.peer_id,
+            chat_id: message.chat_id.as_deref(),
+            account_id: message.account_id.as_deref(),
+            group_id: message.group.as_ref().map(|g| g.group_id.as_str()),
+        }
+    }
+
+    fn route_request(&self, text: &'a str) -> RouteRequest<'a> {
+        RouteRequest {
+            channel: self.channel,
+            account_id: self.account_id,
+            group_id: self.group_id,
+            sender_id: self.sender_id,
+            text,
+        }
+    }
+}
+
+/// Inbound media handling settings from `media.inbound`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct InboundMediaSettings {
+    enabled: bool,
+    max_bytes: u64,
+    transcribe: bool,
+}
+
+impl InboundMediaSettings {
+    fn from_config(cfg: &Value) -> Self {
+        let section = cfg.get("media").and_then(|m| m.get("inbound"));
+        let flag = |key: &str| {
+            section
+                .and_then(|s| s.get(key))
+                .and_then(|v| v.as_bool())
+                .unwrap_or(true)
+        };
+        Self {
+            enabled: flag("enabled"),
+            max_bytes: section
+                .and_then(|s| s.get("maxBytes"))
+                .and_then(|v| v.as_u64())
+                .filter(|v| *v > 0)
+                .unwrap_or(DEFAULT_INBOUND_MEDIA_MAX_BYTES),
+            transcribe: flag("transcribeVoice"),
+        }
+    }
+}
+
+/// Link understanding settings from `channels.<name>.links`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct LinkSettings {
+    enabled: bool,
+    max_links: usize,
+    max_bytes_per_link: usize,
+    max_total_bytes: usize,
+}
+
+impl LinkSettings {
+    fn from_config(cfg: &Value, channel: &str) -> Self {
+        let section = cfg
+            .get("channels")
+            .and_then(|c| c.get(channel))
+            .and_then(|c| c.get("links"));
+        let limit = |key: &str, default: usize, max: usize| {
+            section
+                .and_then(|s| s.get(key))
+                .and_then(|v| v.as_u64())
+                .filter(|v| *v > 0)
+                .map(|v| (v as usize).min(max))
+                .unwrap_or(default)
+        };
+        Self {
+            enabled: section
+                .and_then(|s| s.get("enabled"))
+                .and_then(|v| v.as_bool())
+                .unwrap_or(false),
+            max_links: limit("maxLinks", DEFAULT_INBOUND_MAX_LINKS, MAX_URLS_PER_MESSAGE),
+            max_bytes_per_link: limit(
+                "maxBytesPerLink",
+                DEFAULT_INBOUND_LINK_MAX_BYTES,
+                MAX_INBOUND_LINK_BYTES,
+            ),
+            max_total_bytes: limit(
+                "maxTotalBytes",
+                DEFAULT_INBOUND_LINKS_TOTAL_BYTES,
+                usize::MAX,
+            ),
+        }
+    }
+
+    /// Whether `text` has links this channel should fetch.
+    fn applies_to(&self, text: &str) -> bool {
+        self.enabled && !LinkUnderstanding::extract_urls(text).is_empty()
+    }
+}
 
 /// Dispatch an inbound text message into the agent pipeline.
 ///
@@ -20,38 +247,295 @@ pub fn dispatch_inbound_text(
     peer_id: &str,
     text: &str,
     chat_id: Option<String>,
+) -> Result<String, String> {
+    // Without group info, a chat other than the sender's own is a group
+    let is_group = chat_id.as_deref().is_some_and(|chat| chat != sender_id);
+    if !authorize_sender(state, channel, sender_id, chat_id.as_deref(), is_group) {
+        return Err(format!(
+            "sender {} is not allowed on {}",
+            sender_id, channel
+        ));
+    }
+    let origin = InboundOrigin {
+        channel,
+        sender_id,
+        peer_id,
+        chat_id: chat_id.as_deref(),
+        ..Default::default()
+    };
+    dispatch_text(state, &origin, text)
+}
+
+fn dispatch_text(
+    state: &Arc<WsServerState>,
+    origin: &InboundOrigin<'_>,
+    text: &str,
+) -> Result<String, String> {
+    let message = ChatMessageInput {
+        text,
+        metadata: None,
+    };
+    dispatch_user_message(state, origin, message)
+}
+
+/// Dispatch an inbound message, resolving its media first.
+///
+/// Text-only messages are dispatched inline. Messages with media, or with
+/// links to fetch, are handed to a background task so webhook handlers can
+/// acknowledge immediately; failures there are logged. Group messages that do not meet the channel's
+/// activation policy, and messages from senders the channel's access policy
+/// does not admit, are dropped before any media is downloaded.
+pub fn dispatch_inbound(state: &Arc<WsServerState>, message: InboundMessage) {
+    if message.text.is_empty() && message.media.is_empty() {
+        return;
+    }
+    if !check_activation(state, &message) {
+        return;
+    }
+    if !authorize_sender(
+        state,
+        &message.channel,
+        &message.sender_id,
+        message.chat_id.as_deref(),
+        message.group.is_some(),
+    ) {
+        return;
+    }
+
+    let cfg = crate::config::load_config_shared()
+        .unwrap_or_else(|_| Arc::new(Value::Object(serde_json::Map::new())));
+    let fetch_links =
+        LinkSettings::from_config(cfg.as_ref(), &message.channel).applies_to(&message.text);
+    if message.media.is_empty() && !fetch_links {
+        if let Err(err) = dispatch_text(state, &InboundOrigin::of(&message), &message.text) {
+            warn!(channel = %message.channel, "Inbound dispatch failed: {}", err);
+        }
+        return;
+    }
+
+    let state = state.clone();
+    tokio::spawn(async move {
+        let channel = message.channel.clone();
+        if let Err(err) = dispatch_message(&state, message).await {
+            warn!(channel = %channel, "Inbound media dispatch failed: {}", err);
+        }
+    });
+}
+
+/// Download and store the message's media, then dispatch it into the agent
+/// pipeline.
+///
+/// Images and documents are attached by media-store reference; audio is
+/// transcribed into the message text. A failed download becomes a short note
+/// in the text rather than dropping the message. Links in the text are
+/// fetched and attached when the channel enables link understanding.
+pub async fn dispatch_inbound_message(
+    state: &Arc<WsServerState>,
+    message: InboundMessage,
+) -> Result<String, String> {
+    if !authorize_sender(
+        state,
+        &message.channel,
+        &message.sender_id,
+        message.chat_id.as_deref(),
+        message.group.is_some(),
+    ) {
+        return Err(format!(
+            "sender {} is not allowed on {}",
+            message.sender_id, message.channel
+        ));
+    }
+    dispatch_message(state, message).await
+}
+
+async fn dispatch_message(
+    state: &Arc<WsServerState>,
+    message: InboundMessage,
 ) -> Result<String, String> {
     let cfg = crate::config::load_config_shared()
         .unwrap_or_else(|_| Arc::new(Value::Object(serde_json::Map::new())));
-    let effective_peer_id = if peer_id.is_empty() {
-        sender_id
+    let settings = InboundMediaSettings::from_config(cfg.as_ref());
+
+    let mut text_parts: Vec<String> = Vec::new();
+    if !message.text.is_empty() {
+        text_parts.push(message.text.clone());
+    }
+    let mut attachments: Vec<AttachmentRef> = Vec::new();
+
+    if settings.enabled && !message.media.is_empty() {
+        let store = MediaStore::new(StoreConfig::default())
+            .await
+            .map_err(|e| format!("media store unavailable: {}", e))?;
+        for media in &message.media {
+            match resolve_media(cfg.as_ref(), &settings, &store, media).await {
+                Ok(ResolvedMedia::Attachment(attachment)) => attachments.push(attachment),
+                Ok(ResolvedMedia::Transcript(transcript)) => text_parts.push(transcript),
+                Err(err) => {
+                    warn!(
+                        channel = %message.channel,
+                        error = %err,
+                        "Failed to fetch inbound media"
+                    );
+                    text_parts.push(format!("[{} could not be downloaded]", media_label(media)));
+                }
+            }
+        }
+    }
+
+    if text_parts.is_empty() && attachments.is_empty() {
+        return Err("inbound message has no usable content".to_string());
+    }
+
+    let link_settings = LinkSettings::from_config(cfg.as_ref(), &message.channel);
+    let links = if link_settings.applies_to(&message.text) {
+        fetch_links(&link_settings, &message.text).await
     } else {
-        peer_id
+        Vec::new()
+    };
+
+    let mut metadata = serde_json::Map::new();
+    if !attachments.is_empty() {
+        if let Value::Object(map) = attachments_metadata(&attachments) {
+            metadata.extend(map);
+        }
+    }
+    if !links.is_empty() {
+        if let Value::Object(map) = links_metadata(&links) {
+            metadata.extend(map);
+        }
+    }
+
+    let text = text_parts.join("\n\n");
+    let input = ChatMessageInput {
+        text: &text,
+        metadata: (!metadata.is_empty()).then_some(Value::Object(metadata)),
     };
+    dispatch_user_message(state, &InboundOrigin::of(&message), input)
+}
+
+/// Apply the channel's group activation policy.
+///
+/// Returns `true` when the message should reach the agent: direct messages
+/// always do. A group message that triggers nothing is kept as passive
+/// context when the policy asks for it and the sender is admitted, and is
+/// otherwise dropped.
+fn check_activation(state: &Arc<WsServerState>, message: &InboundMessage) -> bool {
+    let Some(group) = &message.group else {
+        return true;
+    };
+    let cfg = crate::config::load_config_shared()
+        .unwrap_or_else(|_| Arc::new(Value::Object(serde_json::Map::new())));
+    let session_config = ChannelSessionConfig::from_config(cfg.as_ref(), &message.channel);
+    let activation = session_config.activation_for(&group.group_id);
+    if activation.is_triggered(&message.text, group.mentioned, group.replied_to_bot) {
+        return true;
+    }
+
+    if activation.passive_context
+        && !message.text.is_empty()
+        && is_sender_admitted(state, &message.channel, &message.sender_id)
+    {
+        if let Err(err) = store_passive_message(state, cfg.as_ref(), message) {
+            warn!(channel = %message.channel, "Failed to store passive message: {}", err);
+        }
+    }
+    debug!(
+        channel = %message.channel,
+        group = %group.group_id,
+        "Group message did not activate the agent"
+    );
+    false
+}
+
+/// Append a non-triggering group message to the session without starting a
+/// run. It is marked `passive` so the context builder can present it as
+/// background rather than a request.
+fn store_passive_message(
+    state: &Arc<WsServerState>,
+    cfg: &Value,
+    message: &InboundMessage,
+) -> Result<(), String> {
+    let session = scoped_session(state, cfg, &InboundOrigin::of(message), &message.text)?;
+    let chat_message = ChatMessage::user(session.id, &message.text)
+        .with_metadata(serde_json::json!({ "passive": true, "senderId": message.sender_id }));
+    state
+        .session_store()
+        .append_message(chat_message)
+        .map_err(|e| format!("failed to append message: {}", e))?;
+    Ok(())
+}
+
+/// Find or create the session an inbound message belongs to under the
+/// channel's scoping rules.
+///
+/// When `agents.routes` binds the message to an agent, the session is keyed
+/// by that agent and records its ID, so later runs stay with it.
+fn scoped_session(
+    state: &Arc<WsServerState>,
+    cfg: &Value,
+    origin: &InboundOrigin<'_>,
+    text: &str,
+) -> Result<Session, String> {
+    let effective_peer_id = if origin.peer_id.is_empty() {
+        origin.sender_id
+    } else {
+        origin.peer_id
+    };
+
+    let agent_id = routing::route_agent(cfg, &origin.route_request(text));
+    let agent_key = agent_id.as_deref().map(|agent_id| {
+        let (session_key, _, _) = resolve_scoped_session_key(
+            cfg,
+            origin.channel,
+            origin.sender_id,
+            effective_peer_id,
+            None,
+        );
+        routing::agent_session_key(agent_id, &session_key)
+    });
 
     let metadata = SessionMetadata {
-        channel: Some(channel.to_string()),
-        user_id: Some(sender_id.to_string()),
-        chat_id,
+        channel: Some(origin.channel.to_string()),
+        user_id: Some(origin.sender_id.to_string()),
+        chat_id: origin.chat_id.map(String::from),
+        agent_id,
         ..Default::default()
     };
 
-    let session_store = state.session_store();
-    let session = get_or_create_scoped_session(
-        session_store,
-        cfg.as_ref(),
-        channel,
-        sender_id,
+    get_or_create_scoped_session(
+        state.session_store(),
+        cfg,
+        origin.channel,
+        origin.sender_id,
         effective_peer_id,
-        None,
+        agent_key.as_deref(),
         metadata,
     )
-    .map_err(|e| format!("failed to get/create session: {}", e))?;
+    .map_err(|e| format!("failed to get/create session: {}", e))
+}
 
-    if let Err(e) = state
-        .session_store()
-        .append_message(ChatMessage::user(session.id.clone(), text))
-    {
+/// User message text plus optional `ChatMessage` metadata.
+struct ChatMessageInput<'a> {
+    text: &'a str,
+    metadata: Option<Value>,
+}
+
+/// Append the user message to the scoped session and start an agent run.
+fn dispatch_user_message(
+    state: &Arc<WsServerState>,
+    origin: &InboundOrigin<'_>,
+    message: ChatMessageInput<'_>,
+) -> Result<String, String> {
+    let cfg = crate::config::load_config_shared()
+        .unwrap_or_else(|_| Arc::new(Value::Object(serde_json::Map::new())));
+    let session = scoped_session(state, cfg.as_ref(), origin, message.text)?;
+
+    let text = message.text;
+    let mut chat_message = ChatMessage::user(session.id.clone(), text);
+    if let Some(metadata) = message.metadata {
+        chat_message = chat_message.with_metadata(metadata);
+    }
+    if let Err(e) = state.session_store().append_message(chat_message) {
         return Err(format!("failed to append message: {}", e));
     }
 
@@ -83,7 +567,11 @@ pub fn dispatch_inbound_text(
 
     if let Some(provider) = state.llm_provider() {
         let mut config = crate::agent::AgentConfig::default();
-        crate::agent::apply_agent_config_from_settings(&mut config, cfg.as_ref(), None);
+        crate::agent::apply_agent_config_from_settings(
+            &mut config,
+            cfg.as_ref(),
+            session.metadata.agent_id.as_deref(),
+        );
         config.deliver = true;
         crate::agent::spawn_run(
             run_id.clone(),
@@ -95,17 +583,327 @@ pub fn dispatch_inbound_text(
         );
         debug!(
             run_id = %run_id,
-            channel = %channel,
-            sender = %sender_id,
+            channel = %origin.channel,
+            sender = %origin.sender_id,
+            agent = ?session.metadata.agent_id,
             "Inbound agent run dispatched"
         );
     } else {
         debug!(
             run_id = %run_id,
-            channel = %channel,
+            channel = %origin.channel,
             "Inbound message queued (no LLM provider)"
         );
     }
 
     Ok(run_id)
 }
+
+/// Fetch the links in `text` and cut their content to the channel's budgets.
+///
+/// Links that fail to fetch or are not text are skipped.
+async fn fetch_links(settings: &LinkSettings, text: &str) -> Vec<LinkContext> {
+    let mut summaries = Vec::new();
+    for url in LinkUnderstanding::extract_urls(text)
+        .iter()
+        .take(settings.max_links)
+    {
+        match LINKS.fetch_and_summarize(url).await {
+            Ok(summary) => summaries.push(summary),
+            Err(err) => debug!(url = %url, error = %err, "Skipping inbound link"),
+        }
+    }
+    link_context(
+        &summaries,
+        settings.max_bytes_per_link,
+        settings.max_total_bytes,
+    )
+}
+
+/// Outcome of resolving one inbound media item.
+enum ResolvedMedia {
+    Attachment(AttachmentRef),
+    Transcript(String),
+}
+
+/// Download one media item, store it, and either attach or transcribe it.
+async fn resolve_media(
+    cfg: &Value,
+    settings: &InboundMediaSettings,
+    store: &MediaStore,
+    media: &InboundMedia,
+) -> Result<ResolvedMedia, String> {
+    let (bytes, content_type) = fetch_media(cfg, settings.max_bytes, &media.source).await?;
+    let mime_type = media
+        .mime_type
+        .clone()
+        .or(content_type)
+        .map(|m| m.split(';').next().unwrap_or("").trim().to_lowercase())
+        .filter(|m| m.contains('/'))
+        .unwrap_or_else(|| "application/octet-stream".to_string());
+    let attachment = store_attachment(store, bytes, &mime_type, media.file_name.clone())
+        .await
+        .map_err(|e| e.to_string())?;
+
+    if !(media.is_audio() || mime_type.starts_with("audio/")) {
+        return Ok(ResolvedMedia::Attachment(attachment));
+    }
+
+    if !settings.transcribe {
+        return Ok(ResolvedMedia::Transcript(format!(
+            "[{} received; transcription is disabled]",
+            media_label(media)
+        )));
+    }
+    Ok(ResolvedMedia::Transcript(
+        match transcribe(cfg, &attachment).await {
+            Ok(transcript) => format!("[{} transcript] {}", media_label(media), transcript),
+            Err(err) => {
+                warn!(error = %err, "Inbound voice transcription failed");
+                format!(
+                    "[{} received; transcription unavailable]",
+                    media_label(media)
+                )
+            }
+        },
+    ))
+}
+
+/// Transcribe stored audio through the Whisper path in `media::analysis`.
+async fn transcribe(cfg: &Value, attachment: &AttachmentRef) -> Result<String, String> {
+    use crate::agent::builtin_tools::{resolve_openai_base_url, resolve_openai_media_key};
+    use crate::media::analysis::{analyze, OpenAiMediaAnalyzer};
+
+    let key = resolve_openai_media_key(cfg)
+        .ok_or_else(|| "OpenAI API key is required for transcription".to_string())?;
+    let mut analyzer = OpenAiMediaAnalyzer::new(key).map_err(|e| e.to_string())?;
+    if let Some(base_url) = resolve_openai_base_url(cfg) {
+        analyzer = analyzer.with_base_url(base_url);
+    }
+    let analysis = analyze(&attachment.path, &attachment.media_type, &analyzer, None)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(analysis.description)
+}
+
+/// Download media bytes, returning them with the response Content-Type.
+async fn fetch_media(
+    cfg: &Value,
+    max_bytes: u64,
+    source: &InboundMediaSource,
+) -> Result<(Vec<u8>, Option<String>), String> {
+    match source {
+        InboundMediaSource::Url(url) => fetch_public(url, FetchConfig::default(), max_bytes).await,
+        InboundMediaSource::SlackFile(url) => {
+            let token = channel_setting(cfg, "slack", "botToken", "SLACK_BOT_TOKEN")
+                .ok_or_else(|| "Slack bot token not configured".to_string())?;
+            let config =
+                FetchConfig::default().with_header("Authorization", format!("Bearer {}", token));
+            fetch_public(url, config, max_bytes).await
+        }
+        InboundMediaSource::Telegram(file_id) => {
+            let token = channel_setting(cfg, "telegram", "botToken", "TELEGRAM_BOT_TOKEN")
+                .ok_or_else(|| "Telegram bot token not configured".to_string())?;
+            let base_url = channel_setting(cfg, "telegram", "baseUrl", "TELEGRAM_BASE_URL")
+                .unwrap_or_else(|| "https://api.telegram.org".to_string());
+            let base_url = base_url.trim_end_matches('/');
+            let client = channel_api_client()?;
+            let file: Value = client
+                .get(format!(
+                    "{}/bot{}/getFile?file_id={}",
+                    base_url,
+                    token,
+                    urlencoding::encode(file_id)
+                ))
+                .send()
+                .await
+                .map_err(|e| format!("getFile failed: {}", e))?
+                .json()
+                .await
+                .map_err(|e| format!("getFile returned invalid JSON: {}", e))?;
+            let file_path = file
+                .get("result")
+                .and_then(|r| r.get("file_path"))
+                .and_then(|v| v.as_str())
+                .ok_or_else(|| "getFile returned no file_path".to_string())?;
+            fetch_trusted(
+                &client,
+                &format!("{}/file/bot{}/{}", base_url, token, file_path),
+                max_bytes,
+            )
+            .await
+        }
+        InboundMediaSource::Signal {
+            base_url,
+            attachment_id,
+        } => {
+            let url = format!(
+                "{}/v1/attachments/{}",
+                base_url.trim_end_matches('/'),
+                urlencoding::encode(attachment_id)
+            );
+            fetch_trusted(&channel_api_client()?, &url, max_bytes).await
+        }
+    }
+}
+
+/// Fetch a payload-supplied URL through the SSRF-protected media fetcher.
+async fn fetch_public(
+    url: &str,
+    config: FetchConfig,
+    max_bytes: u64,
+) -> Result<(Vec<u8>, Option<String>), String> {
+    let fetcher = MediaFetcher::with_config(config.with_max_size(max_bytes));
+    let result = fetcher.fetch(url).await.map_err(|e| e.to_string())?;
+    Ok((result.bytes, result.content_type))
+}
+
+/// Fetch from an operator-configured channel endpoint (Telegram Bot API,
+/// signal-cli REST API). These may legitimately live on a private network,
+/// so SSRF filtering does not apply; only the URL path comes from the
+/// payload.
+async fn fetch_trusted(
+    client: &reqwest::Client,
+    url: &str,
+    max_bytes: u64,
+) -> Result<(Vec<u8>, Option<String>), String> {
+    let response = client
+        .get(url)
+        .send()
+        .await
+        .map_err(|e| format!("download failed: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("download failed: HTTP {}", response.status()));
+    }
+    if response.content_length().is_some_and(|len| len > max_bytes) {
+        return Err(format!("media too large (max {} bytes)", max_bytes));
+    }
+    let content_type = response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .map(String::from);
+    let bytes = response
+        .bytes()
+        .await
+        .map_err(|e| format!("download failed: {}", e))?;
+    if bytes.len() as u64 > max_bytes {
+        return Err(format!("media too large (max {} bytes)", max_bytes));
+    }
+    Ok((bytes.to_vec(), content_type))
+}
+
+fn channel_api_client() -> Result<reqwest::Client, String> {
+    reqwest::Client::builder()
+        .timeout(CHANNEL_API_TIMEOUT)
+        .build()
+        .map_err(|e| format!("failed to build HTTP client: {}", e))
+}
+
+/// Read `<channel>.<key>` from config, falling back to an environment variable.
+fn channel_setting(cfg: &Value, channel: &str, key: &str, env_var: &str) -> Option<String> {
+    cfg.get(channel)
+        .and_then(|c| c.get(key))
+        .and_then(|v| v.as_str())
+        .map(String::from)
+        .or_else(|| std::env::var(env_var).ok())
+        .filter(|v| !v.is_empty())
+}
+
+/// Short description of a media item for notes in the message text.
+fn media_label(media: &InboundMedia) -> String {
+    let kind = if media.voice {
+        "voice note"
+    } else {
+        "attachment"
+    };
+    match &media.file_name {
+        Some(name) => format!("{} \"{}\"", kind, name),
+        None => kind.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_inbound_media_settings_defaults_and_overrides() {
+        let defaults = InboundMediaSettings::from_config(&json!({}));
+        assert!(defaults.enabled);
+        assert!(defaults.transcribe);
+        assert_eq!(defaults.max_bytes, DEFAULT_INBOUND_MEDIA_MAX_BYTES);
+
+        let cfg = json!({
+            "media": { "inbound": { "enabled": false, "maxBytes": 1024, "transcribeVoice": false } }
+        });
+        let settings = InboundMediaSettings::from_config(&cfg);
+        assert!(!settings.enabled);
+        assert!(!settings.transcribe);
+        assert_eq!(settings.max_bytes, 1024);
+    }
+
+    #[test]
+    fn test_link_settings_from_config() {
+        let defaults = LinkSettings::from_config(&json!({}), "telegram");
+        assert!(!defaults.enabled);
+        assert!(!defaults.applies_to("see https://example.com"));
+        assert_eq!(defaults.max_links, DEFAULT_INBOUND_MAX_LINKS);
+        assert_eq!(defaults.max_bytes_per_link, DEFAULT_INBOUND_LINK_MAX_BYTES);
+        assert_eq!(defaults.max_total_bytes, DEFAULT_INBOUND_LINKS_TOTAL_BYTES);
+
+        let cfg = json!({
+            "channels": {
+                "telegram": {
+                    "links": {
+                        "enabled": true,
+                        "maxLinks": 50,
+                        "maxBytesPerLink": 1_000_000,
+                        "maxTotalBytes": 4000
+                    }
+                }
+            }
+        });
+        let settings = LinkSettings::from_config(&cfg, "telegram");
+        assert!(settings.applies_to("see https://example.com"));
+        assert!(!settings.applies_to("no links here"));
+        assert_eq!(settings.max_links, MAX_URLS_PER_MESSAGE);
+        assert_eq!(settings.max_bytes_per_link, MAX_INBOUND_LINK_BYTES);
+        assert_eq!(settings.max_total_bytes, 4000);
+        // Other channels keep the defaults
+        assert!(!LinkSettings::from_config(&cfg, "slack").enabled);
+    }
+
+    #[test]
+    fn test_media_label_and_audio_detection() {
+        let voice = InboundMedia {
+            source: InboundMediaSource::Telegram("f1".into()),
+            mime_type: None,
+            file_name: None,
+            voice: true,
+        };
+        assert!(voice.is_audio());
+        assert_eq!(media_label(&voice), "voice note");
+
+        let document = InboundMedia {
+            source: InboundMediaSource::Url("https://cdn.example.com/a.pdf".into()),
+            mime_type: Some("application/pdf".into()),
+            file_name: Some("a.pdf".into()),
+            voice: false,
+        };
+        assert!(!document.is_audio());
+        assert_eq!(media_label(&document), "attachment \"a.pdf\"");
+    }
+
+    #[tokio::test]
+    async fn test_fetch_public_rejects_private_addresses() {
+        let result = fetch_media(
+            &json!({}),
+            DEFAULT_INBOUND_MEDIA_MAX_BYTES,
+            &InboundMediaSource::Url("http://127.0.0.1/secret.png".into()),
+        )
+        .await;
+        assert!(result.is_err());
+    }
+}
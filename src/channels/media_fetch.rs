@@ -138,5 +138,6 @@ fn error_result(error: impl Into<String>, retryable: bool) -> DeliveryResult {
         conversation_id: None,
         to_jid: None,
         poll_id: None,
+        retry_after_ms: None,
     }
 }
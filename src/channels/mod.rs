@@ -3,6 +3,7 @@
 //! Provides channel registry for tracking active messaging channels
 //! and their connection states.
 
+pub mod access;
 pub mod console;
 pub mod discord;
 pub mod discord_gateway;
@@ -289,6 +290,21 @@ pub fn create_registry() -> Arc<ChannelRegistry> {
     Arc::new(ChannelRegistry::new())
 }
 
+/// Parse a `Retry-After` header given in (possibly fractional) seconds into
+/// milliseconds. HTTP-date values are not supported and yield `None`.
+pub(crate) fn retry_after_header_ms(headers: &reqwest::header::HeaderMap) -> Option<u64> {
+    headers
+        .get(reqwest::header::RETRY_AFTER)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.trim().parse::<f64>().ok())
+        .and_then(seconds_to_ms)
+}
+
+/// Convert a seconds value from a platform rate-limit hint to milliseconds.
+pub(crate) fn seconds_to_ms(seconds: f64) -> Option<u64> {
+    (seconds.is_finite() && seconds >= 0.0).then(|| (seconds * 1000.0).ceil() as u64)
+}
+
 /// Get current time in milliseconds since Unix epoch
 fn now_millis() -> i64 {
     use std::time::{SystemTime, UNIX_EPOCH};
@@ -302,6 +318,22 @@ fn now_millis() -> i64 {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_retry_after_header_ms() {
+        let mut headers = reqwest::header::HeaderMap::new();
+        assert_eq!(retry_after_header_ms(&headers), None);
+        headers.insert(reqwest::header::RETRY_AFTER, "2".parse().unwrap());
+        assert_eq!(retry_after_header_ms(&headers), Some(2000));
+        headers.insert(reqwest::header::RETRY_AFTER, "0.25".parse().unwrap());
+        assert_eq!(retry_after_header_ms(&headers), Some(250));
+        headers.insert(
+            reqwest::header::RETRY_AFTER,
+            "Wed, 21 Oct 2015 07:28:00 GMT".parse().unwrap(),
+        );
+        assert_eq!(retry_after_header_ms(&headers), None);
+        assert_eq!(seconds_to_ms(-1.0), None);
+    }
+
     #[test]
     fn test_channel_status_default() {
         assert_eq!(ChannelStatus::default(), ChannelStatus::Disconnected);
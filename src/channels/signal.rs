@@ -9,7 +9,7 @@ use uuid::Uuid;
 
 use crate::plugins::{
     BindingError, ChannelCapabilities, ChannelInfo, ChannelPluginInstance, ChatType,
-    DeliveryResult, OutboundContext,
+    DeliveryResult, MessageTarget, OutboundContext,
 };
 
 /// Maximum media size to fetch and base64-encode (50 MB).
@@ -36,6 +36,16 @@ impl SignalChannel {
     fn send_url(&self) -> String {
         format!("{}/v2/send", self.base_url)
     }
+
+    /// Build the reactions endpoint URL.
+    fn reactions_url(&self) -> String {
+        format!("{}/v1/reactions/{}", self.base_url, self.phone_number)
+    }
+
+    /// Build the remote-delete endpoint URL.
+    fn remote_delete_url(&self) -> String {
+        format!("{}/v1/remote-delete/{}", self.base_url, self.phone_number)
+    }
 }
 
 impl ChannelPluginInstance for SignalChannel {
@@ -55,6 +65,8 @@ impl ChannelPluginInstance for SignalChannel {
             chat_types: vec![ChatType::Dm],
             media: true,
             reactions: true,
+            edit: true,
+            unsend: true,
             ..Default::default()
         })
     }
@@ -94,6 +106,7 @@ impl ChannelPluginInstance for SignalChannel {
                             conversation_id: None,
                             to_jid: None,
                             poll_id: None,
+                            retry_after_ms: None,
                         });
                     }
                 }
@@ -108,6 +121,7 @@ impl ChannelPluginInstance for SignalChannel {
                             conversation_id: None,
                             to_jid: None,
                             poll_id: None,
+                            retry_after_ms: None,
                         });
                     }
                 }
@@ -121,6 +135,7 @@ impl ChannelPluginInstance for SignalChannel {
                     conversation_id: None,
                     to_jid: None,
                     poll_id: None,
+                    retry_after_ms: None,
                 });
             }
             Err(e) => {
@@ -132,6 +147,7 @@ impl ChannelPluginInstance for SignalChannel {
                     conversation_id: None,
                     to_jid: None,
                     poll_id: None,
+                    retry_after_ms: None,
                 });
             }
         };
@@ -147,22 +163,95 @@ impl ChannelPluginInstance for SignalChannel {
 
         self.post_send(&body)
     }
+
+    fn edit_message(
+        &self,
+        target: MessageTarget,
+        new_text: String,
+    ) -> Result<DeliveryResult, BindingError> {
+        let timestamp = match parse_timestamp(&target.message_id) {
+            Ok(ts) => ts,
+            Err(e) => return Ok(e),
+        };
+        let body = serde_json::json!({
+            "number": self.phone_number,
+            "recipients": [target.to],
+            "message": new_text,
+            "edit_timestamp": timestamp,
+        });
+
+        self.post_send(&body)
+    }
+
+    fn delete_message(&self, target: MessageTarget) -> Result<DeliveryResult, BindingError> {
+        let timestamp = match parse_timestamp(&target.message_id) {
+            Ok(ts) => ts,
+            Err(e) => return Ok(e),
+        };
+        let body = serde_json::json!({
+            "recipient": target.to,
+            "timestamp": timestamp,
+        });
+
+        Ok(self.send_json(
+            self.client.delete(self.remote_delete_url()),
+            &body,
+            target.message_id,
+        ))
+    }
+
+    /// React to a message. `message_id` is the message timestamp, optionally
+    /// prefixed with `<author>:` when reacting to someone else's message;
+    /// without a prefix the target is one of our own messages.
+    fn react(&self, target: MessageTarget, emoji: String) -> Result<DeliveryResult, BindingError> {
+        let (author, raw_timestamp) = match target.message_id.rsplit_once(':') {
+            Some((author, ts)) if !author.is_empty() => (author.to_string(), ts),
+            _ => (self.phone_number.clone(), target.message_id.as_str()),
+        };
+        let timestamp = match parse_timestamp(raw_timestamp) {
+            Ok(ts) => ts,
+            Err(e) => return Ok(e),
+        };
+        if emoji.trim().is_empty() {
+            return Ok(error_result("emoji must not be empty", false));
+        }
+        let body = serde_json::json!({
+            "reaction": emoji.trim(),
+            "recipient": target.to,
+            "target_author": author,
+            "timestamp": timestamp,
+        });
+
+        Ok(self.send_json(
+            self.client.post(self.reactions_url()),
+            &body,
+            target.message_id.clone(),
+        ))
+    }
 }
 
 impl SignalChannel {
+    /// Send via `/v2/send`, reporting the message timestamp as its ID so the
+    /// message can later be edited, deleted or reacted to.
     fn post_send(&self, body: &serde_json::Value) -> Result<DeliveryResult, BindingError> {
         match self.client.post(self.send_url()).json(body).send() {
             Ok(resp) => {
                 let status = resp.status();
                 if status.is_success() {
+                    let body_text = resp.text().unwrap_or_default();
+                    let message_id = serde_json::from_str::<serde_json::Value>(&body_text)
+                        .ok()
+                        .and_then(|v| v.get("timestamp").and_then(timestamp_to_string))
+                        .unwrap_or_else(|| Uuid::new_v4().to_string());
                     Ok(DeliveryResult {
                         ok: true,
-                        message_id: Some(Uuid::new_v4().to_string()),
+                        message_id: Some(message_id),
                         error: None,
                         retryable: false,
                         conversation_id: None,
                         to_jid: None,
                         poll_id: None,
+                        retry_after_ms: None,
                     })
                 } else {
                     let retryable = status.is_server_error();
@@ -175,6 +264,7 @@ impl SignalChannel {
                         conversation_id: None,
                         to_jid: None,
                         poll_id: None,
+                        retry_after_ms: None,
                     })
                 }
             }
@@ -186,9 +276,68 @@ impl SignalChannel {
                 conversation_id: None,
                 to_jid: None,
                 poll_id: None,
+                retry_after_ms: None,
             }),
         }
     }
+
+    /// Send a JSON request for an operation on an existing message.
+    fn send_json(
+        &self,
+        request: reqwest::blocking::RequestBuilder,
+        body: &serde_json::Value,
+        message_id: String,
+    ) -> DeliveryResult {
+        match request.json(body).send() {
+            Ok(resp) if resp.status().is_success() => DeliveryResult {
+                ok: true,
+                message_id: Some(message_id),
+                error: None,
+                retryable: false,
+                conversation_id: None,
+                to_jid: None,
+                poll_id: None,
+                retry_after_ms: None,
+            },
+            Ok(resp) => {
+                let status = resp.status();
+                let body_text = resp.text().unwrap_or_default();
+                error_result(
+                    format!("HTTP {}: {}", status, body_text),
+                    status.is_server_error(),
+                )
+            }
+            Err(e) => error_result(e.to_string(), true),
+        }
+    }
+}
+
+fn timestamp_to_string(value: &serde_json::Value) -> Option<String> {
+    value
+        .as_str()
+        .map(|s| s.to_string())
+        .or_else(|| value.as_u64().map(|n| n.to_string()))
+}
+
+/// Signal identifies messages by their millisecond send timestamp.
+#[allow(clippy::result_large_err)]
+fn parse_timestamp(raw: &str) -> Result<u64, DeliveryResult> {
+    raw.trim()
+        .parse::<u64>()
+        .map_err(|_| error_result("invalid message_id: expected message timestamp", false))
+}
+
+fn error_result(error: impl Into<String>, retryable: bool) -> DeliveryResult {
+    DeliveryResult {
+        ok: false,
+        message_id: None,
+        error: Some(error.into()),
+        retryable,
+        conversation_id: None,
+        to_jid: None,
+        poll_id: None,
+        retry_after_ms: None,
+    }
 }
 
 #[cfg(test)]
@@ -220,7 +369,8 @@ mod tests {
         assert!(caps.reactions);
         assert_eq!(caps.chat_types, vec![ChatType::Dm]);
         assert!(!caps.polls);
-        assert!(!caps.edit);
+        assert!(caps.edit);
+        assert!(caps.unsend);
         assert!(!caps.threads);
     }
 
@@ -302,4 +452,64 @@ mod tests {
         assert_eq!(ch.base_url, "http://localhost:8080");
         assert_eq!(ch.phone_number, "+15551234567");
     }
+
+    fn target(message_id: &str) -> MessageTarget {
+        MessageTarget {
+            to: "+15559876543".to_string(),
+            message_id: message_id.to_string(),
+            account_id: None,
+        }
+    }
+
+    #[test]
+    fn test_signal_operation_urls() {
+        let ch = test_channel();
+        assert_eq!(
+            ch.reactions_url(),
+            "http://localhost:8080/v1/reactions/+15551234567"
+        );
+        assert_eq!(
+            ch.remote_delete_url(),
+            "http://localhost:8080/v1/remote-delete/+15551234567"
+        );
+    }
+
+    #[test]
+    fn test_signal_operations_reject_invalid_timestamp() {
+        let ch = test_channel();
+        let result = ch
+            .edit_message(target("not-a-ts"), "x".to_string())
+            .unwrap();
+        assert!(!result.ok);
+        assert!(!result.retryable);
+        assert!(!ch.delete_message(target("")).unwrap().ok);
+        assert!(!ch.react(target("+1555:abc"), "👍".to_string()).unwrap().ok);
+    }
+
+    #[test]
+    fn test_signal_operations_connection_failure() {
+        let ch = SignalChannel::new("http://192.0.2.1:1".to_string(), "+15551234567".to_string());
+        let result = ch.delete_message(target("1700000000000")).unwrap();
+        assert!(!result.ok);
+        assert!(result.retryable);
+
+        let result = ch
+            .react(target("+15559876543:1700000000000"), "👍".to_string())
+            .unwrap();
+        assert!(!result.ok);
+        assert!(result.retryable);
+    }
+
+    #[test]
+    fn test_signal_timestamp_to_string() {
+        assert_eq!(
+            timestamp_to_string(&serde_json::json!("1700000000000")).as_deref(),
+            Some("1700000000000")
+        );
+        assert_eq!(
+            timestamp_to_string(&serde_json::json!(1700000000000u64)).as_deref(),
+            Some("1700000000000")
+        );
+        assert!(timestamp_to_string(&serde_json::json!(null)).is_none());
+    }
 }
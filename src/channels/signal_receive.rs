@@ -7,12 +7,13 @@ use std::sync::Arc;
 use std::time::Duration;
 
 use serde::Deserialize;
-use serde_json::Value;
-use tracing::{debug, error, info, warn};
+use tracing::{debug, info, warn};
 
+use crate::channels::inbound::{
+    dispatch_inbound, InboundGroup, InboundMedia, InboundMediaSource, InboundMessage,
+};
 use crate::channels::{ChannelRegistry, ChannelStatus};
 use crate::server::ws::WsServerState;
-use crate::sessions::{get_or_create_scoped_session, SessionMetadata};
 
 /// Interval between receive polls.
 const POLL_INTERVAL: Duration = Duration::from_secs(2);
@@ -50,6 +51,51 @@ pub struct SignalDataMessage {
     /// Group info, if this is a group message.
     #[serde(default, alias = "groupInfo")]
     pub group_info: Option<SignalGroupInfo>,
+
+    /// Attachments (photos, voice notes, files) held by signal-cli.
+    #[serde(default)]
+    pub attachments: Vec<SignalAttachment>,
+
+    /// Users mentioned in the message text.
+    #[serde(default)]
+    pub mentions: Vec<SignalMention>,
+
+    /// The message this one replies to, if any.
+    #[serde(default)]
+    pub quote: Option<SignalQuote>,
+}
+
+/// A mention of a user within a Signal message.
+#[derive(Debug, Deserialize)]
+pub struct SignalMention {
+    #[serde(default)]
+    pub number: Option<String>,
+}
+
+/// The quoted (replied-to) message on a Signal reply.
+#[derive(Debug, Deserialize)]
+pub struct SignalQuote {
+    #[serde(default)]
+    pub author: Option<String>,
+
+    #[serde(default, rename = "authorNumber")]
+    pub author_number: Option<String>,
+}
+
+/// An attachment on a Signal message, downloadable from
+/// `GET /v1/attachments/{id}`.
+#[derive(Debug, Deserialize)]
+pub struct SignalAttachment {
+    pub id: String,
+
+    #[serde(default, alias = "contentType")]
+    pub content_type: Option<String>,
+
+    #[serde(default)]
+    pub filename: Option<String>,
+
+    #[serde(default, alias = "voiceNote")]
+    pub voice_note: bool,
 }
 
 /// Group metadata on a Signal message.
@@ -111,7 +157,7 @@ pub async fn signal_receive_loop(
                 match resp.json::<Vec<SignalEnvelope>>().await {
                     Ok(envelopes) => {
                         for envelope in envelopes {
-                            process_envelope(&envelope, &state);
+                            process_envelope(&envelope, &state, &base_url, &phone_number);
                         }
                     }
                     Err(e) => {
@@ -154,119 +200,89 @@ pub async fn signal_receive_loop(
 }
 
 /// Process a single inbound Signal envelope by routing it into the chat pipeline.
-fn process_envelope(envelope: &SignalEnvelope, state: &Arc<WsServerState>) {
+fn process_envelope(
+    envelope: &SignalEnvelope,
+    state: &Arc<WsServerState>,
+    base_url: &str,
+    own_number: &str,
+) {
     let data_message = match &envelope.data_message {
         Some(dm) => dm,
         None => return, // Not a data message (e.g., receipt, typing indicator)
     };
 
-    let text = match &data_message.message {
-        Some(t) if !t.is_empty() => t,
-        _ => return, // No text content
-    };
-
     let sender = match &envelope.source_number {
         Some(s) => s,
         None => return, // No sender info
     };
 
+    let text = data_message.message.clone().unwrap_or_default();
+    if text.is_empty() && data_message.attachments.is_empty() {
+        return; // No content
+    }
+
     debug!(
         sender = %sender,
         text_len = text.len(),
+        attachments = data_message.attachments.len(),
         "Signal inbound message"
     );
 
-    let cfg = crate::config::load_config_shared()
-        .unwrap_or_else(|_| Arc::new(Value::Object(serde_json::Map::new())));
+    // Access policy, session scoping and media handling are shared with the
+    // other channels.
     let group_id = data_message
         .group_info
         .as_ref()
         .and_then(|group| group.group_id.clone());
-    let peer_id = group_id.as_deref().unwrap_or(sender).to_string();
-
-    let metadata = SessionMetadata {
-        channel: Some("signal".to_string()),
-        user_id: Some(sender.to_string()),
-        chat_id: group_id,
-        ..Default::default()
-    };
-
-    let session_store = state.session_store();
-    let session = match get_or_create_scoped_session(
-        session_store,
-        cfg.as_ref(),
-        "signal",
-        sender,
-        peer_id.as_str(),
-        None,
-        metadata,
-    ) {
-        Ok(session) => session,
-        Err(e) => {
-            error!("Failed to get/create Signal session for {}: {}", sender, e);
-            return;
-        }
-    };
-
-    // Append the user message
-    if let Err(e) = state
-        .session_store()
-        .append_message(crate::sessions::ChatMessage::user(session.id.clone(), text))
-    {
-        error!("Failed to append Signal message: {}", e);
-        return;
-    }
-
-    // Register and spawn agent run
-    let run_id = uuid::Uuid::new_v4().to_string();
-    let now = std::time::SystemTime::now()
-        .duration_since(std::time::UNIX_EPOCH)
-        .unwrap_or_default()
-        .as_millis() as u64;
-
-    let cancel_token = tokio_util::sync::CancellationToken::new();
-    let run = crate::server::ws::AgentRun {
-        run_id: run_id.clone(),
-        session_key: session.session_key.clone(),
-        status: crate::server::ws::AgentRunStatus::Queued,
-        message: text.to_string(),
-        response: String::new(),
-        error: None,
-        created_at: now,
-        started_at: None,
-        completed_at: None,
-        cancel_token: cancel_token.clone(),
-        waiters: Vec::new(),
-    };
+    dispatch_inbound(
+        state,
+        InboundMessage {
+            channel: "signal".to_string(),
+            sender_id: sender.clone(),
+            peer_id: group_id.clone().unwrap_or_else(|| sender.clone()),
+            text,
+            chat_id: group_id,
+            account_id: Some(own_number.to_string()),
+            media: attachment_media(data_message, base_url),
+            group: group_context(data_message, own_number),
+        },
+    );
+}
 
-    {
-        let mut registry = state.agent_run_registry.lock();
-        registry.register(run);
-    }
+/// Group context for a Signal group message: whether it mentions or quotes
+/// the account the receive loop runs as.
+fn group_context(data_message: &SignalDataMessage, own_number: &str) -> Option<InboundGroup> {
+    let group_id = data_message.group_info.as_ref()?.group_id.clone()?;
+    let mentioned = data_message
+        .mentions
+        .iter()
+        .any(|m| m.number.as_deref() == Some(own_number));
+    let replied_to_bot = data_message.quote.as_ref().is_some_and(|quote| {
+        quote.author_number.as_deref() == Some(own_number)
+            || quote.author.as_deref() == Some(own_number)
+    });
+    Some(InboundGroup {
+        group_id,
+        mentioned,
+        replied_to_bot,
+    })
+}
 
-    if let Some(provider) = state.llm_provider() {
-        let mut config = crate::agent::AgentConfig::default();
-        crate::agent::apply_agent_config_from_settings(&mut config, &cfg, None);
-        config.deliver = true;
-        crate::agent::spawn_run(
-            run_id.clone(),
-            session.session_key.clone(),
-            config,
-            state.clone(),
-            provider,
-            cancel_token,
-        );
-        debug!(
-            run_id = %run_id,
-            sender = %sender,
-            "Signal agent run dispatched"
-        );
-    } else {
-        debug!(
-            run_id = %run_id,
-            "Signal message queued (no LLM provider)"
-        );
-    }
+/// Describe a Signal message's attachments for the inbound media path.
+fn attachment_media(data_message: &SignalDataMessage, base_url: &str) -> Vec<InboundMedia> {
+    data_message
+        .attachments
+        .iter()
+        .map(|attachment| InboundMedia {
+            source: InboundMediaSource::Signal {
+                base_url: base_url.to_string(),
+                attachment_id: attachment.id.clone(),
+            },
+            mime_type: attachment.content_type.clone(),
+            file_name: attachment.filename.clone(),
+            voice: attachment.voice_note,
+        })
+        .collect()
 }
 
 #[cfg(test)]
@@ -354,6 +370,35 @@ mod tests {
         assert_eq!(envelopes[0].source_number.as_deref(), Some("+15559876543"));
     }
 
+    #[test]
+    fn test_parse_voice_note_attachment() {
+        let json = r#"[
+            {
+                "source": "+15559876543",
+                "dataMessage": {
+                    "attachments": [
+                        { "id": "Xy9.aac", "contentType": "audio/aac", "voiceNote": true, "size": 4096 }
+                    ]
+                }
+            }
+        ]"#;
+
+        let envelopes: Vec<SignalEnvelope> = serde_json::from_str(json).unwrap();
+        let dm = envelopes[0].data_message.as_ref().unwrap();
+        assert!(dm.message.is_none());
+        let media = attachment_media(dm, "http://127.0.0.1:8080");
+        assert_eq!(media.len(), 1);
+        assert!(media[0].voice);
+        assert_eq!(media[0].mime_type.as_deref(), Some("audio/aac"));
+        assert_eq!(
+            media[0].source,
+            InboundMediaSource::Signal {
+                base_url: "http://127.0.0.1:8080".to_string(),
+                attachment_id: "Xy9.aac".to_string(),
+            }
+        );
+    }
+
     #[test]
     fn test_parse_missing_text() {
         let json = r#"[
@@ -368,4 +413,32 @@ mod tests {
         let dm = envelopes[0].data_message.as_ref().unwrap();
         assert!(dm.message.is_none());
     }
+
+    #[test]
+    fn test_group_context_mention_and_quote() {
+        let json = r#"{
+            "message": "@bot what do you think?",
+            "groupInfo": { "groupId": "grp==" },
+            "mentions": [{ "number": "+15550000000", "start": 0, "length": 1 }],
+            "quote": { "id": 1, "author": "+15551111111", "authorNumber": "+15551111111" }
+        }"#;
+        let dm: SignalDataMessage = serde_json::from_str(json).unwrap();
+        let group = group_context(&dm, "+15550000000").unwrap();
+        assert_eq!(group.group_id, "grp==");
+        assert!(group.mentioned);
+        assert!(!group.replied_to_bot);
+
+        let json = r#"{
+            "message": "agreed",
+            "groupInfo": { "groupId": "grp==" },
+            "quote": { "id": 1, "authorNumber": "+15550000000" }
+        }"#;
+        let dm: SignalDataMessage = serde_json::from_str(json).unwrap();
+        let group = group_context(&dm, "+15550000000").unwrap();
+        assert!(!group.mentioned);
+        assert!(group.replied_to_bot);
+
+        let direct: SignalDataMessage = serde_json::from_str(r#"{ "message": "hi" }"#).unwrap();
+        assert!(group_context(&direct, "+15550000000").is_none());
+    }
 }
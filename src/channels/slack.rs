@@ -8,10 +8,10 @@ use reqwest::StatusCode;
 use serde_json::{json, Value};
 
 use crate::channels::media_fetch::fetch_media_bytes;
-use crate::channels::{ChannelAuthError, ChannelAuthResult};
+use crate::channels::{retry_after_header_ms, ChannelAuthError, ChannelAuthResult};
 use crate::plugins::{
     BindingError, ChannelCapabilities, ChannelInfo, ChannelPluginInstance, ChatType,
-    DeliveryResult, OutboundContext,
+    DeliveryResult, MessageTarget, OutboundContext,
 };
 
 /// Maximum media size to fetch and upload (50 MB).
@@ -93,6 +93,7 @@ impl SlackChannel {
 
     fn parse_response(resp: reqwest::blocking::Response) -> DeliveryResult {
         let status = resp.status();
+        let retry_after = retry_after_header_ms(resp.headers());
         let body_text = resp.text().unwrap_or_default();
         let parsed: Value = serde_json::from_str(&body_text).unwrap_or(Value::Null);
 
@@ -102,12 +103,11 @@ impl SlackChannel {
             .unwrap_or(status.is_success());
 
         if ok {
-            let message_id = parsed.get("ts").and_then(value_to_string).or_else(|| {
-                parsed
-                    .get("message")
-                    .and_then(|m| m.get("ts"))
-                    .and_then(value_to_string)
-            });
+            let message_id = parsed
+                .get("ts")
+                .or_else(|| parsed.get("message").and_then(|m| m.get("ts")))
+                .or_else(|| parsed.get("message_ts"))
+                .and_then(value_to_string);
             return success_result(message_id);
         }
 
@@ -124,10 +124,28 @@ impl SlackChannel {
             })
             .unwrap_or_else(|| "request failed".to_string());
 
-        error_result(
+        let mut result = error_result(
             error,
             status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS,
-        )
+        );
+        if status == StatusCode::TOO_MANY_REQUESTS {
+            result.retry_after_ms = retry_after;
+        }
+        result
+    }
+
+    /// POST a JSON body to a Web API method.
+    fn call_method(&self, method: &str, body: &Value) -> DeliveryResult {
+        match self
+            .client
+            .post(self.api_url(method))
+            .bearer_auth(&self.bot_token)
+            .json(body)
+            .send()
+        {
+            Ok(resp) => Self::parse_response(resp),
+            Err(e) => error_result(format!("request failed: {}", e), true),
+        }
     }
 }
 
@@ -146,6 +164,9 @@ impl ChannelPluginInstance for SlackChannel {
     fn get_capabilities(&self) -> Result<ChannelCapabilities, BindingError> {
         Ok(ChannelCapabilities {
             chat_types: vec![ChatType::Dm, ChatType::Channel, ChatType::Thread],
+            reactions: true,
+            edit: true,
+            unsend: true,
             media: true,
             reply: true,
             threads: true,
@@ -158,27 +179,7 @@ impl ChannelPluginInstance for SlackChannel {
             return Ok(error_result("text must not be empty", false));
         }
 
-        let thread_ts = ctx
-            .thread_id
-            .as_deref()
-            .filter(|id| !id.is_empty())
-            .or(ctx.reply_to_id.as_deref());
-
-        let mut body = json!({ "channel": ctx.to, "text": ctx.text });
-        if let Some(thread_ts) = thread_ts {
-            body["thread_ts"] = json!(thread_ts);
-        }
-
-        match self
-            .client
-            .post(self.api_url("chat.postMessage"))
-            .bearer_auth(&self.bot_token)
-            .json(&body)
-            .send()
-        {
-            Ok(resp) => Ok(Self::parse_response(resp)),
-            Err(e) => Ok(error_result(format!("request failed: {}", e), true)),
-        }
+        Ok(self.call_method("chat.postMessage", &message_body(&ctx)))
     }
 
     fn send_media(&self, ctx: OutboundContext) -> Result<DeliveryResult, BindingError> {
@@ -221,6 +222,100 @@ impl ChannelPluginInstance for SlackChannel {
             Err(e) => Ok(error_result(format!("request failed: {}", e), true)),
         }
     }
+
+    fn edit_message(
+        &self,
+        target: MessageTarget,
+        new_text: String,
+    ) -> Result<DeliveryResult, BindingError> {
+        if new_text.is_empty() {
+            return Ok(error_result("text must not be empty", false));
+        }
+        if target.message_id.trim().is_empty() {
+            return Ok(error_result("message_id must not be empty", false));
+        }
+        let body = json!({ "channel": target.to, "ts": target.message_id, "text": new_text });
+        Ok(self.call_method("chat.update", &body))
+    }
+
+    fn delete_message(&self, target: MessageTarget) -> Result<DeliveryResult, BindingError> {
+        if target.message_id.trim().is_empty() {
+            return Ok(error_result("message_id must not be empty", false));
+        }
+        let body = json!({ "channel": target.to, "ts": target.message_id });
+        Ok(self.call_method("chat.delete", &body))
+    }
+
+    fn react(&self, target: MessageTarget, emoji: String) -> Result<DeliveryResult, BindingError> {
+        if target.message_id.trim().is_empty() {
+            return Ok(error_result("message_id must not be empty", false));
+        }
+        let name = emoji_name(&emoji);
+        if name.is_empty() {
+            return Ok(error_result("emoji must not be empty", false));
+        }
+        let body = json!({
+            "channel": target.to,
+            "timestamp": target.message_id,
+            "name": name,
+        });
+        let mut result = self.call_method("reactions.add", &body);
+        if result.ok && result.message_id.is_none() {
+            result.message_id = Some(target.message_id);
+        }
+        Ok(result)
+    }
+
+    fn send_blocks(
+        &self,
+        ctx: OutboundContext,
+        blocks: Value,
+    ) -> Result<DeliveryResult, BindingError> {
+        if blocks.as_array().is_none_or(|b| b.is_empty()) {
+            return Ok(error_result("blocks must be a non-empty array", false));
+        }
+        // `text` stays as the notification and accessibility fallback.
+        let mut body = message_body(&ctx);
+        body["blocks"] = blocks;
+        Ok(self.call_method("chat.postMessage", &body))
+    }
+
+    fn send_ephemeral(
+        &self,
+        ctx: OutboundContext,
+        user: String,
+    ) -> Result<DeliveryResult, BindingError> {
+        if ctx.text.is_empty() {
+            return Ok(error_result("text must not be empty", false));
+        }
+        if user.trim().is_empty() {
+            return Ok(error_result("user must not be empty", false));
+        }
+        let mut body = message_body(&ctx);
+        body["user"] = json!(user.trim());
+        Ok(self.call_method("chat.postEphemeral", &body))
+    }
+}
+
+/// `chat.postMessage`-style body for an outbound message, threaded under
+/// the thread or reply target when one is set.
+fn message_body(ctx: &OutboundContext) -> Value {
+    let thread_ts = ctx
+        .thread_id
+        .as_deref()
+        .filter(|id| !id.is_empty())
+        .or(ctx.reply_to_id.as_deref());
+
+    let mut body = json!({ "channel": ctx.to, "text": ctx.text });
+    if let Some(thread_ts) = thread_ts {
+        body["thread_ts"] = json!(thread_ts);
+    }
+    body
+}
+
+/// Slack reactions take the emoji name without surrounding colons.
+fn emoji_name(emoji: &str) -> &str {
+    emoji.trim().trim_matches(':')
 }
 
 fn value_to_string(value: &Value) -> Option<String> {
@@ -250,6 +345,7 @@ fn success_result(message_id: Option<String>) -> DeliveryResult {
         conversation_id: None,
         to_jid: None,
         poll_id: None,
+        retry_after_ms: None,
     }
 }
 
@@ -262,6 +358,7 @@ fn error_result(error: impl Into<String>, retryable: bool) -> DeliveryResult {
         conversation_id: None,
         to_jid: None,
         poll_id: None,
+        retry_after_ms: None,
     }
 }
 
@@ -290,6 +387,10 @@ mod tests {
         assert!(caps.media);
         assert!(caps.reply);
         assert!(caps.threads);
+        assert!(caps.reactions);
+        assert!(caps.edit);
+        assert!(caps.unsend);
+        assert!(!caps.polls);
         assert_eq!(
             caps.chat_types,
             vec![ChatType::Dm, ChatType::Channel, ChatType::Thread]
@@ -339,4 +440,97 @@ mod tests {
         assert!(!result.ok);
         assert!(result.retryable);
     }
+
+    fn target(message_id: &str) -> MessageTarget {
+        MessageTarget {
+            to: "C123".to_string(),
+            message_id: message_id.to_string(),
+            account_id: None,
+        }
+    }
+
+    #[test]
+    fn test_slack_emoji_name() {
+        assert_eq!(emoji_name(":thumbsup:"), "thumbsup");
+        assert_eq!(emoji_name("eyes"), "eyes");
+        assert_eq!(emoji_name(" :: "), "");
+    }
+
+    #[test]
+    fn test_slack_operations_validate_input() {
+        let ch = test_channel();
+        assert!(!ch.edit_message(target("1.2"), String::new()).unwrap().ok);
+        assert!(!ch.delete_message(target("")).unwrap().ok);
+        assert!(!ch.react(target("1.2"), "::".to_string()).unwrap().ok);
+    }
+
+    #[test]
+    fn test_slack_poll_unsupported() {
+        let ch = test_channel();
+        let poll = crate::plugins::PollContext {
+            to: "C123".to_string(),
+            poll: crate::plugins::PollInput {
+                question: "Lunch?".to_string(),
+                options: vec!["Pizza".to_string(), "Sushi".to_string()],
+                allow_multiple: false,
+            },
+            account_id: None,
+        };
+        let result = ch.send_poll(poll).unwrap();
+        assert!(!result.ok);
+        assert!(!result.retryable);
+        assert!(result.error.unwrap().contains("not supported"));
+    }
+
+    #[test]
+    fn test_slack_update_connection_failure() {
+        let ch = SlackChannel::new("http://192.0.2.1:1".to_string(), "token".to_string());
+        let result = ch
+            .edit_message(target("1.2"), "edited".to_string())
+            .unwrap();
+        assert!(!result.ok);
+        assert!(result.retryable);
+    }
+
+    fn ctx(text: &str, thread_id: Option<&str>) -> OutboundContext {
+        OutboundContext {
+            to: "C123".to_string(),
+            text: text.to_string(),
+            media_url: None,
+            gif_playback: false,
+            reply_to_id: None,
+            thread_id: thread_id.map(String::from),
+            account_id: None,
+        }
+    }
+
+    #[test]
+    fn test_message_body_threads_replies() {
+        assert_eq!(
+            message_body(&ctx("hi", None)),
+            json!({ "channel": "C123", "text": "hi" })
+        );
+        assert_eq!(
+            message_body(&ctx("hi", Some("1700000000.000100"))),
+            json!({ "channel": "C123", "text": "hi", "thread_ts": "1700000000.000100" })
+        );
+    }
+
+    #[test]
+    fn test_slack_blocks_and_ephemeral_validate_input() {
+        let ch = test_channel();
+        let result = ch.send_blocks(ctx("hi", None), json!([])).unwrap();
+        assert!(!result.ok);
+        assert!(!result.retryable);
+        assert!(
+            !ch.send_ephemeral(ctx("hi", None), " ".to_string())
+                .unwrap()
+                .ok
+        );
+        assert!(
+            !ch.send_ephemeral(ctx("", None), "U1".to_string())
+                .unwrap()
+                .ok
+        );
+    }
 }
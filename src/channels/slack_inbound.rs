@@ -4,6 +4,8 @@ use hmac::{Hmac, Mac};
 use serde_json::Value;
 use sha2::Sha256;
 
+use crate::channels::inbound::{InboundGroup, InboundMedia, InboundMediaSource};
+
 /// Slack signature version prefix.
 pub const SLACK_SIGNATURE_VERSION: &str = "v0";
 
@@ -16,6 +18,9 @@ pub struct SlackInbound {
     pub sender_id: String,
     pub channel_id: String,
     pub text: String,
+    pub media: Vec<InboundMedia>,
+    /// Set for messages outside direct messages.
+    pub group: Option<InboundGroup>,
 }
 
 /// Verify a Slack signature against the raw request body.
@@ -42,32 +47,92 @@ pub fn verify_slack_signature(
     crate::auth::timing_safe_eq(&expected, signature)
 }
 
+/// The bot user the Events API payload was delivered for, from
+/// `authorizations[0].user_id`.
+pub fn payload_bot_user_id(payload: &Value) -> Option<&str> {
+    payload
+        .get("authorizations")
+        .and_then(|v| v.as_array())
+        .and_then(|auths| auths.first())
+        .and_then(|auth| auth.get("user_id"))
+        .and_then(|v| v.as_str())
+}
+
 /// Extract an inbound message event from a Slack event object.
-pub fn extract_inbound_event(event: &Value) -> Option<SlackInbound> {
+///
+/// `bot_user_id` is used to detect mentions of, and thread replies to, the
+/// bot in channels.
+pub fn extract_inbound_event(event: &Value, bot_user_id: Option<&str>) -> Option<SlackInbound> {
     let event_type = event.get("type").and_then(|v| v.as_str())?;
     if event_type != "message" && event_type != "app_mention" {
         return None;
     }
 
-    if event_type == "message" && event.get("subtype").is_some() {
+    // Edits, bot messages etc. carry a subtype; file shares are user messages
+    if event_type == "message"
+        && event
+            .get("subtype")
+            .is_some_and(|v| v.as_str() != Some("file_share"))
+    {
         return None;
     }
 
-    let text = event.get("text").and_then(|v| v.as_str())?;
-    if text.is_empty() {
+    let text = event.get("text").and_then(|v| v.as_str()).unwrap_or("");
+    let media = extract_files(event);
+    if text.is_empty() && media.is_empty() {
         return None;
     }
 
     let user = event.get("user").and_then(|v| v.as_str())?;
     let channel = event.get("channel").and_then(|v| v.as_str())?;
 
+    // app_mention events carry no channel_type; DM channel IDs start with D
+    let is_dm = match event.get("channel_type").and_then(|v| v.as_str()) {
+        Some(channel_type) => channel_type == "im",
+        None => channel.starts_with('D'),
+    };
+    let group = (!is_dm).then(|| InboundGroup {
+        group_id: channel.to_string(),
+        mentioned: event_type == "app_mention"
+            || bot_user_id.is_some_and(|bot| text.contains(&format!("<@{}>", bot))),
+        replied_to_bot: bot_user_id
+            .is_some_and(|bot| event.get("parent_user_id").and_then(|v| v.as_str()) == Some(bot)),
+    });
+
     Some(SlackInbound {
         sender_id: user.to_string(),
         channel_id: channel.to_string(),
         text: text.to_string(),
+        media,
+        group,
     })
 }
 
+/// Collect the shared files on a Slack message event.
+fn extract_files(event: &Value) -> Vec<InboundMedia> {
+    let Some(files) = event.get("files").and_then(|v| v.as_array()) else {
+        return Vec::new();
+    };
+    files
+        .iter()
+        .filter_map(|file| {
+            let url = file
+                .get("url_private_download")
+                .or_else(|| file.get("url_private"))
+                .and_then(|v| v.as_str())?;
+            Some(InboundMedia {
+                source: InboundMediaSource::SlackFile(url.to_string()),
+                mime_type: file
+                    .get("mimetype")
+                    .and_then(|v| v.as_str())
+                    .map(String::from),
+                file_name: file.get("name").and_then(|v| v.as_str()).map(String::from),
+                voice: file.get("subtype").and_then(|v| v.as_str()) == Some("slack_audio"),
+            })
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -100,7 +165,7 @@ mod tests {
             "channel": "C456",
             "text": "hello"
         });
-        let inbound = extract_inbound_event(&json).unwrap();
+        let inbound = extract_inbound_event(&json, None).unwrap();
         assert_eq!(inbound.sender_id, "U123");
         assert_eq!(inbound.channel_id, "C456");
         assert_eq!(inbound.text, "hello");
@@ -115,7 +180,42 @@ mod tests {
             "channel": "C456",
             "text": "hi"
         });
-        assert!(extract_inbound_event(&json).is_none());
+        assert!(extract_inbound_event(&json, None).is_none());
+    }
+
+    #[test]
+    fn test_extract_inbound_event_file_share() {
+        let json = serde_json::json!({
+            "type": "message",
+            "subtype": "file_share",
+            "user": "U123",
+            "channel": "C456",
+            "text": "",
+            "files": [
+                {
+                    "name": "chart.png",
+                    "mimetype": "image/png",
+                    "url_private": "https://files.slack.com/files-pri/T1-F1/chart.png",
+                    "url_private_download": "https://files.slack.com/files-pri/T1-F1/download/chart.png"
+                },
+                {
+                    "subtype": "slack_audio",
+                    "mimetype": "audio/webm",
+                    "url_private": "https://files.slack.com/files-pri/T1-F2/clip.webm"
+                }
+            ]
+        });
+        let inbound = extract_inbound_event(&json, None).unwrap();
+        assert!(inbound.text.is_empty());
+        assert_eq!(inbound.media.len(), 2);
+        assert_eq!(
+            inbound.media[0].source,
+            InboundMediaSource::SlackFile(
+                "https://files.slack.com/files-pri/T1-F1/download/chart.png".to_string()
+            )
+        );
+        assert_eq!(inbound.media[0].file_name.as_deref(), Some("chart.png"));
+        assert!(inbound.media[1].voice);
     }
 
     #[test]
@@ -126,9 +226,65 @@ mod tests {
             "channel": "C888",
             "text": "<@B123> hello"
         });
-        let inbound = extract_inbound_event(&json).unwrap();
+        let inbound = extract_inbound_event(&json, None).unwrap();
         assert_eq!(inbound.sender_id, "U777");
         assert_eq!(inbound.channel_id, "C888");
         assert_eq!(inbound.text, "<@B123> hello");
     }
+
+    #[test]
+    fn test_extract_inbound_event_group_detection() {
+        let dm = serde_json::json!({
+            "type": "message",
+            "channel_type": "im",
+            "user": "U123",
+            "channel": "D456",
+            "text": "hello"
+        });
+        assert!(extract_inbound_event(&dm, Some("UBOT"))
+            .unwrap()
+            .group
+            .is_none());
+
+        let mention = serde_json::json!({
+            "type": "message",
+            "channel_type": "channel",
+            "user": "U123",
+            "channel": "C456",
+            "text": "<@UBOT> what's up?"
+        });
+        let group = extract_inbound_event(&mention, Some("UBOT"))
+            .unwrap()
+            .group
+            .unwrap();
+        assert_eq!(group.group_id, "C456");
+        assert!(group.mentioned);
+        assert!(!group.replied_to_bot);
+
+        let thread_reply = serde_json::json!({
+            "type": "message",
+            "channel_type": "channel",
+            "user": "U123",
+            "channel": "C456",
+            "text": "thanks",
+            "thread_ts": "1.0",
+            "parent_user_id": "UBOT"
+        });
+        let group = extract_inbound_event(&thread_reply, Some("UBOT"))
+            .unwrap()
+            .group
+            .unwrap();
+        assert!(!group.mentioned);
+        assert!(group.replied_to_bot);
+    }
+
+    #[test]
+    fn test_payload_bot_user_id() {
+        let payload = serde_json::json!({
+            "type": "event_callback",
+            "authorizations": [{ "user_id": "UBOT", "is_bot": true }]
+        });
+        assert_eq!(payload_bot_user_id(&payload), Some("UBOT"));
+        assert_eq!(payload_bot_user_id(&serde_json::json!({})), None);
+    }
 }
@@ -8,10 +8,10 @@ use reqwest::StatusCode;
 use serde_json::{json, Value};
 
 use crate::channels::media_fetch::fetch_media_bytes;
-use crate::channels::{ChannelAuthError, ChannelAuthResult};
+use crate::channels::{retry_after_header_ms, seconds_to_ms, ChannelAuthError, ChannelAuthResult};
 use crate::plugins::{
     BindingError, ChannelCapabilities, ChannelInfo, ChannelPluginInstance, ChatType,
-    DeliveryResult, OutboundContext,
+    DeliveryResult, MessageTarget, OutboundContext, PollContext,
 };
 
 /// Maximum media size to fetch and upload (50 MB).
@@ -86,6 +86,7 @@ impl TelegramChannel {
 
     fn parse_response(resp: reqwest::blocking::Response) -> DeliveryResult {
         let status = resp.status();
+        let retry_after_header = retry_after_header_ms(resp.headers());
         let body_text = resp.text().unwrap_or_default();
         let parsed: Value = serde_json::from_str(&body_text).unwrap_or(Value::Null);
 
@@ -95,11 +96,16 @@ impl TelegramChannel {
             .unwrap_or(status.is_success());
 
         if ok {
-            let message_id = parsed
-                .get("result")
+            let result = parsed.get("result");
+            let message_id = result
                 .and_then(|r| r.get("message_id"))
                 .and_then(value_to_string);
-            return success_result(message_id);
+            let mut delivery = success_result(message_id);
+            delivery.poll_id = result
+                .and_then(|r| r.get("poll"))
+                .and_then(|p| p.get("id"))
+                .and_then(value_to_string);
+            return delivery;
         }
 
         let error = parsed
@@ -115,10 +121,29 @@ impl TelegramChannel {
             })
             .unwrap_or_else(|| "request failed".to_string());
 
-        error_result(
+        let mut result = error_result(
             error,
             status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS,
-        )
+        );
+        // Flood control: `parameters.retry_after` is in seconds
+        result.retry_after_ms = parsed
+            .get("parameters")
+            .and_then(|p| p.get("retry_after"))
+            .and_then(|v| v.as_f64())
+            .and_then(seconds_to_ms)
+            .or(retry_after_header);
+        if result.retry_after_ms.is_some() {
+            result.retryable = true;
+        }
+        result
+    }
+
+    /// POST a JSON body to a Bot API method.
+    fn call_method(&self, method: &str, body: &Value) -> DeliveryResult {
+        match self.client.post(self.api_url(method)).json(body).send() {
+            Ok(resp) => Self::parse_response(resp),
+            Err(e) => error_result(format!("request failed: {}", e), true),
+        }
     }
 }
 
@@ -137,6 +162,10 @@ impl ChannelPluginInstance for TelegramChannel {
     fn get_capabilities(&self) -> Result<ChannelCapabilities, BindingError> {
         Ok(ChannelCapabilities {
             chat_types: vec![ChatType::Dm, ChatType::Group, ChatType::Channel],
+            polls: true,
+            reactions: true,
+            edit: true,
+            unsend: true,
             media: true,
             reply: true,
             threads: true,
@@ -227,6 +256,137 @@ impl ChannelPluginInstance for TelegramChannel {
             Err(e) => Ok(error_result(format!("request failed: {}", e), true)),
         }
     }
+
+    fn send_poll(&self, ctx: PollContext) -> Result<DeliveryResult, BindingError> {
+        let options: Vec<Value> = ctx
+            .poll
+            .options
+            .iter()
+            .map(|option| json!({ "text": option }))
+            .collect();
+        let body = json!({
+            "chat_id": ctx.to,
+            "question": ctx.poll.question,
+            "options": options,
+            "allows_multiple_answers": ctx.poll.allow_multiple,
+        });
+        Ok(self.call_method("sendPoll", &body))
+    }
+
+    fn edit_message(
+        &self,
+        target: MessageTarget,
+        new_text: String,
+    ) -> Result<DeliveryResult, BindingError> {
+        if new_text.is_empty() {
+            return Ok(error_result("text must not be empty", false));
+        }
+        let message_id = match parse_message_id(&target.message_id) {
+            Ok(id) => id,
+            Err(e) => return Ok(e),
+        };
+        let body = json!({
+            "chat_id": target.to,
+            "message_id": message_id,
+            "text": new_text,
+        });
+        Ok(self.call_method("editMessageText", &body))
+    }
+
+    fn delete_message(&self, target: MessageTarget) -> Result<DeliveryResult, BindingError> {
+        let message_id = match parse_message_id(&target.message_id) {
+            Ok(id) => id,
+            Err(e) => return Ok(e),
+        };
+        let body = json!({ "chat_id": target.to, "message_id": message_id });
+        Ok(with_message_id(
+            self.call_method("deleteMessage", &body),
+            target.message_id,
+        ))
+    }
+
+    fn react(&self, target: MessageTarget, emoji: String) -> Result<DeliveryResult, BindingError> {
+        let message_id = match parse_message_id(&target.message_id) {
+            Ok(id) => id,
+            Err(e) => return Ok(e),
+        };
+        let body = json!({
+            "chat_id": target.to,
+            "message_id": message_id,
+            "reaction": [{ "type": "emoji", "emoji": emoji }],
+        });
+        Ok(with_message_id(
+            self.call_method("setMessageReaction", &body),
+            target.message_id,
+        ))
+    }
+
+    fn pin_message(
+        &self,
+        target: MessageTarget,
+        silent: bool,
+    ) -> Result<DeliveryResult, BindingError> {
+        let message_id = match parse_message_id(&target.message_id) {
+            Ok(id) => id,
+            Err(e) => return Ok(e),
+        };
+        let body = json!({
+            "chat_id": target.to,
+            "message_id": message_id,
+            "disable_notification": silent,
+        });
+        Ok(with_message_id(
+            self.call_method("pinChatMessage", &body),
+            target.message_id,
+        ))
+    }
+
+    fn set_reply_markup(
+        &self,
+        target: MessageTarget,
+        buttons: Value,
+    ) -> Result<DeliveryResult, BindingError> {
+        let message_id = match parse_message_id(&target.message_id) {
+            Ok(id) => id,
+            Err(e) => return Ok(e),
+        };
+        let body = json!({
+            "chat_id": target.to,
+            "message_id": message_id,
+            "reply_markup": { "inline_keyboard": inline_keyboard(buttons) },
+        });
+        Ok(with_message_id(
+            self.call_method("editMessageReplyMarkup", &body),
+            target.message_id,
+        ))
+    }
+}
+
+/// Arrange buttons into keyboard rows.
+///
+/// A flat list of buttons becomes a single row; a list of rows is kept.
+fn inline_keyboard(buttons: Value) -> Value {
+    match buttons {
+        Value::Array(items) if items.iter().all(Value::is_array) => Value::Array(items),
+        Value::Array(items) => json!([items]),
+        other => json!([[other]]),
+    }
+}
+
+#[allow(clippy::result_large_err)]
+fn parse_message_id(raw: &str) -> Result<i64, DeliveryResult> {
+    match parse_optional_i64(Some(raw), "message_id")? {
+        Some(id) => Ok(id),
+        None => Err(error_result("message_id must not be empty", false)),
+    }
+}
+
+/// Report the target message ID on successful calls that return no message.
+fn with_message_id(mut result: DeliveryResult, message_id: String) -> DeliveryResult {
+    if result.ok && result.message_id.is_none() {
+        result.message_id = Some(message_id);
+    }
+    result
 }
 
 #[allow(clippy::result_large_err)]
@@ -274,6 +434,7 @@ fn success_result(message_id: Option<String>) -> DeliveryResult {
         conversation_id: None,
         to_jid: None,
         poll_id: None,
+        retry_after_ms: None,
     }
 }
 
@@ -286,6 +447,7 @@ fn error_result(error: impl Into<String>, retryable: bool) -> DeliveryResult {
         conversation_id: None,
         to_jid: None,
         poll_id: None,
+        retry_after_ms: None,
     }
 }
 
@@ -314,6 +476,10 @@ mod tests {
         assert!(caps.media);
         assert!(caps.reply);
         assert!(caps.threads);
+        assert!(caps.polls);
+        assert!(caps.reactions);
+        assert!(caps.edit);
+        assert!(caps.unsend);
         assert_eq!(
             caps.chat_types,
             vec![ChatType::Dm, ChatType::Group, ChatType::Channel]
@@ -363,4 +529,87 @@ mod tests {
         assert!(!result.ok);
         assert!(result.retryable);
     }
+
+    fn target(message_id: &str) -> MessageTarget {
+        MessageTarget {
+            to: "123456".to_string(),
+            message_id: message_id.to_string(),
+            account_id: None,
+        }
+    }
+
+    #[test]
+    fn test_telegram_edit_rejects_non_numeric_message_id() {
+        let ch = test_channel();
+        let result = ch.edit_message(target("abc"), "new".to_string()).unwrap();
+        assert!(!result.ok);
+        assert!(!result.retryable);
+        assert!(result.error.unwrap().contains("message_id"));
+    }
+
+    #[test]
+    fn test_telegram_edit_rejects_empty_text() {
+        let ch = test_channel();
+        let result = ch.edit_message(target("42"), String::new()).unwrap();
+        assert!(!result.ok);
+        assert!(!result.retryable);
+    }
+
+    #[test]
+    fn test_telegram_delete_and_react_reject_empty_message_id() {
+        let ch = test_channel();
+        assert!(!ch.delete_message(target(" ")).unwrap().ok);
+        assert!(!ch.react(target(""), "👍".to_string()).unwrap().ok);
+    }
+
+    #[test]
+    fn test_telegram_operations_connection_failure() {
+        let ch = TelegramChannel::new("http://192.0.2.1:1".to_string(), "token".to_string());
+        let result = ch.delete_message(target("42")).unwrap();
+        assert!(!result.ok);
+        assert!(result.retryable);
+
+        let poll = PollContext {
+            to: "123456".to_string(),
+            poll: crate::plugins::PollInput {
+                question: "Lunch?".to_string(),
+                options: vec!["Pizza".to_string(), "Sushi".to_string()],
+                allow_multiple: false,
+            },
+            account_id: None,
+        };
+        let result = ch.send_poll(poll).unwrap();
+        assert!(!result.ok);
+        assert!(result.retryable);
+    }
+
+    #[test]
+    fn test_with_message_id_fills_only_successful_results() {
+        let ok = with_message_id(success_result(None), "42".to_string());
+        assert_eq!(ok.message_id.as_deref(), Some("42"));
+        let failed = with_message_id(error_result("nope", false), "42".to_string());
+        assert!(failed.message_id.is_none());
+    }
+
+    #[test]
+    fn test_telegram_pin_and_markup_reject_empty_message_id() {
+        let ch = test_channel();
+        assert!(!ch.pin_message(target(""), true).unwrap().ok);
+        let result = ch.set_reply_markup(target(""), json!([])).unwrap();
+        assert!(!result.ok);
+        assert!(!result.retryable);
+    }
+
+    #[test]
+    fn test_inline_keyboard_rows() {
+        let button = json!({ "text": "Yes", "callback_data": "y" });
+        assert_eq!(
+            inline_keyboard(json!([button.clone()])),
+            json!([[button.clone()]])
+        );
+        assert_eq!(
+            inline_keyboard(json!([[button.clone()], [button.clone()]])),
+            json!([[button.clone()], [button]])
+        );
+    }
 }
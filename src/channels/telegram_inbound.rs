@@ -1,6 +1,9 @@
 //! Telegram inbound webhook parsing helpers.
 
 use serde::Deserialize;
+use serde_json::Value;
+
+use crate::channels::inbound::{InboundGroup, InboundMedia, InboundMediaSource};
 
 /// Telegram update payload.
 #[derive(Debug, Deserialize)]
@@ -27,6 +30,43 @@ pub struct TelegramMessage {
     pub from: Option<TelegramUser>,
     #[serde(default, rename = "sender_chat")]
     pub sender_chat: Option<TelegramChat>,
+    /// Photo in several sizes, smallest first.
+    #[serde(default)]
+    pub photo: Vec<TelegramFile>,
+    #[serde(default)]
+    pub voice: Option<TelegramFile>,
+    #[serde(default)]
+    pub audio: Option<TelegramFile>,
+    #[serde(default)]
+    pub document: Option<TelegramFile>,
+    #[serde(default)]
+    pub entities: Vec<TelegramEntity>,
+    #[serde(default)]
+    pub caption_entities: Vec<TelegramEntity>,
+    #[serde(default)]
+    pub reply_to_message: Option<Box<TelegramMessage>>,
+}
+
+/// A formatting entity within message text; offsets are in UTF-16 units.
+#[derive(Debug, Deserialize)]
+pub struct TelegramEntity {
+    #[serde(rename = "type")]
+    pub entity_type: String,
+    pub offset: usize,
+    pub length: usize,
+    /// Set for `text_mention` entities (users without a username).
+    #[serde(default)]
+    pub user: Option<TelegramUser>,
+}
+
+/// A file reference (photo size, voice note, audio or document).
+#[derive(Debug, Deserialize)]
+pub struct TelegramFile {
+    pub file_id: String,
+    #[serde(default)]
+    pub mime_type: Option<String>,
+    #[serde(default)]
+    pub file_name: Option<String>,
 }
 
 /// Telegram chat metadata.
@@ -43,6 +83,47 @@ pub struct TelegramUser {
     pub id: i64,
     #[serde(default)]
     pub is_bot: bool,
+    #[serde(default)]
+    pub username: Option<String>,
+}
+
+/// The bot's own identity, used to detect mentions and replies in groups.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TelegramBotIdentity {
+    /// Bot user ID (the numeric prefix of the bot token).
+    pub id: Option<i64>,
+    /// Bot username without the leading `@`.
+    pub username: Option<String>,
+}
+
+impl TelegramBotIdentity {
+    /// Resolve from `telegram.botToken` (or `TELEGRAM_BOT_TOKEN`) and
+    /// `telegram.botUsername`.
+    pub fn from_config(cfg: &Value) -> Self {
+        let telegram = cfg.get("telegram");
+        let token = telegram
+            .and_then(|t| t.get("botToken"))
+            .and_then(|v| v.as_str())
+            .map(String::from)
+            .or_else(|| std::env::var("TELEGRAM_BOT_TOKEN").ok());
+        Self {
+            id: token.and_then(|t| t.split(':').next()?.parse().ok()),
+            username: telegram
+                .and_then(|t| t.get("botUsername"))
+                .and_then(|v| v.as_str())
+                .map(|s| s.trim_start_matches('@').to_string())
+                .filter(|s| !s.is_empty()),
+        }
+    }
+
+    fn is_bot_user(&self, user: &TelegramUser) -> bool {
+        self.id == Some(user.id)
+            || self.username.as_deref().is_some_and(|name| {
+                user.username
+                    .as_deref()
+                    .is_some_and(|u| u.eq_ignore_ascii_case(name))
+            })
+    }
 }
 
 /// Parsed inbound Telegram message.
@@ -51,10 +132,16 @@ pub struct TelegramInbound {
     pub sender_id: String,
     pub chat_id: String,
     pub text: String,
+    pub media: Vec<InboundMedia>,
+    /// Set for group and supergroup messages.
+    pub group: Option<InboundGroup>,
 }
 
-/// Extract a text-bearing inbound message from a Telegram update.
-pub fn extract_inbound(update: &TelegramUpdate) -> Option<TelegramInbound> {
+/// Extract an inbound message with text and/or media from a Telegram update.
+pub fn extract_inbound(
+    update: &TelegramUpdate,
+    bot: &TelegramBotIdentity,
+) -> Option<TelegramInbound> {
     let message = update
         .message
         .as_ref()
@@ -72,8 +159,13 @@ pub fn extract_inbound(update: &TelegramUpdate) -> Option<TelegramInbound> {
         .text
         .as_ref()
         .filter(|t| !t.is_empty())
-        .or_else(|| message.caption.as_ref().filter(|t| !t.is_empty()))?
-        .to_string();
+        .or_else(|| message.caption.as_ref().filter(|t| !t.is_empty()))
+        .cloned()
+        .unwrap_or_default();
+    let media = extract_media(message);
+    if text.is_empty() && media.is_empty() {
+        return None;
+    }
 
     let sender_id = message
         .from
@@ -82,13 +174,91 @@ pub fn extract_inbound(update: &TelegramUpdate) -> Option<TelegramInbound> {
         .or_else(|| message.sender_chat.as_ref().map(|c| c.id))
         .unwrap_or(message.chat.id);
 
+    let is_group = matches!(
+        message.chat.chat_type.as_deref(),
+        Some("group") | Some("supergroup")
+    );
+    let group = is_group.then(|| InboundGroup {
+        group_id: message.chat.id.to_string(),
+        mentioned: mentions_bot(message, bot),
+        replied_to_bot: message
+            .reply_to_message
+            .as_ref()
+            .and_then(|reply| reply.from.as_ref())
+            .is_some_and(|from| bot.is_bot_user(from)),
+    });
+
     Some(TelegramInbound {
         sender_id: sender_id.to_string(),
         chat_id: message.chat.id.to_string(),
         text,
+        media,
+        group,
     })
 }
 
+/// Whether the message's text or caption entities mention the bot.
+fn mentions_bot(message: &TelegramMessage, bot: &TelegramBotIdentity) -> bool {
+    let sources = [
+        (message.text.as_deref(), &message.entities),
+        (message.caption.as_deref(), &message.caption_entities),
+    ];
+    sources.iter().any(|(text, entities)| {
+        entities
+            .iter()
+            .any(|entity| match entity.entity_type.as_str() {
+                "text_mention" => entity.user.as_ref().is_some_and(|u| bot.is_bot_user(u)),
+                "mention" => match (text, bot.username.as_deref()) {
+                    (Some(text), Some(username)) => entity_text(text, entity)
+                        .is_some_and(|m| m.trim_start_matches('@').eq_ignore_ascii_case(username)),
+                    _ => false,
+                },
+                _ => false,
+            })
+    })
+}
+
+/// The text an entity covers, converting its UTF-16 offsets.
+fn entity_text(text: &str, entity: &TelegramEntity) -> Option<String> {
+    let units: Vec<u16> = text.encode_utf16().collect();
+    let end = entity.offset.checked_add(entity.length)?;
+    String::from_utf16(units.get(entity.offset..end)?).ok()
+}
+
+/// Collect the media attached to a Telegram message.
+fn extract_media(message: &TelegramMessage) -> Vec<InboundMedia> {
+    let mut media = Vec::new();
+    // The last photo size is the largest
+    if let Some(photo) = message.photo.last() {
+        media.push(InboundMedia {
+            source: InboundMediaSource::Telegram(photo.file_id.clone()),
+            mime_type: Some("image/jpeg".to_string()),
+            file_name: None,
+            voice: false,
+        });
+    }
+    if let Some(voice) = &message.voice {
+        media.push(InboundMedia {
+            source: InboundMediaSource::Telegram(voice.file_id.clone()),
+            mime_type: voice
+                .mime_type
+                .clone()
+                .or_else(|| Some("audio/ogg".to_string())),
+            file_name: None,
+            voice: true,
+        });
+    }
+    for file in [&message.audio, &message.document].into_iter().flatten() {
+        media.push(InboundMedia {
+            source: InboundMediaSource::Telegram(file.file_id.clone()),
+            mime_type: file.mime_type.clone(),
+            file_name: file.file_name.clone(),
+            voice: false,
+        });
+    }
+    media
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -103,7 +273,7 @@ mod tests {
             }
         }"#;
         let update: TelegramUpdate = serde_json::from_str(json).unwrap();
-        let inbound = extract_inbound(&update).unwrap();
+        let inbound = extract_inbound(&update, &TelegramBotIdentity::default()).unwrap();
         assert_eq!(inbound.sender_id, "456");
         assert_eq!(inbound.chat_id, "123");
         assert_eq!(inbound.text, "Hello");
@@ -119,12 +289,61 @@ mod tests {
             }
         }"#;
         let update: TelegramUpdate = serde_json::from_str(json).unwrap();
-        let inbound = extract_inbound(&update).unwrap();
+        let inbound = extract_inbound(&update, &TelegramBotIdentity::default()).unwrap();
         assert_eq!(inbound.sender_id, "888");
         assert_eq!(inbound.chat_id, "999");
         assert_eq!(inbound.text, "Announcement");
     }
 
+    #[test]
+    fn test_extract_inbound_photo_and_voice() {
+        let json = r#"{
+            "message": {
+                "caption": "Look",
+                "chat": { "id": 123, "type": "private" },
+                "from": { "id": 456, "is_bot": false },
+                "photo": [
+                    { "file_id": "small", "width": 90, "height": 90 },
+                    { "file_id": "large", "width": 1280, "height": 1280 }
+                ]
+            }
+        }"#;
+        let update: TelegramUpdate = serde_json::from_str(json).unwrap();
+        let inbound = extract_inbound(&update, &TelegramBotIdentity::default()).unwrap();
+        assert_eq!(inbound.text, "Look");
+        assert_eq!(inbound.media.len(), 1);
+        assert_eq!(
+            inbound.media[0].source,
+            InboundMediaSource::Telegram("large".to_string())
+        );
+
+        // A voice note with no text is still an inbound message
+        let json = r#"{
+            "message": {
+                "chat": { "id": 123, "type": "private" },
+                "from": { "id": 456, "is_bot": false },
+                "voice": { "file_id": "v1", "mime_type": "audio/ogg", "duration": 3 }
+            }
+        }"#;
+        let update: TelegramUpdate = serde_json::from_str(json).unwrap();
+        let inbound = extract_inbound(&update, &TelegramBotIdentity::default()).unwrap();
+        assert!(inbound.text.is_empty());
+        assert!(inbound.media[0].voice);
+        assert_eq!(inbound.media[0].mime_type.as_deref(), Some("audio/ogg"));
+    }
+
+    #[test]
+    fn test_extract_inbound_skips_empty_message() {
+        let json = r#"{
+            "message": {
+                "chat": { "id": 123, "type": "private" },
+                "from": { "id": 456, "is_bot": false }
+            }
+        }"#;
+        let update: TelegramUpdate = serde_json::from_str(json).unwrap();
+        assert!(extract_inbound(&update, &TelegramBotIdentity::default()).is_none());
+    }
+
     #[test]
     fn test_extract_inbound_skips_bot() {
         let json = r#"{
@@ -135,6 +354,97 @@ mod tests {
             }
         }"#;
         let update: TelegramUpdate = serde_json::from_str(json).unwrap();
-        assert!(extract_inbound(&update).is_none());
+        assert!(extract_inbound(&update, &TelegramBotIdentity::default()).is_none());
+    }
+
+    fn group_bot() -> TelegramBotIdentity {
+        TelegramBotIdentity {
+            id: Some(777),
+            username: Some("carapace_bot".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_extract_inbound_private_chat_has_no_group() {
+        let json = r#"{
+            "message": {
+                "text": "Hello",
+                "chat": { "id": 123, "type": "private" },
+                "from": { "id": 456, "is_bot": false }
+            }
+        }"#;
+        let update: TelegramUpdate = serde_json::from_str(json).unwrap();
+        let inbound = extract_inbound(&update, &group_bot()).unwrap();
+        assert!(inbound.group.is_none());
+    }
+
+    #[test]
+    fn test_extract_inbound_group_mention() {
+        // "👋 @Carapace_Bot hi": the emoji is two UTF-16 units
+        let json = r#"{
+            "message": {
+                "text": "👋 @Carapace_Bot hi",
+                "chat": { "id": -100, "type": "supergroup" },
+                "from": { "id": 456, "is_bot": false },
+                "entities": [{ "type": "mention", "offset": 3, "length": 13 }]
+            }
+        }"#;
+        let update: TelegramUpdate = serde_json::from_str(json).unwrap();
+        let group = extract_inbound(&update, &group_bot())
+            .unwrap()
+            .group
+            .unwrap();
+        assert_eq!(group.group_id, "-100");
+        assert!(group.mentioned);
+        assert!(!group.replied_to_bot);
+
+        // Mentioning someone else does not count
+        let json = r#"{
+            "message": {
+                "text": "@someone hi",
+                "chat": { "id": -100, "type": "group" },
+                "from": { "id": 456, "is_bot": false },
+                "entities": [{ "type": "mention", "offset": 0, "length": 8 }]
+            }
+        }"#;
+        let update: TelegramUpdate = serde_json::from_str(json).unwrap();
+        let group = extract_inbound(&update, &group_bot())
+            .unwrap()
+            .group
+            .unwrap();
+        assert!(!group.mentioned);
+    }
+
+    #[test]
+    fn test_extract_inbound_group_reply_to_bot() {
+        let json = r#"{
+            "message": {
+                "text": "thanks",
+                "chat": { "id": -100, "type": "group" },
+                "from": { "id": 456, "is_bot": false },
+                "reply_to_message": {
+                    "text": "answer",
+                    "chat": { "id": -100, "type": "group" },
+                    "from": { "id": 777, "is_bot": true }
+                }
+            }
+        }"#;
+        let update: TelegramUpdate = serde_json::from_str(json).unwrap();
+        let group = extract_inbound(&update, &group_bot())
+            .unwrap()
+            .group
+            .unwrap();
+        assert!(group.replied_to_bot);
+        assert!(!group.mentioned);
+    }
+
+    #[test]
+    fn test_bot_identity_from_config() {
+        let cfg = serde_json::json!({
+            "telegram": { "botToken": "12345:ABC", "botUsername": "@my_bot" }
+        });
+        let bot = TelegramBotIdentity::from_config(&cfg);
+        assert_eq!(bot.id, Some(12345));
+        assert_eq!(bot.username.as_deref(), Some("my_bot"));
     }
 }
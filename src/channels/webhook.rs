@@ -87,6 +87,7 @@ impl WebhookChannel {
                 conversation_id: None,
                 to_jid: None,
                 poll_id: None,
+                retry_after_ms: None,
             });
         }
 
@@ -108,6 +109,7 @@ impl WebhookChannel {
                         conversation_id: None,
                         to_jid: None,
                         poll_id: None,
+                        retry_after_ms: None,
                     })
                 } else {
                     let retryable = status.is_server_error();
@@ -119,6 +121,7 @@ impl WebhookChannel {
                         conversation_id: None,
                         to_jid: None,
                         poll_id: None,
+                        retry_after_ms: None,
                     })
                 }
             }
@@ -130,6 +133,7 @@ impl WebhookChannel {
                 conversation_id: None,
                 to_jid: None,
                 poll_id: None,
+                retry_after_ms: None,
             }),
         }
     }
@@ -11,6 +11,7 @@
 //! - `reset` -- clear specific data categories
 //! - `setup` -- interactive first-run configuration wizard
 //! - `pair` -- pair with a remote gateway node
+//! - `senders list|approve|reject|revoke` -- manage inbound channel senders
 //! - `update` -- check for updates or self-update
 
 pub mod backup_crypto;
@@ -144,6 +145,32 @@ pub enum Command {
         trust: bool,
     },
 
+    /// Manage inbound channel senders and their pairing codes.
+    Senders {
+        #[command(subcommand)]
+        action: SendersCommand,
+
+        /// Port of the running instance (default: from config or 18789).
+        #[arg(short, long, global = true)]
+        port: Option<u16>,
+
+        /// Host of the running instance.
+        #[arg(long, default_value = "127.0.0.1", global = true)]
+        host: String,
+
+        /// Use TLS (wss://) for remote connections.
+        #[arg(long, global = true)]
+        tls: bool,
+
+        /// Accept invalid TLS certificates (only with --tls).
+        #[arg(long, global = true)]
+        trust: bool,
+
+        /// Allow plaintext ws:// for non-loopback hosts (unsafe).
+        #[arg(long, global = true)]
+        allow_plaintext: bool,
+    },
+
     /// Check for updates or install a specific version.
     Update {
         /// Check for updates without installing.
@@ -209,6 +236,39 @@ pub enum TlsCommand {
     },
 }
 
+#[derive(Subcommand, Debug)]
+pub enum SendersCommand {
+    /// List pending pairing codes and approved senders.
+    List,
+
+    /// Approve the sender behind a pairing code.
+    Approve {
+        /// Channel the code was issued on (e.g. "telegram").
+        channel: String,
+
+        /// Pairing code the sender received.
+        code: String,
+    },
+
+    /// Reject a pairing code.
+    Reject {
+        /// Channel the code was issued on.
+        channel: String,
+
+        /// Pairing code the sender received.
+        code: String,
+    },
+
+    /// Revoke a previously approved sender.
+    Revoke {
+        /// Channel the sender was approved on.
+        channel: String,
+
+        /// Channel-specific sender ID.
+        sender_id: String,
+    },
+}
+
 #[derive(Subcommand, Debug)]
 pub enum ConfigCommand {
     /// Print the fully loaded configuration (secrets redacted) as JSON.
@@ -419,6 +479,31 @@ struct LogsTailResponse {
     truncated: bool,
 }
 
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SendersPendingEntry {
+    channel: String,
+    sender_id: String,
+    code: String,
+    expires_at_ms: u64,
+}
+
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SendersApprovedEntry {
+    channel: String,
+    sender_id: String,
+    approved_at_ms: u64,
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct SendersListResponse {
+    #[serde(default)]
+    pending: Vec<SendersPendingEntry>,
+    #[serde(default)]
+    approved: Vec<SendersApprovedEntry>,
+}
+
 type WsStream =
     tokio_tungstenite::WebSocketStream<tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>>;
 type WsRead = futures_util::stream::SplitStream<WsStream>;
@@ -1095,19 +1180,21 @@ fn extract_pairing_request_id(details: &Value) -> Option<String> {
         .map(|id| id.to_string())
 }
 
-pub async fn handle_logs(
+/// Connect to a running instance as an operator with `scopes`, completing
+/// the connect handshake. Exits with a hint when the server is unreachable.
+async fn connect_operator_ws(
     host: &str,
     port: Option<u16>,
-    lines: usize,
     tls: bool,
     trust: bool,
     allow_plaintext: bool,
-) -> Result<(), Box<dyn std::error::Error>> {
+    scopes: &[String],
+) -> Result<(WsWrite, WsRead), Box<dyn std::error::Error>> {
     let port = resolve_port(port);
 
     let is_loopback = is_loopback_host(host);
     if !is_loopback && !tls && !allow_plaintext {
-        eprintln!("Remote logs require TLS or explicit plaintext opt-in.");
+        eprintln!("Remote connections require TLS or explicit plaintext opt-in.");
         eprintln!("Use --tls for wss:// or pass --allow-plaintext to override.");
         std::process::exit(1);
     }
@@ -1149,7 +1236,6 @@ pub async fn handle_logs(
     let nonce = await_connect_challenge(&mut ws_read, &mut ws_write).await?;
 
     let role = "operator";
-    let scopes = vec!["operator.read".to_string()];
     let mut connect_params = serde_json::json!({
         "minProtocol": 3,
         "maxProtocol": 3,
@@ -1160,7 +1246,7 @@ pub async fn handle_logs(
             "mode": "cli"
         },
         "role": role,
-        "scopes": scopes.clone()
+        "scopes": scopes
     });
     let GatewayAuth { token, password } = auth;
     let token_for_signature = token.clone();
@@ -1174,7 +1260,7 @@ pub async fn handle_logs(
         "cli",
         "cli",
         role,
-        &scopes,
+        scopes,
         token_for_signature.as_deref(),
         Some(&nonce),
     )?;
@@ -1208,6 +1294,21 @@ pub async fn handle_logs(
         return Err(Box::new(err));
     }
 
+    Ok((ws_write, ws_read))
+}
+
+pub async fn handle_logs(
+    host: &str,
+    port: Option<u16>,
+    lines: usize,
+    tls: bool,
+    trust: bool,
+    allow_plaintext: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let scopes = vec!["operator.read".to_string()];
+    let (mut ws_write, mut ws_read) =
+        connect_operator_ws(host, port, tls, trust, allow_plaintext, &scopes).await?;
+
     let logs_frame = serde_json::json!({
         "type": "req",
         "id": "logs-1",
@@ -1243,6 +1344,111 @@ pub async fn handle_logs(
     Ok(())
 }
 
+/// Run a `senders` subcommand against a running instance.
+pub async fn handle_senders(
+    action: SendersCommand,
+    host: &str,
+    port: Option<u16>,
+    tls: bool,
+    trust: bool,
+    allow_plaintext: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let (method, params, scope) = match &action {
+        SendersCommand::List => ("channels.pair.list", serde_json::json!({}), "operator.read"),
+        SendersCommand::Approve { channel, code } => (
+            "channels.pair.approve",
+            serde_json::json!({ "channel": channel, "code": code }),
+            "operator.pairing",
+        ),
+        SendersCommand::Reject { channel, code } => (
+            "channels.pair.reject",
+            serde_json::json!({ "channel": channel, "code": code }),
+            "operator.pairing",
+        ),
+        SendersCommand::Revoke { channel, sender_id } => (
+            "channels.senders.revoke",
+            serde_json::json!({ "channel": channel, "senderId": sender_id }),
+            "operator.pairing",
+        ),
+    };
+    let scopes = vec![scope.to_string()];
+    let (mut ws_write, mut ws_read) =
+        connect_operator_ws(host, port, tls, trust, allow_plaintext, &scopes).await?;
+
+    let frame = serde_json::json!({
+        "type": "req",
+        "id": "senders-1",
+        "method": method,
+        "params": params
+    });
+    ws_write
+        .send(Message::Text(serde_json::to_string(&frame)?.into()))
+        .await?;
+
+    let payload = match await_ws_response(&mut ws_read, &mut ws_write, "senders-1").await {
+        Ok(payload) => payload,
+        Err(err) => {
+            eprintln!("{} failed: {}", method, err);
+            std::process::exit(1);
+        }
+    };
+
+    let field = |value: &Value, key: &str| {
+        value
+            .get(key)
+            .and_then(|v| v.as_str())
+            .unwrap_or("")
+            .to_string()
+    };
+    match action {
+        SendersCommand::List => {
+            let response: SendersListResponse = serde_json::from_value(payload)?;
+            println!("Pending pairing codes:");
+            if response.pending.is_empty() {
+                println!("  (none)");
+            }
+            for request in response.pending {
+                println!(
+                    "  {} {} {} (expires {})",
+                    request.channel,
+                    request.sender_id,
+                    request.code,
+                    format_timestamp(request.expires_at_ms)
+                );
+            }
+            println!("Approved senders:");
+            if response.approved.is_empty() {
+                println!("  (none)");
+            }
+            for sender in response.approved {
+                println!(
+                    "  {} {} (approved {})",
+                    sender.channel,
+                    sender.sender_id,
+                    format_timestamp(sender.approved_at_ms)
+                );
+            }
+        }
+        SendersCommand::Approve { .. } => println!(
+            "Approved sender {} on {}",
+            field(&payload, "senderId"),
+            field(&payload, "channel")
+        ),
+        SendersCommand::Reject { .. } => println!(
+            "Rejected pairing code from sender {} on {}",
+            field(&payload, "senderId"),
+            field(&payload, "channel")
+        ),
+        SendersCommand::Revoke { .. } => println!(
+            "Revoked sender {} on {}",
+            field(&payload, "senderId"),
+            field(&payload, "channel")
+        ),
+    }
+
+    Ok(())
+}
+
 /// Run the `version` subcommand.
 pub fn handle_version() {
     println!("cara {}", env!("CARGO_PKG_VERSION"));
@@ -1276,11 +1482,7 @@ fn resolve_state_dir() -> PathBuf {
 
 /// Resolve the memory store directory.
 fn resolve_memory_dir() -> PathBuf {
-    dirs::home_dir()
-        .unwrap_or_else(|| PathBuf::from("."))
-        .join(".config")
-        .join("carapace")
-        .join("memory")
+    resolve_state_dir().join("memory")
 }
 
 /// Name of the marker file inside backup archives to identify them as
@@ -3288,6 +3490,45 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_cli_senders_subcommands() {
+        let cli = Cli::try_parse_from(["cara", "senders", "list", "--port", "9000"]).unwrap();
+        match cli.command {
+            Some(Command::Senders {
+                action: SendersCommand::List,
+                port,
+                ..
+            }) => assert_eq!(port, Some(9000)),
+            other => panic!("Expected Senders list, got {:?}", other),
+        }
+
+        let cli =
+            Cli::try_parse_from(["cara", "senders", "approve", "telegram", "ABCD2345"]).unwrap();
+        match cli.command {
+            Some(Command::Senders {
+                action: SendersCommand::Approve { channel, code },
+                ref host,
+                ..
+            }) => {
+                assert_eq!(channel, "telegram");
+                assert_eq!(code, "ABCD2345");
+                assert_eq!(host, "127.0.0.1");
+            }
+            other => panic!("Expected Senders approve, got {:?}", other),
+        }
+
+        let cli =
+            Cli::try_parse_from(["cara", "senders", "revoke", "signal", "+15550001"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Command::Senders {
+                action: SendersCommand::Revoke { .. },
+                ..
+            })
+        ));
+        assert!(Cli::try_parse_from(["cara", "senders", "approve", "telegram"]).is_err());
+    }
+
     #[test]
     fn test_pair_url_validation_https() {
         // Valid https URL should not trigger the URL validation error.
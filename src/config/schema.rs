@@ -106,6 +106,7 @@ pub fn validate_schema(config: &Value) -> Vec<SchemaIssue> {
     validate_hooks(obj, &mut issues);
     validate_logging(obj, &mut issues);
     validate_agents(obj, &mut issues);
+    validate_agent_routes(obj, &mut issues);
     validate_session(obj, &mut issues);
     validate_cron(obj, &mut issues);
     validate_prompt_guard(obj, &mut issues);
@@ -114,6 +115,8 @@ pub fn validate_schema(config: &Value) -> Vec<SchemaIssue> {
     validate_skills_sandbox(obj, &mut issues);
     validate_session_integrity(obj, &mut issues);
     validate_usage(obj, &mut issues);
+    validate_channel_access(obj, &mut issues);
+    validate_channel_activation(obj, &mut issues);
 
     // Run agent config lint if prompt guard config lint is enabled
     if let Some(agents) = obj.get("agents") {
@@ -251,6 +254,20 @@ fn validate_gateway(obj: &serde_json::Map<String, Value>, issues: &mut Vec<Schem
                 });
             }
         }
+
+        if let Some(dir) = hooks.get("transformsDir") {
+            if !dir.is_string() {
+                issues.push(SchemaIssue {
+                    severity: Severity::Warning,
+                    path: ".gateway.hooks.transformsDir".to_string(),
+                    message: "transformsDir must be a string".to_string(),
+                });
+            }
+        }
+
+        if let Some(mappings) = hooks.get("mappings") {
+            validate_hook_mappings(mappings, issues);
+        }
     }
 
     // .gateway.controlUi sub-section
@@ -287,6 +304,48 @@ fn validate_gateway(obj: &serde_json::Map<String, Value>, issues: &mut Vec<Schem
     }
 }
 
+/// Validate `gateway.hooks.mappings`, including each mapping's transform.
+fn validate_hook_mappings(mappings: &Value, issues: &mut Vec<SchemaIssue>) {
+    let Some(mappings) = mappings.as_array() else {
+        issues.push(SchemaIssue {
+            severity: Severity::Error,
+            path: ".gateway.hooks.mappings".to_string(),
+            message: "mappings must be an array".to_string(),
+        });
+        return;
+    };
+
+    for (i, mapping) in mappings.iter().enumerate() {
+        let Some(transform) = mapping.get("transform").filter(|t| !t.is_null()) else {
+            continue;
+        };
+        let path = format!(".gateway.hooks.mappings[{}].transform", i);
+        let module = transform.get("module").and_then(|v| v.as_str());
+        let module_error = match module {
+            None => Some("transform.module must be a string".to_string()),
+            Some(module) => {
+                crate::hooks::transform::resolve_module_path(std::path::Path::new(""), module).err()
+            }
+        };
+        if let Some(message) = module_error {
+            issues.push(SchemaIssue {
+                severity: Severity::Error,
+                path: format!("{}.module", path),
+                message,
+            });
+        }
+        if let Some(export) = transform.get("export") {
+            if !export.is_string() {
+                issues.push(SchemaIssue {
+                    severity: Severity::Error,
+                    path: format!("{}.export", path),
+                    message: "transform.export must be a string".to_string(),
+                });
+            }
+        }
+    }
+}
+
 fn validate_hooks(obj: &serde_json::Map<String, Value>, issues: &mut Vec<SchemaIssue>) {
     if obj.get("hooks").is_some() {
         issues.push(SchemaIssue {
@@ -355,6 +414,69 @@ fn validate_agents(obj: &serde_json::Map<String, Value>, issues: &mut Vec<Schema
     }
 }
 
+fn validate_agent_routes(obj: &serde_json::Map<String, Value>, issues: &mut Vec<SchemaIssue>) {
+    let routes = match obj.get("agents").and_then(|a| a.get("routes")) {
+        Some(r) => r,
+        None => return,
+    };
+    let Some(routes) = routes.as_array() else {
+        issues.push(SchemaIssue {
+            severity: Severity::Warning,
+            path: ".agents.routes".to_string(),
+            message: "agents.routes must be an array".to_string(),
+        });
+        return;
+    };
+    let known: Vec<&str> = obj
+        .get("agents")
+        .and_then(|a| a.get("list"))
+        .and_then(|v| v.as_array())
+        .map(|list| {
+            list.iter()
+                .filter_map(|entry| entry.get("id").and_then(|v| v.as_str()))
+                .collect()
+        })
+        .unwrap_or_default();
+    let conditions = ["channel", "accountId", "groupId", "senderId", "prefix"];
+    for (index, route) in routes.iter().enumerate() {
+        let path = format!(".agents.routes[{}]", index);
+        match route.get("agentId").and_then(|v| v.as_str()) {
+            Some(id) if known.contains(&id.trim()) => {}
+            Some(id) => issues.push(SchemaIssue {
+                severity: Severity::Warning,
+                path: format!("{}.agentId", path),
+                message: format!(
+                    "agent \"{}\" is not in agents.list; the route is ignored",
+                    id
+                ),
+            }),
+            None => issues.push(SchemaIssue {
+                severity: Severity::Warning,
+                path: format!("{}.agentId", path),
+                message: "route needs an agentId; the route is ignored".to_string(),
+            }),
+        }
+        let has_condition = route
+            .get("match")
+            .and_then(|v| v.as_object())
+            .is_some_and(|m| {
+                conditions
+                    .iter()
+                    .any(|key| m.get(*key).is_some_and(|v| v.is_string() || v.is_number()))
+            });
+        if !has_condition {
+            issues.push(SchemaIssue {
+                severity: Severity::Warning,
+                path: format!("{}.match", path),
+                message: format!(
+                    "route match needs at least one of {}; the route is ignored",
+                    conditions.join("/")
+                ),
+            });
+        }
+    }
+}
+
 fn validate_session(obj: &serde_json::Map<String, Value>, issues: &mut Vec<SchemaIssue>) {
     let sessions = obj.get("sessions").and_then(|v| v.as_object());
     let legacy_session = obj.get("session").and_then(|v| v.as_object());
@@ -406,6 +528,13 @@ fn validate_cron(obj: &serde_json::Map<String, Value>, issues: &mut Vec<SchemaIs
         None => return,
     };
 
+    if let Some(max) = cron.get("maxJobsPerSession") {
+        check_positive_integer(max, ".cron.maxJobsPerSession", issues);
+    }
+    if let Some(min) = cron.get("minIntervalMinutes") {
+        check_positive_integer(min, ".cron.minIntervalMinutes", issues);
+    }
+
     let entries = match cron.get("entries").and_then(|v| v.as_array()) {
         Some(a) => a,
         None => return,
@@ -424,7 +553,7 @@ fn validate_cron(obj: &serde_json::Map<String, Value>, issues: &mut Vec<SchemaIs
             }
         };
 
-        // Validate schedule (basic regex: should have 5 or 6 cron fields)
+        // Validate schedule (5 or 6 cron fields, or an @ macro)
         if let Some(schedule) = entry_obj.get("schedule") {
             if let Some(s) = schedule.as_str() {
                 if !is_plausible_cron(s) {
@@ -636,6 +765,137 @@ fn validate_session_integrity(obj: &serde_json::Map<String, Value>, issues: &mut
     }
 }
 
+fn validate_channel_access(obj: &serde_json::Map<String, Value>, issues: &mut Vec<SchemaIssue>) {
+    for channel in ["telegram", "slack", "discord", "signal"] {
+        let access = match obj
+            .get("channels")
+            .and_then(|c| c.get(channel))
+            .and_then(|c| c.get("access"))
+        {
+            Some(a) => a,
+            None => continue,
+        };
+        let access = match access.as_object() {
+            Some(a) => a,
+            None => {
+                issues.push(SchemaIssue {
+                    severity: Severity::Warning,
+                    path: format!(".channels.{}.access", channel),
+                    message: "access must be an object".to_string(),
+                });
+                continue;
+            }
+        };
+
+        if let Some(mode) = access.get("mode") {
+            let valid = ["open", "allowlist", "pairing"];
+            if !mode.as_str().is_some_and(|m| valid.contains(&m)) {
+                issues.push(SchemaIssue {
+                    severity: Severity::Warning,
+                    path: format!(".channels.{}.access.mode", channel),
+                    message: format!(
+                        "mode should be one of open/allowlist/pairing, got {}; unknown modes are treated as allowlist",
+                        mode
+                    ),
+                });
+            }
+        }
+
+        if let Some(allow_from) = access.get("allowFrom") {
+            let valid = allow_from
+                .as_array()
+                .is_some_and(|ids| ids.iter().all(|id| id.is_string() || id.is_number()));
+            if !valid {
+                issues.push(SchemaIssue {
+                    severity: Severity::Warning,
+                    path: format!(".channels.{}.access.allowFrom", channel),
+                    message: "allowFrom must be an array of sender IDs".to_string(),
+                });
+            }
+        }
+    }
+}
+
+fn validate_channel_activation(
+    obj: &serde_json::Map<String, Value>,
+    issues: &mut Vec<SchemaIssue>,
+) {
+    let channels = match obj.get("channels").and_then(|v| v.as_object()) {
+        Some(c) => c,
+        None => return,
+    };
+    for (name, channel) in channels {
+        let session = match channel.get("session") {
+            Some(s) => s,
+            None => continue,
+        };
+        let base = format!(".channels.{}.session", name);
+        if let Some(activation) = session.get("activation") {
+            check_activation(activation, &format!("{}.activation", base), issues);
+        }
+        if let Some(groups) = session.get("groups").and_then(|v| v.as_object()) {
+            for (group_id, group) in groups {
+                if let Some(activation) = group.get("activation") {
+                    let path = format!("{}.groups.{}.activation", base, group_id);
+                    check_activation(activation, &path, issues);
+                }
+            }
+        }
+    }
+}
+
+fn check_activation(activation: &Value, path: &str, issues: &mut Vec<SchemaIssue>) {
+    let valid = ["always", "mention", "reply", "keyword", "prefix"];
+    let (mode, mode_path) = match activation {
+        Value::String(_) => (Some(activation), path.to_string()),
+        Value::Object(a) => (a.get("mode"), format!("{}.mode", path)),
+        _ => {
+            issues.push(SchemaIssue {
+                severity: Severity::Warning,
+                path: path.to_string(),
+                message: "activation must be a trigger name or an object".to_string(),
+            });
+            return;
+        }
+    };
+    for key in ["keywords", "prefixes"] {
+        let blank = activation
+            .get(key)
+            .and_then(|v| v.as_array())
+            .is_some_and(|items| {
+                items
+                    .iter()
+                    .any(|v| v.as_str().is_some_and(|s| s.trim().is_empty()))
+            });
+        if blank {
+            issues.push(SchemaIssue {
+                severity: Severity::Warning,
+                path: format!("{}.{}", path, key),
+                message: format!("blank {} are ignored", key),
+            });
+        }
+    }
+    let Some(mode) = mode else {
+        return;
+    };
+    let modes: Vec<&Value> = match mode {
+        Value::Array(items) => items.iter().collect(),
+        other => vec![other],
+    };
+    for m in modes {
+        if !m.as_str().is_some_and(|m| valid.contains(&m)) {
+            issues.push(SchemaIssue {
+                severity: Severity::Warning,
+                path: mode_path.clone(),
+                message: format!(
+                    "activation mode should be one of always/mention/reply/keyword/prefix, got {}; unknown triggers are ignored",
+                    m
+                ),
+            });
+        }
+    }
+}
+
 fn validate_usage(obj: &serde_json::Map<String, Value>, issues: &mut Vec<SchemaIssue>) {
     let usage = match obj.get("usage").and_then(|v| v.as_object()) {
         Some(u) => u,
@@ -814,21 +1074,10 @@ fn check_non_negative_number(value: &Value, path: &str, issues: &mut Vec<SchemaI
     }
 }
 
-/// Basic plausibility check for cron expressions.
-/// Accepts 5-field (standard) or 6-field (with seconds) expressions.
-/// Each field should be `*`, a number, or a more complex expression.
+/// Plausibility check for cron expressions: they must parse with the
+/// scheduler's own grammar (5 or 6 fields, or an `@` macro).
 fn is_plausible_cron(s: &str) -> bool {
-    let fields: Vec<&str> = s.split_whitespace().collect();
-    if !(5..=6).contains(&fields.len()) {
-        return false;
-    }
-    // Each field must be non-empty and contain only valid cron characters
-    fields.iter().all(|f| {
-        !f.is_empty()
-            && f.chars().all(|c| {
-                c.is_ascii_digit() || matches!(c, '*' | ',' | '-' | '/' | '?' | '#' | 'L' | 'W')
-            })
-    })
+    crate::cron::CronExpr::parse(s).is_ok()
 }
 
 #[cfg(test)]
@@ -1093,6 +1342,28 @@ mod tests {
         assert!(issues.iter().any(|i| i.path.contains("payload")));
     }
 
+    #[test]
+    fn test_cron_max_jobs_per_session() {
+        let cfg = json!({ "cron": { "maxJobsPerSession": 5 } });
+        let issues = validate_schema(&cfg);
+        assert!(!issues.iter().any(|i| i.path.starts_with(".cron")));
+
+        let cfg = json!({ "cron": { "maxJobsPerSession": 0 } });
+        let issues = validate_schema(&cfg);
+        assert!(issues.iter().any(|i| i.path == ".cron.maxJobsPerSession"));
+    }
+
+    #[test]
+    fn test_cron_min_interval_minutes() {
+        let cfg = json!({ "cron": { "minIntervalMinutes": 15 } });
+        let issues = validate_schema(&cfg);
+        assert!(!issues.iter().any(|i| i.path.starts_with(".cron")));
+
+        let cfg = json!({ "cron": { "minIntervalMinutes": 0 } });
+        let issues = validate_schema(&cfg);
+        assert!(issues.iter().any(|i| i.path == ".cron.minIntervalMinutes"));
+    }
+
     #[test]
     fn test_cron_entry_not_object() {
         let cfg = json!({ "cron": { "entries": ["bad"] } });
@@ -1100,6 +1371,92 @@ mod tests {
         assert!(issues.iter().any(|i| i.path.contains("entries[0]")));
     }
 
+    // --- channel access ---
+
+    #[test]
+    fn test_channel_access_valid() {
+        let cfg = json!({
+            "channels": {
+                "telegram": { "access": { "mode": "pairing", "allowFrom": ["123", 456] } },
+                "slack": { "access": { "mode": "open" } }
+            }
+        });
+        let issues = validate_schema(&cfg);
+        assert!(!issues.iter().any(|i| i.path.contains(".access")));
+    }
+
+    #[test]
+    fn test_channel_access_invalid() {
+        let cfg = json!({
+            "channels": {
+                "discord": { "access": { "mode": "closed", "allowFrom": "u1" } },
+                "signal": { "access": true }
+            }
+        });
+        let issues = validate_schema(&cfg);
+        assert!(issues
+            .iter()
+            .any(|i| i.path == ".channels.discord.access.mode"));
+        assert!(issues
+            .iter()
+            .any(|i| i.path == ".channels.discord.access.allowFrom"));
+        assert!(issues.iter().any(|i| i.path == ".channels.signal.access"));
+    }
+
+    #[test]
+    fn test_channel_activation_validation() {
+        let cfg = json!({
+            "channels": {
+                "telegram": { "session": { "activation": { "mode": ["mention", "reply"] } } },
+                "discord": {
+                    "session": {
+                        "activation": "shout",
+                        "groups": { "c1": { "activation": { "mode": ["prefix", 3] } } }
+                    }
+                },
+                "slack": {
+                    "session": { "activation": { "mode": "prefix", "prefixes": ["/ask", ""] } }
+                }
+            }
+        });
+        let issues = validate_schema(&cfg);
+        assert!(!issues
+            .iter()
+            .any(|i| i.path.starts_with(".channels.telegram")));
+        assert!(issues
+            .iter()
+            .any(|i| i.path == ".channels.discord.session.activation"));
+        assert!(issues
+            .iter()
+            .any(|i| i.path == ".channels.discord.session.groups.c1.activation.mode"));
+        assert!(issues
+            .iter()
+            .any(|i| i.path == ".channels.slack.session.activation.prefixes"));
+    }
+
+    #[test]
+    fn test_agent_routes_validation() {
+        let cfg = json!({
+            "agents": {
+                "list": [{ "id": "main" }, { "id": "support" }],
+                "routes": [
+                    { "agentId": "support", "match": { "channel": "telegram" } },
+                    { "agentId": "ghost", "match": { "prefix": "/ghost" } },
+                    { "agentId": "main", "match": {} }
+                ]
+            }
+        });
+        let issues = validate_schema(&cfg);
+        assert!(!issues
+            .iter()
+            .any(|i| i.path.starts_with(".agents.routes[0]")));
+        assert!(issues.iter().any(|i| i.path == ".agents.routes[1].agentId"));
+        assert!(issues.iter().any(|i| i.path == ".agents.routes[2].match"));
+
+        let issues = validate_schema(&json!({ "agents": { "routes": {} } }));
+        assert!(issues.iter().any(|i| i.path == ".agents.routes"));
+    }
+
     // --- unknown keys ---
 
     #[test]
@@ -1138,6 +1495,37 @@ mod tests {
         assert!(issues.iter().any(|i| i.path.contains("messageRate")));
     }
 
+    #[test]
+    fn test_hook_mapping_transforms_validated() {
+        let cfg = json!({
+            "gateway": { "hooks": { "mappings": [
+                { "id": "github", "transform": { "module": "github.wasm" } },
+                { "id": "escape", "transform": { "module": "../x.wasm" } },
+                { "id": "missing", "transform": { "export": 1 } },
+                { "id": "plain", "messageTemplate": "{{text}}" }
+            ] } }
+        });
+        let issues = validate_schema(&cfg);
+        let paths: Vec<&str> = issues
+            .iter()
+            .filter(|i| i.severity == Severity::Error)
+            .map(|i| i.path.as_str())
+            .collect();
+        assert_eq!(
+            paths,
+            [
+                ".gateway.hooks.mappings[1].transform.module",
+                ".gateway.hooks.mappings[2].transform.module",
+                ".gateway.hooks.mappings[2].transform.export",
+            ]
+        );
+
+        let cfg = json!({ "gateway": { "hooks": { "mappings": {} } } });
+        assert!(validate_schema(&cfg)
+            .iter()
+            .any(|i| i.path == ".gateway.hooks.mappings" && i.severity == Severity::Error));
+    }
+
     // --- is_plausible_cron ---
 
     #[test]
@@ -1148,6 +1536,11 @@ mod tests {
         assert!(is_plausible_cron("0 0 1 1 *"));
         assert!(is_plausible_cron("0 0 * * 1-5"));
         assert!(is_plausible_cron("0 0 0 * * *")); // 6-field
+        assert!(is_plausible_cron("@daily"));
+        assert!(is_plausible_cron("0 17 * JAN-JUN 5L"));
+        assert!(is_plausible_cron("0 0 LW * ?"));
+        assert!(!is_plausible_cron("0 0 32 * *")); // day out of range
+        assert!(!is_plausible_cron("@sometimes"));
         assert!(!is_plausible_cron("not a cron"));
         assert!(!is_plausible_cron("* * *")); // too few fields
         assert!(!is_plausible_cron(""));
@@ -267,6 +267,13 @@ pub async fn write_device_identity(state_dir: PathBuf, value: &str) -> Result<()
     store.set(&key, value, None).await
 }
 
+/// Open the credential store backed by the platform's native keychain.
+pub async fn open_default_store(
+    state_dir: PathBuf,
+) -> Result<CredentialStore<impl CredentialBackend + 'static>, CredentialError> {
+    CredentialStore::new(default_backend(), state_dir).await
+}
+
 #[cfg(target_os = "macos")]
 fn default_backend() -> macos::MacOsCredentialBackend {
     macos::MacOsCredentialBackend::new()
@@ -2,10 +2,16 @@
 //!
 //! Executes the payload of a cron job after `run()` marks it as started.
 //! Supports `SystemEvent` (broadcast) and `AgentTurn` (spawn agent run).
+//!
+//! `AgentTurn` jobs targeting `main` run in the agent's main session, or
+//! in the session that scheduled them for agent-created jobs.
+//! `isolated` jobs run in a `cron:{job_id}` session of their own and, once
+//! the run completes, post a summary or the full output to the main session
+//! as set by the job's [`CronIsolation`].
 
 use std::sync::Arc;
 
-use crate::cron::CronPayload;
+use crate::cron::{CronIsolation, CronJob, CronPayload, CronSessionTarget};
 use crate::messages::outbound::{
     MessageContent, MessageMetadata, OutboundContext, OutboundMessage,
 };
@@ -14,6 +20,15 @@ use serde_json::Value;
 use std::time::Duration;
 use tokio_util::sync::CancellationToken;
 
+/// Prefix for output posted to the main session when the job sets none.
+pub const DEFAULT_POST_TO_MAIN_PREFIX: &str = "[Cron]";
+
+/// Characters kept of a `summary` post: the output's first paragraph.
+pub const POST_TO_MAIN_SUMMARY_CHARS: usize = 500;
+
+/// Default cap on a `full` post.
+pub const DEFAULT_POST_TO_MAIN_MAX_CHARS: usize = 8000;
+
 /// Outcome of executing a cron payload.
 #[derive(Debug)]
 pub enum CronRunOutcome {
@@ -23,16 +38,108 @@ pub enum CronRunOutcome {
     Spawned { run_id: String },
 }
 
+/// Key of an agent's main session: `session.mainKey` (default `main`),
+/// prefixed with the agent ID for agents other than the default one.
+pub fn main_session_key(cfg: &Value, agent_id: Option<&str>) -> String {
+    let main_key = cfg
+        .get("session")
+        .and_then(|s| s.get("mainKey"))
+        .and_then(|v| v.as_str())
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .unwrap_or("main");
+    match agent_id {
+        Some(agent_id) if crate::agent::routing::default_agent_id(cfg) != Some(agent_id) => {
+            crate::agent::routing::agent_session_key(agent_id, main_key)
+        }
+        _ => main_key.to_string(),
+    }
+}
+
+/// Text an isolated job posts to the main session for a completed run, or
+/// `None` when there is nothing to post.
+///
+/// `postToMainMode` `full` posts the output up to `postToMainMaxChars`;
+/// anything else posts a summary, the output's first paragraph. Either is
+/// prefixed with `postToMainPrefix` and the job name.
+pub fn post_to_main_text(job: &CronJob, output: &str) -> Option<String> {
+    let output = output.trim();
+    if output.is_empty() {
+        return None;
+    }
+    let isolation = job.isolation.clone().unwrap_or_default();
+    let body = match isolation.post_to_main_mode.as_deref() {
+        Some("full") => {
+            let max_chars = isolation
+                .post_to_main_max_chars
+                .map(|n| n as usize)
+                .filter(|n| *n > 0)
+                .unwrap_or(DEFAULT_POST_TO_MAIN_MAX_CHARS);
+            truncate_chars(output, max_chars)
+        }
+        _ => {
+            let paragraph = output.split("\n\n").next().unwrap_or(output).trim();
+            truncate_chars(paragraph, POST_TO_MAIN_SUMMARY_CHARS)
+        }
+    };
+    let prefix = isolation_prefix(&isolation);
+    Some(format!("{} {}: {}", prefix, job.name, body))
+}
+
+fn isolation_prefix(isolation: &CronIsolation) -> &str {
+    isolation
+        .post_to_main_prefix
+        .as_deref()
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .unwrap_or(DEFAULT_POST_TO_MAIN_PREFIX)
+}
+
+fn truncate_chars(text: &str, max_chars: usize) -> String {
+    match text.char_indices().nth(max_chars) {
+        Some((end, _)) => format!("{}…", &text[..end]),
+        None => text.to_string(),
+    }
+}
+
+/// Append an isolated job's output to the main session as an assistant
+/// message tagged with the job and the isolated session it came from.
+pub fn post_to_main(
+    state: &WsServerState,
+    job: &CronJob,
+    main_key: &str,
+    text: &str,
+) -> Result<(), String> {
+    let metadata = crate::sessions::SessionMetadata {
+        agent_id: job.agent_id.clone(),
+        ..Default::default()
+    };
+    let session = state
+        .session_store()
+        .get_or_create_session(main_key, metadata)
+        .map_err(|e| format!("failed to load main session: {}", e))?;
+    let message = crate::sessions::ChatMessage::assistant(&session.id, text).with_metadata(
+        serde_json::json!({
+            "cron": { "jobId": job.id, "sessionKey": format!("cron:{}", job.id) }
+        }),
+    );
+    state
+        .session_store()
+        .append_message(message)
+        .map_err(|e| format!("failed to post to main session: {}", e))
+}
+
 /// Execute a cron job payload.
 ///
 /// For `SystemEvent`: enqueues the event into system event history.
-/// For `AgentTurn`: creates a session, registers an agent run, and spawns execution.
+/// For `AgentTurn`: creates or reuses the session the job's `sessionTarget`
+/// selects, registers an agent run as the job's agent, and spawns execution.
 pub async fn execute_payload(
-    job_id: &str,
-    payload: &CronPayload,
+    job: &CronJob,
     state: &Arc<WsServerState>,
 ) -> Result<CronRunOutcome, String> {
-    match payload {
+    let job_id = job.id.as_str();
+    match &job.payload {
         CronPayload::SystemEvent { text } => {
             let now = crate::cron::now_ms();
             state.enqueue_system_event(SystemEvent {
@@ -57,7 +164,18 @@ pub async fn execute_payload(
             to,
             best_effort_deliver,
         } => {
-            let session_key = format!("cron:{}", job_id);
+            let cfg = crate::config::load_config().unwrap_or(Value::Object(serde_json::Map::new()));
+            let agent_id = job.agent_id.as_deref();
+            let main_key = match &job.created_by {
+                Some(creator) => creator.session_key().to_string(),
+                None => main_session_key(&cfg, agent_id),
+            };
+            let isolated = job.session_target == CronSessionTarget::Isolated;
+            let session_key = if isolated {
+                format!("cron:{}", job_id)
+            } else {
+                main_key.clone()
+            };
             let run_id = uuid::Uuid::new_v4().to_string();
 
             let normalized_channel = channel
@@ -71,24 +189,32 @@ pub async fn execute_payload(
                 .filter(|s| !s.is_empty())
                 .map(|s| s.to_string());
 
-            let mut metadata = crate::sessions::SessionMetadata::default();
-            if let Some(ref value) = normalized_channel {
-                metadata.channel = Some(value.clone());
-            }
-            if let Some(ref value) = normalized_to {
-                metadata.chat_id = Some(value.clone());
-            }
-            if let Some(ref value) = thinking {
-                metadata.thinking_level = Some(value.clone());
-            }
-            if let Some(ref value) = model {
-                metadata.model = Some(value.clone());
+            let mut metadata = crate::sessions::SessionMetadata {
+                agent_id: job.agent_id.clone(),
+                ..Default::default()
+            };
+            // The job's delivery and model settings are kept on its own
+            // session; the main session's settings belong to the user.
+            if isolated {
+                if let Some(ref value) = normalized_channel {
+                    metadata.channel = Some(value.clone());
+                }
+                if let Some(ref value) = normalized_to {
+                    metadata.chat_id = Some(value.clone());
+                }
+                if let Some(ref value) = thinking {
+                    metadata.thinking_level = Some(value.clone());
+                }
+                if let Some(ref value) = model {
+                    metadata.model = Some(value.clone());
+                }
             }
 
-            let has_metadata_updates = normalized_channel.is_some()
-                || normalized_to.is_some()
-                || thinking.is_some()
-                || model.is_some();
+            let has_metadata_updates = isolated
+                && (normalized_channel.is_some()
+                    || normalized_to.is_some()
+                    || thinking.is_some()
+                    || model.is_some());
 
             // Ensure session exists
             let session = match state.session_store().get_session_by_key(&session_key) {
@@ -119,18 +245,30 @@ pub async fn execute_payload(
                 .map_err(|e| format!("failed to append message: {}", e))?;
 
             // Build agent config
-            let cfg = crate::config::load_config().unwrap_or(Value::Object(serde_json::Map::new()));
             let mut config = crate::agent::AgentConfig::default();
-            crate::agent::apply_agent_config_from_settings(&mut config, &cfg, None);
-            config.model = model
-                .clone()
-                .unwrap_or_else(|| crate::agent::DEFAULT_MODEL.to_string());
+            crate::agent::apply_agent_config_from_settings(&mut config, &cfg, agent_id);
+            if let Some(model) = model {
+                config.model = model.clone();
+            }
+            // Isolated sessions carry the job's thinking level; in the main
+            // session it applies unless the session sets its own.
+            if let Some(level) = thinking.as_deref().filter(|_| !isolated) {
+                match crate::agent::provider::ThinkingLevel::parse(level) {
+                    Ok(level) => config.thinking = level,
+                    Err(e) => {
+                        tracing::warn!(job_id = %job_id, error = %e, "ignoring cron thinking level")
+                    }
+                }
+            }
             if let Some(&allow) = allow_unsafe_external_content.as_ref() {
                 config.exfiltration_guard = !allow;
             }
             if let Some(&deliver) = deliver.as_ref() {
-                // Delivery for cron runs is handled via a completion waiter below.
-                config.deliver = deliver;
+                // An explicit channel and recipient are delivered to by the
+                // completion waiter below; otherwise the run delivers to the
+                // session's own channel.
+                config.deliver =
+                    deliver && (normalized_channel.is_none() || normalized_to.is_none());
             }
 
             // Register the agent run
@@ -187,6 +325,35 @@ pub async fn execute_payload(
                 .llm_provider()
                 .ok_or_else(|| "no LLM provider configured".to_string())?;
 
+            if isolated {
+                let waiter = {
+                    let mut registry = state.agent_run_registry.lock();
+                    registry.add_waiter(&run_id)
+                };
+                if let Some(waiter) = waiter {
+                    let state = state.clone();
+                    let job = job.clone();
+                    tokio::spawn(async move {
+                        let Ok(result) = waiter.await else {
+                            return;
+                        };
+                        if result.status != crate::server::ws::AgentRunStatus::Completed {
+                            return;
+                        }
+                        let Some(text) = result
+                            .response
+                            .as_deref()
+                            .and_then(|output| post_to_main_text(&job, output))
+                        else {
+                            return;
+                        };
+                        if let Err(err) = post_to_main(&state, &job, &main_key, &text) {
+                            tracing::warn!(job_id = %job.id, error = %err, "cron post to main failed");
+                        }
+                    });
+                }
+            }
+
             if deliver.unwrap_or(false) {
                 match (normalized_channel.clone(), normalized_to.clone()) {
                     (Some(channel_id), Some(recipient_id)) => {
@@ -242,16 +409,10 @@ pub async fn execute_payload(
                             });
                         }
                     }
-                    (None, _) => {
-                        tracing::warn!(
+                    _ => {
+                        tracing::debug!(
                             job_id = %job_id,
-                            "cron delivery requested without channel; skipping"
-                        );
-                    }
-                    (_, None) => {
-                        tracing::warn!(
-                            job_id = %job_id,
-                            "cron delivery requested without recipient; skipping"
+                            "cron delivery without explicit channel and recipient; using the session's"
                         );
                     }
                 }
@@ -275,9 +436,10 @@ pub async fn execute_payload(
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::cron::CronPayload;
+    use crate::cron::{CronJobState, CronPayload, CronSchedule, CronWakeMode};
     use crate::server::ws::{WsServerConfig, WsServerState};
     use crate::sessions;
+    use serde_json::json;
     use std::sync::Arc;
 
     /// Create a WsServerState backed by a temp directory so tests work on all
@@ -291,6 +453,44 @@ mod tests {
         (Arc::new(state), tmp)
     }
 
+    fn make_job(id: &str, session_target: CronSessionTarget, payload: CronPayload) -> CronJob {
+        CronJob {
+            id: id.to_string(),
+            agent_id: None,
+            name: "Daily report".to_string(),
+            description: None,
+            enabled: true,
+            delete_after_run: None,
+            created_at_ms: 0,
+            updated_at_ms: 0,
+            schedule: CronSchedule::Every {
+                every_ms: 60_000,
+                anchor_ms: None,
+            },
+            session_target,
+            wake_mode: CronWakeMode::Now,
+            payload,
+            isolation: None,
+            created_by: None,
+            policy: Default::default(),
+            state: CronJobState::default(),
+        }
+    }
+
+    fn agent_turn(message: &str) -> CronPayload {
+        CronPayload::AgentTurn {
+            message: message.to_string(),
+            model: None,
+            thinking: None,
+            timeout_seconds: None,
+            allow_unsafe_external_content: None,
+            deliver: None,
+            channel: None,
+            to: None,
+            best_effort_deliver: None,
+        }
+    }
+
     #[tokio::test]
     async fn test_execute_system_event() {
         let (state, _tmp) = make_test_state();
@@ -299,7 +499,8 @@ mod tests {
             text: "test cron event".to_string(),
         };
 
-        let result = execute_payload("job-1", &payload, &state).await;
+        let job = make_job("job-1", CronSessionTarget::Main, payload);
+        let result = execute_payload(&job, &state).await;
         assert!(result.is_ok());
         assert!(matches!(result.unwrap(), CronRunOutcome::Broadcast));
     }
@@ -309,19 +510,12 @@ mod tests {
         // Without an LLM provider, agent turn should fail
         let (state, _tmp) = make_test_state();
 
-        let payload = CronPayload::AgentTurn {
-            message: "do something".to_string(),
-            model: None,
-            thinking: None,
-            timeout_seconds: None,
-            allow_unsafe_external_content: None,
-            deliver: None,
-            channel: None,
-            to: None,
-            best_effort_deliver: None,
-        };
-
-        let result = execute_payload("job-2", &payload, &state).await;
+        let job = make_job(
+            "job-2",
+            CronSessionTarget::Isolated,
+            agent_turn("do something"),
+        );
+        let result = execute_payload(&job, &state).await;
         assert!(result.is_err());
         assert!(result.unwrap_err().contains("no LLM provider"));
     }
@@ -342,7 +536,8 @@ mod tests {
             best_effort_deliver: Some(true),
         };
 
-        let result = execute_payload("job-meta", &payload, &state).await;
+        let job = make_job("job-meta", CronSessionTarget::Isolated, payload);
+        let result = execute_payload(&job, &state).await;
         assert!(result.is_err());
 
         let session = state
@@ -383,7 +578,8 @@ mod tests {
             best_effort_deliver: Some(true),
         };
 
-        let result = execute_payload("job-update", &payload, &state).await;
+        let job = make_job("job-update", CronSessionTarget::Isolated, payload);
+        let result = execute_payload(&job, &state).await;
         assert!(result.is_err());
 
         let session = state
@@ -395,4 +591,140 @@ mod tests {
         assert_eq!(session.metadata.thinking_level, Some("deep".to_string()));
         assert_eq!(session.metadata.model, Some("new-model".to_string()));
     }
+
+    #[tokio::test]
+    async fn test_main_target_runs_in_main_session() {
+        let (state, _tmp) = make_test_state();
+
+        let payload = CronPayload::AgentTurn {
+            message: "check the inbox".to_string(),
+            model: Some("model-x".to_string()),
+            thinking: None,
+            timeout_seconds: None,
+            allow_unsafe_external_content: None,
+            deliver: None,
+            channel: None,
+            to: None,
+            best_effort_deliver: None,
+        };
+        let job = make_job("job-main", CronSessionTarget::Main, payload);
+        let result = execute_payload(&job, &state).await;
+        assert!(result.unwrap_err().contains("no LLM provider"));
+
+        let session = state.session_store().get_session_by_key("main").unwrap();
+        let history = state
+            .session_store()
+            .get_history(&session.id, None, None)
+            .unwrap();
+        assert_eq!(history.last().unwrap().content, "check the inbox");
+        // The job's model applies to the run, not to the user's main session
+        assert_eq!(session.metadata.model, None);
+        assert!(state
+            .session_store()
+            .get_session_by_key("cron:job-main")
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_agent_created_job_runs_in_creating_session() {
+        let (state, _tmp) = make_test_state();
+
+        let mut job = make_job(
+            "job-agent",
+            CronSessionTarget::Main,
+            agent_turn("water the plants"),
+        );
+        job.created_by = Some(crate::cron::CronJobCreator::Agent {
+            session_key: "telegram:dm:42".to_string(),
+        });
+        let result = execute_payload(&job, &state).await;
+        assert!(result.unwrap_err().contains("no LLM provider"));
+
+        let session = state
+            .session_store()
+            .get_session_by_key("telegram:dm:42")
+            .unwrap();
+        let history = state
+            .session_store()
+            .get_history(&session.id, None, None)
+            .unwrap();
+        assert_eq!(history.last().unwrap().content, "water the plants");
+        assert!(state.session_store().get_session_by_key("main").is_err());
+    }
+
+    #[test]
+    fn test_main_session_key() {
+        assert_eq!(main_session_key(&json!({}), None), "main");
+        assert_eq!(main_session_key(&json!({}), Some("ops")), "agent:ops:main");
+
+        let cfg = json!({
+            "session": { "mainKey": "home" },
+            "agents": { "list": [{ "id": "main", "default": true }, { "id": "ops" }] }
+        });
+        assert_eq!(main_session_key(&cfg, None), "home");
+        assert_eq!(main_session_key(&cfg, Some("main")), "home");
+        assert_eq!(main_session_key(&cfg, Some("ops")), "agent:ops:home");
+    }
+
+    #[test]
+    fn test_post_to_main_text_modes() {
+        let output = "All builds green.\n\nDetails: 42 jobs ran, 0 failed.";
+        let mut job = make_job(
+            "job-post",
+            CronSessionTarget::Isolated,
+            agent_turn("report"),
+        );
+
+        // Summary by default: the first paragraph
+        assert_eq!(
+            post_to_main_text(&job, output).as_deref(),
+            Some("[Cron] Daily report: All builds green.")
+        );
+        assert_eq!(post_to_main_text(&job, "   "), None);
+
+        job.isolation = Some(CronIsolation {
+            post_to_main_prefix: Some("[Nightly]".to_string()),
+            post_to_main_mode: Some("full".to_string()),
+            post_to_main_max_chars: None,
+        });
+        assert_eq!(
+            post_to_main_text(&job, output).as_deref(),
+            Some("[Nightly] Daily report: All builds green.\n\nDetails: 42 jobs ran, 0 failed.")
+        );
+
+        job.isolation.as_mut().unwrap().post_to_main_max_chars = Some(10);
+        assert_eq!(
+            post_to_main_text(&job, output).as_deref(),
+            Some("[Nightly] Daily report: All builds…")
+        );
+
+        let long = "é".repeat(POST_TO_MAIN_SUMMARY_CHARS + 10);
+        job.isolation = None;
+        let text = post_to_main_text(&job, &long).unwrap();
+        assert!(text.ends_with('…'));
+        assert_eq!(
+            text.chars().count(),
+            "[Cron] Daily report: ".len() + POST_TO_MAIN_SUMMARY_CHARS + 1
+        );
+    }
+
+    #[test]
+    fn test_post_to_main_appends_to_main_session() {
+        let (state, _tmp) = make_test_state();
+        let job = make_job("job-iso", CronSessionTarget::Isolated, agent_turn("report"));
+
+        post_to_main(&state, &job, "main", "[Cron] Daily report: done").unwrap();
+
+        let session = state.session_store().get_session_by_key("main").unwrap();
+        let history = state
+            .session_store()
+            .get_history(&session.id, None, None)
+            .unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].role, sessions::MessageRole::Assistant);
+        assert_eq!(history[0].content, "[Cron] Daily report: done");
+        let metadata = history[0].metadata.as_ref().unwrap();
+        assert_eq!(metadata["cron"]["jobId"], "job-iso");
+        assert_eq!(metadata["cron"]["sessionKey"], "cron:job-iso");
+    }
 }
@@ -6,13 +6,18 @@
 //! - Cron expression jobs (run on cron schedule with optional timezone)
 //!
 //! Jobs can execute either in the main session or in isolated sessions,
-//! and can deliver messages to various channels.
+//! and can deliver messages to various channels. Jobs with
+//! `wakeMode: "next-heartbeat"` are held until the next heartbeat.
+//!
+//! Each job's [`CronRunPolicy`] decides what happens to firings missed
+//! while the gateway was down, to firings that overlap a running run, and
+//! to failed runs, and adds jitter to run times.
 
 pub mod executor;
 pub mod tick;
 
 use chrono::{Datelike, Offset, TimeZone, Timelike, Utc};
-use parking_lot::RwLock;
+use parking_lot::{Mutex, RwLock};
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeSet;
 use std::fs::{self, File};
@@ -125,6 +130,176 @@ pub struct CronIsolation {
     pub post_to_main_max_chars: Option<u32>,
 }
 
+/// What to do about firings missed while the gateway was down.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CronCatchUpMode {
+    /// Drop missed firings and wait for the next scheduled one.
+    #[default]
+    Skip,
+    /// Run once on startup for any number of missed firings.
+    RunOnce,
+    /// Run once per missed firing, back to back.
+    #[serde(rename = "run-all-missed")]
+    RunAllMissed,
+}
+
+/// Catch-up policy for missed firings.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CronCatchUp {
+    /// How missed firings are handled.
+    #[serde(default)]
+    pub mode: CronCatchUpMode,
+    /// Firings older than this are dropped whatever the mode.
+    #[serde(rename = "maxLagMs", skip_serializing_if = "Option::is_none")]
+    pub max_lag_ms: Option<u64>,
+}
+
+/// What to do when a job comes due while a previous run is still going.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CronConcurrency {
+    /// Skip the firing.
+    #[default]
+    SkipIfRunning,
+    /// Run the firing once the previous run finishes.
+    Queue,
+    /// Start another run alongside the previous one.
+    Allow,
+}
+
+/// Per-job run policies: catch-up, jitter, overlap and retries.
+///
+/// Flattened into the job, so each policy is a top-level job key.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CronRunPolicy {
+    /// Handling of firings missed while the gateway was down.
+    #[serde(rename = "catchUp", default, skip_serializing_if = "Option::is_none")]
+    pub catch_up: Option<CronCatchUp>,
+    /// Random delay of up to this many milliseconds added to each run time.
+    #[serde(rename = "jitterMs", default, skip_serializing_if = "Option::is_none")]
+    pub jitter_ms: Option<u64>,
+    /// Handling of firings that overlap a running run.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub concurrency: Option<CronConcurrency>,
+    /// Retries after a failed run before waiting for the next firing.
+    #[serde(
+        rename = "maxRetries",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub max_retries: Option<u32>,
+    /// Delay before the first retry; doubles with each further retry.
+    #[serde(
+        rename = "retryBackoffMs",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub retry_backoff_ms: Option<u64>,
+}
+
+impl CronRunPolicy {
+    /// Overwrite the policies set in `patch`.
+    fn apply(&mut self, patch: CronRunPolicy) {
+        if patch.catch_up.is_some() {
+            self.catch_up = patch.catch_up;
+        }
+        if patch.jitter_ms.is_some() {
+            self.jitter_ms = patch.jitter_ms;
+        }
+        if patch.concurrency.is_some() {
+            self.concurrency = patch.concurrency;
+        }
+        if patch.max_retries.is_some() {
+            self.max_retries = patch.max_retries;
+        }
+        if patch.retry_backoff_ms.is_some() {
+            self.retry_backoff_ms = patch.retry_backoff_ms;
+        }
+    }
+
+    /// Delay before retry number `attempt` (1-based).
+    fn retry_delay_ms(&self, attempt: u32) -> u64 {
+        let base = self
+            .retry_backoff_ms
+            .unwrap_or(DEFAULT_RETRY_BACKOFF_MS)
+            .max(1);
+        base.saturating_mul(1u64 << attempt.saturating_sub(1).min(20))
+            .min(MAX_RETRY_BACKOFF_MS)
+    }
+
+    /// A random delay within the job's jitter window.
+    fn jitter(&self) -> u64 {
+        match self.jitter_ms {
+            Some(max) if max > 0 => getrandom::u64().unwrap_or(0) % (max + 1),
+            _ => 0,
+        }
+    }
+}
+
+/// Default delay before the first retry of a failed run.
+pub const DEFAULT_RETRY_BACKOFF_MS: u64 = 30_000;
+
+/// Longest delay between retries.
+pub const MAX_RETRY_BACKOFF_MS: u64 = 3_600_000;
+
+/// Most missed firings a `run-all-missed` job replays, and most firings a
+/// `queue` job holds.
+pub const MAX_PENDING_RUNS: u32 = 10;
+
+/// Why a run started.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CronRunTrigger {
+    /// The job came due.
+    Schedule,
+    /// Started through `cron.run`.
+    Manual,
+    /// Replaying a firing missed while the gateway was down.
+    CatchUp,
+    /// Retrying a failed run.
+    Retry,
+    /// A firing queued behind a previous run.
+    Queued,
+}
+
+/// Why a firing did not start a run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CronSkipReason {
+    /// It fell due while the gateway was down.
+    Missed,
+    /// A previous run was still going.
+    Overlap,
+}
+
+/// Who created a job.
+///
+/// Absent for jobs added by operators through `cron.add`; set for jobs an
+/// agent scheduled from a conversation with the scheduling tools.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum CronJobCreator {
+    /// Created by an agent on behalf of a session.
+    #[serde(rename = "agent")]
+    Agent {
+        /// The session the job was scheduled from.
+        #[serde(rename = "sessionKey")]
+        session_key: String,
+    },
+}
+
+impl CronJobCreator {
+    /// The session key of an agent-created job.
+    pub fn session_key(&self) -> &str {
+        match self {
+            CronJobCreator::Agent { session_key } => session_key,
+        }
+    }
+}
+
 /// Runtime state of a cron job.
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -147,6 +322,29 @@ pub struct CronJobState {
     /// Duration of last run in milliseconds.
     #[serde(rename = "lastDurationMs", skip_serializing_if = "Option::is_none")]
     pub last_duration_ms: Option<u64>,
+    /// Why the current or last run started.
+    #[serde(
+        rename = "lastTrigger",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub last_trigger: Option<CronRunTrigger>,
+    /// Runs in progress (more than one only with `concurrency: allow`).
+    #[serde(rename = "activeRuns", default, skip_serializing_if = "is_zero")]
+    pub active_runs: u32,
+    /// Firings waiting for the running run (`concurrency: queue`).
+    #[serde(rename = "queuedRuns", default, skip_serializing_if = "is_zero")]
+    pub queued_runs: u32,
+    /// Missed firings still to replay.
+    #[serde(rename = "catchUpRuns", default, skip_serializing_if = "is_zero")]
+    pub catch_up_runs: u32,
+    /// Failed runs retried since the last success.
+    #[serde(rename = "retryCount", default, skip_serializing_if = "is_zero")]
+    pub retry_count: u32,
+}
+
+fn is_zero(n: &u32) -> bool {
+    *n == 0
 }
 
 /// Status of a job run.
@@ -196,6 +394,12 @@ pub struct CronJob {
     /// Isolation settings (for isolated sessions).
     #[serde(skip_serializing_if = "Option::is_none")]
     pub isolation: Option<CronIsolation>,
+    /// Who created the job, when it was not added through `cron.add`.
+    #[serde(rename = "createdBy", default, skip_serializing_if = "Option::is_none")]
+    pub created_by: Option<CronJobCreator>,
+    /// Catch-up, jitter, overlap and retry policies.
+    #[serde(flatten)]
+    pub policy: CronRunPolicy,
     /// Runtime state.
     pub state: CronJobState,
 }
@@ -219,6 +423,8 @@ pub struct CronJobCreate {
     pub wake_mode: CronWakeMode,
     pub payload: CronPayload,
     pub isolation: Option<CronIsolation>,
+    #[serde(flatten, default)]
+    pub policy: CronRunPolicy,
 }
 
 fn default_enabled() -> bool {
@@ -243,6 +449,9 @@ pub struct CronJobPatch {
     pub wake_mode: Option<CronWakeMode>,
     pub payload: Option<CronPayload>,
     pub isolation: Option<CronIsolation>,
+    /// Policies to overwrite; unset ones are kept.
+    #[serde(flatten, default)]
+    pub policy: CronRunPolicy,
 }
 
 /// A log entry for a job run.
@@ -254,8 +463,14 @@ pub struct CronRunLogEntry {
     /// The job ID.
     #[serde(rename = "jobId")]
     pub job_id: String,
-    /// The action type (always "finished" for now).
+    /// The action type: "finished", "skipped" or "queued".
     pub action: String,
+    /// Why the run started.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trigger: Option<CronRunTrigger>,
+    /// Why the firing was skipped or queued.
+    #[serde(rename = "skipReason", skip_serializing_if = "Option::is_none")]
+    pub skip_reason: Option<CronSkipReason>,
     /// Status of the run.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub status: Option<CronJobStatus>,
@@ -318,6 +533,8 @@ pub struct CronScheduler {
     persist_path: Option<PathBuf>,
     /// Whether we have already ensured the persist directory exists.
     dir_ensured: AtomicBool,
+    /// Started `next-heartbeat` jobs waiting for the heartbeat loop.
+    deferred: Mutex<Vec<String>>,
 }
 
 impl CronScheduler {
@@ -334,6 +551,7 @@ impl CronScheduler {
             event_tx: None,
             persist_path,
             dir_ensured: AtomicBool::new(false),
+            deferred: Mutex::new(Vec::new()),
         }
     }
 
@@ -346,6 +564,7 @@ impl CronScheduler {
             event_tx: None,
             persist_path: None,
             dir_ensured: AtomicBool::new(false),
+            deferred: Mutex::new(Vec::new()),
         }
     }
 
@@ -359,7 +578,8 @@ impl CronScheduler {
     ///
     /// If `persist_path` is `None` or the file does not exist, this is a no-op.
     /// Stale runtime state (`running_at_ms`) is cleared and `next_run_at_ms` is
-    /// recomputed for enabled jobs. Errors are logged but never propagated —
+    /// recomputed for enabled jobs; enabled `@reboot` jobs become due now.
+    /// Errors are logged but never propagated —
     /// the scheduler starts empty on failure.
     pub fn load(&self) {
         let path = match &self.persist_path {
@@ -385,20 +605,68 @@ impl CronScheduler {
         };
 
         let now = now_ms();
+        let mut skipped = Vec::new();
         for job in &mut loaded {
             // Clear stale runtime state — the process just started, nothing is running.
             job.state.running_at_ms = None;
-            // Recompute next run time for enabled jobs.
-            if job.enabled {
-                job.state.next_run_at_ms = compute_next_run(&job.schedule, now);
+            job.state.active_runs = 0;
+            // Recompute next run time for enabled jobs, then apply the
+            // catch-up policy to firings missed while we were down.
+            if job.enabled && is_reboot(&job.schedule) {
+                // `@reboot` jobs run once per start and have nothing to catch up.
+                job.state.next_run_at_ms = Some(now.saturating_add(job.policy.jitter()));
+            } else if job.enabled {
+                let missed_since = job.state.next_run_at_ms.filter(|next| *next <= now);
+                job.state.next_run_at_ms = next_run_for(&job.schedule, &job.policy, now);
+                if let Some(since) = missed_since {
+                    skipped.extend(Self::catch_up(job, since, now));
+                }
             }
         }
 
         let count = loaded.len();
         *self.jobs.write() = loaded;
+        for entry in skipped {
+            self.record_run(entry);
+        }
         tracing::info!(count, path = %path.display(), "loaded cron jobs from disk");
     }
 
+    /// Apply a loaded job's catch-up policy to the firings it missed from
+    /// `since` until `now`.
+    ///
+    /// Replays are queued by making the job due now; a log entry is returned
+    /// for the firings dropped.
+    fn catch_up(job: &mut CronJob, since: u64, now: u64) -> Option<CronRunLogEntry> {
+        let catch_up = job.policy.catch_up.unwrap_or_default();
+        let oldest = catch_up.max_lag_ms.map_or(0, |lag| now.saturating_sub(lag));
+        let (stale, recent) = missed_firings(&job.schedule, since, oldest, now);
+        let replay = match catch_up.mode {
+            CronCatchUpMode::Skip => 0,
+            CronCatchUpMode::RunOnce => recent.min(1),
+            CronCatchUpMode::RunAllMissed => recent.min(MAX_PENDING_RUNS),
+        };
+        job.state.catch_up_runs = replay;
+        if replay > 0 {
+            job.state.next_run_at_ms = Some(now.saturating_add(job.policy.jitter()));
+        }
+
+        let dropped = stale + recent - replay;
+        (dropped > 0).then(|| CronRunLogEntry {
+            ts: now,
+            job_id: job.id.clone(),
+            action: "skipped".to_string(),
+            trigger: None,
+            skip_reason: Some(CronSkipReason::Missed),
+            status: Some(CronJobStatus::Skipped),
+            error: None,
+            summary: Some(format!("{dropped} missed run(s) skipped")),
+            run_at_ms: Some(since),
+            duration_ms: None,
+            next_run_at_ms: job.state.next_run_at_ms,
+        })
+    }
+
     /// Flush the current jobs list to disk via atomic write.
     ///
     /// No-op when `persist_path` is `None`. Errors are logged but never
@@ -516,11 +784,53 @@ impl CronScheduler {
 
     /// Add a new job.
     pub fn add(&self, input: CronJobCreate) -> Result<CronJob, CronError> {
+        self.insert(input, None)
+    }
+
+    /// Add a job on behalf of an agent session.
+    ///
+    /// At most `max_per_session` jobs created by the same session may exist
+    /// at once. Unlike [`add`](Self::add), a full scheduler never evicts
+    /// other jobs to make room for an agent-created one.
+    pub fn add_for_session(
+        &self,
+        input: CronJobCreate,
+        session_key: &str,
+        max_per_session: usize,
+    ) -> Result<CronJob, CronError> {
+        self.insert(
+            input,
+            Some((
+                CronJobCreator::Agent {
+                    session_key: session_key.to_string(),
+                },
+                max_per_session,
+            )),
+        )
+    }
+
+    /// List jobs created by the given session.
+    pub fn list_for_session(&self, session_key: &str) -> Vec<CronJob> {
+        self.list(true)
+            .into_iter()
+            .filter(|job| {
+                job.created_by
+                    .as_ref()
+                    .is_some_and(|c| c.session_key() == session_key)
+            })
+            .collect()
+    }
+
+    fn insert(
+        &self,
+        input: CronJobCreate,
+        creator: Option<(CronJobCreator, usize)>,
+    ) -> Result<CronJob, CronError> {
         let now = now_ms();
         let job_id = Uuid::new_v4().to_string();
 
         let next_run_at_ms = if input.enabled {
-            compute_next_run(&input.schedule, now)
+            next_run_for(&input.schedule, &input.policy, now)
         } else {
             None
         };
@@ -539,19 +849,29 @@ impl CronScheduler {
             wake_mode: input.wake_mode,
             payload: input.payload,
             isolation: input.isolation,
+            created_by: creator.as_ref().map(|(c, _)| c.clone()),
+            policy: input.policy,
             state: CronJobState {
                 next_run_at_ms,
-                running_at_ms: None,
-                last_run_at_ms: None,
-                last_status: None,
-                last_error: None,
-                last_duration_ms: None,
+                ..Default::default()
             },
         };
 
         let mut evicted_job: Option<CronJob> = None;
         {
             let mut jobs = self.jobs.write();
+            if let Some((creator, max_per_session)) = &creator {
+                let owned = jobs
+                    .iter()
+                    .filter(|j| j.created_by.as_ref() == Some(creator))
+                    .count();
+                if owned >= *max_per_session {
+                    return Err(CronError::SessionLimitExceeded(*max_per_session));
+                }
+                if jobs.len() >= Self::MAX_JOBS {
+                    return Err(CronError::LimitExceeded(Self::MAX_JOBS));
+                }
+            }
             if jobs.len() >= Self::MAX_JOBS {
                 let eviction = jobs
                     .iter()
@@ -632,12 +952,13 @@ impl CronScheduler {
         if let Some(isolation) = patch.isolation {
             job.isolation = Some(isolation);
         }
+        job.policy.apply(patch.policy);
 
         job.updated_at_ms = now;
 
         // Recompute next run time
         if job.enabled {
-            job.state.next_run_at_ms = compute_next_run(&job.schedule, now);
+            job.state.next_run_at_ms = next_run_for(&job.schedule, &job.policy, now);
         } else {
             job.state.next_run_at_ms = None;
             job.state.running_at_ms = None;
@@ -714,15 +1035,107 @@ impl CronScheduler {
             });
         }
 
-        // Mark as running
-        job.state.running_at_ms = Some(now);
+        // Mark as running and compute next run time so scheduler won't re-fire
+        start_run(job, CronRunTrigger::Manual, now);
+        self.finish_start(jobs, id)
+    }
+
+    /// Start a due job from the tick loop, applying its run policies.
+    ///
+    /// A firing that comes due while a previous run is still going is
+    /// skipped or queued per the job's `concurrency`; missed-run replays and
+    /// retries wait for the running run instead.
+    pub fn run_due(&self, id: &str) -> Result<CronRunResult, CronError> {
+        let now = now_ms();
+
+        let mut jobs = self.jobs.write();
+        let job = jobs
+            .iter_mut()
+            .find(|j| j.id == id)
+            .ok_or_else(|| CronError::JobNotFound(id.to_string()))?;
+
+        let running = job.state.running_at_ms.is_some();
+        let due = job.enabled && job.state.next_run_at_ms.is_some_and(|next| now >= next);
+        let trigger = if job.enabled && job.state.queued_runs > 0 && !running {
+            job.state.queued_runs -= 1;
+            Some(CronRunTrigger::Queued)
+        } else if !due {
+            None
+        } else if job.state.catch_up_runs > 0 {
+            (!running).then_some(CronRunTrigger::CatchUp)
+        } else if job.state.retry_count > 0 {
+            (!running).then_some(CronRunTrigger::Retry)
+        } else {
+            Some(CronRunTrigger::Schedule)
+        };
+        let Some(trigger) = trigger else {
+            return Ok(CronRunResult {
+                ok: true,
+                ran: false,
+                reason: Some(CronRunReason::NotDue),
+                payload: None,
+                job_id: id.to_string(),
+            });
+        };
 
-        // Compute next run time so scheduler won't re-fire
-        job.state.next_run_at_ms = compute_next_run(&job.schedule, now);
+        let concurrency = job.policy.concurrency.unwrap_or_default();
+        if running && trigger == CronRunTrigger::Schedule && concurrency != CronConcurrency::Allow {
+            let queued =
+                concurrency == CronConcurrency::Queue && job.state.queued_runs < MAX_PENDING_RUNS;
+            if queued {
+                job.state.queued_runs += 1;
+            }
+            job.state.next_run_at_ms = next_run_for(&job.schedule, &job.policy, now);
+            let entry = CronRunLogEntry {
+                ts: now,
+                job_id: id.to_string(),
+                action: if queued { "queued" } else { "skipped" }.to_string(),
+                trigger: Some(trigger),
+                skip_reason: Some(CronSkipReason::Overlap),
+                status: (!queued).then_some(CronJobStatus::Skipped),
+                error: None,
+                summary: None,
+                run_at_ms: Some(now),
+                duration_ms: None,
+                next_run_at_ms: job.state.next_run_at_ms,
+            };
+            drop(jobs);
+
+            self.flush_to_disk();
+            self.record_run(entry);
+            return Ok(CronRunResult {
+                ok: true,
+                ran: false,
+                reason: Some(if queued {
+                    CronRunReason::Queued
+                } else {
+                    CronRunReason::Running
+                }),
+                payload: None,
+                job_id: id.to_string(),
+            });
+        }
+
+        start_run(job, trigger, now);
+        self.finish_start(jobs, id)
+    }
+
+    /// Release the jobs lock after [`start_run`], persist, and emit the
+    /// `Started` event.
+    fn finish_start(
+        &self,
+        jobs: parking_lot::RwLockWriteGuard<'_, Vec<CronJob>>,
+        id: &str,
+    ) -> Result<CronRunResult, CronError> {
+        let job = jobs
+            .iter()
+            .find(|j| j.id == id)
+            .ok_or_else(|| CronError::JobNotFound(id.to_string()))?;
 
         // Clone payload before dropping the lock
         let payload = job.payload.clone();
         let next_run_at_ms = job.state.next_run_at_ms;
+        let trigger = job.state.last_trigger;
         let job_id = id.to_string();
 
         drop(jobs);
@@ -733,7 +1146,7 @@ impl CronScheduler {
             job_id: job_id.clone(),
             action: CronEventAction::Started,
             next_run_at_ms,
-            details: None,
+            details: Some(serde_json::json!({ "trigger": trigger })),
         });
 
         // Actual execution is handled by the caller (cron executor / tick loop).
@@ -774,20 +1187,38 @@ impl CronScheduler {
 
     /// Get IDs of jobs that are due to run now.
     ///
-    /// Returns job IDs that are enabled, not currently running, and past their next_run_at_ms.
+    /// Returns job IDs that are enabled and past their next_run_at_ms, or
+    /// that have a queued firing and are no longer running. Whether a due
+    /// job that is still running starts again is up to [`run_due`](Self::run_due).
     pub fn get_due_job_ids(&self) -> Vec<String> {
         let now = now_ms();
         let jobs = self.jobs.read();
         jobs.iter()
             .filter(|j| {
                 j.enabled
-                    && j.state.running_at_ms.is_none()
-                    && j.state.next_run_at_ms.is_some_and(|next| now >= next)
+                    && ((j.state.queued_runs > 0 && j.state.running_at_ms.is_none())
+                        || j.state.next_run_at_ms.is_some_and(|next| now >= next))
             })
             .map(|j| j.id.clone())
             .collect()
     }
 
+    /// Hold a started job until the next heartbeat.
+    ///
+    /// The job stays marked as running, so it is not fired again while it
+    /// waits.
+    pub fn defer_until_heartbeat(&self, job_id: &str) {
+        let mut deferred = self.deferred.lock();
+        if !deferred.iter().any(|id| id == job_id) {
+            deferred.push(job_id.to_string());
+        }
+    }
+
+    /// Take the jobs waiting for a heartbeat, oldest first.
+    pub fn take_deferred(&self) -> Vec<String> {
+        std::mem::take(&mut *self.deferred.lock())
+    }
+
     /// Mark a job run as finished, updating state and recording a log entry.
     ///
     /// Called by the cron executor after payload execution completes.
@@ -805,40 +1236,53 @@ impl CronScheduler {
             .find(|j| j.id == job_id)
             .ok_or_else(|| CronError::JobNotFound(job_id.to_string()))?;
 
-        job.state.running_at_ms = None;
+        job.state.active_runs = job.state.active_runs.saturating_sub(1);
+        if job.state.active_runs == 0 {
+            job.state.running_at_ms = None;
+        }
         job.state.last_run_at_ms = Some(now);
         job.state.last_duration_ms = Some(duration_ms);
         job.state.last_status = Some(status);
         job.state.last_error = error.clone();
 
-        let should_delete = job.delete_after_run == Some(true);
+        // Failed runs are retried with backoff, or sooner if the job is
+        // due again before then.
+        let retrying = status == CronJobStatus::Error
+            && job.state.retry_count < job.policy.max_retries.unwrap_or(0);
+        if retrying {
+            job.state.retry_count += 1;
+            let retry_at = now.saturating_add(job.policy.retry_delay_ms(job.state.retry_count));
+            job.state.next_run_at_ms = Some(
+                job.state
+                    .next_run_at_ms
+                    .map_or(retry_at, |next| next.min(retry_at)),
+            );
+        } else {
+            job.state.retry_count = 0;
+        }
+
+        let should_delete = job.delete_after_run == Some(true) && !retrying;
         let next_run = job.state.next_run_at_ms;
+        let trigger = job.state.last_trigger;
         let job_id_owned = job_id.to_string();
         drop(jobs);
 
         self.flush_to_disk();
 
         // Record run log
-        let log_entry = CronRunLogEntry {
+        self.record_run(CronRunLogEntry {
             ts: now,
             job_id: job_id_owned.clone(),
             action: "finished".to_string(),
+            trigger,
+            skip_reason: None,
             status: Some(status),
             error,
             summary: None,
             run_at_ms: Some(now),
             duration_ms: Some(duration_ms),
             next_run_at_ms: next_run,
-        };
-
-        {
-            let mut run_log = self.run_log.write();
-            run_log.push(log_entry);
-            if run_log.len() > 1000 {
-                let drain_count = run_log.len() - 1000;
-                run_log.drain(0..drain_count);
-            }
-        }
+        });
 
         self.emit_event(CronEvent {
             job_id: job_id_owned.clone(),
@@ -857,6 +1301,16 @@ impl CronScheduler {
         Ok(())
     }
 
+    /// Append a run log entry, keeping the newest 1000.
+    fn record_run(&self, entry: CronRunLogEntry) {
+        let mut run_log = self.run_log.write();
+        run_log.push(entry);
+        if run_log.len() > 1000 {
+            let drain_count = run_log.len() - 1000;
+            run_log.drain(0..drain_count);
+        }
+    }
+
     fn emit_event(&self, event: CronEvent) {
         if let Some(tx) = &self.event_tx {
             let _ = tx.send(event);
@@ -919,6 +1373,10 @@ impl CronRunMode {
 #[serde(rename_all = "kebab-case")]
 pub enum CronRunReason {
     NotDue,
+    /// Skipped because a previous run is still going.
+    Running,
+    /// Queued behind a previous run.
+    Queued,
 }
 
 /// Result of running a job.
@@ -945,14 +1403,20 @@ pub enum CronError {
     StoreError(String),
     #[error("job limit exceeded (max {0})")]
     LimitExceeded(usize),
+    #[error("per-session job limit exceeded (max {0})")]
+    SessionLimitExceeded(usize),
 }
 
-/// A parsed cron expression (5-field: minute hour day-of-month month day-of-week).
+/// A parsed cron expression.
 ///
-/// Each field is stored as a set of valid values. A datetime matches when all
-/// five fields match the corresponding component.
-#[derive(Debug, Clone)]
+/// Five fields (`minute hour day-of-month month day-of-week`), six with a
+/// leading seconds field, or one of the `@hourly`-style macros. Each field is
+/// stored as a set of valid values; day-of-month and day-of-week also carry
+/// the `L`, `W` and `#` rules. A datetime matches when all fields match.
+#[derive(Debug, Clone, Default)]
 pub struct CronExpr {
+    /// Valid seconds (0-59); only 0 for 5-field expressions.
+    pub seconds: BTreeSet<u32>,
     /// Valid minutes (0-59).
     pub minutes: BTreeSet<u32>,
     /// Valid hours (0-23).
@@ -963,43 +1427,99 @@ pub struct CronExpr {
     pub months: BTreeSet<u32>,
     /// Valid days of week (0-6, where 0=Sunday).
     pub days_of_week: BTreeSet<u32>,
+    /// `L` in day-of-month: the last day of the month.
+    pub last_day_of_month: bool,
+    /// `LW` in day-of-month: the last weekday (Monday-Friday) of the month.
+    pub last_weekday_of_month: bool,
+    /// `nW` in day-of-month: the weekday nearest day `n`, within the month.
+    pub nearest_weekdays: BTreeSet<u32>,
+    /// `nL` in day-of-week: the last day `n` (0=Sunday) of the month.
+    pub last_days_of_week: BTreeSet<u32>,
+    /// `n#k` in day-of-week: the `k`-th day `n` of the month, as `(n, k)`.
+    pub nth_days_of_week: BTreeSet<(u32, u32)>,
+    /// `@reboot`: runs once when the gateway starts, never on a schedule.
+    pub reboot: bool,
+    /// Whether the hour field starts with `*`. Such expressions follow
+    /// elapsed time across DST changes instead of the wall clock.
+    pub hour_wildcard: bool,
 }
 
 /// Errors from parsing a cron expression.
 #[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
 pub enum CronParseError {
-    #[error("expected 5 fields, got {0}")]
+    #[error("expected 5 or 6 fields, got {0}")]
     WrongFieldCount(usize),
     #[error("invalid field '{field}': {reason}")]
     InvalidField { field: String, reason: String },
+    #[error("unknown macro '{0}'")]
+    UnknownMacro(String),
 }
 
+/// Month names accepted in the month field, from January.
+const MONTH_NAMES: &[&str] = &[
+    "JAN", "FEB", "MAR", "APR", "MAY", "JUN", "JUL", "AUG", "SEP", "OCT", "NOV", "DEC",
+];
+
+/// Day names accepted in the day-of-week field, from Sunday.
+const DAY_NAMES: &[&str] = &["SUN", "MON", "TUE", "WED", "THU", "FRI", "SAT"];
+
+/// How many years ahead `next_after` searches before giving up.
+const MAX_SEARCH_YEARS: i32 = 28;
+
 impl CronExpr {
-    /// Parse a standard 5-field cron expression.
+    /// Parse a cron expression.
     ///
-    /// Format: `minute hour day-of-month month day-of-week`
+    /// Format: `[second] minute hour day-of-month month day-of-week`, or one
+    /// of `@yearly` (`@annually`), `@monthly`, `@weekly`, `@daily`
+    /// (`@midnight`), `@hourly` and `@reboot`.
     ///
     /// Each field supports: `*`, a number, a range (`1-5`), a list (`1,3,5`),
-    /// and steps (`*/5`, `1-10/2`).
+    /// and steps (`*/5`, `1-10/2`). Months and days of week also take names
+    /// (`JAN`, `MON-FRI`). Day-of-month takes `L` (last day), `LW` (last
+    /// weekday) and `nW` (weekday nearest day `n`); day-of-week takes `nL`
+    /// (last day `n` of the month) and `n#k` (`k`-th day `n`). `?` in either
+    /// day field means `*`.
     pub fn parse(expr: &str) -> Result<Self, CronParseError> {
+        let expr = expr.trim();
+        if let Some(name) = expr.strip_prefix('@') {
+            let expanded = match name.to_ascii_lowercase().as_str() {
+                "yearly" | "annually" => "0 0 1 1 *",
+                "monthly" => "0 0 1 * *",
+                "weekly" => "0 0 * * 0",
+                "daily" | "midnight" => "0 0 * * *",
+                "hourly" => "0 * * * *",
+                "reboot" => {
+                    return Ok(Self {
+                        reboot: true,
+                        ..Default::default()
+                    })
+                }
+                _ => return Err(CronParseError::UnknownMacro(expr.to_string())),
+            };
+            return Self::parse(expanded);
+        }
+
         let fields: Vec<&str> = expr.split_whitespace().collect();
-        if fields.len() != 5 {
-            return Err(CronParseError::WrongFieldCount(fields.len()));
-        }
-
-        let minutes = Self::parse_field(fields[0], 0, 59, "minute")?;
-        let hours = Self::parse_field(fields[1], 0, 23, "hour")?;
-        let days_of_month = Self::parse_field(fields[2], 1, 31, "day-of-month")?;
-        let months = Self::parse_field(fields[3], 1, 12, "month")?;
-        let days_of_week = Self::parse_dow_field(fields[4])?;
-
-        Ok(Self {
-            minutes,
-            hours,
-            days_of_month,
-            months,
-            days_of_week,
-        })
+        let (seconds, fields) = match fields.len() {
+            5 => (BTreeSet::from([0]), &fields[..]),
+            6 => (
+                Self::parse_field(fields[0], 0, 59, "second", &[])?,
+                &fields[1..],
+            ),
+            n => return Err(CronParseError::WrongFieldCount(n)),
+        };
+
+        let mut parsed = Self {
+            seconds,
+            minutes: Self::parse_field(fields[0], 0, 59, "minute", &[])?,
+            hours: Self::parse_field(fields[1], 0, 23, "hour", &[])?,
+            months: Self::parse_field(fields[3], 1, 12, "month", MONTH_NAMES)?,
+            hour_wildcard: fields[1].starts_with('*'),
+            ..Default::default()
+        };
+        parsed.parse_dom_field(fields[2])?;
+        parsed.parse_dow_field(fields[4])?;
+        Ok(parsed)
     }
 
     /// Parse a single cron field into a set of valid values.
@@ -1010,15 +1530,18 @@ impl CronExpr {
     /// - `N`           -> single value
     /// - `N-M`         -> range from `N` to `M` inclusive
     /// - `N-M/step`    -> range with step
+    ///
+    /// `names` are accepted in place of numbers, the first standing for `min`.
     fn parse_field(
         field: &str,
         min: u32,
         max: u32,
         name: &str,
+        names: &[&str],
     ) -> Result<BTreeSet<u32>, CronParseError> {
         let mut result = BTreeSet::new();
         for part in field.split(',') {
-            let values = Self::parse_field_part(part, min, max, name)?;
+            let values = Self::parse_field_part(part, min, max, name, names)?;
             result.extend(values);
         }
         if result.is_empty() {
@@ -1030,12 +1553,23 @@ impl CronExpr {
         Ok(result)
     }
 
+    /// Parse a number or one of `names` (case-insensitive).
+    fn parse_value(value: &str, min: u32, names: &[&str]) -> Option<u32> {
+        value.parse().ok().or_else(|| {
+            names
+                .iter()
+                .position(|n| n.eq_ignore_ascii_case(value))
+                .map(|i| min + i as u32)
+        })
+    }
+
     /// Parse a single comma-separated item of a cron field.
     fn parse_field_part(
         part: &str,
         min: u32,
         max: u32,
         name: &str,
+        names: &[&str],
     ) -> Result<BTreeSet<u32>, CronParseError> {
         let make_err = |reason: String| CronParseError::InvalidField {
             field: part.to_string(),
@@ -1059,12 +1593,10 @@ impl CronExpr {
         let (range_min, range_max) = if range_part == "*" {
             (min, max)
         } else if let Some((lo, hi)) = range_part.split_once('-') {
-            let lo: u32 = lo
-                .parse()
-                .map_err(|_| make_err(format!("invalid range start '{lo}' in {name}")))?;
-            let hi: u32 = hi
-                .parse()
-                .map_err(|_| make_err(format!("invalid range end '{hi}' in {name}")))?;
+            let lo = Self::parse_value(lo, min, names)
+                .ok_or_else(|| make_err(format!("invalid range start '{lo}' in {name}")))?;
+            let hi = Self::parse_value(hi, min, names)
+                .ok_or_else(|| make_err(format!("invalid range end '{hi}' in {name}")))?;
             if lo < min || hi > max {
                 return Err(make_err(format!(
                     "range {lo}-{hi} out of bounds ({min}-{max}) for {name}"
@@ -1076,9 +1608,8 @@ impl CronExpr {
             (lo, hi)
         } else {
             // Single number
-            let val: u32 = range_part
-                .parse()
-                .map_err(|_| make_err(format!("invalid value '{range_part}' in {name}")))?;
+            let val = Self::parse_value(range_part, min, names)
+                .ok_or_else(|| make_err(format!("invalid value '{range_part}' in {name}")))?;
             if val < min || val > max {
                 return Err(make_err(format!(
                     "value {val} out of bounds ({min}-{max}) for {name}"
@@ -1108,142 +1639,346 @@ impl CronExpr {
         Ok(set)
     }
 
-    /// Parse the day-of-week field, handling 7 as an alias for Sunday (0).
-    fn parse_dow_field(field: &str) -> Result<BTreeSet<u32>, CronParseError> {
+    /// Parse the day-of-month field, including `L`, `LW` and `nW`.
+    fn parse_dom_field(&mut self, field: &str) -> Result<(), CronParseError> {
+        for part in field.split(',') {
+            if part.eq_ignore_ascii_case("L") {
+                self.last_day_of_month = true;
+            } else if part.eq_ignore_ascii_case("LW") {
+                self.last_weekday_of_month = true;
+            } else if let Some(day) = part.strip_suffix(['W', 'w']) {
+                let day = day
+                    .parse::<u32>()
+                    .ok()
+                    .filter(|d| (1..=31).contains(d))
+                    .ok_or_else(|| CronParseError::InvalidField {
+                        field: part.to_string(),
+                        reason: format!("invalid day '{day}' before W in day-of-month"),
+                    })?;
+                self.nearest_weekdays.insert(day);
+            } else {
+                let part = if part == "?" { "*" } else { part };
+                let values = Self::parse_field_part(part, 1, 31, "day-of-month", &[])?;
+                self.days_of_month.extend(values);
+            }
+        }
+        Ok(())
+    }
+
+    /// Parse the day-of-week field, handling 7 as an alias for Sunday (0),
+    /// `nL` and `n#k`.
+    fn parse_dow_field(&mut self, field: &str) -> Result<(), CronParseError> {
         // Day-of-week: 0-7 where 0 and 7 both mean Sunday.
         // We parse with range 0-7, then normalize 7 -> 0.
-        let mut result = BTreeSet::new();
+        let day = |value: &str, part: &str| {
+            Self::parse_value(value, 0, DAY_NAMES)
+                .filter(|d| *d <= 7)
+                .map(|d| d % 7)
+                .ok_or_else(|| CronParseError::InvalidField {
+                    field: part.to_string(),
+                    reason: format!("invalid day '{value}' in day-of-week"),
+                })
+        };
         for part in field.split(',') {
-            let values = Self::parse_field_part(part, 0, 7, "day-of-week")?;
-            result.extend(values);
-        }
-        // Normalize: 7 -> 0 (Sunday)
-        if result.remove(&7) {
-            result.insert(0);
-        }
-        if result.is_empty() {
-            return Err(CronParseError::InvalidField {
-                field: field.to_string(),
-                reason: "day-of-week field produced no valid values".to_string(),
-            });
+            if let Some(value) = part.strip_suffix(['L', 'l']).filter(|v| !v.is_empty()) {
+                self.last_days_of_week.insert(day(value, part)?);
+            } else if let Some((value, nth)) = part.split_once('#') {
+                let nth = nth
+                    .parse::<u32>()
+                    .ok()
+                    .filter(|n| (1..=5).contains(n))
+                    .ok_or_else(|| CronParseError::InvalidField {
+                        field: part.to_string(),
+                        reason: format!("invalid occurrence '{nth}' after # (1-5)"),
+                    })?;
+                self.nth_days_of_week.insert((day(value, part)?, nth));
+            } else {
+                let part = if part == "?" { "*" } else { part };
+                let values = Self::parse_field_part(part, 0, 7, "day-of-week", DAY_NAMES)?;
+                self.days_of_week.extend(values.into_iter().map(|d| d % 7));
+            }
         }
-        Ok(result)
+        Ok(())
     }
 
-    /// Check whether raw time components match this cron expression.
-    fn matches_components(&self, minute: u32, hour: u32, day: u32, month: u32, dow: u32) -> bool {
-        self.minutes.contains(&minute)
-            && self.hours.contains(&hour)
-            && self.days_of_month.contains(&day)
-            && self.months.contains(&month)
-            && self.days_of_week.contains(&dow)
+    /// Check whether a calendar date matches the month and day fields.
+    ///
+    /// The day must match both the day-of-month and the day-of-week field.
+    fn matches_date(&self, date: chrono::NaiveDate) -> bool {
+        if !self.months.contains(&date.month()) {
+            return false;
+        }
+        let (year, month, day) = (date.year(), date.month(), date.day());
+        let last = last_day_of_month(year, month);
+        let dom = self.days_of_month.contains(&day)
+            || (self.last_day_of_month && day == last)
+            || (self.last_weekday_of_month && day == nearest_weekday(date, last))
+            || self
+                .nearest_weekdays
+                .iter()
+                .any(|n| *n <= last && day == nearest_weekday(date, *n));
+
+        // cron: Sun=0, Mon=1 .. Sat=6 via weekday().num_days_from_sunday()
+        let dow = date.weekday().num_days_from_sunday();
+        let dow_matches = self.days_of_week.contains(&dow)
+            || (self.last_days_of_week.contains(&dow) && day + 7 > last)
+            || self.nth_days_of_week.contains(&(dow, (day - 1) / 7 + 1));
+
+        dom && dow_matches
     }
 
     /// Check if a `chrono::DateTime<Utc>` matches this cron expression.
     pub fn matches(&self, dt: &chrono::DateTime<Utc>) -> bool {
-        let minute = dt.minute();
-        let hour = dt.hour();
-        let day = dt.day();
-        let month = dt.month();
-        // chrono: Mon=0 .. Sun=6 via weekday().num_days_from_monday()
-        // cron: Sun=0, Mon=1 .. Sat=6 via weekday().num_days_from_sunday()
-        let dow = dt.weekday().num_days_from_sunday();
+        !self.reboot
+            && self.matches_date(dt.date_naive())
+            && self.hours.contains(&dt.hour())
+            && self.minutes.contains(&dt.minute())
+            && self.seconds.contains(&dt.second())
+    }
+
+    /// Find the first wall-clock time at or after `from` that matches.
+    ///
+    /// Walks field by field, skipping whole months, days, hours and minutes
+    /// that cannot match. Gives up after [`MAX_SEARCH_YEARS`] (e.g., Feb 31).
+    fn next_local(&self, from: chrono::NaiveDateTime) -> Option<chrono::NaiveDateTime> {
+        use chrono::{Duration as CDuration, NaiveDate};
+
+        if self.reboot {
+            return None;
+        }
+        let limit_year = from.year() + MAX_SEARCH_YEARS;
+        let next_in = |set: &BTreeSet<u32>, from: u32| set.range(from..).next().copied();
 
-        self.matches_components(minute, hour, day, month, dow)
+        let mut t = from.with_nanosecond(0)?;
+        if t < from {
+            t += CDuration::seconds(1);
+        }
+        loop {
+            if t.year() > limit_year {
+                return None;
+            }
+            let date = t.date();
+            if !self.months.contains(&t.month()) {
+                let (year, month) = if t.month() == 12 {
+                    (t.year() + 1, 1)
+                } else {
+                    (t.year(), t.month() + 1)
+                };
+                t = NaiveDate::from_ymd_opt(year, month, 1)?.and_hms_opt(0, 0, 0)?;
+                continue;
+            }
+            if !self.matches_date(date) {
+                t = date.succ_opt()?.and_hms_opt(0, 0, 0)?;
+                continue;
+            }
+            let Some(hour) = next_in(&self.hours, t.hour()) else {
+                t = date.succ_opt()?.and_hms_opt(0, 0, 0)?;
+                continue;
+            };
+            if hour != t.hour() {
+                t = date.and_hms_opt(hour, 0, 0)?;
+            }
+            let Some(minute) = next_in(&self.minutes, t.minute()) else {
+                t = date.and_hms_opt(hour, 0, 0)? + CDuration::hours(1);
+                continue;
+            };
+            if minute != t.minute() {
+                t = date.and_hms_opt(hour, minute, 0)?;
+            }
+            let Some(second) = next_in(&self.seconds, t.second()) else {
+                t = date.and_hms_opt(hour, minute, 0)? + CDuration::minutes(1);
+                continue;
+            };
+            return date.and_hms_opt(hour, minute, second);
+        }
     }
 
-    /// Find the next minute (as UTC `DateTime`) after `after` that matches this expression.
+    /// Find the next second (as UTC `DateTime`) after `after` that matches this expression.
     ///
-    /// Searches up to ~4 years (2,100,000 minutes) to account for leap years and
-    /// edge cases. Returns `None` if no match is found (e.g., Feb 31).
+    /// Returns `None` if no match is found within 28 years (e.g., Feb 31),
+    /// and always for `@reboot`.
     pub fn next_after(&self, after: &chrono::DateTime<Utc>) -> Option<chrono::DateTime<Utc>> {
-        use chrono::Duration as CDuration;
-
-        // Start from the next whole minute after `after`
-        let mut candidate = *after + CDuration::seconds(60 - after.second() as i64)
-            - CDuration::nanoseconds(after.nanosecond() as i64);
-        // If `after` was already at a whole minute boundary, we already advanced by 60s,
-        // which is what we want (next minute after `after`, not `after` itself).
-        // But if after had sub-minute components, the above math may still land on the same
-        // minute. Let's normalize to ensure we're at seconds=0, nanos=0 of the next minute.
-        candidate = candidate
-            .with_second(0)
-            .unwrap_or(candidate)
-            .with_nanosecond(0)
-            .unwrap_or(candidate);
-
-        // If we're still at or before `after`, push forward one more minute
-        if candidate <= *after {
-            candidate += CDuration::minutes(1);
-        }
-
-        // Search up to ~4 years of minutes
-        let max_iterations = 2_100_000u32;
-        for _ in 0..max_iterations {
-            if self.matches(&candidate) {
-                return Some(candidate);
+        self.next_after_tz(after, &Utc)
+    }
+
+    /// Find the next instant after `after` at which the wall clock in `tz`
+    /// matches this expression.
+    ///
+    /// Across DST changes, expressions with a fixed hour follow the wall
+    /// clock: a time skipped when clocks spring forward runs at the moment
+    /// they jump, and a time repeated when they fall back runs only the
+    /// first time. Expressions with `*` in the hour field follow elapsed
+    /// time instead: they run in both passes of a repeated hour and not at
+    /// all in a skipped one.
+    pub fn next_after_tz<Z: TimeZone>(
+        &self,
+        after: &chrono::DateTime<Utc>,
+        tz: &Z,
+    ) -> Option<chrono::DateTime<Utc>> {
+        use chrono::{Duration as CDuration, LocalResult};
+
+        let start = after.with_nanosecond(0)? + CDuration::seconds(1);
+        if self.hour_wildcard {
+            return self.next_elapsed(start, tz);
+        }
+
+        let mut from = start.with_timezone(tz).naive_local();
+        loop {
+            let candidate = self.next_local(from)?;
+            let at = match tz.from_local_datetime(&candidate) {
+                LocalResult::Single(dt) => dt,
+                LocalResult::Ambiguous(first, _) => first,
+                LocalResult::None => first_after_gap(tz, candidate)?,
+            }
+            .with_timezone(&Utc);
+            if at >= start {
+                return Some(at);
+            }
+            from = candidate + CDuration::seconds(1);
+        }
+    }
+
+    /// [`next_after_tz`](Self::next_after_tz) for expressions that follow
+    /// elapsed time: search the wall clock one UTC offset at a time.
+    fn next_elapsed<Z: TimeZone>(
+        &self,
+        mut start: chrono::DateTime<Utc>,
+        tz: &Z,
+    ) -> Option<chrono::DateTime<Utc>> {
+        // Each pass moves past one offset change.
+        for _ in 0..(MAX_SEARCH_YEARS * 4) {
+            let offset = start.with_timezone(tz).offset().fix();
+            let local = start.naive_utc() + offset;
+            let candidate = self.next_local(local)?;
+            let at = (candidate - offset).and_utc();
+            if at.with_timezone(tz).offset().fix() == offset {
+                return Some(at);
             }
-            candidate += CDuration::minutes(1);
+            // The offset changes before the candidate; resume from there.
+            start = offset_change(tz, start, at, offset);
         }
         None
     }
 }
 
-/// Find the next UTC minute after `after` whose local-time representation in
-/// `tz` matches `expr`.
-///
-/// Uses brute-force minute iteration (same budget as `CronExpr::next_after`)
-/// but converts each UTC candidate to the target timezone before matching.
-///
-/// **Fall-back deduplication:** during a DST fall-back two consecutive UTC
-/// instants map to the same local clock reading. We fire only on the *first*
-/// occurrence by checking whether the UTC offset decreased compared to one
-/// hour earlier at the same local time.
-fn next_after_in_tz(
-    expr: &CronExpr,
-    after: &chrono::DateTime<Utc>,
-    tz: chrono_tz::Tz,
-) -> Option<chrono::DateTime<Utc>> {
+/// The last day of a month.
+fn last_day_of_month(year: i32, month: u32) -> u32 {
+    let (next_year, next_month) = if month == 12 {
+        (year + 1, 1)
+    } else {
+        (year, month + 1)
+    };
+    chrono::NaiveDate::from_ymd_opt(next_year, next_month, 1)
+        .and_then(|d| d.pred_opt())
+        .map_or(28, |d| d.day())
+}
+
+/// The weekday (Monday-Friday) nearest day `day` of the month `date` is in,
+/// without leaving the month.
+fn nearest_weekday(date: chrono::NaiveDate, day: u32) -> u32 {
+    let last = last_day_of_month(date.year(), date.month());
+    let Some(target) = date.with_day(day) else {
+        return 0;
+    };
+    match target.weekday() {
+        chrono::Weekday::Sat if day == 1 => day + 2,
+        chrono::Weekday::Sat => day - 1,
+        chrono::Weekday::Sun if day == last => day - 2,
+        chrono::Weekday::Sun => day + 1,
+        _ => day,
+    }
+}
+
+/// The first instant after a wall-clock time skipped by a DST change.
+fn first_after_gap<Z: TimeZone>(
+    tz: &Z,
+    skipped: chrono::NaiveDateTime,
+) -> Option<chrono::DateTime<Z>> {
     use chrono::Duration as CDuration;
 
-    // Start from the next whole UTC minute after `after`.
-    let mut candidate = *after + CDuration::seconds(60 - after.second() as i64)
-        - CDuration::nanoseconds(after.nanosecond() as i64);
-    candidate = candidate
-        .with_second(0)
-        .unwrap_or(candidate)
-        .with_nanosecond(0)
-        .unwrap_or(candidate);
-    if candidate <= *after {
-        candidate += CDuration::minutes(1);
-    }
-
-    let max_iterations = 2_100_000u32;
-    for _ in 0..max_iterations {
-        let local = candidate.with_timezone(&tz);
-        let minute = local.minute();
-        let hour = local.hour();
-        let day = local.day();
-        let month = local.month();
-        let dow = local.weekday().num_days_from_sunday();
-
-        if expr.matches_components(minute, hour, day, month, dow) {
-            // Fall-back dedup: if offset decreased vs one hour ago AND the
-            // local clock reading was the same, this is the second occurrence
-            // of an ambiguous wall-clock time — skip it.
-            let one_hour_ago = candidate - CDuration::hours(1);
-            let local_ago = one_hour_ago.with_timezone(&tz);
-            let offset_now = local.offset().fix().local_minus_utc();
-            let offset_ago = local_ago.offset().fix().local_minus_utc();
-            if offset_now < offset_ago && local_ago.hour() == hour && local_ago.minute() == minute {
-                candidate += CDuration::minutes(1);
-                continue;
+    // Gaps are usually an hour; a few zones have skipped a whole day.
+    (1..=48 * 60).find_map(|minutes| {
+        let local = skipped + CDuration::minutes(minutes);
+        let local = local.with_second(0)?;
+        tz.from_local_datetime(&local).earliest()
+    })
+}
+
+/// The first instant in `(from, to]` whose UTC offset in `tz` differs from
+/// `offset`, to the second.
+fn offset_change<Z: TimeZone>(
+    tz: &Z,
+    from: chrono::DateTime<Utc>,
+    to: chrono::DateTime<Utc>,
+    offset: chrono::FixedOffset,
+) -> chrono::DateTime<Utc> {
+    let (mut lo, mut hi) = (from.timestamp(), to.timestamp());
+    while hi - lo > 1 {
+        let mid = lo + (hi - lo) / 2;
+        let at = Utc.timestamp_opt(mid, 0).single().unwrap_or(to);
+        if at.with_timezone(tz).offset().fix() == offset {
+            lo = mid;
+        } else {
+            hi = mid;
+        }
+    }
+    Utc.timestamp_opt(hi, 0).single().unwrap_or(to)
+}
+
+/// Mark `job` as started for `trigger` and move its next run on.
+fn start_run(job: &mut CronJob, trigger: CronRunTrigger, now: u64) {
+    job.state.running_at_ms = Some(now);
+    job.state.active_runs += 1;
+    job.state.last_trigger = Some(trigger);
+    match trigger {
+        // A queued firing was already accounted for in the schedule.
+        CronRunTrigger::Queued => {}
+        CronRunTrigger::CatchUp => {
+            job.state.catch_up_runs = job.state.catch_up_runs.saturating_sub(1);
+            // Further replays follow as soon as this run finishes.
+            job.state.next_run_at_ms = if job.state.catch_up_runs > 0 {
+                Some(now)
+            } else {
+                next_run_for(&job.schedule, &job.policy, now)
+            };
+        }
+        _ => job.state.next_run_at_ms = next_run_for(&job.schedule, &job.policy, now),
+    }
+}
+
+/// Next run time for a job: the schedule's next firing plus jitter.
+fn next_run_for(schedule: &CronSchedule, policy: &CronRunPolicy, now: u64) -> Option<u64> {
+    compute_next_run(schedule, now).map(|next| next.saturating_add(policy.jitter()))
+}
+
+/// Cap on the firings counted when looking back over missed runs.
+const MISSED_SCAN_LIMIT: u32 = 1000;
+
+/// Count the firings of `schedule` from `since` up to `now`, split into
+/// those before `oldest` and those at or after it.
+///
+/// Past the scan limit the walk jumps to `oldest` so recent firings are
+/// still found after a long outage.
+fn missed_firings(schedule: &CronSchedule, since: u64, oldest: u64, now: u64) -> (u32, u32) {
+    let (mut stale, mut recent) = (0u32, 0u32);
+    let mut at = Some(since);
+    while let Some(t) = at.filter(|t| *t <= now) {
+        if t < oldest {
+            stale += 1;
+            at = if stale >= MISSED_SCAN_LIMIT {
+                compute_next_run(schedule, oldest - 1)
+            } else {
+                compute_next_run(schedule, t)
+            };
+        } else {
+            recent += 1;
+            if recent >= MISSED_SCAN_LIMIT {
+                break;
             }
-            return Some(candidate);
+            at = compute_next_run(schedule, t);
         }
-        candidate += CDuration::minutes(1);
     }
-    None
+    (stale, recent)
 }
 
 /// Compute the next run time for a schedule.
@@ -1298,13 +2033,18 @@ fn compute_next_run(schedule: &CronSchedule, now: u64) -> Option<u64> {
 
             let next_dt = match timezone {
                 None => parsed.next_after(&now_dt)?,
-                Some(tz_val) => next_after_in_tz(&parsed, &now_dt, tz_val)?,
+                Some(tz_val) => parsed.next_after_tz(&now_dt, &tz_val)?,
             };
             Some(next_dt.timestamp_millis() as u64)
         }
     }
 }
 
+/// Whether `schedule` is an `@reboot` cron expression.
+fn is_reboot(schedule: &CronSchedule) -> bool {
+    matches!(schedule, CronSchedule::Cron { expr, .. } if expr.trim().eq_ignore_ascii_case("@reboot"))
+}
+
 pub(crate) fn now_ms() -> u64 {
     SystemTime::now()
         .duration_since(UNIX_EPOCH)
@@ -1351,6 +2091,7 @@ mod tests {
                 text: "Hello from cron!".to_string(),
             },
             isolation: None,
+            policy: Default::default(),
         };
 
         let job = scheduler.add(input).unwrap();
@@ -1385,6 +2126,7 @@ mod tests {
                     text: "test".to_string(),
                 },
                 isolation: None,
+                policy: Default::default(),
             })
             .unwrap();
 
@@ -1406,6 +2148,7 @@ mod tests {
                     text: "test".to_string(),
                 },
                 isolation: None,
+                policy: Default::default(),
             })
             .unwrap();
 
@@ -1440,6 +2183,7 @@ mod tests {
                     text: "test".to_string(),
                 },
                 isolation: None,
+                policy: Default::default(),
             })
             .unwrap();
 
@@ -1480,6 +2224,7 @@ mod tests {
                     text: "test".to_string(),
                 },
                 isolation: None,
+                policy: Default::default(),
             })
             .unwrap();
 
@@ -1515,6 +2260,7 @@ mod tests {
                     text: "test".to_string(),
                 },
                 isolation: None,
+                policy: Default::default(),
             })
             .unwrap();
 
@@ -1557,6 +2303,7 @@ mod tests {
                     text: "test".to_string(),
                 },
                 isolation: None,
+                policy: Default::default(),
             })
             .unwrap();
 
@@ -1588,6 +2335,7 @@ mod tests {
                     text: "test".to_string(),
                 },
                 isolation: None,
+                policy: Default::default(),
             })
             .unwrap();
 
@@ -1620,6 +2368,7 @@ mod tests {
                         text: "t".to_string(),
                     },
                     isolation: None,
+                    policy: Default::default(),
                 })
                 .unwrap();
             existing_ids.push(job.id);
@@ -1639,6 +2388,7 @@ mod tests {
                 text: "t".to_string(),
             },
             isolation: None,
+            policy: Default::default(),
         });
         let new_job = result.unwrap();
         assert_eq!(scheduler.jobs.read().len(), CronScheduler::MAX_JOBS);
@@ -1726,6 +2476,7 @@ mod tests {
                     text: "due".to_string(),
                 },
                 isolation: None,
+                policy: Default::default(),
             })
             .unwrap();
 
@@ -1754,6 +2505,7 @@ mod tests {
                     text: "disabled".to_string(),
                 },
                 isolation: None,
+                policy: Default::default(),
             })
             .unwrap();
 
@@ -1774,6 +2526,7 @@ mod tests {
                     text: "future".to_string(),
                 },
                 isolation: None,
+                policy: Default::default(),
             })
             .unwrap();
 
@@ -1800,6 +2553,7 @@ mod tests {
                     text: "payload test".to_string(),
                 },
                 isolation: None,
+                policy: Default::default(),
             })
             .unwrap();
 
@@ -1832,6 +2586,7 @@ mod tests {
                     text: "test".to_string(),
                 },
                 isolation: None,
+                policy: Default::default(),
             })
             .unwrap();
 
@@ -1877,6 +2632,7 @@ mod tests {
                     text: "oneshot".to_string(),
                 },
                 isolation: None,
+                policy: Default::default(),
             })
             .unwrap();
 
@@ -2027,10 +2783,10 @@ mod tests {
         ));
 
         // Too many fields
-        assert!(CronExpr::parse("* * * * * *").is_err());
+        assert!(CronExpr::parse("* * * * * * *").is_err());
         assert!(matches!(
-            CronExpr::parse("* * * * * *"),
-            Err(CronParseError::WrongFieldCount(6))
+            CronExpr::parse("* * * * * * *"),
+            Err(CronParseError::WrongFieldCount(7))
         ));
 
         // Empty string
@@ -2196,6 +2952,200 @@ mod tests {
         assert_eq!(expr.days_of_week.len(), 7); // 0-6
     }
 
+    #[test]
+    fn test_cron_expr_seconds_field() {
+        // "*/15 * * * * *" — every 15 seconds
+        let expr = CronExpr::parse("*/15 * * * * *").unwrap();
+        let expected: BTreeSet<u32> = [0, 15, 30, 45].into();
+        assert_eq!(expr.seconds, expected);
+        let now = Utc.with_ymd_and_hms(2025, 6, 15, 10, 0, 20).unwrap();
+        let next = expr.next_after(&now).unwrap();
+        assert_eq!(next, Utc.with_ymd_and_hms(2025, 6, 15, 10, 0, 30).unwrap());
+
+        // Five-field expressions fire on the minute.
+        let expr = CronExpr::parse("* * * * *").unwrap();
+        assert_eq!(expr.seconds, BTreeSet::from([0]));
+        assert_eq!(expr.next_after(&now).unwrap(), utc(2025, 6, 15, 10, 1));
+    }
+
+    #[test]
+    fn test_cron_expr_macros() {
+        let now = utc(2025, 6, 15, 10, 30);
+        let next = |expr: &str| CronExpr::parse(expr).unwrap().next_after(&now);
+        assert_eq!(next("@hourly"), Some(utc(2025, 6, 15, 11, 0)));
+        assert_eq!(next("@daily"), Some(utc(2025, 6, 16, 0, 0)));
+        assert_eq!(next("@midnight"), Some(utc(2025, 6, 16, 0, 0)));
+        // 2025-06-22 is a Sunday
+        assert_eq!(next("@weekly"), Some(utc(2025, 6, 22, 0, 0)));
+        assert_eq!(next("@monthly"), Some(utc(2025, 7, 1, 0, 0)));
+        assert_eq!(next("@yearly"), Some(utc(2026, 1, 1, 0, 0)));
+        assert_eq!(next("@ANNUALLY"), Some(utc(2026, 1, 1, 0, 0)));
+
+        // @reboot parses but never fires on a schedule
+        let reboot = CronExpr::parse("@reboot").unwrap();
+        assert!(reboot.reboot);
+        assert!(reboot.next_after(&now).is_none());
+        assert!(!reboot.matches(&now));
+
+        assert_eq!(
+            CronExpr::parse("@fortnightly").unwrap_err(),
+            CronParseError::UnknownMacro("@fortnightly".to_string())
+        );
+    }
+
+    #[test]
+    fn test_cron_expr_named_months_and_days() {
+        let expr = CronExpr::parse("0 9 * jan-mar,DEC MON-FRI").unwrap();
+        assert_eq!(expr.months, BTreeSet::from([1, 2, 3, 12]));
+        assert_eq!(expr.days_of_week, BTreeSet::from([1, 2, 3, 4, 5]));
+
+        let expr = CronExpr::parse("0 0 ? * Sun").unwrap();
+        assert_eq!(expr.days_of_week, BTreeSet::from([0]));
+        assert_eq!(expr.days_of_month.len(), 31);
+
+        assert!(CronExpr::parse("0 0 * FOO *").is_err());
+        assert!(CronExpr::parse("0 0 * * MON-FOO").is_err());
+    }
+
+    #[test]
+    fn test_cron_expr_last_day_of_month() {
+        let expr = CronExpr::parse("0 0 L * *").unwrap();
+        assert_eq!(
+            expr.next_after(&utc(2025, 2, 10, 0, 0)),
+            Some(utc(2025, 2, 28, 0, 0))
+        );
+        assert_eq!(
+            expr.next_after(&utc(2024, 2, 10, 0, 0)),
+            Some(utc(2024, 2, 29, 0, 0))
+        );
+        assert_eq!(
+            expr.next_after(&utc(2025, 4, 30, 0, 0)),
+            Some(utc(2025, 5, 31, 0, 0))
+        );
+        // Like `LW`, `L` is case-insensitive
+        assert_eq!(
+            CronExpr::parse("0 0 l * *")
+                .unwrap()
+                .next_after(&utc(2025, 2, 10, 0, 0)),
+            Some(utc(2025, 2, 28, 0, 0))
+        );
+    }
+
+    #[test]
+    fn test_cron_expr_nearest_weekday() {
+        // 2025-03-15 is a Saturday → Friday the 14th
+        let expr = CronExpr::parse("0 0 15W * *").unwrap();
+        assert_eq!(
+            expr.next_after(&utc(2025, 3, 1, 0, 0)),
+            Some(utc(2025, 3, 14, 0, 0))
+        );
+        // 2025-06-15 is a Sunday → Monday the 16th
+        assert_eq!(
+            expr.next_after(&utc(2025, 6, 1, 0, 0)),
+            Some(utc(2025, 6, 16, 0, 0))
+        );
+
+        // 2025-11-01 is a Saturday; 1W stays in the month → Monday the 3rd
+        let expr = CronExpr::parse("0 0 1W * *").unwrap();
+        assert_eq!(
+            expr.next_after(&utc(2025, 10, 15, 0, 0)),
+            Some(utc(2025, 11, 3, 0, 0))
+        );
+
+        // Last weekday: 2025-08-31 is a Sunday → Friday the 29th
+        let expr = CronExpr::parse("0 0 LW * *").unwrap();
+        assert_eq!(
+            expr.next_after(&utc(2025, 8, 1, 0, 0)),
+            Some(utc(2025, 8, 29, 0, 0))
+        );
+
+        assert!(CronExpr::parse("0 0 32W * *").is_err());
+    }
+
+    #[test]
+    fn test_cron_expr_last_friday_of_month() {
+        let expr = CronExpr::parse("0 17 * * 5L").unwrap();
+        let now = utc(2025, 1, 1, 0, 0);
+        assert_eq!(expr.next_after(&now), Some(utc(2025, 1, 31, 17, 0)));
+        assert_eq!(
+            expr.next_after(&utc(2025, 1, 31, 17, 0)),
+            Some(utc(2025, 2, 28, 17, 0))
+        );
+        assert_eq!(
+            expr.next_after(&utc(2025, 3, 1, 0, 0)),
+            Some(utc(2025, 3, 28, 17, 0))
+        );
+        assert_eq!(
+            CronExpr::parse("0 17 * * FRIL").unwrap().next_after(&now),
+            Some(utc(2025, 1, 31, 17, 0))
+        );
+        assert_eq!(
+            CronExpr::parse("0 17 * * 5l").unwrap().next_after(&now),
+            Some(utc(2025, 1, 31, 17, 0))
+        );
+    }
+
+    #[test]
+    fn test_cron_expr_nth_weekday() {
+        // Second Tuesday: 2025-06-10
+        let expr = CronExpr::parse("0 10 * * 2#2").unwrap();
+        assert_eq!(
+            expr.next_after(&utc(2025, 6, 1, 0, 0)),
+            Some(utc(2025, 6, 10, 10, 0))
+        );
+        // Fifth Monday: June 30, then skips July and August, which have
+        // only four
+        let expr = CronExpr::parse("0 0 * * MON#5").unwrap();
+        assert_eq!(
+            expr.next_after(&utc(2025, 6, 1, 0, 0)),
+            Some(utc(2025, 6, 30, 0, 0))
+        );
+        assert_eq!(
+            expr.next_after(&utc(2025, 7, 1, 0, 0)),
+            Some(utc(2025, 9, 29, 0, 0))
+        );
+
+        assert!(CronExpr::parse("0 0 * * 1#6").is_err());
+        assert!(CronExpr::parse("0 0 * * 1#0").is_err());
+    }
+
+    #[test]
+    fn test_cron_expr_next_after_tz_fixed_hour() {
+        // Europe/London springs forward on 2025-03-30 at 01:00 GMT.
+        let tz: chrono_tz::Tz = "Europe/London".parse().unwrap();
+        let expr = CronExpr::parse("30 1 * * *").unwrap();
+        let next = expr.next_after_tz(&utc(2025, 3, 29, 12, 0), &tz);
+        // 01:30 does not exist; runs at the jump (02:00 BST = 01:00 UTC)
+        assert_eq!(next, Some(utc(2025, 3, 30, 1, 0)));
+        // 01:30 on Oct 26 happens twice; only the first (BST) runs
+        let next = expr.next_after_tz(&utc(2025, 10, 26, 0, 0), &tz);
+        assert_eq!(next, Some(utc(2025, 10, 26, 0, 30)));
+        let next = expr.next_after_tz(&next.unwrap(), &tz);
+        assert_eq!(next, Some(utc(2025, 10, 27, 1, 30)));
+    }
+
+    #[test]
+    fn test_load_runs_reboot_jobs_at_startup() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("jobs.json");
+
+        let s1 = CronScheduler::new(true, Some(path.clone()));
+        let mut create = test_job_create("on boot");
+        create.schedule = CronSchedule::Cron {
+            expr: "@reboot".to_string(),
+            tz: None,
+        };
+        let job = s1.add(create).unwrap();
+        assert_eq!(job.state.next_run_at_ms, None);
+
+        let before = now_ms();
+        let s2 = CronScheduler::new(true, Some(path));
+        s2.load();
+        let loaded = s2.get(&job.id).unwrap();
+        assert!(loaded.state.next_run_at_ms.unwrap() >= before);
+        assert_eq!(s2.get_due_job_ids(), vec![job.id]);
+    }
+
     #[test]
     fn test_cron_job_limit_enforced_under_concurrent_access() {
         use std::sync::Arc;
@@ -2218,6 +3168,7 @@ mod tests {
                         text: "t".to_string(),
                     },
                     isolation: None,
+                    policy: Default::default(),
                 })
                 .unwrap();
         }
@@ -2244,6 +3195,7 @@ mod tests {
                             text: "t".to_string(),
                         },
                         isolation: None,
+                        policy: Default::default(),
                     })
                 })
             })
@@ -2284,6 +3236,7 @@ mod tests {
                 text: format!("hello from {name}"),
             },
             isolation: None,
+            policy: Default::default(),
         }
     }
 
@@ -2464,8 +3417,8 @@ mod tests {
     #[test]
     fn test_compute_next_run_cron_spring_forward_skip() {
         // US spring forward 2025: Mar 9 at 2:00 AM ET clocks jump to 3:00 AM.
-        // "30 2 * * *" with tz=America/New_York — 2:30 AM doesn't exist on Mar 9.
-        // The next valid 2:30 AM should be Mar 10.
+        // "30 2 * * *" with tz=America/New_York — 2:30 AM doesn't exist on Mar 9,
+        // so the job runs when the clocks jump: 3:00 AM EDT → 07:00 UTC.
         let now_dt = utc(2025, 3, 9, 0, 0);
         let now = now_dt.timestamp_millis() as u64;
         let schedule = CronSchedule::Cron {
@@ -2473,11 +3426,39 @@ mod tests {
             tz: Some("America/New_York".to_string()),
         };
         let next = compute_next_run(&schedule, now).unwrap();
-        // Mar 10 is EDT (UTC-4): 2:30 AM EDT → 06:30 UTC.
+        let expected = utc(2025, 3, 9, 7, 0).timestamp_millis() as u64;
+        assert_eq!(next, expected);
+
+        // The day after, 2:30 AM EDT exists again → 06:30 UTC.
+        let next = compute_next_run(&schedule, next).unwrap();
         let expected = utc(2025, 3, 10, 6, 30).timestamp_millis() as u64;
         assert_eq!(next, expected);
     }
 
+    #[test]
+    fn test_compute_next_run_cron_wildcard_hour_follows_elapsed_time() {
+        // "15 * * * *" with tz=America/New_York follows elapsed time:
+        // the repeated 1:15 AM on Nov 2, 2025 runs in both passes...
+        let schedule = CronSchedule::Cron {
+            expr: "15 * * * *".to_string(),
+            tz: Some("America/New_York".to_string()),
+        };
+        let first = compute_next_run(&schedule, utc(2025, 11, 2, 5, 0).timestamp_millis() as u64);
+        assert_eq!(
+            first,
+            Some(utc(2025, 11, 2, 5, 15).timestamp_millis() as u64)
+        );
+        let second = compute_next_run(&schedule, first.unwrap());
+        assert_eq!(
+            second,
+            Some(utc(2025, 11, 2, 6, 15).timestamp_millis() as u64)
+        );
+
+        // ...and the skipped 2:15 AM on Mar 9, 2025 does not run at all.
+        let next = compute_next_run(&schedule, utc(2025, 3, 9, 6, 15).timestamp_millis() as u64);
+        assert_eq!(next, Some(utc(2025, 3, 9, 7, 15).timestamp_millis() as u64));
+    }
+
     #[test]
     fn test_compute_next_run_cron_fall_back_first_only() {
         // US fall back 2025: Nov 2 at 2:00 AM ET clocks go back to 1:00 AM.
@@ -2573,4 +3554,289 @@ mod tests {
         assert_eq!(loaded.state.last_status, Some(CronJobStatus::Ok));
         assert_eq!(loaded.state.last_duration_ms, Some(100));
     }
+
+    // ---------------------------------------------------------------
+    // Run policy tests
+    // ---------------------------------------------------------------
+
+    fn policy_job_create(name: &str, policy: CronRunPolicy) -> CronJobCreate {
+        CronJobCreate {
+            policy,
+            ..test_job_create(name)
+        }
+    }
+
+    /// Helper: persist jobs whose next run fell due `missed_ms` ago, then
+    /// load them into a fresh scheduler.
+    fn load_after_outage(
+        policies: &[CronRunPolicy],
+        missed_ms: u64,
+    ) -> (CronScheduler, Vec<String>) {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cron").join("jobs.json");
+
+        let s1 = CronScheduler::new(true, Some(path.clone()));
+        let ids: Vec<String> = policies
+            .iter()
+            .enumerate()
+            .map(|(i, p)| {
+                s1.add(policy_job_create(&format!("job-{i}"), p.clone()))
+                    .unwrap()
+                    .id
+            })
+            .collect();
+        {
+            let mut jobs = s1.jobs.write();
+            for job in jobs.iter_mut() {
+                job.state.next_run_at_ms = Some(now_ms() - missed_ms);
+            }
+        }
+        s1.flush_to_disk();
+
+        let s2 = CronScheduler::new(true, Some(path));
+        s2.load();
+        (s2, ids)
+    }
+
+    fn catch_up(mode: CronCatchUpMode, max_lag_ms: Option<u64>) -> CronRunPolicy {
+        CronRunPolicy {
+            catch_up: Some(CronCatchUp { mode, max_lag_ms }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_load_applies_catch_up_policy() {
+        // Every minute, last due 10.25 minutes ago: 11 missed firings. The
+        // last one is 15s old, so the lag window is not at a boundary.
+        let (s, ids) = load_after_outage(
+            &[
+                CronRunPolicy::default(),
+                catch_up(CronCatchUpMode::RunOnce, None),
+                catch_up(CronCatchUpMode::RunAllMissed, None),
+                catch_up(CronCatchUpMode::RunAllMissed, Some(150_000)),
+            ],
+            615_000,
+        );
+        let now = now_ms();
+
+        // Skip: nothing to replay, next run in the future
+        let skip = s.get(&ids[0]).unwrap();
+        assert_eq!(skip.state.catch_up_runs, 0);
+        assert!(skip.state.next_run_at_ms.unwrap() > now);
+        let log = s.runs(Some(&ids[0]), None);
+        assert_eq!(log.len(), 1);
+        assert_eq!(log[0].action, "skipped");
+        assert_eq!(log[0].skip_reason, Some(CronSkipReason::Missed));
+        assert_eq!(log[0].summary.as_deref(), Some("11 missed run(s) skipped"));
+
+        // Run once: due now, one replay
+        let once = s.get(&ids[1]).unwrap();
+        assert_eq!(once.state.catch_up_runs, 1);
+        assert!(once.state.next_run_at_ms.unwrap() <= now);
+        assert_eq!(
+            s.runs(Some(&ids[1]), None)[0].summary.as_deref(),
+            Some("10 missed run(s) skipped")
+        );
+
+        // Run all: capped replays
+        let all = s.get(&ids[2]).unwrap();
+        assert_eq!(all.state.catch_up_runs, MAX_PENDING_RUNS);
+
+        // Run all within 150s: only the last 3 firings
+        let lagged = s.get(&ids[3]).unwrap();
+        assert_eq!(lagged.state.catch_up_runs, 3);
+        assert_eq!(
+            s.runs(Some(&ids[3]), None)[0].summary.as_deref(),
+            Some("8 missed run(s) skipped")
+        );
+    }
+
+    #[test]
+    fn test_catch_up_runs_replay_back_to_back() {
+        let (s, ids) = load_after_outage(&[catch_up(CronCatchUpMode::RunAllMissed, None)], 150_000);
+        let id = &ids[0];
+        assert_eq!(s.get(id).unwrap().state.catch_up_runs, 3);
+
+        for remaining in (0..3).rev() {
+            assert!(s.get_due_job_ids().contains(id));
+            let result = s.run_due(id).unwrap();
+            assert!(result.ran);
+            // Replays wait for the running run
+            assert!(!s.run_due(id).unwrap().ran);
+            s.mark_run_finished(id, CronJobStatus::Ok, 1, None).unwrap();
+            assert_eq!(s.get(id).unwrap().state.catch_up_runs, remaining);
+        }
+        assert!(s.get_due_job_ids().is_empty());
+
+        let log = s.runs(Some(id), None);
+        let triggers: Vec<_> = log.iter().filter_map(|e| e.trigger).collect();
+        assert_eq!(triggers, vec![CronRunTrigger::CatchUp; 3]);
+    }
+
+    fn make_due(s: &CronScheduler, id: &str) {
+        let mut jobs = s.jobs.write();
+        let job = jobs.iter_mut().find(|j| j.id == id).unwrap();
+        job.state.next_run_at_ms = Some(1);
+    }
+
+    #[test]
+    fn test_overlapping_firing_skipped_by_default() {
+        let s = CronScheduler::in_memory();
+        let id = s.add(test_job_create("overlap")).unwrap().id;
+        make_due(&s, &id);
+        assert!(s.run_due(&id).unwrap().ran);
+
+        make_due(&s, &id);
+        let result = s.run_due(&id).unwrap();
+        assert!(!result.ran);
+        assert_eq!(result.reason, Some(CronRunReason::Running));
+        assert!(s.get(&id).unwrap().state.next_run_at_ms.unwrap() > now_ms());
+
+        let log = s.runs(Some(&id), None);
+        assert_eq!(log[0].action, "skipped");
+        assert_eq!(log[0].skip_reason, Some(CronSkipReason::Overlap));
+    }
+
+    #[test]
+    fn test_overlapping_firing_queued() {
+        let s = CronScheduler::in_memory();
+        let policy = CronRunPolicy {
+            concurrency: Some(CronConcurrency::Queue),
+            ..Default::default()
+        };
+        let id = s.add(policy_job_create("queue", policy)).unwrap().id;
+        make_due(&s, &id);
+        assert!(s.run_due(&id).unwrap().ran);
+
+        make_due(&s, &id);
+        assert_eq!(s.run_due(&id).unwrap().reason, Some(CronRunReason::Queued));
+        assert_eq!(s.get(&id).unwrap().state.queued_runs, 1);
+        // Held while the first run is going
+        assert!(s.get_due_job_ids().is_empty());
+
+        s.mark_run_finished(&id, CronJobStatus::Ok, 1, None)
+            .unwrap();
+        assert_eq!(s.get_due_job_ids(), vec![id.clone()]);
+        assert!(s.run_due(&id).unwrap().ran);
+        let job = s.get(&id).unwrap();
+        assert_eq!(job.state.queued_runs, 0);
+        assert_eq!(job.state.last_trigger, Some(CronRunTrigger::Queued));
+    }
+
+    #[test]
+    fn test_overlapping_firing_allowed() {
+        let s = CronScheduler::in_memory();
+        let policy = CronRunPolicy {
+            concurrency: Some(CronConcurrency::Allow),
+            ..Default::default()
+        };
+        let id = s.add(policy_job_create("allow", policy)).unwrap().id;
+        make_due(&s, &id);
+        assert!(s.run_due(&id).unwrap().ran);
+        make_due(&s, &id);
+        assert!(s.run_due(&id).unwrap().ran);
+        assert_eq!(s.get(&id).unwrap().state.active_runs, 2);
+
+        s.mark_run_finished(&id, CronJobStatus::Ok, 1, None)
+            .unwrap();
+        assert!(s.get(&id).unwrap().state.running_at_ms.is_some());
+        s.mark_run_finished(&id, CronJobStatus::Ok, 1, None)
+            .unwrap();
+        assert!(s.get(&id).unwrap().state.running_at_ms.is_none());
+    }
+
+    #[test]
+    fn test_failed_run_retried_with_backoff() {
+        let s = CronScheduler::in_memory();
+        let policy = CronRunPolicy {
+            max_retries: Some(2),
+            retry_backoff_ms: Some(10_000),
+            ..Default::default()
+        };
+        let create = CronJobCreate {
+            schedule: CronSchedule::At { at_ms: 1 },
+            delete_after_run: Some(true),
+            ..policy_job_create("flaky", policy)
+        };
+        let id = s.add(create).unwrap().id;
+        s.run(&id, Some(CronRunMode::Force)).unwrap();
+
+        for (attempt, backoff) in [(1, 10_000), (2, 20_000)] {
+            let before = now_ms();
+            s.mark_run_finished(&id, CronJobStatus::Error, 1, Some("boom".into()))
+                .unwrap();
+            let job = s.get(&id).expect("one-shot kept while retrying");
+            assert_eq!(job.state.retry_count, attempt);
+            let next = job.state.next_run_at_ms.unwrap();
+            assert!(next >= before + backoff && next <= now_ms() + backoff);
+
+            make_due(&s, &id);
+            assert!(s.run_due(&id).unwrap().ran);
+            assert_eq!(
+                s.get(&id).unwrap().state.last_trigger,
+                Some(CronRunTrigger::Retry)
+            );
+        }
+
+        // Out of retries: the one-shot is deleted
+        s.mark_run_finished(&id, CronJobStatus::Error, 1, Some("boom".into()))
+            .unwrap();
+        assert!(s.get(&id).is_none());
+        let log = s.runs(Some(&id), None);
+        assert_eq!(log.len(), 3);
+        assert_eq!(log[0].trigger, Some(CronRunTrigger::Manual));
+        assert_eq!(log[2].trigger, Some(CronRunTrigger::Retry));
+    }
+
+    #[test]
+    fn test_retry_delay_doubles_and_caps() {
+        let policy = CronRunPolicy::default();
+        assert_eq!(policy.retry_delay_ms(1), DEFAULT_RETRY_BACKOFF_MS);
+        assert_eq!(policy.retry_delay_ms(2), DEFAULT_RETRY_BACKOFF_MS * 2);
+        assert_eq!(policy.retry_delay_ms(40), MAX_RETRY_BACKOFF_MS);
+    }
+
+    #[test]
+    fn test_jitter_delays_next_run_within_window() {
+        let s = CronScheduler::in_memory();
+        let policy = CronRunPolicy {
+            jitter_ms: Some(5_000),
+            ..Default::default()
+        };
+        let create = CronJobCreate {
+            schedule: CronSchedule::Every {
+                every_ms: 60_000,
+                anchor_ms: Some(0),
+            },
+            ..policy_job_create("jittered", policy)
+        };
+        for _ in 0..20 {
+            let job = s.add(create.clone()).unwrap();
+            let base = compute_next_run(&job.schedule, job.created_at_ms).unwrap();
+            let next = job.state.next_run_at_ms.unwrap();
+            assert!(next >= base && next <= base + 5_000);
+        }
+    }
+
+    #[test]
+    fn test_policy_round_trips_as_job_keys() {
+        let policy = CronRunPolicy {
+            catch_up: Some(CronCatchUp {
+                mode: CronCatchUpMode::RunOnce,
+                max_lag_ms: None,
+            }),
+            concurrency: Some(CronConcurrency::Queue),
+            ..Default::default()
+        };
+        let job = CronScheduler::in_memory()
+            .add(policy_job_create("keys", policy.clone()))
+            .unwrap();
+        let value = serde_json::to_value(&job).unwrap();
+        assert_eq!(value["catchUp"]["mode"], "run-once");
+        assert_eq!(value["concurrency"], "queue");
+        assert!(value.get("jitterMs").is_none());
+        let back: CronJob = serde_json::from_value(value).unwrap();
+        assert_eq!(back.policy, policy);
+    }
 }
@@ -1,28 +1,39 @@
 //! Cron tick loop.
 //!
-//! Background task that periodically scans for due cron jobs and executes them.
+//! Background task that periodically scans for due cron jobs and executes them,
+//! applying each job's overlap, catch-up and retry policies. The loop wakes
+//! at the next job's due time when that comes before the regular interval,
+//! so schedules finer than the interval (e.g. a seconds field) run on time.
+//! Jobs with `wakeMode: "next-heartbeat"` are held until the heartbeat loop
+//! calls [`run_deferred_jobs`], or run on the next tick when heartbeats are
+//! off.
 
 use std::sync::Arc;
 use std::time::Duration;
 
 use crate::cron::executor::{execute_payload, CronRunOutcome};
-use crate::cron::{CronJobStatus, CronRunMode};
+use crate::cron::{now_ms, CronJob, CronJobStatus, CronWakeMode};
 use crate::server::ws::{AgentRunStatus, WsServerState};
 
+/// Shortest wait between scans, so jobs that stay due (e.g. held by their
+/// overlap policy) do not spin the loop.
+const MIN_TICK: Duration = Duration::from_secs(1);
+
 /// Run the cron tick loop.
 ///
-/// Periodically checks for due jobs and spawns their execution.
-/// Stops when a shutdown signal is received.
+/// Checks for due jobs every `interval`, or sooner when a job is due
+/// before then, and spawns their execution. Stops when a shutdown signal
+/// is received.
 pub async fn cron_tick_loop(
     state: Arc<WsServerState>,
     interval: Duration,
     mut shutdown: tokio::sync::watch::Receiver<bool>,
 ) {
-    let mut ticker = tokio::time::interval(interval);
+    let mut wait = Duration::ZERO;
 
     loop {
         tokio::select! {
-            _ = ticker.tick() => {}
+            _ = tokio::time::sleep(wait) => {}
             _ = shutdown.changed() => break,
         }
 
@@ -36,7 +47,7 @@ pub async fn cron_tick_loop(
         let due_ids = state.cron_scheduler.get_due_job_ids();
 
         for job_id in due_ids {
-            let result = match state.cron_scheduler.run(&job_id, Some(CronRunMode::Due)) {
+            let result = match state.cron_scheduler.run_due(&job_id) {
                 Ok(r) => r,
                 Err(e) => {
                     tracing::warn!(job_id = %job_id, error = %e, "cron run error");
@@ -44,72 +55,106 @@ pub async fn cron_tick_loop(
                 }
             };
 
-            if !result.ran {
+            if !result.ran || result.payload.is_none() {
                 continue;
             }
+            let Some(job) = state.cron_scheduler.get(&result.job_id) else {
+                continue;
+            };
+            if job.wake_mode == CronWakeMode::NextHeartbeat && state.heartbeats_enabled() {
+                state.cron_scheduler.defer_until_heartbeat(&job.id);
+                continue;
+            }
+            spawn_job(state.clone(), job);
+        }
+
+        // With heartbeats off there is no heartbeat to wait for.
+        if !state.heartbeats_enabled() {
+            run_deferred_jobs(&state);
+        }
+
+        wait = tick_wait(
+            state.cron_scheduler.status().next_run_at_ms,
+            now_ms(),
+            interval,
+        );
+    }
+}
+
+/// How long to sleep before the next scan: until the earliest due job, but
+/// no longer than `interval` and no shorter than [`MIN_TICK`].
+fn tick_wait(next_run_at_ms: Option<u64>, now: u64, interval: Duration) -> Duration {
+    let until_due = next_run_at_ms.map_or(interval, |next| {
+        Duration::from_millis(next.saturating_sub(now))
+    });
+    until_due.min(interval).max(MIN_TICK.min(interval))
+}
 
-            if let Some(payload) = result.payload {
-                let state = state.clone();
-                let job_id = result.job_id.clone();
-                tokio::spawn(async move {
-                    let start = std::time::Instant::now();
-                    let outcome = execute_payload(&job_id, &payload, &state).await;
-
-                    // For AgentTurn payloads, wait for the agent run to actually complete
-                    // before reporting the cron job status.
-                    let (status, error) = match outcome {
-                        Ok(CronRunOutcome::Spawned { run_id }) => {
-                            let waiter = {
-                                let mut registry = state.agent_run_registry.lock();
-                                registry.add_waiter(&run_id)
-                            };
-                            if let Some(rx) = waiter {
-                                match rx.await {
-                                    Ok(result) => match result.status {
-                                        AgentRunStatus::Completed => (CronJobStatus::Ok, None),
-                                        AgentRunStatus::Failed => {
-                                            (CronJobStatus::Error, result.error)
-                                        }
-                                        AgentRunStatus::Cancelled => (
-                                            CronJobStatus::Error,
-                                            Some("agent run cancelled".to_string()),
-                                        ),
-                                        _ => (CronJobStatus::Ok, None),
-                                    },
-                                    Err(_) => (
-                                        CronJobStatus::Error,
-                                        Some("agent run waiter dropped".to_string()),
-                                    ),
-                                }
-                            } else {
-                                // Run not found in registry (shouldn't happen)
-                                (
-                                    CronJobStatus::Error,
-                                    Some("agent run not found".to_string()),
-                                )
-                            }
-                        }
-                        Ok(CronRunOutcome::Broadcast) => (CronJobStatus::Ok, None),
-                        Err(e) => (CronJobStatus::Error, Some(e)),
-                    };
-
-                    let duration_ms = start.elapsed().as_millis() as u64;
-
-                    if let Err(e) =
-                        state
-                            .cron_scheduler
-                            .mark_run_finished(&job_id, status, duration_ms, error)
-                    {
-                        tracing::warn!(
-                            job_id = %job_id,
-                            error = %e,
-                            "failed to mark cron run finished"
-                        );
+/// Run the jobs held for a heartbeat. Called by the heartbeat loop after
+/// each heartbeat.
+pub fn run_deferred_jobs(state: &Arc<WsServerState>) {
+    for job_id in state.cron_scheduler.take_deferred() {
+        if let Some(job) = state.cron_scheduler.get(&job_id) {
+            spawn_job(state.clone(), job);
+        }
+    }
+}
+
+/// Execute a started job's payload in the background and record the result.
+fn spawn_job(state: Arc<WsServerState>, job: CronJob) {
+    tokio::spawn(async move {
+        let start = std::time::Instant::now();
+        let outcome = execute_payload(&job, &state).await;
+
+        // For AgentTurn payloads, wait for the agent run to actually complete
+        // before reporting the cron job status.
+        let (status, error) = match outcome {
+            Ok(CronRunOutcome::Spawned { run_id }) => {
+                let waiter = {
+                    let mut registry = state.agent_run_registry.lock();
+                    registry.add_waiter(&run_id)
+                };
+                if let Some(rx) = waiter {
+                    match rx.await {
+                        Ok(result) => match result.status {
+                            AgentRunStatus::Completed => (CronJobStatus::Ok, None),
+                            AgentRunStatus::Failed => (CronJobStatus::Error, result.error),
+                            AgentRunStatus::Cancelled => (
+                                CronJobStatus::Error,
+                                Some("agent run cancelled".to_string()),
+                            ),
+                            _ => (CronJobStatus::Ok, None),
+                        },
+                        Err(_) => (
+                            CronJobStatus::Error,
+                            Some("agent run waiter dropped".to_string()),
+                        ),
                     }
-                });
+                } else {
+                    // Run not found in registry (shouldn't happen)
+                    (
+                        CronJobStatus::Error,
+                        Some("agent run not found".to_string()),
+                    )
+                }
             }
+            Ok(CronRunOutcome::Broadcast) => (CronJobStatus::Ok, None),
+            Err(e) => (CronJobStatus::Error, Some(e)),
+        };
+
+        let duration_ms = start.elapsed().as_millis() as u64;
+
+        if let Err(e) = state
+            .cron_scheduler
+            .mark_run_finished(&job.id, status, duration_ms, error)
+        {
+            tracing::warn!(
+                job_id = %job.id,
+                error = %e,
+                "failed to mark cron run finished"
+            );
         }
-    }
+    });
 }
 
 #[cfg(test)]
@@ -120,6 +165,23 @@ mod tests {
     use std::sync::Arc;
     use std::time::Duration;
 
+    #[test]
+    fn test_tick_wait_wakes_for_next_due_job() {
+        let interval = Duration::from_secs(10);
+        assert_eq!(tick_wait(None, 1_000, interval), interval);
+        assert_eq!(tick_wait(Some(60_000), 1_000, interval), interval);
+        assert_eq!(
+            tick_wait(Some(4_000), 1_000, interval),
+            Duration::from_secs(3)
+        );
+        // Overdue jobs do not spin the loop
+        assert_eq!(tick_wait(Some(500), 1_000, interval), MIN_TICK);
+        assert_eq!(
+            tick_wait(Some(500), 1_000, Duration::from_millis(50)),
+            Duration::from_millis(50)
+        );
+    }
+
     #[tokio::test]
     async fn test_tick_loop_shutdown() {
         let state = Arc::new(WsServerState::new(WsServerConfig::default()));
@@ -161,6 +223,7 @@ mod tests {
                     text: "tick test".to_string(),
                 },
                 isolation: None,
+                policy: Default::default(),
             })
             .unwrap();
 
@@ -191,4 +254,101 @@ mod tests {
         let runs = state.cron_scheduler.runs(None, None);
         assert!(!runs.is_empty(), "expected at least one run log entry");
     }
+
+    #[tokio::test]
+    async fn test_next_heartbeat_jobs_wait_for_heartbeat() {
+        let state = Arc::new(WsServerState::new(WsServerConfig::default()));
+        state.set_heartbeat_settings(true, 60_000);
+
+        let job = state
+            .cron_scheduler
+            .add(CronJobCreate {
+                name: "Heartbeat Job".to_string(),
+                agent_id: None,
+                description: None,
+                enabled: true,
+                delete_after_run: None,
+                schedule: CronSchedule::Every {
+                    every_ms: 1_000_000_000,
+                    anchor_ms: Some(1),
+                },
+                session_target: CronSessionTarget::Main,
+                wake_mode: CronWakeMode::NextHeartbeat,
+                payload: CronPayload::SystemEvent {
+                    text: "heartbeat test".to_string(),
+                },
+                isolation: None,
+                policy: Default::default(),
+            })
+            .unwrap();
+        {
+            let mut jobs = state.cron_scheduler.jobs.write();
+            let j = jobs.iter_mut().find(|j| j.id == job.id).unwrap();
+            j.state.next_run_at_ms = Some(1);
+        }
+
+        let (shutdown_tx, shutdown_rx) = tokio::sync::watch::channel(false);
+        let st = state.clone();
+        let handle = tokio::spawn(async move {
+            cron_tick_loop(st, Duration::from_millis(50), shutdown_rx).await;
+        });
+        tokio::time::sleep(Duration::from_millis(200)).await;
+
+        // Started but held: no run has finished, and the job is not re-fired
+        assert!(state.cron_scheduler.runs(None, None).is_empty());
+        let held = state.cron_scheduler.get(&job.id).unwrap();
+        assert!(held.state.running_at_ms.is_some());
+
+        // The heartbeat loop releases it
+        run_deferred_jobs(&state);
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert_eq!(state.cron_scheduler.runs(None, None).len(), 1);
+        assert!(state.cron_scheduler.take_deferred().is_empty());
+
+        let _ = shutdown_tx.send(true);
+        let _ = handle.await;
+    }
+
+    #[tokio::test]
+    async fn test_next_heartbeat_jobs_run_when_heartbeats_off() {
+        let state = Arc::new(WsServerState::new(WsServerConfig::default()));
+        let job = state
+            .cron_scheduler
+            .add(CronJobCreate {
+                name: "Heartbeat Job".to_string(),
+                agent_id: None,
+                description: None,
+                enabled: true,
+                delete_after_run: None,
+                schedule: CronSchedule::Every {
+                    every_ms: 1_000_000_000,
+                    anchor_ms: Some(1),
+                },
+                session_target: CronSessionTarget::Main,
+                wake_mode: CronWakeMode::NextHeartbeat,
+                payload: CronPayload::SystemEvent {
+                    text: "heartbeat test".to_string(),
+                },
+                isolation: None,
+                policy: Default::default(),
+            })
+            .unwrap();
+        {
+            let mut jobs = state.cron_scheduler.jobs.write();
+            let j = jobs.iter_mut().find(|j| j.id == job.id).unwrap();
+            j.state.next_run_at_ms = Some(1);
+        }
+
+        let (shutdown_tx, shutdown_rx) = tokio::sync::watch::channel(false);
+        let st = state.clone();
+        let handle = tokio::spawn(async move {
+            cron_tick_loop(st, Duration::from_millis(50), shutdown_rx).await;
+        });
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        let _ = shutdown_tx.send(true);
+        let _ = handle.await;
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        assert!(!state.cron_scheduler.runs(None, None).is_empty());
+    }
 }
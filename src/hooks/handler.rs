@@ -75,6 +75,8 @@ pub struct AgentRequest {
     pub deliver: Option<bool>,
     pub wake_mode: Option<String>,
     pub session_key: Option<String>,
+    /// Agent to run, overriding `agents.routes`.
+    pub agent_id: Option<String>,
     pub timeout_seconds: Option<f64>,
     pub allow_unsafe_external_content: Option<bool>,
     pub venice_parameters: Option<serde_json::Value>,
@@ -147,6 +149,7 @@ pub struct ValidatedAgentRequest {
     pub deliver: bool,
     pub wake_mode: WakeMode,
     pub session_key: Option<String>,
+    pub agent_id: Option<String>,
     pub timeout_seconds: Option<u32>,
     pub allow_unsafe_external_content: bool,
     pub venice_parameters: Option<serde_json::Value>,
@@ -210,6 +213,12 @@ pub fn validate_agent_request(
     }
 
     let session_key = req.session_key.clone();
+    let agent_id = req
+        .agent_id
+        .as_deref()
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(String::from);
 
     // Parse timeout_seconds (floor to integer, ignore invalid values)
     let timeout_seconds = req.timeout_seconds.and_then(|t| {
@@ -235,6 +244,7 @@ pub fn validate_agent_request(
             .map(|s| WakeMode::from_str_lenient(s))
             .unwrap_or(WakeMode::Now),
         session_key,
+        agent_id,
         timeout_seconds,
         allow_unsafe_external_content: req.allow_unsafe_external_content.unwrap_or(false),
         venice_parameters: match &req.venice_parameters {
@@ -350,6 +360,7 @@ mod tests {
             deliver: None,
             wake_mode: None,
             session_key: None,
+            agent_id: None,
             timeout_seconds: None,
             allow_unsafe_external_content: None,
             venice_parameters: None,
@@ -375,6 +386,7 @@ mod tests {
             deliver: None,
             wake_mode: None,
             session_key: None,
+            agent_id: None,
             timeout_seconds: None,
             allow_unsafe_external_content: None,
             venice_parameters: None,
@@ -395,6 +407,7 @@ mod tests {
             deliver: None,
             wake_mode: None,
             session_key: None,
+            agent_id: None,
             timeout_seconds: None,
             allow_unsafe_external_content: None,
             venice_parameters: None,
@@ -415,6 +428,7 @@ mod tests {
             deliver: None,
             wake_mode: None,
             session_key: None,
+            agent_id: None,
             timeout_seconds: None,
             allow_unsafe_external_content: None,
             venice_parameters: None,
@@ -435,6 +449,7 @@ mod tests {
             deliver: None,
             wake_mode: None,
             session_key: None,
+            agent_id: None,
             timeout_seconds: None,
             allow_unsafe_external_content: None,
             venice_parameters: None,
@@ -455,6 +470,7 @@ mod tests {
             deliver: None,
             wake_mode: None,
             session_key: None,
+            agent_id: None,
             timeout_seconds: None,
             allow_unsafe_external_content: None,
             venice_parameters: None,
@@ -475,6 +491,7 @@ mod tests {
             deliver: None,
             wake_mode: None,
             session_key: None,
+            agent_id: None,
             timeout_seconds: Some(60.7),
             allow_unsafe_external_content: None,
             venice_parameters: None,
@@ -495,6 +512,7 @@ mod tests {
             deliver: None,
             wake_mode: None,
             session_key: None,
+            agent_id: None,
             timeout_seconds: Some(-1.0),
             allow_unsafe_external_content: None,
             venice_parameters: None,
@@ -515,6 +533,7 @@ mod tests {
             deliver: None,
             wake_mode: None,
             session_key: Some("my-custom-session".to_string()),
+            agent_id: None,
             timeout_seconds: None,
             allow_unsafe_external_content: None,
             venice_parameters: None,
@@ -523,6 +542,31 @@ mod tests {
         assert_eq!(result.session_key.as_deref(), Some("my-custom-session"));
     }
 
+    #[test]
+    fn test_validate_agent_request_agent_id() {
+        let mut req = AgentRequest {
+            message: Some("test".to_string()),
+            name: None,
+            channel: None,
+            to: None,
+            model: None,
+            thinking: None,
+            deliver: None,
+            wake_mode: None,
+            session_key: None,
+            agent_id: Some("  support ".to_string()),
+            timeout_seconds: None,
+            allow_unsafe_external_content: None,
+            venice_parameters: None,
+        };
+        let result = validate_agent_request(&req, &[]).unwrap();
+        assert_eq!(result.agent_id.as_deref(), Some("support"));
+
+        req.agent_id = Some("   ".to_string());
+        let result = validate_agent_request(&req, &[]).unwrap();
+        assert!(result.agent_id.is_none());
+    }
+
     #[test]
     fn test_validate_agent_request_deliver_false() {
         let req = AgentRequest {
@@ -535,6 +579,7 @@ mod tests {
             deliver: Some(false),
             wake_mode: None,
             session_key: None,
+            agent_id: None,
             timeout_seconds: None,
             allow_unsafe_external_content: None,
             venice_parameters: None,
@@ -555,6 +600,7 @@ mod tests {
             deliver: None,
             wake_mode: None,
             session_key: None,
+            agent_id: None,
             timeout_seconds: None,
             allow_unsafe_external_content: None,
             venice_parameters: Some(serde_json::json!("not an object")),
@@ -575,6 +621,7 @@ mod tests {
             deliver: None,
             wake_mode: None,
             session_key: None,
+            agent_id: None,
             timeout_seconds: None,
             allow_unsafe_external_content: None,
             venice_parameters: Some(serde_json::json!({"enable_web_search": "on"})),
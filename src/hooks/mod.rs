@@ -8,6 +8,7 @@
 pub mod auth;
 pub mod handler;
 pub mod registry;
+pub mod transform;
 
 pub use auth::{extract_hooks_token, timing_safe_equal, validate_hooks_token};
 pub use handler::{
@@ -18,3 +19,4 @@ pub use registry::{
     create_registry as create_hook_registry, HookAction, HookMapping, HookMappingContext,
     HookMappingError, HookMappingResult, HookMatch, HookRegistry, HookTransform,
 };
+pub use transform::HookTransformOutput;
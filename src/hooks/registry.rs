@@ -6,9 +6,14 @@
 use parking_lot::RwLock;
 use regex::Regex;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use std::collections::HashMap;
+use std::path::Path;
 use std::sync::Arc;
 
+use super::transform::{self, HookTransformOutput, DEFAULT_TRANSFORMS_DIR};
+use crate::plugins::runtime::{TransformModule, TransformRuntime};
+
 /// Hook mapping action type
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
 #[serde(rename_all = "lowercase")]
@@ -34,7 +39,7 @@ pub struct HookMatch {
 pub struct HookTransform {
     /// Path to transform module (relative to transforms dir)
     pub module: Option<String>,
-    /// Export name (default: 'default' or 'transform')
+    /// Export name (default: 'transform')
     pub export: Option<String>,
 }
 
@@ -177,6 +182,8 @@ pub struct HookRegistry {
     mappings: RwLock<Vec<HookMapping>>,
     /// Preset mappings by name
     presets: RwLock<HashMap<String, HookMapping>>,
+    /// Loaded transform modules by `<module>#<export>`
+    transforms: RwLock<HashMap<String, TransformModule>>,
 }
 
 impl Default for HookRegistry {
@@ -221,7 +228,84 @@ impl HookRegistry {
         Self {
             mappings: RwLock::new(Vec::new()),
             presets: RwLock::new(presets),
+            transforms: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Register the mappings and presets configured under `gateway.hooks`
+    /// and load their transforms.
+    ///
+    /// `transformsDir` is resolved against `state_dir` when relative.
+    /// Returns the loaded transforms; any invalid mapping, preset or
+    /// transform fails the whole load.
+    pub fn configure(&self, cfg: &Value, state_dir: &Path) -> Result<Vec<String>, String> {
+        let hooks = cfg.get("gateway").and_then(|g| g.get("hooks"));
+        let Some(hooks) = hooks else {
+            return Ok(Vec::new());
+        };
+
+        // Custom mappings take precedence over presets
+        if let Some(mappings) = hooks.get("mappings") {
+            let mappings: Vec<HookMapping> = serde_json::from_value(mappings.clone())
+                .map_err(|e| format!("gateway.hooks.mappings: {}", e))?;
+            self.register_all(mappings);
         }
+        if let Some(presets) = hooks.get("presets") {
+            let presets: Vec<String> = serde_json::from_value(presets.clone())
+                .map_err(|e| format!("gateway.hooks.presets: {}", e))?;
+            for name in presets {
+                if !self.enable_preset(&name) {
+                    return Err(format!("gateway.hooks.presets: unknown preset '{}'", name));
+                }
+            }
+        }
+
+        let dir = hooks
+            .get("transformsDir")
+            .and_then(|v| v.as_str())
+            .map(|d| state_dir.join(d))
+            .unwrap_or_else(|| state_dir.join(DEFAULT_TRANSFORMS_DIR));
+        self.load_transforms(&dir)
+    }
+
+    /// Compile and type-check the transform of every registered mapping.
+    ///
+    /// Modules are resolved inside `dir`. Returns the loaded transforms as
+    /// `<module>#<export>`.
+    pub fn load_transforms(&self, dir: &Path) -> Result<Vec<String>, String> {
+        let mut runtime: Option<TransformRuntime> = None;
+        let mut loaded = HashMap::new();
+        for mapping in self.mappings.read().iter() {
+            let Some(config) = &mapping.transform else {
+                continue;
+            };
+            let id = mapping.id.as_deref().unwrap_or("<unnamed>");
+            let key = config
+                .key()
+                .ok_or_else(|| format!("hook mapping '{}': transform.module is required", id))?;
+            if loaded.contains_key(&key) {
+                continue;
+            }
+            let runtime = match &mut runtime {
+                Some(runtime) => runtime,
+                None => runtime.insert(TransformRuntime::shared().map_err(|e| e.to_string())?),
+            };
+            let module = transform::load_transform(runtime, dir, config)
+                .map_err(|e| format!("hook mapping '{}': {}", id, e))?;
+            loaded.insert(key, module);
+        }
+
+        let mut keys: Vec<String> = loaded.keys().cloned().collect();
+        keys.sort();
+        *self.transforms.write() = loaded;
+        Ok(keys)
+    }
+
+    /// List the loaded transforms as `<module>#<export>`
+    pub fn transforms(&self) -> Vec<String> {
+        let mut keys: Vec<String> = self.transforms.read().keys().cloned().collect();
+        keys.sort();
+        keys
     }
 
     /// Register a hook mapping
@@ -274,14 +358,36 @@ impl HookRegistry {
     }
 
     /// Evaluate a hook mapping against a context
+    ///
+    /// A mapping with a transform runs it first; its output overrides the
+    /// mapping's settings and templates, and a `null` output skips the
+    /// webhook.
     pub fn evaluate(
         &self,
         mapping: &HookMapping,
         ctx: &HookMappingContext,
     ) -> Result<HookMappingResult, HookMappingError> {
-        match mapping.action {
+        let overrides = match &mapping.transform {
+            Some(config) => {
+                let module = config
+                    .key()
+                    .and_then(|key| self.transforms.read().get(&key).cloned())
+                    .ok_or_else(|| {
+                        HookMappingError::TransformError("transform not loaded".to_string())
+                    })?;
+                match transform::run_transform(&module, ctx)? {
+                    Some(output) => output,
+                    None => return Ok(HookMappingResult::Skip),
+                }
+            }
+            None => HookTransformOutput::default(),
+        };
+
+        match overrides.action.unwrap_or(mapping.action) {
             HookAction::Wake => {
-                let text = if let Some(template) = &mapping.text_template {
+                let text = if let Some(text) = overrides.text {
+                    text
+                } else if let Some(template) = &mapping.text_template {
                     evaluate_template(template, ctx)?
                 } else {
                     // Default: stringify payload
@@ -294,14 +400,16 @@ impl HookRegistry {
 
                 Ok(HookMappingResult::Wake {
                     text,
-                    mode: mapping
-                        .wake_mode
-                        .clone()
+                    mode: overrides
+                        .mode
+                        .or_else(|| mapping.wake_mode.clone())
                         .unwrap_or_else(|| "now".to_string()),
                 })
             }
             HookAction::Agent => {
-                let message = if let Some(template) = &mapping.message_template {
+                let message = if let Some(message) = overrides.message {
+                    message
+                } else if let Some(template) = &mapping.message_template {
                     evaluate_template(template, ctx)?
                 } else {
                     return Err(HookMappingError::MissingMessageTemplate);
@@ -311,7 +419,9 @@ impl HookRegistry {
                     return Err(HookMappingError::EmptyMessage);
                 }
 
-                let session_key = if let Some(template) = &mapping.session_key {
+                let session_key = if let Some(session_key) = overrides.session_key {
+                    session_key
+                } else if let Some(template) = &mapping.session_key {
                     evaluate_template(template, ctx)?
                 } else {
                     format!("hook:{}:{}", ctx.path, uuid::Uuid::new_v4())
@@ -319,21 +429,24 @@ impl HookRegistry {
 
                 Ok(HookMappingResult::Agent {
                     message,
-                    name: mapping.name.clone().unwrap_or_else(|| "Hook".to_string()),
-                    channel: mapping
+                    name: overrides
+                        .name
+                        .or_else(|| mapping.name.clone())
+                        .unwrap_or_else(|| "Hook".to_string()),
+                    channel: overrides
                         .channel
-                        .clone()
+                        .or_else(|| mapping.channel.clone())
                         .unwrap_or_else(|| "last".to_string()),
-                    to: mapping.to.clone(),
-                    model: mapping.model.clone(),
-                    thinking: mapping.thinking.clone(),
-                    deliver: mapping.deliver.unwrap_or(true),
-                    wake_mode: mapping
+                    to: overrides.to.or_else(|| mapping.to.clone()),
+                    model: overrides.model.or_else(|| mapping.model.clone()),
+                    thinking: overrides.thinking.or_else(|| mapping.thinking.clone()),
+                    deliver: overrides.deliver.or(mapping.deliver).unwrap_or(true),
+                    wake_mode: overrides
                         .wake_mode
-                        .clone()
+                        .or_else(|| mapping.wake_mode.clone())
                         .unwrap_or_else(|| "now".to_string()),
                     session_key,
-                    timeout_seconds: mapping.timeout_seconds,
+                    timeout_seconds: overrides.timeout_seconds.or(mapping.timeout_seconds),
                     allow_unsafe_external_content: mapping
                         .allow_unsafe_external_content
                         .unwrap_or(false),
@@ -874,4 +987,245 @@ mod tests {
             _ => panic!("Expected Agent result"),
         }
     }
+
+    /// A transform component whose export returns `output` (as `ok` or
+    /// `err`), or loops forever when `output` is `None`.
+    fn transform_wat(export: &str, output: Option<(bool, &str)>) -> String {
+        let body = match output {
+            Some((ok, text)) => format!(
+                "(i32.store8 (i32.const 0) (i32.const {}))
+                 (i32.store (i32.const 4) (i32.const 16))
+                 (i32.store (i32.const 8) (i32.const {}))
+                 (i32.const 0)",
+                if ok { 0 } else { 1 },
+                text.len()
+            ),
+            None => "(loop $l (br $l)) (i32.const 0)".to_string(),
+        };
+        let data = output.map_or(String::new(), |(_, text)| text.replace('"', "\\\""));
+        format!(
+            r#"(component
+                (core module $m
+                    (memory (export "memory") 1)
+                    (global $next (mut i32) (i32.const 1024))
+                    (func (export "realloc") (param i32 i32 i32 i32) (result i32)
+                        (local $p i32)
+                        (local.set $p (global.get $next))
+                        (global.set $next (i32.add (global.get $next) (local.get 3)))
+                        (local.get $p))
+                    (data (i32.const 16) "{data}")
+                    (func (export "run") (param i32 i32) (result i32) {body}))
+                (core instance $i (instantiate $m))
+                (func (export "{export}") (param "request" string) (result (result string (error string)))
+                    (canon lift (core func $i "run") (memory $i "memory") (realloc (func $i "realloc")))))"#
+        )
+    }
+
+    /// A registry with one transform mapping loaded from a temp dir.
+    fn registry_with_transform(mapping: HookMapping, wat: &str) -> HookRegistry {
+        let dir = tempfile::tempdir().unwrap();
+        let module = mapping.transform.as_ref().unwrap().module.clone().unwrap();
+        std::fs::write(dir.path().join(module), wat).unwrap();
+        let registry = HookRegistry::new();
+        registry.register(mapping);
+        registry.load_transforms(dir.path()).unwrap();
+        registry
+    }
+
+    fn transform_mapping(id: &str, export: Option<&str>) -> HookMapping {
+        let mut mapping = HookMapping::new(id).with_path(id);
+        mapping.transform = Some(HookTransform {
+            module: Some(format!("{id}.wasm")),
+            export: export.map(String::from),
+        });
+        mapping
+    }
+
+    fn transform_ctx(path: &str) -> HookMappingContext {
+        HookMappingContext {
+            path: path.to_string(),
+            headers: HashMap::new(),
+            payload: json!({ "action": "opened" }),
+            query: None,
+            now: "2024-01-01T00:00:00Z".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_transform_returns_wake_action() {
+        let wat = transform_wat(
+            "transform",
+            Some((
+                true,
+                r#"{"action":"wake","text":"PR opened","mode":"next-heartbeat"}"#,
+            )),
+        );
+        let registry = registry_with_transform(transform_mapping("github", None), &wat);
+        assert_eq!(registry.transforms(), vec!["github.wasm#transform"]);
+
+        let mapping = registry.list().remove(0);
+        match registry
+            .evaluate(&mapping, &transform_ctx("github"))
+            .unwrap()
+        {
+            HookMappingResult::Wake { text, mode } => {
+                assert_eq!(text, "PR opened");
+                assert_eq!(mode, "next-heartbeat");
+            }
+            other => panic!("Expected Wake result, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_transform_overrides_agent_mapping() {
+        let wat = transform_wat(
+            "on-event",
+            Some((
+                true,
+                r#"{"message":"Payment failed","sessionKey":"hook:stripe:cus_1"}"#,
+            )),
+        );
+        let mut mapping = transform_mapping("stripe", Some("on-event"));
+        mapping.name = Some("Stripe".to_string());
+        mapping.message_template = Some("unused {{action}}".to_string());
+        mapping.allow_unsafe_external_content = Some(true);
+        let registry = registry_with_transform(mapping.clone(), &wat);
+
+        match registry
+            .evaluate(&mapping, &transform_ctx("stripe"))
+            .unwrap()
+        {
+            HookMappingResult::Agent {
+                message,
+                name,
+                session_key,
+                allow_unsafe_external_content,
+                ..
+            } => {
+                assert_eq!(message, "Payment failed");
+                assert_eq!(name, "Stripe");
+                assert_eq!(session_key, "hook:stripe:cus_1");
+                assert!(allow_unsafe_external_content);
+            }
+            other => panic!("Expected Agent result, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_transform_null_skips() {
+        let wat = transform_wat("transform", Some((true, "null")));
+        let mapping = transform_mapping("ha", None);
+        let registry = registry_with_transform(mapping.clone(), &wat);
+        assert!(matches!(
+            registry.evaluate(&mapping, &transform_ctx("ha")),
+            Ok(HookMappingResult::Skip)
+        ));
+    }
+
+    #[test]
+    fn test_transform_errors() {
+        // Guest error
+        let mapping = transform_mapping("err", None);
+        let wat = transform_wat("transform", Some((false, "unsupported event")));
+        let registry = registry_with_transform(mapping.clone(), &wat);
+        assert_eq!(
+            registry
+                .evaluate(&mapping, &transform_ctx("err"))
+                .unwrap_err(),
+            HookMappingError::TransformError("unsupported event".to_string())
+        );
+
+        // Output that is not an action
+        let mapping = transform_mapping("bad", None);
+        let wat = transform_wat("transform", Some((true, r#"{"deliver":"yes"}"#)));
+        let registry = registry_with_transform(mapping.clone(), &wat);
+        assert!(matches!(
+            registry.evaluate(&mapping, &transform_ctx("bad")),
+            Err(HookMappingError::TransformError(msg)) if msg.contains("invalid output")
+        ));
+
+        // Runaway loop is stopped by the fuel budget
+        let mapping = transform_mapping("spin", None);
+        let registry = registry_with_transform(mapping.clone(), &transform_wat("transform", None));
+        assert!(matches!(
+            registry.evaluate(&mapping, &transform_ctx("spin")),
+            Err(HookMappingError::TransformError(msg)) if msg.contains("fuel exhausted")
+        ));
+    }
+
+    #[test]
+    fn test_load_transforms_validates_modules() {
+        let dir = tempfile::tempdir().unwrap();
+        let registry = HookRegistry::new();
+
+        // Missing file
+        registry.register(transform_mapping("github", None));
+        let err = registry.load_transforms(dir.path()).unwrap_err();
+        assert!(err.contains("hook mapping 'github'"), "{err}");
+        assert!(err.contains("failed to read transform"), "{err}");
+
+        // Export missing
+        std::fs::write(
+            dir.path().join("github.wasm"),
+            transform_wat("other", Some((true, "null"))),
+        )
+        .unwrap();
+        let err = registry.load_transforms(dir.path()).unwrap_err();
+        assert!(err.contains("export 'transform'"), "{err}");
+
+        // Modules may not import host functions
+        std::fs::write(
+            dir.path().join("github.wasm"),
+            r#"(component (import "host" (instance)))"#,
+        )
+        .unwrap();
+        assert!(registry.load_transforms(dir.path()).is_err());
+
+        // Escaping the transforms dir
+        let registry = HookRegistry::new();
+        let mut mapping = HookMapping::new("escape");
+        mapping.transform = Some(HookTransform {
+            module: Some("../github.wasm".to_string()),
+            export: None,
+        });
+        registry.register(mapping);
+        assert!(registry
+            .load_transforms(&dir.path().join("transforms"))
+            .unwrap_err()
+            .contains("relative path"));
+    }
+
+    #[test]
+    fn test_configure_from_config() {
+        let state_dir = tempfile::tempdir().unwrap();
+        let transforms = state_dir.path().join("hooks/transforms");
+        std::fs::create_dir_all(&transforms).unwrap();
+        std::fs::write(
+            transforms.join("github.wasm"),
+            transform_wat("transform", Some((true, "null"))),
+        )
+        .unwrap();
+
+        let cfg = json!({
+            "gateway": { "hooks": {
+                "presets": ["gmail"],
+                "mappings": [
+                    { "id": "github", "match": { "path": "github" },
+                      "transform": { "module": "github.wasm" } },
+                    { "id": "plain", "messageTemplate": "{{text}}" }
+                ]
+            } }
+        });
+        let registry = HookRegistry::new();
+        let loaded = registry.configure(&cfg, state_dir.path()).unwrap();
+        assert_eq!(loaded, vec!["github.wasm#transform"]);
+        let ids: Vec<_> = registry.list().into_iter().filter_map(|m| m.id).collect();
+        assert_eq!(ids, ["github", "plain", "preset:gmail"]);
+
+        let cfg = json!({ "gateway": { "hooks": { "presets": ["nope"] } } });
+        assert!(HookRegistry::new()
+            .configure(&cfg, state_dir.path())
+            .unwrap_err()
+            .contains("unknown preset"));
+    }
 }
@@ -0,0 +1,219 @@
+//! Hook mapping transforms
+//!
+//! A mapping's `transform` is a WASM component that turns the webhook
+//! request into the action to take, for payloads too involved for `{{expr}}`
+//! templates (GitHub, Stripe, Home Assistant). Transforms run in the
+//! import-free [`TransformRuntime`] sandbox. Modules live in the transforms
+//! directory (`gateway.hooks.transformsDir`, default
+//! `<state dir>/hooks/transforms`) and are compiled and type-checked when
+//! the config is loaded.
+
+use std::path::{Component as PathComponent, Path, PathBuf};
+
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use super::registry::{HookAction, HookMappingContext, HookMappingError, HookTransform};
+use crate::plugins::runtime::{TransformModule, TransformRuntime};
+
+/// Export called when `transform.export` is not set
+pub const DEFAULT_TRANSFORM_EXPORT: &str = "transform";
+
+/// Transforms directory under the state dir
+pub const DEFAULT_TRANSFORMS_DIR: &str = "hooks/transforms";
+
+/// What a transform returns for a webhook it handles.
+///
+/// Every field is optional and overrides the mapping's own setting or
+/// template. `allowUnsafeExternalContent` can only be set by the mapping.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct HookTransformOutput {
+    /// Action to take instead of the mapping's
+    pub action: Option<HookAction>,
+    /// Wake text
+    pub text: Option<String>,
+    /// Wake mode for a wake action
+    pub mode: Option<String>,
+    /// Agent message
+    pub message: Option<String>,
+    /// Display name
+    pub name: Option<String>,
+    /// Session key
+    pub session_key: Option<String>,
+    /// Target channel
+    pub channel: Option<String>,
+    /// Recipient
+    pub to: Option<String>,
+    /// Model override
+    pub model: Option<String>,
+    /// Thinking level
+    pub thinking: Option<String>,
+    /// Whether to deliver the reply
+    pub deliver: Option<bool>,
+    /// Wake mode for an agent action
+    pub wake_mode: Option<String>,
+    /// Timeout in seconds
+    pub timeout_seconds: Option<u32>,
+}
+
+impl HookTransform {
+    /// Export to call, defaulting to `transform`
+    pub fn export_name(&self) -> &str {
+        self.export
+            .as_deref()
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .unwrap_or(DEFAULT_TRANSFORM_EXPORT)
+    }
+
+    /// Key identifying the loaded module: `<module>#<export>`
+    pub fn key(&self) -> Option<String> {
+        let module = self.module.as_deref()?.trim();
+        Some(format!("{}#{}", module, self.export_name()))
+    }
+}
+
+/// Resolve a transform module path inside the transforms directory.
+///
+/// Module paths must be relative and may not leave the directory.
+pub fn resolve_module_path(dir: &Path, module: &str) -> Result<PathBuf, String> {
+    let relative = Path::new(module.trim());
+    if relative.as_os_str().is_empty() {
+        return Err("transform.module is required".to_string());
+    }
+    let escapes = relative
+        .components()
+        .any(|c| !matches!(c, PathComponent::Normal(_) | PathComponent::CurDir));
+    if escapes {
+        return Err(format!(
+            "transform module '{}' must be a relative path inside the transforms directory",
+            module
+        ));
+    }
+    Ok(dir.join(relative))
+}
+
+/// Read, compile and type-check a mapping's transform module.
+pub fn load_transform(
+    runtime: &TransformRuntime,
+    dir: &Path,
+    transform: &HookTransform,
+) -> Result<TransformModule, String> {
+    let module = transform.module.as_deref().unwrap_or_default();
+    let path = resolve_module_path(dir, module)?;
+    let wasm = std::fs::read(&path)
+        .map_err(|e| format!("failed to read transform '{}': {}", path.display(), e))?;
+    runtime
+        .load(&wasm, transform.export_name())
+        .map_err(|e| format!("invalid transform '{}': {}", path.display(), e))
+}
+
+/// The request as passed to a transform
+fn transform_request(ctx: &HookMappingContext) -> Value {
+    json!({
+        "path": ctx.path,
+        "headers": ctx.headers,
+        "query": ctx.query,
+        "payload": ctx.payload,
+        "now": ctx.now,
+    })
+}
+
+/// Run a transform on a webhook request.
+///
+/// Returns `None` when the transform returns `null` to skip the webhook.
+pub fn run_transform(
+    module: &TransformModule,
+    ctx: &HookMappingContext,
+) -> Result<Option<HookTransformOutput>, HookMappingError> {
+    let request = transform_request(ctx).to_string();
+    let output = module
+        .call(&request)
+        .map_err(|e| HookMappingError::TransformError(e.to_string()))?
+        .map_err(HookMappingError::TransformError)?;
+
+    let output: Value = serde_json::from_str(&output).map_err(|e| {
+        HookMappingError::TransformError(format!("transform returned invalid JSON: {}", e))
+    })?;
+    if output.is_null() {
+        return Ok(None);
+    }
+    serde_json::from_value(output).map(Some).map_err(|e| {
+        HookMappingError::TransformError(format!("transform returned invalid output: {}", e))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_resolve_module_path() {
+        let dir = Path::new("/state/hooks/transforms");
+        assert_eq!(
+            resolve_module_path(dir, "github.wasm").unwrap(),
+            dir.join("github.wasm")
+        );
+        assert_eq!(
+            resolve_module_path(dir, "./vendor/stripe.wasm").unwrap(),
+            dir.join("./vendor/stripe.wasm")
+        );
+        assert!(resolve_module_path(dir, "../secrets.wasm").is_err());
+        assert!(resolve_module_path(dir, "vendor/../../x.wasm").is_err());
+        assert!(resolve_module_path(dir, "/etc/x.wasm").is_err());
+        assert!(resolve_module_path(dir, "  ").is_err());
+    }
+
+    #[test]
+    fn test_transform_export_and_key() {
+        let transform = HookTransform {
+            module: Some("github.wasm".to_string()),
+            export: None,
+        };
+        assert_eq!(transform.export_name(), "transform");
+        assert_eq!(transform.key().as_deref(), Some("github.wasm#transform"));
+
+        let transform = HookTransform {
+            module: Some("github.wasm".to_string()),
+            export: Some("push".to_string()),
+        };
+        assert_eq!(transform.key().as_deref(), Some("github.wasm#push"));
+        assert_eq!(HookTransform::default().key(), None);
+    }
+
+    #[test]
+    fn test_transform_request_shape() {
+        let mut headers = HashMap::new();
+        headers.insert("x-github-event".to_string(), "push".to_string());
+        let ctx = HookMappingContext {
+            path: "github".to_string(),
+            headers,
+            payload: json!({ "ref": "refs/heads/main" }),
+            query: Some("a=1".to_string()),
+            now: "2024-01-01T00:00:00Z".to_string(),
+        };
+        assert_eq!(
+            transform_request(&ctx),
+            json!({
+                "path": "github",
+                "headers": { "x-github-event": "push" },
+                "query": "a=1",
+                "payload": { "ref": "refs/heads/main" },
+                "now": "2024-01-01T00:00:00Z",
+            })
+        );
+    }
+
+    #[test]
+    fn test_transform_output_rejects_unknown_fields() {
+        let output: HookTransformOutput =
+            serde_json::from_value(json!({ "action": "wake", "text": "hi" })).unwrap();
+        assert_eq!(output.action, Some(HookAction::Wake));
+        assert!(serde_json::from_value::<HookTransformOutput>(
+            json!({ "allowUnsafeExternalContent": true })
+        )
+        .is_err());
+    }
+}
@@ -19,6 +19,7 @@ pub mod hooks;
 pub mod links;
 pub mod logging;
 pub mod media;
+pub mod memory;
 pub mod messages;
 pub mod nodes;
 pub mod plugins;
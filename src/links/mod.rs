@@ -8,6 +8,8 @@
 //! - SSRF-protected fetching via [`MediaFetcher`](crate::media::MediaFetcher)
 //! - HTML-to-text conversion with title and meta description extraction
 //! - LRU cache with TTL-based expiration (default: 1 hour, 100 entries)
+//! - [`LinkContext`] entries stored in a user message's `metadata.links`, so
+//!   fetched content travels with the message without rewriting its text
 //!
 //! # Example
 //!
@@ -34,6 +36,8 @@ use std::time::{SystemTime, UNIX_EPOCH};
 
 use parking_lot::Mutex;
 use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use thiserror::Error;
 
 use crate::media::{FetchConfig, FetchError, MediaFetcher};
@@ -390,6 +394,95 @@ impl LinkUnderstanding {
     }
 }
 
+// ---------------------------------------------------------------------------
+// Session context
+// ---------------------------------------------------------------------------
+
+/// Metadata key for link context on a user `ChatMessage`.
+pub const LINKS_METADATA_KEY: &str = "links";
+
+/// Fetched link content attached to a user message.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LinkContext {
+    pub url: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub text: String,
+}
+
+impl LinkContext {
+    /// Render the link for the model: URL, title and description, then the
+    /// page text.
+    pub fn render(&self) -> String {
+        let mut out = format!("Linked page: {}", self.url);
+        if let Some(title) = &self.title {
+            out.push_str(&format!("\nTitle: {}", title));
+        }
+        if let Some(description) = &self.description {
+            out.push_str(&format!("\nDescription: {}", description));
+        }
+        if !self.text.is_empty() {
+            out.push_str("\n\n");
+            out.push_str(&self.text);
+        }
+        out
+    }
+}
+
+/// Turn link summaries into [`LinkContext`] entries within a byte budget.
+///
+/// Each page's text is cut to `max_bytes_per_link`, and to whatever remains
+/// of `max_total_bytes`; links past the total budget are dropped.
+pub fn link_context(
+    summaries: &[LinkSummary],
+    max_bytes_per_link: usize,
+    max_total_bytes: usize,
+) -> Vec<LinkContext> {
+    let mut remaining = max_total_bytes;
+    let mut links = Vec::new();
+    for summary in summaries {
+        if remaining == 0 {
+            break;
+        }
+        let text = truncate_preview(&summary.text_preview, max_bytes_per_link.min(remaining));
+        remaining = remaining.saturating_sub(text.len());
+        links.push(LinkContext {
+            url: summary.url.clone(),
+            title: summary.title.clone(),
+            description: summary.description.clone(),
+            text,
+        });
+    }
+    links
+}
+
+/// Parse link context out of a message's metadata.
+///
+/// Malformed entries are skipped.
+pub fn links_from_metadata(metadata: Option<&Value>) -> Vec<LinkContext> {
+    metadata
+        .and_then(|m| m.get(LINKS_METADATA_KEY))
+        .and_then(|v| v.as_array())
+        .map(|items| {
+            items
+                .iter()
+                .filter_map(|item| serde_json::from_value(item.clone()).ok())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Build the metadata value for a list of link context entries.
+pub fn links_metadata(links: &[LinkContext]) -> Value {
+    serde_json::json!({
+        LINKS_METADATA_KEY: links,
+    })
+}
+
 // ---------------------------------------------------------------------------
 // HTML processing helpers
 // ---------------------------------------------------------------------------
@@ -567,6 +660,47 @@ fn current_epoch_secs() -> u64 {
 mod tests {
     use super::*;
 
+    // -- Session context --------------------------------------------------
+
+    fn summary(url: &str, text: &str) -> LinkSummary {
+        LinkSummary {
+            url: url.to_string(),
+            title: Some("Title".to_string()),
+            description: None,
+            text_preview: text.to_string(),
+            content_type: "text/html".to_string(),
+            fetched_at: 0,
+        }
+    }
+
+    #[test]
+    fn test_link_context_applies_byte_budgets() {
+        let summaries = vec![
+            summary("https://a.com", &"a".repeat(100)),
+            summary("https://b.com", &"b".repeat(100)),
+            summary("https://c.com", "c"),
+        ];
+        let links = link_context(&summaries, 60, 90);
+        assert_eq!(links.len(), 2);
+        assert!(links[0].text.starts_with(&"a".repeat(60)));
+        // The second link only gets what is left of the total budget
+        assert!(links[1].text.len() < 60);
+        assert!(links[1].text.starts_with('b'));
+    }
+
+    #[test]
+    fn test_links_metadata_round_trip() {
+        let links = link_context(&[summary("https://a.com", "hello")], 100, 100);
+        let metadata = links_metadata(&links);
+        assert_eq!(metadata["links"][0]["url"], "https://a.com");
+        assert_eq!(links_from_metadata(Some(&metadata)), links);
+        assert!(links_from_metadata(None).is_empty());
+
+        let rendered = links[0].render();
+        assert!(rendered.starts_with("Linked page: https://a.com\nTitle: Title"));
+        assert!(rendered.ends_with("\n\nhello"));
+    }
+
     // -- URL extraction ---------------------------------------------------
 
     #[test]
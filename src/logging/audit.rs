@@ -171,6 +171,31 @@ pub enum AuditEvent {
         reasoning: String,
         run_id: String,
     },
+    /// An LLM call failed and the run moved on to the next fallback model.
+    ProviderFailover {
+        run_id: String,
+        from_model: String,
+        to_model: String,
+        reason: String,
+    },
+    /// An inbound sender was checked against its channel access policy.
+    ChannelAccessDecision {
+        channel: String,
+        sender_id: String,
+        mode: String,
+        decision: String,
+    },
+    /// An operator approved or rejected a sender pairing code.
+    ChannelPairingResolved {
+        channel: String,
+        sender_id: String,
+        approved: bool,
+    },
+    /// An operator revoked a previously approved sender.
+    ChannelSenderRevoked {
+        channel: String,
+        sender_id: String,
+    },
 }
 
 impl AuditEvent {
@@ -201,6 +226,10 @@ impl AuditEvent {
             AuditEvent::SessionIntegrityViolation { .. } => "session_integrity_violation",
             AuditEvent::ClassifierBlocked { .. } => "classifier_blocked",
             AuditEvent::ClassifierWarned { .. } => "classifier_warned",
+            AuditEvent::ProviderFailover { .. } => "provider_failover",
+            AuditEvent::ChannelAccessDecision { .. } => "channel_access_decision",
+            AuditEvent::ChannelPairingResolved { .. } => "channel_pairing_resolved",
+            AuditEvent::ChannelSenderRevoked { .. } => "channel_sender_revoked",
         }
     }
 }
@@ -541,6 +570,27 @@ mod tests {
                 reasoning: "r".into(),
                 run_id: "rid".into(),
             },
+            AuditEvent::ProviderFailover {
+                run_id: "rid".into(),
+                from_model: "a".into(),
+                to_model: "b".into(),
+                reason: "r".into(),
+            },
+            AuditEvent::ChannelAccessDecision {
+                channel: "telegram".into(),
+                sender_id: "s".into(),
+                mode: "pairing".into(),
+                decision: "denied".into(),
+            },
+            AuditEvent::ChannelPairingResolved {
+                channel: "telegram".into(),
+                sender_id: "s".into(),
+                approved: true,
+            },
+            AuditEvent::ChannelSenderRevoked {
+                channel: "telegram".into(),
+                sender_id: "s".into(),
+            },
         ];
         let names: Vec<&str> = events.iter().map(|e| e.event_name()).collect();
         assert!(names.iter().all(|n| !n.is_empty()));
@@ -13,8 +13,10 @@ mod discovery;
 mod exec;
 mod gateway;
 mod hooks;
+mod links;
 mod logging;
 mod media;
+mod memory;
 mod messages;
 mod nodes;
 mod plugins;
@@ -89,6 +91,15 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             cli::handle_pair(&url, name.as_deref(), trust).await
         }
 
+        Some(Command::Senders {
+            action,
+            port,
+            host,
+            tls,
+            trust,
+            allow_plaintext,
+        }) => cli::handle_senders(action, &host, port, tls, trust, allow_plaintext).await,
+
         Some(Command::Update { check, version }) => {
             cli::handle_update(check, version.as_deref()).await
         }
@@ -148,9 +159,12 @@ async fn run_server() -> Result<(), Box<dyn std::error::Error>> {
     let plugin_registry = Arc::new(plugins::PluginRegistry::new());
     let tools_registry = Arc::new(plugins::tools::ToolsRegistry::new());
     let hook_registry = Arc::new(hooks::registry::HookRegistry::new());
+    configure_hook_registry(&hook_registry, &cfg, &state_dir)?;
+
+    let _plugin_runtime = load_wasm_plugins(&state_dir, plugin_registry.clone()).await;
 
     let ws_state = server::ws::build_ws_state_from_config().await?;
-    let ws_state = configure_ws_with_llm(ws_state, &cfg)?;
+    let ws_state = configure_ws_with_llm(ws_state, &cfg, &plugin_registry)?;
     let ws_state =
         configure_ws_with_registries(ws_state, tools_registry.clone(), plugin_registry.clone())?;
     let ws_state = register_console_channel(ws_state)?;
@@ -234,12 +248,79 @@ fn resolve_bind_config(
     Ok(server::bind::resolve_bind_with_metadata(&bind_mode, port)?)
 }
 
+/// Register the `gateway.hooks` mappings and presets and load their
+/// transforms. An invalid mapping or transform fails startup.
+fn configure_hook_registry(
+    hook_registry: &hooks::registry::HookRegistry,
+    cfg: &Value,
+    state_dir: &std::path::Path,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let transforms = hook_registry.configure(cfg, state_dir)?;
+    for transform in &transforms {
+        info!(transform = %transform, "Loaded hook transform");
+    }
+    if !hook_registry.is_empty() {
+        info!(
+            "Registered {} hook mapping(s), {} transform(s)",
+            hook_registry.len(),
+            transforms.len()
+        );
+    }
+    Ok(())
+}
+
+/// Load WASM plugins from the skills directory into the shared plugin registry.
+///
+/// The returned runtime must be kept alive for plugin execution timeouts to
+/// keep ticking. Failures are logged and leave the gateway without plugins.
+async fn load_wasm_plugins(
+    state_dir: &std::path::Path,
+    plugin_registry: Arc<plugins::PluginRegistry>,
+) -> Option<plugins::runtime::PluginRuntime<impl credentials::CredentialBackend + 'static>> {
+    let plugins_dir = state_dir.join("skills");
+    if !plugins_dir.exists() {
+        return None;
+    }
+    let loader = match plugins::loader::PluginLoader::new(plugins_dir) {
+        Ok(loader) => Arc::new(loader),
+        Err(e) => {
+            warn!(error = %e, "Failed to create plugin loader");
+            return None;
+        }
+    };
+    if let Err(e) = loader.load_all() {
+        warn!(error = %e, "Failed to load plugins");
+        return None;
+    }
+    let credential_store = match credentials::open_default_store(state_dir.to_path_buf()).await {
+        Ok(store) => Arc::new(store),
+        Err(e) => {
+            warn!(error = %e, "Credential store unavailable; skipping plugin instantiation");
+            return None;
+        }
+    };
+    let runtime = match plugins::runtime::PluginRuntime::new(loader, credential_store) {
+        Ok(runtime) => runtime.with_registry(plugin_registry),
+        Err(e) => {
+            warn!(error = %e, "Failed to create plugin runtime");
+            return None;
+        }
+    };
+    match runtime.load_all().await {
+        Ok(loaded) if !loaded.is_empty() => info!("Loaded {} WASM plugin(s)", loaded.len()),
+        Ok(_) => {}
+        Err(e) => warn!(error = %e, "Failed to instantiate plugins"),
+    }
+    Some(runtime)
+}
+
 /// Configure LLM providers on the WsServerState via the provider factory.
 fn configure_ws_with_llm(
     ws_state: Arc<server::ws::WsServerState>,
     cfg: &Value,
+    plugin_registry: &plugins::PluginRegistry,
 ) -> Result<Arc<server::ws::WsServerState>, Box<dyn std::error::Error>> {
-    match agent::factory::build_providers(cfg)? {
+    match agent::factory::build_providers(cfg, Some(plugin_registry))? {
         Some(multi_provider) => {
             let inner = Arc::try_unwrap(ws_state)
                 .map_err(|_| "WsServerState Arc should have single owner at startup")?;
@@ -24,6 +24,9 @@ pub const DEFAULT_ANTHROPIC_MODEL: &str = "claude-sonnet-4-20250514";
 /// Default model for OpenAI image analysis.
 pub const DEFAULT_OPENAI_VISION_MODEL: &str = "gpt-4o";
 
+/// Default OpenAI model for audio transcription
+pub const DEFAULT_OPENAI_TRANSCRIPTION_MODEL: &str = "whisper-1";
+
 /// Default max tokens for analysis responses.
 pub const DEFAULT_ANALYSIS_MAX_TOKENS: u32 = 1024;
 
@@ -301,6 +304,7 @@ pub struct OpenAiMediaAnalyzer {
     api_key: String,
     base_url: String,
     vision_model: String,
+    transcription_model: String,
     max_tokens: u32,
 }
 
@@ -326,6 +330,7 @@ impl OpenAiMediaAnalyzer {
             api_key,
             base_url: "https://api.openai.com".to_string(),
             vision_model: DEFAULT_OPENAI_VISION_MODEL.to_string(),
+            transcription_model: DEFAULT_OPENAI_TRANSCRIPTION_MODEL.to_string(),
             max_tokens: DEFAULT_ANALYSIS_MAX_TOKENS,
         })
     }
@@ -342,6 +347,12 @@ impl OpenAiMediaAnalyzer {
         self
     }
 
+    /// Set a custom transcription model (default: `whisper-1`).
+    pub fn with_transcription_model(mut self, model: String) -> Self {
+        self.transcription_model = model;
+        self
+    }
+
     /// Set custom max tokens for analysis responses.
     pub fn with_max_tokens(mut self, max_tokens: u32) -> Self {
         self.max_tokens = max_tokens;
@@ -470,7 +481,7 @@ impl MediaAnalyzer for OpenAiMediaAnalyzer {
             .map_err(|e| AnalysisError::ApiRequest(format!("failed to build form part: {e}")))?;
 
         let form = reqwest::multipart::Form::new()
-            .text("model", "whisper-1")
+            .text("model", self.transcription_model.clone())
             .text("response_format", "json")
             .part("file", file_part);
 
@@ -708,9 +719,10 @@ fn audio_mime_to_extension(mime_type: &str) -> &'static str {
     match base {
         "audio/mpeg" | "audio/mp3" => ".mp3",
         "audio/wav" | "audio/x-wav" | "audio/wave" => ".wav",
-        "audio/mp4" | "audio/m4a" | "audio/x-m4a" => ".m4a",
+        // Signal voice notes are AAC in an MP4 container
+        "audio/mp4" | "audio/m4a" | "audio/x-m4a" | "audio/aac" => ".m4a",
         "audio/webm" => ".webm",
-        "audio/ogg" => ".ogg",
+        "audio/ogg" | "audio/opus" => ".ogg",
         "audio/flac" => ".flac",
         _ => ".bin",
     }
@@ -922,8 +934,10 @@ mod tests {
         assert_eq!(audio_mime_to_extension("audio/x-wav"), ".wav");
         assert_eq!(audio_mime_to_extension("audio/mp4"), ".m4a");
         assert_eq!(audio_mime_to_extension("audio/m4a"), ".m4a");
+        assert_eq!(audio_mime_to_extension("audio/aac"), ".m4a");
         assert_eq!(audio_mime_to_extension("audio/webm"), ".webm");
         assert_eq!(audio_mime_to_extension("audio/ogg"), ".ogg");
+        assert_eq!(audio_mime_to_extension("audio/opus"), ".ogg");
         assert_eq!(audio_mime_to_extension("audio/flac"), ".flac");
         assert_eq!(audio_mime_to_extension("audio/unknown"), ".bin");
     }
@@ -0,0 +1,212 @@
+//! Message attachments
+//!
+//! Images and documents attached to a chat message are written to the
+//! [`MediaStore`] and referenced from the message metadata, so session
+//! transcripts never carry inline base64. The references are resolved back
+//! into bytes when the conversation is replayed to a model.
+
+use std::path::PathBuf;
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine as _;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::store::{MediaStore, StoreError};
+
+/// Metadata key under which attachment references are stored.
+pub const ATTACHMENTS_METADATA_KEY: &str = "attachments";
+
+/// Image MIME types accepted by every vision-capable provider.
+pub const SUPPORTED_IMAGE_TYPES: &[&str] = &["image/png", "image/jpeg", "image/gif", "image/webp"];
+
+/// What an attachment is, as far as the model is concerned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AttachmentKind {
+    Image,
+    Document,
+}
+
+impl AttachmentKind {
+    /// Classify a MIME type. Anything that is not a supported image is sent
+    /// as a document.
+    pub fn from_mime(mime: &str) -> Self {
+        if SUPPORTED_IMAGE_TYPES.contains(&mime) {
+            Self::Image
+        } else {
+            Self::Document
+        }
+    }
+}
+
+/// A reference to an attachment held in the media store.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AttachmentRef {
+    #[serde(rename = "type")]
+    pub kind: AttachmentKind,
+    pub media_type: String,
+    pub path: PathBuf,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub size: u64,
+}
+
+impl AttachmentRef {
+    /// Read the referenced file and return its contents as base64.
+    ///
+    /// Returns `None` once the media store has expired the file.
+    pub fn read_base64(&self) -> Option<String> {
+        std::fs::read(&self.path)
+            .ok()
+            .map(|bytes| BASE64.encode(bytes))
+    }
+
+    /// Short human-readable label, e.g. `image "cat.png"` or `application/pdf document`.
+    pub fn label(&self) -> String {
+        let kind = match self.kind {
+            AttachmentKind::Image => "image",
+            AttachmentKind::Document => "document",
+        };
+        match &self.name {
+            Some(name) => format!("{kind} \"{name}\""),
+            None => format!("{} {kind}", self.media_type),
+        }
+    }
+}
+
+/// Write an attachment to the media store and return a reference to it.
+pub async fn store_attachment(
+    store: &MediaStore,
+    bytes: Vec<u8>,
+    media_type: &str,
+    name: Option<String>,
+) -> Result<AttachmentRef, StoreError> {
+    let media_type = normalize_media_type(media_type);
+    let metadata = store.store(bytes, Some(media_type.clone())).await?;
+    Ok(AttachmentRef {
+        kind: AttachmentKind::from_mime(&media_type),
+        media_type,
+        path: metadata.path,
+        name,
+        size: metadata.size,
+    })
+}
+
+/// Decode a base64 payload, accepting an optional `data:<mime>;base64,` prefix.
+pub fn decode_base64_payload(content: &str) -> Result<Vec<u8>, String> {
+    let trimmed = content.trim();
+    let data = match trimmed.strip_prefix("data:") {
+        Some(rest) => rest
+            .split_once(";base64,")
+            .map(|(_, data)| data)
+            .ok_or_else(|| "data URL must be base64-encoded".to_string())?,
+        None => trimmed,
+    };
+    BASE64
+        .decode(data)
+        .map_err(|e| format!("invalid base64 content: {e}"))
+}
+
+/// Parse attachment references out of a message's metadata.
+///
+/// Malformed entries are skipped.
+pub fn attachments_from_metadata(metadata: Option<&Value>) -> Vec<AttachmentRef> {
+    metadata
+        .and_then(|m| m.get(ATTACHMENTS_METADATA_KEY))
+        .and_then(|v| v.as_array())
+        .map(|items| {
+            items
+                .iter()
+                .filter_map(|item| serde_json::from_value(item.clone()).ok())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Build the metadata value for a list of attachment references.
+pub fn attachments_metadata(attachments: &[AttachmentRef]) -> Value {
+    serde_json::json!({
+        ATTACHMENTS_METADATA_KEY: attachments,
+    })
+}
+
+/// Lowercase a MIME type and strip any parameters.
+fn normalize_media_type(media_type: &str) -> String {
+    let base = media_type.split(';').next().unwrap_or("").trim();
+    match base.to_ascii_lowercase().as_str() {
+        "image/jpg" => "image/jpeg".to_string(),
+        other => other.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::media::StoreConfig;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_kind_from_mime() {
+        assert_eq!(
+            AttachmentKind::from_mime("image/png"),
+            AttachmentKind::Image
+        );
+        assert_eq!(
+            AttachmentKind::from_mime("image/svg+xml"),
+            AttachmentKind::Document
+        );
+        assert_eq!(
+            AttachmentKind::from_mime("application/pdf"),
+            AttachmentKind::Document
+        );
+    }
+
+    #[test]
+    fn test_decode_base64_payload() {
+        assert_eq!(decode_base64_payload("aGk=").unwrap(), b"hi");
+        assert_eq!(
+            decode_base64_payload("data:text/plain;base64,aGk=").unwrap(),
+            b"hi"
+        );
+        assert!(decode_base64_payload("data:text/plain,hi").is_err());
+        assert!(decode_base64_payload("not base64!").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_store_and_resolve_attachment() {
+        let temp_dir = tempdir().unwrap();
+        let store = MediaStore::new(StoreConfig::default().with_base_dir(temp_dir.path().into()))
+            .await
+            .unwrap();
+
+        let attachment =
+            store_attachment(&store, b"png".to_vec(), "Image/JPG", Some("cat.jpg".into()))
+                .await
+                .unwrap();
+        assert_eq!(attachment.kind, AttachmentKind::Image);
+        assert_eq!(attachment.media_type, "image/jpeg");
+        assert_eq!(attachment.size, 3);
+        assert_eq!(attachment.read_base64().as_deref(), Some("cG5n"));
+
+        // Round-trips through message metadata without inlining the bytes
+        let metadata = attachments_metadata(std::slice::from_ref(&attachment));
+        assert!(!metadata.to_string().contains("cG5n"));
+        assert_eq!(attachments_from_metadata(Some(&metadata)), vec![attachment]);
+    }
+
+    #[test]
+    fn test_expired_attachment_reads_none() {
+        let attachment = AttachmentRef {
+            kind: AttachmentKind::Document,
+            media_type: "application/pdf".into(),
+            path: PathBuf::from("/nonexistent/carapace/file.pdf"),
+            name: None,
+            size: 0,
+        };
+        assert!(attachment.read_base64().is_none());
+        assert_eq!(attachment.label(), "application/pdf document");
+    }
+}
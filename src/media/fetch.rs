@@ -79,6 +79,9 @@ pub struct FetchConfig {
 
     /// SSRF protection configuration
     pub ssrf_config: SsrfConfig,
+
+    /// Extra request headers (e.g. `Authorization` for private file URLs)
+    pub headers: Vec<(String, String)>,
 }
 
 impl Default for FetchConfig {
@@ -87,6 +90,7 @@ impl Default for FetchConfig {
             max_size: DEFAULT_MAX_SIZE,
             timeout_ms: DEFAULT_FETCH_TIMEOUT_MS,
             ssrf_config: SsrfConfig::default(),
+            headers: Vec::new(),
         }
     }
 }
@@ -109,6 +113,12 @@ impl FetchConfig {
         self.ssrf_config.allow_tailscale = true;
         self
     }
+
+    /// Add a request header
+    pub fn with_header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
 }
 
 /// Media fetcher with SSRF protection
@@ -225,8 +235,11 @@ impl MediaFetcher {
             .map_err(|e| FetchError::HttpRequest(format!("Failed to create HTTP client: {}", e)))?;
 
         // Make the request
-        let response = client
-            .get(url)
+        let mut request = client.get(url);
+        for (name, value) in &config.headers {
+            request = request.header(name.as_str(), value.as_str());
+        }
+        let response = request
             .send()
             .await
             .map_err(|e| FetchError::HttpRequest(format!("Request failed: {}", e)))?;
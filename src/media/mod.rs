@@ -14,6 +14,9 @@
 //!   - Concurrent-safe operations
 //!   - Background cleanup task
 //!
+//! - **Attachments**: Store-backed references for images and documents attached
+//!   to chat messages, resolved back to bytes when replayed to a model
+//!
 //! - **MediaAnalyzer**: Provider-agnostic media analysis via LLM APIs
 //!   - Image analysis via Claude Vision and GPT-4 Vision
 //!   - Audio transcription via OpenAI Whisper
@@ -39,6 +42,7 @@
 //! ```
 
 pub mod analysis;
+pub mod attachments;
 pub mod fetch;
 pub mod store;
 
@@ -47,6 +51,7 @@ pub use analysis::{
     analyze, AnalysisError, AnthropicMediaAnalyzer, MediaAnalysis, MediaAnalyzer, MediaType,
     OpenAiMediaAnalyzer,
 };
+pub use attachments::{AttachmentKind, AttachmentRef};
 pub use fetch::{
     FetchConfig, FetchError, FetchResult, MediaFetcher, DEFAULT_FETCH_TIMEOUT_MS, DEFAULT_MAX_SIZE,
     MAX_FETCH_TIMEOUT_MS, MAX_URL_LENGTH,
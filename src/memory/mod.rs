@@ -0,0 +1,1185 @@
+//! Long-term memory store
+//!
+//! Memories are short notes an agent keeps across sessions.  Every memory
+//! belongs to an agent and is either agent-wide (shared by everyone the agent
+//! talks to) or scoped to a single user.  Memories carry free-form tags and an
+//! optional expiry, and can be searched with BM25 ranking over their key,
+//! tags and content.
+//!
+//! Storage is one JSON file per agent under `<state_dir>/memory/`, named by
+//! the percent-encoded agent ID.  Files in the legacy flat `{ key: value }`
+//! format written by earlier versions of the `memory_*` tools, and files
+//! saved under an older file name, are migrated on load.
+
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, File};
+use std::io::Write as IoWrite;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use uuid::Uuid;
+
+use crate::agent::prompt_guard::tagging::{self, ContentSource};
+use crate::agent::prompt_guard::TaggingConfig;
+
+/// Agent ID used when a run has none.
+pub const DEFAULT_AGENT_ID: &str = "default";
+
+/// Maximum memory key length (bytes)
+pub const MAX_KEY_LEN: usize = 256;
+
+/// Maximum memory content length (bytes)
+pub const MAX_CONTENT_LEN: usize = 32 * 1024;
+
+/// Maximum number of tags on one memory
+pub const MAX_TAGS: usize = 16;
+
+/// Maximum tag length (bytes)
+pub const MAX_TAG_LEN: usize = 64;
+
+/// Maximum number of memories per agent (all users included)
+pub const MAX_ENTRIES_PER_AGENT: usize = 5000;
+
+/// On-disk format version
+const FILE_VERSION: u32 = 1;
+
+/// BM25 term-frequency saturation
+const BM25_K1: f64 = 1.2;
+
+/// BM25 document-length normalization
+const BM25_B: f64 = 0.75;
+
+/// A single memory
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MemoryEntry {
+    /// Unique memory ID
+    pub id: String,
+    /// Owning agent
+    pub agent_id: String,
+    /// Owning user; `None` for agent-wide memories
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user_id: Option<String>,
+    /// Key, unique within the agent/user namespace
+    pub key: String,
+    /// Remembered text
+    pub content: String,
+    /// Normalized (lowercase, deduplicated) tags
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Creation time (Unix ms)
+    pub created_at: u64,
+    /// Last update time (Unix ms)
+    pub updated_at: u64,
+    /// Expiry time (Unix ms); expired memories are hidden and purged
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<u64>,
+}
+
+impl MemoryEntry {
+    /// Whether the memory has expired at `now` (Unix ms).
+    pub fn is_expired(&self, now: u64) -> bool {
+        self.expires_at.is_some_and(|at| at <= now)
+    }
+
+    fn has_tags(&self, tags: &[String]) -> bool {
+        tags.iter().all(|tag| self.tags.contains(tag))
+    }
+}
+
+/// Input for [`MemoryStore::put`]
+#[derive(Debug, Clone, Default)]
+pub struct MemoryWrite {
+    pub agent_id: String,
+    pub user_id: Option<String>,
+    pub key: String,
+    pub content: String,
+    pub tags: Vec<String>,
+    pub expires_at: Option<u64>,
+}
+
+/// Partial update for [`MemoryStore::update`]
+#[derive(Debug, Clone, Default)]
+pub struct MemoryPatch {
+    pub content: Option<String>,
+    pub tags: Option<Vec<String>>,
+    /// `Some(None)` clears the expiry
+    pub expires_at: Option<Option<u64>>,
+}
+
+/// Which user namespaces a query covers
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum MemoryScope {
+    /// Every namespace
+    #[default]
+    Any,
+    /// Agent-wide memories only
+    Agent,
+    /// One user's memories only
+    User(String),
+    /// Agent-wide memories plus the given user's own (what a run can see)
+    Visible(Option<String>),
+}
+
+impl MemoryScope {
+    fn matches(&self, user_id: Option<&str>) -> bool {
+        match self {
+            Self::Any => true,
+            Self::Agent => user_id.is_none(),
+            Self::User(user) => user_id == Some(user.as_str()),
+            Self::Visible(user) => user_id.is_none() || user_id == user.as_deref(),
+        }
+    }
+}
+
+/// Filter for [`MemoryStore::list`] and [`MemoryStore::search`]
+#[derive(Debug, Clone, Default)]
+pub struct MemoryFilter {
+    /// Restrict to one agent
+    pub agent_id: Option<String>,
+    /// Restrict user namespaces
+    pub scope: MemoryScope,
+    /// Require all of these tags
+    pub tags: Vec<String>,
+    /// Include expired memories that have not been purged yet
+    pub include_expired: bool,
+}
+
+impl MemoryFilter {
+    /// Memories visible to a run of `agent_id` talking to `user_id`.
+    pub fn visible_to(agent_id: &str, user_id: Option<&str>) -> Self {
+        Self {
+            agent_id: Some(agent_id.to_string()),
+            scope: MemoryScope::Visible(user_id.map(str::to_string)),
+            ..Default::default()
+        }
+    }
+
+    pub fn with_tags(mut self, tags: Vec<String>) -> Self {
+        self.tags = normalize_tags(tags);
+        self
+    }
+
+    fn matches(&self, entry: &MemoryEntry, now: u64) -> bool {
+        (self.include_expired || !entry.is_expired(now))
+            && self.agent_id.as_ref().is_none_or(|a| *a == entry.agent_id)
+            && self.scope.matches(entry.user_id.as_deref())
+            && entry.has_tags(&self.tags)
+    }
+}
+
+/// A ranked search result
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MemoryHit {
+    #[serde(flatten)]
+    pub entry: MemoryEntry,
+    pub score: f64,
+}
+
+/// Errors from the memory store
+#[derive(Debug, thiserror::Error)]
+pub enum MemoryError {
+    #[error("memory not found: {0}")]
+    NotFound(String),
+    #[error("invalid memory: {0}")]
+    Invalid(String),
+    #[error("memory limit reached ({0} per agent)")]
+    LimitExceeded(usize),
+    #[error("I/O error: {0}")]
+    Io(String),
+    #[error("JSON error: {0}")]
+    Json(String),
+}
+
+/// On-disk file layout
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct MemoryFile {
+    version: u32,
+    #[serde(default)]
+    entries: Vec<MemoryEntry>,
+}
+
+/// Thread-safe memory store with per-agent persistence
+pub struct MemoryStore {
+    /// Memories by agent ID
+    entries: RwLock<HashMap<String, Vec<MemoryEntry>>>,
+    /// Storage directory; `None` for in-memory stores
+    dir: Option<PathBuf>,
+}
+
+impl std::fmt::Debug for MemoryStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MemoryStore")
+            .field("dir", &self.dir)
+            .finish_non_exhaustive()
+    }
+}
+
+impl MemoryStore {
+    /// Create an in-memory only store (for testing)
+    pub fn in_memory() -> Self {
+        Self {
+            entries: RwLock::new(HashMap::new()),
+            dir: None,
+        }
+    }
+
+    /// Open the store in `dir`, loading every agent file in it.
+    ///
+    /// Unreadable or corrupt files are skipped with a warning rather than
+    /// failing startup.
+    pub fn open(dir: PathBuf) -> Self {
+        let store = Self {
+            entries: RwLock::new(HashMap::new()),
+            dir: Some(dir.clone()),
+        };
+        let Ok(read_dir) = fs::read_dir(&dir) else {
+            return store;
+        };
+        let mut migrated = Vec::new();
+        let mut renamed = Vec::new();
+        {
+            let mut entries = store.entries.write();
+            for path in read_dir.flatten().map(|e| e.path()) {
+                if path.extension().and_then(|e| e.to_str()) != Some("json") {
+                    continue;
+                }
+                match load_file(&path) {
+                    Ok((loaded, legacy)) => {
+                        let stem = path.file_stem().and_then(|s| s.to_str());
+                        let named = |e: &MemoryEntry| Some(file_stem(&e.agent_id).as_str()) == stem;
+                        let misnamed = !loaded.iter().all(named);
+                        if legacy || misnamed {
+                            migrated.extend(loaded.iter().map(|e| e.agent_id.clone()));
+                        }
+                        // A file shared by several agents is rewritten for
+                        // the agent it is named after, if any
+                        if misnamed && !loaded.iter().any(named) {
+                            renamed.push(path.clone());
+                        }
+                        for entry in loaded {
+                            entries
+                                .entry(entry.agent_id.clone())
+                                .or_default()
+                                .push(entry);
+                        }
+                    }
+                    Err(e) => {
+                        // Move corrupt files aside so the next save does not
+                        // overwrite them
+                        tracing::warn!(
+                            path = %path.display(),
+                            error = %e,
+                            "skipping unreadable memory file"
+                        );
+                        if matches!(e, MemoryError::Json(_)) {
+                            let backup = path.with_extension(format!("json.corrupt.{}", now_ms()));
+                            if let Err(err) = fs::rename(&path, &backup) {
+                                tracing::warn!(
+                                    path = %path.display(),
+                                    error = %err,
+                                    "failed to backup corrupted memory file"
+                                );
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        let purged = store.purge_expired();
+        migrated.sort();
+        migrated.dedup();
+        let mut saved = true;
+        for agent_id in migrated {
+            tracing::info!(agent_id = %agent_id, "migrated legacy memory file");
+            if let Err(e) = store.save_agent(&agent_id) {
+                tracing::warn!(agent_id = %agent_id, error = %e, "failed to save migrated memory");
+                saved = false;
+            }
+        }
+        // Files written under a sanitized name are only removed once every
+        // memory they held has been saved under its new name
+        for path in renamed.into_iter().filter(|_| saved) {
+            if let Err(e) = fs::remove_file(&path) {
+                tracing::warn!(path = %path.display(), error = %e, "failed to remove old memory file");
+            }
+        }
+        if purged > 0 {
+            tracing::debug!(purged, "purged expired memories on load");
+        }
+        store
+    }
+
+    /// Import legacy flat memory files from `legacy_dir` for agents that have
+    /// no memories yet.  Returns the number of memories imported.  Each legacy
+    /// file is renamed to `<name>.json.migrated` so it is only considered once.
+    pub fn import_legacy_dir(&self, legacy_dir: &Path) -> usize {
+        if self.dir.as_deref() == Some(legacy_dir) {
+            return 0;
+        }
+        let Ok(read_dir) = fs::read_dir(legacy_dir) else {
+            return 0;
+        };
+        let mut imported = 0;
+        for path in read_dir.flatten().map(|e| e.path()) {
+            if path.extension().and_then(|e| e.to_str()) != Some("json") {
+                continue;
+            }
+            let Ok((loaded, true)) = load_file(&path) else {
+                continue;
+            };
+            if let Some(agent_id) = loaded.first().map(|e| e.agent_id.clone()) {
+                let fresh = {
+                    let mut entries = self.entries.write();
+                    let slot = entries.entry(agent_id.clone()).or_default();
+                    let fresh = slot.is_empty();
+                    if fresh {
+                        imported += loaded.len();
+                        *slot = loaded;
+                    }
+                    fresh
+                };
+                if fresh {
+                    if let Err(e) = self.save_agent(&agent_id) {
+                        tracing::warn!(
+                            agent_id = %agent_id,
+                            error = %e,
+                            "failed to save imported memory; keeping legacy file"
+                        );
+                        continue;
+                    }
+                }
+            }
+            let migrated = path.with_extension("json.migrated");
+            if let Err(e) = fs::rename(&path, &migrated) {
+                tracing::warn!(path = %path.display(), error = %e, "failed to rename legacy memory file");
+            }
+        }
+        imported
+    }
+
+    /// Create or replace the memory with the same agent, user and key.
+    pub fn put(&self, write: MemoryWrite) -> Result<MemoryEntry, MemoryError> {
+        let key = write.key.trim().to_string();
+        validate_key(&key)?;
+        validate_content(&write.content)?;
+        let tags = validate_tags(write.tags)?;
+        let agent_id = normalize_agent_id(&write.agent_id);
+        let now = now_ms();
+
+        let entry = {
+            let mut entries = self.entries.write();
+            let agent_entries = entries.entry(agent_id.clone()).or_default();
+            agent_entries.retain(|e| !e.is_expired(now));
+            if let Some(existing) = agent_entries
+                .iter_mut()
+                .find(|e| e.key == key && e.user_id == write.user_id)
+            {
+                existing.content = write.content;
+                existing.tags = tags;
+                existing.expires_at = write.expires_at;
+                existing.updated_at = now;
+                existing.clone()
+            } else {
+                if agent_entries.len() >= MAX_ENTRIES_PER_AGENT {
+                    return Err(MemoryError::LimitExceeded(MAX_ENTRIES_PER_AGENT));
+                }
+                let entry = MemoryEntry {
+                    id: Uuid::new_v4().to_string(),
+                    agent_id: agent_id.clone(),
+                    user_id: write.user_id,
+                    key,
+                    content: write.content,
+                    tags,
+                    created_at: now,
+                    updated_at: now,
+                    expires_at: write.expires_at,
+                };
+                agent_entries.push(entry.clone());
+                entry
+            }
+        };
+        self.save_agent(&agent_id)?;
+        Ok(entry)
+    }
+
+    /// Apply a partial update to the memory with `id`.
+    pub fn update(&self, id: &str, patch: MemoryPatch) -> Result<MemoryEntry, MemoryError> {
+        if let Some(content) = &patch.content {
+            validate_content(content)?;
+        }
+        let tags = patch.tags.map(validate_tags).transpose()?;
+        let entry = {
+            let mut entries = self.entries.write();
+            let entry = entries
+                .values_mut()
+                .flat_map(|v| v.iter_mut())
+                .find(|e| e.id == id)
+                .ok_or_else(|| MemoryError::NotFound(id.to_string()))?;
+            if let Some(content) = patch.content {
+                entry.content = content;
+            }
+            if let Some(tags) = tags {
+                entry.tags = tags;
+            }
+            if let Some(expires_at) = patch.expires_at {
+                entry.expires_at = expires_at;
+            }
+            entry.updated_at = now_ms();
+            entry.clone()
+        };
+        self.save_agent(&entry.agent_id)?;
+        Ok(entry)
+    }
+
+    /// Get a memory by ID (expired memories included).
+    pub fn get(&self, id: &str) -> Option<MemoryEntry> {
+        self.entries
+            .read()
+            .values()
+            .flatten()
+            .find(|e| e.id == id)
+            .cloned()
+    }
+
+    /// Get a live memory by agent, user and key.
+    pub fn get_by_key(
+        &self,
+        agent_id: &str,
+        user_id: Option<&str>,
+        key: &str,
+    ) -> Option<MemoryEntry> {
+        let now = now_ms();
+        let agent_id = normalize_agent_id(agent_id);
+        self.entries.read().get(&agent_id).and_then(|entries| {
+            entries
+                .iter()
+                .find(|e| e.key == key && e.user_id.as_deref() == user_id && !e.is_expired(now))
+                .cloned()
+        })
+    }
+
+    /// Delete a memory by ID.
+    pub fn delete(&self, id: &str) -> Result<MemoryEntry, MemoryError> {
+        let removed = {
+            let mut entries = self.entries.write();
+            entries.values_mut().find_map(|agent_entries| {
+                let pos = agent_entries.iter().position(|e| e.id == id)?;
+                Some(agent_entries.remove(pos))
+            })
+        }
+        .ok_or_else(|| MemoryError::NotFound(id.to_string()))?;
+        self.save_agent(&removed.agent_id)?;
+        Ok(removed)
+    }
+
+    /// List memories matching `filter`, most recently updated first.
+    pub fn list(&self, filter: &MemoryFilter) -> Vec<MemoryEntry> {
+        let now = now_ms();
+        let mut matches: Vec<MemoryEntry> = self
+            .entries
+            .read()
+            .values()
+            .flatten()
+            .filter(|e| filter.matches(e, now))
+            .cloned()
+            .collect();
+        matches.sort_by(|a, b| {
+            b.updated_at
+                .cmp(&a.updated_at)
+                .then_with(|| a.key.cmp(&b.key))
+        });
+        matches
+    }
+
+    /// Full-text search over memories matching `filter`, best match first.
+    ///
+    /// Ranks with BM25 over key, tags and content; key and tag terms count
+    /// twice.  Memories that share no term with the query are not returned.
+    pub fn search(&self, query: &str, filter: &MemoryFilter, limit: usize) -> Vec<MemoryHit> {
+        let query_terms: HashSet<String> = tokenize(query).into_iter().collect();
+        if query_terms.is_empty() || limit == 0 {
+            return Vec::new();
+        }
+        let candidates = self.list(filter);
+        if candidates.is_empty() {
+            return Vec::new();
+        }
+
+        let docs: Vec<HashMap<String, u32>> = candidates.iter().map(term_counts).collect();
+        let total_len: u32 = docs.iter().map(|d| d.values().sum::<u32>()).sum();
+        let avg_len = (total_len as f64 / docs.len() as f64).max(1.0);
+        let n = docs.len() as f64;
+
+        let idf: HashMap<&str, f64> = query_terms
+            .iter()
+            .map(|term| {
+                let df = docs.iter().filter(|d| d.contains_key(term)).count() as f64;
+                (term.as_str(), (1.0 + (n - df + 0.5) / (df + 0.5)).ln())
+            })
+            .collect();
+
+        let mut hits: Vec<MemoryHit> = candidates
+            .into_iter()
+            .zip(docs.iter())
+            .filter_map(|(entry, doc)| {
+                let len = doc.values().sum::<u32>() as f64;
+                let score: f64 = idf
+                    .iter()
+                    .filter_map(|(term, idf)| {
+                        let tf = *doc.get(*term)? as f64;
+                        let norm = BM25_K1 * (1.0 - BM25_B + BM25_B * len / avg_len);
+                        Some(idf * tf * (BM25_K1 + 1.0) / (tf + norm))
+                    })
+                    .sum();
+                (score > 0.0).then_some(MemoryHit { entry, score })
+            })
+            .collect();
+        // `list` already orders by recency, so a stable sort breaks ties by it.
+        hits.sort_by(|a, b| b.score.total_cmp(&a.score));
+        hits.truncate(limit);
+        hits
+    }
+
+    /// Drop expired memories from every agent.  Returns the number removed.
+    pub fn purge_expired(&self) -> usize {
+        let now = now_ms();
+        let mut touched = Vec::new();
+        let mut removed = 0;
+        {
+            let mut entries = self.entries.write();
+            for (agent_id, agent_entries) in entries.iter_mut() {
+                let before = agent_entries.len();
+                agent_entries.retain(|e| !e.is_expired(now));
+                if agent_entries.len() != before {
+                    removed += before - agent_entries.len();
+                    touched.push(agent_id.clone());
+                }
+            }
+        }
+        for agent_id in touched {
+            if let Err(e) = self.save_agent(&agent_id) {
+                tracing::warn!(agent_id = %agent_id, error = %e, "failed to save purged memory");
+            }
+        }
+        removed
+    }
+
+    /// Number of memories per agent (expired memories included).
+    pub fn counts(&self) -> HashMap<String, usize> {
+        self.entries
+            .read()
+            .iter()
+            .filter(|(_, v)| !v.is_empty())
+            .map(|(k, v)| (k.clone(), v.len()))
+            .collect()
+    }
+
+    /// Write the agent's file.
+    fn save_agent(&self, agent_id: &str) -> Result<(), MemoryError> {
+        let Some(dir) = &self.dir else {
+            return Ok(());
+        };
+        let path = dir.join(format!("{}.json", file_stem(agent_id)));
+
+        let mut file = MemoryFile {
+            version: FILE_VERSION,
+            entries: self
+                .entries
+                .read()
+                .get(agent_id)
+                .cloned()
+                .unwrap_or_default(),
+        };
+        file.entries.sort_by_key(|e| e.created_at);
+
+        if file.entries.is_empty() {
+            return match fs::remove_file(&path) {
+                Ok(()) => Ok(()),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+                Err(e) => Err(MemoryError::Io(e.to_string())),
+            };
+        }
+
+        let content =
+            serde_json::to_string_pretty(&file).map_err(|e| MemoryError::Json(e.to_string()))?;
+        fs::create_dir_all(dir).map_err(|e| MemoryError::Io(e.to_string()))?;
+        let temp_path = path.with_extension("tmp");
+        let mut out = File::create(&temp_path).map_err(|e| MemoryError::Io(e.to_string()))?;
+        IoWrite::write_all(&mut out, content.as_bytes())
+            .map_err(|e| MemoryError::Io(e.to_string()))?;
+        out.sync_all().map_err(|e| MemoryError::Io(e.to_string()))?;
+        fs::rename(&temp_path, &path).map_err(|e| MemoryError::Io(e.to_string()))?;
+        Ok(())
+    }
+}
+
+/// Directory used by the `memory_*` tools before memories moved under the
+/// state dir.
+pub fn legacy_memory_dir() -> PathBuf {
+    dirs::home_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join(".config")
+        .join("carapace")
+        .join("memory")
+}
+
+/// Open the memory store under `state_dir`, importing legacy memory files.
+pub fn create_store(state_dir: &Path) -> MemoryStore {
+    let store = MemoryStore::open(state_dir.join("memory"));
+    let imported = store.import_legacy_dir(&legacy_memory_dir());
+    if imported > 0 {
+        tracing::info!(imported, "imported legacy memories");
+    }
+    store
+}
+
+/// Namespace for a user's memories: the sender ID qualified by channel, so
+/// the same numeric ID on two platforms stays separate.
+pub fn user_namespace(channel: Option<&str>, user_id: Option<&str>) -> Option<String> {
+    let user_id = user_id.map(str::trim).filter(|u| !u.is_empty())?;
+    Some(match channel.map(str::trim).filter(|c| !c.is_empty()) {
+        Some(channel) => format!("{channel}:{user_id}"),
+        None => user_id.to_string(),
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Prompt injection
+// ---------------------------------------------------------------------------
+
+/// Memory settings (`agents.defaults.memory` or per agent).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MemoryConfig {
+    /// Add memories relevant to the latest user message to the system prompt
+    /// at run start.  Default: `false`.
+    #[serde(default)]
+    pub inject: bool,
+
+    /// Maximum number of injected memories.  Default: `5`.
+    #[serde(default = "default_inject_limit")]
+    pub inject_limit: usize,
+}
+
+fn default_inject_limit() -> usize {
+    5
+}
+
+impl Default for MemoryConfig {
+    fn default() -> Self {
+        Self {
+            inject: false,
+            inject_limit: default_inject_limit(),
+        }
+    }
+}
+
+/// Longest memory content quoted into the system prompt (characters).
+const INJECTED_CONTENT_MAX_CHARS: usize = 1000;
+
+/// Render the memories most relevant to `query` as a system prompt section,
+/// or `None` if nothing matches.  The memories are tagged as untrusted
+/// content, since they were written from earlier conversations.
+pub fn recall_section(
+    store: &MemoryStore,
+    agent_id: &str,
+    user_id: Option<&str>,
+    query: &str,
+    limit: usize,
+    tagging_config: &TaggingConfig,
+) -> Option<String> {
+    let hits = store.search(query, &MemoryFilter::visible_to(agent_id, user_id), limit);
+    if hits.is_empty() {
+        return None;
+    }
+    let mut memories = String::new();
+    for hit in hits {
+        let content: String = if hit.entry.content.chars().count() > INJECTED_CONTENT_MAX_CHARS {
+            let truncated: String = hit
+                .entry
+                .content
+                .chars()
+                .take(INJECTED_CONTENT_MAX_CHARS)
+                .collect();
+            format!("{truncated}…")
+        } else {
+            hit.entry.content.clone()
+        };
+        memories.push_str(&format!("- {}: {}", hit.entry.key, content));
+        if !hit.entry.tags.is_empty() {
+            memories.push_str(&format!(" [{}]", hit.entry.tags.join(", ")));
+        }
+        memories.push('\n');
+    }
+    Some(format!(
+        "## Relevant memories\nNotes saved in earlier conversations that may help with this one:\n{}\n",
+        tagging::tag_content(memories.trim_end(), ContentSource::Memory, tagging_config)
+    ))
+}
+
+// ---------------------------------------------------------------------------
+// Helpers
+// ---------------------------------------------------------------------------
+
+/// Load a memory file, converting the legacy flat format.  The flag is `true`
+/// for legacy files.
+fn load_file(path: &Path) -> Result<(Vec<MemoryEntry>, bool), MemoryError> {
+    let content = fs::read_to_string(path).map_err(|e| MemoryError::Io(e.to_string()))?;
+    if let Ok(file) = serde_json::from_str::<MemoryFile>(&content) {
+        return Ok((file.entries, false));
+    }
+    let legacy: HashMap<String, String> =
+        serde_json::from_str(&content).map_err(|e| MemoryError::Json(e.to_string()))?;
+    let agent_id = path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or(DEFAULT_AGENT_ID)
+        .to_string();
+    let modified = fs::metadata(path)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or_else(now_ms);
+    let mut entries: Vec<MemoryEntry> = legacy
+        .into_iter()
+        .map(|(key, content)| MemoryEntry {
+            id: Uuid::new_v4().to_string(),
+            agent_id: agent_id.clone(),
+            user_id: None,
+            key,
+            content,
+            tags: Vec::new(),
+            created_at: modified,
+            updated_at: modified,
+            expires_at: None,
+        })
+        .collect();
+    entries.sort_by(|a, b| a.key.cmp(&b.key));
+    Ok((entries, true))
+}
+
+fn normalize_agent_id(agent_id: &str) -> String {
+    let trimmed = agent_id.trim();
+    if trimmed.is_empty() {
+        DEFAULT_AGENT_ID.to_string()
+    } else {
+        trimmed.to_string()
+    }
+}
+
+/// File name for an agent, safe against path traversal.  Bytes other than
+/// ASCII letters, digits, `-` and `_` are percent-encoded, so distinct agent
+/// IDs never share a file.
+fn file_stem(agent_id: &str) -> String {
+    let mut stem = String::with_capacity(agent_id.len());
+    for byte in agent_id.bytes() {
+        if byte.is_ascii_alphanumeric() || byte == b'-' || byte == b'_' {
+            stem.push(byte as char);
+        } else {
+            stem.push_str(&format!("%{byte:02X}"));
+        }
+    }
+    stem
+}
+
+fn validate_key(key: &str) -> Result<(), MemoryError> {
+    if key.is_empty() {
+        return Err(MemoryError::Invalid("key cannot be empty".to_string()));
+    }
+    if key.len() > MAX_KEY_LEN {
+        return Err(MemoryError::Invalid(format!(
+            "key too long (max {MAX_KEY_LEN} bytes)"
+        )));
+    }
+    Ok(())
+}
+
+fn validate_content(content: &str) -> Result<(), MemoryError> {
+    if content.len() > MAX_CONTENT_LEN {
+        return Err(MemoryError::Invalid(format!(
+            "content too long (max {MAX_CONTENT_LEN} bytes)"
+        )));
+    }
+    Ok(())
+}
+
+fn validate_tags(tags: Vec<String>) -> Result<Vec<String>, MemoryError> {
+    let tags = normalize_tags(tags);
+    if tags.len() > MAX_TAGS {
+        return Err(MemoryError::Invalid(format!(
+            "too many tags (max {MAX_TAGS})"
+        )));
+    }
+    if tags.iter().any(|t| t.len() > MAX_TAG_LEN) {
+        return Err(MemoryError::Invalid(format!(
+            "tag too long (max {MAX_TAG_LEN} bytes)"
+        )));
+    }
+    Ok(tags)
+}
+
+/// Lowercase, trim and deduplicate tags, keeping their order.
+pub fn normalize_tags(tags: Vec<String>) -> Vec<String> {
+    let mut seen = HashSet::new();
+    tags.into_iter()
+        .map(|t| t.trim().to_lowercase())
+        .filter(|t| !t.is_empty() && seen.insert(t.clone()))
+        .collect()
+}
+
+/// Split text into lowercase alphanumeric terms.
+fn tokenize(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|t| !t.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// Term frequencies for a memory; key and tag terms count twice.
+fn term_counts(entry: &MemoryEntry) -> HashMap<String, u32> {
+    let mut counts: HashMap<String, u32> = HashMap::new();
+    let weighted = tokenize(&entry.key)
+        .into_iter()
+        .chain(entry.tags.iter().flat_map(|t| tokenize(t)));
+    for term in weighted {
+        *counts.entry(term).or_default() += 2;
+    }
+    for term in tokenize(&entry.content) {
+        *counts.entry(term).or_default() += 1;
+    }
+    counts
+}
+
+/// Get current time in milliseconds
+pub(crate) fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or(Duration::ZERO)
+        .as_millis() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(agent: &str, user: Option<&str>, key: &str, content: &str) -> MemoryWrite {
+        MemoryWrite {
+            agent_id: agent.to_string(),
+            user_id: user.map(str::to_string),
+            key: key.to_string(),
+            content: content.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_put_upserts_within_namespace() {
+        let store = MemoryStore::in_memory();
+        let first = store.put(write("a", None, "tz", "UTC")).unwrap();
+        let second = store.put(write("a", None, "tz", "Europe/Berlin")).unwrap();
+        assert_eq!(first.id, second.id);
+        assert_eq!(second.content, "Europe/Berlin");
+
+        // Same key for a user is a separate memory
+        let user = store
+            .put(write("a", Some("telegram:1"), "tz", "Asia/Tokyo"))
+            .unwrap();
+        assert_ne!(user.id, first.id);
+        assert_eq!(store.list(&MemoryFilter::default()).len(), 2);
+        assert_eq!(
+            store
+                .get_by_key("a", Some("telegram:1"), "tz")
+                .unwrap()
+                .content,
+            "Asia/Tokyo"
+        );
+    }
+
+    #[test]
+    fn test_visible_scope_hides_other_users() {
+        let store = MemoryStore::in_memory();
+        store.put(write("a", None, "shared", "x")).unwrap();
+        store.put(write("a", Some("u1"), "mine", "x")).unwrap();
+        store.put(write("a", Some("u2"), "theirs", "x")).unwrap();
+        store.put(write("b", None, "other agent", "x")).unwrap();
+
+        let keys: Vec<String> = store
+            .list(&MemoryFilter::visible_to("a", Some("u1")))
+            .into_iter()
+            .map(|e| e.key)
+            .collect();
+        assert_eq!(keys.len(), 2);
+        assert!(keys.contains(&"shared".to_string()));
+        assert!(keys.contains(&"mine".to_string()));
+    }
+
+    #[test]
+    fn test_search_ranks_by_relevance() {
+        let store = MemoryStore::in_memory();
+        store
+            .put(write("a", None, "coffee", "Prefers oat milk flat whites"))
+            .unwrap();
+        store
+            .put(write(
+                "a",
+                None,
+                "travel",
+                "Flying to Lisbon in May, likes coffee there",
+            ))
+            .unwrap();
+        store
+            .put(write("a", None, "pets", "Has a cat named Miso"))
+            .unwrap();
+
+        let hits = store.search("coffee order", &MemoryFilter::default(), 10);
+        assert_eq!(hits.len(), 2);
+        assert_eq!(hits[0].entry.key, "coffee");
+        assert!(hits[0].score > hits[1].score);
+
+        assert!(store
+            .search("dinosaurs", &MemoryFilter::default(), 10)
+            .is_empty());
+        assert!(store.search("  ", &MemoryFilter::default(), 10).is_empty());
+    }
+
+    #[test]
+    fn test_tags_are_normalized_and_filter() {
+        let store = MemoryStore::in_memory();
+        let mut w = write("a", None, "k", "v");
+        w.tags = vec!["Work".into(), " work ".into(), "urgent".into()];
+        let entry = store.put(w).unwrap();
+        assert_eq!(entry.tags, vec!["work", "urgent"]);
+
+        store.put(write("a", None, "k2", "v")).unwrap();
+        let filter = MemoryFilter::default().with_tags(vec!["WORK".into()]);
+        assert_eq!(store.list(&filter).len(), 1);
+    }
+
+    #[test]
+    fn test_expired_memories_are_hidden_and_purged() {
+        let store = MemoryStore::in_memory();
+        store.put(write("a", None, "new", "v")).unwrap();
+        let mut w = write("a", None, "old", "v");
+        w.expires_at = Some(1);
+        let expired = store.put(w).unwrap();
+
+        assert_eq!(store.list(&MemoryFilter::default()).len(), 1);
+        assert!(store.get_by_key("a", None, "old").is_none());
+        assert!(store.get(&expired.id).is_some());
+        assert_eq!(store.purge_expired(), 1);
+        assert!(store.get(&expired.id).is_none());
+    }
+
+    #[test]
+    fn test_update_and_delete() {
+        let store = MemoryStore::in_memory();
+        let entry = store.put(write("a", None, "k", "v")).unwrap();
+        let updated = store
+            .update(
+                &entry.id,
+                MemoryPatch {
+                    content: Some("v2".into()),
+                    tags: Some(vec!["t".into()]),
+                    expires_at: Some(Some(u64::MAX)),
+                },
+            )
+            .unwrap();
+        assert_eq!(updated.content, "v2");
+        assert_eq!(updated.tags, vec!["t"]);
+        assert_eq!(updated.expires_at, Some(u64::MAX));
+
+        store.delete(&entry.id).unwrap();
+        assert!(matches!(
+            store.delete(&entry.id),
+            Err(MemoryError::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_validation() {
+        let store = MemoryStore::in_memory();
+        assert!(matches!(
+            store.put(write("a", None, "  ", "v")),
+            Err(MemoryError::Invalid(_))
+        ));
+        assert!(matches!(
+            store.put(write("a", None, "k", &"x".repeat(MAX_CONTENT_LEN + 1))),
+            Err(MemoryError::Invalid(_))
+        ));
+    }
+
+    #[test]
+    fn test_persistence_roundtrip() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path().join("memory");
+        {
+            let store = MemoryStore::open(dir.clone());
+            store.put(write("agent/../x", None, "k", "v")).unwrap();
+            store.put(write("main", Some("u"), "k", "w")).unwrap();
+        }
+        assert!(dir.join("agent%2F%2E%2E%2Fx.json").exists());
+
+        let store = MemoryStore::open(dir);
+        assert_eq!(
+            store.get_by_key("agent/../x", None, "k").unwrap().content,
+            "v"
+        );
+        assert_eq!(
+            store.get_by_key("main", Some("u"), "k").unwrap().content,
+            "w"
+        );
+    }
+
+    #[test]
+    fn test_file_names_do_not_collide() {
+        assert_eq!(file_stem("main"), "main");
+        assert_eq!(file_stem("a.b"), "a%2Eb");
+        assert_ne!(file_stem("a.b"), file_stem("a_b"));
+        assert_ne!(file_stem("a%2Eb"), file_stem("a.b"));
+
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path().join("memory");
+        {
+            let store = MemoryStore::open(dir.clone());
+            store.put(write("a.b", None, "k", "dot")).unwrap();
+            store.put(write("a_b", None, "k", "underscore")).unwrap();
+        }
+        let store = MemoryStore::open(dir);
+        assert_eq!(store.get_by_key("a.b", None, "k").unwrap().content, "dot");
+        assert_eq!(
+            store.get_by_key("a_b", None, "k").unwrap().content,
+            "underscore"
+        );
+    }
+
+    #[test]
+    fn test_sanitized_file_name_is_renamed() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path().join("memory");
+        {
+            let store = MemoryStore::open(dir.clone());
+            store.put(write("a.b", None, "k", "v")).unwrap();
+        }
+        // Earlier versions wrote `a.b` to `a_b.json`
+        fs::rename(dir.join("a%2Eb.json"), dir.join("a_b.json")).unwrap();
+
+        let store = MemoryStore::open(dir.clone());
+        assert_eq!(store.get_by_key("a.b", None, "k").unwrap().content, "v");
+        assert!(!dir.join("a_b.json").exists());
+        assert!(dir.join("a%2Eb.json").exists());
+        assert_eq!(
+            MemoryStore::open(dir).list(&MemoryFilter::default()).len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_legacy_file_migrated_in_place() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path().join("memory");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("main.json"), r#"{"greeting":"hello world"}"#).unwrap();
+
+        let store = MemoryStore::open(dir.clone());
+        let entry = store.get_by_key("main", None, "greeting").unwrap();
+        assert_eq!(entry.content, "hello world");
+
+        // Rewritten in the new format
+        let raw = fs::read_to_string(dir.join("main.json")).unwrap();
+        assert!(raw.contains("\"version\": 1"));
+        let reopened = MemoryStore::open(dir);
+        assert_eq!(
+            reopened.get_by_key("main", None, "greeting").unwrap().id,
+            entry.id
+        );
+    }
+
+    #[test]
+    fn test_corrupt_file_is_moved_aside() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path().join("memory");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("main.json"), "{ not json").unwrap();
+
+        let store = MemoryStore::open(dir.clone());
+        assert!(store.list(&MemoryFilter::default()).is_empty());
+        assert!(!dir.join("main.json").exists());
+        let backups = fs::read_dir(&dir).unwrap().count();
+        assert_eq!(backups, 1);
+    }
+
+    #[test]
+    fn test_import_legacy_dir_skips_existing_agents() {
+        let tmp = tempfile::tempdir().unwrap();
+        let legacy = tmp.path().join("legacy");
+        fs::create_dir_all(&legacy).unwrap();
+        fs::write(legacy.join("main.json"), r#"{"a":"1","b":"2"}"#).unwrap();
+        fs::write(legacy.join("busy.json"), r#"{"c":"3"}"#).unwrap();
+
+        let store = MemoryStore::open(tmp.path().join("memory"));
+        store.put(write("busy", None, "existing", "x")).unwrap();
+        assert_eq!(store.import_legacy_dir(&legacy), 2);
+        assert!(store.get_by_key("busy", None, "c").is_none());
+        assert_eq!(store.get_by_key("main", None, "b").unwrap().content, "2");
+        assert!(legacy.join("main.json.migrated").exists());
+        assert!(legacy.join("busy.json.migrated").exists());
+
+        // Legacy files are only imported once, even after a reset
+        let store = MemoryStore::in_memory();
+        assert_eq!(store.import_legacy_dir(&legacy), 0);
+    }
+
+    #[test]
+    fn test_user_namespace() {
+        assert_eq!(
+            user_namespace(Some("telegram"), Some("42")).as_deref(),
+            Some("telegram:42")
+        );
+        assert_eq!(user_namespace(None, Some("42")).as_deref(), Some("42"));
+        assert_eq!(user_namespace(Some("telegram"), Some(" ")), None);
+        assert_eq!(user_namespace(Some("telegram"), None), None);
+    }
+
+    #[test]
+    fn test_recall_section() {
+        let store = MemoryStore::in_memory();
+        let mut w = write("a", Some("u"), "diet", "Vegetarian, allergic to peanuts");
+        w.tags = vec!["food".into()];
+        store.put(w).unwrap();
+        store
+            .put(write("a", Some("other"), "diet", "Eats everything"))
+            .unwrap();
+
+        let tagging = TaggingConfig { enabled: true };
+        let section = recall_section(
+            &store,
+            "a",
+            Some("u"),
+            "suggest a dinner with peanuts",
+            5,
+            &tagging,
+        )
+        .unwrap();
+        assert!(section.starts_with("## Relevant memories"));
+        assert!(section.contains(
+            "<<<UNTRUSTED>>>\n- diet: Vegetarian, allergic to peanuts [food]\n<<<UNTRUSTED_END>>>"
+        ));
+        assert!(!section.contains("Eats everything"));
+        assert!(recall_section(&store, "a", Some("u"), "weather", 5, &tagging).is_none());
+
+        let untagged = recall_section(
+            &store,
+            "a",
+            Some("u"),
+            "peanuts",
+            5,
+            &TaggingConfig { enabled: false },
+        )
+        .unwrap();
+        assert!(!untagged.contains("<<<UNTRUSTED>>>"));
+    }
+}
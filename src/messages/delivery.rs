@@ -1,8 +1,15 @@
 //! Message delivery worker.
 //!
 //! Background loop that drains the outbound message pipeline and delivers
-//! messages via channel plugins. Wakes on `Notify` or periodic 5-second poll.
+//! messages via channel plugins. Wakes on `Notify`, when the next retry
+//! backoff or rate-limit window elapses, or on a periodic 5-second poll.
+//!
+//! Sends are paced by a per-channel token bucket. Retryable failures are
+//! re-queued with exponential backoff and jitter, or after the platform's
+//! own `retry_after` hint, which also pauses the whole channel. Messages
+//! that fail permanently land in the pipeline's dead-letter store.
 
+use std::collections::HashMap;
 use std::sync::Arc;
 use std::time::Duration;
 
@@ -10,26 +17,133 @@ use serde_json::{json, Value};
 use tracing::warn;
 
 use crate::channels::ChannelRegistry;
-use crate::messages::outbound::{MessageContent, MessagePipeline};
+use crate::messages::outbound::{
+    ChannelAction, MessageContent, MessageId, MessagePipeline, OutboundMessage,
+};
+use crate::messages::ratelimit::{ChannelRate, ChannelRateLimiter};
 use crate::plugins::hook_utils;
 use crate::plugins::{self, OutboundContext, PluginRegistry};
 use crate::server::ws::WsServerState;
 
+/// Poll interval when there is nothing ready to deliver
+const IDLE_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Upper bound on a platform `retry_after` hint we will honour
+const MAX_RETRY_HINT: Duration = Duration::from_secs(3600);
+
+/// Retries allowed for rate-limited sends whose context has retries disabled
+const RATE_LIMIT_RETRIES: u32 = 5;
+
+/// Relative jitter applied to computed backoff delays (±20%)
+const BACKOFF_JITTER: f64 = 0.2;
+
+/// Exponential backoff schedule for retryable failures
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BackoffPolicy {
+    /// Delay before the first retry
+    pub base: Duration,
+    /// Maximum delay between retries
+    pub max: Duration,
+}
+
+impl Default for BackoffPolicy {
+    fn default() -> Self {
+        Self {
+            base: Duration::from_secs(1),
+            max: Duration::from_secs(300),
+        }
+    }
+}
+
+impl BackoffPolicy {
+    /// Delay before retrying after `attempts` failed attempts.
+    ///
+    /// `jitter` is a sample in `[0, 1)` that spreads the delay by
+    /// ±[`BACKOFF_JITTER`] so retries from many messages do not align.
+    pub fn delay(&self, attempts: u32, jitter: f64) -> Duration {
+        let exponent = attempts.saturating_sub(1).min(30);
+        let raw = self.base.saturating_mul(1u32 << exponent).min(self.max);
+        let factor = 1.0 + BACKOFF_JITTER * (2.0 * jitter.clamp(0.0, 1.0) - 1.0);
+        raw.mul_f64(factor).min(self.max)
+    }
+}
+
+/// Delivery loop configuration, read from `messages.delivery`.
+#[derive(Debug, Clone, Default)]
+pub struct DeliveryConfig {
+    /// Retry backoff schedule
+    pub backoff: BackoffPolicy,
+    /// Per-channel rate limit overrides
+    pub rate_limits: HashMap<String, ChannelRate>,
+}
+
+/// Build a [`DeliveryConfig`] from the raw config.
+///
+/// Reads `messages.delivery.retryBaseMs`, `messages.delivery.retryMaxMs`, and
+/// `messages.delivery.rateLimits.<channel>.{rate,burst}`, falling back to
+/// defaults when keys are absent.
+pub fn build_delivery_config(cfg: &Value) -> DeliveryConfig {
+    let delivery = cfg.get("messages").and_then(|v| v.get("delivery"));
+    let defaults = BackoffPolicy::default();
+
+    let ms = |key: &str| {
+        delivery
+            .and_then(|d| d.get(key))
+            .and_then(|v| v.as_u64())
+            .map(Duration::from_millis)
+    };
+    let base = ms("retryBaseMs").unwrap_or(defaults.base);
+    let max = ms("retryMaxMs").unwrap_or(defaults.max).max(base);
+
+    let rate_limits = delivery
+        .and_then(|d| d.get("rateLimits"))
+        .and_then(|v| v.as_object())
+        .map(|limits| {
+            limits
+                .iter()
+                .filter_map(|(channel, limit)| {
+                    let default = ChannelRate::default_for(channel);
+                    let rate = limit
+                        .get("rate")
+                        .and_then(|v| v.as_f64())
+                        .unwrap_or(default.rate);
+                    let burst = limit
+                        .get("burst")
+                        .and_then(|v| v.as_u64())
+                        .map(|b| b.min(u32::MAX as u64) as u32)
+                        .unwrap_or(default.burst);
+                    (rate > 0.0).then(|| (channel.clone(), ChannelRate::new(rate, burst)))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    DeliveryConfig {
+        backoff: BackoffPolicy { base, max },
+        rate_limits,
+    }
+}
+
 /// Run the delivery worker loop.
 ///
-/// Wakes when notified by the pipeline, every 5 seconds, or on shutdown.
+/// Wakes when notified by the pipeline, when the next backoff or rate-limit
+/// window elapses (at most every 5 seconds), or on shutdown.
 pub async fn delivery_loop(
     pipeline: Arc<MessagePipeline>,
     plugin_registry: Arc<PluginRegistry>,
     channel_registry: Arc<ChannelRegistry>,
     _state: Arc<WsServerState>,
+    config: DeliveryConfig,
     mut shutdown: tokio::sync::watch::Receiver<bool>,
 ) {
+    let limiter = ChannelRateLimiter::with_overrides(config.rate_limits.clone());
+    let mut wait = IDLE_POLL_INTERVAL;
+
     loop {
         // Wait for notification, timeout, or shutdown
         tokio::select! {
             _ = pipeline.notifier().notified() => {}
-            _ = tokio::time::sleep(Duration::from_secs(5)) => {}
+            _ = tokio::time::sleep(wait) => {}
             _ = shutdown.changed() => {
                 break;
             }
@@ -42,27 +156,57 @@ pub async fn delivery_loop(
 
         let channel_ids = pipeline.channels_with_messages();
 
-        process_channel_messages(&channel_ids, &pipeline, &plugin_registry, &channel_registry)
-            .await;
+        wait = process_channel_messages(
+            &channel_ids,
+            &pipeline,
+            &plugin_registry,
+            &channel_registry,
+            &limiter,
+            &config.backoff,
+        )
+        .await;
     }
 }
 
 /// Process pending messages for each connected channel.
+///
+/// Returns how long the loop may sleep before something becomes ready:
+/// zero if a message was handled (more may be waiting), otherwise the time
+/// until the earliest backoff or rate-limit window elapses.
 async fn process_channel_messages(
     channel_ids: &[String],
     pipeline: &MessagePipeline,
     plugin_registry: &Arc<PluginRegistry>,
     channel_registry: &ChannelRegistry,
-) {
+    limiter: &ChannelRateLimiter,
+    backoff: &BackoffPolicy,
+) -> Duration {
+    let mut wait = IDLE_POLL_INTERVAL;
+
     for channel_id in channel_ids {
         if !channel_registry.is_connected(channel_id) {
             continue;
         }
 
+        match pipeline.next_due_in(channel_id) {
+            None => continue,
+            Some(due_in) if !due_in.is_zero() => {
+                wait = wait.min(due_in);
+                continue;
+            }
+            Some(_) => {}
+        }
+
+        if let Err(limited_for) = limiter.try_acquire(channel_id) {
+            wait = wait.min(limited_for);
+            continue;
+        }
+
         let msg = match pipeline.next_for_channel(channel_id) {
             Some(m) => m,
             None => continue,
         };
+        wait = Duration::ZERO;
 
         let message_id = msg.message.id.clone();
         let mut message = msg.message.clone();
@@ -114,16 +258,7 @@ async fn process_channel_messages(
             }
         };
 
-        let metadata = &message.metadata;
-
-        let result = deliver_message(
-            &plugin,
-            &message.content,
-            metadata.recipient_id.as_deref().unwrap_or_default(),
-            metadata.reply_to.as_deref(),
-            metadata.thread_id.as_deref(),
-        )
-        .await;
+        let result = deliver_outbound(&plugin, &message).await;
 
         let delivery_snapshot = match &result {
             Ok(delivery) => json!({
@@ -153,14 +288,23 @@ async fn process_channel_messages(
             }),
         );
 
-        handle_delivery_result(pipeline, &message_id, result);
+        handle_delivery_result(pipeline, limiter, backoff, channel_id, &message_id, result);
     }
+
+    wait
 }
 
 /// Handle the result of a message delivery attempt.
+///
+/// Retryable failures are re-queued after a backoff delay; a platform retry
+/// hint overrides the computed delay and pauses the whole channel. Anything
+/// else is marked failed, which moves it to the dead-letter store.
 fn handle_delivery_result(
     pipeline: &MessagePipeline,
-    message_id: &crate::messages::outbound::MessageId,
+    limiter: &ChannelRateLimiter,
+    backoff: &BackoffPolicy,
+    channel_id: &str,
+    message_id: &MessageId,
     result: Result<plugins::DeliveryResult, plugins::BindingError>,
 ) {
     match result {
@@ -171,12 +315,33 @@ fn handle_delivery_result(
             let error = delivery
                 .error
                 .unwrap_or_else(|| "delivery failed".to_string());
-            if delivery.retryable && pipeline.can_retry(message_id) {
-                let _ = pipeline.mark_retry(message_id, &error);
+            let retry_hint = delivery
+                .retry_after_ms
+                .map(|ms| Duration::from_millis(ms).min(MAX_RETRY_HINT));
+            let attempts = pipeline
+                .get_message(message_id)
+                .map(|m| m.attempts)
+                .unwrap_or(0);
+
+            // Rate-limited sends were never delivered, so they get a small
+            // retry budget even when the sender did not ask for retries.
+            let can_retry = pipeline.can_retry(message_id)
+                || (retry_hint.is_some() && attempts < RATE_LIMIT_RETRIES);
+
+            if delivery.retryable && can_retry {
+                let delay = match retry_hint {
+                    Some(hint) => {
+                        limiter.pause(channel_id, hint);
+                        hint
+                    }
+                    None => backoff.delay(attempts, jitter_sample()),
+                };
+                let _ = pipeline.mark_retry_after(message_id, &error, delay);
                 warn!(
                     id = %message_id,
                     error = %error,
-                    "retryable delivery failure, reset to queued for retry"
+                    delay_ms = delay.as_millis() as u64,
+                    "retryable delivery failure, scheduled retry"
                 );
             } else {
                 let _ = pipeline.mark_failed(message_id, &error);
@@ -188,6 +353,15 @@ fn handle_delivery_result(
     }
 }
 
+/// Uniform sample in `[0, 1)` for backoff jitter.
+fn jitter_sample() -> f64 {
+    let mut bytes = [0u8; 8];
+    if getrandom::fill(&mut bytes).is_err() {
+        return 0.5;
+    }
+    (u64::from_le_bytes(bytes) >> 11) as f64 / (1u64 << 53) as f64
+}
+
 fn dispatch_message_hook(
     plugin_registry: &Arc<PluginRegistry>,
     hook_name: &str,
@@ -200,10 +374,7 @@ fn parse_hook_payload(result: &plugins::HookDispatchResult, hook_name: &str) ->
     hook_utils::parse_hook_payload(result, hook_name)
 }
 
-fn apply_message_hook_overrides(
-    message: &mut crate::messages::outbound::OutboundMessage,
-    payload: &Value,
-) {
+fn apply_message_hook_overrides(message: &mut OutboundMessage, payload: &Value) {
     let Some(obj) = payload.as_object() else {
         return;
     };
@@ -223,6 +394,147 @@ fn apply_message_hook_overrides(
     }
 }
 
+/// Deliver an outbound message, honouring any channel action it carries.
+///
+/// Each action goes to the matching channel plugin operation. Channels
+/// without native support for an operation report it as a non-retryable
+/// failure rather than sending a new message; embeds and blocks degrade to
+/// their plain-text fallback content.
+async fn deliver_outbound(
+    plugin: &Arc<dyn plugins::ChannelPluginInstance>,
+    message: &OutboundMessage,
+) -> Result<plugins::DeliveryResult, plugins::BindingError> {
+    let metadata = &message.metadata;
+    let to = metadata.recipient_id.clone().unwrap_or_default();
+    let target = |target_id: &str| plugins::MessageTarget {
+        to: to.clone(),
+        message_id: target_id.to_string(),
+        account_id: None,
+    };
+    let text_context = |text: &str| OutboundContext {
+        to: to.clone(),
+        text: text.to_string(),
+        media_url: None,
+        gif_playback: false,
+        reply_to_id: metadata.reply_to.clone(),
+        thread_id: metadata.thread_id.clone(),
+        account_id: None,
+    };
+
+    match &metadata.action {
+        None => {}
+        Some(ChannelAction::Edit { target_id }) => {
+            let Some(text) = message.content.as_text() else {
+                return Ok(failed_delivery("edit requires text content", false));
+            };
+            let (target, text) = (target(target_id), text.to_string());
+            return run_plugin_op(plugin, move |p| p.edit_message(target, text)).await;
+        }
+        Some(ChannelAction::Delete { target_id }) => {
+            let target = target(target_id);
+            return run_plugin_op(plugin, move |p| p.delete_message(target)).await;
+        }
+        Some(ChannelAction::React { target_id, emoji }) => {
+            let (target, emoji) = (target(target_id), emoji.clone());
+            return run_plugin_op(plugin, move |p| p.react(target, emoji)).await;
+        }
+        Some(ChannelAction::Poll {
+            question,
+            options,
+            allow_multiple,
+        }) => {
+            let poll = plugins::PollInput {
+                question: question.clone(),
+                options: options.clone(),
+                allow_multiple: *allow_multiple,
+            };
+            if let Err(e) = poll.validate() {
+                return Ok(failed_delivery(e, false));
+            }
+            let ctx = plugins::PollContext {
+                to,
+                poll,
+                account_id: None,
+            };
+            return run_plugin_op(plugin, move |p| p.send_poll(ctx)).await;
+        }
+        Some(ChannelAction::Pin { target_id, silent }) => {
+            let (target, silent) = (target(target_id), *silent);
+            return run_plugin_op(plugin, move |p| p.pin_message(target, silent)).await;
+        }
+        Some(ChannelAction::ReplyMarkup { target_id, buttons }) => {
+            let (target, buttons) = (target(target_id), buttons.clone());
+            return run_plugin_op(plugin, move |p| p.set_reply_markup(target, buttons)).await;
+        }
+        Some(ChannelAction::CreateThread {
+            target_id,
+            name,
+            auto_archive_minutes,
+        }) => {
+            let (target, name, minutes) = (target(target_id), name.clone(), *auto_archive_minutes);
+            return run_plugin_op(plugin, move |p| p.create_thread(target, name, minutes)).await;
+        }
+        Some(ChannelAction::Embed { embed }) => {
+            let ctx = text_context(message.content.as_text().unwrap_or_default());
+            let embed = embed.clone();
+            return run_plugin_op(plugin, move |p| p.send_embed(ctx, embed)).await;
+        }
+        Some(ChannelAction::Blocks { blocks }) => {
+            let ctx = text_context(message.content.as_text().unwrap_or_default());
+            let blocks = blocks.clone();
+            return run_plugin_op(plugin, move |p| p.send_blocks(ctx, blocks)).await;
+        }
+        Some(ChannelAction::Ephemeral { user }) => {
+            let Some(text) = message.content.as_text() else {
+                return Ok(failed_delivery("ephemeral requires text content", false));
+            };
+            let (ctx, user) = (text_context(text), user.clone());
+            return run_plugin_op(plugin, move |p| p.send_ephemeral(ctx, user)).await;
+        }
+    }
+
+    deliver_message(
+        plugin,
+        &message.content,
+        &to,
+        metadata.reply_to.as_deref(),
+        metadata.thread_id.as_deref(),
+    )
+    .await
+}
+
+/// Run a sync channel plugin operation on the blocking pool.
+async fn run_plugin_op<F>(
+    plugin: &Arc<dyn plugins::ChannelPluginInstance>,
+    op: F,
+) -> Result<plugins::DeliveryResult, plugins::BindingError>
+where
+    F: FnOnce(
+            &dyn plugins::ChannelPluginInstance,
+        ) -> Result<plugins::DeliveryResult, plugins::BindingError>
+        + Send
+        + 'static,
+{
+    let p = plugin.clone();
+    tokio::task::spawn_blocking(move || op(p.as_ref()))
+        .await
+        .map_err(|e| plugins::BindingError::CallError(e.to_string()))?
+}
+
+/// Build a failed delivery result.
+fn failed_delivery(error: impl Into<String>, retryable: bool) -> plugins::DeliveryResult {
+    plugins::DeliveryResult {
+        ok: false,
+        message_id: None,
+        error: Some(error.into()),
+        retryable,
+        conversation_id: None,
+        to_jid: None,
+        poll_id: None,
+        retry_after_ms: None,
+    }
+}
+
 /// Deliver a message via the channel plugin, dispatching to send_text or send_media.
 ///
 /// `ChannelPluginInstance` methods are sync, so we run them via `spawn_blocking`.
@@ -276,6 +588,7 @@ async fn deliver_message(
                 conversation_id: None,
                 to_jid: None,
                 poll_id: None,
+                retry_after_ms: None,
             };
             for part in parts {
                 last_result =
@@ -304,8 +617,11 @@ mod tests {
     struct MockChannel {
         send_text_count: AtomicU32,
         send_media_count: AtomicU32,
+        edit_count: AtomicU32,
+        poll_count: AtomicU32,
         fail: bool,
         retryable: bool,
+        retry_after_ms: Option<u64>,
     }
 
     impl MockChannel {
@@ -313,8 +629,11 @@ mod tests {
             Self {
                 send_text_count: AtomicU32::new(0),
                 send_media_count: AtomicU32::new(0),
+                edit_count: AtomicU32::new(0),
+                poll_count: AtomicU32::new(0),
                 fail: false,
                 retryable: false,
+                retry_after_ms: None,
             }
         }
 
@@ -322,8 +641,18 @@ mod tests {
             Self {
                 send_text_count: AtomicU32::new(0),
                 send_media_count: AtomicU32::new(0),
+                edit_count: AtomicU32::new(0),
+                poll_count: AtomicU32::new(0),
                 fail: true,
                 retryable,
+                retry_after_ms: None,
+            }
+        }
+
+        fn rate_limited(retry_after_ms: u64) -> Self {
+            Self {
+                retry_after_ms: Some(retry_after_ms),
+                ..Self::failing(true)
             }
         }
     }
@@ -355,6 +684,7 @@ mod tests {
                     conversation_id: None,
                     to_jid: None,
                     poll_id: None,
+                    retry_after_ms: self.retry_after_ms,
                 })
             } else {
                 Ok(DeliveryResult {
@@ -365,6 +695,7 @@ mod tests {
                     conversation_id: None,
                     to_jid: None,
                     poll_id: None,
+                    retry_after_ms: None,
                 })
             }
         }
@@ -379,10 +710,82 @@ mod tests {
                 conversation_id: None,
                 to_jid: None,
                 poll_id: None,
+                retry_after_ms: None,
+            })
+        }
+
+        fn edit_message(
+            &self,
+            target: plugins::MessageTarget,
+            _new_text: String,
+        ) -> Result<DeliveryResult, BindingError> {
+            self.edit_count.fetch_add(1, Ordering::Relaxed);
+            Ok(DeliveryResult {
+                ok: true,
+                message_id: Some(target.message_id),
+                error: None,
+                retryable: false,
+                conversation_id: None,
+                to_jid: None,
+                poll_id: None,
+                retry_after_ms: None,
+            })
+        }
+
+        fn send_poll(&self, _ctx: plugins::PollContext) -> Result<DeliveryResult, BindingError> {
+            self.poll_count.fetch_add(1, Ordering::Relaxed);
+            Ok(DeliveryResult {
+                ok: true,
+                message_id: Some("poll-1".to_string()),
+                error: None,
+                retryable: false,
+                conversation_id: None,
+                to_jid: None,
+                poll_id: Some("poll-1".to_string()),
+                retry_after_ms: None,
             })
         }
     }
 
+    /// Delivery config that retries immediately.
+    fn no_backoff() -> DeliveryConfig {
+        DeliveryConfig {
+            backoff: BackoffPolicy {
+                base: Duration::ZERO,
+                max: Duration::ZERO,
+            },
+            ..Default::default()
+        }
+    }
+
+    /// Run the delivery loop until the queue has been drained once.
+    async fn run_delivery_briefly(
+        pipeline: Arc<MessagePipeline>,
+        plugin_reg: Arc<PluginRegistry>,
+        channel_reg: Arc<ChannelRegistry>,
+    ) {
+        let (shutdown_tx, shutdown_rx) = tokio::sync::watch::channel(false);
+        let state = Arc::new(crate::server::ws::WsServerState::new(
+            crate::server::ws::WsServerConfig::default(),
+        ));
+        let pl = pipeline.clone();
+        let handle = tokio::spawn(async move {
+            delivery_loop(
+                pl,
+                plugin_reg,
+                channel_reg,
+                state,
+                DeliveryConfig::default(),
+                shutdown_rx,
+            )
+            .await;
+        });
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        let _ = shutdown_tx.send(true);
+        pipeline.notifier().notify_one();
+        let _ = handle.await;
+    }
+
     fn make_pipeline_and_registries(
         channel_id: &str,
         plugin: Option<Arc<dyn ChannelPluginInstance>>,
@@ -433,7 +836,7 @@ mod tests {
         let cr = channel_reg.clone();
         let st = state.clone();
         let handle = tokio::spawn(async move {
-            delivery_loop(pl, pr, cr, st, shutdown_rx).await;
+            delivery_loop(pl, pr, cr, st, DeliveryConfig::default(), shutdown_rx).await;
         });
 
         // Give it time to process
@@ -462,7 +865,15 @@ mod tests {
 
         let pl = pipeline.clone();
         let handle = tokio::spawn(async move {
-            delivery_loop(pl, plugin_reg, channel_reg, state, shutdown_rx).await;
+            delivery_loop(
+                pl,
+                plugin_reg,
+                channel_reg,
+                state,
+                DeliveryConfig::default(),
+                shutdown_rx,
+            )
+            .await;
         });
 
         tokio::time::sleep(Duration::from_millis(100)).await;
@@ -493,7 +904,15 @@ mod tests {
 
         let pl = pipeline.clone();
         let handle = tokio::spawn(async move {
-            delivery_loop(pl, plugin_reg, channel_reg, state, shutdown_rx).await;
+            delivery_loop(
+                pl,
+                plugin_reg,
+                channel_reg,
+                state,
+                DeliveryConfig::default(),
+                shutdown_rx,
+            )
+            .await;
         });
 
         tokio::time::sleep(Duration::from_millis(100)).await;
@@ -523,7 +942,15 @@ mod tests {
 
         let pl = pipeline.clone();
         let handle = tokio::spawn(async move {
-            delivery_loop(pl, plugin_reg, channel_reg, state, shutdown_rx).await;
+            delivery_loop(
+                pl,
+                plugin_reg,
+                channel_reg,
+                state,
+                DeliveryConfig::default(),
+                shutdown_rx,
+            )
+            .await;
         });
 
         tokio::time::sleep(Duration::from_millis(100)).await;
@@ -568,7 +995,15 @@ mod tests {
 
         let pl = pipeline.clone();
         let handle = tokio::spawn(async move {
-            delivery_loop(pl, plugin_reg, channel_reg, state, shutdown_rx).await;
+            delivery_loop(
+                pl,
+                plugin_reg,
+                channel_reg,
+                state,
+                DeliveryConfig::default(),
+                shutdown_rx,
+            )
+            .await;
         });
 
         tokio::time::sleep(Duration::from_millis(100)).await;
@@ -611,7 +1046,15 @@ mod tests {
 
         let pl = pipeline.clone();
         let handle = tokio::spawn(async move {
-            delivery_loop(pl, plugin_reg, channel_reg, state, shutdown_rx).await;
+            delivery_loop(
+                pl,
+                plugin_reg,
+                channel_reg,
+                state,
+                no_backoff(),
+                shutdown_rx,
+            )
+            .await;
         });
 
         // Allow enough time for multiple delivery loop iterations to run.
@@ -645,6 +1088,146 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_delivery_unsupported_action_fails_without_sending() {
+        let mock = Arc::new(MockChannel::new());
+        let (pipeline, plugin_reg, channel_reg) =
+            make_pipeline_and_registries("action-ch", Some(mock.clone()), true);
+
+        let msg = OutboundMessage::new("action-ch", MessageContent::text("")).with_metadata(
+            crate::messages::outbound::MessageMetadata {
+                recipient_id: Some("chat-1".to_string()),
+                action: Some(crate::messages::outbound::ChannelAction::Pin {
+                    target_id: "7".to_string(),
+                    silent: false,
+                }),
+                ..Default::default()
+            },
+        );
+        let result = pipeline
+            .queue(msg, MsgOutboundContext::new().with_retries(3))
+            .unwrap();
+
+        let (shutdown_tx, shutdown_rx) = tokio::sync::watch::channel(false);
+        let state = Arc::new(crate::server::ws::WsServerState::new(
+            crate::server::ws::WsServerConfig::default(),
+        ));
+
+        let pl = pipeline.clone();
+        let handle = tokio::spawn(async move {
+            delivery_loop(
+                pl,
+                plugin_reg,
+                channel_reg,
+                state,
+                DeliveryConfig::default(),
+                shutdown_rx,
+            )
+            .await;
+        });
+
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        let _ = shutdown_tx.send(true);
+        pipeline.notifier().notify_one();
+        let _ = handle.await;
+
+        assert_eq!(
+            mock.send_text_count.load(Ordering::Relaxed),
+            0,
+            "an action must never be delivered as a new text message"
+        );
+        let queued = pipeline.get_message(&result.message_id).unwrap();
+        assert_eq!(
+            queued.status,
+            crate::messages::outbound::DeliveryStatus::Failed
+        );
+        assert!(queued.last_error.unwrap().contains("pin"));
+    }
+
+    #[tokio::test]
+    async fn test_delivery_edit_action_calls_edit_message() {
+        let mock = Arc::new(MockChannel::new());
+        let (pipeline, plugin_reg, channel_reg) =
+            make_pipeline_and_registries("edit-ch", Some(mock.clone()), true);
+
+        let msg = OutboundMessage::new("edit-ch", MessageContent::text("fixed typo"))
+            .with_metadata(crate::messages::outbound::MessageMetadata {
+                recipient_id: Some("chat-1".to_string()),
+                action: Some(crate::messages::outbound::ChannelAction::Edit {
+                    target_id: "42".to_string(),
+                }),
+                ..Default::default()
+            });
+        let result = pipeline.queue(msg, MsgOutboundContext::new()).unwrap();
+
+        run_delivery_briefly(pipeline.clone(), plugin_reg, channel_reg).await;
+
+        assert_eq!(mock.edit_count.load(Ordering::Relaxed), 1);
+        assert_eq!(mock.send_text_count.load(Ordering::Relaxed), 0);
+        let queued = pipeline.get_message(&result.message_id).unwrap();
+        assert_eq!(
+            queued.status,
+            crate::messages::outbound::DeliveryStatus::Sent
+        );
+    }
+
+    #[tokio::test]
+    async fn test_delivery_embed_falls_back_to_text_without_native_support() {
+        let mock = Arc::new(MockChannel::new());
+        let (pipeline, plugin_reg, channel_reg) =
+            make_pipeline_and_registries("embed-ch", Some(mock.clone()), true);
+
+        let msg = OutboundMessage::new("embed-ch", MessageContent::text("Build passed"))
+            .with_metadata(crate::messages::outbound::MessageMetadata {
+                recipient_id: Some("chat-1".to_string()),
+                action: Some(crate::messages::outbound::ChannelAction::Embed {
+                    embed: serde_json::json!({ "title": "Build passed" }),
+                }),
+                ..Default::default()
+            });
+        let result = pipeline.queue(msg, MsgOutboundContext::new()).unwrap();
+
+        run_delivery_briefly(pipeline.clone(), plugin_reg, channel_reg).await;
+
+        assert_eq!(mock.send_text_count.load(Ordering::Relaxed), 1);
+        let queued = pipeline.get_message(&result.message_id).unwrap();
+        assert_eq!(
+            queued.status,
+            crate::messages::outbound::DeliveryStatus::Sent
+        );
+    }
+
+    #[tokio::test]
+    async fn test_delivery_invalid_poll_fails_before_plugin_call() {
+        let mock = Arc::new(MockChannel::new());
+        let (pipeline, plugin_reg, channel_reg) =
+            make_pipeline_and_registries("poll-ch", Some(mock.clone()), true);
+
+        let msg = OutboundMessage::new("poll-ch", MessageContent::text("")).with_metadata(
+            crate::messages::outbound::MessageMetadata {
+                recipient_id: Some("chat-1".to_string()),
+                action: Some(crate::messages::outbound::ChannelAction::Poll {
+                    question: "Lunch?".to_string(),
+                    options: vec!["Pizza".to_string()],
+                    allow_multiple: false,
+                }),
+                ..Default::default()
+            },
+        );
+        let result = pipeline
+            .queue(msg, MsgOutboundContext::new().with_retries(3))
+            .unwrap();
+
+        run_delivery_briefly(pipeline.clone(), plugin_reg, channel_reg).await;
+
+        assert_eq!(mock.poll_count.load(Ordering::Relaxed), 0);
+        let queued = pipeline.get_message(&result.message_id).unwrap();
+        assert_eq!(
+            queued.status,
+            crate::messages::outbound::DeliveryStatus::Failed
+        );
+    }
+
     #[tokio::test]
     async fn test_delivery_shutdown() {
         let (pipeline, plugin_reg, channel_reg) =
@@ -656,7 +1239,15 @@ mod tests {
         ));
 
         let handle = tokio::spawn(async move {
-            delivery_loop(pipeline, plugin_reg, channel_reg, state, shutdown_rx).await;
+            delivery_loop(
+                pipeline,
+                plugin_reg,
+                channel_reg,
+                state,
+                DeliveryConfig::default(),
+                shutdown_rx,
+            )
+            .await;
         });
 
         // Should exit quickly since shutdown is already true
@@ -666,4 +1257,136 @@ mod tests {
             .expect("delivery loop should exit on shutdown")
             .expect("task should not panic");
     }
+
+    #[test]
+    fn test_backoff_delay_grows_and_caps() {
+        let policy = BackoffPolicy {
+            base: Duration::from_secs(1),
+            max: Duration::from_secs(10),
+        };
+        // jitter sample 0.5 means no jitter
+        assert_eq!(policy.delay(1, 0.5), Duration::from_secs(1));
+        assert_eq!(policy.delay(2, 0.5), Duration::from_secs(2));
+        assert_eq!(policy.delay(4, 0.5), Duration::from_secs(8));
+        assert_eq!(policy.delay(5, 0.5), Duration::from_secs(10));
+        assert_eq!(policy.delay(64, 0.5), Duration::from_secs(10));
+
+        // Jitter spreads by ±20% but never exceeds the cap
+        assert_eq!(policy.delay(1, 0.0), Duration::from_millis(800));
+        assert!(policy.delay(1, 0.999) > Duration::from_millis(1190));
+        assert_eq!(policy.delay(5, 0.999), Duration::from_secs(10));
+    }
+
+    #[test]
+    fn test_build_delivery_config() {
+        let config = build_delivery_config(&json!({}));
+        assert_eq!(config.backoff, BackoffPolicy::default());
+        assert!(config.rate_limits.is_empty());
+
+        let config = build_delivery_config(&json!({
+            "messages": {
+                "delivery": {
+                    "retryBaseMs": 500,
+                    "retryMaxMs": 100,
+                    "rateLimits": {
+                        "slack": { "rate": 0.5 },
+                        "telegram": { "rate": 10, "burst": 3 },
+                        "broken": { "rate": 0 }
+                    }
+                }
+            }
+        }));
+        assert_eq!(config.backoff.base, Duration::from_millis(500));
+        // max is never below base
+        assert_eq!(config.backoff.max, Duration::from_millis(500));
+        assert_eq!(config.rate_limits["slack"], ChannelRate::new(0.5, 5));
+        assert_eq!(config.rate_limits["telegram"], ChannelRate::new(10.0, 3));
+        assert!(!config.rate_limits.contains_key("broken"));
+    }
+
+    #[test]
+    fn test_jitter_sample_in_unit_range() {
+        for _ in 0..100 {
+            let sample = jitter_sample();
+            assert!((0.0..1.0).contains(&sample));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_delivery_backoff_defers_retry() {
+        let mock = Arc::new(MockChannel::failing(true));
+        let (pipeline, plugin_reg, channel_reg) =
+            make_pipeline_and_registries("backoff-ch", Some(mock.clone()), true);
+
+        let msg = OutboundMessage::new("backoff-ch", MessageContent::text("hello"));
+        let result = pipeline
+            .queue(msg, MsgOutboundContext::new().with_retries(3))
+            .unwrap();
+
+        run_delivery_briefly(pipeline.clone(), plugin_reg, channel_reg).await;
+
+        // One attempt, then parked behind the backoff rather than retried
+        assert_eq!(mock.send_text_count.load(Ordering::Relaxed), 1);
+        let queued = pipeline.get_message(&result.message_id).unwrap();
+        assert!(queued.next_attempt_at.is_some());
+        assert!(pipeline.next_for_channel("backoff-ch").is_none());
+        assert!(pipeline.next_due_in("backoff-ch").unwrap() > Duration::ZERO);
+    }
+
+    #[tokio::test]
+    async fn test_delivery_honours_platform_retry_hint() {
+        let mock = Arc::new(MockChannel::rate_limited(30_000));
+        let (pipeline, plugin_reg, channel_reg) =
+            make_pipeline_and_registries("limited-ch", Some(mock.clone()), true);
+
+        // Retries are not enabled, but a rate-limited send is still retried
+        let first = pipeline
+            .queue(
+                OutboundMessage::new("limited-ch", MessageContent::text("one")),
+                MsgOutboundContext::new(),
+            )
+            .unwrap();
+        pipeline
+            .queue(
+                OutboundMessage::new("limited-ch", MessageContent::text("two")),
+                MsgOutboundContext::new(),
+            )
+            .unwrap();
+
+        run_delivery_briefly(pipeline.clone(), plugin_reg, channel_reg).await;
+
+        // The hint pauses the whole channel, so the second message waits too
+        assert_eq!(mock.send_text_count.load(Ordering::Relaxed), 1);
+        let queued = pipeline.get_message(&first.message_id).unwrap();
+        assert_eq!(
+            queued.status,
+            crate::messages::outbound::DeliveryStatus::Queued
+        );
+        let delay = queued.next_attempt_at.unwrap() - queued.updated_at;
+        assert!((29_000..=30_000).contains(&delay));
+        assert_eq!(pipeline.queue_size("limited-ch"), 2);
+        assert_eq!(pipeline.dead_letter_count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_delivery_permanent_failure_goes_to_dead_letters() {
+        let mock = Arc::new(MockChannel::failing(false));
+        let (pipeline, plugin_reg, channel_reg) =
+            make_pipeline_and_registries("dead-ch", Some(mock.clone()), true);
+
+        let result = pipeline
+            .queue(
+                OutboundMessage::new("dead-ch", MessageContent::text("hello")),
+                MsgOutboundContext::new(),
+            )
+            .unwrap();
+
+        run_delivery_briefly(pipeline.clone(), plugin_reg, channel_reg).await;
+
+        let dead = pipeline.dead_letters();
+        assert_eq!(dead.len(), 1);
+        assert_eq!(dead[0].message.id, result.message_id);
+        assert_eq!(dead[0].last_error.as_deref(), Some("mock failure"));
+        assert_eq!(pipeline.stats().dead_letters, 1);
+    }
 }
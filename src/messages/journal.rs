@@ -0,0 +1,369 @@
+//! Outbound queue journal
+//!
+//! Append-only JSONL write-ahead log backing the
+//! [`MessagePipeline`](super::outbound::MessagePipeline). Every state change
+//! of a queued message is written and synced to disk before the pipeline
+//! acknowledges it, so queued and retrying messages survive a crash or
+//! restart. The journal is periodically rewritten to drop completed entries.
+
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use super::outbound::{DeliveryStatus, MessageId, QueuedMessage};
+
+/// Minimum number of records before the journal is considered for compaction
+const COMPACT_MIN_RECORDS: usize = 256;
+
+/// Compact once the journal holds this many records per live entry
+const COMPACT_RATIO: usize = 4;
+
+/// A single journal line.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub(crate) enum JournalRecord {
+    /// Latest state of a message (queued, in-flight, or completed)
+    Message { entry: Box<QueuedMessage> },
+    /// Idempotency key recorded for a message
+    Key {
+        key: String,
+        message_id: MessageId,
+        created_at: i64,
+    },
+    /// Absolute pipeline counters, written at the end of a compacted journal
+    Counters { queued: u64, sent: u64, failed: u64 },
+}
+
+/// Pipeline state reconstructed from the journal.
+#[derive(Debug, Default)]
+pub(crate) struct ReplayedState {
+    /// Latest state of every message, in the order it was first queued
+    pub entries: Vec<QueuedMessage>,
+    /// Idempotency keys: key -> (message ID, created_at)
+    pub keys: HashMap<String, (MessageId, i64)>,
+    pub queued: u64,
+    pub sent: u64,
+    pub failed: u64,
+}
+
+/// Statistics about the on-disk journal
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct JournalStats {
+    /// Path of the journal file
+    pub path: PathBuf,
+    /// Records currently in the journal file
+    pub records: usize,
+    /// Pending messages restored from the journal at startup
+    pub replayed: usize,
+    /// Number of compactions since startup
+    pub compactions: u64,
+    /// When the journal was last compacted (Unix ms)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_compacted_at: Option<i64>,
+}
+
+/// Append-only journal file for the outbound pipeline.
+#[derive(Debug)]
+pub(crate) struct OutboundJournal {
+    path: PathBuf,
+    file: File,
+    records: usize,
+    replayed: usize,
+    compactions: u64,
+    last_compacted_at: Option<i64>,
+}
+
+impl OutboundJournal {
+    /// Open (or create) the journal at `path` and replay its contents.
+    pub fn open(path: impl Into<PathBuf>) -> io::Result<(Self, ReplayedState)> {
+        let path = path.into();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let (state, records) = if path.exists() {
+            replay(&path)?
+        } else {
+            (ReplayedState::default(), 0)
+        };
+
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let replayed = state
+            .entries
+            .iter()
+            .filter(|e| is_pending(e.status))
+            .count();
+
+        Ok((
+            Self {
+                path,
+                file,
+                records,
+                replayed,
+                compactions: 0,
+                last_compacted_at: None,
+            },
+            state,
+        ))
+    }
+
+    /// Append a record and sync it to disk.
+    pub fn append(&mut self, record: &JournalRecord) -> io::Result<()> {
+        let mut line = serde_json::to_vec(record).map_err(io::Error::other)?;
+        line.push(b'\n');
+        self.file.write_all(&line)?;
+        self.file.sync_data()?;
+        self.records += 1;
+        Ok(())
+    }
+
+    /// Whether the journal has grown enough relative to `live` entries to be
+    /// worth rewriting.
+    pub fn should_compact(&self, live: usize) -> bool {
+        self.records >= COMPACT_MIN_RECORDS && self.records > live.saturating_mul(COMPACT_RATIO)
+    }
+
+    /// Atomically replace the journal with `records`.
+    pub fn rewrite(&mut self, records: &[JournalRecord], now: i64) -> io::Result<()> {
+        let temp_path = self.path.with_extension("jsonl.tmp");
+
+        {
+            let file = File::create(&temp_path)?;
+            let mut writer = BufWriter::new(file);
+            for record in records {
+                serde_json::to_writer(&mut writer, record).map_err(io::Error::other)?;
+                writeln!(writer)?;
+            }
+            writer.flush()?;
+            writer.get_ref().sync_all()?;
+        }
+
+        // Atomic rename
+        fs::rename(&temp_path, &self.path)?;
+
+        self.file = OpenOptions::new().append(true).open(&self.path)?;
+        self.records = records.len();
+        self.compactions += 1;
+        self.last_compacted_at = Some(now);
+        Ok(())
+    }
+
+    /// Snapshot of the journal statistics
+    pub fn stats(&self) -> JournalStats {
+        JournalStats {
+            path: self.path.clone(),
+            records: self.records,
+            replayed: self.replayed,
+            compactions: self.compactions,
+            last_compacted_at: self.last_compacted_at,
+        }
+    }
+}
+
+/// Whether a message with this status still needs delivery.
+pub(crate) fn is_pending(status: DeliveryStatus) -> bool {
+    matches!(status, DeliveryStatus::Queued | DeliveryStatus::Sending)
+}
+
+/// Fold the journal at `path` into the latest state of every message.
+///
+/// Corrupt lines (e.g. a torn write from a crash) are skipped.
+fn replay(path: &Path) -> io::Result<(ReplayedState, usize)> {
+    let reader = BufReader::new(File::open(path)?);
+
+    let mut state = ReplayedState::default();
+    let mut index: HashMap<String, usize> = HashMap::new();
+    let mut records = 0;
+
+    for line in reader.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let record: JournalRecord = match serde_json::from_str(&line) {
+            Ok(r) => r,
+            Err(e) => {
+                tracing::warn!(
+                    path = %path.display(),
+                    error = %e,
+                    "skipping corrupt JSONL line in outbound journal"
+                );
+                continue;
+            }
+        };
+        records += 1;
+
+        match record {
+            JournalRecord::Message { entry } => {
+                let entry = *entry;
+                let previous = match index.get(&entry.message.id.0) {
+                    Some(&i) => Some(std::mem::replace(&mut state.entries[i], entry.clone())),
+                    None => {
+                        index.insert(entry.message.id.0.clone(), state.entries.len());
+                        state.entries.push(entry.clone());
+                        None
+                    }
+                };
+                let previous_status = previous.map(|p| p.status);
+                if previous_status.is_none() {
+                    state.queued += 1;
+                }
+                if previous_status != Some(entry.status) {
+                    match entry.status {
+                        DeliveryStatus::Sent => state.sent += 1,
+                        DeliveryStatus::Failed => state.failed += 1,
+                        _ => {}
+                    }
+                }
+            }
+            JournalRecord::Key {
+                key,
+                message_id,
+                created_at,
+            } => {
+                state.keys.insert(key, (message_id, created_at));
+            }
+            JournalRecord::Counters {
+                queued,
+                sent,
+                failed,
+            } => {
+                state.queued = queued;
+                state.sent = sent;
+                state.failed = failed;
+            }
+        }
+    }
+
+    Ok((state, records))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::messages::outbound::{MessageContent, OutboundContext, OutboundMessage};
+    use tempfile::tempdir;
+
+    fn entry(text: &str) -> QueuedMessage {
+        QueuedMessage::new(
+            OutboundMessage::new("telegram", MessageContent::text(text)),
+            OutboundContext::new(),
+        )
+    }
+
+    #[test]
+    fn test_replay_keeps_latest_state_in_queue_order() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("outbound.jsonl");
+
+        let mut first = entry("first");
+        let second = entry("second");
+        {
+            let (mut journal, state) = OutboundJournal::open(&path).unwrap();
+            assert!(state.entries.is_empty());
+            journal
+                .append(&JournalRecord::Message {
+                    entry: Box::new(first.clone()),
+                })
+                .unwrap();
+            journal
+                .append(&JournalRecord::Message {
+                    entry: Box::new(second.clone()),
+                })
+                .unwrap();
+            first.mark_sending();
+            first.mark_sent();
+            journal
+                .append(&JournalRecord::Message {
+                    entry: Box::new(first.clone()),
+                })
+                .unwrap();
+        }
+
+        let (journal, state) = OutboundJournal::open(&path).unwrap();
+        assert_eq!(state.entries.len(), 2);
+        assert_eq!(state.entries[0].message.id, first.message.id);
+        assert_eq!(state.entries[0].status, DeliveryStatus::Sent);
+        assert_eq!(state.entries[1].message.id, second.message.id);
+        assert_eq!((state.queued, state.sent, state.failed), (2, 1, 0));
+        assert_eq!(journal.stats().records, 3);
+        assert_eq!(journal.stats().replayed, 1);
+    }
+
+    #[test]
+    fn test_replay_skips_torn_line() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("outbound.jsonl");
+        let queued = entry("hello");
+        {
+            let (mut journal, _) = OutboundJournal::open(&path).unwrap();
+            journal
+                .append(&JournalRecord::Message {
+                    entry: Box::new(queued.clone()),
+                })
+                .unwrap();
+        }
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(b"{\"op\":\"message\",\"entry\":{\"mess")
+            .unwrap();
+
+        let (_, state) = OutboundJournal::open(&path).unwrap();
+        assert_eq!(state.entries.len(), 1);
+        assert_eq!(state.entries[0].message.id, queued.message.id);
+    }
+
+    #[test]
+    fn test_rewrite_replaces_contents_and_counters() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("outbound.jsonl");
+        let queued = entry("pending");
+
+        {
+            let (mut journal, _) = OutboundJournal::open(&path).unwrap();
+            for _ in 0..3 {
+                journal
+                    .append(&JournalRecord::Message {
+                        entry: Box::new(entry("done")),
+                    })
+                    .unwrap();
+            }
+            journal
+                .rewrite(
+                    &[
+                        JournalRecord::Message {
+                            entry: Box::new(queued.clone()),
+                        },
+                        JournalRecord::Counters {
+                            queued: 4,
+                            sent: 3,
+                            failed: 0,
+                        },
+                    ],
+                    1,
+                )
+                .unwrap();
+            assert_eq!(journal.stats().records, 2);
+            assert_eq!(journal.stats().compactions, 1);
+            assert!(!path.with_extension("jsonl.tmp").exists());
+        }
+
+        let (_, state) = OutboundJournal::open(&path).unwrap();
+        assert_eq!(state.entries.len(), 1);
+        assert_eq!((state.queued, state.sent, state.failed), (4, 3, 0));
+    }
+
+    #[test]
+    fn test_should_compact_threshold() {
+        let dir = tempdir().unwrap();
+        let (mut journal, _) = OutboundJournal::open(dir.path().join("q.jsonl")).unwrap();
+        journal.records = COMPACT_MIN_RECORDS - 1;
+        assert!(!journal.should_compact(0));
+        journal.records = COMPACT_MIN_RECORDS;
+        assert!(journal.should_compact(0));
+        assert!(!journal.should_compact(COMPACT_MIN_RECORDS));
+    }
+}
@@ -1,4 +1,6 @@
 //! Message pipeline module
 
 pub mod delivery;
+pub mod journal;
 pub mod outbound;
+pub mod ratelimit;
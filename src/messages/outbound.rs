@@ -2,15 +2,23 @@
 //!
 //! Provides types and interfaces for queuing and delivering messages
 //! to messaging channels.
+//!
+//! A pipeline opened with [`MessagePipeline::open`] is backed by an on-disk
+//! journal (see [`super::journal`]) and restores queued and retrying
+//! messages, plus idempotency keys, when the gateway restarts. Delivery is
+//! at-least-once: a message that was in flight during a crash is sent again.
 
-use parking_lot::RwLock;
+use parking_lot::{Mutex, MutexGuard, RwLock};
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, VecDeque};
+use std::path::PathBuf;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 use tokio::sync::Notify;
 use uuid::Uuid;
 
+use super::journal::{self, JournalRecord, JournalStats, OutboundJournal};
+
 /// Maximum number of completed messages to retain for status lookup
 const MAX_COMPLETED_MESSAGES: usize = 10_000;
 
@@ -20,6 +28,9 @@ const COMPLETED_MESSAGE_TTL_MS: i64 = 3600 * 1000;
 /// TTL for idempotency keys (24 hours)
 const IDEMPOTENCY_KEY_TTL_MS: i64 = 24 * 3600 * 1000;
 
+/// Maximum number of dead letters retained; the oldest are dropped first
+const MAX_DEAD_LETTERS: usize = 1000;
+
 /// Unique identifier for a message in the pipeline
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct MessageId(pub String);
@@ -130,6 +141,78 @@ impl MessageContent {
             mime_type: None,
         }
     }
+
+    /// Text body of a plain text message, if this is one
+    pub fn as_text(&self) -> Option<&str> {
+        match self {
+            Self::Text { text } => Some(text),
+            _ => None,
+        }
+    }
+}
+
+/// Channel-specific operation carried by an outbound message.
+///
+/// Messages without an action are plain sends. Actions that target an
+/// existing message reference it by its channel-native ID.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ChannelAction {
+    /// Replace the text of an existing message
+    Edit { target_id: String },
+    /// Delete (unsend) an existing message
+    Delete { target_id: String },
+    /// Add a reaction to an existing message
+    React { target_id: String, emoji: String },
+    /// Send a poll (text content is ignored)
+    Poll {
+        question: String,
+        options: Vec<String>,
+        #[serde(default)]
+        allow_multiple: bool,
+    },
+    /// Pin an existing message
+    Pin {
+        target_id: String,
+        #[serde(default)]
+        silent: bool,
+    },
+    /// Attach inline keyboard buttons to an existing message
+    ReplyMarkup {
+        target_id: String,
+        buttons: serde_json::Value,
+    },
+    /// Start a thread from an existing message
+    CreateThread {
+        target_id: String,
+        name: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        auto_archive_minutes: Option<u32>,
+    },
+    /// Send a rich embed (text content is the plain-text fallback)
+    Embed { embed: serde_json::Value },
+    /// Send Block Kit blocks (text content is the plain-text fallback)
+    Blocks { blocks: serde_json::Value },
+    /// Send a message visible only to one user
+    Ephemeral { user: String },
+}
+
+impl ChannelAction {
+    /// Short name of the action, for logging and error messages
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Edit { .. } => "edit",
+            Self::Delete { .. } => "delete",
+            Self::React { .. } => "react",
+            Self::Poll { .. } => "poll",
+            Self::Pin { .. } => "pin",
+            Self::ReplyMarkup { .. } => "reply_markup",
+            Self::CreateThread { .. } => "create_thread",
+            Self::Embed { .. } => "embed",
+            Self::Blocks { .. } => "blocks",
+            Self::Ephemeral { .. } => "ephemeral",
+        }
+    }
 }
 
 /// Metadata for message delivery context
@@ -150,6 +233,9 @@ pub struct MessageMetadata {
     /// Channel-specific extra data
     #[serde(skip_serializing_if = "Option::is_none")]
     pub extra: Option<serde_json::Value>,
+    /// Channel operation to perform instead of a plain send
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub action: Option<ChannelAction>,
     /// Priority (higher = more urgent, default 0)
     #[serde(default)]
     pub priority: i32,
@@ -283,6 +369,9 @@ pub struct QueuedMessage {
     pub last_error: Option<String>,
     /// When status was last updated (Unix ms)
     pub updated_at: i64,
+    /// Earliest time the next delivery attempt may run (Unix ms)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next_attempt_at: Option<i64>,
 }
 
 impl QueuedMessage {
@@ -295,6 +384,7 @@ impl QueuedMessage {
             attempts: 0,
             last_error: None,
             updated_at: now_millis(),
+            next_attempt_at: None,
         }
     }
 
@@ -329,15 +419,27 @@ impl QueuedMessage {
     /// Records the error from the failed attempt but resets status so the
     /// message will be picked up again by the delivery loop.
     pub fn mark_retry(&mut self, error: impl Into<String>) {
+        self.mark_retry_at(error, None);
+    }
+
+    /// Reset the message to Queued, deferring the next attempt until
+    /// `next_attempt_at` (Unix ms) when given.
+    pub fn mark_retry_at(&mut self, error: impl Into<String>, next_attempt_at: Option<i64>) {
         self.status = DeliveryStatus::Queued;
         self.last_error = Some(error.into());
         self.updated_at = now_millis();
+        self.next_attempt_at = next_attempt_at;
     }
 
     /// Check if the message can be retried
     pub fn can_retry(&self) -> bool {
         self.context.retry_enabled && self.attempts < self.context.max_retries
     }
+
+    /// Whether the message's backoff (if any) has elapsed at `now` (Unix ms)
+    pub fn is_due(&self, now: i64) -> bool {
+        self.next_attempt_at.is_none_or(|at| at <= now)
+    }
 }
 
 /// Delivery result fields returned from channel plugins (re-exported for convenience)
@@ -384,6 +486,8 @@ pub enum PipelineError {
     InvalidMessage(String),
     #[error("Delivery failed: {0}")]
     DeliveryFailed(String),
+    #[error("Journal error: {0}")]
+    Journal(String),
 }
 
 /// Statistics for the message pipeline
@@ -397,8 +501,13 @@ pub struct PipelineStats {
     pub total_failed: u64,
     /// Currently queued messages
     pub current_queue_size: usize,
+    /// Permanently failed messages held in the dead-letter store
+    pub dead_letters: usize,
     /// Messages by channel
     pub by_channel: HashMap<String, ChannelStats>,
+    /// On-disk journal statistics (persistent pipelines only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub persistence: Option<JournalStats>,
 }
 
 /// Per-channel statistics
@@ -433,6 +542,8 @@ pub struct MessagePipeline {
     messages: RwLock<HashMap<String, QueuedMessage>>,
     /// Idempotency key deduplication store: key -> entry
     idempotency_keys: RwLock<HashMap<String, IdempotencyEntry>>,
+    /// Permanently failed messages, oldest first
+    dead_letters: RwLock<VecDeque<QueuedMessage>>,
     /// Maximum queue size per channel
     max_queue_size: usize,
     /// Statistics counters
@@ -441,6 +552,11 @@ pub struct MessagePipeline {
     stats_failed: AtomicU64,
     /// Notify delivery workers when messages are queued
     notify: Arc<Notify>,
+    /// Write-ahead journal; `None` for in-memory pipelines.
+    ///
+    /// Mutating operations hold this lock for their whole duration so that
+    /// journal order matches in-memory order.
+    journal: Option<Mutex<OutboundJournal>>,
 }
 
 impl std::fmt::Debug for MessagePipeline {
@@ -449,8 +565,10 @@ impl std::fmt::Debug for MessagePipeline {
             .field("queues", &self.queues)
             .field("messages", &self.messages)
             .field("idempotency_keys", &self.idempotency_keys)
+            .field("dead_letters", &self.dead_letters)
             .field("max_queue_size", &self.max_queue_size)
             .field("notify", &"Notify")
+            .field("journal", &self.journal)
             .finish()
     }
 }
@@ -473,14 +591,88 @@ impl MessagePipeline {
             queues: RwLock::new(HashMap::new()),
             messages: RwLock::new(HashMap::new()),
             idempotency_keys: RwLock::new(HashMap::new()),
+            dead_letters: RwLock::new(VecDeque::new()),
             max_queue_size,
             stats_queued: AtomicU64::new(0),
             stats_sent: AtomicU64::new(0),
             stats_failed: AtomicU64::new(0),
             notify: Arc::new(Notify::new()),
+            journal: None,
         }
     }
 
+    /// Open a persistent pipeline backed by the journal at `path`.
+    ///
+    /// Replays the journal: queued and in-flight messages are re-queued (in
+    /// their original order), dead letters, idempotency keys and counters
+    /// are restored, and the journal is compacted.
+    pub fn open(path: impl Into<PathBuf>) -> Result<Self, PipelineError> {
+        Self::open_with_max_queue_size(path, 1000)
+    }
+
+    /// Open a persistent pipeline with a custom max queue size
+    pub fn open_with_max_queue_size(
+        path: impl Into<PathBuf>,
+        max_queue_size: usize,
+    ) -> Result<Self, PipelineError> {
+        let (mut journal, replayed) =
+            OutboundJournal::open(path).map_err(|e| PipelineError::Journal(e.to_string()))?;
+
+        let mut pipeline = Self::with_max_queue_size(max_queue_size);
+        {
+            let mut queues = pipeline.queues.write();
+            let mut messages = pipeline.messages.write();
+            let mut dead_letters = pipeline.dead_letters.write();
+            for mut entry in replayed.entries {
+                if journal::is_pending(entry.status) {
+                    // In-flight at shutdown: the send may or may not have
+                    // happened, so deliver it again.
+                    entry.status = DeliveryStatus::Queued;
+                    queues
+                        .entry(entry.message.channel_id.clone())
+                        .or_default()
+                        .push_back(entry.clone());
+                } else if entry.status == DeliveryStatus::Failed {
+                    dead_letters.push_back(entry.clone());
+                }
+                messages.insert(entry.message.id.0.clone(), entry);
+            }
+            while dead_letters.len() > MAX_DEAD_LETTERS {
+                dead_letters.pop_front();
+            }
+        }
+        {
+            let now = now_millis();
+            let mut idempotency_store = pipeline.idempotency_keys.write();
+            for (key, (message_id, created_at)) in replayed.keys {
+                if now - created_at < IDEMPOTENCY_KEY_TTL_MS {
+                    idempotency_store.insert(
+                        key,
+                        IdempotencyEntry {
+                            message_id,
+                            created_at,
+                        },
+                    );
+                }
+            }
+        }
+        pipeline.stats_queued = AtomicU64::new(replayed.queued);
+        pipeline.stats_sent = AtomicU64::new(replayed.sent);
+        pipeline.stats_failed = AtomicU64::new(replayed.failed);
+
+        pipeline
+            .rewrite_journal(&mut journal)
+            .map_err(|e| PipelineError::Journal(e.to_string()))?;
+        pipeline.journal = Some(Mutex::new(journal));
+
+        Ok(pipeline)
+    }
+
+    /// Whether this pipeline is backed by an on-disk journal
+    pub fn is_persistent(&self) -> bool {
+        self.journal.is_some()
+    }
+
     /// Get the notifier for delivery workers to await on
     pub fn notifier(&self) -> &Arc<Notify> {
         &self.notify
@@ -508,6 +700,8 @@ impl MessagePipeline {
         context: OutboundContext,
         idempotency_key: Option<&str>,
     ) -> Result<QueueResult, PipelineError> {
+        let mut journal = self.lock_journal();
+
         // Check idempotency key for deduplication
         if let Some(key) = idempotency_key {
             let now = now_millis();
@@ -552,6 +746,26 @@ impl MessagePipeline {
             }
         }
 
+        // Persist before acknowledging so the message survives a crash
+        let key_created_at = now_millis();
+        if let Some(journal) = journal.as_deref_mut() {
+            let mut records = vec![JournalRecord::Message {
+                entry: Box::new(queued.clone()),
+            }];
+            if let Some(key) = idempotency_key {
+                records.push(JournalRecord::Key {
+                    key: key.to_string(),
+                    message_id: message_id.clone(),
+                    created_at: key_created_at,
+                });
+            }
+            for record in &records {
+                journal
+                    .append(record)
+                    .map_err(|e| PipelineError::Journal(e.to_string()))?;
+            }
+        }
+
         // Add to queues
         let queue_position = {
             let mut queues = self.queues.write();
@@ -573,7 +787,7 @@ impl MessagePipeline {
                 key.to_string(),
                 IdempotencyEntry {
                     message_id: message_id.clone(),
-                    created_at: now_millis(),
+                    created_at: key_created_at,
                 },
             );
         }
@@ -607,11 +821,13 @@ impl MessagePipeline {
     ///
     /// Only works for messages that haven't been sent yet.
     pub fn cancel(&self, message_id: &MessageId) -> Result<(), PipelineError> {
+        let mut journal = self.lock_journal();
         {
             let mut messages = self.messages.write();
             if let Some(queued) = messages.get_mut(&message_id.0) {
                 if queued.status == DeliveryStatus::Queued {
                     queued.mark_cancelled();
+                    Self::journal_entry(journal.as_deref_mut(), queued);
                 } else {
                     return Err(PipelineError::InvalidMessage(format!(
                         "Cannot cancel message with status: {}",
@@ -624,6 +840,7 @@ impl MessagePipeline {
         }
 
         self.remove_from_queue(message_id);
+        self.maybe_compact(journal.as_deref_mut());
         Ok(())
     }
 
@@ -633,6 +850,7 @@ impl MessagePipeline {
         message_id: &MessageId,
         message: OutboundMessage,
     ) -> Result<(), PipelineError> {
+        let mut journal = self.lock_journal();
         let channel_id = {
             let mut messages = self.messages.write();
             if let Some(queued) = messages.get_mut(&message_id.0) {
@@ -648,6 +866,7 @@ impl MessagePipeline {
                     ));
                 }
                 queued.message = message.clone();
+                Self::journal_entry(journal.as_deref_mut(), queued);
                 queued.message.channel_id.clone()
             } else {
                 return Err(PipelineError::MessageNotFound(message_id.0.clone()));
@@ -681,13 +900,18 @@ impl MessagePipeline {
 
     /// Get the next message to deliver for a channel
     ///
-    /// This is used by delivery workers to get messages to send.
+    /// This is used by delivery workers to get messages to send. Messages
+    /// still waiting out a retry backoff are skipped.
     pub fn next_for_channel(&self, channel_id: &str) -> Option<QueuedMessage> {
+        let now = now_millis();
         let queues = self.queues.read();
         if let Some(queue) = queues.get(channel_id) {
             // Find first non-cancelled, non-expired message
             for msg in queue.iter() {
-                if msg.status == DeliveryStatus::Queued && !msg.message.is_expired() {
+                if msg.status == DeliveryStatus::Queued
+                    && !msg.message.is_expired()
+                    && msg.is_due(now)
+                {
                     return Some(msg.clone());
                 }
             }
@@ -695,15 +919,35 @@ impl MessagePipeline {
         None
     }
 
+    /// Time until the earliest queued message on a channel becomes due.
+    ///
+    /// Returns `None` when the channel has no queued messages and
+    /// `Some(Duration::ZERO)` when one is ready now.
+    pub fn next_due_in(&self, channel_id: &str) -> Option<std::time::Duration> {
+        let now = now_millis();
+        let queues = self.queues.read();
+        queues
+            .get(channel_id)?
+            .iter()
+            .filter(|msg| msg.status == DeliveryStatus::Queued && !msg.message.is_expired())
+            .map(|msg| {
+                let at = msg.next_attempt_at.unwrap_or(now);
+                std::time::Duration::from_millis((at - now).max(0) as u64)
+            })
+            .min()
+    }
+
     /// Mark a message as being sent
     ///
     /// Updates both the `messages` lookup map and the `queues` entry so that
     /// `next_for_channel` will not return a message that is already in-flight.
     pub fn mark_sending(&self, message_id: &MessageId) -> Result<(), PipelineError> {
+        let mut journal = self.lock_journal();
         let channel_id = {
             let mut messages = self.messages.write();
             if let Some(queued) = messages.get_mut(&message_id.0) {
                 queued.mark_sending();
+                Self::journal_entry(journal.as_deref_mut(), queued);
                 queued.message.channel_id.clone()
             } else {
                 return Err(PipelineError::MessageNotFound(message_id.0.clone()));
@@ -726,10 +970,12 @@ impl MessagePipeline {
 
     /// Mark a message as sent successfully
     pub fn mark_sent(&self, message_id: &MessageId) -> Result<(), PipelineError> {
+        let mut journal = self.lock_journal();
         {
             let mut messages = self.messages.write();
             if let Some(queued) = messages.get_mut(&message_id.0) {
                 queued.mark_sent();
+                Self::journal_entry(journal.as_deref_mut(), queued);
             } else {
                 return Err(PipelineError::MessageNotFound(message_id.0.clone()));
             }
@@ -742,6 +988,7 @@ impl MessagePipeline {
 
         // Clean up old completed messages to prevent memory leak
         self.maybe_cleanup_completed();
+        self.maybe_compact(journal.as_deref_mut());
 
         Ok(())
     }
@@ -755,11 +1002,26 @@ impl MessagePipeline {
         message_id: &MessageId,
         error: impl Into<String>,
     ) -> Result<(), PipelineError> {
+        self.mark_retry_after(message_id, error, std::time::Duration::ZERO)
+    }
+
+    /// Reset a message to Queued, deferring its next attempt by `delay`.
+    ///
+    /// `next_for_channel` skips the message until the delay has elapsed.
+    pub fn mark_retry_after(
+        &self,
+        message_id: &MessageId,
+        error: impl Into<String>,
+        delay: std::time::Duration,
+    ) -> Result<(), PipelineError> {
+        let next_attempt_at = (!delay.is_zero()).then(|| now_millis() + delay.as_millis() as i64);
+        let mut journal = self.lock_journal();
         let (channel_id, error_str) = {
             let error_string = error.into();
             let mut messages = self.messages.write();
             if let Some(queued) = messages.get_mut(&message_id.0) {
-                queued.mark_retry(&error_string);
+                queued.mark_retry_at(&error_string, next_attempt_at);
+                Self::journal_entry(journal.as_deref_mut(), queued);
                 (queued.message.channel_id.clone(), error_string)
             } else {
                 return Err(PipelineError::MessageNotFound(message_id.0.clone()));
@@ -771,7 +1033,7 @@ impl MessagePipeline {
         if let Some(queue) = queues.get_mut(&channel_id) {
             for entry in queue.iter_mut() {
                 if entry.message.id == *message_id {
-                    entry.mark_retry(error_str);
+                    entry.mark_retry_at(error_str, next_attempt_at);
                     break;
                 }
             }
@@ -780,16 +1042,24 @@ impl MessagePipeline {
         Ok(())
     }
 
-    /// Mark a message as failed
+    /// Mark a message as permanently failed and move it to the dead-letter
+    /// store
     pub fn mark_failed(
         &self,
         message_id: &MessageId,
         error: impl Into<String>,
     ) -> Result<(), PipelineError> {
+        let mut journal = self.lock_journal();
         {
             let mut messages = self.messages.write();
             if let Some(queued) = messages.get_mut(&message_id.0) {
                 queued.mark_failed(error);
+                Self::journal_entry(journal.as_deref_mut(), queued);
+                let mut dead_letters = self.dead_letters.write();
+                dead_letters.push_back(queued.clone());
+                if dead_letters.len() > MAX_DEAD_LETTERS {
+                    dead_letters.pop_front();
+                }
             } else {
                 return Err(PipelineError::MessageNotFound(message_id.0.clone()));
             }
@@ -802,6 +1072,7 @@ impl MessagePipeline {
 
         // Clean up old completed messages to prevent memory leak
         self.maybe_cleanup_completed();
+        self.maybe_compact(journal.as_deref_mut());
 
         Ok(())
     }
@@ -843,6 +1114,183 @@ impl MessagePipeline {
         before - messages.len()
     }
 
+    /// List dead letters, oldest first
+    pub fn dead_letters(&self) -> Vec<QueuedMessage> {
+        self.dead_letters.read().iter().cloned().collect()
+    }
+
+    /// Number of dead letters currently held
+    pub fn dead_letter_count(&self) -> usize {
+        self.dead_letters.read().len()
+    }
+
+    /// Move a dead letter back onto its channel queue with a fresh retry
+    /// budget.
+    pub fn retry_dead_letter(&self, message_id: &MessageId) -> Result<QueueResult, PipelineError> {
+        let mut journal = self.lock_journal();
+
+        let mut entry = {
+            let mut dead_letters = self.dead_letters.write();
+            let position = dead_letters
+                .iter()
+                .position(|entry| entry.message.id == *message_id)
+                .ok_or_else(|| PipelineError::MessageNotFound(message_id.0.clone()))?;
+            if self.queue_size(&dead_letters[position].message.channel_id) >= self.max_queue_size {
+                return Err(PipelineError::QueueFull(
+                    dead_letters[position].message.channel_id.clone(),
+                ));
+            }
+            dead_letters
+                .remove(position)
+                .ok_or_else(|| PipelineError::MessageNotFound(message_id.0.clone()))?
+        };
+
+        entry.status = DeliveryStatus::Queued;
+        entry.attempts = 0;
+        entry.next_attempt_at = None;
+        entry.updated_at = now_millis();
+        Self::journal_entry(journal.as_deref_mut(), &entry);
+
+        let queue_position = {
+            let mut queues = self.queues.write();
+            let queue = queues.entry(entry.message.channel_id.clone()).or_default();
+            queue.push_back(entry.clone());
+            queue.len()
+        };
+        self.messages.write().insert(message_id.0.clone(), entry);
+
+        self.notify.notify_one();
+
+        Ok(QueueResult {
+            message_id: message_id.clone(),
+            status: DeliveryStatus::Queued,
+            queue_position: Some(queue_position),
+            delivery_result: None,
+        })
+    }
+
+    /// Delete dead letters: the given IDs, or all of them when `ids` is
+    /// `None`. Returns the number removed.
+    pub fn purge_dead_letters(&self, ids: Option<&[MessageId]>) -> usize {
+        let mut journal = self.lock_journal();
+
+        let purged: Vec<QueuedMessage> = {
+            let mut dead_letters = self.dead_letters.write();
+            let (purged, kept): (Vec<_>, Vec<_>) = dead_letters
+                .drain(..)
+                .partition(|entry| ids.is_none_or(|ids| ids.contains(&entry.message.id)));
+            *dead_letters = kept.into();
+            purged
+        };
+        if purged.is_empty() {
+            return 0;
+        }
+
+        {
+            let mut messages = self.messages.write();
+            for entry in &purged {
+                messages.remove(&entry.message.id.0);
+            }
+        }
+
+        // Rewrite so purged entries are not restored on restart
+        if let Some(journal) = journal.as_deref_mut() {
+            if let Err(e) = self.rewrite_journal(journal) {
+                tracing::warn!(error = %e, "failed to compact outbound journal after purge");
+            }
+        }
+
+        purged.len()
+    }
+
+    /// Lock the journal, if this pipeline is persistent
+    fn lock_journal(&self) -> Option<MutexGuard<'_, OutboundJournal>> {
+        self.journal.as_ref().map(|journal| journal.lock())
+    }
+
+    /// Record the latest state of a message in the journal.
+    ///
+    /// The in-memory transition has already happened, so a write failure is
+    /// logged rather than returned; the next compaction rewrites the entry.
+    fn journal_entry(journal: Option<&mut OutboundJournal>, entry: &QueuedMessage) {
+        let Some(journal) = journal else {
+            return;
+        };
+        if let Err(e) = journal.append(&JournalRecord::Message {
+            entry: Box::new(entry.clone()),
+        }) {
+            tracing::warn!(
+                id = %entry.message.id,
+                error = %e,
+                "failed to write outbound journal entry"
+            );
+        }
+    }
+
+    /// Compact the journal once it has accumulated enough completed entries
+    fn maybe_compact(&self, journal: Option<&mut OutboundJournal>) {
+        let Some(journal) = journal else {
+            return;
+        };
+        let live =
+            self.total_queue_size() + self.dead_letter_count() + self.idempotency_keys.read().len();
+        if !journal.should_compact(live) {
+            return;
+        }
+        if let Err(e) = self.rewrite_journal(journal) {
+            tracing::warn!(error = %e, "failed to compact outbound journal");
+        }
+    }
+
+    /// Rewrite the journal from the current in-memory state.
+    ///
+    /// Pending messages are written in queue order, followed by dead letters,
+    /// live idempotency keys and the absolute counters. Other completed
+    /// messages are dropped; their keys still deduplicate after a restart.
+    fn rewrite_journal(&self, journal: &mut OutboundJournal) -> std::io::Result<()> {
+        let now = now_millis();
+        let idempotency_cutoff = now - IDEMPOTENCY_KEY_TTL_MS;
+
+        let mut records = Vec::new();
+        {
+            let queues = self.queues.read();
+            let messages = self.messages.read();
+            for queue in queues.values() {
+                for queued in queue {
+                    if let Some(entry) = messages.get(&queued.message.id.0) {
+                        records.push(JournalRecord::Message {
+                            entry: Box::new(entry.clone()),
+                        });
+                    }
+                }
+            }
+        }
+        for entry in self.dead_letters.read().iter() {
+            records.push(JournalRecord::Message {
+                entry: Box::new(entry.clone()),
+            });
+        }
+        {
+            let idempotency_store = self.idempotency_keys.read();
+            for (key, entry) in idempotency_store.iter() {
+                if entry.created_at > idempotency_cutoff {
+                    records.push(JournalRecord::Key {
+                        key: key.clone(),
+                        message_id: entry.message_id.clone(),
+                        created_at: entry.created_at,
+                    });
+                }
+            }
+        }
+        records.push(JournalRecord::Counters {
+            queued: self.stats_queued.load(Ordering::Relaxed),
+            sent: self.stats_sent.load(Ordering::Relaxed),
+            failed: self.stats_failed.load(Ordering::Relaxed),
+        });
+
+        journal.rewrite(&records, now)
+    }
+
     /// Remove a message from its channel queue
     fn remove_from_queue(&self, message_id: &MessageId) {
         // Get the channel ID first
@@ -895,18 +1343,29 @@ impl MessagePipeline {
             total_sent: self.stats_sent.load(Ordering::Relaxed),
             total_failed: self.stats_failed.load(Ordering::Relaxed),
             current_queue_size: self.total_queue_size(),
+            dead_letters: self.dead_letter_count(),
             by_channel,
+            persistence: self.journal.as_ref().map(|journal| journal.lock().stats()),
         }
     }
 
     /// Clear all queues (for testing or shutdown)
     pub fn clear(&self) {
-        let mut queues = self.queues.write();
-        let mut messages = self.messages.write();
-        let mut idempotency_store = self.idempotency_keys.write();
-        queues.clear();
-        messages.clear();
-        idempotency_store.clear();
+        let mut journal = self.lock_journal();
+        {
+            let mut queues = self.queues.write();
+            let mut messages = self.messages.write();
+            let mut idempotency_store = self.idempotency_keys.write();
+            queues.clear();
+            messages.clear();
+            idempotency_store.clear();
+            self.dead_letters.write().clear();
+        }
+        if let Some(journal) = journal.as_deref_mut() {
+            if let Err(e) = self.rewrite_journal(journal) {
+                tracing::warn!(error = %e, "failed to clear outbound journal");
+            }
+        }
     }
 
     /// List all channel IDs with queued messages
@@ -925,6 +1384,25 @@ pub fn create_pipeline() -> Arc<MessagePipeline> {
     Arc::new(MessagePipeline::new())
 }
 
+/// Create a shared message pipeline backed by the journal at `path`.
+///
+/// Falls back to an in-memory pipeline (with a warning) if the journal
+/// cannot be opened, so a bad state directory does not block startup.
+pub fn create_persistent_pipeline(path: impl Into<PathBuf>) -> Arc<MessagePipeline> {
+    let path = path.into();
+    match MessagePipeline::open(&path) {
+        Ok(pipeline) => Arc::new(pipeline),
+        Err(e) => {
+            tracing::warn!(
+                path = %path.display(),
+                error = %e,
+                "failed to open outbound journal; queued messages will not survive restarts"
+            );
+            create_pipeline()
+        }
+    }
+}
+
 /// Get current time in milliseconds since Unix epoch
 fn now_millis() -> i64 {
     use std::time::{SystemTime, UNIX_EPOCH};
@@ -1594,4 +2072,239 @@ mod tests {
             assert!(store.is_empty());
         }
     }
+
+    #[test]
+    fn test_persistent_pipeline_replays_pending_messages() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("outbound").join("queue.jsonl");
+
+        let (sent_id, in_flight_id, queued_id) = {
+            let pipeline = MessagePipeline::open(&path).unwrap();
+            assert!(pipeline.is_persistent());
+            let ids: Vec<MessageId> = ["sent", "in flight", "queued"]
+                .into_iter()
+                .map(|text| {
+                    pipeline
+                        .queue(
+                            OutboundMessage::new("telegram", MessageContent::text(text)),
+                            OutboundContext::new().with_retries(3),
+                        )
+                        .unwrap()
+                        .message_id
+                })
+                .collect();
+            pipeline.mark_sending(&ids[0]).unwrap();
+            pipeline.mark_sent(&ids[0]).unwrap();
+            // Simulate a crash while the second message is in flight
+            pipeline.mark_sending(&ids[1]).unwrap();
+            (ids[0].clone(), ids[1].clone(), ids[2].clone())
+        };
+
+        let pipeline = MessagePipeline::open(&path).unwrap();
+        assert_eq!(pipeline.queue_size("telegram"), 2);
+        assert_eq!(pipeline.get_status(&sent_id), Some(DeliveryStatus::Sent));
+
+        let next = pipeline.next_for_channel("telegram").unwrap();
+        assert_eq!(next.message.id, in_flight_id);
+        assert_eq!(next.attempts, 1);
+        assert!(pipeline.can_retry(&in_flight_id));
+        assert_eq!(
+            pipeline.get_status(&queued_id),
+            Some(DeliveryStatus::Queued)
+        );
+
+        let stats = pipeline.stats();
+        assert_eq!(stats.total_queued, 3);
+        assert_eq!(stats.total_sent, 1);
+        let persistence = stats.persistence.unwrap();
+        assert_eq!(persistence.replayed, 2);
+        assert_eq!(persistence.path, path);
+    }
+
+    #[test]
+    fn test_persistent_pipeline_idempotency_survives_restart() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("queue.jsonl");
+
+        let original = {
+            let pipeline = MessagePipeline::open(&path).unwrap();
+            pipeline
+                .queue_with_idempotency(
+                    OutboundMessage::new("telegram", MessageContent::text("Hello")),
+                    OutboundContext::new(),
+                    Some("restart-key"),
+                )
+                .unwrap()
+        };
+
+        let pipeline = MessagePipeline::open(&path).unwrap();
+        let duplicate = pipeline
+            .queue_with_idempotency(
+                OutboundMessage::new("telegram", MessageContent::text("Hello")),
+                OutboundContext::new(),
+                Some("restart-key"),
+            )
+            .unwrap();
+        assert_eq!(duplicate.message_id, original.message_id);
+        assert_eq!(duplicate.status, DeliveryStatus::Queued);
+        assert_eq!(pipeline.queue_size("telegram"), 1);
+    }
+
+    #[test]
+    fn test_persistent_pipeline_compacts_completed_messages() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("queue.jsonl");
+
+        {
+            let pipeline = MessagePipeline::open(&path).unwrap();
+            for i in 0..200 {
+                let id = pipeline
+                    .queue(
+                        OutboundMessage::new("telegram", MessageContent::text(format!("{i}"))),
+                        OutboundContext::new(),
+                    )
+                    .unwrap()
+                    .message_id;
+                pipeline.mark_sending(&id).unwrap();
+                pipeline.mark_sent(&id).unwrap();
+            }
+            let persistence = pipeline.stats().persistence.unwrap();
+            assert!(persistence.compactions >= 2);
+            assert!(persistence.records < 600);
+        }
+
+        // Completed messages are dropped; counters are carried over
+        let pipeline = MessagePipeline::open(&path).unwrap();
+        assert_eq!(pipeline.total_queue_size(), 0);
+        let stats = pipeline.stats();
+        assert_eq!(stats.total_queued, 200);
+        assert_eq!(stats.total_sent, 200);
+    }
+
+    #[test]
+    fn test_persistent_pipeline_clear_truncates_journal() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("queue.jsonl");
+
+        {
+            let pipeline = MessagePipeline::open(&path).unwrap();
+            pipeline
+                .queue(
+                    OutboundMessage::new("telegram", MessageContent::text("Hello")),
+                    OutboundContext::new(),
+                )
+                .unwrap();
+            pipeline.clear();
+        }
+
+        let pipeline = MessagePipeline::open(&path).unwrap();
+        assert_eq!(pipeline.total_queue_size(), 0);
+    }
+
+    #[test]
+    fn test_pipeline_mark_retry_after_defers_message() {
+        let pipeline = MessagePipeline::new();
+        let id = pipeline
+            .queue(
+                OutboundMessage::new("telegram", MessageContent::text("Hello")),
+                OutboundContext::new().with_retries(3),
+            )
+            .unwrap()
+            .message_id;
+        pipeline.mark_sending(&id).unwrap();
+        pipeline
+            .mark_retry_after(&id, "busy", std::time::Duration::from_secs(60))
+            .unwrap();
+
+        assert!(pipeline.next_for_channel("telegram").is_none());
+        let due_in = pipeline.next_due_in("telegram").unwrap();
+        assert!(due_in > std::time::Duration::from_secs(59));
+        assert_eq!(pipeline.next_due_in("discord"), None);
+
+        // A zero delay makes it due again immediately
+        pipeline
+            .mark_retry_after(&id, "busy", std::time::Duration::ZERO)
+            .unwrap();
+        assert_eq!(
+            pipeline.next_for_channel("telegram").unwrap().message.id,
+            id
+        );
+    }
+
+    #[test]
+    fn test_dead_letter_retry_and_purge() {
+        let pipeline = MessagePipeline::new();
+        let ids: Vec<MessageId> = (0..3)
+            .map(|i| {
+                let id = pipeline
+                    .queue(
+                        OutboundMessage::new("telegram", MessageContent::text(format!("{i}"))),
+                        OutboundContext::new().with_retries(2),
+                    )
+                    .unwrap()
+                    .message_id;
+                pipeline.mark_sending(&id).unwrap();
+                pipeline.mark_failed(&id, "boom").unwrap();
+                id
+            })
+            .collect();
+        assert_eq!(pipeline.dead_letter_count(), 3);
+        assert_eq!(pipeline.total_queue_size(), 0);
+
+        let requeued = pipeline.retry_dead_letter(&ids[0]).unwrap();
+        assert_eq!(requeued.status, DeliveryStatus::Queued);
+        let next = pipeline.next_for_channel("telegram").unwrap();
+        assert_eq!(next.message.id, ids[0]);
+        assert_eq!(next.attempts, 0);
+        assert_eq!(pipeline.dead_letter_count(), 2);
+        assert!(matches!(
+            pipeline.retry_dead_letter(&ids[0]),
+            Err(PipelineError::MessageNotFound(_))
+        ));
+
+        assert_eq!(pipeline.purge_dead_letters(Some(&ids[1..2])), 1);
+        assert_eq!(pipeline.dead_letters()[0].message.id, ids[2]);
+        assert!(pipeline.get_message(&ids[1]).is_none());
+        assert_eq!(pipeline.purge_dead_letters(None), 1);
+        assert_eq!(pipeline.dead_letter_count(), 0);
+    }
+
+    #[test]
+    fn test_persistent_pipeline_restores_dead_letters() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("queue.jsonl");
+
+        let (kept, purged) = {
+            let pipeline = MessagePipeline::open(&path).unwrap();
+            let ids: Vec<MessageId> = (0..2)
+                .map(|i| {
+                    let id = pipeline
+                        .queue(
+                            OutboundMessage::new("slack", MessageContent::text(format!("{i}"))),
+                            OutboundContext::new(),
+                        )
+                        .unwrap()
+                        .message_id;
+                    pipeline.mark_failed(&id, "channel_not_found").unwrap();
+                    id
+                })
+                .collect();
+            pipeline.purge_dead_letters(Some(&ids[1..]));
+            (ids[0].clone(), ids[1].clone())
+        };
+
+        let pipeline = MessagePipeline::open(&path).unwrap();
+        let dead = pipeline.dead_letters();
+        assert_eq!(dead.len(), 1);
+        assert_eq!(dead[0].message.id, kept);
+        assert_ne!(dead[0].message.id, purged);
+        assert_eq!(pipeline.total_queue_size(), 0);
+
+        // Retrying survives another restart as a queued message
+        pipeline.retry_dead_letter(&kept).unwrap();
+        drop(pipeline);
+        let pipeline = MessagePipeline::open(&path).unwrap();
+        assert_eq!(pipeline.dead_letter_count(), 0);
+        assert_eq!(pipeline.next_for_channel("slack").unwrap().message.id, kept);
+    }
 }
@@ -0,0 +1,191 @@
+//! Per-channel outbound rate limiting
+//!
+//! Each channel gets a token bucket sized to the platform's documented send
+//! limits. A channel can also be paused outright when the platform answers
+//! with a rate-limit hint (`retry_after`, HTTP 429), so the whole channel
+//! backs off rather than just the message that tripped the limit.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use parking_lot::Mutex;
+
+/// Sustained rate and burst size for a channel's token bucket.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ChannelRate {
+    /// Messages per second
+    pub rate: f64,
+    /// Maximum burst
+    pub burst: u32,
+}
+
+impl ChannelRate {
+    pub fn new(rate: f64, burst: u32) -> Self {
+        Self {
+            rate: rate.max(0.001),
+            burst: burst.max(1),
+        }
+    }
+
+    /// Default limits for built-in channels.
+    ///
+    /// Telegram allows ~30 msgs/s per bot, Discord ~50 requests/s globally,
+    /// and Slack `chat.postMessage` ~1 msg/s per channel.
+    pub fn default_for(channel_id: &str) -> Self {
+        match channel_id {
+            "telegram" => Self::new(25.0, 30),
+            "discord" => Self::new(40.0, 50),
+            "slack" => Self::new(1.0, 5),
+            "signal" => Self::new(5.0, 10),
+            _ => Self::new(10.0, 20),
+        }
+    }
+}
+
+/// Token bucket state for a single channel
+#[derive(Debug)]
+struct ChannelBucket {
+    tokens: f64,
+    last_refill: Instant,
+    limit: ChannelRate,
+    paused_until: Option<Instant>,
+}
+
+impl ChannelBucket {
+    fn new(limit: ChannelRate) -> Self {
+        Self {
+            tokens: limit.burst as f64,
+            last_refill: Instant::now(),
+            limit,
+            paused_until: None,
+        }
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.duration_since(self.last_refill).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.limit.rate).min(self.limit.burst as f64);
+        self.last_refill = now;
+    }
+
+    /// Time until a send is allowed, or zero if one is allowed now.
+    fn wait_time(&mut self, now: Instant) -> Duration {
+        if let Some(until) = self.paused_until {
+            if until > now {
+                return until - now;
+            }
+            self.paused_until = None;
+        }
+        self.refill(now);
+        if self.tokens >= 1.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64((1.0 - self.tokens) / self.limit.rate)
+        }
+    }
+}
+
+/// Token-bucket rate limiter keyed by channel ID.
+#[derive(Debug, Default)]
+pub struct ChannelRateLimiter {
+    buckets: Mutex<HashMap<String, ChannelBucket>>,
+    overrides: HashMap<String, ChannelRate>,
+}
+
+impl ChannelRateLimiter {
+    /// Create a limiter using the built-in defaults for every channel.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a limiter with per-channel overrides of the defaults.
+    pub fn with_overrides(overrides: HashMap<String, ChannelRate>) -> Self {
+        Self {
+            buckets: Mutex::new(HashMap::new()),
+            overrides,
+        }
+    }
+
+    fn limit_for(&self, channel_id: &str) -> ChannelRate {
+        self.overrides
+            .get(channel_id)
+            .copied()
+            .unwrap_or_else(|| ChannelRate::default_for(channel_id))
+    }
+
+    /// Try to take a send slot for `channel_id`.
+    ///
+    /// Returns `Err(wait)` with the time until a slot frees up when the
+    /// channel is over its limit or paused.
+    pub fn try_acquire(&self, channel_id: &str) -> Result<(), Duration> {
+        let now = Instant::now();
+        let mut buckets = self.buckets.lock();
+        let bucket = buckets
+            .entry(channel_id.to_string())
+            .or_insert_with(|| ChannelBucket::new(self.limit_for(channel_id)));
+        let wait = bucket.wait_time(now);
+        if wait.is_zero() {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(wait)
+        }
+    }
+
+    /// Pause all sends on `channel_id` for `duration` (platform retry hint).
+    ///
+    /// An existing longer pause is kept.
+    pub fn pause(&self, channel_id: &str, duration: Duration) {
+        let until = Instant::now() + duration;
+        let mut buckets = self.buckets.lock();
+        let bucket = buckets
+            .entry(channel_id.to_string())
+            .or_insert_with(|| ChannelBucket::new(self.limit_for(channel_id)));
+        if bucket.paused_until.is_none_or(|current| current < until) {
+            bucket.paused_until = Some(until);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bucket_allows_burst_then_limits() {
+        let limiter = ChannelRateLimiter::with_overrides(HashMap::from([(
+            "slow".to_string(),
+            ChannelRate::new(1.0, 2),
+        )]));
+        assert!(limiter.try_acquire("slow").is_ok());
+        assert!(limiter.try_acquire("slow").is_ok());
+        let wait = limiter.try_acquire("slow").unwrap_err();
+        assert!(wait > Duration::ZERO && wait <= Duration::from_secs(1));
+
+        // Buckets are independent per channel
+        assert!(limiter.try_acquire("telegram").is_ok());
+    }
+
+    #[test]
+    fn test_pause_blocks_channel_until_elapsed() {
+        let limiter = ChannelRateLimiter::new();
+        limiter.pause("discord", Duration::from_secs(30));
+        let wait = limiter.try_acquire("discord").unwrap_err();
+        assert!(wait > Duration::from_secs(29));
+
+        // A shorter hint does not shorten an existing pause
+        limiter.pause("discord", Duration::from_secs(1));
+        assert!(limiter.try_acquire("discord").unwrap_err() > Duration::from_secs(29));
+
+        limiter.pause("slack", Duration::ZERO);
+        assert!(limiter.try_acquire("slack").is_ok());
+    }
+
+    #[test]
+    fn test_default_rates() {
+        assert_eq!(ChannelRate::default_for("slack"), ChannelRate::new(1.0, 5));
+        assert_eq!(
+            ChannelRate::default_for("custom-plugin"),
+            ChannelRate::new(10.0, 20)
+        );
+    }
+}
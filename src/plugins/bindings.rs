@@ -63,6 +63,25 @@ pub struct DeliveryResult {
     pub to_jid: Option<String>,
     /// Poll identifier when the message is a poll
     pub poll_id: Option<String>,
+    /// Platform hint for how long to wait before retrying (e.g. HTTP 429
+    /// `Retry-After`), in milliseconds
+    pub retry_after_ms: Option<u64>,
+}
+
+impl DeliveryResult {
+    /// Non-retryable failure for an operation the channel does not support.
+    pub fn unsupported(operation: &str) -> Self {
+        Self {
+            ok: false,
+            message_id: None,
+            error: Some(format!("{operation} is not supported by this channel")),
+            retryable: false,
+            conversation_id: None,
+            to_jid: None,
+            poll_id: None,
+            retry_after_ms: None,
+        }
+    }
 }
 
 /// Chat type supported by channels
@@ -114,6 +133,75 @@ pub struct OutboundContext {
     pub account_id: Option<String>,
 }
 
+/// Poll definition
+#[derive(Debug, Clone, PartialEq)]
+pub struct PollInput {
+    pub question: String,
+    pub options: Vec<String>,
+    pub allow_multiple: bool,
+}
+
+impl PollInput {
+    /// Maximum question length (characters).
+    pub const MAX_QUESTION_CHARS: usize = 300;
+    /// Maximum option length (characters).
+    pub const MAX_OPTION_CHARS: usize = 100;
+    /// Allowed number of options.
+    pub const OPTION_COUNT: std::ops::RangeInclusive<usize> = 2..=12;
+
+    /// Validate the poll against the limits declared in `wit/plugin.wit`.
+    pub fn validate(&self) -> Result<(), String> {
+        let question = self.question.trim();
+        if question.is_empty() {
+            return Err("poll question must not be empty".to_string());
+        }
+        if question.chars().count() > Self::MAX_QUESTION_CHARS {
+            return Err(format!(
+                "poll question exceeds {} characters",
+                Self::MAX_QUESTION_CHARS
+            ));
+        }
+        if !Self::OPTION_COUNT.contains(&self.options.len()) {
+            return Err(format!(
+                "poll needs between {} and {} options",
+                Self::OPTION_COUNT.start(),
+                Self::OPTION_COUNT.end()
+            ));
+        }
+        for option in &self.options {
+            if option.trim().is_empty() {
+                return Err("poll options must not be empty".to_string());
+            }
+            if option.chars().count() > Self::MAX_OPTION_CHARS {
+                return Err(format!(
+                    "poll option exceeds {} characters",
+                    Self::MAX_OPTION_CHARS
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Poll context for sending
+#[derive(Debug, Clone)]
+pub struct PollContext {
+    pub to: String,
+    pub poll: PollInput,
+    pub account_id: Option<String>,
+}
+
+/// Reference to a previously delivered message (edit, delete, react)
+///
+/// `to` is the conversation the message lives in; the WIT exports only
+/// receive `message_id`, built-in channels need both.
+#[derive(Debug, Clone)]
+pub struct MessageTarget {
+    pub to: String,
+    pub message_id: String,
+    pub account_id: Option<String>,
+}
+
 /// Tool definition
 #[derive(Debug, Clone)]
 pub struct ToolDefinition {
@@ -172,6 +260,178 @@ pub struct HookResult {
     pub modified_payload: Option<String>,
 }
 
+/// Provider metadata
+#[derive(Debug, Clone)]
+pub struct ProviderInfo {
+    pub id: String,
+    pub label: String,
+    pub model_family: String,
+    pub docs_path: String,
+    pub supports_streaming: bool,
+    pub supports_tools: bool,
+    pub supports_vision: bool,
+}
+
+/// Model cost (USD per million tokens)
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ModelCost {
+    pub input: f64,
+    pub output: f64,
+    pub cache_read: f64,
+    pub cache_write: f64,
+}
+
+/// Input modality accepted by a model
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputModality {
+    Text,
+    Image,
+}
+
+/// Model definition advertised by a provider plugin
+#[derive(Debug, Clone)]
+pub struct ModelDefinition {
+    pub id: String,
+    pub name: String,
+    /// API type (`openai-completions`, `anthropic-messages`, ...)
+    pub api: Option<String>,
+    pub reasoning: bool,
+    pub input: Vec<InputModality>,
+    pub cost: ModelCost,
+    pub context_window: u32,
+    pub max_tokens: u32,
+    /// JSON object of custom headers
+    pub headers: Option<String>,
+    /// `compat.max-tokens-field`
+    pub max_tokens_field: Option<String>,
+}
+
+impl ModelDefinition {
+    /// Maximum model ID length.
+    pub const MAX_ID_LEN: usize = 128;
+    /// Maximum display name length.
+    pub const MAX_NAME_LEN: usize = 256;
+
+    /// Validate the model against the host rules in `wit/plugin.wit`.
+    ///
+    /// An empty `input` list is defaulted to `[text]` rather than rejected.
+    pub fn validate(&mut self) -> Result<(), PluginError> {
+        let invalid = |message: String| PluginError {
+            code: "INVALID_MODEL".to_string(),
+            message,
+            retryable: false,
+        };
+
+        if self.id.is_empty() || self.id.len() > Self::MAX_ID_LEN {
+            return Err(invalid(format!(
+                "model id must be 1-{} characters",
+                Self::MAX_ID_LEN
+            )));
+        }
+        if !self
+            .id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '.' | '/' | ':' | '_'))
+        {
+            return Err(invalid(format!(
+                "model id '{}' contains invalid characters",
+                self.id
+            )));
+        }
+        if self.name.is_empty() || self.name.chars().count() > Self::MAX_NAME_LEN {
+            return Err(invalid(format!(
+                "model '{}': name must be 1-{} characters",
+                self.id,
+                Self::MAX_NAME_LEN
+            )));
+        }
+        let cost = self.cost;
+        if [cost.input, cost.output, cost.cache_read, cost.cache_write]
+            .iter()
+            .any(|v| !v.is_finite() || *v < 0.0)
+        {
+            return Err(invalid(format!(
+                "model '{}': cost values must be >= 0",
+                self.id
+            )));
+        }
+        if self.context_window == 0 {
+            return Err(invalid(format!(
+                "model '{}': context window must be > 0",
+                self.id
+            )));
+        }
+        if self.max_tokens == 0 || self.max_tokens > self.context_window {
+            return Err(invalid(format!(
+                "model '{}': max tokens must be > 0 and <= context window",
+                self.id
+            )));
+        }
+        if let Some(field) = &self.max_tokens_field {
+            if field != "max_completion_tokens" && field != "max_tokens" {
+                return Err(invalid(format!(
+                    "model '{}': unsupported max-tokens-field '{}'",
+                    self.id, field
+                )));
+            }
+        }
+        if self.input.is_empty() {
+            self.input.push(InputModality::Text);
+        }
+        Ok(())
+    }
+}
+
+/// Chat message sent to a provider plugin
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProviderChatMessage {
+    /// "system", "user", "assistant" or "tool"
+    pub role: String,
+    pub content: String,
+    pub name: Option<String>,
+    pub tool_call_id: Option<String>,
+}
+
+/// Completion request sent to a provider plugin
+#[derive(Debug, Clone)]
+pub struct ProviderCompletionRequest {
+    pub model: String,
+    pub messages: Vec<ProviderChatMessage>,
+    pub max_tokens: Option<u32>,
+    pub temperature: Option<f32>,
+    pub stream: bool,
+    /// JSON array of tool definitions
+    pub tools: Option<String>,
+}
+
+/// Token usage reported by a provider plugin
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ProviderUsageInfo {
+    pub prompt_tokens: u32,
+    pub completion_tokens: u32,
+    pub total_tokens: u32,
+}
+
+/// Non-streaming completion response from a provider plugin
+#[derive(Debug, Clone)]
+pub struct ProviderCompletionResponse {
+    pub content: Option<String>,
+    /// JSON array of tool calls
+    pub tool_calls: Option<String>,
+    /// "stop", "tool_calls", "length" or "error"
+    pub finish_reason: String,
+    pub usage: Option<ProviderUsageInfo>,
+}
+
+/// Streaming chunk from a provider plugin
+#[derive(Debug, Clone, Default)]
+pub struct ProviderStreamChunk {
+    pub delta_content: Option<String>,
+    /// JSON array of tool calls
+    pub delta_tool_calls: Option<String>,
+    pub finish_reason: Option<String>,
+}
+
 /// Plugin instance trait for channel plugins
 pub trait ChannelPluginInstance: Send + Sync {
     /// Get channel info
@@ -185,6 +445,89 @@ pub trait ChannelPluginInstance: Send + Sync {
 
     /// Send a media message
     fn send_media(&self, ctx: OutboundContext) -> Result<DeliveryResult, BindingError>;
+
+    /// Send a poll
+    fn send_poll(&self, _ctx: PollContext) -> Result<DeliveryResult, BindingError> {
+        Ok(DeliveryResult::unsupported("poll"))
+    }
+
+    /// Edit a previously delivered message
+    fn edit_message(
+        &self,
+        _target: MessageTarget,
+        _new_text: String,
+    ) -> Result<DeliveryResult, BindingError> {
+        Ok(DeliveryResult::unsupported("edit"))
+    }
+
+    /// Delete a previously delivered message
+    fn delete_message(&self, _target: MessageTarget) -> Result<DeliveryResult, BindingError> {
+        Ok(DeliveryResult::unsupported("delete"))
+    }
+
+    /// React to a message
+    fn react(
+        &self,
+        _target: MessageTarget,
+        _emoji: String,
+    ) -> Result<DeliveryResult, BindingError> {
+        Ok(DeliveryResult::unsupported("react"))
+    }
+
+    /// Pin a message, optionally without notifying members
+    fn pin_message(
+        &self,
+        _target: MessageTarget,
+        _silent: bool,
+    ) -> Result<DeliveryResult, BindingError> {
+        Ok(DeliveryResult::unsupported("pin"))
+    }
+
+    /// Replace the inline keyboard attached to a message
+    fn set_reply_markup(
+        &self,
+        _target: MessageTarget,
+        _buttons: serde_json::Value,
+    ) -> Result<DeliveryResult, BindingError> {
+        Ok(DeliveryResult::unsupported("reply_markup"))
+    }
+
+    /// Start a thread from a message
+    fn create_thread(
+        &self,
+        _target: MessageTarget,
+        _name: String,
+        _auto_archive_minutes: Option<u32>,
+    ) -> Result<DeliveryResult, BindingError> {
+        Ok(DeliveryResult::unsupported("create_thread"))
+    }
+
+    /// Send a rich embed; channels without embeds send `ctx.text` instead
+    fn send_embed(
+        &self,
+        ctx: OutboundContext,
+        _embed: serde_json::Value,
+    ) -> Result<DeliveryResult, BindingError> {
+        self.send_text(ctx)
+    }
+
+    /// Send a Block Kit message; channels without blocks send `ctx.text` instead
+    fn send_blocks(
+        &self,
+        ctx: OutboundContext,
+        _blocks: serde_json::Value,
+    ) -> Result<DeliveryResult, BindingError> {
+        self.send_text(ctx)
+    }
+
+    /// Send a message only `user` can see
+    fn send_ephemeral(
+        &self,
+        _ctx: OutboundContext,
+        _user: String,
+    ) -> Result<DeliveryResult, BindingError> {
+        Ok(DeliveryResult::unsupported("ephemeral"))
+    }
 }
 
 /// Plugin instance trait for tool plugins
@@ -231,6 +574,33 @@ pub trait HookPluginInstance: Send + Sync {
     fn handle(&self, event: HookEvent) -> Result<HookResult, BindingError>;
 }
 
+/// Plugin instance trait for LLM provider plugins
+pub trait ProviderPluginInstance: Send + Sync {
+    /// Get provider info
+    fn get_info(&self) -> Result<ProviderInfo, BindingError>;
+
+    /// List available models
+    fn list_models(&self) -> Result<Vec<ModelDefinition>, BindingError>;
+
+    /// Check whether the provider can serve requests
+    fn is_ready(&self) -> Result<bool, BindingError>;
+
+    /// Create a non-streaming completion
+    fn complete(
+        &self,
+        req: ProviderCompletionRequest,
+    ) -> Result<ProviderCompletionResponse, BindingError>;
+
+    /// Start a streaming completion, returning the stream ID
+    fn complete_stream(&self, req: ProviderCompletionRequest) -> Result<String, BindingError>;
+
+    /// Get the next chunk of a stream (`None` when the stream ends)
+    fn stream_next(&self, stream_id: &str) -> Result<Option<ProviderStreamChunk>, BindingError>;
+
+    /// Cancel a stream
+    fn stream_cancel(&self, stream_id: &str) -> Result<(), BindingError>;
+}
+
 /// Host implementation for WIT bindings
 ///
 /// This struct implements the host interface that plugins call into.
@@ -347,6 +717,13 @@ impl<B: CredentialBackend + 'static> WitHost<B> {
     }
 }
 
+/// Provider plugin entry: ID, instance and its validated models
+type RegisteredProvider = (
+    String,
+    Arc<dyn ProviderPluginInstance>,
+    Vec<ModelDefinition>,
+);
+
 /// Plugin registry tracks all loaded plugin instances
 pub struct PluginRegistry {
     /// Channel plugin instances
@@ -359,6 +736,8 @@ pub struct PluginRegistry {
     service_plugins: Mutex<Vec<(String, Arc<dyn ServicePluginInstance>)>>,
     /// Hook plugin instances
     hook_plugins: Mutex<Vec<(String, Arc<dyn HookPluginInstance>)>>,
+    /// Provider plugin instances with their validated models
+    provider_plugins: Mutex<Vec<RegisteredProvider>>,
 }
 
 impl Default for PluginRegistry {
@@ -375,6 +754,7 @@ impl PluginRegistry {
             webhook_plugins: Mutex::new(Vec::new()),
             service_plugins: Mutex::new(Vec::new()),
             hook_plugins: Mutex::new(Vec::new()),
+            provider_plugins: Mutex::new(Vec::new()),
         }
     }
 
@@ -408,6 +788,17 @@ impl PluginRegistry {
         plugins.push((id, instance));
     }
 
+    /// Register a provider plugin and the models it advertises
+    pub fn register_provider(
+        &self,
+        id: String,
+        instance: Arc<dyn ProviderPluginInstance>,
+        models: Vec<ModelDefinition>,
+    ) {
+        let mut plugins = self.provider_plugins.lock();
+        plugins.push((id, instance, models));
+    }
+
     /// Get all channel plugins
     pub fn get_channels(&self) -> Vec<(String, Arc<dyn ChannelPluginInstance>)> {
         self.channel_plugins.lock().clone()
@@ -460,6 +851,24 @@ impl PluginRegistry {
         self.hook_plugins.lock().clone()
     }
 
+    /// Get all provider plugins
+    pub fn get_providers(&self) -> Vec<(String, Arc<dyn ProviderPluginInstance>)> {
+        let plugins = self.provider_plugins.lock();
+        plugins
+            .iter()
+            .map(|(id, p, _)| (id.clone(), p.clone()))
+            .collect()
+    }
+
+    /// Get the models advertised by each provider plugin
+    pub fn get_provider_models(&self) -> Vec<(String, Vec<ModelDefinition>)> {
+        let plugins = self.provider_plugins.lock();
+        plugins
+            .iter()
+            .map(|(id, _, models)| (id.clone(), models.clone()))
+            .collect()
+    }
+
     /// Unregister a plugin by ID
     pub fn unregister(&self, id: &str) {
         {
@@ -482,6 +891,10 @@ impl PluginRegistry {
             let mut plugins = self.hook_plugins.lock();
             plugins.retain(|(pid, _)| pid != id);
         }
+        {
+            let mut plugins = self.provider_plugins.lock();
+            plugins.retain(|(pid, _, _)| pid != id);
+        }
     }
 
     /// Get count of all registered plugins
@@ -491,6 +904,7 @@ impl PluginRegistry {
             + self.webhook_plugins.lock().len()
             + self.service_plugins.lock().len()
             + self.hook_plugins.lock().len()
+            + self.provider_plugins.lock().len()
     }
 }
 
@@ -518,6 +932,7 @@ mod tests {
             conversation_id: None,
             to_jid: None,
             poll_id: None,
+            retry_after_ms: None,
         };
         assert!(result.ok);
         assert_eq!(result.message_id, Some("msg-123".to_string()));
@@ -533,6 +948,7 @@ mod tests {
             conversation_id: Some("conv-789".to_string()),
             to_jid: Some("user@example.com".to_string()),
             poll_id: Some("poll-001".to_string()),
+            retry_after_ms: None,
         };
         assert!(result.ok);
         assert_eq!(result.conversation_id, Some("conv-789".to_string()));
@@ -580,4 +996,44 @@ mod tests {
         assert!(result.handled);
         assert!(!result.cancel);
     }
+
+    fn model(id: &str) -> ModelDefinition {
+        ModelDefinition {
+            id: id.to_string(),
+            name: "Test Model".to_string(),
+            api: None,
+            reasoning: false,
+            input: Vec::new(),
+            cost: ModelCost::default(),
+            context_window: 8192,
+            max_tokens: 4096,
+            headers: None,
+            max_tokens_field: None,
+        }
+    }
+
+    #[test]
+    fn test_model_definition_validate_defaults_input() {
+        let mut m = model("openrouter/anthropic/claude-3");
+        m.validate().unwrap();
+        assert_eq!(m.input, vec![InputModality::Text]);
+    }
+
+    #[test]
+    fn test_model_definition_validate_rejects_invalid() {
+        let mut m = model("bad id!");
+        assert_eq!(m.validate().unwrap_err().code, "INVALID_MODEL");
+
+        let mut m = model("ok");
+        m.max_tokens = 10_000;
+        assert!(m.validate().is_err());
+
+        let mut m = model("ok");
+        m.cost.output = -1.0;
+        assert!(m.validate().is_err());
+
+        let mut m = model("ok");
+        m.max_tokens_field = Some("maxTokens".to_string());
+        assert!(m.validate().is_err());
+    }
 }
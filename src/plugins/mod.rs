@@ -54,9 +54,12 @@ mod tests;
 // Re-export commonly used types
 pub use bindings::{
     BindingError, ChannelCapabilities, ChannelInfo, ChannelPluginInstance, ChatType,
-    DeliveryResult, HookEvent, HookPluginInstance, HookResult, OutboundContext, PluginError,
-    PluginRegistry, ServicePluginInstance, ToolContext, ToolDefinition, ToolPluginInstance,
-    ToolResult, WebhookPluginInstance, WebhookRequest, WebhookResponse,
+    DeliveryResult, HookEvent, HookPluginInstance, HookResult, InputModality, MessageTarget,
+    ModelCost, ModelDefinition, OutboundContext, PluginError, PluginRegistry, PollContext,
+    PollInput, ProviderChatMessage, ProviderCompletionRequest, ProviderCompletionResponse,
+    ProviderInfo, ProviderPluginInstance, ProviderStreamChunk, ProviderUsageInfo,
+    ServicePluginInstance, ToolContext, ToolDefinition, ToolPluginInstance, ToolResult,
+    WebhookPluginInstance, WebhookRequest, WebhookResponse,
 };
 pub use capabilities::{
     CapabilityError, ConfigEnforcer, CredentialEnforcer, RateLimiterRegistry, SsrfProtection,
@@ -77,8 +80,8 @@ pub use permissions::{
     PermissionOverride,
 };
 pub use runtime::{
-    HostState, PluginInstanceHandle, PluginRuntime, RuntimeError, DEFAULT_EXECUTION_TIMEOUT,
-    DEFAULT_FUEL_BUDGET, MAX_PLUGIN_MEMORY_BYTES,
+    HostState, PluginInstanceHandle, PluginRuntime, RuntimeError, TransformModule,
+    TransformRuntime, DEFAULT_EXECUTION_TIMEOUT, DEFAULT_FUEL_BUDGET, MAX_PLUGIN_MEMORY_BYTES,
 };
 pub use tools::{
     create_registry as create_tools_registry, BuiltinTool, ToolInvokeContext, ToolInvokeError,
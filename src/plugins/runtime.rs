@@ -9,6 +9,7 @@
 //! - `PluginRuntime`: Main runtime that manages plugin instances
 //! - `PluginInstance`: A single instantiated plugin with its store and exports
 //! - `HostState`: Per-instance state containing host context and async support
+//! - `TransformRuntime`: Import-free sandbox for hook mapping transforms
 //!
 //! # Security
 //!
@@ -21,7 +22,7 @@
 
 use std::collections::HashMap;
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::Arc;
+use std::sync::{Arc, OnceLock};
 use std::time::Duration;
 
 use parking_lot::RwLock;
@@ -33,9 +34,12 @@ use crate::credentials::{CredentialBackend, CredentialStore};
 
 use super::bindings::{
     BindingError, ChannelCapabilities, ChannelInfo, ChannelPluginInstance, ChatType,
-    DeliveryResult, HookEvent, HookPluginInstance, HookResult, OutboundContext, PluginRegistry,
-    ServicePluginInstance, ToolContext, ToolDefinition, ToolPluginInstance, ToolResult,
-    WebhookPluginInstance, WebhookRequest, WebhookResponse, WitHost,
+    DeliveryResult, HookEvent, HookPluginInstance, HookResult, InputModality, MessageTarget,
+    ModelCost, ModelDefinition, OutboundContext, PluginRegistry, PollContext,
+    ProviderCompletionRequest, ProviderCompletionResponse, ProviderInfo, ProviderPluginInstance,
+    ProviderStreamChunk, ProviderUsageInfo, ServicePluginInstance, ToolContext, ToolDefinition,
+    ToolPluginInstance, ToolResult, WebhookPluginInstance, WebhookRequest, WebhookResponse,
+    WitHost,
 };
 use super::capabilities::{RateLimiterRegistry, SsrfConfig};
 use super::host::{HostError, HttpRequest, PluginHostContext};
@@ -301,6 +305,30 @@ struct WitOutboundContext {
     account_id: Option<String>,
 }
 
+/// WIT `poll-input` record.
+#[derive(Clone, Debug, ComponentType, Lift, Lower)]
+#[component(record)]
+struct WitPollInput {
+    #[component(name = "question")]
+    question: String,
+    #[component(name = "options")]
+    options: Vec<String>,
+    #[component(name = "allow-multiple")]
+    allow_multiple: bool,
+}
+
+/// WIT `poll-context` record passed to `channel-adapter.send-poll`.
+#[derive(Clone, Debug, ComponentType, Lift, Lower)]
+#[component(record)]
+struct WitPollContext {
+    #[component(name = "to")]
+    to: String,
+    #[component(name = "poll")]
+    poll: WitPollInput,
+    #[component(name = "account-id")]
+    account_id: Option<String>,
+}
+
 /// WIT `plugin-error` record returned in `result<T, plugin-error>` exports.
 #[derive(Clone, Debug, ComponentType, Lift, Lower)]
 #[component(record)]
@@ -415,6 +443,182 @@ struct WitHookResult {
     modified_payload: Option<String>,
 }
 
+/// WIT `provider-info` record returned by `provider.get-info` export.
+#[derive(Clone, Debug, ComponentType, Lift, Lower)]
+#[component(record)]
+struct WitProviderInfo {
+    #[component(name = "id")]
+    id: String,
+    #[component(name = "label")]
+    label: String,
+    #[component(name = "model-family")]
+    model_family: String,
+    #[component(name = "docs-path")]
+    docs_path: String,
+    #[component(name = "supports-streaming")]
+    supports_streaming: bool,
+    #[component(name = "supports-tools")]
+    supports_tools: bool,
+    #[component(name = "supports-vision")]
+    supports_vision: bool,
+}
+
+/// WIT `model-api` enum used in model definitions.
+#[derive(Clone, Copy, Debug, ComponentType, Lift, Lower)]
+#[component(enum)]
+#[repr(u8)]
+#[allow(dead_code)] // variants are only constructed by `Lift`
+enum WitModelApi {
+    #[component(name = "openai-completions")]
+    OpenaiCompletions,
+    #[component(name = "openai-responses")]
+    OpenaiResponses,
+    #[component(name = "anthropic-messages")]
+    AnthropicMessages,
+    #[component(name = "google-generative-ai")]
+    GoogleGenerativeAi,
+    #[component(name = "github-copilot")]
+    GithubCopilot,
+    #[component(name = "bedrock-converse-stream")]
+    BedrockConverseStream,
+}
+
+/// WIT `model-cost` record (per million tokens).
+#[derive(Clone, Debug, ComponentType, Lift, Lower)]
+#[component(record)]
+struct WitModelCost {
+    #[component(name = "input")]
+    input: f64,
+    #[component(name = "output")]
+    output: f64,
+    #[component(name = "cache-read")]
+    cache_read: f64,
+    #[component(name = "cache-write")]
+    cache_write: f64,
+}
+
+/// WIT `input-modality` enum used in model definitions.
+#[derive(Clone, Copy, Debug, ComponentType, Lift, Lower)]
+#[component(enum)]
+#[repr(u8)]
+#[allow(dead_code)] // variants are only constructed by `Lift`
+enum WitInputModality {
+    #[component(name = "text")]
+    Text,
+    #[component(name = "image")]
+    Image,
+}
+
+/// WIT `model-compat` record used in model definitions.
+#[derive(Clone, Debug, ComponentType, Lift, Lower)]
+#[component(record)]
+struct WitModelCompat {
+    #[component(name = "supports-store")]
+    supports_store: Option<bool>,
+    #[component(name = "supports-developer-role")]
+    supports_developer_role: Option<bool>,
+    #[component(name = "supports-reasoning-effort")]
+    supports_reasoning_effort: Option<bool>,
+    #[component(name = "max-tokens-field")]
+    max_tokens_field: Option<String>,
+}
+
+/// WIT `model-definition` record returned by `provider.list-models` export.
+#[derive(Clone, Debug, ComponentType, Lift, Lower)]
+#[component(record)]
+struct WitModelDefinition {
+    #[component(name = "id")]
+    id: String,
+    #[component(name = "name")]
+    name: String,
+    #[component(name = "api")]
+    api: Option<WitModelApi>,
+    #[component(name = "reasoning")]
+    reasoning: bool,
+    #[component(name = "input")]
+    input: Vec<WitInputModality>,
+    #[component(name = "cost")]
+    cost: WitModelCost,
+    #[component(name = "context-window")]
+    context_window: u32,
+    #[component(name = "max-tokens")]
+    max_tokens: u32,
+    #[component(name = "headers")]
+    headers: Option<String>,
+    #[component(name = "compat")]
+    compat: Option<WitModelCompat>,
+}
+
+/// WIT `chat-message` record passed in completion requests.
+#[derive(Clone, Debug, ComponentType, Lift, Lower)]
+#[component(record)]
+struct WitChatMessage {
+    #[component(name = "role")]
+    role: String,
+    #[component(name = "content")]
+    content: String,
+    #[component(name = "name")]
+    name: Option<String>,
+    #[component(name = "tool-call-id")]
+    tool_call_id: Option<String>,
+}
+
+/// WIT `completion-request` record passed to `provider.complete` exports.
+#[derive(Clone, Debug, ComponentType, Lift, Lower)]
+#[component(record)]
+struct WitCompletionRequest {
+    #[component(name = "model")]
+    model: String,
+    #[component(name = "messages")]
+    messages: Vec<WitChatMessage>,
+    #[component(name = "max-tokens")]
+    max_tokens: Option<u32>,
+    #[component(name = "temperature")]
+    temperature: Option<f32>,
+    #[component(name = "stream")]
+    stream: bool,
+    #[component(name = "tools")]
+    tools: Option<String>,
+}
+
+/// WIT `usage-info` record returned with completion responses.
+#[derive(Clone, Debug, ComponentType, Lift, Lower)]
+#[component(record)]
+struct WitUsageInfo {
+    #[component(name = "prompt-tokens")]
+    prompt_tokens: u32,
+    #[component(name = "completion-tokens")]
+    completion_tokens: u32,
+    #[component(name = "total-tokens")]
+    total_tokens: u32,
+}
+
+/// WIT `completion-response` record returned by `provider.complete` export.
+#[derive(Clone, Debug, ComponentType, Lift, Lower)]
+#[component(record)]
+struct WitCompletionResponse {
+    #[component(name = "content")]
+    content: Option<String>,
+    #[component(name = "tool-calls")]
+    tool_calls: Option<String>,
+    #[component(name = "finish-reason")]
+    finish_reason: String,
+    #[component(name = "usage")]
+    usage: Option<WitUsageInfo>,
+}
+
+/// WIT `stream-chunk` record returned by `provider.stream-next` export.
+#[derive(Clone, Debug, ComponentType, Lift, Lower)]
+#[component(record)]
+struct WitStreamChunk {
+    #[component(name = "delta-content")]
+    delta_content: Option<String>,
+    #[component(name = "delta-tool-calls")]
+    delta_tool_calls: Option<String>,
+    #[component(name = "finish-reason")]
+    finish_reason: Option<String>,
+}
+
 // ============== Type Conversions ==============
 
 impl From<WitChannelInfo> for ChannelInfo {
@@ -476,6 +680,20 @@ impl From<&OutboundContext> for WitOutboundContext {
     }
 }
 
+impl From<&PollContext> for WitPollContext {
+    fn from(ctx: &PollContext) -> Self {
+        Self {
+            to: ctx.to.clone(),
+            poll: WitPollInput {
+                question: ctx.poll.question.clone(),
+                options: ctx.poll.options.clone(),
+                allow_multiple: ctx.poll.allow_multiple,
+            },
+            account_id: ctx.account_id.clone(),
+        }
+    }
+}
+
 impl From<WitDeliveryResult> for DeliveryResult {
     fn from(wit: WitDeliveryResult) -> Self {
         Self {
@@ -488,6 +706,7 @@ impl From<WitDeliveryResult> for DeliveryResult {
             conversation_id: None,
             to_jid: None,
             poll_id: None,
+            retry_after_ms: None,
         }
     }
 }
@@ -564,6 +783,109 @@ impl From<WitHookResult> for HookResult {
     }
 }
 
+impl From<WitProviderInfo> for ProviderInfo {
+    fn from(wit: WitProviderInfo) -> Self {
+        Self {
+            id: wit.id,
+            label: wit.label,
+            model_family: wit.model_family,
+            docs_path: wit.docs_path,
+            supports_streaming: wit.supports_streaming,
+            supports_tools: wit.supports_tools,
+            supports_vision: wit.supports_vision,
+        }
+    }
+}
+
+impl WitModelApi {
+    fn as_str(self) -> &'static str {
+        match self {
+            WitModelApi::OpenaiCompletions => "openai-completions",
+            WitModelApi::OpenaiResponses => "openai-responses",
+            WitModelApi::AnthropicMessages => "anthropic-messages",
+            WitModelApi::GoogleGenerativeAi => "google-generative-ai",
+            WitModelApi::GithubCopilot => "github-copilot",
+            WitModelApi::BedrockConverseStream => "bedrock-converse-stream",
+        }
+    }
+}
+
+impl From<WitModelDefinition> for ModelDefinition {
+    fn from(wit: WitModelDefinition) -> Self {
+        Self {
+            id: wit.id,
+            name: wit.name,
+            api: wit.api.map(|api| api.as_str().to_string()),
+            reasoning: wit.reasoning,
+            input: wit
+                .input
+                .into_iter()
+                .map(|m| match m {
+                    WitInputModality::Text => InputModality::Text,
+                    WitInputModality::Image => InputModality::Image,
+                })
+                .collect(),
+            cost: ModelCost {
+                input: wit.cost.input,
+                output: wit.cost.output,
+                cache_read: wit.cost.cache_read,
+                cache_write: wit.cost.cache_write,
+            },
+            context_window: wit.context_window,
+            max_tokens: wit.max_tokens,
+            headers: wit.headers,
+            max_tokens_field: wit.compat.and_then(|c| c.max_tokens_field),
+        }
+    }
+}
+
+impl From<&ProviderCompletionRequest> for WitCompletionRequest {
+    fn from(req: &ProviderCompletionRequest) -> Self {
+        Self {
+            model: req.model.clone(),
+            messages: req
+                .messages
+                .iter()
+                .map(|m| WitChatMessage {
+                    role: m.role.clone(),
+                    content: m.content.clone(),
+                    name: m.name.clone(),
+                    tool_call_id: m.tool_call_id.clone(),
+                })
+                .collect(),
+            max_tokens: req.max_tokens,
+            temperature: req.temperature,
+            stream: req.stream,
+            tools: req.tools.clone(),
+        }
+    }
+}
+
+impl From<WitCompletionResponse> for ProviderCompletionResponse {
+    fn from(wit: WitCompletionResponse) -> Self {
+        Self {
+            content: wit.content,
+            tool_calls: wit.tool_calls,
+            finish_reason: wit.finish_reason,
+            usage: wit.usage.map(|u| ProviderUsageInfo {
+                prompt_tokens: u.prompt_tokens,
+                completion_tokens: u.completion_tokens,
+                total_tokens: u.total_tokens,
+            }),
+        }
+    }
+}
+
+impl From<WitStreamChunk> for ProviderStreamChunk {
+    fn from(wit: WitStreamChunk) -> Self {
+        Self {
+            delta_content: wit.delta_content,
+            delta_tool_calls: wit.delta_tool_calls,
+            finish_reason: wit.finish_reason,
+        }
+    }
+}
+
 /// Plugin runtime errors
 #[derive(Error, Debug)]
 pub enum RuntimeError {
@@ -942,6 +1264,13 @@ impl<B: CredentialBackend + Send + Sync + 'static> PluginRuntime<B> {
         })
     }
 
+    /// Register plugin capabilities into a shared registry instead of a
+    /// private one
+    pub fn with_registry(mut self, registry: Arc<PluginRegistry>) -> Self {
+        self.registry = registry;
+        self
+    }
+
     /// Get the plugin registry
     pub fn registry(&self) -> Arc<PluginRegistry> {
         self.registry.clone()
@@ -1088,15 +1417,15 @@ impl<B: CredentialBackend + Send + Sync + 'static> PluginRuntime<B> {
             component,
         });
 
+        // Register capabilities based on plugin kind
+        self.register_capabilities(plugin_id, &loaded, handle.clone())?;
+
         // Store the instance
         {
             let mut instances = self.instances.write();
-            instances.insert(plugin_id.to_string(), handle.clone());
+            instances.insert(plugin_id.to_string(), handle);
         }
 
-        // Register capabilities based on plugin kind
-        self.register_capabilities(plugin_id, &loaded, handle)?;
-
         Ok(())
     }
 
@@ -1361,8 +1690,10 @@ impl<B: CredentialBackend + Send + Sync + 'static> PluginRuntime<B> {
                     .register_hook(plugin_id.to_string(), Arc::new(adapter));
             }
             PluginKind::Provider => {
-                // Provider plugins are handled separately
-                tracing::debug!(plugin_id = %plugin_id, "Provider plugin registered (inference only)");
+                let adapter = ProviderAdapter::new(plugin_id.to_string(), handle);
+                let models = validated_provider_models(plugin_id, &adapter)?;
+                self.registry
+                    .register_provider(plugin_id.to_string(), Arc::new(adapter), models);
             }
         }
 
@@ -1487,13 +1818,60 @@ impl<B: CredentialBackend + Send + Sync + 'static> ChannelPluginInstance for Cha
         let (result,): (Result<WitDeliveryResult, WitPluginError>,) = self
             .handle
             .call_export_one_arg("channel-adapter", "send-media", (wit_ctx,))?;
-        match result {
-            Ok(dr) => Ok(DeliveryResult::from(dr)),
-            Err(pe) => Err(BindingError::CallError(format!(
-                "plugin error [{}]: {}",
-                pe.code, pe.message
-            ))),
-        }
+        delivery_from_wit(result)
+    }
+
+    fn send_poll(&self, ctx: PollContext) -> Result<DeliveryResult, BindingError> {
+        tracing::debug!(plugin_id = %self.plugin_id, to = %ctx.to, "Calling WASM export channel-adapter.send-poll");
+        let wit_ctx = WitPollContext::from(&ctx);
+        let (result,): (Result<WitDeliveryResult, WitPluginError>,) = self
+            .handle
+            .call_export_one_arg("channel-adapter", "send-poll", (wit_ctx,))?;
+        delivery_from_wit(result)
+    }
+
+    fn edit_message(
+        &self,
+        target: MessageTarget,
+        new_text: String,
+    ) -> Result<DeliveryResult, BindingError> {
+        tracing::debug!(plugin_id = %self.plugin_id, message_id = %target.message_id, "Calling WASM export channel-adapter.edit-message");
+        let (result,): (Result<WitDeliveryResult, WitPluginError>,) =
+            self.handle.call_export_one_arg(
+                "channel-adapter",
+                "edit-message",
+                (target.message_id, new_text),
+            )?;
+        delivery_from_wit(result)
+    }
+
+    fn delete_message(&self, target: MessageTarget) -> Result<DeliveryResult, BindingError> {
+        tracing::debug!(plugin_id = %self.plugin_id, message_id = %target.message_id, "Calling WASM export channel-adapter.delete-message");
+        let (result,): (Result<WitDeliveryResult, WitPluginError>,) = self
+            .handle
+            .call_export_one_arg("channel-adapter", "delete-message", (target.message_id,))?;
+        delivery_from_wit(result)
+    }
+
+    fn react(&self, target: MessageTarget, emoji: String) -> Result<DeliveryResult, BindingError> {
+        tracing::debug!(plugin_id = %self.plugin_id, message_id = %target.message_id, "Calling WASM export channel-adapter.react");
+        let (result,): (Result<WitDeliveryResult, WitPluginError>,) = self
+            .handle
+            .call_export_one_arg("channel-adapter", "react", (target.message_id, emoji))?;
+        delivery_from_wit(result)
+    }
+}
+
+/// Map a `result<delivery-result, plugin-error>` returned by a channel export.
+fn delivery_from_wit(
+    result: Result<WitDeliveryResult, WitPluginError>,
+) -> Result<DeliveryResult, BindingError> {
+    match result {
+        Ok(dr) => Ok(DeliveryResult::from(dr)),
+        Err(pe) => Err(BindingError::CallError(format!(
+            "plugin error [{}]: {}",
+            pe.code, pe.message
+        ))),
     }
 }
 
@@ -1685,6 +2063,280 @@ impl<B: CredentialBackend + Send + Sync + 'static> HookPluginInstance for HookAd
     }
 }
 
+/// Adapter that implements ProviderPluginInstance for WASM plugins
+struct ProviderAdapter<B: CredentialBackend + Send + Sync + 'static> {
+    plugin_id: String,
+    handle: Arc<PluginInstanceHandle<B>>,
+}
+
+impl<B: CredentialBackend + Send + Sync + 'static> ProviderAdapter<B> {
+    fn new(plugin_id: String, handle: Arc<PluginInstanceHandle<B>>) -> Self {
+        Self { plugin_id, handle }
+    }
+}
+
+// SAFETY: ProviderAdapter only holds an Arc<PluginInstanceHandle<B>> whose interior
+// wasmtime Store is guarded by an RwLock.  All access goes through the lock,
+// so sharing across threads is safe.
+unsafe impl<B: CredentialBackend + Send + Sync + 'static> Send for ProviderAdapter<B> {}
+unsafe impl<B: CredentialBackend + Send + Sync + 'static> Sync for ProviderAdapter<B> {}
+
+impl<B: CredentialBackend + Send + Sync + 'static> ProviderPluginInstance for ProviderAdapter<B> {
+    fn get_info(&self) -> Result<ProviderInfo, BindingError> {
+        tracing::debug!(plugin_id = %self.plugin_id, "Calling WASM export provider.get-info");
+        let (info,): (WitProviderInfo,) =
+            self.handle.call_export_no_args("provider", "get-info")?;
+        Ok(ProviderInfo::from(info))
+    }
+
+    fn list_models(&self) -> Result<Vec<ModelDefinition>, BindingError> {
+        tracing::debug!(plugin_id = %self.plugin_id, "Calling WASM export provider.list-models");
+        let (models,): (Vec<WitModelDefinition>,) =
+            self.handle.call_export_no_args("provider", "list-models")?;
+        Ok(models.into_iter().map(ModelDefinition::from).collect())
+    }
+
+    fn is_ready(&self) -> Result<bool, BindingError> {
+        tracing::debug!(plugin_id = %self.plugin_id, "Calling WASM export provider.is-ready");
+        let (ready,): (bool,) = self.handle.call_export_no_args("provider", "is-ready")?;
+        Ok(ready)
+    }
+
+    fn complete(
+        &self,
+        req: ProviderCompletionRequest,
+    ) -> Result<ProviderCompletionResponse, BindingError> {
+        tracing::debug!(plugin_id = %self.plugin_id, model = %req.model, "Calling WASM export provider.complete");
+        let wit_req = WitCompletionRequest::from(&req);
+        let (result,): (Result<WitCompletionResponse, WitPluginError>,) = self
+            .handle
+            .call_export_one_arg("provider", "complete", (wit_req,))?;
+        result
+            .map(ProviderCompletionResponse::from)
+            .map_err(plugin_call_error)
+    }
+
+    fn complete_stream(&self, req: ProviderCompletionRequest) -> Result<String, BindingError> {
+        tracing::debug!(plugin_id = %self.plugin_id, model = %req.model, "Calling WASM export provider.complete-stream");
+        let wit_req = WitCompletionRequest::from(&req);
+        let (result,): (Result<String, WitPluginError>,) =
+            self.handle
+                .call_export_one_arg("provider", "complete-stream", (wit_req,))?;
+        result.map_err(plugin_call_error)
+    }
+
+    fn stream_next(&self, stream_id: &str) -> Result<Option<ProviderStreamChunk>, BindingError> {
+        let (result,): (Result<Option<WitStreamChunk>, WitPluginError>,) = self
+            .handle
+            .call_export_one_arg("provider", "stream-next", (stream_id.to_string(),))?;
+        result
+            .map(|chunk| chunk.map(ProviderStreamChunk::from))
+            .map_err(plugin_call_error)
+    }
+
+    fn stream_cancel(&self, stream_id: &str) -> Result<(), BindingError> {
+        tracing::debug!(plugin_id = %self.plugin_id, stream_id = %stream_id, "Calling WASM export provider.stream-cancel");
+        let (result,): (Result<(), WitPluginError>,) = self.handle.call_export_one_arg(
+            "provider",
+            "stream-cancel",
+            (stream_id.to_string(),),
+        )?;
+        result.map_err(plugin_call_error)
+    }
+}
+
+/// Map a WIT `plugin-error` to a binding call error.
+fn plugin_call_error(pe: WitPluginError) -> BindingError {
+    BindingError::CallError(format!("plugin error [{}]: {}", pe.code, pe.message))
+}
+
+/// Fetch a provider plugin's models, dropping any that fail host validation.
+fn validated_provider_models(
+    plugin_id: &str,
+    provider: &dyn ProviderPluginInstance,
+) -> Result<Vec<ModelDefinition>, RuntimeError> {
+    let models = provider.list_models().map_err(|e| {
+        RuntimeError::InstantiationError(format!("provider.list-models failed: {}", e))
+    })?;
+    Ok(models
+        .into_iter()
+        .filter_map(|mut model| match model.validate() {
+            Ok(()) => Some(model),
+            Err(e) => {
+                tracing::warn!(plugin_id = %plugin_id, error = %e, "Rejected provider model");
+                None
+            }
+        })
+        .collect())
+}
+
+// ============== Hook Transforms ==============
+
+/// Maximum memory per hook transform instance (16MB)
+pub const MAX_TRANSFORM_MEMORY_BYTES: u64 = 16 * 1024 * 1024;
+
+/// Fuel budget per hook transform call (100 million instructions).
+pub const TRANSFORM_FUEL_BUDGET: u64 = 100_000_000;
+
+/// Execution timeout per hook transform call (5s)
+pub const TRANSFORM_EXECUTION_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Guard region after a transform's linear memory (64KB)
+const TRANSFORM_MEMORY_GUARD_BYTES: u64 = 64 * 1024;
+
+/// Sandbox for hook mapping transforms.
+///
+/// Transforms are WASM components exporting a single pure function,
+/// `func(request: string) -> result<string, string>` (the `hook-transform`
+/// world in `wit/plugin.wit`). They get no host imports, so no credentials,
+/// network or config, and each call runs in a fresh instance under
+/// [`TRANSFORM_FUEL_BUDGET`], [`MAX_TRANSFORM_MEMORY_BYTES`] and
+/// [`TRANSFORM_EXECUTION_TIMEOUT`].
+#[derive(Clone)]
+pub struct TransformRuntime {
+    engine: Engine,
+    epoch_deadline_ticks: u64,
+    _epoch_ticker: Arc<EpochTicker>,
+}
+
+/// A compiled, type-checked hook transform.
+#[derive(Clone)]
+pub struct TransformModule {
+    runtime: TransformRuntime,
+    component: Component,
+    export: String,
+}
+
+/// Store state for a transform call.
+struct TransformState {
+    limiter: PluginResourceLimiter,
+}
+
+/// Signature of a transform export.
+type TransformFunc = wasmtime::component::TypedFunc<(String,), (Result<String, String>,)>;
+
+impl TransformRuntime {
+    /// The process-wide transform runtime, created on first use.
+    ///
+    /// All transforms share one engine and epoch ticker, so reloading the
+    /// config does not start another ticker thread.
+    pub fn shared() -> Result<Self, RuntimeError> {
+        static SHARED: OnceLock<TransformRuntime> = OnceLock::new();
+        if let Some(runtime) = SHARED.get() {
+            return Ok(runtime.clone());
+        }
+        let runtime = Self::new()?;
+        Ok(SHARED.get_or_init(|| runtime).clone())
+    }
+
+    /// Create a transform runtime with its own engine and epoch ticker.
+    fn new() -> Result<Self, RuntimeError> {
+        let mut config = Config::new();
+        config.wasm_component_model(true);
+        config.consume_fuel(true);
+        config.epoch_interruption(true);
+        // Reserve only what a transform may use instead of the default
+        // multi-GiB virtual reservation per linear memory, which fails
+        // under address-space limits (RLIMIT_AS).
+        config.memory_reservation(MAX_TRANSFORM_MEMORY_BYTES);
+        config.memory_reservation_for_growth(0);
+        config.memory_guard_size(TRANSFORM_MEMORY_GUARD_BYTES);
+
+        let engine =
+            Engine::new(&config).map_err(|e| RuntimeError::WasmtimeError(e.to_string()))?;
+        let epoch_ticker = EpochTicker::start(engine.clone(), DEFAULT_EPOCH_TICK_INTERVAL);
+
+        Ok(Self {
+            engine,
+            epoch_deadline_ticks: compute_epoch_deadline_ticks(TRANSFORM_EXECUTION_TIMEOUT),
+            _epoch_ticker: Arc::new(epoch_ticker),
+        })
+    }
+
+    /// Compile a transform component and check that it instantiates without
+    /// imports and exports `export` with the transform signature.
+    pub fn load(&self, wasm: &[u8], export: &str) -> Result<TransformModule, RuntimeError> {
+        let component = Component::new(&self.engine, wasm).map_err(|e| {
+            RuntimeError::WasmtimeError(format!("Failed to create component: {}", e))
+        })?;
+        let module = TransformModule {
+            runtime: self.clone(),
+            component,
+            export: export.to_string(),
+        };
+        module.instantiate()?;
+        Ok(module)
+    }
+}
+
+impl TransformModule {
+    /// The export this transform calls.
+    pub fn export(&self) -> &str {
+        &self.export
+    }
+
+    /// Instantiate the component in a fresh store and look up the export.
+    fn instantiate(&self) -> Result<(Store<TransformState>, TransformFunc), RuntimeError> {
+        let runtime = &self.runtime;
+        let mut store = Store::new(
+            &runtime.engine,
+            TransformState {
+                limiter: PluginResourceLimiter {
+                    max_memory_bytes: MAX_TRANSFORM_MEMORY_BYTES as usize,
+                    max_table_elements: MAX_PLUGIN_TABLE_ELEMENTS,
+                },
+            },
+        );
+        // SECURITY: enforce per-call memory limits for transform code.
+        store.limiter(|state| &mut state.limiter);
+        store.set_epoch_deadline(runtime.epoch_deadline_ticks);
+        store
+            .set_fuel(TRANSFORM_FUEL_BUDGET)
+            .map_err(|e| RuntimeError::WasmtimeError(e.to_string()))?;
+
+        // No host functions: transforms only see the request they are given.
+        let linker: Linker<TransformState> = Linker::new(&runtime.engine);
+        let instance = linker
+            .instantiate(&mut store, &self.component)
+            .map_err(|e| transform_call_error(e, "instantiate"))?;
+        let func = instance
+            .get_typed_func::<(String,), (Result<String, String>,)>(&mut store, &self.export)
+            .map_err(|e| {
+                RuntimeError::InstantiationError(format!(
+                    "export '{}' is not func(string) -> result<string, string>: {}",
+                    self.export, e
+                ))
+            })?;
+        Ok((store, func))
+    }
+
+    /// Run the transform on `input`.
+    ///
+    /// Returns the guest's own `Ok`/`Err` result; traps and exhausted limits
+    /// are runtime errors.
+    pub fn call(&self, input: &str) -> Result<Result<String, String>, RuntimeError> {
+        let (mut store, func) = self.instantiate()?;
+        let (result,) = func
+            .call(&mut store, (input.to_string(),))
+            .map_err(|e| transform_call_error(e, &self.export))?;
+        func.post_return(&mut store)
+            .map_err(|e| transform_call_error(e, &self.export))?;
+        Ok(result)
+    }
+}
+
+/// Map a wasmtime error from a transform to a runtime error, naming the
+/// limit that was hit.
+fn transform_call_error(e: wasmtime::Error, func_name: &str) -> RuntimeError {
+    match e.downcast_ref::<wasmtime::Trap>() {
+        Some(wasmtime::Trap::OutOfFuel) => RuntimeError::FuelExhausted {
+            budget: TRANSFORM_FUEL_BUDGET,
+        },
+        Some(wasmtime::Trap::Interrupt) => RuntimeError::ExecutionTimeout,
+        _ => RuntimeError::CallError(format!("call to '{}' failed: {}", func_name, e)),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1866,6 +2518,56 @@ mod tests {
         assert_eq!(wit.account_id, Some("acc-456".to_string()));
     }
 
+    #[test]
+    fn test_wit_poll_context_conversion() {
+        let ctx = PollContext {
+            to: "chat-1".to_string(),
+            poll: crate::plugins::PollInput {
+                question: "Lunch?".to_string(),
+                options: vec!["Pizza".to_string(), "Sushi".to_string()],
+                allow_multiple: true,
+            },
+            account_id: None,
+        };
+        let wit = WitPollContext::from(&ctx);
+        assert_eq!(wit.to, "chat-1");
+        assert_eq!(wit.poll.question, "Lunch?");
+        assert_eq!(wit.poll.options, vec!["Pizza", "Sushi"]);
+        assert!(wit.poll.allow_multiple);
+        assert!(wit.account_id.is_none());
+    }
+
+    #[test]
+    fn test_wit_model_definition_conversion() {
+        let wit = WitModelDefinition {
+            id: "qwen-max".to_string(),
+            name: "Qwen Max".to_string(),
+            api: Some(WitModelApi::OpenaiCompletions),
+            reasoning: true,
+            input: vec![WitInputModality::Text, WitInputModality::Image],
+            cost: WitModelCost {
+                input: 1.6,
+                output: 6.4,
+                cache_read: 0.0,
+                cache_write: 0.0,
+            },
+            context_window: 32_768,
+            max_tokens: 8_192,
+            headers: None,
+            compat: Some(WitModelCompat {
+                supports_store: None,
+                supports_developer_role: None,
+                supports_reasoning_effort: None,
+                max_tokens_field: Some("max_tokens".to_string()),
+            }),
+        };
+        let model = ModelDefinition::from(wit);
+        assert_eq!(model.api.as_deref(), Some("openai-completions"));
+        assert_eq!(model.input, vec![InputModality::Text, InputModality::Image]);
+        assert_eq!(model.cost.output, 6.4);
+        assert_eq!(model.max_tokens_field.as_deref(), Some("max_tokens"));
+    }
+
     #[test]
     fn test_wit_delivery_result_conversion() {
         let wit = WitDeliveryResult {
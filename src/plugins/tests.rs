@@ -71,6 +71,7 @@ mod integration_tests {
                 conversation_id: None,
                 to_jid: None,
                 poll_id: None,
+                retry_after_ms: None,
             })
         }
 
@@ -83,6 +84,7 @@ mod integration_tests {
                 conversation_id: None,
                 to_jid: None,
                 poll_id: None,
+                retry_after_ms: None,
             })
         }
     }
@@ -3,18 +3,32 @@
 //! Provides a registry for tools that can be invoked via the /tools/invoke endpoint.
 //! Supports both built-in tools and plugin-provided tools.
 
+use futures_util::future::BoxFuture;
 use parking_lot::RwLock;
 use serde::Serialize;
 use serde_json::Value;
 use std::collections::{HashMap, HashSet};
+use std::future::Future;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
+use tokio_util::sync::CancellationToken;
 
-use super::bindings::{ToolContext, ToolDefinition, ToolPluginInstance};
+use super::bindings::{ToolContext, ToolDefinition, ToolPluginInstance, ToolResult};
 use super::{DispatchError, PluginRegistry, ToolDispatcher};
+use crate::cron::CronScheduler;
+use crate::memory::MemoryStore;
+use crate::messages::outbound::MessagePipeline;
+use crate::server::ws::WsServerState;
+use crate::sessions::SessionStore;
+use crate::usage::UsageTracker;
 
 /// Tool invocation context
-#[derive(Debug, Clone)]
+///
+/// Carries the identity of the calling conversation and handles to the
+/// server subsystems a tool may need. Handles are `None` when the tool is
+/// invoked outside a running server (e.g. in tests or from the standalone
+/// `/tools/invoke` endpoint without a WebSocket state).
+#[derive(Clone)]
 pub struct ToolInvokeContext {
     /// Agent ID (if specified)
     pub agent_id: Option<String>,
@@ -28,6 +42,22 @@ pub struct ToolInvokeContext {
     pub sandboxed: bool,
     /// Dry run mode (reserved for future use)
     pub dry_run: bool,
+    /// Recipient (chat/conversation ID) of the originating conversation, used
+    /// as the default target for outbound messages
+    pub recipient_id: Option<String>,
+    /// Memory namespace of the user the run is talking to (channel-qualified
+    /// sender ID), used to scope per-user memories
+    pub user_id: Option<String>,
+    /// Cancellation token of the run that invoked the tool
+    pub cancel_token: CancellationToken,
+    /// Shared WebSocket server state (if available)
+    pub state: Option<Arc<WsServerState>>,
+    /// Session store (if available)
+    pub session_store: Option<Arc<SessionStore>>,
+    /// Shared outbound message pipeline (if available)
+    pub message_pipeline: Option<Arc<MessagePipeline>>,
+    /// Long-term memory store (if available)
+    pub memory_store: Option<Arc<MemoryStore>>,
 }
 
 impl Default for ToolInvokeContext {
@@ -39,10 +69,63 @@ impl Default for ToolInvokeContext {
             account_id: None,
             sandboxed: false,
             dry_run: false,
+            recipient_id: None,
+            user_id: None,
+            cancel_token: CancellationToken::new(),
+            state: None,
+            session_store: None,
+            message_pipeline: None,
+            memory_store: None,
         }
     }
 }
 
+impl std::fmt::Debug for ToolInvokeContext {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ToolInvokeContext")
+            .field("agent_id", &self.agent_id)
+            .field("session_key", &self.session_key)
+            .field("message_channel", &self.message_channel)
+            .field("account_id", &self.account_id)
+            .field("sandboxed", &self.sandboxed)
+            .field("dry_run", &self.dry_run)
+            .field("recipient_id", &self.recipient_id)
+            .field("user_id", &self.user_id)
+            .field("cancelled", &self.cancel_token.is_cancelled())
+            .field("state", &self.state.is_some())
+            .field("session_store", &self.session_store.is_some())
+            .field("message_pipeline", &self.message_pipeline.is_some())
+            .field("memory_store", &self.memory_store.is_some())
+            .finish()
+    }
+}
+
+impl ToolInvokeContext {
+    /// Attach the server state and the subsystems it owns.
+    pub fn with_server_state(mut self, state: &Arc<WsServerState>) -> Self {
+        self.session_store = Some(state.session_store().clone());
+        self.message_pipeline = Some(state.message_pipeline().clone());
+        self.memory_store = Some(state.memory_store().clone());
+        self.state = Some(state.clone());
+        self
+    }
+
+    /// Cron scheduler of the running server, if available.
+    pub fn cron_scheduler(&self) -> Option<&CronScheduler> {
+        self.state.as_ref().map(|state| &state.cron_scheduler)
+    }
+
+    /// Process-wide usage tracker.
+    pub fn usage_tracker(&self) -> &'static RwLock<UsageTracker> {
+        crate::usage::global_tracker()
+    }
+
+    /// Whether the invoking run has been cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel_token.is_cancelled()
+    }
+}
+
 /// Tool invocation result
 #[derive(Debug, Clone, Serialize)]
 #[serde(untagged)]
@@ -84,9 +167,23 @@ impl ToolInvokeResult {
     }
 }
 
+/// Future returned by a built-in tool handler
+pub type ToolFuture = BoxFuture<'static, ToolInvokeResult>;
+
 /// Built-in tool handler function type
-pub type BuiltinToolHandler =
-    Box<dyn Fn(Value, &ToolInvokeContext) -> ToolInvokeResult + Send + Sync>;
+///
+/// Handlers receive an owned copy of the invoke context so the returned
+/// future can outlive the registry lock.
+pub type BuiltinToolHandler = Box<dyn Fn(Value, ToolInvokeContext) -> ToolFuture + Send + Sync>;
+
+/// Wrap an async closure as a [`BuiltinToolHandler`].
+pub fn tool_handler<F, Fut>(f: F) -> BuiltinToolHandler
+where
+    F: Fn(Value, ToolInvokeContext) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = ToolInvokeResult> + Send + 'static,
+{
+    Box::new(move |args, ctx| Box::pin(f(args, ctx)))
+}
 
 /// Built-in tool definition
 pub struct BuiltinTool {
@@ -139,7 +236,7 @@ impl ToolsRegistry {
                 "properties": {},
                 "additionalProperties": false
             }),
-            handler: Box::new(|_args, _ctx| {
+            handler: tool_handler(|_args, _ctx| async {
                 let now = chrono::Utc::now();
                 ToolInvokeResult::success(serde_json::json!({
                     "timestamp": now.format("%Y-%m-%dT%H:%M:%SZ").to_string(),
@@ -347,7 +444,10 @@ impl ToolsRegistry {
     /// Invoke a tool by name.
     ///
     /// Channel-specific tools take precedence when a message channel is set.
-    pub fn invoke(
+    /// Built-in and plugin tools are raced against the context's cancellation
+    /// token; a cancelled run yields a tool error without waiting for the
+    /// tool to finish.
+    pub async fn invoke(
         &self,
         tool_name: &str,
         args: Value,
@@ -358,111 +458,85 @@ impl ToolsRegistry {
             return ToolInvokeResult::not_found(tool_name);
         }
 
+        if let Some(future) = self.builtin_future(tool_name, &args, ctx) {
+            return tokio::select! {
+                result = future => result,
+                _ = ctx.cancel_token.cancelled() => {
+                    ToolInvokeResult::tool_error(format!("tool \"{tool_name}\" cancelled"))
+                }
+            };
+        }
+
+        self.invoke_plugin_tool(tool_name, args, ctx).await
+    }
+
+    /// Resolve a built-in tool (channel-specific first) and start its handler.
+    fn builtin_future(
+        &self,
+        tool_name: &str,
+        args: &Value,
+        ctx: &ToolInvokeContext,
+    ) -> Option<ToolFuture> {
         // Check channel-specific built-in tools first
         if let Some(channel) = ctx.message_channel.as_deref() {
             for tool in crate::agent::builtin_tools::channel_specific_tools(Some(channel)) {
                 if tool.name.eq_ignore_ascii_case(tool_name) {
-                    return (tool.handler)(args, ctx);
+                    return Some((tool.handler)(args.clone(), ctx.clone()));
                 }
             }
         }
 
         // Check built-in tools next
-        {
-            let tools = self.builtin_tools.read();
-            if let Some(tool) = tools.get(tool_name) {
-                return (tool.handler)(args, ctx);
-            }
-            if let Some((_name, tool)) = tools
+        let tools = self.builtin_tools.read();
+        let tool = tools.get(tool_name).or_else(|| {
+            tools
                 .iter()
                 .find(|(name, _)| name.eq_ignore_ascii_case(tool_name))
-            {
-                return (tool.handler)(args, ctx);
-            }
-        }
+                .map(|(_name, tool)| tool)
+        })?;
+        Some((tool.handler)(args.clone(), ctx.clone()))
+    }
 
-        // Check plugin tools
-        if let Some(dispatcher) = self.plugin_dispatcher() {
-            let tool_ctx = ToolContext {
-                agent_id: ctx.agent_id.clone(),
-                session_key: Some(ctx.session_key.clone()),
-                message_channel: ctx.message_channel.clone(),
-                sandboxed: ctx.sandboxed,
-            };
-            let params = serde_json::to_string(&args).unwrap_or_else(|_| "{}".to_string());
-            match dispatcher.invoke(tool_name, &params, tool_ctx) {
-                Ok(result) => {
-                    if result.success {
-                        let result_value = result
-                            .result
-                            .as_ref()
-                            .and_then(|s| serde_json::from_str(s).ok())
-                            .unwrap_or(Value::Null);
-                        return ToolInvokeResult::success(result_value);
-                    }
-                    return ToolInvokeResult::tool_error(
-                        result.error.unwrap_or_else(|| "Unknown error".to_string()),
-                    );
-                }
-                Err(DispatchError::ToolNotFound(_)) => {}
-                Err(e) => {
-                    return ToolInvokeResult::tool_error(e.to_string());
-                }
-            }
+    /// Invoke a plugin-provided tool.
+    ///
+    /// Plugin calls block, so they run on the blocking pool and are raced
+    /// against the context's cancellation token like built-in handlers.
+    async fn invoke_plugin_tool(
+        &self,
+        tool_name: &str,
+        args: Value,
+        ctx: &ToolInvokeContext,
+    ) -> ToolInvokeResult {
+        let dispatcher = self.plugin_dispatcher();
+        let plugins: Vec<(String, Arc<dyn ToolPluginInstance>)> = if dispatcher.is_none() {
+            self.plugin_tools
+                .read()
+                .iter()
+                .map(|(id, instance)| (id.clone(), instance.clone()))
+                .collect()
         } else {
-            let plugins = self.plugin_tools.read();
-            for (plugin_id, instance) in plugins.iter() {
-                match instance.get_definitions() {
-                    Ok(definitions) => {
-                        if let Some(def) = definitions
-                            .iter()
-                            .find(|d| d.name.eq_ignore_ascii_case(tool_name))
-                        {
-                            let tool_ctx = ToolContext {
-                                agent_id: ctx.agent_id.clone(),
-                                session_key: Some(ctx.session_key.clone()),
-                                message_channel: ctx.message_channel.clone(),
-                                sandboxed: ctx.sandboxed,
-                            };
-
-                            let params =
-                                serde_json::to_string(&args).unwrap_or_else(|_| "{}".to_string());
-                            match instance.invoke(&def.name, &params, tool_ctx) {
-                                Ok(result) => {
-                                    if result.success {
-                                        let result_value = result
-                                            .result
-                                            .as_ref()
-                                            .and_then(|s| serde_json::from_str(s).ok())
-                                            .unwrap_or(Value::Null);
-                                        return ToolInvokeResult::success(result_value);
-                                    } else {
-                                        return ToolInvokeResult::tool_error(
-                                            result
-                                                .error
-                                                .unwrap_or_else(|| "Unknown error".to_string()),
-                                        );
-                                    }
-                                }
-                                Err(e) => {
-                                    return ToolInvokeResult::tool_error(e.to_string());
-                                }
-                            }
-                        }
-                    }
-                    Err(err) => {
-                        tracing::warn!(
-                            plugin_id = %plugin_id,
-                            error = %err,
-                            "tool definitions unavailable"
-                        );
-                    }
-                }
+            Vec::new()
+        };
+        let tool_ctx = ToolContext {
+            agent_id: ctx.agent_id.clone(),
+            session_key: Some(ctx.session_key.clone()),
+            message_channel: ctx.message_channel.clone(),
+            sandboxed: ctx.sandboxed,
+        };
+        let params = serde_json::to_string(&args).unwrap_or_else(|_| "{}".to_string());
+        let name = tool_name.to_string();
+
+        let call = tokio::task::spawn_blocking(move || {
+            call_plugin_tool(dispatcher, &plugins, &name, &params, tool_ctx)
+        });
+        tokio::select! {
+            joined = call => joined.unwrap_or_else(|e| {
+                ToolInvokeResult::tool_error(format!("tool \"{tool_name}\" failed: {e}"))
+            }),
+            _ = ctx.cancel_token.cancelled() => {
+                ToolInvokeResult::tool_error(format!("tool \"{tool_name}\" cancelled"))
             }
         }
-
-        // Tool not found
-        ToolInvokeResult::not_found(tool_name)
     }
 
     /// Check if a tool exists (and is allowed).
@@ -560,6 +634,61 @@ impl ToolsRegistry {
     }
 }
 
+/// Look up and call a plugin tool, via the dispatcher when one is available.
+fn call_plugin_tool(
+    dispatcher: Option<Arc<ToolDispatcher>>,
+    plugins: &[(String, Arc<dyn ToolPluginInstance>)],
+    tool_name: &str,
+    params: &str,
+    tool_ctx: ToolContext,
+) -> ToolInvokeResult {
+    if let Some(dispatcher) = dispatcher {
+        return match dispatcher.invoke(tool_name, params, tool_ctx) {
+            Ok(result) => plugin_result(result),
+            Err(DispatchError::ToolNotFound(_)) => ToolInvokeResult::not_found(tool_name),
+            Err(e) => ToolInvokeResult::tool_error(e.to_string()),
+        };
+    }
+
+    for (plugin_id, instance) in plugins {
+        match instance.get_definitions() {
+            Ok(definitions) => {
+                if let Some(def) = definitions
+                    .iter()
+                    .find(|d| d.name.eq_ignore_ascii_case(tool_name))
+                {
+                    return match instance.invoke(&def.name, params, tool_ctx) {
+                        Ok(result) => plugin_result(result),
+                        Err(e) => ToolInvokeResult::tool_error(e.to_string()),
+                    };
+                }
+            }
+            Err(err) => {
+                tracing::warn!(
+                    plugin_id = %plugin_id,
+                    error = %err,
+                    "tool definitions unavailable"
+                );
+            }
+        }
+    }
+
+    ToolInvokeResult::not_found(tool_name)
+}
+
+/// Convert a plugin tool result into an invocation result.
+fn plugin_result(result: ToolResult) -> ToolInvokeResult {
+    if result.success {
+        let result_value = result
+            .result
+            .as_ref()
+            .and_then(|s| serde_json::from_str(s).ok())
+            .unwrap_or(Value::Null);
+        return ToolInvokeResult::success(result_value);
+    }
+    ToolInvokeResult::tool_error(result.error.unwrap_or_else(|| "Unknown error".to_string()))
+}
+
 /// Create a shared tools registry
 pub fn create_registry() -> Arc<ToolsRegistry> {
     Arc::new(ToolsRegistry::new())
@@ -575,12 +704,12 @@ mod tests {
         assert!(registry.has_tool("time"));
     }
 
-    #[test]
-    fn test_invoke_time_tool() {
+    #[tokio::test]
+    async fn test_invoke_time_tool() {
         let registry = ToolsRegistry::new();
         let ctx = ToolInvokeContext::default();
 
-        let result = registry.invoke("time", serde_json::json!({}), &ctx);
+        let result = registry.invoke("time", serde_json::json!({}), &ctx).await;
         match result {
             ToolInvokeResult::Success { ok, result } => {
                 assert!(ok);
@@ -592,12 +721,14 @@ mod tests {
         }
     }
 
-    #[test]
-    fn test_invoke_nonexistent_tool() {
+    #[tokio::test]
+    async fn test_invoke_nonexistent_tool() {
         let registry = ToolsRegistry::new();
         let ctx = ToolInvokeContext::default();
 
-        let result = registry.invoke("nonexistent", serde_json::json!({}), &ctx);
+        let result = registry
+            .invoke("nonexistent", serde_json::json!({}), &ctx)
+            .await;
         match result {
             ToolInvokeResult::Error { ok, error } => {
                 assert!(!ok);
@@ -608,8 +739,8 @@ mod tests {
         }
     }
 
-    #[test]
-    fn test_register_builtin_tool() {
+    #[tokio::test]
+    async fn test_register_builtin_tool() {
         let registry = ToolsRegistry::new();
 
         registry.register_builtin_tool(BuiltinTool {
@@ -621,7 +752,7 @@ mod tests {
                     "message": { "type": "string" }
                 }
             }),
-            handler: Box::new(|args, _ctx| {
+            handler: tool_handler(|args, _ctx| async move {
                 let message = args
                     .get("message")
                     .and_then(|v| v.as_str())
@@ -633,7 +764,9 @@ mod tests {
         assert!(registry.has_tool("echo"));
 
         let ctx = ToolInvokeContext::default();
-        let result = registry.invoke("echo", serde_json::json!({ "message": "hello" }), &ctx);
+        let result = registry
+            .invoke("echo", serde_json::json!({ "message": "hello" }), &ctx)
+            .await;
         match result {
             ToolInvokeResult::Success { result, .. } => {
                 assert_eq!(result["echo"], "hello");
@@ -675,15 +808,15 @@ mod tests {
         assert!(registry.has_tool_for_channel("telegram_edit_message", Some("telegram")));
     }
 
-    #[test]
-    fn test_channel_tool_precedence_over_builtin() {
+    #[tokio::test]
+    async fn test_channel_tool_precedence_over_builtin() {
         let registry = ToolsRegistry::new();
 
         registry.register_builtin_tool(BuiltinTool {
             name: "telegram_edit_message".to_string(),
             description: "Shadow tool".to_string(),
             input_schema: serde_json::json!({"type": "object", "properties": {}}),
-            handler: Box::new(|_args, _ctx| {
+            handler: tool_handler(|_args, _ctx| async {
                 ToolInvokeResult::success(serde_json::json!({ "source": "builtin" }))
             }),
         });
@@ -697,13 +830,17 @@ mod tests {
 
         let ctx = ToolInvokeContext {
             message_channel: Some("telegram".to_string()),
+            recipient_id: Some("chat-1".to_string()),
+            message_pipeline: Some(Arc::new(MessagePipeline::new())),
             ..ToolInvokeContext::default()
         };
-        let result = registry.invoke(
-            "telegram_edit_message",
-            serde_json::json!({"message_id": "1", "text": "hi"}),
-            &ctx,
-        );
+        let result = registry
+            .invoke(
+                "telegram_edit_message",
+                serde_json::json!({"message_id": "1", "text": "hi"}),
+                &ctx,
+            )
+            .await;
         match result {
             ToolInvokeResult::Success { result, .. } => {
                 assert!(
@@ -716,6 +853,120 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_invoke_cancelled_tool() {
+        let registry = ToolsRegistry::new();
+        registry.register_builtin_tool(BuiltinTool {
+            name: "stall".to_string(),
+            description: "Never finishes".to_string(),
+            input_schema: serde_json::json!({"type": "object", "properties": {}}),
+            handler: tool_handler(|_args, _ctx| async {
+                std::future::pending::<()>().await;
+                ToolInvokeResult::success(Value::Null)
+            }),
+        });
+
+        let ctx = ToolInvokeContext::default();
+        ctx.cancel_token.cancel();
+        match registry.invoke("stall", serde_json::json!({}), &ctx).await {
+            ToolInvokeResult::Error { error, .. } => {
+                assert_eq!(error.r#type, "tool_error");
+                assert!(error.message.contains("cancelled"));
+            }
+            _ => panic!("Expected cancellation error"),
+        }
+    }
+
+    /// Plugin tool whose calls block for a while
+    struct SlowPluginTool;
+
+    impl ToolPluginInstance for SlowPluginTool {
+        fn get_definitions(&self) -> Result<Vec<ToolDefinition>, crate::plugins::BindingError> {
+            Ok(vec![ToolDefinition {
+                name: "slow".to_string(),
+                description: "Blocks before answering".to_string(),
+                input_schema: "{}".to_string(),
+            }])
+        }
+
+        fn invoke(
+            &self,
+            _name: &str,
+            params: &str,
+            _ctx: ToolContext,
+        ) -> Result<ToolResult, crate::plugins::BindingError> {
+            std::thread::sleep(Duration::from_millis(300));
+            Ok(ToolResult {
+                success: true,
+                result: Some(params.to_string()),
+                error: None,
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_invoke_plugin_tool_runs_off_the_async_task() {
+        let registry = ToolsRegistry::new();
+        registry.register_plugin("slow-plugin".to_string(), Arc::new(SlowPluginTool));
+
+        let ctx = ToolInvokeContext::default();
+        match registry
+            .invoke("slow", serde_json::json!({"n": 1}), &ctx)
+            .await
+        {
+            ToolInvokeResult::Success { result, .. } => assert_eq!(result["n"], 1),
+            _ => panic!("Expected success result"),
+        }
+
+        let ctx = ToolInvokeContext::default();
+        ctx.cancel_token.cancel();
+        let started = Instant::now();
+        match registry.invoke("slow", serde_json::json!({}), &ctx).await {
+            ToolInvokeResult::Error { error, .. } => {
+                assert!(error.message.contains("cancelled"));
+            }
+            _ => panic!("Expected cancellation error"),
+        }
+        assert!(started.elapsed() < Duration::from_millis(300));
+    }
+
+    #[tokio::test]
+    async fn test_handler_receives_context() {
+        let registry = ToolsRegistry::new();
+        registry.register_builtin_tool(BuiltinTool {
+            name: "whoami".to_string(),
+            description: "Report the invoke context".to_string(),
+            input_schema: serde_json::json!({"type": "object", "properties": {}}),
+            handler: tool_handler(|_args, ctx| async move {
+                ToolInvokeResult::success(serde_json::json!({
+                    "agent": ctx.agent_id,
+                    "session": ctx.session_key,
+                    "has_pipeline": ctx.message_pipeline.is_some(),
+                    "has_cron": ctx.cron_scheduler().is_some(),
+                }))
+            }),
+        });
+
+        let state = Arc::new(WsServerState::new(
+            crate::server::ws::WsServerConfig::default(),
+        ));
+        let ctx = ToolInvokeContext {
+            agent_id: Some("ops".to_string()),
+            session_key: "agent:ops:main".to_string(),
+            ..ToolInvokeContext::default()
+        }
+        .with_server_state(&state);
+        match registry.invoke("whoami", serde_json::json!({}), &ctx).await {
+            ToolInvokeResult::Success { result, .. } => {
+                assert_eq!(result["agent"], "ops");
+                assert_eq!(result["session"], "agent:ops:main");
+                assert_eq!(result["has_pipeline"], true);
+                assert_eq!(result["has_cron"], true);
+            }
+            _ => panic!("Expected success result"),
+        }
+    }
+
     #[test]
     fn test_tool_invoke_result_serialization() {
         let success = ToolInvokeResult::success(serde_json::json!({ "data": 42 }));
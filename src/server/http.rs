@@ -11,7 +11,7 @@
 
 use axum::{
     body::Bytes,
-    extract::{DefaultBodyLimit, Path, Query, State},
+    extract::{multipart::MultipartRejection, DefaultBodyLimit, Multipart, Path, Query, State},
     http::{header, HeaderMap, HeaderValue, Method, StatusCode, Uri},
     middleware,
     response::{IntoResponse, Response},
@@ -37,6 +37,7 @@ use crate::server::headers::{security_headers_middleware, SecurityHeadersConfig}
 use crate::server::openai::{self, OpenAiState};
 use crate::server::ratelimit::{rate_limit_middleware, RateLimitConfig, RateLimiter};
 
+use crate::agent::routing;
 use crate::auth;
 use crate::channels::{inbound, slack_inbound, telegram_inbound, ChannelRegistry};
 use crate::hooks::auth::{extract_hooks_token, validate_hooks_token};
@@ -91,6 +92,10 @@ pub struct HttpConfig {
     pub openai_chat_completions_enabled: bool,
     /// Whether OpenResponses endpoint is enabled
     pub openai_responses_enabled: bool,
+    /// Whether OpenAI embeddings endpoint is enabled
+    pub openai_embeddings_enabled: bool,
+    /// Whether OpenAI audio (transcription/speech) endpoints are enabled
+    pub openai_audio_enabled: bool,
     /// Whether control endpoints are enabled
     pub control_endpoints_enabled: bool,
 }
@@ -114,6 +119,8 @@ impl Default for HttpConfig {
             agents_dir: crate::server::ws::resolve_state_dir().join("agents"),
             openai_chat_completions_enabled: false,
             openai_responses_enabled: false,
+            openai_embeddings_enabled: false,
+            openai_audio_enabled: false,
             control_endpoints_enabled: false,
         }
     }
@@ -250,6 +257,16 @@ pub fn build_http_config(cfg: &Value) -> Result<HttpConfig, String> {
         .and_then(|v| v.as_bool())
         .unwrap_or(false);
 
+    let openai_embeddings_enabled = openai_obj
+        .and_then(|o| o.get("embeddings"))
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+
+    let openai_audio_enabled = openai_obj
+        .and_then(|o| o.get("audio"))
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+
     let control_endpoints_enabled = control_obj
         .and_then(|c| c.get("enabled"))
         .and_then(|v| v.as_bool())
@@ -270,6 +287,8 @@ pub fn build_http_config(cfg: &Value) -> Result<HttpConfig, String> {
         control_ui_dist_path,
         openai_chat_completions_enabled,
         openai_responses_enabled,
+        openai_embeddings_enabled,
+        openai_audio_enabled,
         control_endpoints_enabled,
         ..Default::default()
     })
@@ -392,6 +411,7 @@ pub fn create_router_with_state(
 
     // Extract LLM provider before moving ws_state into AppState
     let llm_provider = ws_state.as_ref().and_then(|ws| ws.llm_provider());
+    let openai_ws_state = ws_state.clone();
 
     // Build health checker if ws_state provides a state directory
     let health_checker = ws_state.as_ref().map(|_| {
@@ -473,63 +493,112 @@ pub fn create_router_with_state(
     router = router.route("/tools/invoke", post(tools_invoke_handler));
 
     // OpenAI compatibility endpoints
-    if config.openai_chat_completions_enabled || config.openai_responses_enabled {
-        let openai_state = OpenAiState {
-            chat_completions_enabled: config.openai_chat_completions_enabled,
-            responses_enabled: config.openai_responses_enabled,
-            gateway_token: config.gateway_token.clone(),
-            gateway_password: config.gateway_password.clone(),
-            gateway_auth_mode: config.gateway_auth_mode.clone(),
-            gateway_allow_tailscale: config.gateway_allow_tailscale,
-            trusted_proxies: config.trusted_proxies.clone(),
-            llm_provider: llm_provider.clone(),
-        };
+    let openai_state = OpenAiState {
+        chat_completions_enabled: config.openai_chat_completions_enabled,
+        responses_enabled: config.openai_responses_enabled,
+        embeddings_enabled: config.openai_embeddings_enabled,
+        audio_enabled: config.openai_audio_enabled,
+        gateway_token: config.gateway_token.clone(),
+        gateway_password: config.gateway_password.clone(),
+        gateway_auth_mode: config.gateway_auth_mode.clone(),
+        gateway_allow_tailscale: config.gateway_allow_tailscale,
+        trusted_proxies: config.trusted_proxies.clone(),
+        llm_provider,
+        ws_state: openai_ws_state,
+    };
 
-        if config.openai_chat_completions_enabled {
-            router = router.route(
-                "/v1/chat/completions",
-                post(
-                    move |connect_info: MaybeConnectInfo, headers: HeaderMap, body: Bytes| {
-                        let state = openai_state.clone();
-                        async move {
-                            openai::chat_completions_handler(
-                                State(state),
-                                connect_info,
-                                headers,
-                                body,
-                            )
+    if openai_state.chat_completions_enabled {
+        let state = openai_state.clone();
+        router = router.route(
+            "/v1/chat/completions",
+            post(
+                move |connect_info: MaybeConnectInfo, headers: HeaderMap, body: Bytes| {
+                    let state = state.clone();
+                    async move {
+                        openai::chat_completions_handler(State(state), connect_info, headers, body)
                             .await
-                        }
-                    },
-                ),
-            );
-        }
+                    }
+                },
+            ),
+        );
+    }
 
-        let openai_state2 = OpenAiState {
-            chat_completions_enabled: config.openai_chat_completions_enabled,
-            responses_enabled: config.openai_responses_enabled,
-            gateway_token: config.gateway_token.clone(),
-            gateway_password: config.gateway_password.clone(),
-            gateway_auth_mode: config.gateway_auth_mode.clone(),
-            gateway_allow_tailscale: config.gateway_allow_tailscale,
-            trusted_proxies: config.trusted_proxies.clone(),
-            llm_provider,
-        };
+    if openai_state.responses_enabled {
+        let state = openai_state.clone();
+        router = router.route(
+            "/v1/responses",
+            post(
+                move |connect_info: MaybeConnectInfo, headers: HeaderMap, body: Bytes| {
+                    let state = state.clone();
+                    async move {
+                        openai::responses_handler(State(state), connect_info, headers, body).await
+                    }
+                },
+            ),
+        );
+    }
 
-        if config.openai_responses_enabled {
-            router = router.route(
-                "/v1/responses",
-                post(
-                    move |connect_info: MaybeConnectInfo, headers: HeaderMap, body: Bytes| {
-                        let state = openai_state2.clone();
-                        async move {
-                            openai::responses_handler(State(state), connect_info, headers, body)
-                                .await
-                        }
-                    },
-                ),
-            );
-        }
+    if openai_state.models_enabled() {
+        let state = openai_state.clone();
+        router = router.route(
+            "/v1/models",
+            get(move |connect_info: MaybeConnectInfo, headers: HeaderMap| {
+                let state = state.clone();
+                async move { openai::models_handler(State(state), connect_info, headers).await }
+            }),
+        );
+    }
+
+    if openai_state.embeddings_enabled {
+        let state = openai_state.clone();
+        router = router.route(
+            "/v1/embeddings",
+            post(
+                move |connect_info: MaybeConnectInfo, headers: HeaderMap, body: Bytes| {
+                    let state = state.clone();
+                    async move {
+                        openai::embeddings_handler(State(state), connect_info, headers, body).await
+                    }
+                },
+            ),
+        );
+    }
+
+    if openai_state.audio_enabled {
+        let state = openai_state.clone();
+        router = router.route(
+            "/v1/audio/transcriptions",
+            post(
+                move |connect_info: MaybeConnectInfo,
+                      headers: HeaderMap,
+                      multipart: Result<Multipart, MultipartRejection>| {
+                    let state = state.clone();
+                    async move {
+                        openai::transcriptions_handler(
+                            State(state),
+                            connect_info,
+                            headers,
+                            multipart,
+                        )
+                        .await
+                    }
+                },
+            )
+            .layer(DefaultBodyLimit::max(openai::MAX_TRANSCRIPTION_BYTES)),
+        );
+
+        let state = openai_state;
+        router = router.route(
+            "/v1/audio/speech",
+            post(
+                move |connect_info: MaybeConnectInfo, headers: HeaderMap, body: Bytes| {
+                    let state = state.clone();
+                    async move {
+                        openai::speech_handler(State(state), connect_info, headers, body).await
+                    }
+                },
+            ),
+        );
     }
 
     // Control endpoints
@@ -820,6 +889,7 @@ fn parse_agent_request(
             deliver: None,
             wake_mode: None,
             session_key: None,
+            agent_id: None,
             timeout_seconds: None,
             allow_unsafe_external_content: None,
             venice_parameters: None,
@@ -843,6 +913,9 @@ fn parse_agent_request(
 
 /// Dispatch a validated agent request through the WebSocket runtime, creating
 /// a session, registering the run, and optionally spawning the LLM executor.
+///
+/// The run uses the request's `agentId`, else the agent bound to the session,
+/// else the agent `agents.routes` selects.
 #[allow(clippy::result_large_err)]
 fn dispatch_agent_run(
     ws: &Arc<WsServerState>,
@@ -862,9 +935,42 @@ fn dispatch_agent_run(
         .map(|s| s.trim())
         .filter(|s| !s.is_empty())
         .unwrap_or(sender_id);
+    let routed_agent = match &validated.agent_id {
+        Some(agent_id) => {
+            if !routing::agent_ids(&cfg).contains(&agent_id.as_str()) {
+                return Err((
+                    StatusCode::BAD_REQUEST,
+                    Json(AgentResponse::error(&format!(
+                        "unknown agentId: {}",
+                        agent_id
+                    ))),
+                )
+                    .into_response());
+            }
+            Some(agent_id.clone())
+        }
+        None => routing::route_agent(
+            &cfg,
+            &routing::RouteRequest {
+                channel,
+                sender_id,
+                text: &validated.message,
+                ..Default::default()
+            },
+        ),
+    };
+    let session_key = validated.session_key.clone().or_else(|| {
+        routed_agent.as_deref().map(|agent_id| {
+            let (session_key, _, _) = crate::sessions::resolve_scoped_session_key(
+                &cfg, channel, sender_id, peer_id, None,
+            );
+            routing::agent_session_key(agent_id, &session_key)
+        })
+    });
     let metadata = crate::sessions::SessionMetadata {
         channel: Some(channel.to_string()),
         user_id: Some(sender_id.to_string()),
+        agent_id: routed_agent.clone(),
         ..Default::default()
     };
     let session = crate::sessions::get_or_create_scoped_session(
@@ -873,7 +979,7 @@ fn dispatch_agent_run(
         channel,
         sender_id,
         peer_id,
-        validated.session_key.as_deref(),
+        session_key.as_deref(),
         metadata,
     )
     .map_err(|e| {
@@ -925,13 +1031,16 @@ fn dispatch_agent_run(
 
     // Spawn agent executor if LLM provider is configured
     if let Some(provider) = ws.llm_provider() {
-        let cfg = crate::config::load_config().unwrap_or(Value::Object(serde_json::Map::new()));
+        let agent_id = validated
+            .agent_id
+            .as_deref()
+            .or(session.metadata.agent_id.as_deref())
+            .or(routed_agent.as_deref());
         let mut config = crate::agent::AgentConfig::default();
-        crate::agent::apply_agent_config_from_settings(&mut config, &cfg, None);
-        config.model = validated
-            .model
-            .clone()
-            .unwrap_or_else(|| crate::agent::DEFAULT_MODEL.to_string());
+        crate::agent::apply_agent_config_from_settings(&mut config, &cfg, agent_id);
+        if let Some(model) = &validated.model {
+            config.model = model.clone();
+        }
         config.deliver = validated.deliver;
         config.extra = validated.venice_parameters.clone();
         crate::agent::spawn_run(
@@ -1051,21 +1160,25 @@ async fn telegram_webhook_handler(
         Err(_) => return StatusCode::BAD_REQUEST.into_response(),
     };
 
-    let inbound = match telegram_inbound::extract_inbound(&update) {
+    let bot = telegram_inbound::TelegramBotIdentity::from_config(&cfg);
+    let inbound = match telegram_inbound::extract_inbound(&update, &bot) {
         Some(inbound) => inbound,
         None => return StatusCode::OK.into_response(),
     };
 
-    if let Err(err) = inbound::dispatch_inbound_text(
+    inbound::dispatch_inbound(
         &ws,
-        "telegram",
-        &inbound.sender_id,
-        &inbound.chat_id,
-        &inbound.text,
-        Some(inbound.chat_id.clone()),
-    ) {
-        warn!("Telegram inbound dispatch failed: {}", err);
-    }
+        inbound::InboundMessage {
+            channel: "telegram".to_string(),
+            sender_id: inbound.sender_id,
+            peer_id: inbound.chat_id.clone(),
+            text: inbound.text,
+            chat_id: Some(inbound.chat_id),
+            account_id: bot.id.map(|id| id.to_string()),
+            media: inbound.media,
+            group: inbound.group,
+        },
+    );
 
     StatusCode::OK.into_response()
 }
@@ -1134,17 +1247,24 @@ async fn slack_events_handler(
 
     if payload.get("type").and_then(|v| v.as_str()) == Some("event_callback") {
         if let Some(event) = payload.get("event") {
-            if let Some(inbound) = slack_inbound::extract_inbound_event(event) {
-                if let Err(err) = inbound::dispatch_inbound_text(
+            let bot_user_id = slack_inbound::payload_bot_user_id(&payload);
+            if let Some(inbound) = slack_inbound::extract_inbound_event(event, bot_user_id) {
+                inbound::dispatch_inbound(
                     &ws,
-                    "slack",
-                    &inbound.sender_id,
-                    &inbound.channel_id,
-                    &inbound.text,
-                    Some(inbound.channel_id.clone()),
-                ) {
-                    warn!("Slack inbound dispatch failed: {}", err);
-                }
+                    inbound::InboundMessage {
+                        channel: "slack".to_string(),
+                        sender_id: inbound.sender_id,
+                        peer_id: inbound.channel_id.clone(),
+                        text: inbound.text,
+                        chat_id: Some(inbound.channel_id),
+                        account_id: payload
+                            .get("team_id")
+                            .and_then(|v| v.as_str())
+                            .map(String::from),
+                        media: inbound.media,
+                        group: inbound.group,
+                    },
+                );
             }
         }
     }
@@ -1217,8 +1337,10 @@ fn hook_result_to_response(
 }
 
 /// Look up a matching hook mapping and evaluate it, returning an HTTP response.
-fn execute_hook_mapping(state: &AppState, path: &str, ctx: &HookMappingContext) -> Response {
-    let mapping = match state.hook_registry.find_match(ctx) {
+///
+/// Evaluation may run a WASM transform, so it happens on the blocking pool.
+async fn execute_hook_mapping(state: &AppState, path: &str, ctx: HookMappingContext) -> Response {
+    let mapping = match state.hook_registry.find_match(&ctx) {
         Some(m) => m,
         None => {
             debug!("No hook mapping found for path: {}", path);
@@ -1228,7 +1350,14 @@ fn execute_hook_mapping(state: &AppState, path: &str, ctx: &HookMappingContext)
 
     debug!("Hook mapping found for path '{}': {:?}", path, mapping.id);
 
-    let result = state.hook_registry.evaluate(&mapping, ctx);
+    let registry = state.hook_registry.clone();
+    let result = tokio::task::spawn_blocking(move || registry.evaluate(&mapping, &ctx))
+        .await
+        .unwrap_or_else(|e| {
+            Err(crate::hooks::HookMappingError::TransformError(
+                e.to_string(),
+            ))
+        });
     hook_result_to_response(result)
 }
 
@@ -1271,7 +1400,7 @@ async fn hooks_mapping_handler(
     };
 
     let ctx = build_hook_context(&headers, &uri, &path, payload);
-    execute_hook_mapping(&state, &path, &ctx)
+    execute_hook_mapping(&state, &path, ctx).await
 }
 
 /// Plugin webhook handler: forwards `/plugins/<plugin-id>/<path>` to plugin instances.
@@ -1503,17 +1632,20 @@ async fn tools_invoke_handler(
         .map(|s| s.to_string());
 
     // Build tool invoke context
-    let ctx = ToolInvokeContext {
+    let mut ctx = ToolInvokeContext {
         agent_id: None,
         session_key: req.session_key.unwrap_or_else(|| "main".to_string()),
         message_channel,
         account_id,
-        sandboxed: false,
         dry_run: req.dry_run.unwrap_or(false),
+        ..Default::default()
     };
+    if let Some(ws) = state.ws_state.as_ref() {
+        ctx = ctx.with_server_state(ws);
+    }
 
     // Invoke the tool via the registry
-    let result = state.tools_registry.invoke(&tool_name, args, &ctx);
+    let result = state.tools_registry.invoke(&tool_name, args, &ctx).await;
 
     match result {
         ToolInvokeResult::Success { ok, result } => (
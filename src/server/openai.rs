@@ -3,6 +3,14 @@
 //! Implements:
 //! - POST /v1/chat/completions - Chat completions API
 //! - POST /v1/responses - OpenResponses API
+//! - GET /v1/models - Agent and provider model list
+//! - POST /v1/embeddings - Embeddings via the configured providers
+//! - POST /v1/audio/transcriptions - Whisper transcription
+//! - POST /v1/audio/speech - OpenAI text-to-speech
+//!
+//! `carapace` / `carapace:<agent>` / `agent:<agent>` models (or the
+//! `x-carapace-agent-id` header) run through the agent loop with server-side
+//! tools and session history; other model names go straight to the provider.
 
 use axum::{
     body::Body,
@@ -16,14 +24,19 @@ use serde_json::Value;
 use std::convert::Infallible;
 use std::net::SocketAddr;
 use std::sync::Arc;
+use tokio::sync::{mpsc, oneshot};
+use tokio_util::sync::CancellationToken;
 use uuid::Uuid;
 
 use crate::agent::provider::{
     CompletionRequest, ContentBlock, LlmMessage, LlmRole, StopReason, StreamEvent, TokenUsage,
+    ToolDefinition,
 };
-use crate::agent::LlmProvider;
+use crate::agent::{AgentConfig, LlmProvider};
 use crate::auth;
 use crate::server::connect_info::MaybeConnectInfo;
+use crate::server::ws::{register_session_run, AgentRunResult, AgentRunStatus, WsServerState};
+use crate::sessions;
 
 /// OpenAI chat completions request
 #[derive(Debug, Deserialize)]
@@ -38,6 +51,10 @@ pub struct ChatCompletionsRequest {
     pub stream: bool,
     /// Optional user identifier
     pub user: Option<String>,
+    /// Client tool (function) definitions
+    pub tools: Option<Vec<ResponsesTool>>,
+    /// Tool choice strategy
+    pub tool_choice: Option<Value>,
 }
 
 fn default_model() -> String {
@@ -50,10 +67,58 @@ pub struct ChatMessage {
     /// Message role
     pub role: String,
     /// Message content (string or array of parts)
-    #[serde(default)]
+    #[serde(default, deserialize_with = "deserialize_content")]
     pub content: ChatContent,
     /// Optional name for tool messages
     pub name: Option<String>,
+    /// Tool calls requested by an assistant message
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_calls: Option<Vec<ToolCall>>,
+    /// ID of the tool call a `tool` message answers
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_call_id: Option<String>,
+}
+
+/// Accept `null` content (assistant messages that only carry tool calls).
+fn deserialize_content<'de, D>(deserializer: D) -> Result<ChatContent, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    Ok(Option::<ChatContent>::deserialize(deserializer)?.unwrap_or_default())
+}
+
+/// Tool call in OpenAI format
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ToolCall {
+    pub id: String,
+    #[serde(default = "default_tool_type")]
+    pub r#type: String,
+    pub function: ToolCallFunction,
+}
+
+/// Function name and JSON-encoded arguments of a tool call
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ToolCallFunction {
+    pub name: String,
+    #[serde(default)]
+    pub arguments: String,
+}
+
+fn default_tool_type() -> String {
+    "function".to_string()
+}
+
+impl ToolCall {
+    fn new(id: String, name: String, input: &Value) -> Self {
+        Self {
+            id,
+            r#type: default_tool_type(),
+            function: ToolCallFunction {
+                name,
+                arguments: input.to_string(),
+            },
+        }
+    }
 }
 
 /// Chat content can be a string or array of content parts
@@ -150,6 +215,16 @@ pub struct ChunkDelta {
     pub role: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub content: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tool_calls: Option<Vec<ChunkToolCall>>,
+}
+
+/// Tool call in a streaming delta
+#[derive(Debug, Serialize)]
+pub struct ChunkToolCall {
+    pub index: usize,
+    #[serde(flatten)]
+    pub call: ToolCall,
 }
 
 /// OpenAI API error response
@@ -209,6 +284,10 @@ pub struct OpenAiState {
     pub chat_completions_enabled: bool,
     /// Whether responses endpoint is enabled
     pub responses_enabled: bool,
+    /// Whether embeddings endpoint is enabled
+    pub embeddings_enabled: bool,
+    /// Whether audio transcription and speech endpoints are enabled
+    pub audio_enabled: bool,
     /// Gateway auth token
     pub gateway_token: Option<String>,
     /// Gateway auth password
@@ -221,6 +300,19 @@ pub struct OpenAiState {
     pub trusted_proxies: Vec<String>,
     /// LLM provider for making actual API calls
     pub llm_provider: Option<Arc<dyn LlmProvider>>,
+    /// Gateway state; when set, `carapace` / `carapace:<agent>` models run
+    /// through the agent loop instead of calling the provider directly
+    pub ws_state: Option<Arc<WsServerState>>,
+}
+
+impl OpenAiState {
+    /// `/v1/models` is served whenever any OpenAI endpoint is enabled.
+    pub fn models_enabled(&self) -> bool {
+        self.chat_completions_enabled
+            || self.responses_enabled
+            || self.embeddings_enabled
+            || self.audio_enabled
+    }
 }
 
 /// Parse agent ID from model string
@@ -257,7 +349,9 @@ fn extract_system_messages(messages: &[ChatMessage]) -> Vec<String> {
 ///
 /// Returns `(system_prompt, messages)`. System/developer messages are merged
 /// into a single system prompt; user and assistant messages are mapped to
-/// `LlmMessage` entries.
+/// `LlmMessage` entries. Assistant `tool_calls` become `ToolUse` blocks and
+/// `tool` messages become `ToolResult` blocks, with consecutive results
+/// grouped into one user message.
 fn convert_to_llm_messages(messages: &[ChatMessage]) -> (Option<String>, Vec<LlmMessage>) {
     let system_parts = extract_system_messages(messages);
     let system = if system_parts.is_empty() {
@@ -266,43 +360,146 @@ fn convert_to_llm_messages(messages: &[ChatMessage]) -> (Option<String>, Vec<Llm
         Some(system_parts.join("\n\n"))
     };
 
-    let llm_messages: Vec<LlmMessage> = messages
-        .iter()
-        .filter(|m| m.role == "user" || m.role == "assistant")
-        .map(|m| {
-            let role = if m.role == "user" {
-                LlmRole::User
-            } else {
-                LlmRole::Assistant
-            };
-            LlmMessage {
-                role,
+    let mut llm_messages: Vec<LlmMessage> = Vec::new();
+    for m in messages {
+        match m.role.as_str() {
+            "user" => llm_messages.push(LlmMessage {
+                role: LlmRole::User,
                 content: vec![ContentBlock::Text {
                     text: m.content.to_text(),
                 }],
+            }),
+            "assistant" => {
+                let text = m.content.to_text();
+                let tool_calls = m.tool_calls.as_deref().unwrap_or_default();
+                let mut content = Vec::new();
+                if !text.is_empty() || tool_calls.is_empty() {
+                    content.push(ContentBlock::Text { text });
+                }
+                content.extend(tool_calls.iter().map(|call| ContentBlock::ToolUse {
+                    id: call.id.clone(),
+                    name: call.function.name.clone(),
+                    input: parse_tool_arguments(&call.function.arguments),
+                }));
+                llm_messages.push(LlmMessage {
+                    role: LlmRole::Assistant,
+                    content,
+                });
             }
-        })
-        .collect();
+            "tool" => {
+                let block = ContentBlock::ToolResult {
+                    tool_use_id: m.tool_call_id.clone().unwrap_or_default(),
+                    content: m.content.to_text(),
+                    is_error: false,
+                };
+                match llm_messages.last_mut() {
+                    Some(last)
+                        if last.role == LlmRole::User
+                            && last
+                                .content
+                                .iter()
+                                .all(|b| matches!(b, ContentBlock::ToolResult { .. })) =>
+                    {
+                        last.content.push(block)
+                    }
+                    _ => llm_messages.push(LlmMessage {
+                        role: LlmRole::User,
+                        content: vec![block],
+                    }),
+                }
+            }
+            _ => {}
+        }
+    }
 
     (system, llm_messages)
 }
 
+/// Parse JSON-encoded tool call arguments; empty or invalid arguments become
+/// an empty object.
+fn parse_tool_arguments(arguments: &str) -> Value {
+    serde_json::from_str::<Value>(arguments)
+        .ok()
+        .filter(|v| v.is_object())
+        .unwrap_or_else(|| Value::Object(serde_json::Map::new()))
+}
+
+/// Extract the function name from a `tool_choice` object, in either the
+/// chat form (`{"type":"function","function":{"name":..}}`) or the
+/// OpenResponses form (`{"type":"function","name":..}`).
+fn tool_choice_function(tool_choice: &Value) -> Option<&str> {
+    let obj = tool_choice.as_object()?;
+    if obj.get("type").and_then(|v| v.as_str()) != Some("function") {
+        return None;
+    }
+    obj.get("function")
+        .and_then(|f| f.get("name"))
+        .or_else(|| obj.get("name"))
+        .and_then(|v| v.as_str())
+}
+
+/// Map client tools to provider tool definitions, honouring `tool_choice`:
+/// `"none"` sends no tools and a function choice sends only that function.
+fn select_tools(
+    tools: Option<&[ResponsesTool]>,
+    tool_choice: Option<&Value>,
+) -> Vec<ToolDefinition> {
+    let tools = tools.unwrap_or_default();
+    match tool_choice {
+        Some(choice) if choice.as_str() == Some("none") => Vec::new(),
+        Some(choice) => match tool_choice_function(choice) {
+            Some(name) => tools
+                .iter()
+                .filter(|t| t.function_name() == Some(name))
+                .filter_map(ResponsesTool::to_definition)
+                .collect(),
+            None => tools
+                .iter()
+                .filter_map(ResponsesTool::to_definition)
+                .collect(),
+        },
+        None => tools
+            .iter()
+            .filter_map(ResponsesTool::to_definition)
+            .collect(),
+    }
+}
+
+/// Map a provider stop reason to an OpenAI finish reason.
+fn finish_reason(reason: StopReason, has_tool_calls: bool) -> &'static str {
+    match reason {
+        _ if has_tool_calls => "tool_calls",
+        StopReason::ToolUse => "tool_calls",
+        StopReason::MaxTokens => "length",
+        StopReason::EndTurn => "stop",
+    }
+}
+
+/// Collected result of a provider call.
+#[derive(Debug)]
+struct LlmCompletion {
+    text: String,
+    tool_calls: Vec<ToolCall>,
+    finish_reason: &'static str,
+    usage: TokenUsage,
+}
+
 /// Call the LLM provider and collect the full response (non-streaming collection).
-///
-/// Returns `(response_text, usage)` on success.
 async fn call_llm_provider(
     provider: &dyn LlmProvider,
     model: &str,
     system: Option<String>,
     messages: Vec<LlmMessage>,
-) -> Result<(String, TokenUsage), String> {
+    tools: Vec<ToolDefinition>,
+) -> Result<LlmCompletion, String> {
     let request = CompletionRequest {
         model: model.to_string(),
         messages,
         system,
-        tools: vec![],
+        tools,
         max_tokens: 8192,
         temperature: None,
+        thinking: None,
         extra: None,
     };
 
@@ -312,29 +509,34 @@ async fn call_llm_provider(
         .await
         .map_err(|e| format!("LLM provider error: {}", e))?;
 
-    let mut text = String::new();
-    let mut usage = TokenUsage::default();
+    let mut completion = LlmCompletion {
+        text: String::new(),
+        tool_calls: Vec::new(),
+        finish_reason: "stop",
+        usage: TokenUsage::default(),
+    };
 
     while let Some(event) = rx.recv().await {
         match event {
             StreamEvent::TextDelta { text: delta } => {
-                text.push_str(&delta);
+                completion.text.push_str(&delta);
+            }
+            StreamEvent::ToolUse { id, name, input } => {
+                completion.tool_calls.push(ToolCall::new(id, name, &input));
             }
-            StreamEvent::Stop { usage: u, .. } => {
-                usage = u;
+            StreamEvent::Stop { reason, usage } => {
+                completion.finish_reason = finish_reason(reason, !completion.tool_calls.is_empty());
+                completion.usage = usage;
                 break;
             }
             StreamEvent::Error { message } => {
                 return Err(message);
             }
-            StreamEvent::ToolUse { .. } => {
-                // Tool calls are not supported in the OpenAI chat endpoint;
-                // treat as end of response.
-            }
+            StreamEvent::ThinkingDelta { .. } | StreamEvent::ThinkingSignature { .. } => {}
         }
     }
 
-    Ok((text, usage))
+    Ok(completion)
 }
 
 /// Stream LLM provider events as OpenAI-format SSE chunks.
@@ -343,6 +545,7 @@ async fn stream_llm_provider(
     model: String,
     system: Option<String>,
     messages: Vec<LlmMessage>,
+    tools: Vec<ToolDefinition>,
     response_id: String,
     created: i64,
 ) -> Response {
@@ -350,9 +553,10 @@ async fn stream_llm_provider(
         model: model.clone(),
         messages,
         system,
-        tools: vec![],
+        tools,
         max_tokens: 8192,
         temperature: None,
+        thinking: None,
         extra: None,
     };
 
@@ -369,6 +573,7 @@ async fn stream_llm_provider(
 
     let stream = async_stream::stream! {
         let mut rx = rx;
+        let mut tool_calls = 0;
 
         yield Ok::<_, Infallible>(format_sse_chunk(&build_chunk(
             &response_id, created, &model,
@@ -382,12 +587,17 @@ async fn stream_llm_provider(
                         &response_id, created, &model, None, Some(text), None,
                     )));
                 }
+                StreamEvent::ToolUse { id, name, input } => {
+                    let mut chunk = build_chunk(&response_id, created, &model, None, None, None);
+                    chunk.choices[0].delta.tool_calls = Some(vec![ChunkToolCall {
+                        index: tool_calls,
+                        call: ToolCall::new(id, name, &input),
+                    }]);
+                    tool_calls += 1;
+                    yield Ok::<_, Infallible>(format_sse_chunk(&chunk));
+                }
                 StreamEvent::Stop { reason, .. } => {
-                    let finish = match reason {
-                        StopReason::EndTurn => "stop",
-                        StopReason::MaxTokens => "length",
-                        StopReason::ToolUse => "stop",
-                    };
+                    let finish = finish_reason(reason, tool_calls > 0);
                     yield Ok::<_, Infallible>(format_sse_chunk(&build_chunk(
                         &response_id, created, &model, None, None, Some(finish.to_string()),
                     )));
@@ -400,7 +610,7 @@ async fn stream_llm_provider(
                     )));
                     break;
                 }
-                StreamEvent::ToolUse { .. } => {}
+                StreamEvent::ThinkingDelta { .. } | StreamEvent::ThinkingSignature { .. } => {}
             }
         }
 
@@ -427,7 +637,11 @@ fn build_chunk(
         model: model.to_string(),
         choices: vec![ChunkChoice {
             index: 0,
-            delta: ChunkDelta { role, content },
+            delta: ChunkDelta {
+                role,
+                content,
+                tool_calls: None,
+            },
             finish_reason,
         }],
     }
@@ -463,6 +677,472 @@ fn build_error_sse_response(error_msg: String) -> Response {
     sse_response(body)
 }
 
+// ============================================================================
+// Agent-backed runs
+// ============================================================================
+
+/// Header selecting the agent session a request continues.
+const SESSION_KEY_HEADER: &str = "x-carapace-session-key";
+
+/// Resolve the agent a request targets.
+///
+/// Returns `None` when the request should go straight to the provider,
+/// `Some(None)` for the default agent (`carapace`) and `Some(Some(id))` for
+/// an agent named by the `x-carapace-agent-id` header or a
+/// `carapace:<id>` / `agent:<id>` model.
+fn resolve_agent_target(headers: &HeaderMap, model: &str) -> Option<Option<String>> {
+    let header_agent = headers
+        .get("x-carapace-agent-id")
+        .or_else(|| headers.get("x-carapace-agent"))
+        .and_then(|v| v.to_str().ok())
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(str::to_string);
+    if header_agent.is_some() {
+        return Some(header_agent);
+    }
+    if model == "carapace" || model.starts_with("carapace:") || model.starts_with("agent:") {
+        return Some(parse_agent_id(model).filter(|id| !id.trim().is_empty()));
+    }
+    None
+}
+
+/// Resolve the persistent session key for an agent request:
+/// `openai:<agent>:<key>` from the `x-carapace-session-key` header, else
+/// from the request's `user` field. Keys are always scoped to the agent's
+/// `openai:` namespace so clients cannot attach to channel or other agents'
+/// sessions. `None` means a per-request session.
+fn agent_session_key(
+    headers: &HeaderMap,
+    agent_id: Option<&str>,
+    user: Option<&str>,
+) -> Option<String> {
+    let key = headers
+        .get(SESSION_KEY_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .or_else(|| user.map(str::trim).filter(|s| !s.is_empty()))?;
+    let namespace = format!(
+        "openai:{}:",
+        agent_id.unwrap_or(crate::memory::DEFAULT_AGENT_ID)
+    );
+    if key.starts_with(&namespace) {
+        return Some(key.to_string());
+    }
+    Some(format!("{}{}", namespace, key))
+}
+
+/// An agent run started for an OpenAI-compatible request.
+///
+/// Dropping the handle before the run finishes (e.g. the client
+/// disconnected mid-stream) cancels the run.
+struct AgentRunHandle {
+    state: Arc<WsServerState>,
+    run_id: String,
+    cancel_token: CancellationToken,
+    events: mpsc::UnboundedReceiver<Value>,
+    waiter: Option<oneshot::Receiver<AgentRunResult>>,
+    /// Per-request session, deleted once the run has ended
+    ephemeral_session: Option<String>,
+    finished: bool,
+}
+
+impl Drop for AgentRunHandle {
+    fn drop(&mut self) {
+        self.state.unsubscribe_agent_events(&self.run_id);
+        if !self.finished {
+            self.cancel_token.cancel();
+        }
+        if let Some(session_id) = self.ephemeral_session.take() {
+            let waiter = self
+                .state
+                .agent_run_registry
+                .lock()
+                .add_waiter(&self.run_id);
+            let state = self.state.clone();
+            tokio::spawn(async move {
+                if let Some(waiter) = waiter {
+                    let _ = waiter.await;
+                }
+                if let Err(e) = state.session_store().delete_session(&session_id) {
+                    tracing::debug!(session_id = %session_id, error = %e, "failed to delete per-request session");
+                }
+            });
+        }
+    }
+}
+
+/// Final output of an agent run.
+struct AgentRunOutput {
+    text: String,
+    finish_reason: &'static str,
+    usage: TokenUsage,
+}
+
+impl AgentRunHandle {
+    /// The run has ended; dropping the handle no longer cancels it.
+    fn mark_finished(&mut self) {
+        self.finished = true;
+    }
+
+    /// Wait for the run to end and collect its response and usage.
+    async fn wait(mut self) -> Result<AgentRunOutput, String> {
+        let waiter = self
+            .waiter
+            .take()
+            .ok_or_else(|| "agent run not found".to_string())?;
+        let result = waiter
+            .await
+            .map_err(|_| "agent run ended without a result".to_string())?;
+        self.mark_finished();
+
+        // `complete` is broadcast before the run is marked completed
+        let mut complete = None;
+        while let Ok(event) = self.events.try_recv() {
+            if event.get("stream").and_then(|v| v.as_str()) == Some("complete") {
+                complete = event.get("data").cloned();
+            }
+        }
+
+        match result.status {
+            AgentRunStatus::Completed => Ok(AgentRunOutput {
+                text: result.response.unwrap_or_default(),
+                finish_reason: complete
+                    .as_ref()
+                    .map(complete_finish_reason)
+                    .unwrap_or("stop"),
+                usage: complete.as_ref().map(complete_usage).unwrap_or_default(),
+            }),
+            _ => Err(result
+                .error
+                .unwrap_or_else(|| "agent run failed".to_string())),
+        }
+    }
+}
+
+/// Map the `stopReason` of a `complete` agent event to an OpenAI finish reason.
+fn complete_finish_reason(data: &Value) -> &'static str {
+    match data.get("stopReason").and_then(|v| v.as_str()) {
+        Some("max_tokens") => "length",
+        _ => "stop",
+    }
+}
+
+/// Read the run totals from a `complete` agent event.
+fn complete_usage(data: &Value) -> TokenUsage {
+    let tokens = |name: &str| {
+        data.get("usage")
+            .and_then(|u| u.get(name))
+            .and_then(|v| v.as_u64())
+            .unwrap_or(0)
+    };
+    TokenUsage {
+        input_tokens: tokens("inputTokens"),
+        output_tokens: tokens("outputTokens"),
+    }
+}
+
+/// Start an agent run for an OpenAI-compatible request.
+///
+/// With a session key the run continues that persistent session and only the
+/// latest user message is appended. Without one, a per-request session is
+/// seeded with the client's earlier turns and deleted after the run. System
+/// messages are appended to the agent's system prompt.
+fn start_agent_run(
+    ws: &Arc<WsServerState>,
+    provider: Arc<dyn LlmProvider>,
+    run_id: &str,
+    agent_id: Option<String>,
+    session_key: Option<String>,
+    user: Option<&str>,
+    messages: &[ChatMessage],
+) -> Result<AgentRunHandle, String> {
+    let last_user = messages
+        .iter()
+        .rposition(|m| m.role == "user")
+        .ok_or_else(|| "Missing user message in `messages`.".to_string())?;
+    let cfg = crate::config::load_config_shared()
+        .unwrap_or_else(|_| Arc::new(Value::Object(serde_json::Map::new())));
+
+    let ephemeral = session_key.is_none();
+    let session_key = session_key.unwrap_or_else(|| {
+        format!(
+            "openai:{}:{}",
+            agent_id
+                .as_deref()
+                .unwrap_or(crate::memory::DEFAULT_AGENT_ID),
+            Uuid::new_v4().simple()
+        )
+    });
+    let metadata = sessions::SessionMetadata {
+        agent_id: agent_id.clone(),
+        channel: Some("openai".to_string()),
+        user_id: user.map(str::to_string),
+        ..Default::default()
+    };
+    let session = ws
+        .session_store()
+        .get_or_create_session(session_key.clone(), metadata)
+        .map_err(|e| format!("session create failed: {}", e))?;
+    let session_id = session.id.clone();
+
+    if ephemeral {
+        // Client tool calls and results are not replayed into agent history
+        let earlier: Vec<sessions::ChatMessage> = messages[..last_user]
+            .iter()
+            .filter(|m| !m.content.to_text().is_empty())
+            .filter_map(|m| match m.role.as_str() {
+                "user" => Some(sessions::ChatMessage::user(
+                    &session_id,
+                    m.content.to_text(),
+                )),
+                "assistant" => Some(sessions::ChatMessage::assistant(
+                    &session_id,
+                    m.content.to_text(),
+                )),
+                _ => None,
+            })
+            .collect();
+        ws.session_store()
+            .append_messages(&earlier)
+            .map_err(|e| format!("session write failed: {}", e))?;
+    }
+
+    let cancel_token =
+        register_session_run(ws, session, &messages[last_user].content.to_text(), run_id)?;
+
+    let mut config = AgentConfig::default();
+    crate::agent::apply_agent_config_from_settings(&mut config, cfg.as_ref(), agent_id.as_deref());
+    let request_system = extract_system_messages(messages);
+    if !request_system.is_empty() {
+        let request_system = request_system.join("\n\n");
+        config.system = Some(match config.system.take() {
+            Some(system) => format!("{}\n\n{}", system, request_system),
+            None => request_system,
+        });
+    }
+    if ephemeral {
+        // The session is thrown away after the run
+        config.compaction.auto = false;
+    }
+
+    let events = ws.subscribe_agent_events(run_id);
+    let waiter = ws.agent_run_registry.lock().add_waiter(run_id);
+    crate::agent::spawn_run(
+        run_id.to_string(),
+        session_key,
+        config,
+        ws.clone(),
+        provider,
+        cancel_token.clone(),
+    );
+
+    Ok(AgentRunHandle {
+        state: ws.clone(),
+        run_id: run_id.to_string(),
+        cancel_token,
+        events,
+        waiter,
+        ephemeral_session: ephemeral.then_some(session_id),
+        finished: false,
+    })
+}
+
+/// Stream an agent run's `text` events as OpenAI-format SSE chunks.
+///
+/// Chunks already sent cannot be retracted, so a failover after the failed
+/// model streamed output ends the stream with an error and cancels the run.
+fn stream_agent_run(
+    mut run: AgentRunHandle,
+    model: String,
+    response_id: String,
+    created: i64,
+) -> Response {
+    let stream = async_stream::stream! {
+        yield Ok::<_, Infallible>(format_sse_chunk(&build_chunk(
+            &response_id, created, &model,
+            Some("assistant".to_string()), None, None,
+        )));
+
+        let mut waiter = run.waiter.take();
+        loop {
+            // Events are drained first: `complete` and `error` precede the
+            // run's result, which only matters when a run ends without them.
+            tokio::select! {
+                biased;
+                event = run.events.recv() => {
+                    let Some(event) = event else { break };
+                    let data = event.get("data").cloned().unwrap_or(Value::Null);
+                    match event.get("stream").and_then(|v| v.as_str()) {
+                        Some("text") => {
+                            if let Some(delta) = data.get("delta").and_then(|v| v.as_str()) {
+                                yield Ok::<_, Infallible>(format_sse_chunk(&build_chunk(
+                                    &response_id, created, &model, None, Some(delta.to_string()), None,
+                                )));
+                            }
+                        }
+                        Some("complete") => {
+                            run.mark_finished();
+                            yield Ok::<_, Infallible>(format_sse_chunk(&build_chunk(
+                                &response_id, created, &model, None, None,
+                                Some(complete_finish_reason(&data).to_string()),
+                            )));
+                            break;
+                        }
+                        Some("error") => {
+                            run.mark_finished();
+                            let message = data
+                                .get("message")
+                                .and_then(|v| v.as_str())
+                                .unwrap_or("agent run failed")
+                                .to_string();
+                            let error_data = serde_json::to_string(&OpenAiError::api_error(message)).unwrap_or_default();
+                            yield Ok::<_, Infallible>(format!("data: {}\n\n", error_data));
+                            break;
+                        }
+                        Some("cancelled") => {
+                            run.mark_finished();
+                            break;
+                        }
+                        Some("failover") if data.get("partial").and_then(|v| v.as_bool()) == Some(true) => {
+                            let message = format!(
+                                "model {} failed after streaming partial output; retry the request",
+                                data.get("fromModel").and_then(|v| v.as_str()).unwrap_or("unknown"),
+                            );
+                            let error_data = serde_json::to_string(&OpenAiError::api_error(message)).unwrap_or_default();
+                            yield Ok::<_, Infallible>(format!("data: {}\n\n", error_data));
+                            break;
+                        }
+                        _ => {}
+                    }
+                }
+                result = async { waiter.as_mut().expect("waiter present").await }, if waiter.is_some() => {
+                    run.mark_finished();
+                    let error = match result {
+                        Ok(result) if result.status == AgentRunStatus::Completed => None,
+                        Ok(result) => Some(result.error.unwrap_or_else(|| "agent run failed".to_string())),
+                        Err(_) => Some("agent run ended without a result".to_string()),
+                    };
+                    match error {
+                        Some(message) => {
+                            let error_data = serde_json::to_string(&OpenAiError::api_error(message)).unwrap_or_default();
+                            yield Ok::<_, Infallible>(format!("data: {}\n\n", error_data));
+                        }
+                        None => {
+                            yield Ok::<_, Infallible>(format_sse_chunk(&build_chunk(
+                                &response_id, created, &model, None, None, Some("stop".to_string()),
+                            )));
+                        }
+                    }
+                    break;
+                }
+            }
+        }
+
+        yield Ok::<_, Infallible>("data: [DONE]\n\n".to_string());
+    };
+
+    sse_response(Body::from_stream(stream))
+}
+
+/// 503 response for requests that arrive without a configured provider.
+fn no_provider_response() -> Response {
+    (
+        StatusCode::SERVICE_UNAVAILABLE,
+        Json(OpenAiError::api_error(
+            "No LLM provider configured. Set ANTHROPIC_API_KEY to enable.",
+        )),
+    )
+        .into_response()
+}
+
+/// 400 response for client `tools` sent to an agent model, which runs its
+/// own tools server-side.
+fn agent_tools_response() -> Response {
+    (
+        StatusCode::BAD_REQUEST,
+        Json(OpenAiError::invalid_request(
+            "Agent models run their own tools; `tools` is not supported.",
+        )),
+    )
+        .into_response()
+}
+
+/// Serve a chat completion from an agent run.
+async fn agent_chat_completion(
+    state: &OpenAiState,
+    ws: &Arc<WsServerState>,
+    headers: &HeaderMap,
+    agent_id: Option<String>,
+    req: ChatCompletionsRequest,
+    response_id: String,
+    created: i64,
+) -> Response {
+    let Some(provider) = ws.llm_provider().or_else(|| state.llm_provider.clone()) else {
+        return no_provider_response();
+    };
+    if req.tools.as_ref().is_some_and(|t| !t.is_empty()) {
+        return agent_tools_response();
+    }
+    let session_key = agent_session_key(headers, agent_id.as_deref(), req.user.as_deref());
+    let run = match start_agent_run(
+        ws,
+        provider,
+        &response_id,
+        agent_id,
+        session_key,
+        req.user.as_deref(),
+        &req.messages,
+    ) {
+        Ok(run) => run,
+        Err(err) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(OpenAiError::api_error(err)),
+            )
+                .into_response();
+        }
+    };
+
+    if req.stream {
+        return stream_agent_run(run, req.model, response_id, created);
+    }
+
+    match run.wait().await {
+        Ok(output) => {
+            let response = ChatCompletionResponse {
+                id: response_id,
+                object: "chat.completion".to_string(),
+                created,
+                model: req.model,
+                choices: vec![ChatChoice {
+                    index: 0,
+                    message: ChatMessage {
+                        role: "assistant".to_string(),
+                        content: ChatContent::Text(output.text),
+                        name: None,
+                        tool_calls: None,
+                        tool_call_id: None,
+                    },
+                    finish_reason: output.finish_reason.to_string(),
+                }],
+                usage: ChatUsage {
+                    prompt_tokens: output.usage.input_tokens,
+                    completion_tokens: output.usage.output_tokens,
+                    total_tokens: output.usage.input_tokens + output.usage.output_tokens,
+                },
+            };
+            (StatusCode::OK, Json(response)).into_response()
+        }
+        Err(err) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(OpenAiError::api_error(err)),
+        )
+            .into_response(),
+    }
+}
+
 /// POST /v1/chat/completions handler
 pub async fn chat_completions_handler(
     State(state): State<OpenAiState>,
@@ -504,30 +1184,28 @@ pub async fn chat_completions_handler(
             .into_response();
     }
 
-    // Parse agent ID from model or headers
-    let _agent_id = headers
-        .get("x-carapace-agent-id")
-        .or_else(|| headers.get("x-carapace-agent"))
-        .and_then(|v| v.to_str().ok())
-        .map(|s| s.to_string())
-        .or_else(|| parse_agent_id(&req.model));
+    // Validate tool_choice
+    if let Some(err) = validate_tool_choice(req.tool_choice.as_ref(), req.tools.as_deref()) {
+        return err;
+    }
 
     // Generate response ID and timestamp
     let response_id = format!("chatcmpl_{}", Uuid::new_v4().simple());
     let created = chrono::Utc::now().timestamp();
 
+    // Agent models run through the agent loop when the gateway state is wired
+    if let (Some(ws), Some(agent_id)) = (
+        state.ws_state.as_ref(),
+        resolve_agent_target(&headers, &req.model),
+    ) {
+        return agent_chat_completion(&state, ws, &headers, agent_id, req, response_id, created)
+            .await;
+    }
+
     // Check if we have an LLM provider
     let provider = match &state.llm_provider {
         Some(p) => p.clone(),
-        None => {
-            return (
-                StatusCode::SERVICE_UNAVAILABLE,
-                Json(OpenAiError::api_error(
-                    "No LLM provider configured. Set ANTHROPIC_API_KEY to enable.",
-                )),
-            )
-                .into_response();
-        }
+        None => return no_provider_response(),
     };
 
     // Convert OpenAI messages to LLM provider format
@@ -553,15 +1231,27 @@ pub async fn chat_completions_handler(
         req.model.clone()
     };
 
+    // Client tools are passed through; the client executes the calls
+    let tools = select_tools(req.tools.as_deref(), req.tool_choice.as_ref());
+
     if req.stream {
         // Streaming response via the LLM provider
-        return stream_llm_provider(provider, model, system, llm_messages, response_id, created)
-            .await;
+        return stream_llm_provider(
+            provider,
+            model,
+            system,
+            llm_messages,
+            tools,
+            response_id,
+            created,
+        )
+        .await;
     }
 
     // Non-streaming response: call the LLM provider and collect the result
-    match call_llm_provider(&*provider, &model, system, llm_messages).await {
-        Ok((text, usage)) => {
+    match call_llm_provider(&*provider, &model, system, llm_messages, tools).await {
+        Ok(completion) => {
+            let usage = completion.usage;
             let response = ChatCompletionResponse {
                 id: response_id,
                 object: "chat.completion".to_string(),
@@ -571,10 +1261,13 @@ pub async fn chat_completions_handler(
                     index: 0,
                     message: ChatMessage {
                         role: "assistant".to_string(),
-                        content: ChatContent::Text(text),
+                        content: ChatContent::Text(completion.text),
                         name: None,
+                        tool_calls: (!completion.tool_calls.is_empty())
+                            .then_some(completion.tool_calls),
+                        tool_call_id: None,
                     },
-                    finish_reason: "stop".to_string(),
+                    finish_reason: completion.finish_reason.to_string(),
                 }],
                 usage: ChatUsage {
                     prompt_tokens: usage.input_tokens,
@@ -678,16 +1371,59 @@ pub enum ResponsesInputItem {
     #[serde(rename = "message")]
     Message { role: String, content: ChatContent },
     #[serde(rename = "function_call")]
-    FunctionCall { name: String, arguments: String },
+    FunctionCall {
+        #[serde(default)]
+        call_id: String,
+        name: String,
+        #[serde(default)]
+        arguments: String,
+    },
     #[serde(rename = "function_call_output")]
     FunctionCallOutput { call_id: String, output: String },
 }
 
-/// Tool definition for OpenResponses
+/// Tool definition for OpenResponses and chat completions.
+///
+/// Accepts both the nested chat form (`{"type":"function","function":{..}}`)
+/// and the flat OpenResponses form (`{"type":"function","name":..}`).
 #[derive(Debug, Deserialize)]
 pub struct ResponsesTool {
     pub r#type: String,
     pub function: Option<ResponsesFunction>,
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub parameters: Option<Value>,
+}
+
+impl ResponsesTool {
+    /// The function name, from either form.
+    fn function_name(&self) -> Option<&str> {
+        self.function
+            .as_ref()
+            .map(|f| f.name.as_str())
+            .or(self.name.as_deref())
+    }
+
+    /// Map a function tool to a provider tool definition.
+    fn to_definition(&self) -> Option<ToolDefinition> {
+        if self.r#type != "function" {
+            return None;
+        }
+        let (name, description, parameters) = match &self.function {
+            Some(f) => (f.name.clone(), f.description.clone(), f.parameters.clone()),
+            None => (
+                self.name.clone()?,
+                self.description.clone(),
+                self.parameters.clone(),
+            ),
+        };
+        Some(ToolDefinition {
+            name,
+            description: description.unwrap_or_default(),
+            input_schema: parameters
+                .unwrap_or_else(|| serde_json::json!({ "type": "object", "properties": {} })),
+        })
+    }
 }
 
 /// Function definition in tool
@@ -768,8 +1504,9 @@ fn has_user_message(input: &ResponsesInput) -> bool {
 /// `convert_to_llm_messages`.
 ///
 /// - `ResponsesInput::Text(s)` becomes a single user message.
-/// - `ResponsesInput::Items` maps Message items to ChatMessages (function call
-///   items are ignored for now).
+/// - `ResponsesInput::Items` maps Message items to ChatMessages,
+///   `function_call` items to assistant `tool_calls` and
+///   `function_call_output` items to `tool` messages.
 /// - If `instructions` is provided it is prepended as a system message.
 fn responses_input_to_chat_messages(
     input: &ResponsesInput,
@@ -782,6 +1519,8 @@ fn responses_input_to_chat_messages(
             role: "system".to_string(),
             content: ChatContent::Text(instr.to_string()),
             name: None,
+            tool_calls: None,
+            tool_call_id: None,
         });
     }
 
@@ -791,6 +1530,8 @@ fn responses_input_to_chat_messages(
                 role: "user".to_string(),
                 content: ChatContent::Text(text.clone()),
                 name: None,
+                tool_calls: None,
+                tool_call_id: None,
             });
         }
         ResponsesInput::Items(items) => {
@@ -801,11 +1542,46 @@ fn responses_input_to_chat_messages(
                             role: role.clone(),
                             content: content.clone(),
                             name: None,
+                            tool_calls: None,
+                            tool_call_id: None,
+                        });
+                    }
+                    ResponsesInputItem::FunctionCall {
+                        call_id,
+                        name,
+                        arguments,
+                    } => {
+                        let call = ToolCall {
+                            id: call_id.clone(),
+                            r#type: default_tool_type(),
+                            function: ToolCallFunction {
+                                name: name.clone(),
+                                arguments: arguments.clone(),
+                            },
+                        };
+                        // Calls following an assistant message belong to its turn
+                        match msgs.last_mut() {
+                            Some(last) if last.role == "assistant" => {
+                                last.tool_calls.get_or_insert_with(Vec::new).push(call)
+                            }
+                            _ => msgs.push(ChatMessage {
+                                role: "assistant".to_string(),
+                                content: ChatContent::default(),
+                                name: None,
+                                tool_calls: Some(vec![call]),
+                                tool_call_id: None,
+                            }),
+                        }
+                    }
+                    ResponsesInputItem::FunctionCallOutput { call_id, output } => {
+                        msgs.push(ChatMessage {
+                            role: "tool".to_string(),
+                            content: ChatContent::Text(output.clone()),
+                            name: None,
+                            tool_calls: None,
+                            tool_call_id: Some(call_id.clone()),
                         });
                     }
-                    // Function call items are not converted to LLM messages
-                    ResponsesInputItem::FunctionCall { .. }
-                    | ResponsesInputItem::FunctionCallOutput { .. } => {}
                 }
             }
         }
@@ -856,27 +1632,27 @@ pub async fn responses_handler(
     }
 
     // Validate tool_choice
-    if let Some(err) = validate_tool_choice(&req) {
+    if let Some(err) = validate_tool_choice(req.tool_choice.as_ref(), req.tools.as_deref()) {
         return err;
     }
 
     // Generate response IDs and timestamp
     let response_id = format!("resp_{}", Uuid::new_v4().simple());
-    let msg_id = format!("msg_{}", Uuid::new_v4().simple());
     let created_at = chrono::Utc::now().timestamp();
 
+    // Agent models run through the agent loop when the gateway state is wired
+    if let (Some(ws), Some(agent_id)) = (
+        state.ws_state.as_ref(),
+        resolve_agent_target(&headers, &req.model),
+    ) {
+        return agent_response(&state, ws, &headers, agent_id, req, response_id, created_at).await;
+    }
+    let msg_id = format!("msg_{}", Uuid::new_v4().simple());
+
     // Require an LLM provider
     let provider = match &state.llm_provider {
         Some(p) => p.clone(),
-        None => {
-            return (
-                StatusCode::SERVICE_UNAVAILABLE,
-                Json(OpenAiError::api_error(
-                    "No LLM provider configured. Set ANTHROPIC_API_KEY to enable.",
-                )),
-            )
-                .into_response();
-        }
+        None => return no_provider_response(),
     };
 
     // Convert OpenResponses input to ChatMessages, then to LLM messages
@@ -903,28 +1679,119 @@ pub async fn responses_handler(
         req.model.clone()
     };
 
+    // Client tools are passed through; the client executes the calls
+    let tools = select_tools(req.tools.as_deref(), req.tool_choice.as_ref());
+
     // Non-streaming: call the LLM provider and collect the result
-    match call_llm_provider(&*provider, &model, system, llm_messages).await {
-        Ok((text, usage)) => {
-            let response = ResponsesResponse {
+    match call_llm_provider(&*provider, &model, system, llm_messages, tools).await {
+        Ok(completion) => {
+            let usage = completion.usage;
+            let mut output = Vec::new();
+            if !completion.text.is_empty() || completion.tool_calls.is_empty() {
+                output.push(ResponsesOutputItem::Message {
+                    id: msg_id,
+                    role: "assistant".to_string(),
+                    content: vec![OutputContent {
+                        r#type: "output_text".to_string(),
+                        text: completion.text,
+                    }],
+                    status: "completed".to_string(),
+                });
+            }
+            output.extend(completion.tool_calls.into_iter().map(|call| {
+                ResponsesOutputItem::FunctionCall {
+                    id: format!("fc_{}", Uuid::new_v4().simple()),
+                    call_id: call.id,
+                    name: call.function.name,
+                    arguments: call.function.arguments,
+                }
+            }));
+            let response = ResponsesResponse {
                 id: response_id,
                 object: "response".to_string(),
                 created_at,
                 status: "completed".to_string(),
                 model,
+                output,
+                usage: ResponsesUsage {
+                    input_tokens: usage.input_tokens,
+                    output_tokens: usage.output_tokens,
+                    total_tokens: usage.input_tokens + usage.output_tokens,
+                },
+                error: None,
+            };
+            (StatusCode::OK, Json(response)).into_response()
+        }
+        Err(err) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(OpenAiError::api_error(err)),
+        )
+            .into_response(),
+    }
+}
+
+/// Serve an OpenResponses request from an agent run.
+async fn agent_response(
+    state: &OpenAiState,
+    ws: &Arc<WsServerState>,
+    headers: &HeaderMap,
+    agent_id: Option<String>,
+    req: ResponsesRequest,
+    response_id: String,
+    created_at: i64,
+) -> Response {
+    let Some(provider) = ws.llm_provider().or_else(|| state.llm_provider.clone()) else {
+        return no_provider_response();
+    };
+    if req.tools.as_ref().is_some_and(|t| !t.is_empty()) {
+        return agent_tools_response();
+    }
+    let chat_messages = responses_input_to_chat_messages(&req.input, req.instructions.as_deref());
+    let session_key = agent_session_key(headers, agent_id.as_deref(), req.user.as_deref());
+    let run = match start_agent_run(
+        ws,
+        provider,
+        &response_id,
+        agent_id,
+        session_key,
+        req.user.as_deref(),
+        &chat_messages,
+    ) {
+        Ok(run) => run,
+        Err(err) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(OpenAiError::api_error(err)),
+            )
+                .into_response();
+        }
+    };
+
+    match run.wait().await {
+        Ok(output) => {
+            let response = ResponsesResponse {
+                id: response_id,
+                object: "response".to_string(),
+                created_at,
+                status: if output.finish_reason == "length" {
+                    "incomplete".to_string()
+                } else {
+                    "completed".to_string()
+                },
+                model: req.model,
                 output: vec![ResponsesOutputItem::Message {
-                    id: msg_id,
+                    id: format!("msg_{}", Uuid::new_v4().simple()),
                     role: "assistant".to_string(),
                     content: vec![OutputContent {
                         r#type: "output_text".to_string(),
-                        text,
+                        text: output.text,
                     }],
                     status: "completed".to_string(),
                 }],
                 usage: ResponsesUsage {
-                    input_tokens: usage.input_tokens,
-                    output_tokens: usage.output_tokens,
-                    total_tokens: usage.input_tokens + usage.output_tokens,
+                    input_tokens: output.usage.input_tokens,
+                    output_tokens: output.usage.output_tokens,
+                    total_tokens: output.usage.input_tokens + output.usage.output_tokens,
                 },
                 error: None,
             };
@@ -940,311 +1807,869 @@ pub async fn responses_handler(
 
 /// Validate tool_choice against the provided tools.
 /// Returns `Some(Response)` on validation failure, `None` if valid.
-fn validate_tool_choice(req: &ResponsesRequest) -> Option<Response> {
-    let tool_choice = req.tool_choice.as_ref()?;
+fn validate_tool_choice(
+    tool_choice: Option<&Value>,
+    tools: Option<&[ResponsesTool]>,
+) -> Option<Response> {
+    let tool_choice = tool_choice?;
+    let tools = tools.unwrap_or_default();
+
+    if tool_choice.as_str() == Some("required") && tools.is_empty() {
+        return Some(
+            (
+                StatusCode::BAD_REQUEST,
+                Json(OpenAiError::invalid_request(
+                    "tool_choice=required but no tools were provided",
+                )),
+            )
+                .into_response(),
+        );
+    }
 
-    if let Some(s) = tool_choice.as_str() {
-        if s == "required" && req.tools.as_ref().map(|t| t.is_empty()).unwrap_or(true) {
+    if let Some(name) = tool_choice_function(tool_choice) {
+        if !tools.iter().any(|t| t.function_name() == Some(name)) {
             return Some(
                 (
                     StatusCode::BAD_REQUEST,
-                    Json(OpenAiError::invalid_request(
-                        "tool_choice=required but no tools were provided",
-                    )),
+                    Json(OpenAiError::invalid_request(format!(
+                        "tool_choice requested unknown tool: {}",
+                        name
+                    ))),
                 )
                     .into_response(),
             );
         }
     }
 
-    if let Some(obj) = tool_choice.as_object() {
-        if obj.get("type").and_then(|v| v.as_str()) == Some("function") {
-            if let Some(func) = obj.get("function") {
-                if let Some(name) = func.get("name").and_then(|v| v.as_str()) {
-                    let has_tool = req
-                        .tools
-                        .as_ref()
-                        .map(|tools| {
-                            tools.iter().any(|t| {
-                                t.function.as_ref().map(|f| f.name == name).unwrap_or(false)
-                            })
-                        })
-                        .unwrap_or(false);
-                    if !has_tool {
-                        return Some(
-                            (
-                                StatusCode::BAD_REQUEST,
-                                Json(OpenAiError::invalid_request(format!(
-                                    "tool_choice requested unknown tool: {}",
-                                    name
-                                ))),
-                            )
-                                .into_response(),
-                        );
-                    }
-                }
-            }
-        }
-    }
-
     None
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    use crate::server::connect_info::MaybeConnectInfo;
+// ============================================================================
+// Models
+// ============================================================================
 
-    fn loopback_connect_info() -> MaybeConnectInfo {
-        MaybeConnectInfo(Some("127.0.0.1:1234".parse().unwrap()))
-    }
+/// Model list entry
+#[derive(Debug, Serialize)]
+pub struct ModelObject {
+    pub id: String,
+    pub object: &'static str,
+    pub created: i64,
+    pub owned_by: String,
+}
 
-    #[test]
-    fn test_parse_agent_id() {
-        assert_eq!(parse_agent_id("carapace"), None);
-        assert_eq!(
-            parse_agent_id("carapace:email-agent"),
-            Some("email-agent".to_string())
-        );
-        assert_eq!(parse_agent_id("agent:main"), Some("main".to_string()));
-        assert_eq!(parse_agent_id("gpt-4"), None);
-    }
+/// Model list response
+#[derive(Debug, Serialize)]
+pub struct ModelList {
+    pub object: &'static str,
+    pub data: Vec<ModelObject>,
+}
 
-    #[test]
-    fn test_chat_content_to_text() {
-        let text = ChatContent::Text("Hello world".to_string());
-        assert_eq!(text.to_text(), "Hello world");
+/// Build the `/v1/models` list: `carapace`, one `carapace:<agent>` entry per
+/// enabled agent, then the `models.list` catalog (which needs gateway state
+/// for plugin models).
+fn build_model_list(ws: Option<&WsServerState>) -> ModelList {
+    let mut data: Vec<ModelObject> = Vec::new();
+    let mut push = |id: String, owned_by: &str| {
+        if !data.iter().any(|m| m.id == id) {
+            data.push(ModelObject {
+                id,
+                object: "model",
+                created: 0,
+                owned_by: owned_by.to_string(),
+            });
+        }
+    };
 
-        let parts = ChatContent::Parts(vec![
-            ContentPart::Text {
-                text: "Hello".to_string(),
-            },
-            ContentPart::Text {
-                text: "World".to_string(),
-            },
-        ]);
-        assert_eq!(parts.to_text(), "Hello\nWorld");
+    push("carapace".to_string(), "carapace");
+    let agents = crate::server::ws::agent_catalog();
+    for agent in agents
+        .get("agents")
+        .and_then(|v| v.as_array())
+        .into_iter()
+        .flatten()
+    {
+        let enabled = agent
+            .get("enabled")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(true);
+        if let (true, Some(id)) = (enabled, agent.get("id").and_then(|v| v.as_str())) {
+            push(format!("carapace:{id}"), "carapace");
+        }
     }
 
-    #[test]
-    fn test_extract_user_message() {
-        let messages = vec![
-            ChatMessage {
-                role: "system".to_string(),
-                content: ChatContent::Text("You are helpful".to_string()),
-                name: None,
-            },
-            ChatMessage {
-                role: "user".to_string(),
-                content: ChatContent::Text("Hello".to_string()),
-                name: None,
-            },
-        ];
-
-        let user_msg = extract_user_message(&messages);
-        assert_eq!(user_msg, Some("Hello".to_string()));
+    if let Some(ws) = ws {
+        for model in crate::server::ws::model_catalog(ws) {
+            let Some(id) = model.get("id").and_then(|v| v.as_str()) else {
+                continue;
+            };
+            let owned_by = model
+                .get("provider")
+                .and_then(|v| v.as_str())
+                .unwrap_or("carapace");
+            push(id.to_string(), owned_by);
+        }
+    }
 
-        let no_user = vec![ChatMessage {
-            role: "system".to_string(),
-            content: ChatContent::Text("System".to_string()),
-            name: None,
-        }];
-        assert_eq!(extract_user_message(&no_user), None);
+    ModelList {
+        object: "list",
+        data,
     }
+}
 
-    #[test]
-    fn test_openai_error_serialization() {
-        let error = OpenAiError::invalid_request("Bad request");
-        let json = serde_json::to_string(&error).unwrap();
-        assert!(json.contains("invalid_request_error"));
-        assert!(json.contains("Bad request"));
+/// GET /v1/models handler
+pub async fn models_handler(
+    State(state): State<OpenAiState>,
+    connect_info: MaybeConnectInfo,
+    headers: HeaderMap,
+) -> Response {
+    if !state.models_enabled() {
+        return (StatusCode::NOT_FOUND, "Not Found").into_response();
+    }
+    if let Some(err) = check_openai_auth(&state, &headers, connect_info.0) {
+        return err;
     }
+    (
+        StatusCode::OK,
+        Json(build_model_list(state.ws_state.as_deref())),
+    )
+        .into_response()
+}
 
-    #[test]
-    fn test_chat_completion_response_serialization() {
-        let response = ChatCompletionResponse {
-            id: "chatcmpl_test".to_string(),
-            object: "chat.completion".to_string(),
-            created: 1700000000,
-            model: "carapace".to_string(),
-            choices: vec![ChatChoice {
-                index: 0,
-                message: ChatMessage {
-                    role: "assistant".to_string(),
-                    content: ChatContent::Text("Hello".to_string()),
-                    name: None,
-                },
-                finish_reason: "stop".to_string(),
-            }],
-            usage: ChatUsage {
-                prompt_tokens: 10,
-                completion_tokens: 5,
-                total_tokens: 15,
-            },
-        };
+// ============================================================================
+// Embeddings
+// ============================================================================
 
-        let json = serde_json::to_string(&response).unwrap();
-        assert!(json.contains("chat.completion"));
-        assert!(json.contains("chatcmpl_test"));
-    }
+/// Embeddings request
+#[derive(Debug, Deserialize)]
+pub struct EmbeddingsRequest {
+    pub model: String,
+    pub input: EmbeddingsInput,
+    #[serde(default)]
+    pub dimensions: Option<u32>,
+    /// `float` (default) or `base64`
+    #[serde(default)]
+    pub encoding_format: Option<String>,
+    #[serde(default)]
+    pub user: Option<String>,
+}
 
-    #[test]
-    fn test_chat_usage_large_token_values_not_truncated() {
-        // Values above i32::MAX (2^31 - 1 = 2_147_483_647) must not be truncated
-        let large: u64 = 5_000_000_000;
-        let usage = ChatUsage {
-            prompt_tokens: large,
-            completion_tokens: large,
-            total_tokens: large * 2,
-        };
-        let json = serde_json::to_string(&usage).unwrap();
-        let parsed: serde_json::Value = serde_json::from_str(&json).unwrap();
-        assert_eq!(parsed["prompt_tokens"].as_u64().unwrap(), large);
-        assert_eq!(parsed["completion_tokens"].as_u64().unwrap(), large);
-        assert_eq!(parsed["total_tokens"].as_u64().unwrap(), large * 2);
+/// Embeddings input (a string or an array of strings)
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum EmbeddingsInput {
+    Text(String),
+    Batch(Vec<String>),
+}
+
+impl EmbeddingsInput {
+    fn into_vec(self) -> Vec<String> {
+        match self {
+            EmbeddingsInput::Text(text) => vec![text],
+            EmbeddingsInput::Batch(batch) => batch,
+        }
     }
+}
 
-    #[test]
-    fn test_responses_usage_large_token_values_not_truncated() {
-        let large: u64 = 5_000_000_000;
-        let usage = ResponsesUsage {
-            input_tokens: large,
-            output_tokens: large,
-            total_tokens: large * 2,
-        };
-        let json = serde_json::to_string(&usage).unwrap();
-        let parsed: serde_json::Value = serde_json::from_str(&json).unwrap();
-        assert_eq!(parsed["input_tokens"].as_u64().unwrap(), large);
-        assert_eq!(parsed["output_tokens"].as_u64().unwrap(), large);
-        assert_eq!(parsed["total_tokens"].as_u64().unwrap(), large * 2);
+/// Provider name recorded in usage tracking for an embeddings model.
+/// Plugin-routed models (`<plugin>:<model>`) are attributed to the plugin.
+fn embedding_usage_provider(model: &str) -> String {
+    match crate::agent::provider::embedding_provider_name(model) {
+        "openai" => match model.split_once(':') {
+            Some((prefix, _)) => prefix.to_lowercase(),
+            None => "openai".to_string(),
+        },
+        name => name.to_string(),
     }
+}
 
-    #[test]
-    fn test_has_user_message() {
-        assert!(has_user_message(&ResponsesInput::Text("hello".to_string())));
+/// Encode a vector as base64 little-endian f32s, as OpenAI does for
+/// `encoding_format: "base64"`.
+fn encode_embedding_base64(embedding: &[f32]) -> String {
+    use base64::Engine as _;
 
-        let items_with_user = ResponsesInput::Items(vec![ResponsesInputItem::Message {
-            role: "user".to_string(),
-            content: ChatContent::Text("hello".to_string()),
-        }]);
-        assert!(has_user_message(&items_with_user));
+    let bytes: Vec<u8> = embedding.iter().flat_map(|f| f.to_le_bytes()).collect();
+    base64::engine::general_purpose::STANDARD.encode(bytes)
+}
 
-        let items_no_user = ResponsesInput::Items(vec![ResponsesInputItem::Message {
-            role: "system".to_string(),
-            content: ChatContent::Text("system".to_string()),
-        }]);
-        assert!(!has_user_message(&items_no_user));
+/// POST /v1/embeddings handler
+pub async fn embeddings_handler(
+    State(state): State<OpenAiState>,
+    connect_info: MaybeConnectInfo,
+    headers: HeaderMap,
+    body: axum::body::Bytes,
+) -> Response {
+    if !state.embeddings_enabled {
+        return (StatusCode::NOT_FOUND, "Not Found").into_response();
+    }
+    if let Some(err) = check_openai_auth(&state, &headers, connect_info.0) {
+        return err;
     }
 
-    // ============== convert_to_llm_messages Tests ==============
+    let req: EmbeddingsRequest = match serde_json::from_slice(&body) {
+        Ok(r) => r,
+        Err(e) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(OpenAiError::invalid_request(format!("Invalid JSON: {}", e))),
+            )
+                .into_response();
+        }
+    };
 
-    #[test]
-    fn test_convert_to_llm_messages_basic() {
-        let messages = vec![ChatMessage {
-            role: "user".to_string(),
-            content: ChatContent::Text("Hello".to_string()),
-            name: None,
-        }];
-
-        let (system, llm_msgs) = convert_to_llm_messages(&messages);
-        assert!(system.is_none());
-        assert_eq!(llm_msgs.len(), 1);
-        assert_eq!(llm_msgs[0].role, LlmRole::User);
-        match &llm_msgs[0].content[0] {
-            ContentBlock::Text { text } => assert_eq!(text, "Hello"),
-            _ => panic!("expected Text block"),
+    let base64 = match req.encoding_format.as_deref() {
+        None | Some("float") => false,
+        Some("base64") => true,
+        Some(other) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(OpenAiError::invalid_request(format!(
+                    "Unsupported encoding_format: {other}"
+                ))),
+            )
+                .into_response();
         }
+    };
+
+    let input = req.input.into_vec();
+    if input.is_empty() || input.iter().any(|text| text.is_empty()) {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(OpenAiError::invalid_request(
+                "`input` must be a non-empty string or array of non-empty strings.",
+            )),
+        )
+            .into_response();
     }
 
-    #[test]
-    fn test_convert_to_llm_messages_with_system() {
-        let messages = vec![
-            ChatMessage {
-                role: "system".to_string(),
-                content: ChatContent::Text("You are a helpful assistant".to_string()),
-                name: None,
-            },
-            ChatMessage {
-                role: "user".to_string(),
-                content: ChatContent::Text("Hi".to_string()),
-                name: None,
+    let provider = match &state.llm_provider {
+        Some(p) => p.clone(),
+        None => return no_provider_response(),
+    };
+
+    let request = crate::agent::provider::EmbeddingRequest {
+        model: req.model.clone(),
+        input,
+        dimensions: req.dimensions,
+    };
+    let result = match provider.embed(request).await {
+        Ok(result) => result,
+        Err(e) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(OpenAiError::api_error(e.to_string())),
+            )
+                .into_response();
+        }
+    };
+
+    crate::usage::record_usage(
+        &embedding_usage_provider(&req.model),
+        &req.model,
+        None,
+        result.input_tokens,
+        0,
+    );
+
+    let data = result
+        .embeddings
+        .iter()
+        .enumerate()
+        .map(|(index, embedding)| {
+            let embedding = if base64 {
+                Value::String(encode_embedding_base64(embedding))
+            } else {
+                serde_json::json!(embedding)
+            };
+            serde_json::json!({
+                "object": "embedding",
+                "index": index,
+                "embedding": embedding,
+            })
+        })
+        .collect::<Vec<_>>();
+
+    (
+        StatusCode::OK,
+        Json(serde_json::json!({
+            "object": "list",
+            "data": data,
+            "model": req.model,
+            "usage": {
+                "prompt_tokens": result.input_tokens,
+                "total_tokens": result.input_tokens,
             },
-        ];
+        })),
+    )
+        .into_response()
+}
 
-        let (system, llm_msgs) = convert_to_llm_messages(&messages);
-        assert_eq!(system.unwrap(), "You are a helpful assistant");
-        assert_eq!(llm_msgs.len(), 1);
-        assert_eq!(llm_msgs[0].role, LlmRole::User);
+// ============================================================================
+// Audio
+// ============================================================================
+
+/// Upload limit for `/v1/audio/transcriptions`, matching OpenAI's 25 MB cap.
+pub const MAX_TRANSCRIPTION_BYTES: usize = 25 * 1024 * 1024;
+
+/// 503 response when no OpenAI API key is available for the audio endpoints.
+fn no_openai_key_response() -> Response {
+    (
+        StatusCode::SERVICE_UNAVAILABLE,
+        Json(OpenAiError::api_error(
+            "OpenAI API key not configured; set models.providers.openai.apiKey in config or OPENAI_API_KEY env var",
+        )),
+    )
+        .into_response()
+}
+
+/// Guess an audio MIME type from an upload's file name, for clients that
+/// send `application/octet-stream` parts.
+fn audio_mime_from_filename(filename: &str) -> Option<&'static str> {
+    let ext = filename.rsplit_once('.')?.1.to_ascii_lowercase();
+    Some(match ext.as_str() {
+        "mp3" | "mpga" | "mpeg" => "audio/mpeg",
+        "wav" => "audio/wav",
+        "m4a" | "mp4" => "audio/mp4",
+        "webm" => "audio/webm",
+        "ogg" | "oga" | "opus" => "audio/ogg",
+        "flac" => "audio/flac",
+        _ => return None,
+    })
+}
+
+/// Parsed `/v1/audio/transcriptions` form
+#[derive(Debug, Default)]
+struct TranscriptionForm {
+    file: Option<(Vec<u8>, String)>,
+    model: Option<String>,
+    response_format: Option<String>,
+}
+
+async fn read_transcription_form(
+    mut multipart: axum::extract::Multipart,
+) -> Result<TranscriptionForm, String> {
+    let mut form = TranscriptionForm::default();
+    while let Some(field) = multipart.next_field().await.map_err(|e| e.to_string())? {
+        match field.name().unwrap_or_default() {
+            "file" => {
+                let declared = field
+                    .content_type()
+                    .filter(|mime| mime.to_ascii_lowercase().starts_with("audio/"))
+                    .map(|mime| mime.to_string());
+                let guessed = field
+                    .file_name()
+                    .and_then(audio_mime_from_filename)
+                    .map(|mime| mime.to_string());
+                let data = field.bytes().await.map_err(|e| e.to_string())?;
+                let mime = declared.or(guessed).ok_or_else(|| {
+                    "Unsupported audio file; send an audio/* part or a known file extension"
+                        .to_string()
+                })?;
+                form.file = Some((data.to_vec(), mime));
+            }
+            "model" => form.model = Some(field.text().await.map_err(|e| e.to_string())?),
+            "response_format" => {
+                form.response_format = Some(field.text().await.map_err(|e| e.to_string())?)
+            }
+            // language, prompt, temperature, ...: not supported, ignored
+            _ => {}
+        }
+    }
+    Ok(form)
+}
+
+/// POST /v1/audio/transcriptions handler
+///
+/// Multipart upload (`file`, optional `model`, `response_format` of `json`
+/// or `text`), transcribed through the Whisper path used for inbound audio.
+pub async fn transcriptions_handler(
+    State(state): State<OpenAiState>,
+    connect_info: MaybeConnectInfo,
+    headers: HeaderMap,
+    multipart: Result<axum::extract::Multipart, axum::extract::multipart::MultipartRejection>,
+) -> Response {
+    if !state.audio_enabled {
+        return (StatusCode::NOT_FOUND, "Not Found").into_response();
+    }
+    if let Some(err) = check_openai_auth(&state, &headers, connect_info.0) {
+        return err;
+    }
+
+    let form = match multipart {
+        Ok(multipart) => read_transcription_form(multipart).await,
+        Err(e) => Err(e.body_text()),
+    };
+    let form = match form {
+        Ok(form) => form,
+        Err(e) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(OpenAiError::invalid_request(format!(
+                    "Invalid multipart body: {e}"
+                ))),
+            )
+                .into_response();
+        }
+    };
+
+    let as_text = match form.response_format.as_deref() {
+        None | Some("json") => false,
+        Some("text") => true,
+        Some(other) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(OpenAiError::invalid_request(format!(
+                    "Unsupported response_format: {other}"
+                ))),
+            )
+                .into_response();
+        }
+    };
+
+    let Some((audio, mime)) = form.file else {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(OpenAiError::invalid_request("Missing `file` field.")),
+        )
+            .into_response();
+    };
+
+    let Some(api_key) = crate::server::ws::resolve_openai_api_key() else {
+        return no_openai_key_response();
+    };
+
+    let mut analyzer = match crate::media::OpenAiMediaAnalyzer::new(api_key) {
+        Ok(analyzer) => analyzer,
+        Err(e) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(OpenAiError::api_error(e.to_string())),
+            )
+                .into_response();
+        }
+    };
+    if let Some(model) = form.model.filter(|m| !m.trim().is_empty()) {
+        analyzer = analyzer.with_transcription_model(model);
+    }
+
+    use crate::media::MediaAnalyzer as _;
+    match analyzer.transcribe_audio(&audio, &mime).await {
+        Ok(analysis) if as_text => (
+            StatusCode::OK,
+            [(header::CONTENT_TYPE, "text/plain; charset=utf-8")],
+            analysis.description,
+        )
+            .into_response(),
+        Ok(analysis) => (
+            StatusCode::OK,
+            Json(serde_json::json!({ "text": analysis.description })),
+        )
+            .into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(OpenAiError::api_error(e.to_string())),
+        )
+            .into_response(),
+    }
+}
+
+/// Speech request
+#[derive(Debug, Deserialize)]
+pub struct SpeechRequest {
+    #[serde(default = "default_speech_model")]
+    pub model: String,
+    pub input: String,
+    #[serde(default = "default_speech_voice")]
+    pub voice: String,
+    #[serde(default)]
+    pub response_format: Option<String>,
+    #[serde(default)]
+    pub speed: Option<f64>,
+}
+
+fn default_speech_model() -> String {
+    "tts-1".to_string()
+}
+
+fn default_speech_voice() -> String {
+    "alloy".to_string()
+}
+
+fn speech_content_type(format: &str) -> &'static str {
+    match format {
+        "opus" => "audio/opus",
+        "aac" => "audio/aac",
+        "flac" => "audio/flac",
+        _ => "audio/mpeg",
+    }
+}
+
+/// POST /v1/audio/speech handler
+///
+/// Synthesizes through the same OpenAI TTS call as `tts.convert` and returns
+/// the raw audio.
+pub async fn speech_handler(
+    State(state): State<OpenAiState>,
+    connect_info: MaybeConnectInfo,
+    headers: HeaderMap,
+    body: axum::body::Bytes,
+) -> Response {
+    if !state.audio_enabled {
+        return (StatusCode::NOT_FOUND, "Not Found").into_response();
+    }
+    if let Some(err) = check_openai_auth(&state, &headers, connect_info.0) {
+        return err;
+    }
+
+    let req: SpeechRequest = match serde_json::from_slice(&body) {
+        Ok(r) => r,
+        Err(e) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(OpenAiError::invalid_request(format!("Invalid JSON: {}", e))),
+            )
+                .into_response();
+        }
+    };
+
+    if req.input.trim().is_empty() {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(OpenAiError::invalid_request("`input` cannot be empty.")),
+        )
+            .into_response();
+    }
+
+    let requested = req.response_format.as_deref().unwrap_or("mp3");
+    let Some(format) = crate::server::ws::OPENAI_AUDIO_FORMATS
+        .iter()
+        .find(|f| f.eq_ignore_ascii_case(requested.trim()))
+        .copied()
+    else {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(OpenAiError::invalid_request(format!(
+                "Unsupported response_format: {requested}; supported: {}",
+                crate::server::ws::OPENAI_AUDIO_FORMATS.join(", ")
+            ))),
+        )
+            .into_response();
+    };
+
+    let Some(api_key) = crate::server::ws::resolve_openai_api_key() else {
+        return no_openai_key_response();
+    };
+
+    match crate::server::ws::synthesize_openai_speech(
+        &api_key,
+        &req.model,
+        &req.input,
+        &req.voice,
+        format,
+        req.speed.unwrap_or(1.0),
+    )
+    .await
+    {
+        Ok(audio) => (
+            StatusCode::OK,
+            [(header::CONTENT_TYPE, speech_content_type(format))],
+            audio,
+        )
+            .into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(OpenAiError::api_error(e)),
+        )
+            .into_response(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::server::connect_info::MaybeConnectInfo;
+
+    fn loopback_connect_info() -> MaybeConnectInfo {
+        MaybeConnectInfo(Some("127.0.0.1:1234".parse().unwrap()))
     }
 
     #[test]
-    fn test_convert_to_llm_messages_with_developer_role() {
-        let messages = vec![
-            ChatMessage {
-                role: "developer".to_string(),
-                content: ChatContent::Text("System instruction".to_string()),
-                name: None,
+    fn test_parse_agent_id() {
+        assert_eq!(parse_agent_id("carapace"), None);
+        assert_eq!(
+            parse_agent_id("carapace:email-agent"),
+            Some("email-agent".to_string())
+        );
+        assert_eq!(parse_agent_id("agent:main"), Some("main".to_string()));
+        assert_eq!(parse_agent_id("gpt-4"), None);
+    }
+
+    #[test]
+    fn test_chat_content_to_text() {
+        let text = ChatContent::Text("Hello world".to_string());
+        assert_eq!(text.to_text(), "Hello world");
+
+        let parts = ChatContent::Parts(vec![
+            ContentPart::Text {
+                text: "Hello".to_string(),
             },
-            ChatMessage {
-                role: "user".to_string(),
-                content: ChatContent::Text("Hello".to_string()),
-                name: None,
+            ContentPart::Text {
+                text: "World".to_string(),
             },
-        ];
-
-        let (system, llm_msgs) = convert_to_llm_messages(&messages);
-        assert_eq!(system.unwrap(), "System instruction");
-        assert_eq!(llm_msgs.len(), 1);
+        ]);
+        assert_eq!(parts.to_text(), "Hello\nWorld");
     }
 
     #[test]
-    fn test_convert_to_llm_messages_multi_system() {
+    fn test_extract_user_message() {
         let messages = vec![
             ChatMessage {
                 role: "system".to_string(),
-                content: ChatContent::Text("Be helpful".to_string()),
-                name: None,
-            },
-            ChatMessage {
-                role: "developer".to_string(),
-                content: ChatContent::Text("Be concise".to_string()),
+                content: ChatContent::Text("You are helpful".to_string()),
                 name: None,
+                tool_calls: None,
+                tool_call_id: None,
             },
             ChatMessage {
                 role: "user".to_string(),
-                content: ChatContent::Text("Hi".to_string()),
+                content: ChatContent::Text("Hello".to_string()),
                 name: None,
+                tool_calls: None,
+                tool_call_id: None,
             },
         ];
 
-        let (system, llm_msgs) = convert_to_llm_messages(&messages);
-        assert_eq!(system.unwrap(), "Be helpful\n\nBe concise");
-        assert_eq!(llm_msgs.len(), 1);
+        let user_msg = extract_user_message(&messages);
+        assert_eq!(user_msg, Some("Hello".to_string()));
+
+        let no_user = vec![ChatMessage {
+            role: "system".to_string(),
+            content: ChatContent::Text("System".to_string()),
+            name: None,
+            tool_calls: None,
+            tool_call_id: None,
+        }];
+        assert_eq!(extract_user_message(&no_user), None);
     }
 
     #[test]
-    fn test_convert_to_llm_messages_conversation() {
-        let messages = vec![
-            ChatMessage {
-                role: "system".to_string(),
-                content: ChatContent::Text("You are a bot".to_string()),
-                name: None,
-            },
+    fn test_openai_error_serialization() {
+        let error = OpenAiError::invalid_request("Bad request");
+        let json = serde_json::to_string(&error).unwrap();
+        assert!(json.contains("invalid_request_error"));
+        assert!(json.contains("Bad request"));
+    }
+
+    #[test]
+    fn test_chat_completion_response_serialization() {
+        let response = ChatCompletionResponse {
+            id: "chatcmpl_test".to_string(),
+            object: "chat.completion".to_string(),
+            created: 1700000000,
+            model: "carapace".to_string(),
+            choices: vec![ChatChoice {
+                index: 0,
+                message: ChatMessage {
+                    role: "assistant".to_string(),
+                    content: ChatContent::Text("Hello".to_string()),
+                    name: None,
+                    tool_calls: None,
+                    tool_call_id: None,
+                },
+                finish_reason: "stop".to_string(),
+            }],
+            usage: ChatUsage {
+                prompt_tokens: 10,
+                completion_tokens: 5,
+                total_tokens: 15,
+            },
+        };
+
+        let json = serde_json::to_string(&response).unwrap();
+        assert!(json.contains("chat.completion"));
+        assert!(json.contains("chatcmpl_test"));
+    }
+
+    #[test]
+    fn test_chat_usage_large_token_values_not_truncated() {
+        // Values above i32::MAX (2^31 - 1 = 2_147_483_647) must not be truncated
+        let large: u64 = 5_000_000_000;
+        let usage = ChatUsage {
+            prompt_tokens: large,
+            completion_tokens: large,
+            total_tokens: large * 2,
+        };
+        let json = serde_json::to_string(&usage).unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed["prompt_tokens"].as_u64().unwrap(), large);
+        assert_eq!(parsed["completion_tokens"].as_u64().unwrap(), large);
+        assert_eq!(parsed["total_tokens"].as_u64().unwrap(), large * 2);
+    }
+
+    #[test]
+    fn test_responses_usage_large_token_values_not_truncated() {
+        let large: u64 = 5_000_000_000;
+        let usage = ResponsesUsage {
+            input_tokens: large,
+            output_tokens: large,
+            total_tokens: large * 2,
+        };
+        let json = serde_json::to_string(&usage).unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed["input_tokens"].as_u64().unwrap(), large);
+        assert_eq!(parsed["output_tokens"].as_u64().unwrap(), large);
+        assert_eq!(parsed["total_tokens"].as_u64().unwrap(), large * 2);
+    }
+
+    #[test]
+    fn test_has_user_message() {
+        assert!(has_user_message(&ResponsesInput::Text("hello".to_string())));
+
+        let items_with_user = ResponsesInput::Items(vec![ResponsesInputItem::Message {
+            role: "user".to_string(),
+            content: ChatContent::Text("hello".to_string()),
+        }]);
+        assert!(has_user_message(&items_with_user));
+
+        let items_no_user = ResponsesInput::Items(vec![ResponsesInputItem::Message {
+            role: "system".to_string(),
+            content: ChatContent::Text("system".to_string()),
+        }]);
+        assert!(!has_user_message(&items_no_user));
+    }
+
+    // ============== convert_to_llm_messages Tests ==============
+
+    #[test]
+    fn test_convert_to_llm_messages_basic() {
+        let messages = vec![ChatMessage {
+            role: "user".to_string(),
+            content: ChatContent::Text("Hello".to_string()),
+            name: None,
+            tool_calls: None,
+            tool_call_id: None,
+        }];
+
+        let (system, llm_msgs) = convert_to_llm_messages(&messages);
+        assert!(system.is_none());
+        assert_eq!(llm_msgs.len(), 1);
+        assert_eq!(llm_msgs[0].role, LlmRole::User);
+        match &llm_msgs[0].content[0] {
+            ContentBlock::Text { text } => assert_eq!(text, "Hello"),
+            _ => panic!("expected Text block"),
+        }
+    }
+
+    #[test]
+    fn test_convert_to_llm_messages_with_system() {
+        let messages = vec![
+            ChatMessage {
+                role: "system".to_string(),
+                content: ChatContent::Text("You are a helpful assistant".to_string()),
+                name: None,
+                tool_calls: None,
+                tool_call_id: None,
+            },
+            ChatMessage {
+                role: "user".to_string(),
+                content: ChatContent::Text("Hi".to_string()),
+                name: None,
+                tool_calls: None,
+                tool_call_id: None,
+            },
+        ];
+
+        let (system, llm_msgs) = convert_to_llm_messages(&messages);
+        assert_eq!(system.unwrap(), "You are a helpful assistant");
+        assert_eq!(llm_msgs.len(), 1);
+        assert_eq!(llm_msgs[0].role, LlmRole::User);
+    }
+
+    #[test]
+    fn test_convert_to_llm_messages_with_developer_role() {
+        let messages = vec![
+            ChatMessage {
+                role: "developer".to_string(),
+                content: ChatContent::Text("System instruction".to_string()),
+                name: None,
+                tool_calls: None,
+                tool_call_id: None,
+            },
             ChatMessage {
                 role: "user".to_string(),
                 content: ChatContent::Text("Hello".to_string()),
                 name: None,
+                tool_calls: None,
+                tool_call_id: None,
+            },
+        ];
+
+        let (system, llm_msgs) = convert_to_llm_messages(&messages);
+        assert_eq!(system.unwrap(), "System instruction");
+        assert_eq!(llm_msgs.len(), 1);
+    }
+
+    #[test]
+    fn test_convert_to_llm_messages_multi_system() {
+        let messages = vec![
+            ChatMessage {
+                role: "system".to_string(),
+                content: ChatContent::Text("Be helpful".to_string()),
+                name: None,
+                tool_calls: None,
+                tool_call_id: None,
+            },
+            ChatMessage {
+                role: "developer".to_string(),
+                content: ChatContent::Text("Be concise".to_string()),
+                name: None,
+                tool_calls: None,
+                tool_call_id: None,
+            },
+            ChatMessage {
+                role: "user".to_string(),
+                content: ChatContent::Text("Hi".to_string()),
+                name: None,
+                tool_calls: None,
+                tool_call_id: None,
+            },
+        ];
+
+        let (system, llm_msgs) = convert_to_llm_messages(&messages);
+        assert_eq!(system.unwrap(), "Be helpful\n\nBe concise");
+        assert_eq!(llm_msgs.len(), 1);
+    }
+
+    #[test]
+    fn test_convert_to_llm_messages_conversation() {
+        let messages = vec![
+            ChatMessage {
+                role: "system".to_string(),
+                content: ChatContent::Text("You are a bot".to_string()),
+                name: None,
+                tool_calls: None,
+                tool_call_id: None,
+            },
+            ChatMessage {
+                role: "user".to_string(),
+                content: ChatContent::Text("Hello".to_string()),
+                name: None,
+                tool_calls: None,
+                tool_call_id: None,
             },
             ChatMessage {
                 role: "assistant".to_string(),
                 content: ChatContent::Text("Hi there!".to_string()),
                 name: None,
+                tool_calls: None,
+                tool_call_id: None,
             },
             ChatMessage {
                 role: "user".to_string(),
                 content: ChatContent::Text("How are you?".to_string()),
                 name: None,
+                tool_calls: None,
+                tool_call_id: None,
             },
         ];
 
@@ -1262,6 +2687,8 @@ mod tests {
             role: "system".to_string(),
             content: ChatContent::Text("You are a bot".to_string()),
             name: None,
+            tool_calls: None,
+            tool_call_id: None,
         }];
 
         let (system, llm_msgs) = convert_to_llm_messages(&messages);
@@ -1278,12 +2705,14 @@ mod tests {
     /// Mock LLM provider for OpenAI endpoint tests.
     struct MockLlmProvider {
         events: parking_lot::Mutex<Vec<StreamEvent>>,
+        requests: parking_lot::Mutex<Vec<CompletionRequest>>,
     }
 
     impl MockLlmProvider {
         fn with_events(events: Vec<StreamEvent>) -> Self {
             Self {
                 events: parking_lot::Mutex::new(events),
+                requests: parking_lot::Mutex::new(Vec::new()),
             }
         }
 
@@ -1313,9 +2742,10 @@ mod tests {
     impl LlmProvider for MockLlmProvider {
         async fn complete(
             &self,
-            _request: CompletionRequest,
+            request: CompletionRequest,
             _cancel_token: tokio_util::sync::CancellationToken,
         ) -> Result<mpsc::Receiver<StreamEvent>, AgentError> {
+            self.requests.lock().push(request);
             let events = {
                 let lock = self.events.lock();
                 lock.clone()
@@ -1328,6 +2758,24 @@ mod tests {
             });
             Ok(rx)
         }
+
+        async fn embed(
+            &self,
+            request: crate::agent::provider::EmbeddingRequest,
+        ) -> Result<crate::agent::provider::EmbeddingResponse, AgentError> {
+            if request.model == "broken-embed" {
+                return Err(AgentError::Provider("API returned 500: boom".to_string()));
+            }
+            Ok(crate::agent::provider::EmbeddingResponse {
+                embeddings: request
+                    .input
+                    .iter()
+                    .enumerate()
+                    .map(|(i, text)| vec![i as f32, text.len() as f32])
+                    .collect(),
+                input_tokens: 4 * request.input.len() as u64,
+            })
+        }
     }
 
     #[tokio::test]
@@ -1340,101 +2788,450 @@ mod tests {
             }],
         }];
 
-        let result = call_llm_provider(&provider, "claude-sonnet-4-20250514", None, messages).await;
+        let result = call_llm_provider(
+            &provider,
+            "claude-sonnet-4-20250514",
+            None,
+            messages,
+            vec![],
+        )
+        .await;
+        assert!(result.is_ok());
+        let completion = result.unwrap();
+        assert_eq!(completion.text, "Hello from LLM!");
+        assert_eq!(completion.finish_reason, "stop");
+        assert!(completion.tool_calls.is_empty());
+        assert_eq!(completion.usage.input_tokens, 50);
+        assert_eq!(completion.usage.output_tokens, 10);
+    }
+
+    #[tokio::test]
+    async fn test_call_llm_provider_error_response() {
+        let provider = MockLlmProvider::error_response("Rate limited");
+        let messages = vec![LlmMessage {
+            role: LlmRole::User,
+            content: vec![ContentBlock::Text {
+                text: "Hi".to_string(),
+            }],
+        }];
+
+        let result = call_llm_provider(
+            &provider,
+            "claude-sonnet-4-20250514",
+            None,
+            messages,
+            vec![],
+        )
+        .await;
+        assert!(result.is_err());
+        assert_eq!(result.unwrap_err(), "Rate limited");
+    }
+
+    #[tokio::test]
+    async fn test_call_llm_provider_multi_delta() {
+        let provider = MockLlmProvider::with_events(vec![
+            StreamEvent::TextDelta {
+                text: "Hello ".to_string(),
+            },
+            StreamEvent::TextDelta {
+                text: "world!".to_string(),
+            },
+            StreamEvent::Stop {
+                reason: StopReason::EndTurn,
+                usage: TokenUsage {
+                    input_tokens: 20,
+                    output_tokens: 5,
+                },
+            },
+        ]);
+        let messages = vec![LlmMessage {
+            role: LlmRole::User,
+            content: vec![ContentBlock::Text {
+                text: "Test".to_string(),
+            }],
+        }];
+
+        let result = call_llm_provider(
+            &provider,
+            "claude-sonnet-4-20250514",
+            None,
+            messages,
+            vec![],
+        )
+        .await;
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap().text, "Hello world!");
+    }
+
+    #[tokio::test]
+    async fn test_call_llm_provider_with_system() {
+        let provider = MockLlmProvider::text_response("I'm helpful!", 30, 5);
+        let messages = vec![LlmMessage {
+            role: LlmRole::User,
+            content: vec![ContentBlock::Text {
+                text: "Hi".to_string(),
+            }],
+        }];
+
+        let result = call_llm_provider(
+            &provider,
+            "claude-sonnet-4-20250514",
+            Some("You are helpful".to_string()),
+            messages,
+            vec![],
+        )
+        .await;
         assert!(result.is_ok());
-        let (text, usage) = result.unwrap();
-        assert_eq!(text, "Hello from LLM!");
-        assert_eq!(usage.input_tokens, 50);
-        assert_eq!(usage.output_tokens, 10);
+        assert_eq!(result.unwrap().text, "I'm helpful!");
+    }
+
+    // ============== Handler integration tests ==============
+
+    #[tokio::test]
+    async fn test_chat_completions_no_provider_returns_503() {
+        let state = OpenAiState {
+            chat_completions_enabled: true,
+            llm_provider: None,
+            gateway_token: Some("test-token".to_string()),
+            ..Default::default()
+        };
+
+        let body = serde_json::to_vec(&serde_json::json!({
+            "messages": [{"role": "user", "content": "Hello"}]
+        }))
+        .unwrap();
+
+        let mut headers = HeaderMap::new();
+        headers.insert("authorization", "Bearer test-token".parse().unwrap());
+
+        let response = chat_completions_handler(
+            State(state),
+            loopback_connect_info(),
+            headers,
+            axum::body::Bytes::from(body),
+        )
+        .await;
+
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+    }
+
+    #[tokio::test]
+    async fn test_chat_completions_non_streaming_with_provider() {
+        let provider = Arc::new(MockLlmProvider::text_response("LLM response", 100, 25));
+        let state = OpenAiState {
+            chat_completions_enabled: true,
+            llm_provider: Some(provider),
+            gateway_token: Some("test-token".to_string()),
+            ..Default::default()
+        };
+
+        let body = serde_json::to_vec(&serde_json::json!({
+            "messages": [
+                {"role": "system", "content": "Be helpful"},
+                {"role": "user", "content": "Hello"}
+            ]
+        }))
+        .unwrap();
+
+        let mut headers = HeaderMap::new();
+        headers.insert("authorization", "Bearer test-token".parse().unwrap());
+
+        let response = chat_completions_handler(
+            State(state),
+            loopback_connect_info(),
+            headers,
+            axum::body::Bytes::from(body),
+        )
+        .await;
+
+        assert_eq!(response.status(), StatusCode::OK);
+
+        // Parse response body
+        let body_bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let parsed: serde_json::Value = serde_json::from_slice(&body_bytes).unwrap();
+
+        assert_eq!(parsed["object"], "chat.completion");
+        assert!(parsed["id"].as_str().unwrap().starts_with("chatcmpl_"));
+        assert_eq!(parsed["choices"][0]["message"]["role"], "assistant");
+        assert_eq!(parsed["choices"][0]["message"]["content"], "LLM response");
+        assert_eq!(parsed["choices"][0]["finish_reason"], "stop");
+        assert_eq!(parsed["usage"]["prompt_tokens"], 100);
+        assert_eq!(parsed["usage"]["completion_tokens"], 25);
+        assert_eq!(parsed["usage"]["total_tokens"], 125);
+    }
+
+    #[tokio::test]
+    async fn test_chat_completions_carapace_model_maps_to_default() {
+        let provider = Arc::new(MockLlmProvider::text_response("ok", 10, 5));
+        let state = OpenAiState {
+            chat_completions_enabled: true,
+            llm_provider: Some(provider),
+            gateway_token: Some("test-token".to_string()),
+            ..Default::default()
+        };
+
+        let body = serde_json::to_vec(&serde_json::json!({
+            "model": "carapace",
+            "messages": [{"role": "user", "content": "test"}]
+        }))
+        .unwrap();
+
+        let mut headers = HeaderMap::new();
+        headers.insert("authorization", "Bearer test-token".parse().unwrap());
+
+        let response = chat_completions_handler(
+            State(state),
+            loopback_connect_info(),
+            headers,
+            axum::body::Bytes::from(body),
+        )
+        .await;
+
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body_bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let parsed: serde_json::Value = serde_json::from_slice(&body_bytes).unwrap();
+
+        // Model should be mapped from "carapace" to the default model
+        assert_eq!(parsed["model"], crate::agent::DEFAULT_MODEL);
+    }
+
+    #[tokio::test]
+    async fn test_chat_completions_provider_error_returns_500() {
+        let provider = Arc::new(MockLlmProvider::error_response("API overloaded"));
+        let state = OpenAiState {
+            chat_completions_enabled: true,
+            llm_provider: Some(provider),
+            gateway_token: Some("test-token".to_string()),
+            ..Default::default()
+        };
+
+        let body = serde_json::to_vec(&serde_json::json!({
+            "messages": [{"role": "user", "content": "Hello"}]
+        }))
+        .unwrap();
+
+        let mut headers = HeaderMap::new();
+        headers.insert("authorization", "Bearer test-token".parse().unwrap());
+
+        let response = chat_completions_handler(
+            State(state),
+            loopback_connect_info(),
+            headers,
+            axum::body::Bytes::from(body),
+        )
+        .await;
+
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+
+        let body_bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let parsed: serde_json::Value = serde_json::from_slice(&body_bytes).unwrap();
+        assert_eq!(parsed["error"]["type"], "api_error");
+        assert!(parsed["error"]["message"]
+            .as_str()
+            .unwrap()
+            .contains("API overloaded"));
+    }
+
+    #[tokio::test]
+    async fn test_chat_completions_streaming_with_provider() {
+        let provider = Arc::new(MockLlmProvider::with_events(vec![
+            StreamEvent::TextDelta {
+                text: "Hello ".to_string(),
+            },
+            StreamEvent::TextDelta {
+                text: "world!".to_string(),
+            },
+            StreamEvent::Stop {
+                reason: StopReason::EndTurn,
+                usage: TokenUsage {
+                    input_tokens: 10,
+                    output_tokens: 5,
+                },
+            },
+        ]));
+        let state = OpenAiState {
+            chat_completions_enabled: true,
+            llm_provider: Some(provider),
+            gateway_token: Some("test-token".to_string()),
+            ..Default::default()
+        };
+
+        let body = serde_json::to_vec(&serde_json::json!({
+            "messages": [{"role": "user", "content": "Hello"}],
+            "stream": true
+        }))
+        .unwrap();
+
+        let mut headers = HeaderMap::new();
+        headers.insert("authorization", "Bearer test-token".parse().unwrap());
+
+        let response = chat_completions_handler(
+            State(state),
+            loopback_connect_info(),
+            headers,
+            axum::body::Bytes::from(body),
+        )
+        .await;
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers().get("content-type").unwrap(),
+            "text/event-stream; charset=utf-8"
+        );
+
+        // Read all stream data
+        let body_bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body_str = String::from_utf8(body_bytes.to_vec()).unwrap();
+
+        // Should have role chunk, text chunks, stop chunk, and [DONE]
+        assert!(body_str.contains("\"role\":\"assistant\""));
+        assert!(body_str.contains("Hello "));
+        assert!(body_str.contains("world!"));
+        assert!(body_str.contains("\"finish_reason\":\"stop\""));
+        assert!(body_str.contains("data: [DONE]"));
+    }
+
+    #[tokio::test]
+    async fn test_chat_completions_only_system_messages_returns_400() {
+        let provider = Arc::new(MockLlmProvider::text_response("should not reach", 0, 0));
+        let state = OpenAiState {
+            chat_completions_enabled: true,
+            llm_provider: Some(provider),
+            gateway_token: Some("test-token".to_string()),
+            ..Default::default()
+        };
+
+        // Only system message, no user message
+        let body = serde_json::to_vec(&serde_json::json!({
+            "messages": [{"role": "system", "content": "Be helpful"}]
+        }))
+        .unwrap();
+
+        let mut headers = HeaderMap::new();
+        headers.insert("authorization", "Bearer test-token".parse().unwrap());
+
+        let response = chat_completions_handler(
+            State(state),
+            loopback_connect_info(),
+            headers,
+            axum::body::Bytes::from(body),
+        )
+        .await;
+
+        // Should fail because there's no user message
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
     }
 
-    #[tokio::test]
-    async fn test_call_llm_provider_error_response() {
-        let provider = MockLlmProvider::error_response("Rate limited");
-        let messages = vec![LlmMessage {
-            role: LlmRole::User,
-            content: vec![ContentBlock::Text {
-                text: "Hi".to_string(),
-            }],
-        }];
+    // ============== responses_input_to_chat_messages Tests ==============
 
-        let result = call_llm_provider(&provider, "claude-sonnet-4-20250514", None, messages).await;
-        assert!(result.is_err());
-        assert_eq!(result.unwrap_err(), "Rate limited");
+    #[test]
+    fn test_responses_input_text_to_chat_messages() {
+        let input = ResponsesInput::Text("Hello there".to_string());
+        let msgs = responses_input_to_chat_messages(&input, None);
+        assert_eq!(msgs.len(), 1);
+        assert_eq!(msgs[0].role, "user");
+        assert_eq!(msgs[0].content.to_text(), "Hello there");
     }
 
-    #[tokio::test]
-    async fn test_call_llm_provider_multi_delta() {
-        let provider = MockLlmProvider::with_events(vec![
-            StreamEvent::TextDelta {
-                text: "Hello ".to_string(),
+    #[test]
+    fn test_responses_input_text_with_instructions() {
+        let input = ResponsesInput::Text("Hello".to_string());
+        let msgs = responses_input_to_chat_messages(&input, Some("Be concise"));
+        assert_eq!(msgs.len(), 2);
+        assert_eq!(msgs[0].role, "system");
+        assert_eq!(msgs[0].content.to_text(), "Be concise");
+        assert_eq!(msgs[1].role, "user");
+        assert_eq!(msgs[1].content.to_text(), "Hello");
+    }
+
+    #[test]
+    fn test_responses_input_items_to_chat_messages() {
+        let input = ResponsesInput::Items(vec![
+            ResponsesInputItem::Message {
+                role: "user".to_string(),
+                content: ChatContent::Text("Hi".to_string()),
             },
-            StreamEvent::TextDelta {
-                text: "world!".to_string(),
+            ResponsesInputItem::Message {
+                role: "assistant".to_string(),
+                content: ChatContent::Text("Hello!".to_string()),
             },
-            StreamEvent::Stop {
-                reason: StopReason::EndTurn,
-                usage: TokenUsage {
-                    input_tokens: 20,
-                    output_tokens: 5,
-                },
+            ResponsesInputItem::Message {
+                role: "user".to_string(),
+                content: ChatContent::Text("How are you?".to_string()),
             },
         ]);
-        let messages = vec![LlmMessage {
-            role: LlmRole::User,
-            content: vec![ContentBlock::Text {
-                text: "Test".to_string(),
-            }],
-        }];
-
-        let result = call_llm_provider(&provider, "claude-sonnet-4-20250514", None, messages).await;
-        assert!(result.is_ok());
-        let (text, _) = result.unwrap();
-        assert_eq!(text, "Hello world!");
+        let msgs = responses_input_to_chat_messages(&input, None);
+        assert_eq!(msgs.len(), 3);
+        assert_eq!(msgs[0].role, "user");
+        assert_eq!(msgs[1].role, "assistant");
+        assert_eq!(msgs[2].role, "user");
     }
 
-    #[tokio::test]
-    async fn test_call_llm_provider_with_system() {
-        let provider = MockLlmProvider::text_response("I'm helpful!", 30, 5);
-        let messages = vec![LlmMessage {
-            role: LlmRole::User,
-            content: vec![ContentBlock::Text {
-                text: "Hi".to_string(),
-            }],
-        }];
-
-        let result = call_llm_provider(
-            &provider,
-            "claude-sonnet-4-20250514",
-            Some("You are helpful".to_string()),
-            messages,
-        )
-        .await;
-        assert!(result.is_ok());
-        let (text, _) = result.unwrap();
-        assert_eq!(text, "I'm helpful!");
+    #[test]
+    fn test_responses_input_items_maps_function_calls() {
+        let input = ResponsesInput::Items(vec![
+            ResponsesInputItem::Message {
+                role: "user".to_string(),
+                content: ChatContent::Text("Hi".to_string()),
+            },
+            ResponsesInputItem::FunctionCall {
+                call_id: "call_1".to_string(),
+                name: "get_weather".to_string(),
+                arguments: "{}".to_string(),
+            },
+            ResponsesInputItem::FunctionCall {
+                call_id: "call_2".to_string(),
+                name: "get_time".to_string(),
+                arguments: "{}".to_string(),
+            },
+            ResponsesInputItem::FunctionCallOutput {
+                call_id: "call_1".to_string(),
+                output: "sunny".to_string(),
+            },
+        ]);
+        let msgs = responses_input_to_chat_messages(&input, None);
+        assert_eq!(msgs.len(), 3);
+        assert_eq!(msgs[0].role, "user");
+        assert_eq!(msgs[1].role, "assistant");
+        let calls = msgs[1].tool_calls.as_ref().unwrap();
+        assert_eq!(calls.len(), 2);
+        assert_eq!(calls[0].id, "call_1");
+        assert_eq!(calls[1].function.name, "get_time");
+        assert_eq!(msgs[2].role, "tool");
+        assert_eq!(msgs[2].tool_call_id.as_deref(), Some("call_1"));
+        assert_eq!(msgs[2].content.to_text(), "sunny");
     }
 
-    // ============== Handler integration tests ==============
+    // ============== OpenResponses handler integration tests ==============
 
     #[tokio::test]
-    async fn test_chat_completions_no_provider_returns_503() {
+    async fn test_responses_no_provider_returns_503() {
         let state = OpenAiState {
-            chat_completions_enabled: true,
+            responses_enabled: true,
             llm_provider: None,
             gateway_token: Some("test-token".to_string()),
             ..Default::default()
         };
 
         let body = serde_json::to_vec(&serde_json::json!({
-            "messages": [{"role": "user", "content": "Hello"}]
+            "model": "carapace",
+            "input": "Hello"
         }))
         .unwrap();
 
         let mut headers = HeaderMap::new();
         headers.insert("authorization", "Bearer test-token".parse().unwrap());
 
-        let response = chat_completions_handler(
+        let response = responses_handler(
             State(state),
             loopback_connect_info(),
             headers,
@@ -1443,30 +3240,38 @@ mod tests {
         .await;
 
         assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+
+        let body_bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let parsed: serde_json::Value = serde_json::from_slice(&body_bytes).unwrap();
+        assert_eq!(parsed["error"]["type"], "api_error");
+        assert!(parsed["error"]["message"]
+            .as_str()
+            .unwrap()
+            .contains("No LLM provider configured"));
     }
 
     #[tokio::test]
-    async fn test_chat_completions_non_streaming_with_provider() {
-        let provider = Arc::new(MockLlmProvider::text_response("LLM response", 100, 25));
+    async fn test_responses_with_provider_text_input() {
+        let provider = Arc::new(MockLlmProvider::text_response("LLM response", 80, 20));
         let state = OpenAiState {
-            chat_completions_enabled: true,
+            responses_enabled: true,
             llm_provider: Some(provider),
             gateway_token: Some("test-token".to_string()),
             ..Default::default()
         };
 
         let body = serde_json::to_vec(&serde_json::json!({
-            "messages": [
-                {"role": "system", "content": "Be helpful"},
-                {"role": "user", "content": "Hello"}
-            ]
+            "model": "carapace",
+            "input": "Hello"
         }))
         .unwrap();
 
         let mut headers = HeaderMap::new();
         headers.insert("authorization", "Bearer test-token".parse().unwrap());
 
-        let response = chat_completions_handler(
+        let response = responses_handler(
             State(state),
             loopback_connect_info(),
             headers,
@@ -1476,42 +3281,46 @@ mod tests {
 
         assert_eq!(response.status(), StatusCode::OK);
 
-        // Parse response body
         let body_bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
             .await
             .unwrap();
         let parsed: serde_json::Value = serde_json::from_slice(&body_bytes).unwrap();
 
-        assert_eq!(parsed["object"], "chat.completion");
-        assert!(parsed["id"].as_str().unwrap().starts_with("chatcmpl_"));
-        assert_eq!(parsed["choices"][0]["message"]["role"], "assistant");
-        assert_eq!(parsed["choices"][0]["message"]["content"], "LLM response");
-        assert_eq!(parsed["choices"][0]["finish_reason"], "stop");
-        assert_eq!(parsed["usage"]["prompt_tokens"], 100);
-        assert_eq!(parsed["usage"]["completion_tokens"], 25);
-        assert_eq!(parsed["usage"]["total_tokens"], 125);
+        assert_eq!(parsed["object"], "response");
+        assert_eq!(parsed["status"], "completed");
+        assert!(parsed["id"].as_str().unwrap().starts_with("resp_"));
+        assert_eq!(parsed["model"], crate::agent::DEFAULT_MODEL);
+        assert_eq!(parsed["output"][0]["type"], "message");
+        assert_eq!(parsed["output"][0]["role"], "assistant");
+        assert_eq!(parsed["output"][0]["content"][0]["type"], "output_text");
+        assert_eq!(parsed["output"][0]["content"][0]["text"], "LLM response");
+        assert_eq!(parsed["usage"]["input_tokens"], 80);
+        assert_eq!(parsed["usage"]["output_tokens"], 20);
+        assert_eq!(parsed["usage"]["total_tokens"], 100);
     }
 
     #[tokio::test]
-    async fn test_chat_completions_carapace_model_maps_to_default() {
-        let provider = Arc::new(MockLlmProvider::text_response("ok", 10, 5));
+    async fn test_responses_with_provider_items_input() {
+        let provider = Arc::new(MockLlmProvider::text_response("Items response", 60, 15));
         let state = OpenAiState {
-            chat_completions_enabled: true,
+            responses_enabled: true,
             llm_provider: Some(provider),
             gateway_token: Some("test-token".to_string()),
             ..Default::default()
         };
 
         let body = serde_json::to_vec(&serde_json::json!({
-            "model": "carapace",
-            "messages": [{"role": "user", "content": "test"}]
+            "model": "custom-model",
+            "input": [
+                {"type": "message", "role": "user", "content": "Hello"}
+            ]
         }))
         .unwrap();
 
         let mut headers = HeaderMap::new();
         headers.insert("authorization", "Bearer test-token".parse().unwrap());
 
-        let response = chat_completions_handler(
+        let response = responses_handler(
             State(state),
             loopback_connect_info(),
             headers,
@@ -1526,29 +3335,32 @@ mod tests {
             .unwrap();
         let parsed: serde_json::Value = serde_json::from_slice(&body_bytes).unwrap();
 
-        // Model should be mapped from "carapace" to the default model
-        assert_eq!(parsed["model"], crate::agent::DEFAULT_MODEL);
+        assert_eq!(parsed["output"][0]["content"][0]["text"], "Items response");
+        // custom-model should be passed through as-is (not remapped)
+        assert_eq!(parsed["model"], "custom-model");
     }
 
     #[tokio::test]
-    async fn test_chat_completions_provider_error_returns_500() {
-        let provider = Arc::new(MockLlmProvider::error_response("API overloaded"));
+    async fn test_responses_with_instructions() {
+        let provider = Arc::new(MockLlmProvider::text_response("With instructions", 90, 30));
         let state = OpenAiState {
-            chat_completions_enabled: true,
+            responses_enabled: true,
             llm_provider: Some(provider),
             gateway_token: Some("test-token".to_string()),
             ..Default::default()
         };
 
         let body = serde_json::to_vec(&serde_json::json!({
-            "messages": [{"role": "user", "content": "Hello"}]
+            "model": "carapace",
+            "input": "Hello",
+            "instructions": "Be very helpful"
         }))
         .unwrap();
 
         let mut headers = HeaderMap::new();
         headers.insert("authorization", "Bearer test-token".parse().unwrap());
 
-        let response = chat_completions_handler(
+        let response = responses_handler(
             State(state),
             loopback_connect_info(),
             headers,
@@ -1556,100 +3368,76 @@ mod tests {
         )
         .await;
 
-        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(response.status(), StatusCode::OK);
 
         let body_bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
             .await
             .unwrap();
         let parsed: serde_json::Value = serde_json::from_slice(&body_bytes).unwrap();
-        assert_eq!(parsed["error"]["type"], "api_error");
-        assert!(parsed["error"]["message"]
-            .as_str()
-            .unwrap()
-            .contains("API overloaded"));
+
+        assert_eq!(
+            parsed["output"][0]["content"][0]["text"],
+            "With instructions"
+        );
     }
 
     #[tokio::test]
-    async fn test_chat_completions_streaming_with_provider() {
-        let provider = Arc::new(MockLlmProvider::with_events(vec![
-            StreamEvent::TextDelta {
-                text: "Hello ".to_string(),
-            },
-            StreamEvent::TextDelta {
-                text: "world!".to_string(),
-            },
-            StreamEvent::Stop {
-                reason: StopReason::EndTurn,
-                usage: TokenUsage {
-                    input_tokens: 10,
-                    output_tokens: 5,
-                },
-            },
-        ]));
+    async fn test_responses_no_user_message_returns_400() {
+        let provider = Arc::new(MockLlmProvider::text_response("should not reach", 0, 0));
         let state = OpenAiState {
-            chat_completions_enabled: true,
+            responses_enabled: true,
             llm_provider: Some(provider),
             gateway_token: Some("test-token".to_string()),
             ..Default::default()
         };
 
         let body = serde_json::to_vec(&serde_json::json!({
-            "messages": [{"role": "user", "content": "Hello"}],
-            "stream": true
+            "model": "carapace",
+            "input": [
+                {"type": "message", "role": "system", "content": "Be helpful"}
+            ]
         }))
         .unwrap();
 
         let mut headers = HeaderMap::new();
         headers.insert("authorization", "Bearer test-token".parse().unwrap());
 
-        let response = chat_completions_handler(
+        let response = responses_handler(
             State(state),
             loopback_connect_info(),
             headers,
             axum::body::Bytes::from(body),
-        )
-        .await;
-
-        assert_eq!(response.status(), StatusCode::OK);
-        assert_eq!(
-            response.headers().get("content-type").unwrap(),
-            "text/event-stream; charset=utf-8"
-        );
-
-        // Read all stream data
-        let body_bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
-            .await
-            .unwrap();
-        let body_str = String::from_utf8(body_bytes.to_vec()).unwrap();
-
-        // Should have role chunk, text chunks, stop chunk, and [DONE]
-        assert!(body_str.contains("\"role\":\"assistant\""));
-        assert!(body_str.contains("Hello "));
-        assert!(body_str.contains("world!"));
-        assert!(body_str.contains("\"finish_reason\":\"stop\""));
-        assert!(body_str.contains("data: [DONE]"));
+        )
+        .await;
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
     }
 
     #[tokio::test]
-    async fn test_chat_completions_only_system_messages_returns_400() {
+    async fn test_responses_only_system_via_instructions_returns_400() {
         let provider = Arc::new(MockLlmProvider::text_response("should not reach", 0, 0));
         let state = OpenAiState {
-            chat_completions_enabled: true,
+            responses_enabled: true,
             llm_provider: Some(provider),
             gateway_token: Some("test-token".to_string()),
             ..Default::default()
         };
 
-        // Only system message, no user message
+        // Items list has only system messages; instructions also provided.
+        // has_user_message check catches this first, returning 400.
         let body = serde_json::to_vec(&serde_json::json!({
-            "messages": [{"role": "system", "content": "Be helpful"}]
+            "model": "carapace",
+            "input": [
+                {"type": "message", "role": "system", "content": "System only"}
+            ],
+            "instructions": "Extra system"
         }))
         .unwrap();
 
         let mut headers = HeaderMap::new();
         headers.insert("authorization", "Bearer test-token".parse().unwrap());
 
-        let response = chat_completions_handler(
+        let response = responses_handler(
             State(state),
             loopback_connect_info(),
             headers,
@@ -1657,83 +3445,15 @@ mod tests {
         )
         .await;
 
-        // Should fail because there's no user message
         assert_eq!(response.status(), StatusCode::BAD_REQUEST);
     }
 
-    // ============== responses_input_to_chat_messages Tests ==============
-
-    #[test]
-    fn test_responses_input_text_to_chat_messages() {
-        let input = ResponsesInput::Text("Hello there".to_string());
-        let msgs = responses_input_to_chat_messages(&input, None);
-        assert_eq!(msgs.len(), 1);
-        assert_eq!(msgs[0].role, "user");
-        assert_eq!(msgs[0].content.to_text(), "Hello there");
-    }
-
-    #[test]
-    fn test_responses_input_text_with_instructions() {
-        let input = ResponsesInput::Text("Hello".to_string());
-        let msgs = responses_input_to_chat_messages(&input, Some("Be concise"));
-        assert_eq!(msgs.len(), 2);
-        assert_eq!(msgs[0].role, "system");
-        assert_eq!(msgs[0].content.to_text(), "Be concise");
-        assert_eq!(msgs[1].role, "user");
-        assert_eq!(msgs[1].content.to_text(), "Hello");
-    }
-
-    #[test]
-    fn test_responses_input_items_to_chat_messages() {
-        let input = ResponsesInput::Items(vec![
-            ResponsesInputItem::Message {
-                role: "user".to_string(),
-                content: ChatContent::Text("Hi".to_string()),
-            },
-            ResponsesInputItem::Message {
-                role: "assistant".to_string(),
-                content: ChatContent::Text("Hello!".to_string()),
-            },
-            ResponsesInputItem::Message {
-                role: "user".to_string(),
-                content: ChatContent::Text("How are you?".to_string()),
-            },
-        ]);
-        let msgs = responses_input_to_chat_messages(&input, None);
-        assert_eq!(msgs.len(), 3);
-        assert_eq!(msgs[0].role, "user");
-        assert_eq!(msgs[1].role, "assistant");
-        assert_eq!(msgs[2].role, "user");
-    }
-
-    #[test]
-    fn test_responses_input_items_skips_function_calls() {
-        let input = ResponsesInput::Items(vec![
-            ResponsesInputItem::Message {
-                role: "user".to_string(),
-                content: ChatContent::Text("Hi".to_string()),
-            },
-            ResponsesInputItem::FunctionCall {
-                name: "get_weather".to_string(),
-                arguments: "{}".to_string(),
-            },
-            ResponsesInputItem::FunctionCallOutput {
-                call_id: "call_1".to_string(),
-                output: "sunny".to_string(),
-            },
-        ]);
-        let msgs = responses_input_to_chat_messages(&input, None);
-        assert_eq!(msgs.len(), 1);
-        assert_eq!(msgs[0].role, "user");
-    }
-
-    // ============== OpenResponses handler integration tests ==============
-
     #[tokio::test]
-    async fn test_responses_no_provider_returns_503() {
+    async fn test_responses_provider_error_returns_500() {
+        let provider = Arc::new(MockLlmProvider::error_response("Service overloaded"));
         let state = OpenAiState {
             responses_enabled: true,
-            llm_provider: None,
+            llm_provider: Some(provider),
             gateway_token: Some("test-token".to_string()),
             ..Default::default()
         };
@@ -1755,7 +3475,7 @@ mod tests {
         )
         .await;
 
-        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
 
         let body_bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
             .await
@@ -1765,15 +3485,13 @@ mod tests {
         assert!(parsed["error"]["message"]
             .as_str()
             .unwrap()
-            .contains("No LLM provider configured"));
+            .contains("Service overloaded"));
     }
 
     #[tokio::test]
-    async fn test_responses_with_provider_text_input() {
-        let provider = Arc::new(MockLlmProvider::text_response("LLM response", 80, 20));
+    async fn test_responses_disabled_returns_404() {
         let state = OpenAiState {
-            responses_enabled: true,
-            llm_provider: Some(provider),
+            responses_enabled: false,
             gateway_token: Some("test-token".to_string()),
             ..Default::default()
         };
@@ -1795,240 +3513,955 @@ mod tests {
         )
         .await;
 
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    // ============== Agent-backed runs ==============
+
+    use crate::server::ws::WsServerConfig;
+
+    fn agent_state(provider: Arc<MockLlmProvider>) -> (OpenAiState, tempfile::TempDir) {
+        let tmp = tempfile::tempdir().unwrap();
+        let store = Arc::new(sessions::SessionStore::with_base_path(
+            tmp.path().join("sessions"),
+        ));
+        let ws = WsServerState::new(WsServerConfig::default())
+            .with_session_store(store)
+            .with_llm_provider(provider);
+        let state = OpenAiState {
+            chat_completions_enabled: true,
+            responses_enabled: true,
+            gateway_token: Some("test-token".to_string()),
+            ws_state: Some(Arc::new(ws)),
+            ..Default::default()
+        };
+        (state, tmp)
+    }
+
+    fn auth_headers() -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert("authorization", "Bearer test-token".parse().unwrap());
+        headers
+    }
+
+    async fn post_chat(state: &OpenAiState, headers: HeaderMap, body: Value) -> Response {
+        chat_completions_handler(
+            State(state.clone()),
+            loopback_connect_info(),
+            headers,
+            axum::body::Bytes::from(serde_json::to_vec(&body).unwrap()),
+        )
+        .await
+    }
+
+    async fn body_json(response: Response) -> Value {
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        serde_json::from_slice(&bytes).unwrap()
+    }
+
+    fn session_history(state: &OpenAiState, key: &str) -> Vec<sessions::ChatMessage> {
+        let store = state.ws_state.as_ref().unwrap().session_store();
+        let session = store.get_session_by_key(key).unwrap();
+        store.get_history(&session.id, None, None).unwrap()
+    }
+
+    #[test]
+    fn test_resolve_agent_target() {
+        let none = HeaderMap::new();
+        assert_eq!(resolve_agent_target(&none, "gpt-4o"), None);
+        assert_eq!(resolve_agent_target(&none, "carapace"), Some(None));
+        assert_eq!(
+            resolve_agent_target(&none, "carapace:email"),
+            Some(Some("email".to_string()))
+        );
+        assert_eq!(
+            resolve_agent_target(&none, "agent:main"),
+            Some(Some("main".to_string()))
+        );
+
+        let mut headers = HeaderMap::new();
+        headers.insert("x-carapace-agent-id", "ops".parse().unwrap());
+        assert_eq!(
+            resolve_agent_target(&headers, "gpt-4o"),
+            Some(Some("ops".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_agent_session_key() {
+        let none = HeaderMap::new();
+        assert_eq!(agent_session_key(&none, Some("main"), None), None);
+        assert_eq!(
+            agent_session_key(&none, Some("main"), Some("alice")),
+            Some("openai:main:alice".to_string())
+        );
+        assert_eq!(
+            agent_session_key(&none, None, Some("alice")),
+            Some("openai:default:alice".to_string())
+        );
+
+        let mut headers = HeaderMap::new();
+        headers.insert(SESSION_KEY_HEADER, "webui-chat-1".parse().unwrap());
+        assert_eq!(
+            agent_session_key(&headers, Some("main"), Some("alice")),
+            Some("openai:main:webui-chat-1".to_string())
+        );
+
+        // Keys outside the agent's namespace are scoped into it
+        headers.insert(SESSION_KEY_HEADER, "agent:ops:main".parse().unwrap());
+        assert_eq!(
+            agent_session_key(&headers, Some("main"), None),
+            Some("openai:main:agent:ops:main".to_string())
+        );
+        headers.insert(SESSION_KEY_HEADER, "openai:ops:bob".parse().unwrap());
+        assert_eq!(
+            agent_session_key(&headers, Some("main"), None),
+            Some("openai:main:openai:ops:bob".to_string())
+        );
+        headers.insert(SESSION_KEY_HEADER, "openai:main:bob".parse().unwrap());
+        assert_eq!(
+            agent_session_key(&headers, Some("main"), None),
+            Some("openai:main:bob".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_chat_completions_agent_session_keyed_by_user() {
+        let provider = Arc::new(MockLlmProvider::text_response("Agent reply", 40, 8));
+        let (state, _tmp) = agent_state(provider.clone());
+
+        for turn in ["Hi", "And again"] {
+            let response = post_chat(
+                &state,
+                auth_headers(),
+                serde_json::json!({
+                    "model": "carapace:main",
+                    "user": "alice",
+                    "messages": [
+                        {"role": "system", "content": "Answer briefly"},
+                        {"role": "user", "content": turn}
+                    ]
+                }),
+            )
+            .await;
+            assert_eq!(response.status(), StatusCode::OK);
+            let parsed = body_json(response).await;
+            assert_eq!(parsed["model"], "carapace:main");
+            assert_eq!(parsed["choices"][0]["message"]["content"], "Agent reply");
+            assert_eq!(parsed["choices"][0]["finish_reason"], "stop");
+            assert_eq!(parsed["usage"]["prompt_tokens"], 40);
+            assert_eq!(parsed["usage"]["completion_tokens"], 8);
+        }
+
+        // Only the latest user message is appended to the persistent session
+        let history = session_history(&state, "openai:main:alice");
+        let contents: Vec<&str> = history.iter().map(|m| m.content.as_str()).collect();
+        assert_eq!(contents, ["Hi", "Agent reply", "And again", "Agent reply"]);
+
+        let requests = provider.requests.lock();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[1].messages.len(), 3);
+        assert!(requests[1]
+            .system
+            .as_deref()
+            .unwrap()
+            .contains("Answer briefly"));
+    }
+
+    #[tokio::test]
+    async fn test_chat_completions_agent_without_user_seeds_throwaway_session() {
+        let provider = Arc::new(MockLlmProvider::text_response("Third", 10, 2));
+        let (state, _tmp) = agent_state(provider.clone());
+
+        let response = post_chat(
+            &state,
+            auth_headers(),
+            serde_json::json!({
+                "model": "carapace",
+                "messages": [
+                    {"role": "user", "content": "First"},
+                    {"role": "assistant", "content": "Second"},
+                    {"role": "user", "content": "Next"}
+                ]
+            }),
+        )
+        .await;
         assert_eq!(response.status(), StatusCode::OK);
+        let parsed = body_json(response).await;
+        assert_eq!(parsed["choices"][0]["message"]["content"], "Third");
 
-        let body_bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+        {
+            let requests = provider.requests.lock();
+            assert_eq!(requests.len(), 1);
+            assert_eq!(requests[0].messages.len(), 3);
+        }
+
+        // The per-request session is removed once the run has ended
+        let store = state.ws_state.as_ref().unwrap().session_store().clone();
+        for _ in 0..50 {
+            if store
+                .list_sessions(sessions::SessionFilter::default())
+                .unwrap()
+                .is_empty()
+            {
+                return;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        panic!("per-request session was not deleted");
+    }
+
+    #[tokio::test]
+    async fn test_chat_completions_agent_streaming() {
+        let provider = Arc::new(MockLlmProvider::with_events(vec![
+            StreamEvent::TextDelta {
+                text: "Hel".to_string(),
+            },
+            StreamEvent::TextDelta {
+                text: "lo".to_string(),
+            },
+            StreamEvent::Stop {
+                reason: StopReason::MaxTokens,
+                usage: TokenUsage {
+                    input_tokens: 5,
+                    output_tokens: 2,
+                },
+            },
+        ]));
+        let (state, _tmp) = agent_state(provider);
+        let mut headers = auth_headers();
+        headers.insert(SESSION_KEY_HEADER, "webui-1".parse().unwrap());
+
+        let response = post_chat(
+            &state,
+            headers,
+            serde_json::json!({
+                "model": "agent:main",
+                "stream": true,
+                "messages": [{"role": "user", "content": "Hello"}]
+            }),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
             .await
             .unwrap();
-        let parsed: serde_json::Value = serde_json::from_slice(&body_bytes).unwrap();
+        let body = String::from_utf8(bytes.to_vec()).unwrap();
+        let chunks: Vec<Value> = body
+            .lines()
+            .filter_map(|l| l.strip_prefix("data: "))
+            .filter(|d| *d != "[DONE]")
+            .map(|d| serde_json::from_str(d).unwrap())
+            .collect();
+
+        let text: String = chunks
+            .iter()
+            .filter_map(|c| c["choices"][0]["delta"]["content"].as_str())
+            .collect();
+        assert_eq!(text, "Hello");
+        assert_eq!(chunks[0]["choices"][0]["delta"]["role"], "assistant");
+        assert_eq!(
+            chunks.last().unwrap()["choices"][0]["finish_reason"],
+            "length"
+        );
+        assert!(body.ends_with("data: [DONE]\n\n"));
 
-        assert_eq!(parsed["object"], "response");
-        assert_eq!(parsed["status"], "completed");
-        assert!(parsed["id"].as_str().unwrap().starts_with("resp_"));
-        assert_eq!(parsed["model"], crate::agent::DEFAULT_MODEL);
-        assert_eq!(parsed["output"][0]["type"], "message");
-        assert_eq!(parsed["output"][0]["role"], "assistant");
-        assert_eq!(parsed["output"][0]["content"][0]["type"], "output_text");
-        assert_eq!(parsed["output"][0]["content"][0]["text"], "LLM response");
-        assert_eq!(parsed["usage"]["input_tokens"], 80);
-        assert_eq!(parsed["usage"]["output_tokens"], 20);
-        assert_eq!(parsed["usage"]["total_tokens"], 100);
+        assert_eq!(session_history(&state, "openai:main:webui-1").len(), 2);
     }
 
     #[tokio::test]
-    async fn test_responses_with_provider_items_input() {
-        let provider = Arc::new(MockLlmProvider::text_response("Items response", 60, 15));
-        let state = OpenAiState {
-            responses_enabled: true,
-            llm_provider: Some(provider),
-            gateway_token: Some("test-token".to_string()),
-            ..Default::default()
+    async fn test_agent_stream_ends_with_error_on_partial_failover() {
+        let ws = Arc::new(WsServerState::new(WsServerConfig::default()));
+        let run_id = "run-partial-failover";
+        let cancel_token = CancellationToken::new();
+        let run = AgentRunHandle {
+            state: ws.clone(),
+            run_id: run_id.to_string(),
+            cancel_token: cancel_token.clone(),
+            events: ws.subscribe_agent_events(run_id),
+            waiter: None,
+            ephemeral_session: None,
+            finished: false,
         };
+        crate::server::ws::broadcast_agent_event(
+            &ws,
+            run_id,
+            0,
+            "text",
+            serde_json::json!({"delta": "half an ans"}),
+        );
+        crate::server::ws::broadcast_agent_event(
+            &ws,
+            run_id,
+            1,
+            "failover",
+            serde_json::json!({
+                "fromModel": "primary",
+                "toModel": "backup",
+                "reason": "overloaded",
+                "partial": true,
+            }),
+        );
+        crate::server::ws::broadcast_agent_event(
+            &ws,
+            run_id,
+            2,
+            "text",
+            serde_json::json!({"delta": "A full answer"}),
+        );
+
+        let response = stream_agent_run(run, "carapace".to_string(), "chatcmpl_1".to_string(), 0);
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body = String::from_utf8(bytes.to_vec()).unwrap();
+        let chunks: Vec<Value> = body
+            .lines()
+            .filter_map(|l| l.strip_prefix("data: "))
+            .filter(|d| *d != "[DONE]")
+            .map(|d| serde_json::from_str(d).unwrap())
+            .collect();
+
+        assert!(!body.contains("A full answer"));
+        let last = chunks.last().unwrap();
+        assert_eq!(last["error"]["type"], "api_error");
+        assert!(last["error"]["message"]
+            .as_str()
+            .unwrap()
+            .contains("primary"));
+        assert!(body.ends_with("data: [DONE]\n\n"));
+        assert!(cancel_token.is_cancelled());
+    }
+
+    #[tokio::test]
+    async fn test_agent_rejects_client_tools() {
+        let provider = Arc::new(MockLlmProvider::text_response("should not reach", 0, 0));
+        let (state, _tmp) = agent_state(provider.clone());
+
+        let response = post_chat(
+            &state,
+            auth_headers(),
+            serde_json::json!({
+                "model": "carapace",
+                "messages": [{"role": "user", "content": "Weather?"}],
+                "tools": [weather_tool()]
+            }),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let parsed = body_json(response).await;
+        assert_eq!(parsed["error"]["type"], "invalid_request_error");
+        assert!(provider.requests.lock().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_chat_completions_agent_error_returns_500() {
+        let provider = Arc::new(MockLlmProvider::error_response("invalid request body"));
+        let (state, _tmp) = agent_state(provider);
+
+        let response = post_chat(
+            &state,
+            auth_headers(),
+            serde_json::json!({
+                "model": "carapace",
+                "user": "bob",
+                "messages": [{"role": "user", "content": "Hello"}]
+            }),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        let parsed = body_json(response).await;
+        assert_eq!(parsed["error"]["type"], "api_error");
+    }
+
+    #[tokio::test]
+    async fn test_chat_completions_raw_model_bypasses_agent() {
+        let provider = Arc::new(MockLlmProvider::text_response("direct", 1, 1));
+        let (mut state, _tmp) = agent_state(provider.clone());
+        state.llm_provider = Some(provider);
+
+        let response = post_chat(
+            &state,
+            auth_headers(),
+            serde_json::json!({
+                "model": "gpt-4o",
+                "user": "carol",
+                "messages": [{"role": "user", "content": "Hello"}]
+            }),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let store = state.ws_state.as_ref().unwrap().session_store();
+        assert!(store.get_session_by_key("openai:default:carol").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_responses_agent_run() {
+        let provider = Arc::new(MockLlmProvider::text_response("Agent says hi", 12, 3));
+        let (state, _tmp) = agent_state(provider.clone());
 
         let body = serde_json::to_vec(&serde_json::json!({
-            "model": "custom-model",
-            "input": [
-                {"type": "message", "role": "user", "content": "Hello"}
-            ]
+            "model": "carapace:main",
+            "input": "Hello",
+            "instructions": "Be terse",
+            "user": "dave"
         }))
         .unwrap();
-
-        let mut headers = HeaderMap::new();
-        headers.insert("authorization", "Bearer test-token".parse().unwrap());
-
         let response = responses_handler(
-            State(state),
+            State(state.clone()),
             loopback_connect_info(),
-            headers,
+            auth_headers(),
             axum::body::Bytes::from(body),
         )
         .await;
-
         assert_eq!(response.status(), StatusCode::OK);
+        let parsed = body_json(response).await;
+        assert_eq!(parsed["status"], "completed");
+        assert_eq!(parsed["model"], "carapace:main");
+        assert_eq!(parsed["output"][0]["content"][0]["text"], "Agent says hi");
+        assert_eq!(parsed["usage"]["total_tokens"], 15);
+
+        assert_eq!(session_history(&state, "openai:main:dave").len(), 2);
+        assert!(provider.requests.lock()[0]
+            .system
+            .as_deref()
+            .unwrap()
+            .contains("Be terse"));
+    }
 
-        let body_bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
-            .await
-            .unwrap();
-        let parsed: serde_json::Value = serde_json::from_slice(&body_bytes).unwrap();
+    // ============== Client function calling ==============
+
+    fn weather_tool() -> Value {
+        serde_json::json!({
+            "type": "function",
+            "function": {
+                "name": "get_weather",
+                "description": "Current weather",
+                "parameters": {
+                    "type": "object",
+                    "properties": {"city": {"type": "string"}}
+                }
+            }
+        })
+    }
 
-        assert_eq!(parsed["output"][0]["content"][0]["text"], "Items response");
-        // custom-model should be passed through as-is (not remapped)
-        assert_eq!(parsed["model"], "custom-model");
+    fn tool_call_events() -> Vec<StreamEvent> {
+        vec![
+            StreamEvent::TextDelta {
+                text: "Checking".to_string(),
+            },
+            StreamEvent::ToolUse {
+                id: "toolu_1".to_string(),
+                name: "get_weather".to_string(),
+                input: serde_json::json!({"city": "Paris"}),
+            },
+            StreamEvent::Stop {
+                reason: StopReason::ToolUse,
+                usage: TokenUsage {
+                    input_tokens: 30,
+                    output_tokens: 12,
+                },
+            },
+        ]
     }
 
-    #[tokio::test]
-    async fn test_responses_with_instructions() {
-        let provider = Arc::new(MockLlmProvider::text_response("With instructions", 90, 30));
-        let state = OpenAiState {
+    fn direct_state(provider: Arc<MockLlmProvider>) -> OpenAiState {
+        OpenAiState {
+            chat_completions_enabled: true,
             responses_enabled: true,
-            llm_provider: Some(provider),
             gateway_token: Some("test-token".to_string()),
+            llm_provider: Some(provider),
             ..Default::default()
-        };
+        }
+    }
 
-        let body = serde_json::to_vec(&serde_json::json!({
-            "model": "carapace",
-            "input": "Hello",
-            "instructions": "Be very helpful"
-        }))
+    #[test]
+    fn test_convert_to_llm_messages_tool_round_trip() {
+        let messages: Vec<ChatMessage> = serde_json::from_value(serde_json::json!([
+            {"role": "user", "content": "Weather in Paris and Rome?"},
+            {"role": "assistant", "content": null, "tool_calls": [
+                {"id": "call_a", "type": "function",
+                 "function": {"name": "get_weather", "arguments": "{\"city\":\"Paris\"}"}},
+                {"id": "call_b", "type": "function",
+                 "function": {"name": "get_weather", "arguments": ""}}
+            ]},
+            {"role": "tool", "tool_call_id": "call_a", "content": "sunny"},
+            {"role": "tool", "tool_call_id": "call_b", "content": "rainy"},
+            {"role": "user", "content": "Thanks"}
+        ]))
         .unwrap();
 
-        let mut headers = HeaderMap::new();
-        headers.insert("authorization", "Bearer test-token".parse().unwrap());
+        let (_, llm_msgs) = convert_to_llm_messages(&messages);
+        assert_eq!(llm_msgs.len(), 4);
 
-        let response = responses_handler(
-            State(state),
-            loopback_connect_info(),
-            headers,
-            axum::body::Bytes::from(body),
+        assert_eq!(llm_msgs[1].role, LlmRole::Assistant);
+        assert_eq!(llm_msgs[1].content.len(), 2);
+        match &llm_msgs[1].content[0] {
+            ContentBlock::ToolUse { id, name, input } => {
+                assert_eq!(id, "call_a");
+                assert_eq!(name, "get_weather");
+                assert_eq!(input["city"], "Paris");
+            }
+            other => panic!("expected ToolUse block, got {other:?}"),
+        }
+        match &llm_msgs[1].content[1] {
+            ContentBlock::ToolUse { input, .. } => assert!(input.as_object().unwrap().is_empty()),
+            other => panic!("expected ToolUse block, got {other:?}"),
+        }
+
+        assert_eq!(llm_msgs[2].role, LlmRole::User);
+        let results: Vec<(&str, &str)> = llm_msgs[2]
+            .content
+            .iter()
+            .map(|b| match b {
+                ContentBlock::ToolResult {
+                    tool_use_id,
+                    content,
+                    ..
+                } => (tool_use_id.as_str(), content.as_str()),
+                other => panic!("expected ToolResult block, got {other:?}"),
+            })
+            .collect();
+        assert_eq!(results, [("call_a", "sunny"), ("call_b", "rainy")]);
+
+        assert_eq!(llm_msgs[3].role, LlmRole::User);
+    }
+
+    #[test]
+    fn test_select_tools() {
+        let tools: Vec<ResponsesTool> = serde_json::from_value(serde_json::json!([
+            weather_tool(),
+            {"type": "function", "name": "get_time", "parameters": {"type": "object"}},
+            {"type": "web_search"}
+        ]))
+        .unwrap();
+
+        let all = select_tools(Some(&tools), None);
+        let names: Vec<&str> = all.iter().map(|t| t.name.as_str()).collect();
+        assert_eq!(names, ["get_weather", "get_time"]);
+        assert_eq!(all[0].description, "Current weather");
+        assert_eq!(all[0].input_schema["properties"]["city"]["type"], "string");
+
+        let auto = select_tools(Some(&tools), Some(&serde_json::json!("auto")));
+        assert_eq!(auto.len(), 2);
+        assert!(select_tools(Some(&tools), Some(&serde_json::json!("none"))).is_empty());
+
+        let chat_choice = serde_json::json!({"type": "function", "function": {"name": "get_time"}});
+        let flat_choice = serde_json::json!({"type": "function", "name": "get_time"});
+        for choice in [chat_choice, flat_choice] {
+            let chosen = select_tools(Some(&tools), Some(&choice));
+            assert_eq!(chosen.len(), 1);
+            assert_eq!(chosen[0].name, "get_time");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_chat_completions_returns_tool_calls() {
+        let provider = Arc::new(MockLlmProvider::with_events(tool_call_events()));
+        let state = direct_state(provider.clone());
+
+        let response = post_chat(
+            &state,
+            auth_headers(),
+            serde_json::json!({
+                "model": "claude-sonnet-4-20250514",
+                "messages": [{"role": "user", "content": "Weather in Paris?"}],
+                "tools": [weather_tool()],
+                "tool_choice": "auto"
+            }),
         )
         .await;
-
         assert_eq!(response.status(), StatusCode::OK);
+        let parsed = body_json(response).await;
+        let choice = &parsed["choices"][0];
+        assert_eq!(choice["finish_reason"], "tool_calls");
+        assert_eq!(choice["message"]["content"], "Checking");
+        let call = &choice["message"]["tool_calls"][0];
+        assert_eq!(call["id"], "toolu_1");
+        assert_eq!(call["type"], "function");
+        assert_eq!(call["function"]["name"], "get_weather");
+        let args: Value =
+            serde_json::from_str(call["function"]["arguments"].as_str().unwrap()).unwrap();
+        assert_eq!(args["city"], "Paris");
+
+        let requests = provider.requests.lock();
+        assert_eq!(requests[0].tools.len(), 1);
+        assert_eq!(requests[0].tools[0].name, "get_weather");
+    }
 
-        let body_bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+    #[tokio::test]
+    async fn test_chat_completions_streams_tool_calls() {
+        let provider = Arc::new(MockLlmProvider::with_events(tool_call_events()));
+        let state = direct_state(provider);
+
+        let response = post_chat(
+            &state,
+            auth_headers(),
+            serde_json::json!({
+                "model": "claude-sonnet-4-20250514",
+                "stream": true,
+                "messages": [{"role": "user", "content": "Weather in Paris?"}],
+                "tools": [weather_tool()]
+            }),
+        )
+        .await;
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
             .await
             .unwrap();
-        let parsed: serde_json::Value = serde_json::from_slice(&body_bytes).unwrap();
-
+        let body = String::from_utf8(bytes.to_vec()).unwrap();
+        let chunks: Vec<Value> = body
+            .lines()
+            .filter_map(|l| l.strip_prefix("data: "))
+            .filter(|d| *d != "[DONE]")
+            .map(|d| serde_json::from_str(d).unwrap())
+            .collect();
+
+        let call_chunk = chunks
+            .iter()
+            .find(|c| c["choices"][0]["delta"]["tool_calls"].is_array())
+            .expect("tool call chunk");
+        let call = &call_chunk["choices"][0]["delta"]["tool_calls"][0];
+        assert_eq!(call["index"], 0);
+        assert_eq!(call["id"], "toolu_1");
+        assert_eq!(call["function"]["name"], "get_weather");
         assert_eq!(
-            parsed["output"][0]["content"][0]["text"],
-            "With instructions"
+            chunks.last().unwrap()["choices"][0]["finish_reason"],
+            "tool_calls"
         );
     }
 
     #[tokio::test]
-    async fn test_responses_no_user_message_returns_400() {
-        let provider = Arc::new(MockLlmProvider::text_response("should not reach", 0, 0));
-        let state = OpenAiState {
-            responses_enabled: true,
-            llm_provider: Some(provider),
-            gateway_token: Some("test-token".to_string()),
-            ..Default::default()
-        };
+    async fn test_chat_completions_unknown_tool_choice_returns_400() {
+        let provider = Arc::new(MockLlmProvider::text_response("unused", 1, 1));
+        let state = direct_state(provider);
+
+        let response = post_chat(
+            &state,
+            auth_headers(),
+            serde_json::json!({
+                "model": "claude-sonnet-4-20250514",
+                "messages": [{"role": "user", "content": "Hi"}],
+                "tools": [weather_tool()],
+                "tool_choice": {"type": "function", "function": {"name": "send_email"}}
+            }),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_responses_round_trips_function_calls() {
+        let provider = Arc::new(MockLlmProvider::with_events(tool_call_events()));
+        let state = direct_state(provider.clone());
 
         let body = serde_json::to_vec(&serde_json::json!({
-            "model": "carapace",
+            "model": "claude-sonnet-4-20250514",
+            "tools": [{"type": "function", "name": "get_weather", "parameters": {"type": "object"}}],
             "input": [
-                {"type": "message", "role": "system", "content": "Be helpful"}
+                {"type": "message", "role": "user", "content": "Weather?"},
+                {"type": "function_call", "call_id": "call_0", "name": "get_weather", "arguments": "{}"},
+                {"type": "function_call_output", "call_id": "call_0", "output": "unknown city"}
             ]
         }))
         .unwrap();
-
-        let mut headers = HeaderMap::new();
-        headers.insert("authorization", "Bearer test-token".parse().unwrap());
-
         let response = responses_handler(
             State(state),
             loopback_connect_info(),
-            headers,
+            auth_headers(),
             axum::body::Bytes::from(body),
         )
         .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let parsed = body_json(response).await;
+        let output = parsed["output"].as_array().unwrap();
+        assert_eq!(output.len(), 2);
+        assert_eq!(output[0]["type"], "message");
+        assert_eq!(output[1]["type"], "function_call");
+        assert_eq!(output[1]["call_id"], "toolu_1");
+        assert_eq!(output[1]["name"], "get_weather");
+
+        let requests = provider.requests.lock();
+        let messages = &requests[0].messages;
+        assert_eq!(messages.len(), 3);
+        assert!(matches!(
+            &messages[1].content[0],
+            ContentBlock::ToolUse { id, .. } if id == "call_0"
+        ));
+        assert!(matches!(
+            &messages[2].content[0],
+            ContentBlock::ToolResult { tool_use_id, .. } if tool_use_id == "call_0"
+        ));
+    }
 
-        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    // ============== Models, embeddings and audio ==============
+
+    #[tokio::test]
+    async fn test_models_lists_agents_and_catalog() {
+        let provider = Arc::new(MockLlmProvider::text_response("unused", 0, 0));
+        let (state, _tmp) = agent_state(provider);
+        let response = models_handler(State(state), loopback_connect_info(), auth_headers()).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let parsed = body_json(response).await;
+        assert_eq!(parsed["object"], "list");
+        let ids: Vec<&str> = parsed["data"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|m| m["id"].as_str().unwrap())
+            .collect();
+        assert_eq!(ids[0], "carapace");
+        assert!(ids.iter().any(|id| id.starts_with("carapace:")));
+        assert!(ids.iter().any(|id| id.starts_with("anthropic:")));
+        assert_eq!(parsed["data"][0]["object"], "model");
     }
 
     #[tokio::test]
-    async fn test_responses_only_system_via_instructions_returns_400() {
-        let provider = Arc::new(MockLlmProvider::text_response("should not reach", 0, 0));
+    async fn test_models_requires_auth_and_enabled_endpoint() {
         let state = OpenAiState {
-            responses_enabled: true,
-            llm_provider: Some(provider),
+            embeddings_enabled: true,
             gateway_token: Some("test-token".to_string()),
             ..Default::default()
         };
+        let response =
+            models_handler(State(state), loopback_connect_info(), HeaderMap::new()).await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
 
-        // Items list has only system messages; instructions also provided.
-        // has_user_message check catches this first, returning 400.
-        let body = serde_json::to_vec(&serde_json::json!({
-            "model": "carapace",
-            "input": [
-                {"type": "message", "role": "system", "content": "System only"}
-            ],
-            "instructions": "Extra system"
-        }))
-        .unwrap();
+        let response = models_handler(
+            State(OpenAiState::default()),
+            loopback_connect_info(),
+            auth_headers(),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
 
-        let mut headers = HeaderMap::new();
-        headers.insert("authorization", "Bearer test-token".parse().unwrap());
+    fn embeddings_state() -> OpenAiState {
+        OpenAiState {
+            embeddings_enabled: true,
+            gateway_token: Some("test-token".to_string()),
+            llm_provider: Some(Arc::new(MockLlmProvider::text_response("unused", 0, 0))),
+            ..Default::default()
+        }
+    }
 
-        let response = responses_handler(
+    async fn post_embeddings(state: OpenAiState, body: Value) -> Response {
+        embeddings_handler(
             State(state),
             loopback_connect_info(),
-            headers,
-            axum::body::Bytes::from(body),
+            auth_headers(),
+            axum::body::Bytes::from(serde_json::to_vec(&body).unwrap()),
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_embeddings_batch() {
+        let response = post_embeddings(
+            embeddings_state(),
+            serde_json::json!({"model": "text-embedding-3-small", "input": ["a", "bcd"]}),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let parsed = body_json(response).await;
+        assert_eq!(parsed["object"], "list");
+        assert_eq!(parsed["model"], "text-embedding-3-small");
+        assert_eq!(parsed["data"][1]["index"], 1);
+        assert_eq!(
+            parsed["data"][1]["embedding"],
+            serde_json::json!([1.0, 3.0])
+        );
+        assert_eq!(parsed["usage"]["prompt_tokens"], 8);
+        assert_eq!(parsed["usage"]["total_tokens"], 8);
+    }
+
+    #[tokio::test]
+    async fn test_embeddings_string_input_base64() {
+        let response = post_embeddings(
+            embeddings_state(),
+            serde_json::json!({
+                "model": "text-embedding-3-small",
+                "input": "hi",
+                "encoding_format": "base64"
+            }),
         )
         .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let parsed = body_json(response).await;
+        assert_eq!(parsed["data"].as_array().unwrap().len(), 1);
+        assert_eq!(
+            parsed["data"][0]["embedding"],
+            encode_embedding_base64(&[0.0, 2.0])
+        );
+    }
 
+    #[tokio::test]
+    async fn test_embeddings_rejects_empty_input() {
+        let response = post_embeddings(
+            embeddings_state(),
+            serde_json::json!({"model": "text-embedding-3-small", "input": []}),
+        )
+        .await;
         assert_eq!(response.status(), StatusCode::BAD_REQUEST);
     }
 
     #[tokio::test]
-    async fn test_responses_provider_error_returns_500() {
-        let provider = Arc::new(MockLlmProvider::error_response("Service overloaded"));
-        let state = OpenAiState {
-            responses_enabled: true,
-            llm_provider: Some(provider),
+    async fn test_embeddings_provider_error() {
+        let response = post_embeddings(
+            embeddings_state(),
+            serde_json::json!({"model": "broken-embed", "input": "x"}),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        let parsed = body_json(response).await;
+        assert_eq!(parsed["error"]["type"], "api_error");
+    }
+
+    #[test]
+    fn test_embedding_usage_provider() {
+        assert_eq!(embedding_usage_provider("text-embedding-3-small"), "openai");
+        assert_eq!(
+            embedding_usage_provider("ollama:nomic-embed-text"),
+            "ollama"
+        );
+        assert_eq!(embedding_usage_provider("voyage:voyage-3"), "voyage");
+        assert_eq!(
+            embedding_usage_provider("amazon.titan-embed-text-v2:0"),
+            "bedrock"
+        );
+    }
+
+    #[test]
+    fn test_encode_embedding_base64_round_trip() {
+        use base64::Engine as _;
+
+        let encoded = encode_embedding_base64(&[1.5, -2.0]);
+        let bytes = base64::engine::general_purpose::STANDARD
+            .decode(encoded)
+            .unwrap();
+        let floats: Vec<f32> = bytes
+            .chunks_exact(4)
+            .map(|c| f32::from_le_bytes([c[0], c[1], c[2], c[3]]))
+            .collect();
+        assert_eq!(floats, vec![1.5, -2.0]);
+    }
+
+    #[test]
+    fn test_audio_mime_from_filename() {
+        assert_eq!(audio_mime_from_filename("note.MP3"), Some("audio/mpeg"));
+        assert_eq!(audio_mime_from_filename("voice.m4a"), Some("audio/mp4"));
+        assert_eq!(audio_mime_from_filename("clip.webm"), Some("audio/webm"));
+        assert_eq!(audio_mime_from_filename("notes.txt"), None);
+        assert_eq!(audio_mime_from_filename("noext"), None);
+    }
+
+    fn audio_state() -> OpenAiState {
+        OpenAiState {
+            audio_enabled: true,
             gateway_token: Some("test-token".to_string()),
             ..Default::default()
-        };
-
-        let body = serde_json::to_vec(&serde_json::json!({
-            "model": "carapace",
-            "input": "Hello"
-        }))
-        .unwrap();
+        }
+    }
 
-        let mut headers = HeaderMap::new();
-        headers.insert("authorization", "Bearer test-token".parse().unwrap());
+    async fn post_transcription(body: &'static str) -> Response {
+        use axum::extract::FromRequest;
 
-        let response = responses_handler(
-            State(state),
+        let request = axum::http::Request::builder()
+            .method("POST")
+            .uri("/v1/audio/transcriptions")
+            .header("content-type", "multipart/form-data; boundary=XX")
+            .body(Body::from(body.replace('\n', "\r\n")))
+            .unwrap();
+        let multipart = axum::extract::Multipart::from_request(request, &()).await;
+        transcriptions_handler(
+            State(audio_state()),
             loopback_connect_info(),
-            headers,
-            axum::body::Bytes::from(body),
+            auth_headers(),
+            multipart,
         )
-        .await;
-
-        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        .await
+    }
 
-        let body_bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
-            .await
-            .unwrap();
-        let parsed: serde_json::Value = serde_json::from_slice(&body_bytes).unwrap();
-        assert_eq!(parsed["error"]["type"], "api_error");
+    #[tokio::test]
+    async fn test_transcriptions_requires_file() {
+        let response = post_transcription(
+            "--XX\nContent-Disposition: form-data; name=\"model\"\n\nwhisper-1\n--XX--\n",
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let parsed = body_json(response).await;
         assert!(parsed["error"]["message"]
             .as_str()
             .unwrap()
-            .contains("Service overloaded"));
+            .contains("file"));
     }
 
     #[tokio::test]
-    async fn test_responses_disabled_returns_404() {
-        let state = OpenAiState {
-            responses_enabled: false,
-            gateway_token: Some("test-token".to_string()),
-            ..Default::default()
-        };
-
-        let body = serde_json::to_vec(&serde_json::json!({
-            "model": "carapace",
-            "input": "Hello"
-        }))
-        .unwrap();
+    async fn test_transcriptions_rejects_non_audio_file() {
+        let response = post_transcription(
+            "--XX\nContent-Disposition: form-data; name=\"file\"; filename=\"a.txt\"\n\
+             Content-Type: text/plain\n\nhello\n--XX--\n",
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
 
-        let mut headers = HeaderMap::new();
-        headers.insert("authorization", "Bearer test-token".parse().unwrap());
+    #[tokio::test]
+    async fn test_transcriptions_rejects_unknown_response_format() {
+        let response = post_transcription(
+            "--XX\nContent-Disposition: form-data; name=\"response_format\"\n\nsrt\n--XX--\n",
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
 
-        let response = responses_handler(
-            State(state),
+    #[tokio::test]
+    async fn test_transcriptions_not_found_when_disabled() {
+        let request = axum::http::Request::builder().body(Body::empty()).unwrap();
+        let multipart = <axum::extract::Multipart as axum::extract::FromRequest<()>>::from_request(
+            request,
+            &(),
+        )
+        .await;
+        let response = transcriptions_handler(
+            State(OpenAiState::default()),
             loopback_connect_info(),
-            headers,
-            axum::body::Bytes::from(body),
+            auth_headers(),
+            multipart,
         )
         .await;
-
         assert_eq!(response.status(), StatusCode::NOT_FOUND);
     }
+
+    async fn post_speech(body: Value) -> Response {
+        speech_handler(
+            State(audio_state()),
+            loopback_connect_info(),
+            auth_headers(),
+            axum::body::Bytes::from(serde_json::to_vec(&body).unwrap()),
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_speech_rejects_empty_input() {
+        let response = post_speech(serde_json::json!({"input": "  "})).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_speech_rejects_unknown_format() {
+        let response =
+            post_speech(serde_json::json!({"input": "hello", "response_format": "wav"})).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let parsed = body_json(response).await;
+        assert!(parsed["error"]["message"].as_str().unwrap().contains("mp3"));
+    }
+
+    #[test]
+    fn test_speech_content_type() {
+        assert_eq!(speech_content_type("mp3"), "audio/mpeg");
+        assert_eq!(speech_content_type("flac"), "audio/flac");
+    }
 }
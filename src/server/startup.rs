@@ -214,9 +214,15 @@ pub fn spawn_background_tasks(
         let pipeline = ws_state.message_pipeline().clone();
         let channels = ws_state.channel_registry().clone();
         let state = ws_state.clone();
+        let delivery_config = messages::delivery::build_delivery_config(raw_config);
         let rx = shutdown_rx.clone();
         tokio::spawn(messages::delivery::delivery_loop(
-            pipeline, plugin_reg, channels, state, rx,
+            pipeline,
+            plugin_reg,
+            channels,
+            state,
+            delivery_config,
+            rx,
         ));
     }
 
@@ -275,7 +281,10 @@ fn spawn_config_watcher_bridge(
                                     crate::agent::factory::fingerprint_providers(&new_cfg);
                                 if new_fingerprint != current_fingerprint {
                                     info!("LLM provider configuration changed, rebuilding providers");
-                                    match crate::agent::factory::build_providers(&new_cfg) {
+                                    match crate::agent::factory::build_providers(
+                                        &new_cfg,
+                                        ws_state_for_config.plugin_registry().map(|r| r.as_ref()),
+                                    ) {
                                         Ok(Some(mp)) => {
                                             ws_state_for_config
                                                 .set_llm_provider(Some(std::sync::Arc::new(mp)));
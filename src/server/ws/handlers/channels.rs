@@ -39,3 +39,205 @@ pub(super) fn handle_channels_logout(
         "channel": channel
     }))
 }
+
+pub(super) fn handle_channels_pair_list(state: &WsServerState) -> Result<Value, ErrorShape> {
+    let registry = state.sender_access();
+    let pending = registry
+        .list_pending()
+        .iter()
+        .map(|req| {
+            json!({
+                "channel": req.channel,
+                "senderId": req.sender_id,
+                "chatId": req.chat_id,
+                "code": req.code,
+                "expiresAtMs": req.expires_at_ms(),
+                "ts": req.created_at_ms
+            })
+        })
+        .collect::<Vec<_>>();
+    let approved = registry
+        .list_approved()
+        .iter()
+        .map(|sender| {
+            json!({
+                "channel": sender.channel,
+                "senderId": sender.sender_id,
+                "approvedAtMs": sender.approved_at_ms
+            })
+        })
+        .collect::<Vec<_>>();
+
+    Ok(json!({ "pending": pending, "approved": approved }))
+}
+
+pub(super) fn handle_channels_pair_approve(
+    params: Option<&Value>,
+    state: &WsServerState,
+) -> Result<Value, ErrorShape> {
+    let (channel, code) = pairing_code_params(params)?;
+    let sender = state
+        .sender_access()
+        .approve(channel, code)
+        .map_err(sender_access_error)?;
+
+    crate::logging::audit::audit(crate::logging::audit::AuditEvent::ChannelPairingResolved {
+        channel: sender.channel.clone(),
+        sender_id: sender.sender_id.clone(),
+        approved: true,
+    });
+    broadcast_event(
+        state,
+        "channels.pair.resolved",
+        json!({
+            "channel": sender.channel,
+            "senderId": sender.sender_id,
+            "decision": "approved",
+            "ts": now_ms()
+        }),
+    );
+
+    Ok(json!({
+        "channel": sender.channel,
+        "senderId": sender.sender_id,
+        "approvedAtMs": sender.approved_at_ms
+    }))
+}
+
+pub(super) fn handle_channels_pair_reject(
+    params: Option<&Value>,
+    state: &WsServerState,
+) -> Result<Value, ErrorShape> {
+    let (channel, code) = pairing_code_params(params)?;
+    let request = state
+        .sender_access()
+        .reject(channel, code)
+        .map_err(sender_access_error)?;
+
+    crate::logging::audit::audit(crate::logging::audit::AuditEvent::ChannelPairingResolved {
+        channel: request.channel.clone(),
+        sender_id: request.sender_id.clone(),
+        approved: false,
+    });
+    broadcast_event(
+        state,
+        "channels.pair.resolved",
+        json!({
+            "channel": request.channel,
+            "senderId": request.sender_id,
+            "decision": "rejected",
+            "ts": now_ms()
+        }),
+    );
+
+    Ok(json!({
+        "channel": request.channel,
+        "senderId": request.sender_id
+    }))
+}
+
+pub(super) fn handle_channels_senders_revoke(
+    params: Option<&Value>,
+    state: &WsServerState,
+) -> Result<Value, ErrorShape> {
+    let channel = params
+        .and_then(|v| v.get("channel"))
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| error_shape(ERROR_INVALID_REQUEST, "channel is required", None))?;
+    let sender_id = params
+        .and_then(|v| v.get("senderId"))
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| error_shape(ERROR_INVALID_REQUEST, "senderId is required", None))?;
+    let sender = state
+        .sender_access()
+        .revoke(channel, sender_id)
+        .map_err(sender_access_error)?;
+
+    crate::logging::audit::audit(crate::logging::audit::AuditEvent::ChannelSenderRevoked {
+        channel: sender.channel.clone(),
+        sender_id: sender.sender_id.clone(),
+    });
+
+    Ok(json!({
+        "channel": sender.channel,
+        "senderId": sender.sender_id,
+        "revoked": true
+    }))
+}
+
+fn pairing_code_params(params: Option<&Value>) -> Result<(&str, &str), ErrorShape> {
+    let channel = params
+        .and_then(|v| v.get("channel"))
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| error_shape(ERROR_INVALID_REQUEST, "channel is required", None))?;
+    let code = params
+        .and_then(|v| v.get("code"))
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| error_shape(ERROR_INVALID_REQUEST, "code is required", None))?;
+    Ok((channel, code))
+}
+
+fn sender_access_error(err: channels::access::SenderAccessError) -> ErrorShape {
+    use channels::access::SenderAccessError;
+    match err {
+        SenderAccessError::RequestNotFound
+        | SenderAccessError::RequestAlreadyResolved
+        | SenderAccessError::RequestExpired
+        | SenderAccessError::SenderNotApproved
+        | SenderAccessError::TooManyApprovedSenders => {
+            error_shape(ERROR_INVALID_REQUEST, &err.to_string(), None)
+        }
+        _ => error_shape(ERROR_UNAVAILABLE, &err.to_string(), None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_channels_pair_approve_and_revoke() {
+        let state = WsServerState::new(WsServerConfig::default());
+        let outcome = state
+            .sender_access()
+            .request_pairing("telegram", "42", Some("42"))
+            .unwrap();
+
+        let list = handle_channels_pair_list(&state).unwrap();
+        assert_eq!(list["pending"][0]["code"], outcome.request.code.as_str());
+
+        let err = handle_channels_pair_approve(
+            Some(&json!({ "channel": "slack", "code": outcome.request.code })),
+            &state,
+        )
+        .unwrap_err();
+        assert_eq!(err.code, ERROR_INVALID_REQUEST);
+
+        let approved = handle_channels_pair_approve(
+            Some(&json!({ "channel": "telegram", "code": outcome.request.code })),
+            &state,
+        )
+        .unwrap();
+        assert_eq!(approved["senderId"], "42");
+        assert!(state.sender_access().is_approved("telegram", "42"));
+
+        let list = handle_channels_pair_list(&state).unwrap();
+        assert_eq!(list["pending"].as_array().unwrap().len(), 0);
+        assert_eq!(list["approved"][0]["senderId"], "42");
+
+        handle_channels_senders_revoke(
+            Some(&json!({ "channel": "telegram", "senderId": "42" })),
+            &state,
+        )
+        .unwrap();
+        assert!(!state.sender_access().is_approved("telegram", "42"));
+    }
+
+    #[test]
+    fn test_channels_pair_reject_requires_code() {
+        let state = WsServerState::new(WsServerConfig::default());
+        let err = handle_channels_pair_reject(Some(&json!({ "channel": "telegram" })), &state)
+            .unwrap_err();
+        assert_eq!(err.message, "code is required");
+    }
+}
@@ -16,7 +16,7 @@ use super::super::*;
 // Re-export types for use by other modules
 pub use crate::cron::{
     CronError, CronIsolation, CronJobCreate, CronJobPatch, CronJobStatus, CronPayload, CronRunMode,
-    CronSchedule, CronSessionTarget, CronWakeMode,
+    CronRunPolicy, CronSchedule, CronSessionTarget, CronWakeMode,
 };
 
 /// Get the cron scheduler status.
@@ -100,6 +100,7 @@ pub(super) fn handle_cron_add(
     let session_target = parse_session_target(params.get("sessionTarget"));
     let wake_mode = parse_wake_mode(params.get("wakeMode"));
     let isolation = params.get("isolation").and_then(parse_isolation);
+    let policy = parse_policy(params)?;
 
     let input = CronJobCreate {
         name: name.to_string(),
@@ -112,6 +113,7 @@ pub(super) fn handle_cron_add(
         wake_mode,
         payload,
         isolation,
+        policy,
     };
 
     let job = state.cron_scheduler.add(input).map_err(|e| match e {
@@ -205,6 +207,7 @@ pub(super) fn handle_cron_update(
             None => None,
         },
         isolation: params.get("isolation").and_then(parse_isolation),
+        policy: parse_policy(params)?,
     };
 
     let job = state
@@ -327,15 +330,14 @@ pub(super) fn handle_cron_run(
         );
 
         // Spawn async payload execution if there's a payload
-        if let Some(payload) = result.payload.clone() {
+        // Manual runs start now whatever the job's wake mode.
+        if let Some(job) = job.clone().filter(|_| result.payload.is_some()) {
             let state_clone = state.clone();
             let job_id_owned = job_id.to_string();
-            let job_name = job.as_ref().map(|j| j.name.clone());
+            let job_name = Some(job.name.clone());
             tokio::spawn(async move {
                 let start = std::time::Instant::now();
-                let outcome =
-                    crate::cron::executor::execute_payload(&job_id_owned, &payload, &state_clone)
-                        .await;
+                let outcome = crate::cron::executor::execute_payload(&job, &state_clone).await;
                 let duration_ms = start.elapsed().as_millis() as u64;
 
                 let (status, error) = match outcome {
@@ -590,6 +592,18 @@ fn parse_isolation(value: &Value) -> Option<CronIsolation> {
     })
 }
 
+/// Parse the run policies (`catchUp`, `jitterMs`, `concurrency`,
+/// `maxRetries`, `retryBackoffMs`) from the job params.
+fn parse_policy(params: &Value) -> Result<CronRunPolicy, ErrorShape> {
+    serde_json::from_value(params.clone()).map_err(|e| {
+        error_shape(
+            ERROR_INVALID_REQUEST,
+            &format!("invalid run policy: {}", e),
+            None,
+        )
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -719,4 +733,29 @@ mod tests {
             CronWakeMode::NextHeartbeat
         );
     }
+
+    #[test]
+    fn test_parse_policy() {
+        let value = json!({
+            "name": "nightly",
+            "catchUp": { "mode": "run-all-missed", "maxLagMs": 3600000 },
+            "jitterMs": 5000,
+            "concurrency": "queue",
+            "maxRetries": 3
+        });
+        let policy = parse_policy(&value).unwrap();
+        let catch_up = policy.catch_up.unwrap();
+        assert_eq!(catch_up.mode, crate::cron::CronCatchUpMode::RunAllMissed);
+        assert_eq!(catch_up.max_lag_ms, Some(3_600_000));
+        assert_eq!(policy.jitter_ms, Some(5000));
+        assert_eq!(
+            policy.concurrency,
+            Some(crate::cron::CronConcurrency::Queue)
+        );
+        assert_eq!(policy.max_retries, Some(3));
+        assert_eq!(policy.retry_backoff_ms, None);
+
+        assert_eq!(parse_policy(&json!({})).unwrap(), CronRunPolicy::default());
+        assert!(parse_policy(&json!({ "concurrency": "sometimes" })).is_err());
+    }
 }
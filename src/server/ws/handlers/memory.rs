@@ -0,0 +1,308 @@
+//! Memory handlers.
+//!
+//! This module implements the long-term memory methods:
+//! - memory.list: List memories (filter by agent, user, tags)
+//! - memory.get: Get one memory by ID
+//! - memory.search: Ranked full-text search
+//! - memory.set: Create, replace or patch a memory
+//! - memory.delete: Delete a memory
+
+use serde_json::{json, Value};
+
+use super::super::*;
+use crate::memory::{MemoryError, MemoryFilter, MemoryPatch, MemoryScope, MemoryWrite};
+
+/// Default and maximum page size for `memory.list` and `memory.search`
+const MEMORY_DEFAULT_LIMIT: usize = 100;
+const MEMORY_MAX_LIMIT: usize = 1000;
+
+fn memory_error(e: MemoryError) -> ErrorShape {
+    match e {
+        MemoryError::NotFound(_) | MemoryError::Invalid(_) | MemoryError::LimitExceeded(_) => {
+            error_shape(ERROR_INVALID_REQUEST, &e.to_string(), None)
+        }
+        MemoryError::Io(_) | MemoryError::Json(_) => {
+            error_shape(ERROR_UNAVAILABLE, &e.to_string(), None)
+        }
+    }
+}
+
+fn str_param<'a>(params: Option<&'a Value>, name: &str) -> Option<&'a str> {
+    params
+        .and_then(|p| p.get(name))
+        .and_then(|v| v.as_str())
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+}
+
+fn tags_param(params: Option<&Value>) -> Result<Option<Vec<String>>, ErrorShape> {
+    let Some(value) = params.and_then(|p| p.get("tags")) else {
+        return Ok(None);
+    };
+    let tags = value
+        .as_array()
+        .and_then(|items| {
+            items
+                .iter()
+                .map(|v| v.as_str().map(str::to_string))
+                .collect::<Option<Vec<_>>>()
+        })
+        .ok_or_else(|| {
+            error_shape(
+                ERROR_INVALID_REQUEST,
+                "tags must be an array of strings",
+                None,
+            )
+        })?;
+    Ok(Some(tags))
+}
+
+fn limit_param(params: Option<&Value>) -> usize {
+    params
+        .and_then(|p| p.get("limit"))
+        .and_then(|v| v.as_u64())
+        .map(|l| (l as usize).clamp(1, MEMORY_MAX_LIMIT))
+        .unwrap_or(MEMORY_DEFAULT_LIMIT)
+}
+
+/// Build a filter from `agentId`, `userId`, `scope` (`agent` | `user` |
+/// `visible` | `any`), `tags` and `includeExpired`.
+fn filter_params(params: Option<&Value>) -> Result<MemoryFilter, ErrorShape> {
+    let user_id = str_param(params, "userId").map(str::to_string);
+    let scope = match (str_param(params, "scope"), user_id) {
+        (Some("agent"), _) => MemoryScope::Agent,
+        (Some("user"), Some(user)) | (None, Some(user)) => MemoryScope::User(user),
+        (Some("visible"), user) => MemoryScope::Visible(user),
+        (Some("user"), None) => {
+            return Err(error_shape(
+                ERROR_INVALID_REQUEST,
+                "userId is required for scope \"user\"",
+                None,
+            ))
+        }
+        (Some("any") | None, _) => MemoryScope::Any,
+        (Some(other), _) => {
+            return Err(error_shape(
+                ERROR_INVALID_REQUEST,
+                &format!("invalid scope: {other}"),
+                None,
+            ))
+        }
+    };
+    Ok(MemoryFilter {
+        agent_id: str_param(params, "agentId").map(str::to_string),
+        scope,
+        include_expired: params
+            .and_then(|p| p.get("includeExpired"))
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false),
+        ..Default::default()
+    }
+    .with_tags(tags_param(params)?.unwrap_or_default()))
+}
+
+/// Parse `expiresAt` (Unix ms, `null` clears) or `ttlSeconds`.
+fn expiry_param(params: &Value) -> Option<Option<u64>> {
+    if let Some(ttl) = params.get("ttlSeconds").and_then(|v| v.as_u64()) {
+        return Some(Some(
+            crate::memory::now_ms().saturating_add(ttl.saturating_mul(1000)),
+        ));
+    }
+    match params.get("expiresAt") {
+        Some(Value::Null) => Some(None),
+        Some(v) => v.as_u64().map(Some),
+        None => None,
+    }
+}
+
+/// List memories, most recently updated first.
+pub(super) fn handle_memory_list(
+    state: &WsServerState,
+    params: Option<&Value>,
+) -> Result<Value, ErrorShape> {
+    let filter = filter_params(params)?;
+    let limit = limit_param(params);
+    let entries = state.memory_store().list(&filter);
+    let total = entries.len();
+    let memories: Vec<Value> = entries
+        .into_iter()
+        .take(limit)
+        .map(|e| serde_json::to_value(e).unwrap_or(json!({})))
+        .collect();
+    Ok(json!({
+        "memories": memories,
+        "total": total,
+    }))
+}
+
+/// Get one memory by `id`.
+pub(super) fn handle_memory_get(
+    state: &WsServerState,
+    params: Option<&Value>,
+) -> Result<Value, ErrorShape> {
+    let id = str_param(params, "id")
+        .ok_or_else(|| error_shape(ERROR_INVALID_REQUEST, "id is required", None))?;
+    let entry = state
+        .memory_store()
+        .get(id)
+        .ok_or_else(|| memory_error(MemoryError::NotFound(id.to_string())))?;
+    Ok(json!({ "memory": entry }))
+}
+
+/// Ranked full-text search (`query`, plus the `memory.list` filters).
+pub(super) fn handle_memory_search(
+    state: &WsServerState,
+    params: Option<&Value>,
+) -> Result<Value, ErrorShape> {
+    let query = str_param(params, "query")
+        .ok_or_else(|| error_shape(ERROR_INVALID_REQUEST, "query is required", None))?;
+    let filter = filter_params(params)?;
+    let results = state
+        .memory_store()
+        .search(query, &filter, limit_param(params));
+    Ok(json!({ "results": results }))
+}
+
+/// Create or replace a memory by `agentId`/`userId`/`key`, or patch one by
+/// `id`.
+pub(super) fn handle_memory_set(
+    state: &WsServerState,
+    params: Option<&Value>,
+) -> Result<Value, ErrorShape> {
+    let p = params.ok_or_else(|| error_shape(ERROR_INVALID_REQUEST, "params required", None))?;
+    let content = p
+        .get("content")
+        .and_then(|v| v.as_str())
+        .map(str::to_string);
+    let tags = tags_param(params)?;
+    let expires_at = expiry_param(p);
+
+    let entry = if let Some(id) = str_param(params, "id") {
+        state
+            .memory_store()
+            .update(
+                id,
+                MemoryPatch {
+                    content,
+                    tags,
+                    expires_at,
+                },
+            )
+            .map_err(memory_error)?
+    } else {
+        let key = str_param(params, "key")
+            .ok_or_else(|| error_shape(ERROR_INVALID_REQUEST, "id or key is required", None))?;
+        let content = content
+            .ok_or_else(|| error_shape(ERROR_INVALID_REQUEST, "content is required", None))?;
+        state
+            .memory_store()
+            .put(MemoryWrite {
+                agent_id: str_param(params, "agentId")
+                    .unwrap_or(crate::memory::DEFAULT_AGENT_ID)
+                    .to_string(),
+                user_id: str_param(params, "userId").map(str::to_string),
+                key: key.to_string(),
+                content,
+                tags: tags.unwrap_or_default(),
+                expires_at: expires_at.flatten(),
+            })
+            .map_err(memory_error)?
+    };
+    Ok(json!({ "ok": true, "memory": entry }))
+}
+
+/// Delete a memory by `id`.
+pub(super) fn handle_memory_delete(
+    state: &WsServerState,
+    params: Option<&Value>,
+) -> Result<Value, ErrorShape> {
+    let id = str_param(params, "id")
+        .ok_or_else(|| error_shape(ERROR_INVALID_REQUEST, "id is required", None))?;
+    let entry = state.memory_store().delete(id).map_err(memory_error)?;
+    Ok(json!({ "ok": true, "id": entry.id }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn set(state: &WsServerState, params: Value) -> Value {
+        handle_memory_set(state, Some(&params)).unwrap()["memory"].clone()
+    }
+
+    #[test]
+    fn test_memory_set_list_and_filter() {
+        let state = WsServerState::new(WsServerConfig::default());
+        set(
+            &state,
+            json!({ "agentId": "main", "key": "tz", "content": "UTC", "tags": ["prefs"] }),
+        );
+        set(
+            &state,
+            json!({ "agentId": "main", "userId": "telegram:1", "key": "tz", "content": "CET" }),
+        );
+
+        let all = handle_memory_list(&state, None).unwrap();
+        assert_eq!(all["total"], 2);
+
+        let shared = handle_memory_list(&state, Some(&json!({ "scope": "agent" }))).unwrap();
+        assert_eq!(shared["total"], 1);
+        assert_eq!(shared["memories"][0]["content"], "UTC");
+
+        let user = handle_memory_list(&state, Some(&json!({ "userId": "telegram:1" }))).unwrap();
+        assert_eq!(user["total"], 1);
+        assert_eq!(user["memories"][0]["content"], "CET");
+
+        let tagged = handle_memory_list(&state, Some(&json!({ "tags": ["PREFS"] }))).unwrap();
+        assert_eq!(tagged["total"], 1);
+
+        assert!(handle_memory_list(&state, Some(&json!({ "scope": "user" }))).is_err());
+    }
+
+    #[test]
+    fn test_memory_patch_get_and_delete() {
+        let state = WsServerState::new(WsServerConfig::default());
+        let created = set(
+            &state,
+            json!({ "key": "k", "content": "v", "ttlSeconds": 60 }),
+        );
+        let id = created["id"].as_str().unwrap();
+        assert_eq!(created["agentId"], "default");
+        assert!(created["expiresAt"].is_u64());
+
+        let patched = set(
+            &state,
+            json!({ "id": id, "content": "v2", "expiresAt": null }),
+        );
+        assert_eq!(patched["content"], "v2");
+        assert!(patched.get("expiresAt").is_none());
+
+        let fetched = handle_memory_get(&state, Some(&json!({ "id": id }))).unwrap();
+        assert_eq!(fetched["memory"]["content"], "v2");
+
+        handle_memory_delete(&state, Some(&json!({ "id": id }))).unwrap();
+        assert!(handle_memory_get(&state, Some(&json!({ "id": id }))).is_err());
+        assert!(handle_memory_delete(&state, Some(&json!({ "id": id }))).is_err());
+    }
+
+    #[test]
+    fn test_memory_search() {
+        let state = WsServerState::new(WsServerConfig::default());
+        set(
+            &state,
+            json!({ "key": "pets", "content": "Has a dog called Rex" }),
+        );
+        set(
+            &state,
+            json!({ "key": "work", "content": "Works as a nurse" }),
+        );
+
+        let result = handle_memory_search(&state, Some(&json!({ "query": "dog name" }))).unwrap();
+        let results = result["results"].as_array().unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0]["key"], "pets");
+        assert!(results[0]["score"].as_f64().unwrap() > 0.0);
+
+        assert!(handle_memory_search(&state, Some(&json!({}))).is_err());
+    }
+}
@@ -10,7 +10,15 @@ use super::super::*;
 use crate::agent::DEFAULT_MODEL;
 
 /// List available models
-pub(super) fn handle_models_list() -> Result<Value, ErrorShape> {
+pub(super) fn handle_models_list(state: &WsServerState) -> Result<Value, ErrorShape> {
+    Ok(json!({ "models": model_catalog(state) }))
+}
+
+/// Models from config, the Anthropic defaults, and provider plugins.
+///
+/// Plugin models are addressed as `<plugin-id>:<model>`. Shared with the
+/// OpenAI-compatible `/v1/models` endpoint.
+pub(crate) fn model_catalog(state: &WsServerState) -> Vec<Value> {
     let cfg = config::load_config().unwrap_or(Value::Object(serde_json::Map::new()));
     let mut models = Vec::new();
 
@@ -98,11 +106,34 @@ pub(super) fn handle_models_list() -> Result<Value, ErrorShape> {
         }));
     }
 
-    Ok(json!({ "models": models }))
+    if let Some(registry) = state.plugin_registry() {
+        for (plugin_id, plugin_models) in registry.get_provider_models() {
+            for model in plugin_models {
+                let full_id = format!("{}:{}", plugin_id, model.id);
+                if models.iter().any(|m| m.get("id") == Some(&json!(full_id))) {
+                    continue;
+                }
+                models.push(json!({
+                    "id": full_id,
+                    "alias": null,
+                    "label": model.name,
+                    "provider": plugin_id,
+                    "model": model.id
+                }));
+            }
+        }
+    }
+
+    models
 }
 
 /// List available agents
 pub(super) fn handle_agents_list() -> Result<Value, ErrorShape> {
+    Ok(agent_catalog())
+}
+
+/// Configured agents (or the built-in default) with the session defaults.
+pub(crate) fn agent_catalog() -> Value {
     let cfg = config::load_config().unwrap_or(Value::Object(serde_json::Map::new()));
     let mut agents = Vec::new();
     let mut default_id: Option<String> = None;
@@ -200,13 +231,13 @@ pub(super) fn handle_agents_list() -> Result<Value, ErrorShape> {
         }
     }
 
-    Ok(json!({
+    json!({
         "defaultId": default_id.unwrap_or_else(|| "default".to_string()),
         "mainKey": main_key.unwrap_or_else(|| "main".to_string()),
         "scope": scope.unwrap_or_else(|| "per-sender".to_string()),
         "agents": agents,
         "count": agents.len()
-    }))
+    })
 }
 
 #[cfg(test)]
@@ -215,12 +246,83 @@ mod tests {
 
     #[test]
     fn test_models_list() {
-        let result = handle_models_list().unwrap();
+        let state = WsServerState::new(WsServerConfig::default());
+        let result = handle_models_list(&state).unwrap();
         assert!(result.get("models").is_some());
         let models = result["models"].as_array().unwrap();
         assert!(!models.is_empty());
     }
 
+    struct StubProvider;
+
+    impl crate::plugins::ProviderPluginInstance for StubProvider {
+        fn get_info(&self) -> Result<crate::plugins::ProviderInfo, crate::plugins::BindingError> {
+            Err(crate::plugins::BindingError::CallError("stub".to_string()))
+        }
+        fn list_models(
+            &self,
+        ) -> Result<Vec<crate::plugins::ModelDefinition>, crate::plugins::BindingError> {
+            Ok(Vec::new())
+        }
+        fn is_ready(&self) -> Result<bool, crate::plugins::BindingError> {
+            Ok(true)
+        }
+        fn complete(
+            &self,
+            _req: crate::plugins::ProviderCompletionRequest,
+        ) -> Result<crate::plugins::ProviderCompletionResponse, crate::plugins::BindingError>
+        {
+            Err(crate::plugins::BindingError::CallError("stub".to_string()))
+        }
+        fn complete_stream(
+            &self,
+            _req: crate::plugins::ProviderCompletionRequest,
+        ) -> Result<String, crate::plugins::BindingError> {
+            Err(crate::plugins::BindingError::CallError("stub".to_string()))
+        }
+        fn stream_next(
+            &self,
+            _stream_id: &str,
+        ) -> Result<Option<crate::plugins::ProviderStreamChunk>, crate::plugins::BindingError>
+        {
+            Ok(None)
+        }
+        fn stream_cancel(&self, _stream_id: &str) -> Result<(), crate::plugins::BindingError> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_models_list_includes_provider_plugin_models() {
+        let registry = Arc::new(crate::plugins::PluginRegistry::new());
+        registry.register_provider(
+            "gw".to_string(),
+            Arc::new(StubProvider),
+            vec![crate::plugins::ModelDefinition {
+                id: "qwen-max".to_string(),
+                name: "Qwen Max".to_string(),
+                api: None,
+                reasoning: false,
+                input: vec![crate::plugins::InputModality::Text],
+                cost: crate::plugins::ModelCost::default(),
+                context_window: 32_000,
+                max_tokens: 8_000,
+                headers: None,
+                max_tokens_field: None,
+            }],
+        );
+        let state = WsServerState::new(WsServerConfig::default()).with_plugin_registry(registry);
+
+        let result = handle_models_list(&state).unwrap();
+        let models = result["models"].as_array().unwrap();
+        let entry = models
+            .iter()
+            .find(|m| m["id"] == "gw:qwen-max")
+            .expect("plugin model listed");
+        assert_eq!(entry["provider"], "gw");
+        assert_eq!(entry["label"], "Qwen Max");
+    }
+
     #[test]
     fn test_agents_list() {
         let result = handle_agents_list().unwrap();
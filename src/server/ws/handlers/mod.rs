@@ -10,6 +10,7 @@ mod cron;
 mod device;
 mod exec;
 mod logs;
+mod memory;
 mod misc;
 mod node;
 pub(crate) mod sessions;
@@ -28,6 +29,7 @@ pub(super) use cron::*;
 use device::*;
 pub(super) use exec::*;
 use logs::*;
+use memory::*;
 use misc::*;
 pub(super) use node::*;
 pub(super) use sessions::*;
@@ -44,8 +46,10 @@ pub(super) use voicewake::*;
 pub(crate) use config::{
     broadcast_config_changed, map_validation_issues, persist_config_file, read_config_snapshot,
 };
+pub(crate) use misc::{agent_catalog, model_catalog};
 pub use sessions::AgentRunRegistry;
 pub use sessions::AgentRunStatus;
+pub(crate) use tts::{resolve_openai_api_key, synthesize_openai_speech, OPENAI_AUDIO_FORMATS};
 pub use usage::record_usage;
 pub(super) use wizard::*;
 
@@ -92,7 +96,8 @@ fn build_status_response(state: &WsServerState) -> Value {
         "sessions": {
             "count": sessions.len(),
             "recent": recent_sessions
-        }
+        },
+        "outbound": state.message_pipeline.stats()
     })
 }
 
@@ -158,7 +163,7 @@ pub(super) const NODE_ONLY_METHODS: [&str; 3] = ["node.invoke.result", "node.eve
 ///
 /// Per Node.js gateway: config.*, wizard.*, update.*, skills.install/update,
 /// channels.logout, sessions.*, and cron.* require operator.admin for operators.
-const OPERATOR_ADMIN_REQUIRED_METHODS: [&str; 43] = [
+const OPERATOR_ADMIN_REQUIRED_METHODS: [&str; 45] = [
     "config.get",
     "config.set",
     "config.apply",
@@ -204,6 +209,9 @@ const OPERATOR_ADMIN_REQUIRED_METHODS: [&str; 43] = [
     "cron.update",
     "cron.remove",
     "cron.run",
+    // Memory
+    "memory.set",
+    "memory.delete",
     // Channels
     "channels.logout",
     // System
@@ -244,6 +252,7 @@ const READ_METHODS: &[&str] = &[
     "node.describe",
     "node.pair.list",
     "device.pair.list",
+    "channels.pair.list",
     "exec.approvals.get",
     "exec.approvals.node.get",
     "usage.status",
@@ -255,6 +264,10 @@ const READ_METHODS: &[&str] = &[
     "update.status",
     "update.releaseNotes",
     "logs.tail",
+    "deadletters.list",
+    "memory.list",
+    "memory.get",
+    "memory.search",
     "system-presence",
     "system.info",
 ];
@@ -319,6 +332,10 @@ const WRITE_METHODS: &[&str] = &[
     "set-heartbeats",
     "wake",
     "send",
+    "deadletters.retry",
+    "deadletters.purge",
+    "memory.set",
+    "memory.delete",
 ];
 
 /// Admin methods (requires admin role, or operator with specific scopes).
@@ -329,6 +346,9 @@ const ADMIN_METHODS: &[&str] = &[
     "device.pair.reject",
     "device.token.rotate",
     "device.token.revoke",
+    "channels.pair.approve",
+    "channels.pair.reject",
+    "channels.senders.revoke",
     "node.pair.request",
     "node.pair.approve",
     "node.pair.reject",
@@ -374,6 +394,9 @@ pub(super) fn get_method_specific_scope(method: &str) -> Option<&'static str> {
         | "device.pair.reject"
         | "device.token.rotate"
         | "device.token.revoke"
+        | "channels.pair.approve"
+        | "channels.pair.reject"
+        | "channels.senders.revoke"
         | "node.pair.request"
         | "node.pair.approve"
         | "node.pair.reject"
@@ -654,7 +677,6 @@ fn dispatch_sessions(
         "sessions.patch" => Some(handle_sessions_patch(state, params)),
         "sessions.reset" => Some(handle_sessions_reset(state, params)),
         "sessions.delete" => Some(handle_sessions_delete(state, params)),
-        "sessions.compact" => Some(handle_sessions_compact(state, params)),
         "sessions.archive" => Some(handle_sessions_archive(state, params)),
         "sessions.restore" => Some(handle_sessions_restore(state, params)),
         "sessions.archives" => Some(handle_sessions_archives(state, params)),
@@ -796,6 +818,10 @@ pub(super) async fn dispatch_method(
         // Channels
         "channels.status" => handle_channels_status(state),
         "channels.logout" => handle_channels_logout(params, state),
+        "channels.pair.list" => handle_channels_pair_list(state),
+        "channels.pair.approve" => handle_channels_pair_approve(params, state),
+        "channels.pair.reject" => handle_channels_pair_reject(params, state),
+        "channels.senders.revoke" => handle_channels_senders_revoke(params, state),
 
         // Agent
         "agent" => handle_agent(params, state.clone(), conn),
@@ -804,9 +830,12 @@ pub(super) async fn dispatch_method(
 
         // Chat
         "chat.history" => handle_chat_history(state, params),
-        "chat.send" => handle_chat_send(state.clone(), params, conn),
+        "chat.send" => handle_chat_send(state.clone(), params, conn).await,
         "chat.abort" => handle_chat_abort(state, params),
 
+        // Sessions (async)
+        "sessions.compact" => handle_sessions_compact(state, params).await,
+
         // TTS async
         "tts.convert" => handle_tts_convert(params).await,
         "tts.speak" => handle_tts_speak(params).await,
@@ -828,7 +857,7 @@ pub(super) async fn dispatch_method(
         "talk.devices" => handle_talk_devices(),
 
         // Models/agents/skills
-        "models.list" => handle_models_list(),
+        "models.list" => handle_models_list(state),
         "agents.list" => handle_agents_list(),
         "skills.status" => handle_skills_status(),
         "skills.bins" => handle_skills_bins(),
@@ -854,11 +883,21 @@ pub(super) async fn dispatch_method(
         // Logs
         "logs.tail" => handle_logs_tail(params),
 
+        // Memory
+        "memory.list" => handle_memory_list(state, params),
+        "memory.get" => handle_memory_get(state, params),
+        "memory.search" => handle_memory_search(state, params),
+        "memory.set" => handle_memory_set(state, params),
+        "memory.delete" => handle_memory_delete(state, params),
+
         // Misc
         "last-heartbeat" => handle_last_heartbeat(state),
         "set-heartbeats" => handle_set_heartbeats(state, params),
         "wake" => handle_wake(state, params),
         "send" => handle_send(state, params, conn),
+        "deadletters.list" => handle_deadletters_list(state, params),
+        "deadletters.retry" => handle_deadletters_retry(state, params),
+        "deadletters.purge" => handle_deadletters_purge(state, params),
         "system.info" => handle_system_info(state),
         "system-presence" => handle_system_presence(state),
         "system-event" => handle_system_event(params, state, conn),
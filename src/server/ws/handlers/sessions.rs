@@ -1406,7 +1406,7 @@ pub(super) fn handle_sessions_purge_user(
     }))
 }
 
-pub(super) fn handle_sessions_compact(
+pub(super) async fn handle_sessions_compact(
     state: &WsServerState,
     params: Option<&Value>,
 ) -> Result<Value, ErrorShape> {
@@ -1467,20 +1467,42 @@ pub(super) fn handle_sessions_compact(
         }));
     }
 
-    let compacted = state
-        .session_store
-        .compact_session(
-            &session.id,
-            keep_recent,
-            |messages: &[sessions::ChatMessage]| format!("Compacted {} messages.", messages.len()),
+    // Summarize with the session agent's compaction model; without a
+    // provider an extractive summary is stored instead
+    let cfg = config::load_config().unwrap_or(Value::Object(serde_json::Map::new()));
+    let mut agent_config = crate::agent::AgentConfig::default();
+    crate::agent::apply_agent_config_from_settings(
+        &mut agent_config,
+        &cfg,
+        session.metadata.agent_id.as_deref(),
+    );
+    let provider = state.llm_provider();
+    let compacted = crate::agent::compaction::compact_session(
+        state,
+        &session,
+        provider.as_deref(),
+        agent_config.compaction.summary_model(&agent_config.model),
+        agent_config.compaction.max_tokens,
+        keep_recent,
+        crate::agent::compaction::CompactionTrigger::Manual,
+    )
+    .await
+    .map_err(|err| {
+        error_shape(
+            ERROR_UNAVAILABLE,
+            &format!("session compact failed: {}", err),
+            None,
         )
-        .map_err(|err| {
-            error_shape(
-                ERROR_UNAVAILABLE,
-                &format!("session compact failed: {}", err),
-                None,
-            )
-        })?;
+    })?;
+
+    let Some(compacted) = compacted else {
+        return Ok(json!({
+            "ok": true,
+            "key": session.session_key,
+            "compacted": false,
+            "reason": "cancelled"
+        }));
+    };
 
     // Return the compaction result
     Ok(json!({
@@ -1488,7 +1510,8 @@ pub(super) fn handle_sessions_compact(
         "key": session.session_key,
         "compacted": compacted.messages_compacted > 0,
         "kept": keep_recent,
-        "messagesCompacted": compacted.messages_compacted
+        "messagesCompacted": compacted.messages_compacted,
+        "summary": compacted.last_summary
     }))
 }
 
@@ -1908,6 +1931,20 @@ fn setup_agent_session(
     Ok((run_id, session_key_out, cancel_token))
 }
 
+/// Append the user message and register an agent run for a session driven
+/// from outside the WebSocket API (e.g. the OpenAI-compatible endpoints).
+/// Returns the run's cancellation token.
+pub(crate) fn register_session_run(
+    state: &Arc<WsServerState>,
+    session: sessions::Session,
+    message: &str,
+    run_id: &str,
+) -> Result<CancellationToken, String> {
+    setup_agent_session(state, session, message, run_id)
+        .map(|(_, _, cancel_token)| cancel_token)
+        .map_err(|err| err.message)
+}
+
 pub(super) fn handle_agent(
     params: Option<&Value>,
     state: Arc<WsServerState>,
@@ -2253,12 +2290,29 @@ pub(super) fn handle_chat_history(
         .map_err(|err| error_shape(ERROR_UNAVAILABLE, &err.to_string(), None))?
         .into_iter()
         .map(|m| {
-            json!({
+            let mut entry = json!({
                 "id": m.id,
                 "role": role_to_string(m.role),
                 "content": m.content,
                 "ts": m.created_at
-            })
+            });
+            // Describe attachments without exposing media store paths
+            let attachments =
+                crate::media::attachments::attachments_from_metadata(m.metadata.as_ref());
+            if !attachments.is_empty() {
+                entry["attachments"] = attachments
+                    .iter()
+                    .map(|a| {
+                        json!({
+                            "type": a.kind,
+                            "mimeType": a.media_type,
+                            "fileName": a.name,
+                            "size": a.size,
+                        })
+                    })
+                    .collect();
+            }
+            entry
         })
         .collect::<Vec<_>>();
 
@@ -2282,7 +2336,10 @@ pub(super) fn handle_chat_history(
 /// - The caller typically polls for completion or uses WebSocket events
 ///
 /// ## Parameters
-/// - `message` (required): The user message to send
+/// - `message` (required unless `attachments` is given): The user message to send
+/// - `attachments` (optional): `[{ mimeType, content, fileName? }]` with base64
+///   `content`; images and documents are stored in the media store and passed
+///   to the model alongside the message
 /// - `idempotencyKey` (required): Unique key for this request (becomes run ID)
 /// - `sessionId` or `sessionKey` (one required): Identifies the session
 /// - `stream` (optional): Whether to stream responses (defaults to true)
@@ -2303,11 +2360,117 @@ struct ChatSendParams<'a> {
     session_id: Option<&'a str>,
     session_key: Option<String>,
     message: &'a str,
+    attachments: Vec<ChatAttachmentInput>,
     idempotency_key: &'a str,
     stream: bool,
     trigger_agent: bool,
 }
 
+/// Maximum number of attachments accepted by a single `chat.send`.
+const MAX_CHAT_ATTACHMENTS: usize = 8;
+
+/// A decoded attachment from `chat.send` params, not yet stored.
+struct ChatAttachmentInput {
+    mime_type: String,
+    bytes: Vec<u8>,
+    file_name: Option<String>,
+}
+
+/// Parse and decode the optional `attachments` array of `chat.send`.
+fn parse_chat_attachments(params: Option<&Value>) -> Result<Vec<ChatAttachmentInput>, ErrorShape> {
+    let Some(items) = params.and_then(|v| v.get("attachments")) else {
+        return Ok(Vec::new());
+    };
+    let items = items
+        .as_array()
+        .ok_or_else(|| error_shape(ERROR_INVALID_REQUEST, "attachments must be an array", None))?;
+    if items.len() > MAX_CHAT_ATTACHMENTS {
+        return Err(error_shape(
+            ERROR_INVALID_REQUEST,
+            &format!("at most {} attachments are allowed", MAX_CHAT_ATTACHMENTS),
+            None,
+        ));
+    }
+    items
+        .iter()
+        .map(|item| {
+            let mime_type = item
+                .get("mimeType")
+                .and_then(|v| v.as_str())
+                .map(|s| s.trim())
+                .filter(|s| s.contains('/'))
+                .ok_or_else(|| {
+                    error_shape(
+                        ERROR_INVALID_REQUEST,
+                        "attachment mimeType is required",
+                        None,
+                    )
+                })?;
+            let content = item
+                .get("content")
+                .and_then(|v| v.as_str())
+                .ok_or_else(|| {
+                    error_shape(
+                        ERROR_INVALID_REQUEST,
+                        "attachment content is required",
+                        None,
+                    )
+                })?;
+            let bytes = crate::media::attachments::decode_base64_payload(content)
+                .map_err(|e| error_shape(ERROR_INVALID_REQUEST, &e, None))?;
+            if bytes.is_empty() {
+                return Err(error_shape(
+                    ERROR_INVALID_REQUEST,
+                    "attachment content is empty",
+                    None,
+                ));
+            }
+            let file_name = item
+                .get("fileName")
+                .and_then(|v| v.as_str())
+                .map(|s| s.trim())
+                .filter(|s| !s.is_empty())
+                .map(String::from);
+            Ok(ChatAttachmentInput {
+                mime_type: mime_type.to_string(),
+                bytes,
+                file_name,
+            })
+        })
+        .collect()
+}
+
+/// Write `chat.send` attachments to the media store and return the message
+/// metadata referencing them.
+async fn store_chat_attachments(
+    attachments: Vec<ChatAttachmentInput>,
+) -> Result<Option<Value>, ErrorShape> {
+    use crate::media::{attachments, MediaStore, StoreConfig};
+
+    if attachments.is_empty() {
+        return Ok(None);
+    }
+    let store_error = |e: crate::media::StoreError| {
+        error_shape(
+            ERROR_UNAVAILABLE,
+            &format!("attachment store failed: {}", e),
+            None,
+        )
+    };
+    let store = MediaStore::new(StoreConfig::default())
+        .await
+        .map_err(store_error)?;
+    let mut refs = Vec::with_capacity(attachments.len());
+    for input in attachments {
+        let stored =
+            attachments::store_attachment(&store, input.bytes, &input.mime_type, input.file_name)
+                .await
+                .map_err(store_error)?;
+        refs.push(stored);
+    }
+    Ok(Some(attachments::attachments_metadata(&refs)))
+}
+
 /// Extract and validate chat send parameters (session key, message, idempotencyKey, etc.).
 fn parse_chat_send_params<'a>(params: Option<&'a Value>) -> Result<ChatSendParams<'a>, ErrorShape> {
     let session_id = params
@@ -2316,11 +2479,12 @@ fn parse_chat_send_params<'a>(params: Option<&'a Value>) -> Result<ChatSendParam
         .map(|s| s.trim())
         .filter(|s| !s.is_empty());
     let session_key = extract_session_key(params);
+    let attachments = parse_chat_attachments(params)?;
     let message = params
         .and_then(|v| v.get("message"))
         .and_then(|v| v.as_str())
-        .ok_or_else(|| error_shape(ERROR_INVALID_REQUEST, "message is required", None))?;
-    if message.trim().is_empty() {
+        .unwrap_or("");
+    if message.trim().is_empty() && attachments.is_empty() {
         return Err(error_shape(
             ERROR_INVALID_REQUEST,
             "message is required",
@@ -2344,6 +2508,7 @@ fn parse_chat_send_params<'a>(params: Option<&'a Value>) -> Result<ChatSendParam
         session_id,
         session_key,
         message,
+        attachments,
         idempotency_key,
         stream,
         trigger_agent,
@@ -2413,12 +2578,12 @@ fn trigger_agent_if_enabled(
     (Some(run_id), status)
 }
 
-pub(super) fn handle_chat_send(
+pub(super) async fn handle_chat_send(
     state: Arc<WsServerState>,
     params: Option<&Value>,
     conn: &ConnectionContext,
 ) -> Result<Value, ErrorShape> {
-    let chat_params = parse_chat_send_params(params)?;
+    let mut chat_params = parse_chat_send_params(params)?;
     let default_session_key = state.default_session_key(&conn.conn_id);
     let cfg = config::load_config().unwrap_or(Value::Object(serde_json::Map::new()));
     let mut metadata = build_session_metadata(params, state.channel_registry());
@@ -2509,8 +2674,14 @@ pub(super) fn handle_chat_send(
         })?
     };
 
-    // Create and append the user message
-    let chat_message = sessions::ChatMessage::user(session.id.clone(), chat_params.message);
+    // Create and append the user message, with attachments stored by reference
+    let mut chat_message = sessions::ChatMessage::user(session.id.clone(), chat_params.message);
+    let attachment_count = chat_params.attachments.len();
+    if let Some(metadata) =
+        store_chat_attachments(std::mem::take(&mut chat_params.attachments)).await?
+    {
+        chat_message = chat_message.with_metadata(metadata);
+    }
     let message_id = chat_message.id.clone();
     state
         .session_store
@@ -2524,16 +2695,20 @@ pub(super) fn handle_chat_send(
         })?;
 
     // Emit chat event for the user message
+    let mut user_event = json!({
+        "role": "user",
+        "content": chat_params.message
+    });
+    if attachment_count > 0 {
+        user_event["attachments"] = json!(attachment_count);
+    }
     broadcast_chat_event(
         &state,
         chat_params.idempotency_key,
         &session.session_key,
         0, // First event in this run
         "delta",
-        Some(json!({
-            "role": "user",
-            "content": chat_params.message
-        })),
+        Some(user_event),
         None,
         None,
         None,
@@ -2919,6 +3094,79 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_parse_chat_send_attachments() {
+        // Attachments make the message text optional
+        let params = json!({
+            "idempotencyKey": "k1",
+            "attachments": [{ "mimeType": "image/png", "content": "cG5n", "fileName": "a.png" }]
+        });
+        let parsed = parse_chat_send_params(Some(&params)).unwrap();
+        assert_eq!(parsed.message, "");
+        assert_eq!(parsed.attachments.len(), 1);
+        assert_eq!(parsed.attachments[0].bytes, b"png");
+        assert_eq!(parsed.attachments[0].file_name.as_deref(), Some("a.png"));
+
+        let missing_message = json!({ "idempotencyKey": "k1" });
+        assert!(parse_chat_send_params(Some(&missing_message)).is_err());
+
+        let bad_base64 = json!({
+            "idempotencyKey": "k1",
+            "attachments": [{ "mimeType": "image/png", "content": "%%%" }]
+        });
+        assert!(parse_chat_send_params(Some(&bad_base64)).is_err());
+
+        let missing_mime = json!({
+            "idempotencyKey": "k1",
+            "attachments": [{ "content": "cG5n" }]
+        });
+        assert!(parse_chat_send_params(Some(&missing_mime)).is_err());
+
+        let too_many = json!({
+            "idempotencyKey": "k1",
+            "attachments": vec![json!({ "mimeType": "image/png", "content": "cG5n" }); MAX_CHAT_ATTACHMENTS + 1]
+        });
+        assert!(parse_chat_send_params(Some(&too_many)).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_handle_chat_send_stores_attachments_by_reference() {
+        let (state, _tmp) = make_state_with_temp_sessions();
+        let state = std::sync::Arc::new(state);
+        let params = json!({
+            "sessionKey": "attach-session",
+            "message": "what is this?",
+            "idempotencyKey": "attach-1",
+            "triggerAgent": false,
+            "attachments": [{ "mimeType": "application/pdf", "content": "JVBERi0=", "fileName": "doc.pdf" }]
+        });
+        handle_chat_send(state.clone(), Some(&params), &make_conn("c1"))
+            .await
+            .unwrap();
+
+        let session = state
+            .session_store
+            .get_session_by_key("attach-session")
+            .unwrap();
+        let history = state
+            .session_store
+            .get_history(&session.id, None, None)
+            .unwrap();
+        let refs =
+            crate::media::attachments::attachments_from_metadata(history[0].metadata.as_ref());
+        assert_eq!(refs.len(), 1);
+        assert_eq!(refs[0].media_type, "application/pdf");
+        assert_eq!(std::fs::read(&refs[0].path).unwrap(), b"%PDF-");
+        let _ = std::fs::remove_file(&refs[0].path);
+
+        let listed =
+            handle_chat_history(&state, Some(&json!({ "sessionKey": "attach-session" }))).unwrap();
+        let attachment = &listed["messages"][0]["attachments"][0];
+        assert_eq!(attachment["type"], "document");
+        assert_eq!(attachment["fileName"], "doc.pdf");
+        assert!(attachment.get("path").is_none());
+    }
+
     #[test]
     fn test_handle_sessions_create_with_key() {
         let (state, _tmp) = make_state_with_temp_sessions();
@@ -312,10 +312,26 @@ pub(super) fn handle_send(
         .and_then(|v| v.get("to"))
         .and_then(|v| v.as_str())
         .ok_or_else(|| error_shape(ERROR_INVALID_REQUEST, "to is required", None))?;
-    let message = params
+    let action = parse_send_action(params)?;
+    // Deletes, reactions and polls carry everything they need in the action.
+    let needs_message = matches!(
+        action,
+        None | Some(messages::outbound::ChannelAction::Edit { .. })
+    );
+    let message = match params
         .and_then(|v| v.get("message"))
         .and_then(|v| v.as_str())
-        .ok_or_else(|| error_shape(ERROR_INVALID_REQUEST, "message is required", None))?;
+    {
+        Some(m) => m,
+        None if !needs_message => "",
+        None => {
+            return Err(error_shape(
+                ERROR_INVALID_REQUEST,
+                "message is required",
+                None,
+            ))
+        }
+    };
     let idempotency_key = params
         .and_then(|v| v.get("idempotencyKey"))
         .and_then(|v| v.as_str())
@@ -329,6 +345,7 @@ pub(super) fn handle_send(
 
     let metadata = messages::outbound::MessageMetadata {
         recipient_id: Some(to.to_string()),
+        action,
         ..Default::default()
     };
 
@@ -370,6 +387,230 @@ pub(super) fn handle_send(
     Ok(response)
 }
 
+/// Parse the optional `action` of a send request.
+///
+/// `edit`, `delete` and `react` target a previously sent message by its
+/// channel-native `targetId`; `poll` takes a `poll` object with `question`,
+/// `options` and optional `allowMultiple`.
+fn parse_send_action(
+    params: Option<&Value>,
+) -> Result<Option<messages::outbound::ChannelAction>, ErrorShape> {
+    use messages::outbound::ChannelAction;
+
+    let Some(action) = params
+        .and_then(|v| v.get("action"))
+        .and_then(|v| v.as_str())
+    else {
+        return Ok(None);
+    };
+    let str_param = |key: &str| {
+        params
+            .and_then(|v| v.get(key))
+            .and_then(|v| v.as_str())
+            .map(|s| s.trim())
+            .filter(|s| !s.is_empty())
+            .map(String::from)
+            .ok_or_else(|| {
+                error_shape(
+                    ERROR_INVALID_REQUEST,
+                    &format!("{key} is required for action {action}"),
+                    None,
+                )
+            })
+    };
+
+    let action = match action {
+        "edit" => ChannelAction::Edit {
+            target_id: str_param("targetId")?,
+        },
+        "delete" => ChannelAction::Delete {
+            target_id: str_param("targetId")?,
+        },
+        "react" => ChannelAction::React {
+            target_id: str_param("targetId")?,
+            emoji: str_param("emoji")?,
+        },
+        "poll" => {
+            let poll = params.and_then(|v| v.get("poll")).ok_or_else(|| {
+                error_shape(
+                    ERROR_INVALID_REQUEST,
+                    "poll is required for action poll",
+                    None,
+                )
+            })?;
+            let input = crate::plugins::PollInput {
+                question: poll
+                    .get("question")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or_default()
+                    .to_string(),
+                options: poll
+                    .get("options")
+                    .and_then(|v| v.as_array())
+                    .map(|opts| {
+                        opts.iter()
+                            .filter_map(|o| o.as_str().map(String::from))
+                            .collect()
+                    })
+                    .unwrap_or_default(),
+                allow_multiple: poll
+                    .get("allowMultiple")
+                    .and_then(|v| v.as_bool())
+                    .unwrap_or(false),
+            };
+            input
+                .validate()
+                .map_err(|e| error_shape(ERROR_INVALID_REQUEST, &e, None))?;
+            ChannelAction::Poll {
+                question: input.question,
+                options: input.options,
+                allow_multiple: input.allow_multiple,
+            }
+        }
+        other => {
+            return Err(error_shape(
+                ERROR_INVALID_REQUEST,
+                &format!("unsupported send action: {other}"),
+                None,
+            ))
+        }
+    };
+    Ok(Some(action))
+}
+
+/// Default and maximum page size for `deadletters.list`
+const DEAD_LETTERS_DEFAULT_LIMIT: usize = 100;
+const DEAD_LETTERS_MAX_LIMIT: usize = 1000;
+
+/// Handle deadletters.list - permanently failed outbound messages, newest
+/// first, optionally filtered by `channel`.
+pub(super) fn handle_deadletters_list(
+    state: &WsServerState,
+    params: Option<&Value>,
+) -> Result<Value, ErrorShape> {
+    let channel = params
+        .and_then(|v| v.get("channel"))
+        .and_then(|v| v.as_str());
+    let limit = params
+        .and_then(|v| v.get("limit"))
+        .and_then(|v| v.as_u64())
+        .map(|l| (l as usize).min(DEAD_LETTERS_MAX_LIMIT))
+        .unwrap_or(DEAD_LETTERS_DEFAULT_LIMIT);
+
+    let entries: Vec<_> = state
+        .message_pipeline
+        .dead_letters()
+        .into_iter()
+        .rev()
+        .filter(|entry| channel.is_none_or(|c| entry.message.channel_id == c))
+        .collect();
+    let items: Vec<Value> = entries
+        .iter()
+        .take(limit)
+        .map(|entry| {
+            json!({
+                "messageId": entry.message.id.0,
+                "channel": entry.message.channel_id,
+                "content": entry.message.content,
+                "metadata": entry.message.metadata,
+                "attempts": entry.attempts,
+                "lastError": entry.last_error,
+                "createdAt": entry.message.created_at,
+                "failedAt": entry.updated_at,
+            })
+        })
+        .collect();
+
+    Ok(json!({
+        "deadLetters": items,
+        "total": entries.len(),
+    }))
+}
+
+/// Read `messageId` / `messageIds` from dead-letter params.
+fn parse_dead_letter_ids(params: Option<&Value>) -> Option<Vec<messages::outbound::MessageId>> {
+    let params = params?;
+    if let Some(id) = params.get("messageId").and_then(|v| v.as_str()) {
+        return Some(vec![messages::outbound::MessageId::from_string(id)]);
+    }
+    params
+        .get("messageIds")
+        .and_then(|v| v.as_array())
+        .map(|ids| {
+            ids.iter()
+                .filter_map(|v| v.as_str())
+                .map(messages::outbound::MessageId::from_string)
+                .collect()
+        })
+}
+
+/// Handle deadletters.retry - re-queue dead letters (`messageId`,
+/// `messageIds`, or `all: true`) with a fresh retry budget.
+pub(super) fn handle_deadletters_retry(
+    state: &WsServerState,
+    params: Option<&Value>,
+) -> Result<Value, ErrorShape> {
+    let all = params
+        .and_then(|v| v.get("all"))
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+    let ids = match parse_dead_letter_ids(params) {
+        Some(ids) => ids,
+        None if all => state
+            .message_pipeline
+            .dead_letters()
+            .into_iter()
+            .map(|entry| entry.message.id)
+            .collect(),
+        None => {
+            return Err(error_shape(
+                ERROR_INVALID_REQUEST,
+                "messageId, messageIds or all is required",
+                None,
+            ))
+        }
+    };
+
+    let mut retried = Vec::new();
+    let mut errors = Vec::new();
+    for id in ids {
+        match state.message_pipeline.retry_dead_letter(&id) {
+            Ok(_) => retried.push(id.0),
+            Err(e) => errors.push(json!({ "messageId": id.0, "error": e.to_string() })),
+        }
+    }
+
+    Ok(json!({
+        "retried": retried,
+        "errors": errors,
+    }))
+}
+
+/// Handle deadletters.purge - delete dead letters (`messageId`,
+/// `messageIds`, or `all: true`).
+pub(super) fn handle_deadletters_purge(
+    state: &WsServerState,
+    params: Option<&Value>,
+) -> Result<Value, ErrorShape> {
+    let all = params
+        .and_then(|v| v.get("all"))
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+    let purged = match parse_dead_letter_ids(params) {
+        Some(ids) => state.message_pipeline.purge_dead_letters(Some(&ids)),
+        None if all => state.message_pipeline.purge_dead_letters(None),
+        None => {
+            return Err(error_shape(
+                ERROR_INVALID_REQUEST,
+                "messageId, messageIds or all is required",
+                None,
+            ))
+        }
+    };
+
+    Ok(json!({ "purged": purged }))
+}
+
 /// Handle system-presence - returns list of connected clients (read-only, no params)
 /// Per Node semantics: returns the presence array directly, not wrapped in {ok, presence}
 /// Also applies TTL pruning and returns entries sorted by ts descending.
@@ -1034,6 +1275,73 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_handle_send_delete_action_without_message() {
+        let state = WsServerState::new(WsServerConfig::default());
+        let conn = make_test_conn();
+        let params = json!({
+            "to": "user123",
+            "action": "delete",
+            "targetId": "42",
+            "idempotencyKey": "key-del",
+            "channel": "telegram"
+        });
+
+        let result = handle_send(&state, Some(&params), &conn).unwrap();
+        let id =
+            crate::messages::outbound::MessageId(result["messageId"].as_str().unwrap().to_string());
+        let queued = state.message_pipeline.get_message(&id).unwrap();
+        assert_eq!(
+            queued.message.metadata.action,
+            Some(crate::messages::outbound::ChannelAction::Delete {
+                target_id: "42".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn test_handle_send_edit_action_requires_message() {
+        let state = WsServerState::new(WsServerConfig::default());
+        let conn = make_test_conn();
+        let params = json!({
+            "to": "user123",
+            "action": "edit",
+            "targetId": "42",
+            "idempotencyKey": "key-edit"
+        });
+
+        assert!(handle_send(&state, Some(&params), &conn).is_err());
+    }
+
+    #[test]
+    fn test_handle_send_rejects_invalid_poll() {
+        let state = WsServerState::new(WsServerConfig::default());
+        let conn = make_test_conn();
+        let params = json!({
+            "to": "user123",
+            "action": "poll",
+            "poll": { "question": "Lunch?", "options": ["Pizza"] },
+            "idempotencyKey": "key-poll"
+        });
+
+        let err = handle_send(&state, Some(&params), &conn).unwrap_err();
+        assert_eq!(err.code, "INVALID_REQUEST");
+    }
+
+    #[test]
+    fn test_handle_send_rejects_unknown_action() {
+        let state = WsServerState::new(WsServerConfig::default());
+        let conn = make_test_conn();
+        let params = json!({
+            "to": "user123",
+            "message": "hi",
+            "action": "pin",
+            "idempotencyKey": "key-pin"
+        });
+
+        assert!(handle_send(&state, Some(&params), &conn).is_err());
+    }
+
     #[test]
     fn test_handle_send_requires_idempotency_key() {
         let state = WsServerState::new(WsServerConfig::default());
@@ -1123,4 +1431,75 @@ mod tests {
         );
         assert_eq!(state.message_pipeline.queue_size("default"), 2);
     }
+
+    fn dead_letter(state: &WsServerState, channel: &str, text: &str) -> String {
+        use crate::messages::outbound::{MessageContent, OutboundContext, OutboundMessage};
+        let id = state
+            .message_pipeline
+            .queue(
+                OutboundMessage::new(channel, MessageContent::text(text)),
+                OutboundContext::new(),
+            )
+            .unwrap()
+            .message_id;
+        state
+            .message_pipeline
+            .mark_failed(&id, "chat not found")
+            .unwrap();
+        id.0
+    }
+
+    #[test]
+    fn test_handle_deadletters_list_newest_first_with_filter() {
+        let state = WsServerState::new(WsServerConfig::default());
+        let first = dead_letter(&state, "telegram", "one");
+        let second = dead_letter(&state, "telegram", "two");
+        dead_letter(&state, "slack", "three");
+
+        let result =
+            handle_deadletters_list(&state, Some(&json!({ "channel": "telegram" }))).unwrap();
+        assert_eq!(result["total"], 2);
+        let items = result["deadLetters"].as_array().unwrap();
+        assert_eq!(items[0]["messageId"], second);
+        assert_eq!(items[1]["messageId"], first);
+        assert_eq!(items[0]["lastError"], "chat not found");
+        assert_eq!(items[0]["content"]["text"], "two");
+
+        let result = handle_deadletters_list(&state, Some(&json!({ "limit": 1 }))).unwrap();
+        assert_eq!(result["total"], 3);
+        assert_eq!(result["deadLetters"].as_array().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_handle_deadletters_retry() {
+        let state = WsServerState::new(WsServerConfig::default());
+        let id = dead_letter(&state, "telegram", "one");
+
+        assert!(handle_deadletters_retry(&state, None).is_err());
+
+        let result =
+            handle_deadletters_retry(&state, Some(&json!({ "messageIds": [id, "missing"] })))
+                .unwrap();
+        assert_eq!(result["retried"], json!([id]));
+        assert_eq!(result["errors"][0]["messageId"], "missing");
+        assert_eq!(state.message_pipeline.queue_size("telegram"), 1);
+        assert_eq!(state.message_pipeline.dead_letter_count(), 0);
+    }
+
+    #[test]
+    fn test_handle_deadletters_purge() {
+        let state = WsServerState::new(WsServerConfig::default());
+        let id = dead_letter(&state, "telegram", "one");
+        dead_letter(&state, "telegram", "two");
+        dead_letter(&state, "slack", "three");
+
+        assert!(handle_deadletters_purge(&state, Some(&json!({}))).is_err());
+
+        let result = handle_deadletters_purge(&state, Some(&json!({ "messageId": id }))).unwrap();
+        assert_eq!(result["purged"], 1);
+
+        let result = handle_deadletters_purge(&state, Some(&json!({ "all": true }))).unwrap();
+        assert_eq!(result["purged"], 2);
+        assert_eq!(state.message_pipeline.dead_letter_count(), 0);
+    }
 }
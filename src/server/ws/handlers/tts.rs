@@ -152,7 +152,7 @@ pub(super) fn handle_tts_disable() -> Result<Value, ErrorShape> {
 }
 
 /// Resolve the OpenAI API key from config or environment.
-fn resolve_openai_api_key() -> Option<String> {
+pub(crate) fn resolve_openai_api_key() -> Option<String> {
     // Try config first: models.providers.openai.apiKey
     if let Ok(cfg) = config::load_config() {
         if let Some(key) = cfg
@@ -200,16 +200,19 @@ fn validate_audio_format(raw: Option<&str>) -> Result<&'static str, ErrorShape>
 }
 
 /// Call the OpenAI TTS API and return raw audio bytes.
-async fn openai_tts_request(
+///
+/// Shared with the OpenAI-compatible `/v1/audio/speech` endpoint.
+pub(crate) async fn synthesize_openai_speech(
     api_key: &str,
+    model: &str,
     text: &str,
     voice: &str,
     format: &str,
     speed: f64,
-) -> Result<bytes::Bytes, ErrorShape> {
+) -> Result<bytes::Bytes, String> {
     let client = reqwest::Client::new();
     let body = json!({
-        "model": "tts-1",
+        "model": model,
         "input": text,
         "voice": voice,
         "response_format": format,
@@ -222,31 +225,18 @@ async fn openai_tts_request(
         .json(&body)
         .send()
         .await
-        .map_err(|e| {
-            error_shape(
-                ERROR_UNAVAILABLE,
-                &format!("OpenAI TTS request failed: {}", e),
-                None,
-            )
-        })?;
+        .map_err(|e| format!("OpenAI TTS request failed: {}", e))?;
 
     if !response.status().is_success() {
         let status = response.status();
         let err_body = response.text().await.unwrap_or_default();
-        return Err(error_shape(
-            ERROR_UNAVAILABLE,
-            &format!("OpenAI TTS API error ({}): {}", status, err_body),
-            None,
-        ));
+        return Err(format!("OpenAI TTS API error ({}): {}", status, err_body));
     }
 
-    response.bytes().await.map_err(|e| {
-        error_shape(
-            ERROR_UNAVAILABLE,
-            &format!("failed to read OpenAI TTS response: {}", e),
-            None,
-        )
-    })
+    response
+        .bytes()
+        .await
+        .map_err(|e| format!("failed to read OpenAI TTS response: {}", e))
 }
 
 /// Convert text to speech.
@@ -304,7 +294,10 @@ pub(super) async fn handle_tts_convert(params: Option<&Value>) -> Result<Value,
             )
         })?;
 
-        let audio_bytes = openai_tts_request(&api_key, text, &voice, audio_format, rate).await?;
+        let audio_bytes =
+            synthesize_openai_speech(&api_key, "tts-1", text, &voice, audio_format, rate)
+                .await
+                .map_err(|e| error_shape(ERROR_UNAVAILABLE, &e, None))?;
 
         let audio_b64 = base64::engine::general_purpose::STANDARD.encode(&audio_bytes);
 
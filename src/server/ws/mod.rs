@@ -24,8 +24,8 @@ use tracing::warn;
 use uuid::Uuid;
 
 use crate::{
-    agent, auth, channels, config, credentials, cron, devices, exec, messages, nodes, plugins,
-    sessions,
+    agent, auth, channels, config, credentials, cron, devices, exec, memory, messages, nodes,
+    plugins, sessions,
 };
 
 #[cfg(test)]
@@ -45,6 +45,16 @@ pub use handlers::AgentRunStatus;
 // Re-export AgentRun for use by cron executor and tests
 pub use handlers::sessions::AgentRun;
 
+// Re-export run setup for the OpenAI-compatible endpoints
+pub(crate) use handlers::sessions::{register_session_run, AgentRunResult};
+
+// Re-export the model/agent catalogs and speech synthesis for the
+// OpenAI-compatible HTTP endpoints
+pub(crate) use handlers::{
+    agent_catalog, model_catalog, resolve_openai_api_key, synthesize_openai_speech,
+    OPENAI_AUDIO_FORMATS,
+};
+
 // Re-export update functions for use by CLI
 pub(crate) use handlers::{apply_staged_update, cleanup_old_binaries};
 
@@ -92,7 +102,7 @@ const ALLOWED_CLIENT_IDS: [&str; 12] = [
 const ALLOWED_CLIENT_MODES: [&str; 7] =
     ["webchat", "cli", "ui", "backend", "node", "probe", "test"];
 
-const GATEWAY_METHODS: [&str; 123] = [
+const GATEWAY_METHODS: [&str; 135] = [
     // Health/status
     "health",
     "status",
@@ -209,6 +219,11 @@ const GATEWAY_METHODS: [&str; 123] = [
     "device.pair.reject",
     "device.token.rotate",
     "device.token.revoke",
+    // Channel sender pairing
+    "channels.pair.list",
+    "channels.pair.approve",
+    "channels.pair.reject",
+    "channels.senders.revoke",
     // Exec approvals
     "exec.approvals.get",
     "exec.approvals.set",
@@ -226,17 +241,26 @@ const GATEWAY_METHODS: [&str; 123] = [
     "usage.daily",
     "usage.monthly",
     "usage.reset",
+    // Memory
+    "memory.list",
+    "memory.get",
+    "memory.search",
+    "memory.set",
+    "memory.delete",
     // Misc
     "last-heartbeat",
     "set-heartbeats",
     "wake",
     "send",
+    "deadletters.list",
+    "deadletters.retry",
+    "deadletters.purge",
     "system-presence",
     "system-event",
     "system.info",
 ];
 
-const GATEWAY_EVENTS: [&str; 20] = [
+const GATEWAY_EVENTS: [&str; 22] = [
     "connect.challenge",
     "agent",
     "chat",
@@ -254,6 +278,8 @@ const GATEWAY_EVENTS: [&str; 20] = [
     "node.event",
     "device.pair.requested",
     "device.pair.resolved",
+    "channels.pair.requested",
+    "channels.pair.resolved",
     "voicewake.changed",
     "exec.approval.requested",
     "exec.approval.resolved",
@@ -381,7 +407,7 @@ pub struct HealthSnapshot {
 }
 
 #[derive(Debug, Clone)]
-struct HeartbeatState {
+pub(crate) struct HeartbeatState {
     enabled: bool,
     interval_ms: u64,
     last_heartbeat_ms: Option<u64>,
@@ -445,6 +471,8 @@ pub struct WsServerState {
     channel_registry: Arc<channels::ChannelRegistry>,
     message_pipeline: Arc<messages::outbound::MessagePipeline>,
     session_store: Arc<sessions::SessionStore>,
+    memory_store: Arc<memory::MemoryStore>,
+    sender_access: Arc<channels::access::SenderAccessRegistry>,
     event_seq: Mutex<u64>,
     /// Tracks connected client presence
     presence: Mutex<HashMap<String, PresenceEntry>>,
@@ -460,6 +488,8 @@ pub struct WsServerState {
     pub cron_scheduler: cron::CronScheduler,
     /// Agent run registry for tracking active/completed agent invocations
     pub agent_run_registry: Mutex<handlers::AgentRunRegistry>,
+    /// Per-run agent event listeners (HTTP endpoints that drive agent runs)
+    agent_event_listeners: Mutex<HashMap<String, Vec<mpsc::UnboundedSender<Value>>>>,
     /// System event history (enqueued via system-event method)
     system_event_history: Mutex<Vec<SystemEvent>>,
     /// LLM provider for agent execution (hot-swappable via RwLock)
@@ -514,6 +544,8 @@ impl WsServerState {
             session_store: Arc::new(sessions::SessionStore::with_base_path(
                 resolve_state_dir().join("sessions"),
             )),
+            memory_store: Arc::new(memory::MemoryStore::in_memory()),
+            sender_access: Arc::new(channels::access::SenderAccessRegistry::in_memory()),
             event_seq: Mutex::new(0),
             presence: Mutex::new(HashMap::new()),
             health_cache: Mutex::new(HealthSnapshot {
@@ -531,6 +563,7 @@ impl WsServerState {
             exec_manager: exec::ExecApprovalManager::new(),
             cron_scheduler: cron::CronScheduler::in_memory(),
             agent_run_registry: Mutex::new(handlers::AgentRunRegistry::new()),
+            agent_event_listeners: Mutex::new(HashMap::new()),
             system_event_history: Mutex::new(Vec::new()),
             llm_provider: parking_lot::RwLock::new(None),
             tools_registry: None,
@@ -545,6 +578,7 @@ impl WsServerState {
     ) -> Result<Self, WsConfigError> {
         let node_pairing = nodes::create_registry(state_dir.clone())?;
         let device_registry = devices::create_registry(state_dir.clone())?;
+        let sender_access = channels::access::create_registry(state_dir.clone())?;
         let connection_tracker = limits::ConnectionTracker::with_limits(
             config
                 .max_ws_connections
@@ -560,10 +594,14 @@ impl WsServerState {
             connections: Mutex::new(HashMap::new()),
             session_defaults: Mutex::new(HashMap::new()),
             channel_registry: channels::create_registry(),
-            message_pipeline: messages::outbound::create_pipeline(),
+            message_pipeline: messages::outbound::create_persistent_pipeline(
+                state_dir.join("outbound").join("queue.jsonl"),
+            ),
             session_store: Arc::new(sessions::SessionStore::with_base_path(
                 state_dir.join("sessions"),
             )),
+            memory_store: Arc::new(memory::create_store(&state_dir)),
+            sender_access,
             event_seq: Mutex::new(0),
             presence: Mutex::new(HashMap::new()),
             health_cache: Mutex::new(HealthSnapshot {
@@ -586,6 +624,7 @@ impl WsServerState {
                 scheduler
             },
             agent_run_registry: Mutex::new(handlers::AgentRunRegistry::new()),
+            agent_event_listeners: Mutex::new(HashMap::new()),
             system_event_history: Mutex::new(Vec::new()),
             llm_provider: parking_lot::RwLock::new(None),
             tools_registry: None,
@@ -604,6 +643,19 @@ impl WsServerState {
         self
     }
 
+    pub fn with_memory_store(mut self, store: Arc<memory::MemoryStore>) -> Self {
+        self.memory_store = store;
+        self
+    }
+
+    pub fn with_sender_access(
+        mut self,
+        registry: Arc<channels::access::SenderAccessRegistry>,
+    ) -> Self {
+        self.sender_access = registry;
+        self
+    }
+
     #[cfg(test)]
     pub(crate) fn with_session_store(mut self, store: Arc<sessions::SessionStore>) -> Self {
         self.session_store = store;
@@ -630,6 +682,45 @@ impl WsServerState {
         &self.session_store
     }
 
+    /// Get the long-term memory store.
+    pub fn memory_store(&self) -> &Arc<memory::MemoryStore> {
+        &self.memory_store
+    }
+
+    /// Get the inbound sender access registry.
+    pub fn sender_access(&self) -> &Arc<channels::access::SenderAccessRegistry> {
+        &self.sender_access
+    }
+
+    /// Receive the `agent` event payloads of one run, in addition to the
+    /// WebSocket broadcast. Call [`Self::unsubscribe_agent_events`] when done.
+    pub fn subscribe_agent_events(&self, run_id: &str) -> mpsc::UnboundedReceiver<Value> {
+        let (tx, rx) = mpsc::unbounded_channel();
+        self.agent_event_listeners
+            .lock()
+            .entry(run_id.to_string())
+            .or_default()
+            .push(tx);
+        rx
+    }
+
+    /// Drop all agent event listeners for a run.
+    pub fn unsubscribe_agent_events(&self, run_id: &str) {
+        self.agent_event_listeners.lock().remove(run_id);
+    }
+
+    /// Forward an agent event payload to the run's listeners, pruning closed
+    /// receivers.
+    fn notify_agent_event_listeners(&self, run_id: &str, payload: &Value) {
+        let mut listeners = self.agent_event_listeners.lock();
+        if let Some(senders) = listeners.get_mut(run_id) {
+            senders.retain(|tx| tx.send(payload.clone()).is_ok());
+            if senders.is_empty() {
+                listeners.remove(run_id);
+            }
+        }
+    }
+
     /// Get the configured session retention period in days, if any.
     pub fn session_retention_days(&self) -> Option<u32> {
         self.config.session_retention_days
@@ -860,8 +951,13 @@ impl WsServerState {
         self.heartbeat_state.lock().clone()
     }
 
+    /// Whether the heartbeat loop is running.
+    pub(crate) fn heartbeats_enabled(&self) -> bool {
+        self.heartbeat_state.lock().enabled
+    }
+
     /// Update heartbeat settings.
-    fn set_heartbeat_settings(&self, enabled: bool, interval_ms: u64) -> HeartbeatState {
+    pub(crate) fn set_heartbeat_settings(&self, enabled: bool, interval_ms: u64) -> HeartbeatState {
         let mut state = self.heartbeat_state.lock();
         state.enabled = enabled;
         state.interval_ms = interval_ms.clamp(MIN_HEARTBEAT_INTERVAL_MS, MAX_HEARTBEAT_INTERVAL_MS);
@@ -975,6 +1071,8 @@ pub enum WsConfigError {
     Nodes(#[from] nodes::NodePairingError),
     #[error(transparent)]
     Devices(#[from] devices::DevicePairingError),
+    #[error(transparent)]
+    SenderAccess(#[from] channels::access::SenderAccessError),
 }
 
 pub async fn build_ws_state_from_config() -> Result<Arc<WsServerState>, WsConfigError> {
@@ -1838,6 +1936,7 @@ pub fn spawn_heartbeat_task(state: Arc<WsServerState>) -> tokio::task::JoinHandl
             tokio::time::sleep(Duration::from_millis(interval_ms)).await;
             if state.heartbeat_snapshot().enabled {
                 broadcast_heartbeat(&state);
+                crate::cron::tick::run_deferred_jobs(&state);
             }
         }
     })
@@ -2944,7 +3043,9 @@ fn event_required_scope(event: &str) -> Option<&'static str> {
         "device.pair.requested"
         | "device.pair.resolved"
         | "node.pair.requested"
-        | "node.pair.resolved" => Some("operator.pairing"),
+        | "node.pair.resolved"
+        | "channels.pair.requested"
+        | "channels.pair.resolved" => Some("operator.pairing"),
         "exec.approval.requested" | "exec.approval.resolved" => Some("operator.approvals"),
         _ => None,
     }
@@ -3012,6 +3113,7 @@ pub fn broadcast_agent_event(
         "ts": now_ms(),
         "data": data
     });
+    state.notify_agent_event_listeners(run_id, &payload);
     broadcast_event(state, "agent", payload);
 }
 
@@ -3179,6 +3281,21 @@ pub fn broadcast_exec_approval_resolved(state: &WsServerState, request_id: &str,
     broadcast_event(state, "exec.approval.resolved", payload);
 }
 
+/// Broadcast a sender pairing request to operators with the pairing scope.
+pub fn broadcast_channel_pair_requested(
+    state: &WsServerState,
+    request: &channels::access::SenderPairingRequest,
+) {
+    let payload = json!({
+        "channel": request.channel,
+        "senderId": request.sender_id,
+        "code": request.code,
+        "expiresAtMs": request.expires_at_ms(),
+        "ts": request.created_at_ms
+    });
+    broadcast_event(state, "channels.pair.requested", payload);
+}
+
 /// Broadcast a shutdown event to all connections.
 /// This notifies clients that the server is shutting down.
 ///
@@ -1902,6 +1902,7 @@ fn test_cron_scheduler_integration() {
                 text: "Hello!".to_string(),
             },
             isolation: None,
+            policy: Default::default(),
         })
         .unwrap();
     assert_eq!(job.name, "Test Job");
@@ -2021,6 +2022,7 @@ fn test_cron_scheduler_event_integration() {
                 text: "Hello!".to_string(),
             },
             isolation: None,
+            policy: Default::default(),
         })
         .unwrap();
 
@@ -2700,6 +2702,9 @@ fn test_every_gateway_method_has_explicit_role_assignment() {
         "device.pair.reject",
         "device.token.rotate",
         "device.token.revoke",
+        "channels.pair.approve",
+        "channels.pair.reject",
+        "channels.senders.revoke",
         "node.pair.request",
         "node.pair.approve",
         "node.pair.reject",
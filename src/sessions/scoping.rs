@@ -21,7 +21,25 @@
 //! | `daily`  | Reset at midnight UTC each day                         |
 //! | `idle`   | Reset after N minutes of inactivity (default 60)       |
 //!
-//! Both the scope and the reset policy are configurable per channel via:
+//! ## Group activation
+//!
+//! Direct messages always reach the agent. In group chats the **activation**
+//! policy decides which messages do:
+//!
+//! | Trigger   | Fires when                                          |
+//! |-----------|-----------------------------------------------------|
+//! | `always`  | Every message (default)                             |
+//! | `mention` | The message mentions the bot                        |
+//! | `reply`   | The message replies to one of the bot's messages    |
+//! | `keyword` | The text contains one of `keywords` as a word       |
+//! | `prefix`  | The text starts with one of `prefixes` (default `/`) |
+//!
+//! Messages that trigger nothing can be kept as passive context in the
+//! session (`passiveContext: true`) so the agent sees the conversation it is
+//! eventually asked about.
+//!
+//! Scope, reset policy and activation are configurable per channel, with
+//! per-group activation overrides keyed by the channel's group ID:
 //!
 //! ```json5
 //! channels: {
@@ -31,12 +49,23 @@
 //!       reset: {
 //!         mode: "idle",            // or "manual", "daily"
 //!         idleMinutes: 30,
+//!       },
+//!       activation: {
+//!         mode: ["mention", "reply"], // or a single trigger
+//!         keywords: ["carapace"],
+//!         prefixes: ["/ask"],
+//!         passiveContext: true,
+//!       },
+//!       groups: {
+//!         "group-id": { activation: { mode: "always" } }
 //!       }
 //!     }
 //!   }
 //! }
 //! ```
 
+use std::collections::BTreeMap;
+
 use chrono::{TimeZone, Utc};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
@@ -115,6 +144,154 @@ impl std::fmt::Display for SessionResetPolicy {
     }
 }
 
+// ---------------------------------------------------------------------------
+// Group activation
+// ---------------------------------------------------------------------------
+
+/// Default command prefix for the `prefix` trigger.
+pub const DEFAULT_ACTIVATION_PREFIX: &str = "/";
+
+/// A condition under which a group message activates the agent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ActivationTrigger {
+    Mention,
+    Reply,
+    Keyword,
+    Prefix,
+}
+
+impl ActivationTrigger {
+    /// Parse a trigger string, returning `None` for unrecognised values.
+    pub fn from_str_opt(s: &str) -> Option<Self> {
+        match s {
+            "mention" => Some(Self::Mention),
+            "reply" => Some(Self::Reply),
+            "keyword" => Some(Self::Keyword),
+            "prefix" => Some(Self::Prefix),
+            _ => None,
+        }
+    }
+}
+
+/// Which group messages reach the agent.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GroupActivation {
+    /// Triggers that activate the agent; empty means every message does.
+    pub triggers: Vec<ActivationTrigger>,
+    /// Words that activate the agent with the `keyword` trigger.
+    pub keywords: Vec<String>,
+    /// Text prefixes that activate the agent with the `prefix` trigger.
+    pub prefixes: Vec<String>,
+    /// Keep non-triggering messages as passive context in the session.
+    pub passive_context: bool,
+}
+
+impl GroupActivation {
+    /// Parse a `GroupActivation` from a JSON value.
+    ///
+    /// Accepts a bare trigger string (`"mention"`) or an object with `mode`
+    /// (a trigger or list of triggers), `keywords`, `prefixes` and
+    /// `passiveContext`. Unknown triggers are ignored; if none remain the
+    /// mode is `always`.
+    pub fn from_value(value: &Value) -> Self {
+        let (mode, section) = match value {
+            Value::String(_) => (Some(value), None),
+            Value::Object(_) => (value.get("mode"), Some(value)),
+            _ => (None, None),
+        };
+        let mode_names: Vec<&str> = match mode {
+            Some(Value::String(s)) => vec![s.as_str()],
+            Some(Value::Array(items)) => items.iter().filter_map(|v| v.as_str()).collect(),
+            _ => Vec::new(),
+        };
+        let mut triggers: Vec<ActivationTrigger> = Vec::new();
+        if !mode_names.contains(&"always") {
+            for trigger in mode_names
+                .into_iter()
+                .filter_map(ActivationTrigger::from_str_opt)
+            {
+                if !triggers.contains(&trigger) {
+                    triggers.push(trigger);
+                }
+            }
+        }
+
+        let strings = |key: &str| -> Vec<String> {
+            section
+                .and_then(|s| s.get(key))
+                .and_then(|v| v.as_array())
+                .map(|items| {
+                    items
+                        .iter()
+                        .filter_map(|v| v.as_str())
+                        .map(str::trim)
+                        .filter(|s| !s.is_empty())
+                        .map(String::from)
+                        .collect()
+                })
+                .unwrap_or_default()
+        };
+        let mut prefixes = strings("prefixes");
+        if prefixes.is_empty() && triggers.contains(&ActivationTrigger::Prefix) {
+            prefixes.push(DEFAULT_ACTIVATION_PREFIX.to_string());
+        }
+
+        Self {
+            triggers,
+            keywords: strings("keywords"),
+            prefixes,
+            passive_context: section
+                .and_then(|s| s.get("passiveContext"))
+                .and_then(|v| v.as_bool())
+                .unwrap_or(false),
+        }
+    }
+
+    /// Whether every group message activates the agent.
+    pub fn is_always(&self) -> bool {
+        self.triggers.is_empty()
+    }
+
+    /// Decide whether a group message activates the agent.
+    ///
+    /// `mentioned` and `replied_to_bot` come from the channel's inbound
+    /// parser; the keyword and prefix triggers inspect `text`.
+    pub fn is_triggered(&self, text: &str, mentioned: bool, replied_to_bot: bool) -> bool {
+        if self.is_always() {
+            return true;
+        }
+        self.triggers.iter().any(|trigger| match trigger {
+            ActivationTrigger::Mention => mentioned,
+            ActivationTrigger::Reply => replied_to_bot,
+            ActivationTrigger::Keyword => self
+                .keywords
+                .iter()
+                .any(|keyword| contains_word(text, keyword)),
+            ActivationTrigger::Prefix => {
+                let text = text.trim_start();
+                self.prefixes
+                    .iter()
+                    .any(|prefix| !prefix.is_empty() && text.starts_with(prefix.as_str()))
+            }
+        })
+    }
+}
+
+/// Case-insensitive whole-word search for `word` in `text`.
+fn contains_word(text: &str, word: &str) -> bool {
+    let text = text.to_lowercase();
+    let word = word.to_lowercase();
+    if word.is_empty() {
+        return false;
+    }
+    let is_boundary = |c: Option<char>| c.is_none_or(|c| !c.is_alphanumeric());
+    text.match_indices(&word).any(|(start, _)| {
+        is_boundary(text[..start].chars().next_back())
+            && is_boundary(text[start + word.len()..].chars().next())
+    })
+}
+
 // ---------------------------------------------------------------------------
 // Session scoping configuration (per-channel)
 // ---------------------------------------------------------------------------
@@ -125,6 +302,10 @@ impl std::fmt::Display for SessionResetPolicy {
 pub struct ChannelSessionConfig {
     pub scope: SessionScope,
     pub reset: SessionResetPolicy,
+    /// Activation policy for group messages on this channel.
+    pub activation: GroupActivation,
+    /// Per-group activation overrides, keyed by group ID.
+    pub groups: BTreeMap<String, GroupActivation>,
 }
 
 impl ChannelSessionConfig {
@@ -151,7 +332,36 @@ impl ChannelSessionConfig {
             None => SessionResetPolicy::default(),
         };
 
-        Self { scope, reset }
+        let activation = value
+            .get("activation")
+            .map(GroupActivation::from_value)
+            .unwrap_or_default();
+
+        let groups = value
+            .get("groups")
+            .and_then(|v| v.as_object())
+            .map(|groups| {
+                groups
+                    .iter()
+                    .filter_map(|(id, group)| {
+                        let activation = group.get("activation")?;
+                        Some((id.clone(), GroupActivation::from_value(activation)))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Self {
+            scope,
+            reset,
+            activation,
+            groups,
+        }
+    }
+
+    /// Activation policy for a group: its override, else the channel's.
+    pub fn activation_for(&self, group_id: &str) -> &GroupActivation {
+        self.groups.get(group_id).unwrap_or(&self.activation)
     }
 
     /// Extract `ChannelSessionConfig` from the full config for a given channel.
@@ -178,7 +388,7 @@ impl ChannelSessionConfig {
 
         Self {
             scope,
-            reset: SessionResetPolicy::default(),
+            ..Default::default()
         }
     }
 }
@@ -573,6 +783,87 @@ mod tests {
         assert_eq!(config.reset, SessionResetPolicy::Manual);
     }
 
+    #[test]
+    fn test_config_default_activation_is_always() {
+        let config = ChannelSessionConfig::from_value(&json!({}));
+        assert!(config.activation.is_always());
+        assert!(config
+            .activation_for("any-group")
+            .is_triggered("hello", false, false));
+    }
+
+    #[test]
+    fn test_activation_parsing() {
+        let config = ChannelSessionConfig::from_value(&json!({
+            "activation": {
+                "mode": ["mention", "keyword", "prefix", "bogus", "mention"],
+                "keywords": ["carapace", " "],
+                "passiveContext": true
+            },
+            "groups": {
+                "g1": { "activation": "always" },
+                "g2": { "activation": "reply" },
+                "g3": { "scope": "global" }
+            }
+        }));
+        let activation = &config.activation;
+        assert_eq!(
+            activation.triggers,
+            vec![
+                ActivationTrigger::Mention,
+                ActivationTrigger::Keyword,
+                ActivationTrigger::Prefix
+            ]
+        );
+        assert_eq!(activation.keywords, vec!["carapace".to_string()]);
+        assert_eq!(activation.prefixes, vec!["/".to_string()]);
+        assert!(activation.passive_context);
+
+        assert!(config.activation_for("g1").is_always());
+        assert_eq!(
+            config.activation_for("g2").triggers,
+            vec![ActivationTrigger::Reply]
+        );
+        // Groups without an activation override use the channel's
+        assert_eq!(config.activation_for("g3"), activation);
+        assert_eq!(config.activation_for("unknown"), activation);
+    }
+
+    #[test]
+    fn test_activation_triggers() {
+        let activation = GroupActivation::from_value(&json!({
+            "mode": ["mention", "reply", "keyword", "prefix"],
+            "keywords": ["Carapace"],
+            "prefixes": ["!ask"]
+        }));
+        assert!(activation.is_triggered("hi", true, false));
+        assert!(activation.is_triggered("hi", false, true));
+        assert!(activation.is_triggered("hey CARAPACE, help", false, false));
+        assert!(!activation.is_triggered("carapaces everywhere", false, false));
+        assert!(activation.is_triggered("  !ask the weather", false, false));
+        assert!(!activation.is_triggered("/ask the weather", false, false));
+        assert!(!activation.is_triggered("just chatting", false, false));
+
+        // Blank prefixes are dropped rather than matching every message
+        let blank = GroupActivation::from_value(&json!({
+            "mode": "prefix",
+            "prefixes": ["", "  "]
+        }));
+        assert_eq!(blank.prefixes, vec!["/".to_string()]);
+        assert!(!blank.is_triggered("just chatting", false, false));
+        let unparsed = GroupActivation {
+            triggers: vec![ActivationTrigger::Prefix],
+            prefixes: vec![String::new()],
+            ..Default::default()
+        };
+        assert!(!unparsed.is_triggered("just chatting", false, false));
+
+        let mention_only = GroupActivation::from_value(&json!("mention"));
+        assert!(!mention_only.is_triggered("carapace", false, true));
+        assert!(mention_only.is_triggered("", true, false));
+        assert!(!mention_only.passive_context);
+    }
+
     #[test]
     fn test_config_from_value_scope_only() {
         let config = ChannelSessionConfig::from_value(&json!({
@@ -541,6 +541,11 @@ impl SessionStore {
         self
     }
 
+    /// Message count at which a session should be compacted
+    pub fn compact_threshold(&self) -> usize {
+        self.compact_threshold
+    }
+
     /// Set the HMAC key for session integrity verification.
     pub fn with_hmac_key(mut self, key: [u8; 32]) -> Self {
         self.hmac_key = Some(key);
@@ -1236,6 +1241,49 @@ impl SessionStore {
     ) -> Result<CompactionMetadata, SessionStoreError>
     where
         F: FnOnce(&[ChatMessage]) -> String,
+    {
+        self.compact_history(
+            session_id,
+            |messages| (messages.len() > keep_recent).then(|| messages.len() - keep_recent),
+            summary_fn,
+        )
+    }
+
+    /// Compact a session up to and including `last_message_id`
+    ///
+    /// Used when the summary is produced asynchronously: the caller
+    /// summarizes a span of history, then replaces exactly that span even if
+    /// messages were appended in the meantime. If `last_message_id` is no
+    /// longer in the history (e.g. a concurrent compaction removed it),
+    /// nothing is compacted.
+    pub fn compact_session_through(
+        &self,
+        session_id: &str,
+        last_message_id: &str,
+        summary: String,
+    ) -> Result<CompactionMetadata, SessionStoreError> {
+        self.compact_history(
+            session_id,
+            |messages| {
+                messages
+                    .iter()
+                    .position(|m| m.id == last_message_id)
+                    .map(|i| i + 1)
+            },
+            |_| summary,
+        )
+    }
+
+    /// Replace the first `split_fn(history)` messages with a summary.
+    fn compact_history<S, F>(
+        &self,
+        session_id: &str,
+        split_fn: S,
+        summary_fn: F,
+    ) -> Result<CompactionMetadata, SessionStoreError>
+    where
+        S: FnOnce(&[ChatMessage]) -> Option<usize>,
+        F: FnOnce(&[ChatMessage]) -> String,
     {
         let mut session = self.get_session(session_id)?;
 
@@ -1261,15 +1309,14 @@ impl SessionStore {
         // Read all messages
         let messages = self.get_history(session_id, None, None)?;
 
-        if messages.len() <= keep_recent {
+        let Some(compact_count) = split_fn(&messages).filter(|&n| n > 0) else {
             // Not enough messages to compact
             session.status = SessionStatus::Active;
             self.write_session_meta(&session)?;
             return Ok(session.metadata.compaction);
-        }
+        };
 
         // Split into messages to compact and messages to keep
-        let compact_count = messages.len() - keep_recent;
         let to_compact: Vec<_> = messages.iter().take(compact_count).cloned().collect();
         let to_keep: Vec<_> = messages.into_iter().skip(compact_count).collect();
 
@@ -2405,6 +2452,46 @@ mod tests {
         assert!(result.is_some());
     }
 
+    #[test]
+    fn test_store_compact_session_through() {
+        let (store, _temp) = create_test_store();
+
+        let session = store
+            .create_session("agent-1", SessionMetadata::default())
+            .unwrap();
+        for i in 0..5 {
+            store
+                .append_message(ChatMessage::user(&session.id, format!("Message {}", i)))
+                .unwrap();
+        }
+        let history = store.get_history(&session.id, None, None).unwrap();
+        let last_summarized = history[2].id.clone();
+
+        // Messages appended after the span was summarized are kept
+        store
+            .append_message(ChatMessage::user(&session.id, "late"))
+            .unwrap();
+
+        let metadata = store
+            .compact_session_through(&session.id, &last_summarized, "Summary".into())
+            .unwrap();
+        assert_eq!(metadata.messages_compacted, 3);
+        assert_eq!(metadata.last_summary.as_deref(), Some("Summary"));
+
+        let history = store.get_history(&session.id, None, None).unwrap();
+        assert_eq!(history.len(), 4);
+        assert_eq!(history[0].role, MessageRole::System);
+        assert_eq!(history[1].content, "Message 3");
+        assert_eq!(history[3].content, "late");
+
+        // The span is gone, so a stale request compacts nothing
+        let metadata = store
+            .compact_session_through(&session.id, &last_summarized, "Stale".into())
+            .unwrap();
+        assert_eq!(metadata.compaction_count, 1);
+        assert_eq!(store.get_history(&session.id, None, None).unwrap().len(), 4);
+    }
+
     #[test]
     fn test_store_persistence() {
         let temp_dir = TempDir::new().unwrap();
@@ -32,6 +32,10 @@ static USAGE_TRACKER: LazyLock<RwLock<UsageTracker>> = LazyLock::new(|| {
 static PRICING_CONFIG: LazyLock<RwLock<PricingConfig>> =
     LazyLock::new(|| RwLock::new(PricingConfig::default()));
 
+/// Pricing advertised by provider plugins, keyed by lowercase model ID.
+static PLUGIN_MODEL_PRICING: LazyLock<RwLock<HashMap<String, ModelPricing>>> =
+    LazyLock::new(|| RwLock::new(HashMap::new()));
+
 const DAY_MS: u64 = 86_400_000;
 const USAGE_DAILY_RETENTION_DAYS: u64 = 365;
 const USAGE_MONTHLY_RETENTION_MONTHS: u64 = 24;
@@ -110,14 +114,34 @@ impl ModelPricing {
 pub fn get_model_pricing(model: &str) -> Option<ModelPricing> {
     let model_lower = model.to_lowercase();
     let config = PRICING_CONFIG.read();
-    lookup_pricing(model, &model_lower, &config)
+    let plugin_models = PLUGIN_MODEL_PRICING.read();
+    lookup_pricing(model, &model_lower, &config, &plugin_models)
+}
+
+/// Register pricing for a model served by a provider plugin.
+///
+/// Config overrides still take precedence; plugin pricing takes precedence
+/// over the built-in table.
+pub fn register_model_pricing(model: &str, pricing: ModelPricing) {
+    PLUGIN_MODEL_PRICING
+        .write()
+        .insert(model.to_lowercase(), pricing);
 }
 
-fn lookup_pricing(model: &str, model_lower: &str, config: &PricingConfig) -> Option<ModelPricing> {
+fn lookup_pricing(
+    model: &str,
+    model_lower: &str,
+    config: &PricingConfig,
+    plugin_models: &HashMap<String, ModelPricing>,
+) -> Option<ModelPricing> {
     if let Some(pricing) = pricing_override(model, model_lower, &config.overrides) {
         return Some(pricing);
     }
 
+    if let Some(pricing) = plugin_models.get(model_lower) {
+        return Some(pricing.clone());
+    }
+
     if let Some(pricing) = builtin_pricing(model_lower) {
         return Some(pricing);
     }
@@ -1163,6 +1187,11 @@ pub struct CostBreakdown {
 
 // ============== Public API for global usage tracker ==============
 
+/// Handle to the global usage tracker
+pub fn global_tracker() -> &'static RwLock<UsageTracker> {
+    &USAGE_TRACKER
+}
+
 /// Record API usage (global tracker)
 pub fn record_usage(
     provider: &str,
@@ -1643,7 +1672,7 @@ mod tests {
         let pricing = parse_pricing_config(&config);
         let model = "gpt-4o";
         let model_lower = model.to_lowercase();
-        let matched = lookup_pricing(model, &model_lower, &pricing).unwrap();
+        let matched = lookup_pricing(model, &model_lower, &pricing, &HashMap::new()).unwrap();
         assert!((matched.input_cost_per_mtok - 5.0).abs() < 0.001);
         assert!((matched.output_cost_per_mtok - 15.0).abs() < 0.001);
     }
@@ -1661,11 +1690,43 @@ mod tests {
         let pricing = parse_pricing_config(&config);
         let model = "custom-model";
         let model_lower = model.to_lowercase();
-        let matched = lookup_pricing(model, &model_lower, &pricing).unwrap();
+        let matched = lookup_pricing(model, &model_lower, &pricing, &HashMap::new()).unwrap();
         assert!((matched.input_cost_per_mtok - 4.0).abs() < 0.001);
         assert!((matched.output_cost_per_mtok - 8.0).abs() < 0.001);
     }
 
+    #[test]
+    fn test_pricing_plugin_model_between_overrides_and_builtin() {
+        let mut plugin_models = HashMap::new();
+        plugin_models.insert(
+            "gw:gpt-4o".to_string(),
+            ModelPricing {
+                input_cost_per_mtok: 0.5,
+                output_cost_per_mtok: 1.5,
+            },
+        );
+
+        let model = "GW:gpt-4o";
+        let model_lower = model.to_lowercase();
+        let matched = lookup_pricing(
+            model,
+            &model_lower,
+            &PricingConfig::default(),
+            &plugin_models,
+        )
+        .unwrap();
+        assert!((matched.input_cost_per_mtok - 0.5).abs() < 0.001);
+
+        let config = serde_json::json!({
+            "usage": { "pricing": { "overrides": [
+                { "match": "gw:gpt-4o", "matchType": "exact", "inputCostPerMTok": 9.0, "outputCostPerMTok": 9.0 }
+            ] } }
+        });
+        let pricing = parse_pricing_config(&config);
+        let matched = lookup_pricing(model, &model_lower, &pricing, &plugin_models).unwrap();
+        assert!((matched.input_cost_per_mtok - 9.0).abs() < 0.001);
+    }
+
     #[test]
     fn test_date_parsing() {
         let (year, month, day) = parse_date("2025-01-27").unwrap();
@@ -221,14 +221,14 @@ fn test_hook_registry_preset() {
 // Tools Registry Tests
 // ============================================================================
 
-#[test]
-fn test_tools_registry_builtin_time() {
+#[tokio::test]
+async fn test_tools_registry_builtin_time() {
     let registry = ToolsRegistry::new();
 
     assert!(registry.has_tool("time"));
 
     let ctx = ToolInvokeContext::default();
-    let result = registry.invoke("time", json!({}), &ctx);
+    let result = registry.invoke("time", json!({}), &ctx).await;
 
     match result {
         ToolInvokeResult::Success { ok, result } => {
@@ -241,12 +241,12 @@ fn test_tools_registry_builtin_time() {
     }
 }
 
-#[test]
-fn test_tools_registry_not_found() {
+#[tokio::test]
+async fn test_tools_registry_not_found() {
     let registry = ToolsRegistry::new();
     let ctx = ToolInvokeContext::default();
 
-    let result = registry.invoke("nonexistent_tool", json!({}), &ctx);
+    let result = registry.invoke("nonexistent_tool", json!({}), &ctx).await;
 
     match result {
         ToolInvokeResult::Error { ok, error } => {
@@ -95,6 +95,7 @@ impl ChannelPluginInstance for MockChannel {
                 conversation_id: None,
                 to_jid: None,
                 poll_id: None,
+                retry_after_ms: None,
             })
         } else {
             Ok(DeliveryResult {
@@ -105,6 +106,7 @@ impl ChannelPluginInstance for MockChannel {
                 conversation_id: None,
                 to_jid: None,
                 poll_id: None,
+                retry_after_ms: None,
             })
         }
     }
@@ -119,6 +121,7 @@ impl ChannelPluginInstance for MockChannel {
             conversation_id: None,
             to_jid: None,
             poll_id: None,
+            retry_after_ms: None,
         })
     }
 }